@@ -9,12 +9,14 @@ use crate::rows::{
   RowDetail, RowId, RowMeta, RowMetaKey, RowMetaUpdate, RowUpdate,
 };
 use crate::views::RowOrder;
-use crate::workspace_database::DatabaseCollabService;
+use crate::workspace_database::{DatabaseCollabService, EncodeCollabByOid};
 
 use collab::lock::RwLock;
 use collab::preclude::Collab;
 use futures::future::join_all;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
 use tokio::sync::broadcast::Sender;
 
@@ -25,6 +27,75 @@ use uuid::Uuid;
 pub enum BlockEvent {
   /// The Row is fetched from the remote.
   DidFetchRow(Vec<RowDetail>),
+  /// The rows can't be fetched after all the attempts of the [FetchRowRetryPolicy].
+  DidFetchRowFailed { row_ids: Vec<RowId>, error: String },
+  /// The progress of loading the rows that aren't in the cache. It's sent by
+  /// [Block::batch_load_rows] after each chunk and by [Block::init_database_rows] after each
+  /// batch. `loaded` includes the rows that fail to load.
+  DidLoadRowsProgress { loaded: usize, total: usize },
+}
+
+/// The number of rows loaded at a time by [Block::batch_load_rows] if the caller doesn't
+/// specify one.
+pub const DEFAULT_LOAD_ROWS_CHUNK_SIZE: usize = 20;
+
+/// Decides how many times a row is fetched before giving up, and how long to wait between the
+/// attempts. The backoff is doubled after each failed attempt, up to `max_backoff`.
+#[derive(Clone, Debug)]
+pub struct FetchRowRetryPolicy {
+  pub max_attempts: usize,
+  pub initial_backoff: Duration,
+  pub max_backoff: Duration,
+}
+
+impl Default for FetchRowRetryPolicy {
+  fn default() -> Self {
+    Self {
+      max_attempts: 3,
+      initial_backoff: Duration::from_millis(200),
+      max_backoff: Duration::from_secs(5),
+    }
+  }
+}
+
+impl FetchRowRetryPolicy {
+  /// Returns the time to wait after the given failed attempt. The first attempt is 1.
+  fn backoff(&self, attempt: usize) -> Duration {
+    let factor = 2_u32.saturating_pow(attempt.saturating_sub(1) as u32);
+    self
+      .initial_backoff
+      .saturating_mul(factor)
+      .min(self.max_backoff)
+  }
+}
+
+/// A snapshot of the counters of a [Block]. It tells where the rows come from: the memory
+/// cache, the local disk or the remote.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockMetrics {
+  pub cache_hits: u64,
+  pub disk_loads: u64,
+  pub remote_fetches: u64,
+  pub failed_fetches: u64,
+  /// The number of rows in the memory cache when the snapshot is taken
+  pub cache_size: usize,
+}
+
+#[derive(Debug, Default)]
+struct BlockMetricsCounter {
+  cache_hits: AtomicU64,
+  disk_loads: AtomicU64,
+  remote_fetches: AtomicU64,
+  failed_fetches: AtomicU64,
+}
+
+impl BlockMetricsCounter {
+  fn reset(&self) {
+    self.cache_hits.store(0, Ordering::Relaxed);
+    self.disk_loads.store(0, Ordering::Relaxed);
+    self.remote_fetches.store(0, Ordering::Relaxed);
+    self.failed_fetches.store(0, Ordering::Relaxed);
+  }
 }
 
 /// Each [Block] contains a list of [DatabaseRow]s. Each [DatabaseRow] represents a row in the database.
@@ -37,6 +108,7 @@ pub struct Block {
   pub row_mem_cache: Arc<DashMap<RowId, Arc<RwLock<DatabaseRow>>>>,
   pub notifier: Arc<Sender<BlockEvent>>,
   row_change_tx: Option<RowChangeSender>,
+  metrics: Arc<BlockMetricsCounter>,
 }
 
 impl Block {
@@ -52,6 +124,7 @@ impl Block {
       row_mem_cache: Arc::new(Default::default()),
       notifier: Arc::new(notifier),
       row_change_tx,
+      metrics: Arc::new(BlockMetricsCounter::default()),
     }
   }
 
@@ -59,32 +132,75 @@ impl Block {
     self.notifier.subscribe()
   }
 
-  pub async fn batch_load_rows(&self, row_ids: Vec<RowId>) -> Result<(), DatabaseError> {
+  /// Returns a snapshot of the counters of the block
+  pub fn metrics(&self) -> BlockMetrics {
+    BlockMetrics {
+      cache_hits: self.metrics.cache_hits.load(Ordering::Relaxed),
+      disk_loads: self.metrics.disk_loads.load(Ordering::Relaxed),
+      remote_fetches: self.metrics.remote_fetches.load(Ordering::Relaxed),
+      failed_fetches: self.metrics.failed_fetches.load(Ordering::Relaxed),
+      cache_size: self.row_mem_cache.len(),
+    }
+  }
+
+  pub fn reset_metrics(&self) {
+    self.metrics.reset();
+  }
+
+  /// Records that the row is loaded from the local disk, or from the remote if the collab of
+  /// the row doesn't exist on disk.
+  fn record_row_load(&self, is_on_disk: bool) {
+    if is_on_disk {
+      self.metrics.disk_loads.fetch_add(1, Ordering::Relaxed);
+    } else {
+      self.metrics.remote_fetches.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+
+  fn is_row_on_disk(&self, row_id: &RowId) -> bool {
+    self
+      .collab_service
+      .persistence()
+      .map(|persistence| persistence.is_collab_exist(row_id))
+      .unwrap_or(false)
+  }
+
+  /// Loads the rows into the cache, `chunk_size` rows at a time. The rows that are already in
+  /// the cache are skipped. A [BlockEvent::DidLoadRowsProgress] is sent after each chunk, and a
+  /// [BlockEvent::DidFetchRow] with all the loaded rows is sent at the end.
+  pub async fn batch_load_rows(
+    &self,
+    row_ids: Vec<RowId>,
+    chunk_size: usize,
+  ) -> Result<(), DatabaseError> {
+    let row_ids = row_ids
+      .into_iter()
+      .filter(|row_id| !self.row_mem_cache.contains_key(row_id))
+      .collect::<Vec<_>>();
     let cloned_notifier = self.notifier.clone();
+    let total = row_ids.len();
+    let mut loaded = 0;
     let mut row_on_disk_details = vec![];
-    for row_id in row_ids.into_iter() {
-      let collab = self
-        .collab_service
-        .build_collab(&row_id, CollabType::DatabaseRow, None)
-        .await?;
-      match DatabaseRow::open(
-        row_id.clone(),
-        collab,
-        self.row_change_tx.clone(),
-        self.collab_service.clone(),
-      ) {
-        Ok(row_collab) => {
-          if let Some(row_detail) = RowDetail::from_collab(&row_collab) {
-            self
-              .row_mem_cache
-              .insert(row_id.clone(), Arc::new(RwLock::from(row_collab)));
-            row_on_disk_details.push(row_detail);
-          }
-        },
-        Err(err) => {
-          error!("fail to load row: {:?}", err);
-        },
+    for chunk in row_ids.chunks(chunk_size.max(1)) {
+      let results = join_all(chunk.iter().map(|row_id| self.fetch_database_row(row_id))).await;
+      for (row_id, result) in chunk.iter().zip(results) {
+        match result {
+          Ok(row_collab) => {
+            if let Some(row_detail) = RowDetail::from_collab(&row_collab) {
+              self
+                .row_mem_cache
+                .insert(row_id.clone(), Arc::new(RwLock::from(row_collab)));
+              row_on_disk_details.push(row_detail);
+            }
+          },
+          Err(err) => {
+            error!("fail to load row: {:?}", err);
+          },
+        }
       }
+
+      loaded += chunk.len();
+      let _ = cloned_notifier.send(BlockEvent::DidLoadRowsProgress { loaded, total });
     }
 
     if !row_on_disk_details.is_empty() {
@@ -106,6 +222,70 @@ impl Block {
     row_orders
   }
 
+  /// Creates the rows without keeping them in the row cache, which is used to import a large
+  /// number of rows. The row collabs are saved with the persistence of the collab service and
+  /// loaded when they are accessed. Without the persistence, the rows are kept in the cache
+  /// because they can't be loaded again.
+  pub async fn import_rows<T>(&self, rows: Vec<T>) -> Result<Vec<RowOrder>, DatabaseError>
+  where
+    T: Into<Row> + Send,
+  {
+    let persistence = match self.collab_service.persistence() {
+      Some(persistence) => persistence,
+      None => return Ok(self.create_rows(rows).await),
+    };
+
+    let mut row_orders = Vec::with_capacity(rows.len());
+    let mut encoded_collabs = Vec::with_capacity(rows.len());
+    for row in rows {
+      let row = row.into();
+      let row_id = row.id.clone();
+      if persistence.is_collab_exist(&row_id) {
+        warn!("The row already exists: {:?}", row_id);
+        continue;
+      }
+      row_orders.push(RowOrder {
+        id: row.id.clone(),
+        height: row.height,
+      });
+      let encoded_collab = default_database_row_data(&row_id, row);
+      encoded_collabs.push((row_id.to_string(), encoded_collab));
+    }
+    persistence.flush_collabs(encoded_collabs)?;
+    Ok(row_orders)
+  }
+
+  /// Writes the rows with the persistence of the collab service and removes them from the row
+  /// cache. They are loaded again when they are accessed. Without the persistence, the rows are
+  /// kept in the cache because they can't be loaded again.
+  pub async fn evict_rows(&self, row_ids: &[RowId]) -> Result<(), DatabaseError> {
+    let persistence = match self.collab_service.persistence() {
+      Some(persistence) => persistence,
+      None => return Ok(()),
+    };
+
+    for row_id in row_ids {
+      if let Some((_, database_row)) = self.row_mem_cache.remove(row_id) {
+        let encoded_collab = database_row.write().await.encoded_collab()?;
+        persistence.save_collab(row_id, encoded_collab)?;
+      }
+    }
+    Ok(())
+  }
+
+  /// Returns true if the row is in the cache or its collab exists on disk. Without persistence,
+  /// the existence can't be checked and the row is considered as existing.
+  pub fn is_row_exist(&self, row_id: &RowId) -> bool {
+    if self.row_mem_cache.contains_key(row_id) {
+      return true;
+    }
+
+    match self.collab_service.persistence() {
+      None => true,
+      Some(persistence) => persistence.is_collab_exist(row_id),
+    }
+  }
+
   pub async fn create_new_row<T: Into<Row>>(&self, row: T) -> Result<RowOrder, DatabaseError> {
     let row = row.into();
     let row_id = row.id.clone();
@@ -136,6 +316,7 @@ impl Block {
       row_id.clone(),
       collab,
       self.row_change_tx.clone(),
+      self.collab_service.uid(),
       self.collab_service.clone(),
     )?;
 
@@ -205,29 +386,23 @@ impl Block {
     row
   }
 
-  pub async fn update_row<F>(&mut self, row_id: RowId, f: F)
+  /// Updates the row. The row is initialized if it's not in the cache. Returns
+  /// [DatabaseError::DatabaseRowNotFound] if the row can't be initialized.
+  pub async fn update_row<F>(&mut self, row_id: RowId, f: F) -> Result<(), DatabaseError>
   where
     F: FnOnce(RowUpdate),
   {
-    match self.get_database_row(&row_id).await {
-      None => {
-        error!(
-          "fail to update row. the database row is not created: {:?}",
-          row_id
-        )
-      },
-      Some(database_row) => {
-        database_row.write().await.update::<F>(f);
-
-        // if row_id is updated, we need to update the the database key value store
-        let new_row_id = &database_row.read().await.row_id;
-        if *new_row_id != row_id {
-          if let Some((_, row_data)) = self.row_mem_cache.remove(&row_id) {
-            self.row_mem_cache.insert(new_row_id.clone(), row_data);
-          };
-        }
-      },
+    let database_row = self.get_or_init_database_row(&row_id).await?;
+    database_row.write().await.update::<F>(f);
+
+    // if row_id is updated, we need to update the the database key value store
+    let new_row_id = &database_row.read().await.row_id;
+    if *new_row_id != row_id {
+      if let Some((_, row_data)) = self.row_mem_cache.remove(&row_id) {
+        self.row_mem_cache.insert(new_row_id.clone(), row_data);
+      };
     }
+    Ok(())
   }
 
   pub async fn update_row_meta<F>(&mut self, row_id: &RowId, f: F)
@@ -266,10 +441,15 @@ impl Block {
           reason: "the row is not exist in local disk".to_string(),
         }
       }),
-      Some(row) => Ok(row),
+      Some(row) => {
+        self.metrics.cache_hits.fetch_add(1, Ordering::Relaxed);
+        Ok(row)
+      },
     }
   }
 
+  /// Returns the rows with the given ids. The rows that aren't in the cache are fetched at once
+  /// and a [BlockEvent::DidLoadRowsProgress] is sent after they are loaded.
   pub async fn init_database_rows(
     &self,
     row_ids: Vec<RowId>,
@@ -280,18 +460,21 @@ impl Block {
       .filter(|id| !self.row_mem_cache.contains_key(id))
       .map(|id| id.to_string())
       .collect();
+    self.metrics.cache_hits.fetch_add(
+      (row_ids.len() - uncached_row_ids.len()) as u64,
+      Ordering::Relaxed,
+    );
 
     // Fetch collabs for the uncached row IDs
-    let encoded_collab_by_id = self
-      .collab_service
-      .get_collabs(uncached_row_ids, CollabType::DatabaseRow)
-      .await?;
+    let num_of_uncached_rows = uncached_row_ids.len();
+    let encoded_collab_by_id = self.fetch_row_collabs(uncached_row_ids).await?;
 
     // Prepare concurrent tasks to initialize database rows
     let futures = encoded_collab_by_id
       .into_iter()
       .map(|(row_id, encoded_collab)| async {
         let row_id = RowId::from(row_id);
+        self.record_row_load(self.is_row_on_disk(&row_id));
         let collab = self
           .collab_service
           .build_collab(
@@ -311,6 +494,12 @@ impl Block {
       .await
       .into_iter()
       .collect::<Result<HashMap<_, _>, _>>()?;
+    if num_of_uncached_rows > 0 {
+      let _ = self.notifier.send(BlockEvent::DidLoadRowsProgress {
+        loaded: num_of_uncached_rows,
+        total: num_of_uncached_rows,
+      });
+    }
 
     // Initialize final database rows by combining cached and newly fetched rows
     let mut database_rows = Vec::with_capacity(row_ids.len());
@@ -325,16 +514,121 @@ impl Block {
     Ok(database_rows)
   }
 
+  /// Fetches the collabs of the rows at once. Like [Block::fetch_database_row], a failed attempt
+  /// is retried according to the [FetchRowRetryPolicy] of the collab service if the error is
+  /// retryable, and a [BlockEvent::DidFetchRowFailed] with all the row ids is sent if the rows
+  /// can't be fetched.
+  async fn fetch_row_collabs(
+    &self,
+    row_ids: Vec<String>,
+  ) -> Result<EncodeCollabByOid, DatabaseError> {
+    if row_ids.is_empty() {
+      return Ok(EncodeCollabByOid::new());
+    }
+
+    let policy = self.collab_service.fetch_row_retry_policy();
+    let mut attempt = 1;
+    loop {
+      match self
+        .collab_service
+        .get_collabs(row_ids.clone(), CollabType::DatabaseRow)
+        .await
+      {
+        Ok(encoded_collab_by_id) => return Ok(encoded_collab_by_id),
+        Err(err) if err.is_retryable() && attempt < policy.max_attempts => {
+          let backoff = policy.backoff(attempt);
+          warn!(
+            "fail to fetch {} rows, attempt: {}, retry in {:?}: {}",
+            row_ids.len(),
+            attempt,
+            backoff,
+            err
+          );
+          tokio::time::sleep(backoff).await;
+          attempt += 1;
+        },
+        Err(err) => {
+          error!(
+            "Can't fetch {} rows after {} attempts",
+            row_ids.len(),
+            attempt
+          );
+          self
+            .metrics
+            .failed_fetches
+            .fetch_add(row_ids.len() as u64, Ordering::Relaxed);
+          let _ = self.notifier.send(BlockEvent::DidFetchRowFailed {
+            row_ids: row_ids.into_iter().map(RowId::from).collect(),
+            error: err.to_string(),
+          });
+          return Err(err);
+        },
+      }
+    }
+  }
+
   pub async fn init_database_row(
     &self,
     row_id: RowId,
   ) -> Result<Arc<RwLock<DatabaseRow>>, DatabaseError> {
     trace!("init row instance: {}", row_id);
-    let collab = self
-      .collab_service
-      .build_collab(&row_id, CollabType::DatabaseRow, None)
-      .await?;
-    self.init_database_row_from_collab(row_id, collab).await
+    let database_row = self.fetch_database_row(&row_id).await?;
+    Ok(self.cache_database_row(row_id, database_row))
+  }
+
+  /// Builds the collab of the row and opens the row. A failed attempt is retried according to
+  /// the [FetchRowRetryPolicy] of the collab service if the error is retryable. A
+  /// [BlockEvent::DidFetchRowFailed] is sent if the row can't be fetched, except when the row
+  /// doesn't exist.
+  async fn fetch_database_row(&self, row_id: &RowId) -> Result<DatabaseRow, DatabaseError> {
+    let policy = self.collab_service.fetch_row_retry_policy();
+    let is_on_disk = self.is_row_on_disk(row_id);
+    let mut attempt = 1;
+    loop {
+      let result = match self
+        .collab_service
+        .build_collab(row_id, CollabType::DatabaseRow, None)
+        .await
+      {
+        Ok(collab) => DatabaseRow::open(
+          row_id.clone(),
+          collab,
+          self.row_change_tx.clone(),
+          self.collab_service.uid(),
+          self.collab_service.clone(),
+        ),
+        Err(err) => Err(err),
+      };
+
+      match result {
+        Ok(database_row) => {
+          self.record_row_load(is_on_disk);
+          return Ok(database_row);
+        },
+        Err(err) if err.is_not_found() => {
+          trace!("the row: {} is not found: {}", row_id, err);
+          return Err(err);
+        },
+        Err(err) if err.is_retryable() && attempt < policy.max_attempts => {
+          let backoff = policy.backoff(attempt);
+          warn!(
+            "fail to fetch row: {}, attempt: {}, retry in {:?}: {}",
+            row_id, attempt, backoff, err
+          );
+          tokio::time::sleep(backoff).await;
+          attempt += 1;
+        },
+        Err(err) => {
+          error!("Can't fetch the row: {} after {} attempts", row_id, attempt);
+          self.metrics.failed_fetches.fetch_add(1, Ordering::Relaxed);
+          let _ = self.notifier.send(BlockEvent::DidFetchRowFailed {
+            row_ids: vec![row_id.clone()],
+            error: err.to_string(),
+          });
+          return Err(err);
+        },
+      }
+    }
   }
 
   pub async fn init_database_row_from_collab(
@@ -346,8 +640,18 @@ impl Block {
       row_id.clone(),
       collab,
       self.row_change_tx.clone(),
+      self.collab_service.uid(),
       self.collab_service.clone(),
     )?;
+    Ok(self.cache_database_row(row_id, database_row))
+  }
+
+  /// Inserts the row into the cache and notifies that the row is fetched
+  fn cache_database_row(
+    &self,
+    row_id: RowId,
+    database_row: DatabaseRow,
+  ) -> Arc<RwLock<DatabaseRow>> {
     let row_details = RowDetail::from_collab(&database_row);
     let database_row = Arc::new(RwLock::from(database_row));
     self.row_mem_cache.insert(row_id, database_row.clone());
@@ -356,6 +660,6 @@ impl Block {
         .notifier
         .send(BlockEvent::DidFetchRow(vec![row_detail]));
     }
-    Ok(database_row)
+    database_row
   }
 }
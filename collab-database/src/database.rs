@@ -1,43 +1,50 @@
 use std::borrow::{Borrow, BorrowMut};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
 use std::ops::{Deref, DerefMut};
 
-use crate::blocks::{Block, BlockEvent};
-use crate::database_state::DatabaseNotify;
+use crate::blocks::{Block, BlockEvent, BlockMetrics, DEFAULT_LOAD_ROWS_CHUNK_SIZE};
+use crate::database_state::{DatabaseChangeReceiver, DatabaseNotify};
 use crate::error::DatabaseError;
+use crate::fields::select_type_option::SelectTypeOption;
 use crate::fields::{
+  select_options_from_text_cells, text_to_number_cell, text_to_select_cell,
   type_option_cell_reader, type_option_cell_writer, Field, FieldChangeReceiver, FieldMap,
-  FieldUpdate, TypeOptionCellReader, TypeOptionCellWriter,
+  FieldUpdate, TypeOptionCellReader, TypeOptionCellWriter, TypeOptionData,
 };
-use crate::meta::MetaMap;
+use crate::meta::{DatabaseMetaData, DatabaseMetaUpdate, MetaMap};
 use crate::rows::{
-  meta_id_from_row_id, CreateRowParams, CreateRowParamsValidator, DatabaseRow, Row, RowCell,
-  RowChangeReceiver, RowDetail, RowId, RowMeta, RowMetaKey, RowMetaUpdate, RowUpdate,
+  get_field_type_from_cell, meta_id_from_row_id, Cell, CreateRowParams, CreateRowParamsValidator,
+  DatabaseRow, DuplicatedRow, Row, RowCell, RowChangeReceiver, RowComment, RowDetail, RowId,
+  RowMeta, RowMetaKey, RowMetaUpdate, RowUpdate,
 };
 use crate::util::encoded_collab;
-use crate::views::define::DATABASE_VIEW_ROW_ORDERS;
 use crate::views::{
-  CalculationMap, DatabaseLayout, DatabaseViewUpdate, DatabaseViews, FieldOrder,
-  FieldSettingsByFieldIdMap, FieldSettingsMap, FilterMap, GroupSettingMap, LayoutSetting,
-  OrderArray, OrderObjectPosition, RowOrder, RowOrderArray, SortMap, ViewChangeReceiver,
+  contains_filter, filter_children, is_valid_sort, retain_valid_filter,
+  set_group_setting_field_ids, update_nested_filter, with_default_calculation_scope,
+  BoardLayoutSetting, CalculationMap, CalendarLayoutSetting, DatabaseLayout, DatabaseViewUpdate,
+  DatabaseViews, FieldOrder, FieldSettingsByFieldIdMap, FieldSettingsMap, FilterMap,
+  GridLayoutSetting, GroupSettingMap, LayoutSetting, OrderObjectPosition, RowOrder, SortMap,
+  ViewChangeReceiver, FILTER_CHILDREN, FILTER_ID,
 };
 use crate::workspace_database::{
   DatabaseCollabService, DatabaseMeta, NoPersistenceDatabaseCollabService,
 };
 
 use crate::entity::{
-  CreateDatabaseParams, CreateViewParams, CreateViewParamsValidator, DatabaseView,
-  DatabaseViewMeta, EncodedCollabInfo, EncodedDatabase, FieldType,
+  default_type_option_data_from_type, CreateDatabaseParams, CreateViewParams,
+  CreateViewParamsValidator, DatabaseView, DatabaseViewMeta, EncodeDatabaseOptions, EncodeRowError,
+  EncodedCollabInfo, EncodedDatabase, FieldType, RepairAction, RepairReport,
 };
-use crate::template::entity::DatabaseTemplate;
+use crate::template::entity::{DatabaseTemplate, DatabaseViewTemplate, FieldTemplate, RowTemplate};
+use crate::template::relation_parse::RelationCellData;
 
 use collab::core::origin::CollabOrigin;
 use collab::entity::EncodedCollab;
 use collab::lock::RwLock;
 use collab::preclude::{
-  Any, Array, Collab, FillRef, JsonValue, Map, MapExt, MapPrelim, MapRef, ReadTxn, ToJson,
-  TransactionMut, YrsValue,
+  Any, Array, ArrayRef, Collab, FillRef, JsonValue, Map, MapExt, MapPrelim, MapRef, ReadTxn,
+  ToJson, TransactionMut, YrsValue,
 };
 use collab::util::{AnyExt, ArrayExt};
 use collab_entity::define::{DATABASE, DATABASE_ID, DATABASE_METAS};
@@ -49,7 +56,6 @@ use nanoid::nanoid;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::iter::ParallelIterator;
 
-use futures::future::join_all;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 pub use tokio_stream::wrappers::WatchStream;
@@ -71,6 +77,7 @@ impl Drop for Database {
 
 const FIELDS: &str = "fields";
 const VIEWS: &str = "views";
+const STREAM_ROWS_CHUNK_SIZE: usize = 30;
 
 pub struct DatabaseContext {
   pub collab_service: Arc<dyn DatabaseCollabService>,
@@ -169,6 +176,85 @@ impl Database {
     Self::create_with_view(params, context).await
   }
 
+  /// Converts the database into a [DatabaseTemplate] that contains the fields, the rows and the
+  /// settings of the given view. The database id, view id and row ids are left empty, so new ids
+  /// are generated each time the template is instantiated. The field ids are kept because the
+  /// cells, filters, sorts and field settings refer to them.
+  ///
+  /// Returns [DatabaseError::DatabaseRowNotFound] if one of the rows of the view can't be loaded.
+  pub async fn to_template(&self, view_id: &str) -> Result<DatabaseTemplate, DatabaseError> {
+    let view = self
+      .get_view(view_id)
+      .ok_or(DatabaseError::RecordNotFound)?;
+    let fields = self
+      .get_fields_ordered(Some(view_id), None)
+      .into_iter()
+      .map(|field| FieldTemplate {
+        field_id: field.id,
+        name: field.name,
+        field_type: FieldType::from(field.field_type),
+        is_primary: field.is_primary,
+        type_options: field
+          .type_options
+          .into_inner()
+          .into_iter()
+          .flat_map(|(type_id, type_option)| {
+            let field_type = FieldType::from(type_id.parse::<i64>().ok()?);
+            Some((field_type, type_option))
+          })
+          .collect(),
+      })
+      .collect();
+
+    // The template must contain all the rows of the view, so a row that can't be loaded fails
+    // the export instead of being left out
+    let row_orders = self.get_row_orders_for_view(view_id);
+    let mut rows_by_id = self
+      .get_rows_from_row_orders(&row_orders, DEFAULT_LOAD_ROWS_CHUNK_SIZE, None)
+      .await
+      .collect::<Vec<_>>()
+      .await
+      .into_iter()
+      .map(|result| result.map(|row| (row.id.clone(), row)))
+      .collect::<Result<HashMap<_, _>, _>>()?;
+    let rows = row_orders
+      .into_iter()
+      .map(|row_order| {
+        let row =
+          rows_by_id
+            .remove(&row_order.id)
+            .ok_or_else(|| DatabaseError::DatabaseRowNotFound {
+              row_id: row_order.id,
+              reason: "the row can't be loaded".to_string(),
+            })?;
+        Ok(RowTemplate {
+          row_id: String::new(),
+          height: row.height,
+          visibility: row.visibility,
+          cells: row.cells,
+        })
+      })
+      .collect::<Result<Vec<_>, DatabaseError>>()?;
+
+    let view_template = DatabaseViewTemplate {
+      name: view.name,
+      layout: view.layout,
+      layout_settings: view.layout_settings,
+      filters: view.filters,
+      group_settings: view.group_settings,
+      sorts: view.sorts,
+      field_settings: view.field_settings,
+    };
+
+    Ok(DatabaseTemplate {
+      database_id: String::new(),
+      view_id: String::new(),
+      fields,
+      rows,
+      views: vec![view_template],
+    })
+  }
+
   /// Create a new database with the given [CreateDatabaseParams]
   /// The method will set the inline view id to the given view_id
   /// from the [CreateDatabaseParams].
@@ -182,6 +268,7 @@ impl Database {
       rows,
       fields,
       views,
+      meta,
     } = params;
 
     let mut database = Self::create(&database_id, context, rows, fields).await?;
@@ -189,6 +276,12 @@ impl Database {
     let field_orders = database.get_all_field_orders();
     {
       let mut txn = database.collab.context.transact_mut();
+      database
+        .body
+        .metas
+        .update_database_meta(&mut txn, |update| {
+          update.set_database_meta(meta);
+        });
 
       // create the linked views
       for linked_view in views {
@@ -209,7 +302,70 @@ impl Database {
     .map_err(|e| DatabaseError::Internal(e.into()))?
   }
 
+  /// Rebuild a database whose views map was lost from the row collabs that are still present.
+  ///
+  /// A fresh inline grid view is created. Fields that are missing from the field map are
+  /// reconstructed from the field types found in the row cells and named "Field N". If none of
+  /// the fields is primary, the first field found in the rows is promoted to the primary field.
+  /// The row orders of the inline view are repopulated from the given row ids, sorted by each
+  /// row's `created_at`. Rows that can't be loaded are skipped.
+  ///
+  /// Returns [DatabaseError::RecordAlreadyExist] if the database still has views, unless `force`
+  /// is true.
+  pub async fn rebuild_from_rows(
+    database_id: &str,
+    context: DatabaseContext,
+    row_ids: Vec<RowId>,
+    force: bool,
+  ) -> Result<Self, DatabaseError> {
+    if database_id.is_empty() {
+      return Err(DatabaseError::InvalidDatabaseID("database_id is empty"));
+    }
+
+    let collab = context
+      .collab_service
+      .build_collab(database_id, CollabType::Database, None)
+      .await?;
+    if !force && has_database_views(&collab) {
+      return Err(DatabaseError::RecordAlreadyExist);
+    }
+
+    info!(
+      "[Fix]: rebuild database:{} from {} rows",
+      database_id,
+      row_ids.len()
+    );
+    let collab_service = context.collab_service.clone();
+    let (body, collab) =
+      DatabaseBody::rebuild(collab, database_id.to_string(), context, row_ids).await?;
+    let database = Self {
+      collab,
+      body,
+      collab_service,
+    };
+
+    tokio::task::spawn_blocking(move || {
+      database.write_to_disk()?;
+      Ok::<_, DatabaseError>(database)
+    })
+    .await
+    .map_err(|e| DatabaseError::Internal(e.into()))?
+  }
+
   pub async fn encode_database_collabs(&self) -> Result<EncodedDatabase, DatabaseError> {
+    self
+      .encode_database_collabs_with_options(EncodeDatabaseOptions::default())
+      .await
+  }
+
+  /// Encode the database collab and all of its row collabs.
+  /// Up to [EncodeDatabaseOptions::concurrency] rows are initialized and encoded concurrently.
+  /// The encoded rows keep the order of the rows in the database. Rows that can't be initialized
+  /// are reported in [EncodedDatabase::failed_rows].
+  pub async fn encode_database_collabs_with_options(
+    &self,
+    options: EncodeDatabaseOptions,
+  ) -> Result<EncodedDatabase, DatabaseError> {
     let database_id = self.collab.object_id().to_string();
     let encoded_database_collab = EncodedCollabInfo {
       object_id: database_id,
@@ -217,44 +373,80 @@ impl Database {
       encoded_collab: encoded_collab(&self.collab, &CollabType::Database)?,
     };
 
-    // Fetch row orders
     let row_orders = self.get_all_row_orders().await;
-    let mut encoded_row_collabs = Vec::new();
-    // Process row orders in chunks
-    for chunk in row_orders.chunks(20) {
-      // Create async tasks for each row in the chunk
-      let tasks: Vec<_> = chunk
-        .iter()
-        .map(|chunk_row| async move {
-          let database_row = self.get_or_init_database_row(&chunk_row.id).await?;
-          let read_guard = database_row.read().await;
-          let row_collab = &read_guard.collab;
-          let encoded_collab = encoded_collab(row_collab, &CollabType::DatabaseRow).ok()?;
-          Some(EncodedCollabInfo {
-            object_id: row_collab.object_id().to_string(),
-            collab_type: CollabType::DatabaseRow,
-            encoded_collab,
-          })
-        })
-        .collect();
+    let mut results = stream::iter(row_orders.into_iter().enumerate())
+      .map(|(index, row_order)| async move {
+        let result = self.encode_row_collab(&row_order.id).await;
+        (index, row_order.id, result)
+      })
+      .buffer_unordered(options.concurrency.max(1))
+      .collect::<Vec<_>>()
+      .await;
+    results.sort_by_key(|(index, _, _)| *index);
 
-      let chunk_results = join_all(tasks).await;
-      for collab_info in chunk_results.into_iter().flatten() {
-        encoded_row_collabs.push(collab_info);
+    let mut encoded_row_collabs = Vec::with_capacity(results.len());
+    let mut failed_rows = vec![];
+    for (_, row_id, result) in results {
+      match result {
+        Ok(collab_info) => encoded_row_collabs.push(collab_info),
+        Err(err) => {
+          error!("Failed to encode row:{}, error: {}", row_id, err);
+          failed_rows.push(EncodeRowError {
+            row_id,
+            reason: err.to_string(),
+          });
+        },
       }
-
-      // Yield to the runtime after processing each chunk
-      tokio::task::yield_now().await;
     }
 
     Ok(EncodedDatabase {
       encoded_database_collab,
       encoded_row_collabs,
+      failed_rows,
     })
   }
 
+  /// The rows that aren't in the row cache are encoded from the persistence if possible, so
+  /// they don't need to be opened.
+  async fn encode_row_collab(&self, row_id: &RowId) -> Result<EncodedCollabInfo, DatabaseError> {
+    if !self.body.block.row_mem_cache.contains_key(row_id) {
+      let encoded_collab = self
+        .collab_service
+        .persistence()
+        .and_then(|persistence| persistence.get_encoded_collab(row_id, CollabType::DatabaseRow));
+      if let Some(encoded_collab) = encoded_collab {
+        return Ok(EncodedCollabInfo {
+          object_id: row_id.to_string(),
+          collab_type: CollabType::DatabaseRow,
+          encoded_collab,
+        });
+      }
+    }
+
+    let database_row = self.body.block.get_or_init_database_row(row_id).await?;
+    let read_guard = database_row.read().await;
+    let row_collab = &read_guard.collab;
+    Ok(EncodedCollabInfo {
+      object_id: row_collab.object_id().to_string(),
+      collab_type: CollabType::DatabaseRow,
+      encoded_collab: encoded_collab(row_collab, &CollabType::DatabaseRow)?,
+    })
+  }
+
+  /// Write the database collab and the rows that have changed since they were last written to
+  /// disk. Use [Database::write_to_disk_all] to write all the rows in the cache.
   #[instrument(level = "info", skip_all, err)]
   pub fn write_to_disk(&self) -> Result<(), DatabaseError> {
+    self.write_collabs_to_disk(false)
+  }
+
+  /// Write the database collab and all the rows in the cache to disk.
+  #[instrument(level = "info", skip_all, err)]
+  pub fn write_to_disk_all(&self) -> Result<(), DatabaseError> {
+    self.write_collabs_to_disk(true)
+  }
+
+  fn write_collabs_to_disk(&self, include_clean_rows: bool) -> Result<(), DatabaseError> {
     if let Some(persistence) = self.collab_service.persistence() {
       let database_encoded = encoded_collab(&self.collab, &CollabType::Database)?;
       let mut encode_collabs = vec![];
@@ -269,18 +461,38 @@ impl Database {
         .collect::<Vec<_>>();
 
       info!("[Database]: encode {} database rows", rows.len());
-      let row_encodings = rows
+      let (written_rows, row_encodings): (Vec<_>, Vec<_>) = rows
         .par_iter()
         .flat_map(|row| {
-          let row_collab = &row.blocking_read().collab;
-          let encoded_collab = encoded_collab(row_collab, &CollabType::DatabaseRow).ok()?;
-          Some((row_collab.object_id().to_string(), encoded_collab))
+          let read_guard = row.blocking_read();
+          if !read_guard.take_dirty() && !include_clean_rows {
+            return None;
+          }
+
+          let row_collab = &read_guard.collab;
+          match encoded_collab(row_collab, &CollabType::DatabaseRow) {
+            Ok(encoded_collab) => Some((
+              row.clone(),
+              (row_collab.object_id().to_string(), encoded_collab),
+            )),
+            Err(err) => {
+              error!("Failed to encode row:{}, error: {}", read_guard.row_id, err);
+              read_guard.mark_dirty();
+              None
+            },
+          }
         })
-        .collect::<Vec<_>>();
+        .unzip();
 
       encode_collabs.extend(row_encodings);
       info!("Write {} database collab", encode_collabs.len());
-      persistence.flush_collabs(encode_collabs)?;
+      if let Err(err) = persistence.flush_collabs(encode_collabs) {
+        // Keep the rows dirty, so they will be written next time
+        for row in written_rows {
+          row.blocking_read().mark_dirty();
+        }
+        return Err(err);
+      }
     }
 
     Ok(())
@@ -291,6 +503,168 @@ impl Database {
     Ok(())
   }
 
+  /// Fix the inconsistencies between the fields, the rows and the orders of each view in one
+  /// transaction:
+  /// - appends the field orders of the fields that are missing from a view
+  /// - removes the field orders that point at deleted fields
+  /// - removes the row orders of the rows whose collab no longer exists
+  ///
+  /// Returns the actions taken, so the caller can log them.
+  pub fn validate_and_repair(&mut self) -> RepairReport {
+    let mut report = RepairReport::default();
+    let mut txn = self.collab.transact_mut();
+    let field_ids = self
+      .body
+      .fields
+      .get_all_field_orders(&txn)
+      .into_iter()
+      .map(|field_order| field_order.id)
+      .collect::<Vec<_>>();
+
+    for view_meta in self.body.views.get_all_views_meta(&txn) {
+      let view_id = view_meta.id;
+      let field_orders = self.body.views.get_field_orders(&txn, &view_id);
+      let stale_field_ids = field_orders
+        .iter()
+        .filter(|field_order| !field_ids.contains(&field_order.id))
+        .map(|field_order| field_order.id.clone())
+        .collect::<Vec<_>>();
+      let missing_field_ids = field_ids
+        .iter()
+        .filter(|field_id| {
+          !field_orders
+            .iter()
+            .any(|field_order| &&field_order.id == field_id)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+      let orphan_row_ids = self
+        .body
+        .views
+        .get_row_orders(&txn, &view_id)
+        .into_iter()
+        .filter(|row_order| !self.body.block.is_row_exist(&row_order.id))
+        .map(|row_order| row_order.id)
+        .collect::<Vec<_>>();
+
+      if stale_field_ids.is_empty() && missing_field_ids.is_empty() && orphan_row_ids.is_empty() {
+        continue;
+      }
+
+      let result = self
+        .body
+        .views
+        .update_database_view(&mut txn, &view_id, |mut update| {
+          for field_id in &stale_field_ids {
+            update = update.remove_field_order(field_id);
+          }
+          for field_id in &missing_field_ids {
+            update = update
+              .insert_field_order(FieldOrder::new(field_id.clone()), &OrderObjectPosition::End);
+          }
+          for row_id in &orphan_row_ids {
+            update = update.remove_row_order(row_id);
+          }
+        });
+      if result.is_err() {
+        continue;
+      }
+
+      report
+        .actions
+        .extend(
+          stale_field_ids
+            .into_iter()
+            .map(|field_id| RepairAction::RemoveFieldOrder {
+              view_id: view_id.clone(),
+              field_id,
+            }),
+        );
+      report
+        .actions
+        .extend(
+          missing_field_ids
+            .into_iter()
+            .map(|field_id| RepairAction::AddFieldOrder {
+              view_id: view_id.clone(),
+              field_id,
+            }),
+        );
+      report
+        .actions
+        .extend(
+          orphan_row_ids
+            .into_iter()
+            .map(|row_id| RepairAction::RemoveRowOrder {
+              view_id: view_id.clone(),
+              row_id,
+            }),
+        );
+    }
+
+    if !report.is_empty() {
+      info!("[Database]: repaired database: {:?}", report);
+    }
+    report
+  }
+
+  /// Writes the id of the database into the rows of the inline view that don't have one. Each
+  /// row is initialized if it's not loaded yet. Returns the number of the repaired rows.
+  pub async fn backfill_row_database_ids(&self) -> usize {
+    let database_id = self.get_database_id();
+    let mut count = 0;
+    for row_order in self.get_inline_row_orders() {
+      match self
+        .body
+        .block
+        .get_or_init_database_row(&row_order.id)
+        .await
+      {
+        Ok(row) => {
+          if row.write().await.repair_database_id(&database_id) {
+            count += 1;
+          }
+        },
+        Err(err) => error!("can't backfill the database id of the row: {}", err),
+      }
+    }
+
+    if count > 0 {
+      info!("[Database]: backfilled the database id of {} rows", count);
+    }
+    count
+  }
+
+  /// Remove the row orders whose row collab no longer exists from all the views in one
+  /// transaction. Returns the ids of the pruned rows.
+  pub fn prune_orphan_row_orders(&mut self) -> Vec<RowId> {
+    let mut txn = self.collab.transact_mut();
+    let mut checked_row_ids = HashSet::new();
+    let mut orphan_row_ids = vec![];
+    for view_meta in self.body.views.get_all_views_meta(&txn) {
+      for row_order in self.body.views.get_row_orders(&txn, &view_meta.id) {
+        if checked_row_ids.insert(row_order.id.clone())
+          && !self.body.block.is_row_exist(&row_order.id)
+        {
+          orphan_row_ids.push(row_order.id);
+        }
+      }
+    }
+
+    if !orphan_row_ids.is_empty() {
+      info!("[Database]: prune orphan row orders: {:?}", orphan_row_ids);
+      self
+        .body
+        .views
+        .update_all_views(&mut txn, |_view_id, mut update| {
+          for row_id in &orphan_row_ids {
+            update = update.remove_row_order(row_id);
+          }
+        });
+    }
+    orphan_row_ids
+  }
+
   pub fn subscribe_row_change(&self) -> Option<RowChangeReceiver> {
     self
       .body
@@ -315,10 +689,32 @@ impl Database {
       .map(|notifier| notifier.view_change_tx.subscribe())
   }
 
+  /// Returns where the rows of the database come from, see [BlockMetrics]
+  pub fn block_metrics(&self) -> BlockMetrics {
+    self.body.block.metrics()
+  }
+
+  pub fn reset_block_metrics(&self) {
+    self.body.block.reset_metrics();
+  }
+
   pub fn subscribe_block_event(&self) -> tokio::sync::broadcast::Receiver<BlockEvent> {
     self.body.block.subscribe_event()
   }
 
+  /// Subscribe to the row, field, view and block changes of the database with a single
+  /// receiver. Returns None if the database is created without a [DatabaseNotify].
+  ///
+  /// The changes of each kind keep their order, but the order across the different kinds of
+  /// changes is not guaranteed.
+  pub fn subscribe_all_changes(&self) -> Option<DatabaseChangeReceiver> {
+    self
+      .body
+      .notifier
+      .as_ref()
+      .map(|notifier| notifier.subscribe_all_changes(self.body.block.subscribe_event()))
+  }
+
   /// Return all field orders without order
   pub fn get_all_field_orders(&self) -> Vec<FieldOrder> {
     let txn = self.collab.transact();
@@ -370,24 +766,81 @@ impl Database {
     Ok(row_order)
   }
 
-  pub fn update_database_view<F>(&mut self, view_id: &str, f: F)
+  /// Create the rows and append their [RowOrder]s to all the views in one transaction. The rows
+  /// aren't kept in the row cache, see [Block::import_rows].
+  pub async fn import_rows(
+    &mut self,
+    rows: Vec<CreateRowParams>,
+  ) -> Result<Vec<RowOrder>, DatabaseError> {
+    let rows = rows
+      .into_iter()
+      .map(CreateRowParamsValidator::validate)
+      .collect::<Result<Vec<_>, _>>()?;
+    let row_orders = self.body.block.import_rows(rows).await?;
+    let mut txn = self.collab.transact_mut();
+    self
+      .body
+      .views
+      .update_all_views(&mut txn, |_view_id, update| {
+        update.set_row_orders(row_orders.clone());
+      });
+    Ok(row_orders)
+  }
+
+  /// Write the rows to disk and remove them from the row cache, see [Block::evict_rows].
+  pub async fn evict_rows(&self, row_ids: &[RowId]) -> Result<(), DatabaseError> {
+    self.body.block.evict_rows(row_ids).await
+  }
+
+  /// Create a new row and write the given meta into it. The [RowOrder] of the row is inserted
+  /// into each view at the position of the [CreateRowParams].
+  pub async fn create_row_with_meta(
+    &mut self,
+    params: CreateRowParams,
+    meta: Option<RowMeta>,
+  ) -> Result<RowOrder, DatabaseError> {
+    let params = CreateRowParamsValidator::validate(params)?;
+    let row_position = params.row_position.clone();
+    let row_order = self.body.block.create_new_row(params).await?;
+    if let Some(meta) = meta {
+      self
+        .body
+        .block
+        .update_row_meta(&row_order.id, |meta_update| {
+          meta_update
+            .insert_icon_if_not_none(meta.icon_url)
+            .insert_cover_if_not_none(meta.cover)
+            .update_is_document_empty(meta.is_document_empty)
+            .update_attachment_count(meta.attachment_count);
+        })
+        .await;
+    }
+
+    let mut txn = self.collab.transact_mut();
+    self
+      .body
+      .views
+      .update_all_views(&mut txn, |_view_id, update| {
+        update.insert_row_order(&row_order, &row_position);
+      });
+    Ok(row_order)
+  }
+
+  pub fn update_database_view<F>(&mut self, view_id: &str, f: F) -> Result<(), DatabaseError>
   where
     F: FnOnce(DatabaseViewUpdate),
   {
     let mut txn = self.collab.transact_mut();
-    self.body.views.update_database_view(&mut txn, view_id, f);
+    self.body.views.update_database_view(&mut txn, view_id, f)
   }
 
   pub fn contains_row(&self, view_id: &str, row_id: &RowId) -> bool {
     let txn = self.collab.transact();
-    if let Some(YrsValue::YMap(view)) = self.body.views.get(&txn, view_id) {
-      if let Some(YrsValue::YArray(row_orders)) = view.get(&txn, DATABASE_VIEW_ROW_ORDERS) {
-        return RowOrderArray::new(row_orders)
-          .get_position_with_txn(&txn, row_id)
-          .is_some();
-      }
-    }
-    false
+    self
+      .body
+      .views
+      .get_row_index(&txn, view_id, row_id)
+      .is_some()
   }
 
   /// Create a new row from the given view.
@@ -437,6 +890,41 @@ impl Database {
     });
   }
 
+  /// Move the row to the given index of the view. The index is clamped to the bounds of the
+  /// view's row orders. Unlike [Database::move_row], only the given view is updated.
+  pub fn move_row_to_index(
+    &mut self,
+    view_id: &str,
+    row_id: &RowId,
+    index: u32,
+  ) -> Result<(), DatabaseError> {
+    let mut txn = self.collab.transact_mut();
+    self
+      .body
+      .views
+      .update_database_view(&mut txn, view_id, |update| {
+        update.move_row_order_to_index(row_id, index);
+      })
+  }
+
+  /// Replace the row orders of the view with the given order in a single transaction, so only
+  /// one [crate::views::DatabaseViewChange::DidUpdateRowOrders] is sent. The heights of the
+  /// rows are kept. The unknown row ids are ignored and the rows that are missing from the
+  /// given ids are appended to the end.
+  pub fn set_row_orders(
+    &mut self,
+    view_id: &str,
+    ordered_row_ids: Vec<RowId>,
+  ) -> Result<(), DatabaseError> {
+    let mut txn = self.collab.transact_mut();
+    self
+      .body
+      .views
+      .update_database_view(&mut txn, view_id, |update| {
+        update.reorder_row_orders(&ordered_row_ids);
+      })
+  }
+
   pub async fn remove_rows(&mut self, row_ids: &[RowId]) -> Vec<Row> {
     {
       let mut txn = self.collab.transact_mut();
@@ -458,15 +946,45 @@ impl Database {
     rows
   }
 
-  /// Update the row
-  pub async fn update_row<F>(&mut self, row_id: RowId, f: F)
+  /// Update the row. Returns [DatabaseError::DatabaseRowNotFound] if the row can't be
+  /// initialized, for example, when it's not synced from the remote yet.
+  ///
+  /// ```no_run
+  /// # use collab_database::database::Database;
+  /// # use collab_database::error::DatabaseError;
+  /// # use collab_database::rows::{new_cell_builder, RowId};
+  /// # async fn example(database: &mut Database, row_id: RowId) -> Result<(), DatabaseError> {
+  /// database
+  ///   .update_row(row_id, |row| {
+  ///     row.update_cells(|cells| {
+  ///       // Write the cell of field "f1" and delete the cell of field "f2"
+  ///       cells.insert("f1", new_cell_builder(1)).remove("f2");
+  ///     });
+  ///   })
+  ///   .await?;
+  /// # Ok(())
+  /// # }
+  /// ```
+  pub async fn update_row<F>(&mut self, row_id: RowId, f: F) -> Result<(), DatabaseError>
   where
     F: FnOnce(RowUpdate),
   {
-    self.body.block.update_row(row_id, f).await;
+    self.body.block.update_row(row_id, f).await
   }
 
   /// Update the meta of the row
+  ///
+  /// ```no_run
+  /// # use collab_database::database::Database;
+  /// # use collab_database::rows::RowId;
+  /// # async fn example(database: &mut Database, row_id: RowId) {
+  /// database
+  ///   .update_row_meta(&row_id, |meta_update| {
+  ///     meta_update.insert_icon("🎉").remove_cover();
+  ///   })
+  ///   .await;
+  /// # }
+  /// ```
   pub async fn update_row_meta<F>(&mut self, row_id: &RowId, f: F)
   where
     F: FnOnce(RowMetaUpdate),
@@ -474,6 +992,107 @@ impl Database {
     self.body.block.update_row_meta(row_id, f).await;
   }
 
+  /// Links the row to the `related_row_id` of the database referenced by the relation field.
+  /// Linking a row that is already linked does nothing.
+  pub async fn add_related_row(
+    &mut self,
+    field_id: &str,
+    row_id: &RowId,
+    related_row_id: RowId,
+  ) -> Result<(), DatabaseError> {
+    self
+      .update_relation_cell(field_id, row_id, |row_ids| {
+        if !row_ids.contains(&related_row_id) {
+          row_ids.push(related_row_id);
+        }
+      })
+      .await
+  }
+
+  /// Unlinks the row from the `related_row_id` of the database referenced by the relation field
+  pub async fn remove_related_row(
+    &mut self,
+    field_id: &str,
+    row_id: &RowId,
+    related_row_id: &RowId,
+  ) -> Result<(), DatabaseError> {
+    self
+      .update_relation_cell(field_id, row_id, |row_ids| {
+        row_ids.retain(|id| id != related_row_id);
+      })
+      .await
+  }
+
+  async fn update_relation_cell(
+    &mut self,
+    field_id: &str,
+    row_id: &RowId,
+    f: impl FnOnce(&mut Vec<RowId>),
+  ) -> Result<(), DatabaseError> {
+    let field = self
+      .get_field(field_id)
+      .ok_or(DatabaseError::RecordNotFound)?;
+    if FieldType::from(field.field_type) != FieldType::Relation {
+      return Err(DatabaseError::Internal(anyhow::anyhow!(
+        "The field: {} is not a relation field",
+        field_id
+      )));
+    }
+
+    let database_row = self.body.block.get_or_init_database_row(row_id).await?;
+    database_row.write().await.update_cell(field_id, |cell| {
+      let mut cell_data = cell
+        .as_ref()
+        .map(RelationCellData::from)
+        .unwrap_or_default();
+      f(&mut cell_data.row_ids);
+      Cell::from(cell_data)
+    });
+    Ok(())
+  }
+
+  /// Returns the comments of the row in the order they were added
+  pub async fn get_row_comments(&self, row_id: &RowId) -> Vec<RowComment> {
+    match self.body.block.get_database_row(row_id).await {
+      None => vec![],
+      Some(row) => row.read().await.get_comments(),
+    }
+  }
+
+  /// Appends the comment to the comments of the row
+  pub async fn add_row_comment(
+    &mut self,
+    row_id: &RowId,
+    comment: RowComment,
+  ) -> Result<(), DatabaseError> {
+    let row = self.body.block.get_or_init_database_row(row_id).await?;
+    row.write().await.add_comment(comment);
+    Ok(())
+  }
+
+  /// Updates the content of the comment of the row
+  pub async fn update_row_comment(
+    &mut self,
+    row_id: &RowId,
+    comment_id: &str,
+    content: &str,
+  ) -> Result<(), DatabaseError> {
+    let row = self.body.block.get_or_init_database_row(row_id).await?;
+    let mut write_guard = row.write().await;
+    write_guard.update_comment(comment_id, content)
+  }
+
+  /// Deletes the comment of the row
+  pub async fn delete_row_comment(
+    &mut self,
+    row_id: &RowId,
+    comment_id: &str,
+  ) -> Result<(), DatabaseError> {
+    let row = self.body.block.get_or_init_database_row(row_id).await?;
+    let mut write_guard = row.write().await;
+    write_guard.delete_comment(comment_id)
+  }
+
   /// Return the index of the row in the given view.
   /// Return None if the row is not found.
   pub fn index_of_row(&self, view_id: &str, row_id: &RowId) -> Option<usize> {
@@ -584,8 +1203,66 @@ impl Database {
       .await
       .ok()?;
 
-    let read_guard = database_row.read().await;
-    read_guard.get_row_detail()
+    let read_guard = database_row.read().await;
+    read_guard.get_row_detail()
+  }
+
+  /// Loads the first `count` rows of the view into the cache, so the rows are ready when the
+  /// view is opened. The rows that are already in the cache are skipped, so it's safe to call
+  /// this method repeatedly.
+  pub async fn prefetch_rows_for_view(
+    &self,
+    view_id: &str,
+    count: usize,
+  ) -> Result<(), DatabaseError> {
+    let uncached_row_ids = self
+      .get_row_orders_for_view(view_id)
+      .into_iter()
+      .take(count)
+      .map(|row_order| row_order.id)
+      .filter(|row_id| !self.body.block.row_mem_cache.contains_key(row_id))
+      .collect::<Vec<_>>();
+    if uncached_row_ids.is_empty() {
+      return Ok(());
+    }
+
+    self
+      .body
+      .block
+      .batch_load_rows(uncached_row_ids, DEFAULT_LOAD_ROWS_CHUNK_SIZE)
+      .await
+  }
+
+  /// Return the [RowDetail]s of the given rows, preserving the order of the given row ids.
+  /// Rows that are not in the cache are loaded with [Block::batch_load_rows] first. Rows that
+  /// can't be found are skipped.
+  #[instrument(level = "debug", skip_all)]
+  pub async fn get_row_details(&self, row_ids: &[RowId]) -> Vec<RowDetail> {
+    let uncached_row_ids = row_ids
+      .iter()
+      .filter(|row_id| !self.body.block.row_mem_cache.contains_key(*row_id))
+      .cloned()
+      .collect::<Vec<_>>();
+    if !uncached_row_ids.is_empty() {
+      if let Err(err) = self
+        .body
+        .block
+        .batch_load_rows(uncached_row_ids, DEFAULT_LOAD_ROWS_CHUNK_SIZE)
+        .await
+      {
+        error!("Failed to load rows: {:?}", err);
+      }
+    }
+
+    let mut row_details = Vec::with_capacity(row_ids.len());
+    for row_id in row_ids {
+      if let Some(database_row) = self.body.block.get_database_row(row_id).await {
+        if let Some(row_detail) = database_row.read().await.get_row_detail() {
+          row_details.push(row_detail);
+        }
+      }
+    }
+    row_details
   }
 
   pub fn get_row_document_id(&self, row_id: &RowId) -> Option<String> {
@@ -593,14 +1270,23 @@ impl Database {
   }
 
   /// Return a list of [Row] for the given view.
-  /// The rows here are ordered by [RowOrder]s of the view.
+  /// The rows here are ordered by [RowOrder]s of the view. The rows hidden in the view are
+  /// skipped unless `include_hidden` is true.
   pub async fn get_rows_for_view(
     &self,
     view_id: &str,
     chunk_size: usize,
     cancel_token: Option<CancellationToken>,
+    include_hidden: bool,
   ) -> impl Stream<Item = Result<Row, DatabaseError>> + '_ {
-    let row_orders = self.get_row_orders_for_view(view_id);
+    let mut row_orders = self.get_row_orders_for_view(view_id);
+    if !include_hidden {
+      let hidden_rows = self.get_hidden_rows(view_id);
+      if !hidden_rows.is_empty() {
+        let hidden_rows = hidden_rows.iter().collect::<HashSet<_>>();
+        row_orders.retain(|row_order| !hidden_rows.contains(&row_order.id));
+      }
+    }
     self
       .get_rows_from_row_orders(&row_orders, chunk_size, cancel_token)
       .await
@@ -616,6 +1302,59 @@ impl Database {
     self.body.views.get_row_orders(&txn, view_id)
   }
 
+  /// Return the rows that are hidden in the given view.
+  pub fn get_hidden_rows(&self, view_id: &str) -> Vec<RowId> {
+    let txn = self.collab.transact();
+    self.body.views.get_hidden_rows(&txn, view_id)
+  }
+
+  /// Hide or show the row in the given view only. The row is still kept in the other views.
+  pub fn set_row_hidden(
+    &mut self,
+    view_id: &str,
+    row_id: &RowId,
+    hidden: bool,
+  ) -> Result<(), DatabaseError> {
+    let mut txn = self.collab.transact_mut();
+    self
+      .body
+      .views
+      .update_database_view(&mut txn, view_id, |update| {
+        if hidden {
+          update.hide_row(row_id);
+        } else {
+          update.unhide_row(row_id);
+        }
+      })
+  }
+
+  /// Return the colors of the rows in the given view, keyed by the row id.
+  pub fn get_row_colors(&self, view_id: &str) -> HashMap<RowId, String> {
+    let txn = self.collab.transact();
+    self.body.views.get_row_colors(&txn, view_id)
+  }
+
+  /// Set the color of the row in the given view. The color is removed if it's None.
+  pub fn set_row_color(
+    &mut self,
+    view_id: &str,
+    row_id: &RowId,
+    color: Option<&str>,
+  ) -> Result<(), DatabaseError> {
+    let mut txn = self.collab.transact_mut();
+    self
+      .body
+      .views
+      .update_database_view(&mut txn, view_id, |update| match color {
+        Some(color) => {
+          update.set_row_color(row_id, color);
+        },
+        None => {
+          update.remove_row_color(row_id);
+        },
+      })
+  }
+
   pub fn get_row_index(&self, view_id: &str, row_id: &RowId) -> Option<usize> {
     let txn = self.collab.transact();
     self.body.index_of_row(&txn, view_id, row_id)
@@ -664,7 +1403,8 @@ impl Database {
   }
 
   /// Returns the [Field] with the given field ids.
-  /// The fields are unordered.
+  /// The fields are unordered. Use [Database::get_fields_ordered] to get the fields in the order
+  /// they are displayed.
   pub fn get_fields(&self, field_ids: Option<Vec<String>>) -> Vec<Field> {
     let txn = self.collab.transact();
     self.body.fields.get_fields_with_txn(&txn, field_ids)
@@ -679,6 +1419,34 @@ impl Database {
     self.body.get_fields_in_view(&txn, view_id, field_ids)
   }
 
+  /// Returns the ids of all fields. The fields are unordered.
+  pub fn get_field_ids(&self) -> Vec<String> {
+    let txn = self.collab.transact();
+    self.body.fields.get_field_ids(&txn)
+  }
+
+  /// Returns the id and name of all fields. The fields are unordered.
+  pub fn get_field_names(&self) -> Vec<(String, String)> {
+    let txn = self.collab.transact();
+    self.body.fields.get_field_names(&txn)
+  }
+
+  /// Returns the fields ordered by the [FieldOrder] of the view. The inline view is used if
+  /// `view_id` is None. The fields that don't have a field order in the view are appended at
+  /// the end. If `field_ids` is Some, only the fields with the given ids are returned.
+  pub fn get_fields_ordered(
+    &self,
+    view_id: Option<&str>,
+    field_ids: Option<Vec<String>>,
+  ) -> Vec<Field> {
+    let txn = self.collab.transact();
+    let view_id = match view_id {
+      None => self.body.get_inline_view_id(&txn),
+      Some(view_id) => view_id.to_string(),
+    };
+    self.body.get_fields_ordered(&txn, &view_id, field_ids)
+  }
+
   /// Creates a new field, inserts field order and adds a field setting. See
   /// `create_field_with_txn` for more information.
   pub fn create_field(
@@ -725,8 +1493,17 @@ impl Database {
     (index, field)
   }
 
-  pub fn delete_field(&mut self, field_id: &str) {
+  /// Delete the field with the given id and remove it from all the views.
+  /// Returns [DatabaseError::CannotDeletePrimaryField] if the field is the primary field. Use
+  /// [Database::set_primary_field] to move the primary flag to another field first.
+  pub fn delete_field(&mut self, field_id: &str) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
+    if let Some(field) = self.body.fields.get_field(&txn, field_id) {
+      if field.is_primary {
+        return Err(DatabaseError::CannotDeletePrimaryField);
+      }
+    }
+
     self
       .body
       .views
@@ -736,6 +1513,60 @@ impl Database {
           .remove_field_setting(field_id);
       });
     self.body.fields.delete_field(&mut txn, field_id);
+
+    // Remove the filters and sorts that reference the deleted field
+    let field_ids = self.body.get_all_field_ids(&txn);
+    for view in self.body.views.get_all_views_meta(&txn) {
+      self
+        .body
+        .remove_stale_view_settings(&mut txn, &view.id, &field_ids);
+    }
+    Ok(())
+  }
+
+  /// Remove the filters and sorts of the view that reference a field that no longer exists.
+  /// The removal is observed as [crate::views::DatabaseViewChange::DidUpdateFilter] and
+  /// [crate::views::DatabaseViewChange::DidUpdateSort].
+  pub fn remove_stale_view_settings(&mut self, view_id: &str) -> Result<(), DatabaseError> {
+    let mut txn = self.collab.transact_mut();
+    if !self.body.views.contains_view(&txn, view_id) {
+      return Err(DatabaseError::DatabaseViewNotExist);
+    }
+    let field_ids = self.body.get_all_field_ids(&txn);
+    self
+      .body
+      .remove_stale_view_settings(&mut txn, view_id, &field_ids);
+    Ok(())
+  }
+
+  /// Mark the field with the given id as the primary field. The primary flag of the previous
+  /// primary field is cleared in the same transaction.
+  pub fn set_primary_field(&mut self, field_id: &str) -> Result<(), DatabaseError> {
+    let mut txn = self.collab.transact_mut();
+    if self.body.fields.get_field(&txn, field_id).is_none() {
+      return Err(DatabaseError::RecordNotFound);
+    }
+
+    let old_primary_field_ids = self
+      .body
+      .fields
+      .get_all_fields(&txn)
+      .into_iter()
+      .filter(|field| field.is_primary && field.id != field_id)
+      .map(|field| field.id)
+      .collect::<Vec<_>>();
+    for old_primary_field_id in old_primary_field_ids {
+      self
+        .body
+        .fields
+        .update_field(&mut txn, &old_primary_field_id, |update| {
+          update.set_primary(false);
+        });
+    }
+    self.body.fields.update_field(&mut txn, field_id, |update| {
+      update.set_primary(true);
+    });
+    Ok(())
   }
 
   pub fn get_all_group_setting<T: TryFrom<GroupSettingMap>>(&self, view_id: &str) -> Vec<T> {
@@ -750,7 +1581,11 @@ impl Database {
   }
 
   /// Add a group setting to the view. If the setting already exists, it will be replaced.
-  pub fn insert_group_setting(&mut self, view_id: &str, group_setting: impl Into<GroupSettingMap>) {
+  pub fn insert_group_setting(
+    &mut self,
+    view_id: &str,
+    group_setting: impl Into<GroupSettingMap>,
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -765,10 +1600,27 @@ impl Database {
           };
           Any::from(group_setting).fill(txn, &settings).unwrap();
         });
-      });
+      })
+  }
+
+  /// Add a group setting that groups the rows by the given fields, in order. The first field is
+  /// the primary field. If the setting already exists, it will be replaced.
+  pub fn insert_group_setting_with_field_ids(
+    &mut self,
+    view_id: &str,
+    group_setting: impl Into<GroupSettingMap>,
+    field_ids: Vec<String>,
+  ) -> Result<(), DatabaseError> {
+    let mut group_setting = group_setting.into();
+    set_group_setting_field_ids(&mut group_setting, field_ids);
+    self.insert_group_setting(view_id, group_setting)
   }
 
-  pub fn delete_group_setting(&mut self, view_id: &str, group_setting_id: &str) {
+  pub fn delete_group_setting(
+    &mut self,
+    view_id: &str,
+    group_setting_id: &str,
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -779,7 +1631,7 @@ impl Database {
             group_update.remove(txn, i);
           }
         });
-      });
+      })
   }
 
   pub fn update_group_setting(
@@ -787,7 +1639,7 @@ impl Database {
     view_id: &str,
     setting_id: &str,
     f: impl FnOnce(&mut GroupSettingMap),
-  ) {
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -796,10 +1648,14 @@ impl Database {
         view_update.update_groups(|txn, group_update| {
           group_update.update_map(txn, setting_id, f);
         });
-      });
+      })
   }
 
-  pub fn remove_group_setting(&mut self, view_id: &str, setting_id: &str) {
+  pub fn remove_group_setting(
+    &mut self,
+    view_id: &str,
+    setting_id: &str,
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -810,10 +1666,14 @@ impl Database {
             group_update.remove(txn, i);
           }
         });
-      });
+      })
   }
 
-  pub fn insert_sort(&mut self, view_id: &str, sort: impl Into<SortMap>) {
+  pub fn insert_sort(
+    &mut self,
+    view_id: &str,
+    sort: impl Into<SortMap>,
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -828,10 +1688,15 @@ impl Database {
             sort_update.push_back(txn, sort);
           }
         });
-      });
+      })
   }
 
-  pub fn move_sort(&mut self, view_id: &str, from_sort_id: &str, to_sort_id: &str) {
+  pub fn move_sort(
+    &mut self,
+    view_id: &str,
+    from_sort_id: &str,
+    to_sort_id: &str,
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -844,7 +1709,7 @@ impl Database {
             }
           }
         });
-      });
+      })
   }
 
   pub fn get_all_sorts<T>(&self, view_id: &str) -> Vec<T>
@@ -868,6 +1733,32 @@ impl Database {
       .collect()
   }
 
+  /// Same as [Database::get_all_sorts], but the sorts that reference a deleted field are
+  /// dropped. The stale sorts are not removed from the view, use
+  /// [Database::remove_stale_view_settings] to remove them.
+  pub fn get_all_sorts_validated<T>(&self, view_id: &str) -> Vec<T>
+  where
+    T: TryFrom<SortMap>,
+    <T as TryFrom<SortMap>>::Error: Debug,
+  {
+    let txn = self.collab.transact();
+    let field_ids = self.body.get_all_field_ids(&txn);
+    self
+      .body
+      .views
+      .get_view_sorts(&txn, view_id)
+      .into_iter()
+      .filter(|sort| is_valid_sort(sort, &field_ids))
+      .flat_map(|sort| match T::try_from(sort) {
+        Ok(sort) => Some(sort),
+        Err(err) => {
+          error!("Failed to convert sort, error: {:?}", err);
+          None
+        },
+      })
+      .collect()
+  }
+
   pub fn get_sort<T>(&self, view_id: &str, sort_id: &str) -> Option<T>
   where
     T: TryFrom<SortMap>,
@@ -896,7 +1787,7 @@ impl Database {
     }
   }
 
-  pub fn remove_sort(&mut self, view_id: &str, sort_id: &str) {
+  pub fn remove_sort(&mut self, view_id: &str, sort_id: &str) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -907,10 +1798,10 @@ impl Database {
             sort_update.remove(txn, i);
           }
         });
-      });
+      })
   }
 
-  pub fn remove_all_sorts(&mut self, view_id: &str) {
+  pub fn remove_all_sorts(&mut self, view_id: &str) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -919,9 +1810,12 @@ impl Database {
         update.update_sorts(|txn, sort_update| {
           sort_update.clear(txn);
         });
-      });
+      })
   }
 
+  /// Returns all the calculations of the view. The calculations without a scope are returned
+  /// with [crate::views::CalculationScope::AllRows], so the scope of each calculation can be
+  /// read with [crate::views::calculation_scope].
   pub fn get_all_calculations<T: TryFrom<CalculationMap>>(&self, view_id: &str) -> Vec<T> {
     let txn = self.collab.transact();
     self
@@ -929,7 +1823,7 @@ impl Database {
       .views
       .get_view_calculations(&txn, view_id)
       .into_iter()
-      .flat_map(|calculation| T::try_from(calculation).ok())
+      .flat_map(|calculation| T::try_from(with_default_calculation_scope(calculation)).ok())
       .collect()
   }
 
@@ -946,7 +1840,7 @@ impl Database {
       .get_view_calculations(&txn, view_id)
       .into_iter()
       .filter(|calculations_map| calculations_map.get("field_id") == Some(&field_id))
-      .flat_map(|value| T::try_from(value).ok())
+      .flat_map(|value| T::try_from(with_default_calculation_scope(value)).ok())
       .collect::<Vec<T>>();
 
     if calculations.is_empty() {
@@ -956,7 +1850,14 @@ impl Database {
     }
   }
 
-  pub fn update_calculation(&mut self, view_id: &str, calculation: impl Into<CalculationMap>) {
+  /// Insert the calculation or update the existing calculation with the same id. The
+  /// [crate::views::CALCULATION_SCOPE] is stored with the other keys of the calculation. Only
+  /// the given keys are updated, so an update without a scope keeps the existing scope.
+  pub fn update_calculation(
+    &mut self,
+    view_id: &str,
+    calculation: impl Into<CalculationMap>,
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -969,10 +1870,14 @@ impl Database {
             Any::from(calculation).fill(txn, &map_ref).unwrap();
           }
         });
-      });
+      })
   }
 
-  pub fn remove_calculation(&mut self, view_id: &str, calculation_id: &str) {
+  pub fn remove_calculation(
+    &mut self,
+    view_id: &str,
+    calculation_id: &str,
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -983,7 +1888,7 @@ impl Database {
             calculation_update.remove(txn, i);
           }
         });
-      });
+      })
   }
 
   pub fn get_all_filters<T>(&self, view_id: &str) -> Vec<T>
@@ -1007,6 +1912,32 @@ impl Database {
       .collect()
   }
 
+  /// Same as [Database::get_all_filters], but the filters that reference a deleted field are
+  /// dropped. The stale filters are not removed from the view, use
+  /// [Database::remove_stale_view_settings] to remove them.
+  pub fn get_all_filters_validated<T>(&self, view_id: &str) -> Vec<T>
+  where
+    T: TryFrom<FilterMap>,
+    <T as TryFrom<FilterMap>>::Error: Debug,
+  {
+    let txn = self.collab.transact();
+    let field_ids = self.body.get_all_field_ids(&txn);
+    self
+      .body
+      .views
+      .get_view_filters(&txn, view_id)
+      .into_iter()
+      .flat_map(|filter| retain_valid_filter(filter, &field_ids))
+      .flat_map(|setting| match T::try_from(setting) {
+        Ok(filter) => Some(filter),
+        Err(err) => {
+          error!("Failed to convert filter: {:?}", err);
+          None
+        },
+      })
+      .collect()
+  }
+
   pub fn get_filter<T>(&self, view_id: &str, filter_id: &str) -> Option<T>
   where
     T: TryFrom<FilterMap>,
@@ -1035,7 +1966,12 @@ impl Database {
     }
   }
 
-  pub fn update_filter(&mut self, view_id: &str, filter_id: &str, f: impl FnOnce(&mut FilterMap)) {
+  pub fn update_filter(
+    &mut self,
+    view_id: &str,
+    filter_id: &str,
+    f: impl FnOnce(&mut FilterMap),
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -1047,10 +1983,10 @@ impl Database {
           f(&mut filter_map);
           Any::from(filter_map).fill(txn, &map).unwrap();
         });
-      });
+      })
   }
 
-  pub fn remove_filter(&mut self, view_id: &str, filter_id: &str) {
+  pub fn remove_filter(&mut self, view_id: &str, filter_id: &str) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -1061,11 +1997,15 @@ impl Database {
             filter_update.remove(txn, i);
           }
         });
-      });
+      })
   }
 
   /// Add a filter to the view. If the setting already exists, it will be replaced.
-  pub fn insert_filter(&mut self, view_id: &str, filter: impl Into<FilterMap>) {
+  pub fn insert_filter(
+    &mut self,
+    view_id: &str,
+    filter: impl Into<FilterMap>,
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -1081,7 +2021,114 @@ impl Database {
             Any::from(filter).fill(txn, &map_ref).unwrap();
           }
         });
-      });
+      })
+  }
+
+  /// Add a filter group to the view. A filter group combines its children with the
+  /// [crate::views::FilterConditionType] and can be created with [crate::views::filter_group_map].
+  ///
+  /// The group is added as a top-level filter if the `parent_filter_id` is None. Otherwise, it's
+  /// appended to the children of the filter group with the given id. Returns
+  /// [DatabaseError::RecordNotFound] if the parent filter doesn't exist.
+  pub fn insert_filter_group(
+    &mut self,
+    view_id: &str,
+    parent_filter_id: Option<&str>,
+    filter_group: impl Into<FilterMap>,
+  ) -> Result<(), DatabaseError> {
+    let filter_group = filter_group.into();
+    let mut txn = self.collab.transact_mut();
+    let parent_filter_id = match parent_filter_id {
+      None => {
+        return self
+          .body
+          .views
+          .update_database_view(&mut txn, view_id, |update| {
+            update.update_filters(|txn, filter_update| {
+              let map_ref = filter_update.push_back(txn, MapPrelim::default());
+              Any::from(filter_group).fill(txn, &map_ref).unwrap();
+            });
+          });
+      },
+      Some(parent_filter_id) => parent_filter_id,
+    };
+
+    if !self.body.views.contains_view(&txn, view_id) {
+      return Err(DatabaseError::DatabaseViewNotExist);
+    }
+    let filters = self.body.views.get_view_filters(&txn, view_id);
+    let (index, mut root_filter) = filters
+      .into_iter()
+      .enumerate()
+      .find(|(_, filter)| contains_filter(filter, parent_filter_id))
+      .ok_or(DatabaseError::RecordNotFound)?;
+    let is_root_parent = root_filter.get(FILTER_ID) == Some(&Any::from(parent_filter_id));
+    if !is_root_parent {
+      update_nested_filter(
+        &mut root_filter,
+        parent_filter_id,
+        &mut Some(|parent: &mut FilterMap| {
+          let mut children = filter_children(parent)
+            .into_iter()
+            .map(Any::from)
+            .collect::<Vec<_>>();
+          children.push(Any::from(filter_group.clone()));
+          parent.insert(FILTER_CHILDREN.to_string(), Any::Array(children.into()));
+        }),
+      );
+    }
+
+    self
+      .body
+      .views
+      .update_database_view(&mut txn, view_id, |update| {
+        update.update_filters(|txn, filter_update| {
+          let map_ref = match filter_update.get(txn, index as u32) {
+            Some(YrsValue::YMap(map_ref)) => map_ref,
+            _ => return,
+          };
+          if is_root_parent {
+            // Only the new child is inserted, so the change is observed as a new filter.
+            let children: ArrayRef = map_ref.get_or_init(txn, FILTER_CHILDREN);
+            children.push_back(txn, Any::from(filter_group));
+          } else {
+            Any::from(root_filter).fill(txn, &map_ref).unwrap();
+          }
+        });
+      })
+  }
+
+  /// Update the filter group, or any other filter, with the given id. Unlike
+  /// [Database::update_filter], the filters nested in the filter groups are searched as well.
+  /// Returns [DatabaseError::RecordNotFound] if the filter doesn't exist.
+  pub fn update_filter_group(
+    &mut self,
+    view_id: &str,
+    filter_id: &str,
+    f: impl FnOnce(&mut FilterMap),
+  ) -> Result<(), DatabaseError> {
+    let mut txn = self.collab.transact_mut();
+    if !self.body.views.contains_view(&txn, view_id) {
+      return Err(DatabaseError::DatabaseViewNotExist);
+    }
+    let filters = self.body.views.get_view_filters(&txn, view_id);
+    let (index, mut root_filter) = filters
+      .into_iter()
+      .enumerate()
+      .find(|(_, filter)| contains_filter(filter, filter_id))
+      .ok_or(DatabaseError::RecordNotFound)?;
+    update_nested_filter(&mut root_filter, filter_id, &mut Some(f));
+
+    self
+      .body
+      .views
+      .update_database_view(&mut txn, view_id, |update| {
+        update.update_filters(|txn, filter_update| {
+          if let Some(YrsValue::YMap(map_ref)) = filter_update.get(txn, index as u32) {
+            Any::from(root_filter).fill(txn, &map_ref).unwrap();
+          }
+        });
+      })
   }
 
   /// Sets the filters of a database view. Requires two generics to work around the situation where
@@ -1090,7 +2137,7 @@ impl Database {
   ///
   /// * `T`: needs to be able to do `AnyMap::from(&T)`.
   /// * `U`: needs to implement `Into<AnyMap>`, could be just an identity conversion.
-  pub fn save_filters<T, U>(&mut self, view_id: &str, filters: &[T])
+  pub fn save_filters<T, U>(&mut self, view_id: &str, filters: &[T]) -> Result<(), DatabaseError>
   where
     U: for<'a> From<&'a T> + Into<FilterMap>,
   {
@@ -1106,7 +2153,7 @@ impl Database {
             .map(Into::into)
             .collect(),
         );
-      });
+      })
   }
 
   pub fn get_layout_setting<T: From<LayoutSetting>>(
@@ -1118,19 +2165,58 @@ impl Database {
     self.body.views.get_layout_setting(&txn, view_id, layout_ty)
   }
 
+  /// Returns the [GridLayoutSetting] of the view, e.g. the number of the frozen columns.
+  pub fn get_grid_layout_setting(&self, view_id: &str) -> Option<GridLayoutSetting> {
+    self.get_layout_setting(view_id, &DatabaseLayout::Grid)
+  }
+
+  /// Returns the [BoardLayoutSetting] of the view, e.g. the collapsed group columns.
+  pub fn get_board_layout_setting(&self, view_id: &str) -> Option<BoardLayoutSetting> {
+    self.get_layout_setting(view_id, &DatabaseLayout::Board)
+  }
+
+  /// Update the [BoardLayoutSetting] of the view. The default setting is used if the view
+  /// doesn't have one yet.
+  pub fn update_board_layout_setting(
+    &mut self,
+    view_id: &str,
+    f: impl FnOnce(&mut BoardLayoutSetting),
+  ) -> Result<(), DatabaseError> {
+    let mut layout_setting = self.get_board_layout_setting(view_id).unwrap_or_default();
+    f(&mut layout_setting);
+    self.insert_layout_setting(view_id, &DatabaseLayout::Board, layout_setting)
+  }
+
   pub fn insert_layout_setting<T: Into<LayoutSetting>>(
     &mut self,
     view_id: &str,
     layout_ty: &DatabaseLayout,
     layout_setting: T,
-  ) {
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
       .views
       .update_database_view(&mut txn, view_id, |update| {
         update.update_layout_settings(layout_ty, layout_setting.into());
-      });
+      })
+  }
+
+  /// Same as [Self::insert_layout_setting], but the calendar layout setting is validated
+  /// against the fields of the database before it's inserted. The other layout settings are
+  /// inserted as is.
+  pub fn insert_layout_setting_with_validation<T: Into<LayoutSetting>>(
+    &mut self,
+    view_id: &str,
+    layout_ty: &DatabaseLayout,
+    layout_setting: T,
+  ) -> Result<(), DatabaseError> {
+    let layout_setting = layout_setting.into();
+    if matches!(layout_ty, DatabaseLayout::Calendar) {
+      CalendarLayoutSetting::try_from_layout_setting(layout_setting.clone())?
+        .validate(&self.get_fields(None))?;
+    }
+    self.insert_layout_setting(view_id, layout_ty, layout_setting)
   }
 
   /// Returns the field settings for the given field ids.
@@ -1161,7 +2247,7 @@ impl Database {
     &mut self,
     view_id: &str,
     field_settings_map: FieldSettingsByFieldIdMap,
-  ) {
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -1176,7 +2262,7 @@ impl Database {
     view_id: &str,
     field_ids: Option<Vec<String>>,
     field_settings: impl Into<FieldSettingsMap>,
-  ) {
+  ) -> Result<(), DatabaseError> {
     let field_ids = field_ids.unwrap_or(
       self
         .get_fields(None)
@@ -1203,7 +2289,11 @@ impl Database {
       })
   }
 
-  pub fn remove_field_settings_for_fields(&mut self, view_id: &str, field_ids: Vec<String>) {
+  pub fn remove_field_settings_for_fields(
+    &mut self,
+    view_id: &str,
+    field_ids: Vec<String>,
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
@@ -1219,14 +2309,25 @@ impl Database {
   }
 
   /// Update the layout type of the view.
-  pub fn update_layout_type(&mut self, view_id: &str, layout_type: &DatabaseLayout) {
+  pub fn update_layout_type(
+    &mut self,
+    view_id: &str,
+    layout_type: &DatabaseLayout,
+  ) -> Result<(), DatabaseError> {
     let mut txn = self.collab.transact_mut();
     self
       .body
       .views
       .update_database_view(&mut txn, view_id, |update| {
         update.set_layout_type(*layout_type);
-      });
+      })
+  }
+
+  /// Returns the meta of the view. Prefer it over [Self::get_view] when only the name, the
+  /// layout or the creation time of the view are needed, since the row orders aren't decoded.
+  pub fn get_view_meta(&self, view_id: &str) -> Option<DatabaseViewMeta> {
+    let txn = self.collab.transact();
+    self.body.views.get_view_meta(&txn, view_id)
   }
 
   /// Returns all the views that the current database has.
@@ -1262,13 +2363,33 @@ impl Database {
 
   /// Create a linked view that duplicate the target view's setting including filter, sort,
   /// group, field setting, etc.
-  pub fn duplicate_linked_view(&mut self, view_id: &str) -> Option<DatabaseView> {
+  ///
+  /// The duplicated view is named after the target view with a "-copy" suffix. If the name is
+  /// already taken, a number is appended, e.g. "Grid-copy 2", "Grid-copy 3".
+  ///
+  /// Returns the index of the duplicated view among the linked views of the database ordered by
+  /// creation time, and the duplicated view. The duplicated view comes after the views that were
+  /// created at the same time.
+  pub fn duplicate_linked_view(&mut self, view_id: &str) -> Option<(usize, DatabaseView)> {
     let mut txn = self.collab.transact_mut();
     let view = self.body.views.get_view(&txn, view_id)?;
+    let views_meta = self
+      .body
+      .views
+      .get_all_views_meta(&txn)
+      .into_iter()
+      .filter(|view| !view.is_inline)
+      .collect::<Vec<_>>();
+    let existing_names = views_meta
+      .iter()
+      .map(|view| view.name.as_str())
+      .collect::<HashSet<_>>();
+    let name = gen_duplicated_view_name(&view.name, &existing_names);
+
     let timestamp = timestamp();
     let duplicated_view = DatabaseView {
       id: gen_database_view_id(),
-      name: format!("{}-copy", view.name),
+      name,
       created_at: timestamp,
       modified_at: timestamp,
       ..view
@@ -1278,22 +2399,27 @@ impl Database {
       .views
       .insert_view(&mut txn, duplicated_view.clone());
 
-    Some(duplicated_view)
+    // The views created later than the duplicated one, e.g. by a client whose clock is ahead,
+    // stay after it
+    let index = views_meta
+      .iter()
+      .filter(|view| view.created_at <= timestamp)
+      .count();
+    Some((index, duplicated_view))
   }
 
-  /// Duplicate the row, and insert it after the original row.
-  pub async fn duplicate_row(&self, row_id: &RowId) -> Option<CreateRowParams> {
+  /// Duplicate the row, and insert it after the original row. The returned [DuplicatedRow]
+  /// contains the meta of the original row and the id of its document.
+  pub async fn duplicate_row(&self, row_id: &RowId) -> Option<DuplicatedRow> {
     let database_id = self.get_database_id();
-    let row = self
-      .body
-      .block
-      .get_database_row(row_id)
-      .await?
-      .read()
-      .await
-      .get_row()?;
+    let database_row = self.body.block.get_database_row(row_id).await?;
+    let (row, meta) = {
+      let read_guard = database_row.read().await;
+      (read_guard.get_row()?, read_guard.get_row_meta())
+    };
+    let source_document_id = row.document_id();
     let timestamp = timestamp();
-    Some(CreateRowParams {
+    let params = CreateRowParams {
       id: gen_row_id(),
       database_id,
       cells: row.cells,
@@ -1302,6 +2428,13 @@ impl Database {
       row_position: OrderObjectPosition::After(row.id.into()),
       created_at: timestamp,
       modified_at: timestamp,
+      created_by: None,
+      modified_by: None,
+    };
+    Some(DuplicatedRow {
+      params,
+      meta,
+      source_document_id,
     })
   }
 
@@ -1339,14 +2472,31 @@ impl Database {
   }
 
   pub async fn get_database_data(&self) -> DatabaseData {
-    let txn = self.collab.transact();
+    self.get_database_data_with_limit(None).await
+  }
 
-    let database_id = self.body.get_database_id(&txn);
-    let inline_view_id = self.body.get_inline_view_id(&txn);
+  /// Same as [Database::get_database_data], but only loads the rows of the first `row_limit`
+  /// row orders of the inline view. The total number of rows is recorded in
+  /// [DatabaseData::row_count].
+  pub async fn get_database_data_with_limit(&self, row_limit: Option<usize>) -> DatabaseData {
+    let (database_id, meta, fields, mut row_orders) = {
+      let txn = self.collab.transact();
+      let database_id = self.body.get_database_id(&txn);
+      let inline_view_id = self.body.get_inline_view_id(&txn);
+      let meta = self.body.metas.get_database_meta(&txn);
+      let fields = self.body.get_fields_in_view(&txn, &inline_view_id, None);
+      let row_orders = self.body.views.get_row_orders(&txn, &inline_view_id);
+      (database_id, meta, fields, row_orders)
+    };
     let views = self.get_all_views();
-    let fields = self.body.get_fields_in_view(&txn, &inline_view_id, None);
-    let rows_stream = self.get_all_rows(20, None).await;
-    let rows: Vec<Row> = rows_stream
+    let row_count = row_orders.len();
+    if let Some(row_limit) = row_limit {
+      row_orders.truncate(row_limit);
+    }
+
+    let rows: Vec<Row> = self
+      .get_rows_from_row_orders(&row_orders, 20, None)
+      .await
       .filter_map(|result| async move { result.ok() })
       .collect()
       .await;
@@ -1356,9 +2506,25 @@ impl Database {
       fields,
       rows,
       views,
+      meta,
+      row_count,
     }
   }
 
+  /// Returns the name, icon and creation time of the database.
+  pub fn get_database_meta(&self) -> DatabaseMetaData {
+    let txn = self.collab.transact();
+    self.body.metas.get_database_meta(&txn)
+  }
+
+  pub fn update_database_meta<F>(&mut self, f: F)
+  where
+    F: FnOnce(DatabaseMetaUpdate),
+  {
+    let mut txn = self.collab.transact_mut();
+    self.body.metas.update_database_meta(&mut txn, f);
+  }
+
   pub fn get_view(&self, view_id: &str) -> Option<DatabaseView> {
     let txn = self.collab.transact();
     self.body.views.get_view(&txn, view_id)
@@ -1390,6 +2556,33 @@ impl Database {
       .await
   }
 
+  /// Return a stream of all the rows in the database, ordered by the [RowOrder]s of the inline
+  /// view. Unlike [Database::collect_all_rows], the row collabs are loaded in chunks while the
+  /// stream is polled, so consumers can process the rows incrementally.
+  pub fn stream_all_rows(&self) -> impl Stream<Item = Row> + '_ {
+    let row_ids = self
+      .get_inline_row_orders()
+      .into_iter()
+      .map(|order| order.id)
+      .collect::<Vec<RowId>>();
+    let database_id = self.get_database_id();
+    self
+      .init_database_rows(row_ids, STREAM_ROWS_CHUNK_SIZE, None)
+      .filter_map(move |result| {
+        let database_id = database_id.clone();
+        async move {
+          let row = result.ok()?;
+          let read_guard = row.read().await;
+          let row_id = read_guard.row_id.clone();
+          Some(
+            read_guard
+              .get_row()
+              .unwrap_or_else(|| Row::empty(row_id, &database_id)),
+          )
+        }
+      })
+  }
+
   pub async fn collect_all_rows(&self) -> Vec<Result<Row, DatabaseError>> {
     let rows_stream = self.get_all_rows(20, None).await;
     rows_stream.collect::<Vec<_>>().await
@@ -1413,10 +2606,92 @@ impl Database {
     self.body.get_inline_view_id(&txn)
   }
 
+  /// Promote the view with the given id to be the inline view of the database.
+  ///
+  /// Row orders and field orders that exist in the current inline view but are missing in the
+  /// new inline view are appended to the new inline view, so no rows or fields get lost. The
+  /// `is_inline` flag of both views is updated in the same transaction.
+  /// Returns [DatabaseError::DatabaseViewNotExist] if the view doesn't exist.
+  pub fn set_inline_view(&mut self, view_id: &str) -> Result<(), DatabaseError> {
+    let mut txn = self.collab.transact_mut();
+    if self.body.views.get_view(&txn, view_id).is_none() {
+      return Err(DatabaseError::DatabaseViewNotExist);
+    }
+
+    let old_inline_view_id = self.body.metas.get_inline_view_id(&txn);
+    let mut missing_row_orders = vec![];
+    let mut missing_field_orders = vec![];
+    if let Some(old_inline_view_id) = old_inline_view_id.filter(|id| id != view_id) {
+      let row_ids = self
+        .body
+        .views
+        .get_row_orders(&txn, view_id)
+        .into_iter()
+        .map(|row_order| row_order.id)
+        .collect::<HashSet<_>>();
+      missing_row_orders = self
+        .body
+        .views
+        .get_row_orders(&txn, &old_inline_view_id)
+        .into_iter()
+        .filter(|row_order| !row_ids.contains(&row_order.id))
+        .collect::<Vec<_>>();
+
+      let field_ids = self
+        .body
+        .views
+        .get_field_orders(&txn, view_id)
+        .into_iter()
+        .map(|field_order| field_order.id)
+        .collect::<HashSet<_>>();
+      missing_field_orders = self
+        .body
+        .views
+        .get_field_orders(&txn, &old_inline_view_id)
+        .into_iter()
+        .filter(|field_order| !field_ids.contains(&field_order.id))
+        .collect::<Vec<_>>();
+
+      // The previous inline view might have been deleted already
+      if self
+        .body
+        .views
+        .get_view(&txn, &old_inline_view_id)
+        .is_some()
+      {
+        self
+          .body
+          .views
+          .update_database_view(&mut txn, &old_inline_view_id, |update| {
+            update.set_is_inline(false);
+          })?;
+      }
+    }
+
+    self
+      .body
+      .views
+      .update_database_view(&mut txn, view_id, |mut update| {
+        for row_order in missing_row_orders {
+          update = update.insert_row_order(row_order, &OrderObjectPosition::End);
+        }
+        for field_order in missing_field_orders {
+          update = update.insert_field_order(field_order, &OrderObjectPosition::End);
+        }
+        update.set_is_inline(true);
+      })?;
+    self.body.metas.set_inline_view_id(&mut txn, view_id);
+    Ok(())
+  }
+
   /// Delete a view from the database. If the view is the inline view it will clear all
   /// the linked views as well. Otherwise, just delete the view with given view id.
+  /// Returns the ids of the deleted views.
+  ///
+  /// The workspace database is not updated here. Use
+  /// [crate::workspace_database::WorkspaceDatabaseManager::delete_view]
+  /// to keep the [DatabaseMeta] in sync with the deleted views.
   pub fn delete_view(&mut self, view_id: &str) -> Vec<String> {
-    // TODO(nathan): delete the database from workspace database
     let mut txn = self.collab.transact_mut();
     if self.body.get_inline_view_id(&txn) == view_id {
       let views = self.body.views.get_all_views_meta(&txn);
@@ -1445,6 +2720,149 @@ impl Database {
     let mut txn = self.collab.transact_mut();
     self.body.fields.update_field(&mut txn, field_id, f);
   }
+
+  /// Rewrites the cells of the field with the `converter` and changes the type of the field.
+  /// The rows of the inline view are loaded in chunks. The cells of a chunk are converted first,
+  /// then each row is updated with its converted cell. The type of the field is changed in one
+  /// transaction after all the chunks, so the field never has the new type while some of its
+  /// cells haven't been converted yet.
+  ///
+  /// The cell is kept as it is if the converter returns None, so the data that can't be
+  /// converted isn't lost. The same applies to the rows of a chunk that can't be loaded: the
+  /// chunk is skipped and logged instead of aborting the conversion halfway. The changes are
+  /// observed as [crate::fields::FieldChange] and [crate::rows::RowChange]. Returns the number
+  /// of converted cells.
+  ///
+  /// See [text_to_number_cell] and [Self::convert_text_field_to_select] for the built-in
+  /// converters.
+  pub async fn convert_field_type(
+    &mut self,
+    field_id: &str,
+    new_type: i64,
+    converter: impl Fn(&Cell) -> Option<Cell>,
+  ) -> Result<usize, DatabaseError> {
+    self
+      .convert_field_type_with_type_option(field_id, new_type, None, converter)
+      .await
+  }
+
+  /// Converts a text field into a number field. The cells that are not numbers are kept as they
+  /// are.
+  pub async fn convert_text_field_to_number(
+    &mut self,
+    field_id: &str,
+  ) -> Result<usize, DatabaseError> {
+    self
+      .convert_field_type(field_id, FieldType::Number.into(), text_to_number_cell)
+      .await
+  }
+
+  /// Converts a text field into a [FieldType::SingleSelect] or [FieldType::MultiSelect] field.
+  /// The comma separated names in the text cells become the options of the field, the options
+  /// that don't exist yet are created.
+  pub async fn convert_text_field_to_select(
+    &mut self,
+    field_id: &str,
+    field_type: FieldType,
+  ) -> Result<usize, DatabaseError> {
+    if !matches!(field_type, FieldType::SingleSelect | FieldType::MultiSelect) {
+      return Err(DatabaseError::Internal(anyhow::anyhow!(
+        "{:?} is not a select field type",
+        field_type
+      )));
+    }
+    let field = self
+      .get_field(field_id)
+      .ok_or(DatabaseError::RecordNotFound)?;
+    let mut type_option = field
+      .get_type_option::<SelectTypeOption>(field_type.type_id())
+      .unwrap_or_default();
+
+    let inline_view_id = self.get_inline_view_id();
+    let cells = self
+      .get_cells_for_field(&inline_view_id, field_id)
+      .await
+      .into_iter()
+      .flat_map(|row_cell| row_cell.cell)
+      .collect::<Vec<_>>();
+    let new_options = select_options_from_text_cells(&cells, &type_option.options);
+    type_option.options.extend(new_options);
+
+    let options = type_option.options.clone();
+    self
+      .convert_field_type_with_type_option(
+        field_id,
+        field_type.into(),
+        Some(type_option.into()),
+        |cell| text_to_select_cell(cell, &options, field_type),
+      )
+      .await
+  }
+
+  async fn convert_field_type_with_type_option(
+    &mut self,
+    field_id: &str,
+    new_type: i64,
+    type_option: Option<TypeOptionData>,
+    converter: impl Fn(&Cell) -> Option<Cell>,
+  ) -> Result<usize, DatabaseError> {
+    let field = self
+      .get_field(field_id)
+      .ok_or(DatabaseError::RecordNotFound)?;
+
+    let row_ids = self
+      .get_inline_row_orders()
+      .into_iter()
+      .map(|row_order| row_order.id)
+      .collect::<Vec<_>>();
+    let mut num_of_converted_cells = 0;
+    for chunk in row_ids.chunks(DEFAULT_LOAD_ROWS_CHUNK_SIZE) {
+      let database_rows = match self.body.block.init_database_rows(chunk.to_vec()).await {
+        Ok(database_rows) => database_rows,
+        Err(err) => {
+          error!(
+            "Skip converting the cells of {} rows of the field:{}, error: {}",
+            chunk.len(),
+            field_id,
+            err
+          );
+          continue;
+        },
+      };
+
+      let mut converted_rows = Vec::with_capacity(database_rows.len());
+      for database_row in database_rows {
+        let new_cell = database_row
+          .read()
+          .await
+          .get_cell(field_id)
+          .and_then(|cell| converter(&cell));
+        if let Some(new_cell) = new_cell {
+          converted_rows.push((database_row, new_cell));
+        }
+      }
+
+      for (database_row, new_cell) in converted_rows {
+        database_row.write().await.update(|update| {
+          update.update_cells(|cells_update| {
+            cells_update.insert_cell(field_id, new_cell);
+          });
+        });
+        num_of_converted_cells += 1;
+      }
+    }
+
+    let type_option = type_option
+      .or_else(|| field.get_any_type_option(new_type))
+      .unwrap_or_else(|| default_type_option_data_from_type(FieldType::from(new_type)));
+    let mut txn = self.collab.transact_mut();
+    self.body.fields.update_field(&mut txn, field_id, |update| {
+      update
+        .set_field_type(new_type)
+        .set_type_option(new_type, Some(type_option));
+    });
+    Ok(num_of_converted_cells)
+  }
 }
 
 impl Deref for Database {
@@ -1478,6 +2896,33 @@ pub fn gen_database_id() -> String {
   uuid::Uuid::new_v4().to_string()
 }
 
+/// Generate the name of a duplicated view. The "-copy" or "-copy N" suffix of the original name
+/// is stripped first, so duplicating "Grid-copy 2" produces "Grid-copy 3" instead of
+/// "Grid-copy 2-copy".
+fn gen_duplicated_view_name(name: &str, existing_names: &HashSet<&str>) -> String {
+  const COPY_SUFFIX: &str = "-copy";
+  let base_name = match name.rsplit_once(COPY_SUFFIX) {
+    Some((base, "")) => base,
+    Some((base, suffix)) if is_copy_number(suffix) => base,
+    _ => name,
+  };
+
+  let mut copy_name = format!("{}{}", base_name, COPY_SUFFIX);
+  let mut number = 2;
+  while existing_names.contains(copy_name.as_str()) {
+    copy_name = format!("{}{} {}", base_name, COPY_SUFFIX, number);
+    number += 1;
+  }
+  copy_name
+}
+
+/// Return true if the suffix is a space followed by ascii digits, e.g. " 2".
+fn is_copy_number(suffix: &str) -> bool {
+  suffix
+    .strip_prefix(' ')
+    .is_some_and(|number| !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()))
+}
+
 pub fn gen_database_view_id() -> String {
   uuid::Uuid::new_v4().to_string()
 }
@@ -1489,8 +2934,11 @@ pub fn gen_database_file_id() -> String {
   uuid::Uuid::new_v4().to_string()
 }
 
+/// Generates a time-ordered row id (UUIDv7). The ids generated by the same process are ordered
+/// by their creation, so the rows created in bulk, for example by importing a CSV file, keep
+/// their order without relying on the timestamps.
 pub fn gen_row_id() -> RowId {
-  RowId::from(uuid::Uuid::new_v4().to_string())
+  RowId::from(uuid::Uuid::now_v7())
 }
 
 pub fn get_row_document_id(row_id: &RowId) -> Result<String, DatabaseError> {
@@ -1526,11 +2974,44 @@ pub fn timestamp() -> i64 {
 /// DatabaseData contains all the data of a database.
 /// It's used when duplicating a database, or during import and export.
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(from = "DatabaseDataDeserializer")]
 pub struct DatabaseData {
   pub database_id: String,
   pub views: Vec<DatabaseView>,
   pub fields: Vec<Field>,
   pub rows: Vec<Row>,
+  pub meta: DatabaseMetaData,
+  /// The total number of rows in the database. It might be greater than the length of `rows`
+  /// if the data was created by [Database::get_database_data_with_limit].
+  pub row_count: usize,
+}
+
+/// Data serialized before `row_count` was introduced doesn't have it. In that case, the number
+/// of rows is used.
+#[derive(Deserialize)]
+struct DatabaseDataDeserializer {
+  database_id: String,
+  views: Vec<DatabaseView>,
+  fields: Vec<Field>,
+  rows: Vec<Row>,
+  #[serde(default)]
+  meta: DatabaseMetaData,
+  #[serde(default)]
+  row_count: Option<usize>,
+}
+
+impl From<DatabaseDataDeserializer> for DatabaseData {
+  fn from(data: DatabaseDataDeserializer) -> Self {
+    let row_count = data.row_count.unwrap_or(data.rows.len());
+    Self {
+      database_id: data.database_id,
+      views: data.views,
+      fields: data.fields,
+      rows: data.rows,
+      meta: data.meta,
+      row_count,
+    }
+  }
 }
 
 impl DatabaseData {
@@ -1620,6 +3101,16 @@ pub fn is_database_collab(collab: &Collab) -> bool {
   collab.get_with_txn(&txn, DATABASE).is_some()
 }
 
+/// Return true if the database collab contains at least one view.
+pub fn has_database_views(collab: &Collab) -> bool {
+  let txn = collab.context.transact();
+  collab
+    .data
+    .get_with_path::<_, _, MapRef>(&txn, [DATABASE, VIEWS])
+    .map(|views| views.len(&txn) > 0)
+    .unwrap_or(false)
+}
+
 /// Quickly retrieve the inline view ID of a database.
 /// Use this function when instantiating a [Database] object is too resource-intensive,
 /// and you need the inline view ID of a specific database.
@@ -1677,7 +3168,12 @@ impl DatabaseBody {
     let metas: MapRef = root.get_or_init(&mut txn, DATABASE_METAS); // { DATABASE: { FIELDS: {:},  VIEWS: {:}, METAS: {:} } }
 
     let fields = FieldMap::new(fields, Some(context.notifier.field_change_tx.clone()));
-    let views = DatabaseViews::new(origin, views, Some(context.notifier.view_change_tx.clone()));
+    let views = DatabaseViews::new_with_txn(
+      origin,
+      views,
+      &txn,
+      Some(context.notifier.view_change_tx.clone()),
+    );
     let block = Block::new(
       database_id.clone(),
       context.collab_service.clone(),
@@ -1710,6 +3206,128 @@ impl DatabaseBody {
     inline_view.field_orders.clone_from(&field_orders);
     views.insert_view(&mut txn, inline_view);
 
+    let metas = MetaMap::new(metas);
+    metas.set_inline_view_id(&mut txn, &inline_view_id.to_string());
+    if metas.get_created_at(&txn).is_none() {
+      metas.update_database_meta(&mut txn, |update| {
+        update.set_created_at(timestamp());
+      });
+    }
+    drop(txn);
+
+    let body = DatabaseBody {
+      root,
+      views: views.into(),
+      fields: fields.into(),
+      metas: metas.into(),
+      block,
+      notifier: Some(context.notifier),
+    };
+    Ok((body, collab))
+  }
+
+  /// Recreate the inline view of the given [Collab] from the row collabs with the given ids.
+  /// See [Database::rebuild_from_rows] for more information.
+  async fn rebuild(
+    mut collab: Collab,
+    database_id: String,
+    context: DatabaseContext,
+    row_ids: Vec<RowId>,
+  ) -> Result<(Self, Collab), DatabaseError> {
+    let block = Block::new(
+      database_id.clone(),
+      context.collab_service.clone(),
+      Some(context.notifier.row_change_tx.clone()),
+    );
+
+    let mut rows = Vec::with_capacity(row_ids.len());
+    for row_id in row_ids {
+      match block.get_or_init_database_row(&row_id).await {
+        Ok(database_row) => {
+          if let Some(row) = database_row.read().await.get_row() {
+            rows.push(row);
+          }
+        },
+        Err(err) => error!(
+          "[Fix]: skip row:{} when rebuilding database: {}",
+          row_id, err
+        ),
+      }
+    }
+    rows.sort_by_key(|row| row.created_at);
+
+    // Collect the field ids in the order they first appear in the rows
+    let mut field_types: Vec<(String, FieldType)> = vec![];
+    for row in rows.iter() {
+      let mut cells = row.cells.iter().collect::<Vec<_>>();
+      cells.sort_by(|a, b| a.0.cmp(b.0));
+      for (field_id, cell) in cells {
+        if field_types.iter().all(|(id, _)| id != field_id) {
+          let field_type =
+            get_field_type_from_cell::<FieldType>(cell).unwrap_or(FieldType::RichText);
+          field_types.push((field_id.clone(), field_type));
+        }
+      }
+    }
+
+    let origin = collab.origin().clone();
+    let mut txn = collab.context.transact_mut();
+    let root: MapRef = collab.data.get_or_init(&mut txn, DATABASE);
+    root.insert(&mut txn, DATABASE_ID, &*database_id);
+    let fields: MapRef = root.get_or_init(&mut txn, FIELDS);
+    let views: MapRef = root.get_or_init(&mut txn, VIEWS);
+    let metas: MapRef = root.get_or_init(&mut txn, DATABASE_METAS);
+
+    let fields = FieldMap::new(fields, Some(context.notifier.field_change_tx.clone()));
+    let views = DatabaseViews::new_with_txn(
+      origin,
+      views,
+      &txn,
+      Some(context.notifier.view_change_tx.clone()),
+    );
+
+    // Keep the field definitions that survived and reconstruct the missing ones
+    let first_field_id = field_types.first().map(|(field_id, _)| field_id.clone());
+    for (index, (field_id, field_type)) in field_types.into_iter().enumerate() {
+      if fields.get_field(&txn, &field_id).is_none() {
+        let field = Field::new(
+          field_id,
+          format!("Field {}", index + 1),
+          field_type.into(),
+          false,
+        )
+        .with_type_option_data(field_type, default_type_option_data_from_type(field_type));
+        fields.insert_field(&mut txn, field);
+      }
+    }
+
+    // A database requires a primary field. Promote the first field of the rows, or any field
+    // that survived if the rows have no cells.
+    if fields.get_primary_field(&txn).is_none() {
+      let primary_field_id =
+        first_field_id.or_else(|| fields.get_field_ids(&txn).into_iter().next());
+      if let Some(field_id) = primary_field_id {
+        fields.update_field(&mut txn, &field_id, |update| {
+          update.set_primary(true);
+        });
+      }
+    }
+
+    let database_id_uuid = Uuid::parse_str(&database_id)
+      .map_err(|_| DatabaseError::InvalidDatabaseID("database_id is not a valid UUID"))?;
+    let inline_view_id = database_inline_view_id(&database_id_uuid);
+    let mut inline_view = DatabaseView::new(
+      database_id,
+      inline_view_id.to_string(),
+      "".to_string(),
+      DatabaseLayout::Grid,
+    );
+    inline_view.is_inline = true;
+    inline_view.row_orders = rows.iter().map(RowOrder::from).collect();
+    inline_view.field_orders = fields.get_all_field_orders(&txn);
+    views.clear(&mut txn);
+    views.insert_view(&mut txn, inline_view);
+
     let metas = MetaMap::new(metas);
     metas.set_inline_view_id(&mut txn, &inline_view_id.to_string());
     drop(txn);
@@ -1747,9 +3365,10 @@ impl DatabaseBody {
     let metas: MapRef = root.get_with_txn(&txn, DATABASE_METAS)?; // { DATABASE: { FIELDS: {:},  VIEWS: {:}, METAS: {:} } }
 
     let fields = FieldMap::new(fields, notifier.as_ref().map(|n| n.field_change_tx.clone()));
-    let views = DatabaseViews::new(
+    let views = DatabaseViews::new_with_txn(
       origin,
       views,
+      &txn,
       notifier.as_ref().map(|n| n.view_change_tx.clone()),
     );
     let metas = MetaMap::new(metas);
@@ -1822,14 +3441,86 @@ impl DatabaseBody {
   }
 
   pub fn index_of_row<T: ReadTxn>(&self, txn: &T, view_id: &str, row_id: &RowId) -> Option<usize> {
-    let view = self.views.get_view(txn, view_id)?;
-    view.row_orders.iter().position(|order| &order.id == row_id)
+    self
+      .views
+      .get_row_index(txn, view_id, row_id)
+      .map(|index| index as usize)
   }
 
   pub fn get_inline_view_id<T: ReadTxn>(&self, txn: &T) -> String {
     self.try_get_inline_view_id(txn).unwrap()
   }
 
+  /// Remove the filters and sorts of the view that reference a field that is not in the
+  /// `field_ids`.
+  fn remove_stale_view_settings(
+    &self,
+    txn: &mut TransactionMut,
+    view_id: &str,
+    field_ids: &HashSet<String>,
+  ) {
+    let mut stale_filter_indexes = vec![];
+    let mut updated_filters = vec![];
+    for (index, filter) in self
+      .views
+      .get_view_filters(txn, view_id)
+      .into_iter()
+      .enumerate()
+    {
+      match retain_valid_filter(filter.clone(), field_ids) {
+        None => stale_filter_indexes.push(index as u32),
+        Some(valid_filter) if valid_filter != filter => {
+          updated_filters.push((index as u32, valid_filter))
+        },
+        Some(_) => {},
+      }
+    }
+    let stale_sort_indexes = self
+      .views
+      .get_view_sorts(txn, view_id)
+      .iter()
+      .enumerate()
+      .filter(|(_, sort)| !is_valid_sort(sort, field_ids))
+      .map(|(index, _)| index as u32)
+      .collect::<Vec<_>>();
+
+    if stale_filter_indexes.is_empty()
+      && updated_filters.is_empty()
+      && stale_sort_indexes.is_empty()
+    {
+      return;
+    }
+
+    let _ = self.views.update_database_view(txn, view_id, |update| {
+      update
+        .update_filters(|txn, filter_update| {
+          for (index, filter) in updated_filters {
+            if let Some(YrsValue::YMap(map_ref)) = filter_update.get(txn, index) {
+              Any::from(filter).fill(txn, &map_ref).unwrap();
+            }
+          }
+          // Remove from the back, so the indexes of the remaining filters don't shift
+          for index in stale_filter_indexes.into_iter().rev() {
+            filter_update.remove(txn, index);
+          }
+        })
+        .update_sorts(|txn, sort_update| {
+          for index in stale_sort_indexes.into_iter().rev() {
+            sort_update.remove(txn, index);
+          }
+        });
+    });
+  }
+
+  fn get_all_field_ids<T: ReadTxn>(&self, txn: &T) -> HashSet<String> {
+    self
+      .fields
+      .get_all_field_orders(txn)
+      .into_iter()
+      .map(|field_order| field_order.id)
+      .collect()
+  }
+
   pub fn try_get_inline_view_id<T: ReadTxn>(&self, txn: &T) -> Option<String> {
     // It's safe to unwrap because each database inline view id was set
     // when initializing the database
@@ -1918,6 +3609,30 @@ impl DatabaseBody {
       .collect()
   }
 
+  /// Returns the fields ordered by the [FieldOrder] of the view. Unlike [Self::get_fields_in_view],
+  /// the fields that don't have a field order are kept and appended at the end.
+  pub fn get_fields_ordered<T: ReadTxn>(
+    &self,
+    txn: &T,
+    view_id: &str,
+    field_ids: Option<Vec<String>>,
+  ) -> Vec<Field> {
+    let fields = self.fields.get_fields_with_txn(txn, field_ids);
+    let field_orders = self.views.get_field_orders(txn, view_id);
+    let index_by_id = field_orders
+      .iter()
+      .enumerate()
+      .map(|(index, order)| (order.id.as_str(), index))
+      .collect::<HashMap<&str, usize>>();
+
+    let (mut ordered, unordered): (Vec<Field>, Vec<Field>) = fields
+      .into_iter()
+      .partition(|field| index_by_id.contains_key(field.id.as_str()));
+    ordered.sort_by_key(|field| index_by_id[field.id.as_str()]);
+    ordered.extend(unordered);
+    ordered
+  }
+
   /// Create a new field that is used by `create_field`, `create_field_with_mut`, and
   /// `create_linked_view`. In all the database views, insert the field order and add a field setting.
   /// Then, add the field to the field map.
@@ -1985,6 +3700,7 @@ impl DatabaseBody {
       id: params.view_id,
       database_id,
       name: params.name,
+      description: params.description,
       layout: params.layout,
       layout_settings: params.layout_settings,
       filters: params.filters,
@@ -1992,9 +3708,13 @@ impl DatabaseBody {
       sorts: params.sorts,
       field_settings: params.field_settings,
       row_orders,
+      hidden_rows: vec![],
+      row_colors: HashMap::new(),
       field_orders,
       created_at: params.created_at,
       modified_at: params.modified_at,
+      created_by: params.created_by,
+      modified_by: params.created_by,
       is_inline: false,
     };
     // tracing::trace!("create linked view with params {:?}", params);
@@ -1,13 +1,79 @@
-use crate::fields::FieldChangeSender;
+use crate::blocks::BlockEvent;
+use crate::fields::{FieldChange, FieldChangeSender};
+use futures::stream::{self, BoxStream, StreamExt};
+use std::future::ready;
+use std::sync::OnceLock;
 use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::BroadcastStream;
 
-use crate::rows::RowChangeSender;
-use crate::views::ViewChangeSender;
+use crate::rows::{RowChange, RowChangeSender};
+use crate::views::{DatabaseViewChange, ViewChangeSender};
+
+pub type DatabaseChangeSender = broadcast::Sender<DatabaseChange>;
+pub type DatabaseChangeReceiver = broadcast::Receiver<DatabaseChange>;
+
+/// Combines the row, field, view and block events of a database into a single event.
+#[derive(Debug, Clone)]
+pub enum DatabaseChange {
+  Row(RowChange),
+  Field(FieldChange),
+  View(DatabaseViewChange),
+  Block(BlockEvent),
+}
 
 pub struct DatabaseNotify {
   pub view_change_tx: ViewChangeSender,
   pub row_change_tx: RowChangeSender,
   pub field_change_tx: FieldChangeSender,
+  pub database_change_tx: DatabaseChangeSender,
+  /// The task that forwards the row, field, view and block events to [Self::database_change_tx].
+  /// It's spawned on the first call of [Self::subscribe_all_changes] and aborted when the
+  /// [DatabaseNotify] is dropped.
+  forward_task: OnceLock<JoinHandle<()>>,
+}
+
+impl DatabaseNotify {
+  /// Subscribe to all the changes of the database. The `block_event_rx` is only used to start
+  /// the forwarding task, so it's ignored if the task is already running.
+  ///
+  /// The events are merged with [stream::select_all], so only the events from the same channel
+  /// are received in the order they were sent.
+  pub fn subscribe_all_changes(
+    &self,
+    block_event_rx: broadcast::Receiver<BlockEvent>,
+  ) -> DatabaseChangeReceiver {
+    // Subscribe before spawning the task, so the receiver doesn't miss any change.
+    let rx = self.database_change_tx.subscribe();
+    self.forward_task.get_or_init(|| {
+      let changes: Vec<BoxStream<'static, DatabaseChange>> = vec![
+        forward_stream(self.row_change_tx.subscribe(), DatabaseChange::Row),
+        forward_stream(self.field_change_tx.subscribe(), DatabaseChange::Field),
+        forward_stream(self.view_change_tx.subscribe(), DatabaseChange::View),
+        forward_stream(block_event_rx, DatabaseChange::Block),
+      ];
+      let database_change_tx = self.database_change_tx.clone();
+      tokio::spawn(async move {
+        let mut changes = stream::select_all(changes);
+        while let Some(change) = changes.next().await {
+          let _ = database_change_tx.send(change);
+        }
+      })
+    });
+    rx
+  }
+}
+
+/// Convert the broadcast receiver into a stream of [DatabaseChange]. The lagged events are
+/// skipped.
+fn forward_stream<T, F>(rx: broadcast::Receiver<T>, f: F) -> BoxStream<'static, DatabaseChange>
+where
+  T: Clone + Send + 'static,
+  F: Fn(T) -> DatabaseChange + Send + 'static,
+{
+  BroadcastStream::new(rx)
+    .filter_map(move |result| ready(result.ok().map(&f)))
+    .boxed()
 }
 
 impl Default for DatabaseNotify {
@@ -15,10 +81,21 @@ impl Default for DatabaseNotify {
     let (view_change_tx, _) = broadcast::channel(100);
     let (row_change_tx, _) = broadcast::channel(100);
     let (field_change_tx, _) = broadcast::channel(100);
+    let (database_change_tx, _) = broadcast::channel(100);
     Self {
       view_change_tx,
       row_change_tx,
       field_change_tx,
+      database_change_tx,
+      forward_task: OnceLock::new(),
+    }
+  }
+}
+
+impl Drop for DatabaseNotify {
+  fn drop(&mut self) {
+    if let Some(task) = self.forward_task.get() {
+      task.abort();
     }
   }
 }
@@ -14,7 +14,8 @@ use crate::fields::timestamp_type_option::TimestampTypeOption;
 use crate::fields::translate_type_option::TranslateTypeOption;
 use crate::fields::url_type_option::URLTypeOption;
 use crate::fields::{Field, TypeOptionData};
-use crate::rows::CreateRowParams;
+use crate::meta::DatabaseMetaData;
+use crate::rows::{CreateRowParams, RowId};
 use crate::views::{
   DatabaseLayout, FieldOrder, FieldSettingsByFieldIdMap, FieldSettingsMap, FilterMap,
   GroupSettingMap, LayoutSetting, LayoutSettings, OrderObjectPosition, RowOrder, SortMap,
@@ -32,6 +33,48 @@ use yrs::{Any, Out};
 pub struct EncodedDatabase {
   pub encoded_database_collab: EncodedCollabInfo,
   pub encoded_row_collabs: Vec<EncodedCollabInfo>,
+  /// The rows that couldn't be encoded
+  pub failed_rows: Vec<EncodeRowError>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EncodeRowError {
+  pub row_id: RowId,
+  pub reason: String,
+}
+
+/// The actions taken by [crate::database::Database::validate_and_repair]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepairReport {
+  pub actions: Vec<RepairAction>,
+}
+
+impl RepairReport {
+  pub fn is_empty(&self) -> bool {
+    self.actions.is_empty()
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepairAction {
+  /// The field exists but its field order was missing from the view
+  AddFieldOrder { view_id: String, field_id: String },
+  /// The field order points at a field that doesn't exist
+  RemoveFieldOrder { view_id: String, field_id: String },
+  /// The row order points at a row whose collab doesn't exist
+  RemoveRowOrder { view_id: String, row_id: RowId },
+}
+
+#[derive(Debug, Clone)]
+pub struct EncodeDatabaseOptions {
+  /// The maximum number of rows that are initialized and encoded at the same time
+  pub concurrency: usize,
+}
+
+impl Default for EncodeDatabaseOptions {
+  fn default() -> Self {
+    Self { concurrency: 20 }
+  }
 }
 
 impl EncodedDatabase {
@@ -53,16 +96,33 @@ pub struct DatabaseView {
   pub id: String,
   pub database_id: String,
   pub name: String,
+  /// The views created before the description was introduced have an empty description. An
+  /// empty description isn't serialized, so the serialized views stay the same as before.
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub description: String,
   pub layout: DatabaseLayout,
   pub layout_settings: LayoutSettings,
   pub filters: Vec<FilterMap>,
   pub group_settings: Vec<GroupSettingMap>,
   pub sorts: Vec<SortMap>,
   pub row_orders: Vec<RowOrder>,
+  /// The rows that are hidden in this view only. The rows are still kept in the [RowOrder]s,
+  /// so they keep their position when they are shown again.
+  #[serde(default)]
+  pub hidden_rows: Vec<RowId>,
+  /// The colors of the rows in this view, keyed by the row id.
+  #[serde(default)]
+  pub row_colors: HashMap<RowId, String>,
   pub field_orders: Vec<FieldOrder>,
   pub field_settings: FieldSettingsByFieldIdMap,
   pub created_at: i64,
   pub modified_at: i64,
+  /// The uid of the user who created the view
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created_by: Option<i64>,
+  /// The uid of the user who modified the view last
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub modified_by: Option<i64>,
   #[serde(default)]
   pub is_inline: bool,
 }
@@ -83,11 +143,15 @@ impl DatabaseView {
 }
 
 /// A meta of [DatabaseView]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct DatabaseViewMeta {
   pub id: String,
   pub name: String,
   pub is_inline: bool,
+  /// The layout of the view. The views without a layout are [DatabaseLayout::Grid].
+  pub layout: DatabaseLayout,
+  /// 0 if the view doesn't have a creation time.
+  pub created_at: i64,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -95,6 +159,8 @@ pub struct CreateViewParams {
   pub database_id: String,
   pub view_id: String,
   pub name: String,
+  #[serde(default)]
+  pub description: String,
   pub layout: DatabaseLayout,
   pub layout_settings: LayoutSettings,
   pub filters: Vec<FilterMap>,
@@ -103,6 +169,9 @@ pub struct CreateViewParams {
   pub field_settings: FieldSettingsByFieldIdMap,
   pub created_at: i64,
   pub modified_at: i64,
+  /// The uid of the user who creates the view
+  #[serde(default)]
+  pub created_by: Option<i64>,
 
   /// When creating a view for a database, it might need to create a new field for the view.
   /// For example, if the view is calendar view, it must have a date field.
@@ -172,6 +241,7 @@ impl From<DatabaseView> for CreateViewParams {
       database_id: view.database_id,
       view_id: view.id,
       name: view.name,
+      description: view.description,
       layout: view.layout,
       filters: view.filters,
       layout_settings: view.layout_settings,
@@ -205,6 +275,8 @@ pub struct CreateDatabaseParams {
   pub fields: Vec<Field>,
   pub rows: Vec<CreateRowParams>,
   pub views: Vec<CreateViewParams>,
+  #[serde(default)]
+  pub meta: DatabaseMetaData,
 }
 
 impl CreateDatabaseParams {
@@ -232,6 +304,8 @@ impl CreateDatabaseParams {
         height: row.height,
         visibility: row.visibility,
         row_position: OrderObjectPosition::End,
+        created_by: None,
+        modified_by: None,
       })
       .collect();
 
@@ -263,6 +337,11 @@ impl CreateDatabaseParams {
       rows: create_row_params,
       fields: data.fields,
       views: create_view_params,
+      meta: DatabaseMetaData {
+        name: data.meta.name,
+        icon: data.meta.icon,
+        created_at: None,
+      },
     }
   }
 }
@@ -36,9 +36,18 @@ pub enum DatabaseError {
   #[error("Record not found")]
   RecordNotFound,
 
+  #[error("Duplicate database meta: {0:?}")]
+  DuplicateDatabaseMeta(Vec<String>),
+
+  #[error("The primary field can't be deleted")]
+  CannotDeletePrimaryField,
+
   #[error("Action cancelled")]
   ActionCancelled,
 
+  #[error("Invalid layout setting: {0}")]
+  InvalidLayoutSetting(String),
+
   #[error("Invalid CSV:{0}")]
   InvalidCSV(String),
 
@@ -53,6 +62,21 @@ impl DatabaseError {
   pub fn is_no_required_data(&self) -> bool {
     matches!(self, DatabaseError::NoRequiredData(_))
   }
+
+  /// The row or the record doesn't exist, trying again won't find it.
+  pub fn is_not_found(&self) -> bool {
+    matches!(
+      self,
+      DatabaseError::NoRequiredData(_)
+        | DatabaseError::DatabaseRowNotFound { .. }
+        | DatabaseError::RecordNotFound
+    )
+  }
+
+  /// The failure of the remote or the storage, which may succeed if it's tried again.
+  pub fn is_retryable(&self) -> bool {
+    matches!(self, DatabaseError::Internal(_))
+  }
 }
 
 impl From<CollabValidateError> for DatabaseError {
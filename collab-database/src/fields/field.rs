@@ -2,10 +2,12 @@ use serde::{Deserialize, Serialize};
 
 use collab::preclude::{Any, Map, MapExt, MapRef, ReadTxn, TransactionMut, YrsValue};
 
-use crate::database::gen_field_id;
+use crate::database::{gen_field_id, gen_option_id};
 use crate::entity::{default_type_option_data_from_type, FieldType};
+use crate::fields::select_type_option::{SelectOption, SelectOptionColor, SelectTypeOption};
 use crate::fields::{TypeOptionData, TypeOptions, TypeOptionsUpdate};
 use crate::{impl_bool_update, impl_i64_update, impl_str_update};
+use tracing::warn;
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub struct Field {
@@ -58,6 +60,18 @@ impl Field {
   pub fn get_any_type_option(&self, type_id: impl ToString) -> Option<TypeOptionData> {
     self.type_options.get(&type_id.to_string()).cloned()
   }
+
+  /// Returns the options of a single select or multi select field. Returns an empty list for the
+  /// other field types.
+  pub fn get_select_options(&self) -> Vec<SelectOption> {
+    match FieldType::from(self.field_type) {
+      field_type @ (FieldType::SingleSelect | FieldType::MultiSelect) => self
+        .get_type_option::<SelectTypeOption>(field_type.type_id())
+        .map(|type_option| type_option.options)
+        .unwrap_or_default(),
+      _ => vec![],
+    }
+  }
 }
 
 const DEFAULT_ICON_VALUE: fn() -> String = || "".to_string();
@@ -139,6 +153,54 @@ impl<'a, 'b, 'c> FieldUpdate<'a, 'b, 'c> {
     self
   }
 
+  /// Appends a new option with the given name and color to the options of a single select or
+  /// multi select field.
+  pub fn insert_select_option(self, name: &str, color: SelectOptionColor) -> Self {
+    self.update_select_type_option(|type_option| {
+      type_option.options.push(SelectOption {
+        id: gen_option_id(),
+        name: name.to_string(),
+        color,
+      });
+    })
+  }
+
+  /// Removes the option with the given id from the options of a single select or multi select
+  /// field.
+  pub fn delete_select_option(self, option_id: &str) -> Self {
+    self.update_select_type_option(|type_option| {
+      type_option.options.retain(|option| option.id != option_id);
+    })
+  }
+
+  fn update_select_type_option(self, f: impl FnOnce(&mut SelectTypeOption)) -> Self {
+    let field_type = match self
+      .map_ref
+      .get_with_txn::<_, i64>(self.txn, FIELD_TYPE)
+      .map(FieldType::from)
+    {
+      Some(field_type @ (FieldType::SingleSelect | FieldType::MultiSelect)) => field_type,
+      field_type => {
+        warn!(
+          "Can't update the select options of the field: {}, field type: {:?}",
+          self.id, field_type
+        );
+        return self;
+      },
+    };
+
+    let type_id = field_type.type_id();
+    let map_ref: MapRef = self.map_ref.get_or_init(self.txn, FIELD_TYPE_OPTION);
+    let mut type_option = TypeOptions::from_map_ref(self.txn, map_ref.clone())
+      .get(&type_id)
+      .cloned()
+      .map(SelectTypeOption::from)
+      .unwrap_or_default();
+    f(&mut type_option);
+    TypeOptionsUpdate::new(self.txn, &map_ref).update(&type_id, type_option);
+    self
+  }
+
   pub fn done(self) -> Option<Field> {
     field_from_map_ref(self.map_ref, self.txn)
   }
@@ -159,6 +221,20 @@ pub fn field_id_from_value<T: ReadTxn>(value: YrsValue, txn: &T) -> Option<Strin
   map_ref.get(txn, FIELD_ID).and_then(|v| v.cast().ok())
 }
 
+/// Get the field id and name from a value without reading the type options of the field
+pub fn field_id_and_name_from_value<T: ReadTxn>(
+  value: YrsValue,
+  txn: &T,
+) -> Option<(String, String)> {
+  let map_ref: MapRef = value.cast().ok()?;
+  let id: String = map_ref.get(txn, FIELD_ID)?.cast().ok()?;
+  let name: String = map_ref
+    .get(txn, FIELD_NAME)
+    .and_then(|v| v.cast().ok())
+    .unwrap_or_default();
+  Some((id, name))
+}
+
 /// Get primary field id from a value
 pub fn primary_field_id_from_value<T: ReadTxn>(value: YrsValue, txn: &T) -> Option<String> {
   let map_ref: MapRef = value.cast().ok()?;
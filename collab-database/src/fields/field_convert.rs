@@ -0,0 +1,61 @@
+use collab::util::AnyMapExt;
+use std::collections::HashSet;
+
+use crate::entity::FieldType;
+use crate::fields::select_type_option::{SelectOption, SelectOptionIds};
+use crate::rows::Cell;
+use crate::template::entity::CELL_DATA;
+use crate::template::number_parse::NumberCellData;
+use crate::template::option_parse::{build_options_from_cells, SELECT_OPTION_SEPARATOR};
+
+/// Converts a text cell into a number cell. Returns None if the text is not a number, so the
+/// cell is kept as it is.
+pub fn text_to_number_cell(cell: &Cell) -> Option<Cell> {
+  let text = cell.get_as::<String>(CELL_DATA)?;
+  let text = text.trim();
+  text.parse::<f64>().ok()?;
+  Some(NumberCellData(text.to_string()).into())
+}
+
+/// Returns an option for each comma separated name in the text cells that doesn't have an option
+/// in `options` yet.
+pub fn select_options_from_text_cells(
+  cells: &[Cell],
+  options: &[SelectOption],
+) -> Vec<SelectOption> {
+  let texts = cells
+    .iter()
+    .flat_map(|cell| cell.get_as::<String>(CELL_DATA))
+    .collect::<Vec<_>>();
+  build_options_from_cells(&texts)
+    .into_iter()
+    .filter(|new_option| !options.iter().any(|option| option.name == new_option.name))
+    .collect()
+}
+
+/// Converts a text cell into a select cell that contains the ids of the options whose names are
+/// in the text. Only the first option is kept for a [FieldType::SingleSelect] field. Returns
+/// None if none of the names has an option, so the cell is kept as it is.
+pub fn text_to_select_cell(
+  cell: &Cell,
+  options: &[SelectOption],
+  field_type: FieldType,
+) -> Option<Cell> {
+  let text = cell.get_as::<String>(CELL_DATA)?;
+  let mut seen = HashSet::new();
+  let mut option_ids = text
+    .split(SELECT_OPTION_SEPARATOR)
+    .flat_map(|name| options.iter().find(|option| option.name == name.trim()))
+    .filter(|option| seen.insert(option.id.clone()))
+    .map(|option| option.id.clone())
+    .collect::<Vec<_>>();
+  if field_type == FieldType::SingleSelect {
+    option_ids.truncate(1);
+  }
+
+  if option_ids.is_empty() {
+    None
+  } else {
+    Some(SelectOptionIds::from(option_ids).to_cell(field_type))
+  }
+}
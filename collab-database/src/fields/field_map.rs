@@ -2,8 +2,9 @@ use collab::preclude::{Map, MapExt, MapRef, ReadTxn, Subscription, TransactionMu
 
 use crate::database::timestamp;
 use crate::fields::{
-  field_from_map_ref, field_from_value, field_id_from_value, primary_field_id_from_value,
-  subscribe_field_change, Field, FieldBuilder, FieldChangeSender, FieldUpdate,
+  field_from_map_ref, field_from_value, field_id_and_name_from_value, field_id_from_value,
+  primary_field_id_from_value, subscribe_field_change, Field, FieldBuilder, FieldChangeSender,
+  FieldUpdate,
 };
 use crate::views::FieldOrder;
 
@@ -94,6 +95,25 @@ impl FieldMap {
       .collect::<Vec<String>>()
   }
 
+  /// Returns the ids of all fields. Only the id of each field is read, so it's cheaper than
+  /// [FieldMap::get_all_fields] which deserializes the type options.
+  pub fn get_field_ids<T: ReadTxn>(&self, txn: &T) -> Vec<String> {
+    self
+      .container
+      .iter(txn)
+      .flat_map(|(_k, v)| field_id_from_value(v, txn))
+      .collect()
+  }
+
+  /// Returns the id and name of all fields without reading the type options
+  pub fn get_field_names<T: ReadTxn>(&self, txn: &T) -> Vec<(String, String)> {
+    self
+      .container
+      .iter(txn)
+      .flat_map(|(_k, v)| field_id_and_name_from_value(v, txn))
+      .collect()
+  }
+
   /// Get all field orders with a transaction
   pub fn get_all_field_orders<T: ReadTxn>(&self, txn: &T) -> Vec<FieldOrder> {
     self
@@ -15,7 +15,7 @@ use serde_json::{json, Value};
 use std::ops::{Deref, DerefMut};
 use std::str::FromStr;
 
-#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct SelectTypeOption {
   pub options: Vec<SelectOption>,
   #[serde(default)]
@@ -94,7 +94,7 @@ impl From<SelectTypeOption> for TypeOptionData {
   }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SelectOption {
   pub id: String,
   pub name: String,
@@ -469,6 +469,23 @@ mod tests {
     assert_eq!(select_type_option.options[1].color, SelectOptionColor::Blue);
   }
 
+  #[test]
+  fn select_type_option_type_option_data_round_trip_test() {
+    let select_type_option = SelectTypeOption {
+      options: vec![
+        SelectOption::new("Option 1"),
+        SelectOption::with_color("Option 2", SelectOptionColor::Purple),
+      ],
+      disable_color: true,
+    };
+    let data = TypeOptionData::from(select_type_option.clone());
+    assert_eq!(SelectTypeOption::from(data), select_type_option);
+
+    // The malformed content falls back to the default type option
+    let data = TypeOptionDataBuilder::from([("content".into(), "{".into())]);
+    assert_eq!(SelectTypeOption::from(data), SelectTypeOption::default());
+  }
+
   #[test]
   fn test_select_option_ids_to_string() {
     let ids = SelectOptionIds::from(vec!["id1".to_string(), "id2".to_string()]);
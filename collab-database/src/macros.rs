@@ -181,16 +181,7 @@ macro_rules! impl_order_update {
         .get_with_txn::<_, ArrayRef>(self.txn, $key)
         .map(|array_ref| $array_ty::new(array_ref))
       {
-        match position {
-          OrderObjectPosition::Start => array.push_front_with_txn(self.txn, object),
-          OrderObjectPosition::Before(next_object_id) => {
-            array.insert_before_with_txn(self.txn, object, &next_object_id)
-          },
-          OrderObjectPosition::After(prev_object_id) => {
-            array.insert_after_with_txn(self.txn, object, &prev_object_id)
-          },
-          OrderObjectPosition::End => array.push_back_with_txn(self.txn, object),
-        };
+        array.insert_with_txn(self.txn, object, position);
       }
       self
     }
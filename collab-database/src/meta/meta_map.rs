@@ -1,8 +1,24 @@
-use collab::preclude::{Any, Map, MapRef, ReadTxn, TransactionMut};
+use collab::preclude::{Any, Map, MapExt, MapRef, ReadTxn, TransactionMut};
 use collab_entity::define::DATABASE_INLINE_VIEW;
+use serde::{Deserialize, Serialize};
 use std::ops::Deref;
 use tracing::error;
 
+use crate::{impl_i64_update, impl_str_update};
+
+const DATABASE_NAME: &str = "name";
+const DATABASE_ICON: &str = "icon";
+const DATABASE_CREATED_AT: &str = "created_at";
+
+/// The metadata of the database itself.
+/// Databases created before the metadata was introduced don't have these values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseMetaData {
+  pub name: Option<String>,
+  pub icon: Option<String>,
+  pub created_at: Option<i64>,
+}
+
 pub struct MetaMap {
   container: MapRef,
 }
@@ -34,6 +50,70 @@ impl MetaMap {
       },
     }
   }
+
+  /// Get the name of the database
+  pub fn get_name<T: ReadTxn>(&self, txn: &T) -> Option<String> {
+    self.container.get_with_txn(txn, DATABASE_NAME)
+  }
+
+  /// Get the icon of the database
+  pub fn get_icon<T: ReadTxn>(&self, txn: &T) -> Option<String> {
+    self.container.get_with_txn(txn, DATABASE_ICON)
+  }
+
+  /// Get the creation time of the database
+  pub fn get_created_at<T: ReadTxn>(&self, txn: &T) -> Option<i64> {
+    self.container.get_with_txn(txn, DATABASE_CREATED_AT)
+  }
+
+  pub fn get_database_meta<T: ReadTxn>(&self, txn: &T) -> DatabaseMetaData {
+    DatabaseMetaData {
+      name: self.get_name(txn),
+      icon: self.get_icon(txn),
+      created_at: self.get_created_at(txn),
+    }
+  }
+
+  pub fn update_database_meta<F>(&self, txn: &mut TransactionMut, f: F)
+  where
+    F: FnOnce(DatabaseMetaUpdate),
+  {
+    let update = DatabaseMetaUpdate::new(txn, &self.container);
+    f(update);
+  }
+}
+
+pub struct DatabaseMetaUpdate<'a, 'b> {
+  map_ref: &'a MapRef,
+  txn: &'a mut TransactionMut<'b>,
+}
+
+impl<'a, 'b> DatabaseMetaUpdate<'a, 'b> {
+  pub fn new(txn: &'a mut TransactionMut<'b>, map_ref: &'a MapRef) -> Self {
+    Self { map_ref, txn }
+  }
+
+  impl_str_update!(set_name, set_name_if_not_none, DATABASE_NAME);
+  impl_str_update!(set_icon, set_icon_if_not_none, DATABASE_ICON);
+  impl_i64_update!(
+    set_created_at,
+    set_created_at_if_not_none,
+    DATABASE_CREATED_AT
+  );
+
+  /// Remove the icon of the database
+  pub fn remove_icon(self) -> Self {
+    self.map_ref.remove(self.txn, DATABASE_ICON);
+    self
+  }
+
+  /// Set the values of the given [DatabaseMetaData]. The `None` values are ignored.
+  pub fn set_database_meta(self, meta: DatabaseMetaData) -> Self {
+    self
+      .set_name_if_not_none(meta.name)
+      .set_icon_if_not_none(meta.icon)
+      .set_created_at_if_not_none(meta.created_at)
+  }
 }
 
 impl Deref for MetaMap {
@@ -6,6 +6,7 @@ use collab::util::AnyMapExt;
 
 use crate::database::timestamp;
 use crate::rows::{RowId, CREATED_AT, LAST_MODIFIED};
+use crate::template::check_list_parse::ChecklistCellData;
 use crate::template::entity::CELL_DATA;
 
 pub type Cells = HashMap<String, Cell>;
@@ -38,12 +39,25 @@ impl<'a, 'b> CellsUpdate<'a, 'b> {
     self.insert_cell(key, cell)
   }
 
+  /// Inserts the checklist as the cell of the given key. See [ChecklistCellData::from] to read
+  /// it back from the [Cell].
+  pub fn insert_checklist(self, key: &str, data: ChecklistCellData) -> Self {
+    self.insert_cell(key, data.into())
+  }
+
   pub fn clear(self, key: &str) -> Self {
     let cell_map_ref: MapRef = self.map_ref.get_or_init(self.txn, key);
     cell_map_ref.clear(self.txn);
 
     self
   }
+
+  /// Removes the cell from the row. Unlike [CellsUpdate::clear], which keeps an empty cell
+  /// around, the key no longer exists in the cells after the removal.
+  pub fn remove(self, key: &str) -> Self {
+    self.map_ref.remove(self.txn, key);
+    self
+  }
 }
 
 pub type Cell = HashMap<String, Any>;
@@ -0,0 +1,203 @@
+use collab::preclude::Any;
+
+use crate::fields::select_type_option::SELECTION_IDS_SEPARATOR;
+use crate::rows::{get_field_type_from_cell, Cell, RowCell};
+use crate::template::entity::CELL_DATA;
+
+/// Typed accessors of the `data` of a [Cell]. The data of a cell might be written as a string
+/// or as a number depending on the client, so the accessors convert between the encodings.
+/// `None` is returned if the cell doesn't have data or the data can't be converted.
+pub trait CellExt {
+  /// Returns the data of the cell
+  fn data(&self) -> Option<&Any>;
+
+  /// Returns the data as text. Numbers and booleans are converted to their string form.
+  fn as_text(&self) -> Option<String> {
+    match self.data()? {
+      Any::String(s) => Some(s.to_string()),
+      Any::BigInt(value) => Some(value.to_string()),
+      Any::Number(value) => Some(value.to_string()),
+      Any::Bool(value) => Some(value.to_string()),
+      _ => None,
+    }
+  }
+
+  /// Returns the data as an integer. A float is only converted if it doesn't have a fraction.
+  fn as_i64(&self) -> Option<i64> {
+    match self.data()? {
+      Any::BigInt(value) => Some(*value),
+      Any::Number(value) if value.fract() == 0.0 => Some(*value as i64),
+      Any::String(s) => {
+        let s = s.trim();
+        s.parse::<i64>().ok().or_else(|| {
+          let value = s.parse::<f64>().ok()?;
+          (value.fract() == 0.0).then_some(value as i64)
+        })
+      },
+      _ => None,
+    }
+  }
+
+  fn as_f64(&self) -> Option<f64> {
+    match self.data()? {
+      Any::Number(value) => Some(*value),
+      Any::BigInt(value) => Some(*value as f64),
+      Any::String(s) => s.trim().parse::<f64>().ok(),
+      _ => None,
+    }
+  }
+
+  /// Returns the data as a boolean. The checkbox cells store "true"/"false", but "yes"/"no"
+  /// and 1/0 are accepted as well.
+  fn as_bool(&self) -> Option<bool> {
+    match self.data()? {
+      Any::Bool(value) => Some(*value),
+      Any::BigInt(1) => Some(true),
+      Any::BigInt(0) => Some(false),
+      Any::String(s) => match s.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" => Some(true),
+        "0" | "false" | "no" => Some(false),
+        _ => None,
+      },
+      _ => None,
+    }
+  }
+
+  /// Returns the data as a list of strings. The select option cells store the ids of the
+  /// options as a comma separated string.
+  fn as_string_list(&self) -> Option<Vec<String>> {
+    match self.data()? {
+      Any::String(s) if s.is_empty() => Some(vec![]),
+      Any::String(s) => Some(
+        s.split(SELECTION_IDS_SEPARATOR)
+          .map(|s| s.trim().to_string())
+          .filter(|s| !s.is_empty())
+          .collect(),
+      ),
+      Any::Array(values) => values
+        .iter()
+        .map(|value| match value {
+          Any::String(s) => Some(s.to_string()),
+          _ => None,
+        })
+        .collect(),
+      _ => None,
+    }
+  }
+
+  fn field_type<T: From<i64>>(&self) -> Option<T>;
+}
+
+impl CellExt for Cell {
+  fn data(&self) -> Option<&Any> {
+    self.get(CELL_DATA)
+  }
+
+  fn field_type<T: From<i64>>(&self) -> Option<T> {
+    get_field_type_from_cell(self)
+  }
+}
+
+impl CellExt for RowCell {
+  fn data(&self) -> Option<&Any> {
+    self.cell.as_ref()?.data()
+  }
+
+  fn field_type<T: From<i64>>(&self) -> Option<T> {
+    get_field_type_from_cell(self.cell.as_ref()?)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::entity::FieldType;
+  use crate::rows::{new_cell_builder, CellsUpdate, RowId};
+  use collab::core::origin::CollabOrigin;
+  use collab::preclude::{Collab, Map, MapRef, ToJson};
+  use collab::util::AnyExt;
+
+  fn write_and_read_cell(cell: Cell) -> Cell {
+    let mut collab = Collab::new_with_origin(CollabOrigin::Empty, "1", vec![], false);
+    let mut txn = collab.context.transact_mut();
+    let cells: MapRef = collab.data.get_or_init(&mut txn, "cells");
+    CellsUpdate::new(&mut txn, &cells).insert("f1", cell);
+    cells
+      .get(&txn, "f1")
+      .unwrap()
+      .to_json(&txn)
+      .into_map()
+      .unwrap()
+  }
+
+  fn cell_with_data(data: impl Into<Any>) -> Cell {
+    let mut cell = new_cell_builder(FieldType::RichText);
+    cell.insert(CELL_DATA.into(), data.into());
+    write_and_read_cell(cell)
+  }
+
+  #[test]
+  fn cell_text_test() {
+    let cell = cell_with_data("hello");
+    assert_eq!(cell.as_text().unwrap(), "hello");
+    assert_eq!(cell.field_type::<FieldType>(), Some(FieldType::RichText));
+    assert_eq!(cell_with_data(Any::BigInt(12)).as_text().unwrap(), "12");
+    assert_eq!(cell_with_data(true).as_text().unwrap(), "true");
+  }
+
+  #[test]
+  fn cell_number_test() {
+    assert_eq!(cell_with_data(Any::BigInt(42)).as_i64(), Some(42));
+    assert_eq!(cell_with_data(Any::BigInt(42)).as_f64(), Some(42.0));
+    assert_eq!(cell_with_data(Any::Number(1.5)).as_f64(), Some(1.5));
+    assert_eq!(cell_with_data(Any::Number(1.5)).as_i64(), None);
+    assert_eq!(cell_with_data(Any::Number(2.0)).as_i64(), Some(2));
+    assert_eq!(cell_with_data(" 42 ").as_i64(), Some(42));
+    assert_eq!(cell_with_data("3.0").as_i64(), Some(3));
+    assert_eq!(cell_with_data("3.25").as_f64(), Some(3.25));
+    assert_eq!(cell_with_data("abc").as_i64(), None);
+    assert_eq!(cell_with_data("abc").as_f64(), None);
+  }
+
+  #[test]
+  fn cell_bool_test() {
+    assert_eq!(cell_with_data(true).as_bool(), Some(true));
+    assert_eq!(cell_with_data("true").as_bool(), Some(true));
+    assert_eq!(cell_with_data("Yes").as_bool(), Some(true));
+    assert_eq!(cell_with_data("false").as_bool(), Some(false));
+    assert_eq!(cell_with_data(Any::BigInt(0)).as_bool(), Some(false));
+    assert_eq!(cell_with_data("maybe").as_bool(), None);
+  }
+
+  #[test]
+  fn cell_string_list_test() {
+    assert_eq!(
+      cell_with_data("a,b, c").as_string_list().unwrap(),
+      vec!["a".to_string(), "b".to_string(), "c".to_string()]
+    );
+    assert!(cell_with_data("").as_string_list().unwrap().is_empty());
+    assert_eq!(cell_with_data(Any::BigInt(1)).as_string_list(), None);
+  }
+
+  #[test]
+  fn malformed_cell_test() {
+    let cell = write_and_read_cell(new_cell_builder(FieldType::Number));
+    assert_eq!(cell.as_text(), None);
+    assert_eq!(cell.as_i64(), None);
+    assert_eq!(cell.as_f64(), None);
+    assert_eq!(cell.as_bool(), None);
+    assert_eq!(cell.as_string_list(), None);
+
+    let cell = Cell::new();
+    assert_eq!(cell.field_type::<FieldType>(), None);
+
+    let row_cell = RowCell::new(RowId::from("1".to_string()), None);
+    assert_eq!(row_cell.as_text(), None);
+    let row_cell = RowCell::new(RowId::from("1".to_string()), Some(cell_with_data("7")));
+    assert_eq!(row_cell.as_i64(), Some(7));
+    assert_eq!(
+      row_cell.field_type::<FieldType>(),
+      Some(FieldType::RichText)
+    );
+  }
+}
@@ -1,13 +1,35 @@
-use collab::preclude::Any;
-use collab::util::deserialize_i64_from_numeric;
+use collab::preclude::{
+  Any, Array, ArrayRef, FillRef, Map, MapPrelim, MapRef, ReadTxn, ToJson, TransactionMut,
+};
+use collab::util::{deserialize_i64_from_numeric, ArrayExt};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::database::timestamp;
+
+pub const COMMENT_CONTENT: &str = "content";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RowComment {
-  uid: i64,
-  content: String,
+  /// The comments added before the id was introduced have an empty id, so they can't be
+  /// updated or deleted by id.
+  #[serde(default)]
+  pub id: String,
+  pub uid: i64,
+  pub content: String,
   #[serde(deserialize_with = "deserialize_i64_from_numeric")]
-  created_at: i64,
+  pub created_at: i64,
+}
+
+impl RowComment {
+  pub fn new(uid: i64, content: String) -> Self {
+    Self {
+      id: Uuid::new_v4().to_string(),
+      uid,
+      content,
+      created_at: timestamp(),
+    }
+  }
 }
 
 impl TryFrom<Any> for RowComment {
@@ -27,3 +49,54 @@ impl From<RowComment> for Any {
     Any::from_json(&json).unwrap()
   }
 }
+
+/// Appends the comment to the end of the comments. Each comment is stored as a map, so its
+/// content can be updated without replacing the comment.
+pub(crate) fn push_comment(txn: &mut TransactionMut, comments: &ArrayRef, comment: RowComment) {
+  let map_ref = comments.push_back(txn, MapPrelim::default());
+  Any::from(comment).fill(txn, &map_ref).unwrap();
+}
+
+/// Updates the content of the comment with the given id. Returns false if the comment doesn't
+/// exist.
+pub(crate) fn update_comment_content(
+  txn: &mut TransactionMut,
+  comments: &ArrayRef,
+  comment_id: &str,
+  content: &str,
+) -> bool {
+  match comments
+    .index_by_id(txn, comment_id)
+    .and_then(|index| comments.get(txn, index))
+    .and_then(|value| value.cast::<MapRef>().ok())
+  {
+    None => false,
+    Some(map_ref) => {
+      map_ref.try_update(txn, COMMENT_CONTENT, content);
+      true
+    },
+  }
+}
+
+/// Removes the comment with the given id. Returns false if the comment doesn't exist.
+pub(crate) fn remove_comment(
+  txn: &mut TransactionMut,
+  comments: &ArrayRef,
+  comment_id: &str,
+) -> bool {
+  match comments.index_by_id(txn, comment_id) {
+    None => false,
+    Some(index) => {
+      comments.remove(txn, index);
+      true
+    },
+  }
+}
+
+/// Returns the comments in the order they were added
+pub fn comments_from_array_ref<T: ReadTxn>(txn: &T, comments: &ArrayRef) -> Vec<RowComment> {
+  comments
+    .iter(txn)
+    .flat_map(|value| RowComment::try_from(value.to_json(txn)).ok())
+    .collect()
+}
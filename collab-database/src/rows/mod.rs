@@ -1,10 +1,12 @@
 pub use cell::*;
+pub use cell_ext::*;
 pub use comment::*;
 pub use row::*;
 pub use row_id::*;
 pub use row_meta::*;
 pub use row_observer::*;
 mod cell;
+mod cell_ext;
 mod comment;
 mod row;
 mod row_id;
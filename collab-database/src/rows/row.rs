@@ -4,6 +4,7 @@ use collab::preclude::{
 use std::borrow::{Borrow, BorrowMut};
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
@@ -17,8 +18,9 @@ use crate::database::timestamp;
 
 use crate::error::DatabaseError;
 use crate::rows::{
-  subscribe_row_data_change, Cell, Cells, CellsUpdate, RowChangeSender, RowId, RowMeta,
-  RowMetaUpdate,
+  comments_from_array_ref, push_comment, remove_comment, subscribe_row_comment_change,
+  subscribe_row_data_change, subscribe_row_dirty_change, update_comment_content, Cell, Cells,
+  CellsUpdate, RowChangeSender, RowComment, RowId, RowMeta, RowMetaUpdate,
 };
 
 use crate::util::encoded_collab;
@@ -43,6 +45,11 @@ pub struct DatabaseRow {
   pub collab: Collab,
   pub body: DatabaseRowBody,
   collab_service: Arc<dyn DatabaseCollabService>,
+  /// Set when the row has changed since it was last written to disk
+  dirty: Arc<AtomicBool>,
+  /// The uid of the user that edits the row. It's stamped as the last modifier of the row when
+  /// [DatabaseRow::update] or [DatabaseRow::update_cell] changes the row.
+  uid: Option<i64>,
 }
 
 pub fn default_database_row_data(row_id: &RowId, row: Row) -> EncodedCollab {
@@ -65,17 +72,19 @@ impl DatabaseRow {
     row_id: RowId,
     mut collab: Collab,
     change_tx: Option<RowChangeSender>,
+    uid: Option<i64>,
     collab_service: Arc<dyn DatabaseCollabService>,
   ) -> Result<Self, DatabaseError> {
     let body = DatabaseRowBody::open(row_id.clone(), &mut collab)?;
-    if let Some(change_tx) = change_tx {
-      subscribe_row_data_change(row_id.clone(), &body.data, change_tx);
-    }
+    let dirty = Arc::new(AtomicBool::new(false));
+    body.subscribe_change(change_tx, dirty.clone());
     Ok(Self {
       row_id,
       collab,
       body,
       collab_service,
+      dirty,
+      uid,
     })
   }
 
@@ -84,17 +93,20 @@ impl DatabaseRow {
     mut collab: Collab,
     change_tx: Option<RowChangeSender>,
     row: Row,
+    uid: Option<i64>,
     collab_service: Arc<dyn DatabaseCollabService>,
   ) -> Self {
     let body = DatabaseRowBody::create(row_id.clone(), &mut collab, row);
-    if let Some(change_tx) = change_tx {
-      subscribe_row_data_change(row_id.clone(), &body.data, change_tx);
-    }
+    // A new row is dirty until it's written to disk
+    let dirty = Arc::new(AtomicBool::new(true));
+    body.subscribe_change(change_tx, dirty.clone());
     Self {
       row_id,
       collab,
       body,
       collab_service,
+      dirty,
+      uid,
     }
   }
 
@@ -113,11 +125,26 @@ impl DatabaseRow {
         })
         .map_err(DatabaseError::Internal)?;
       persistence.flush_collabs(vec![(self.collab.object_id().to_string(), encoded_collab)])?;
+      self.dirty.store(false, Ordering::Release);
     }
 
     Ok(())
   }
 
+  /// Returns true if the row has changed since it was last written to disk
+  pub fn is_dirty(&self) -> bool {
+    self.dirty.load(Ordering::Acquire)
+  }
+
+  /// Clears the dirty flag and returns its previous value
+  pub(crate) fn take_dirty(&self) -> bool {
+    self.dirty.swap(false, Ordering::AcqRel)
+  }
+
+  pub(crate) fn mark_dirty(&self) {
+    self.dirty.store(true, Ordering::Release);
+  }
+
   pub fn validate(&self) -> Result<(), DatabaseError> {
     CollabType::DatabaseRow.validate_require_data(&self.collab)?;
     Ok(())
@@ -152,6 +179,42 @@ impl DatabaseRow {
     cell_from_map_ref(&self.body.data, &txn, field_id)
   }
 
+  /// Returns the comments of the row in the order they were added
+  pub fn get_comments(&self) -> Vec<RowComment> {
+    let txn = self.collab.transact();
+    comments_from_array_ref(&txn, &self.body.comments)
+  }
+
+  /// Appends the comment to the end of the comments of the row
+  pub fn add_comment(&mut self, comment: RowComment) {
+    let comments = self.body.comments.clone();
+    let mut txn = self.collab.transact_mut();
+    push_comment(&mut txn, &comments, comment);
+  }
+
+  /// Updates the content of the comment. Returns [DatabaseError::RecordNotFound] if the comment
+  /// doesn't exist.
+  pub fn update_comment(&mut self, comment_id: &str, content: &str) -> Result<(), DatabaseError> {
+    let comments = self.body.comments.clone();
+    let mut txn = self.collab.transact_mut();
+    if update_comment_content(&mut txn, &comments, comment_id, content) {
+      Ok(())
+    } else {
+      Err(DatabaseError::RecordNotFound)
+    }
+  }
+
+  /// Deletes the comment. Returns [DatabaseError::RecordNotFound] if the comment doesn't exist.
+  pub fn delete_comment(&mut self, comment_id: &str) -> Result<(), DatabaseError> {
+    let comments = self.body.comments.clone();
+    let mut txn = self.collab.transact_mut();
+    if remove_comment(&mut txn, &comments, comment_id) {
+      Ok(())
+    } else {
+      Err(DatabaseError::RecordNotFound)
+    }
+  }
+
   pub fn update<F>(&mut self, f: F)
   where
     F: FnOnce(RowUpdate),
@@ -159,8 +222,15 @@ impl DatabaseRow {
     let data = self.body.data.clone();
     let meta = self.body.meta.clone();
     let mut txn = self.collab.transact_mut();
-    let update = RowUpdate::new(&mut txn, data.clone(), meta);
+    let before = (data.to_json(&txn), meta.to_json(&txn));
+    let update = RowUpdate::new(&mut txn, data.clone(), meta.clone());
     f(update);
+    // The row isn't stamped if the update doesn't change anything, e.g. writes the same values
+    if let Some(uid) = self.uid {
+      if (data.to_json(&txn), meta.to_json(&txn)) != before {
+        RowUpdate::new(&mut txn, data.clone(), meta).set_modified_by(uid);
+      }
+    }
 
     // updates the row_id in case it has changed
     if let Some(row_id) = row_id_from_map_ref(&txn, &data) {
@@ -169,6 +239,54 @@ impl DatabaseRow {
     };
   }
 
+  /// Replaces the cell of the given field with the cell returned by `f`. The current cell is read
+  /// and the new cell is written in the same transaction. Nothing is written if the new cell
+  /// doesn't change the current one.
+  pub fn update_cell<F>(&mut self, field_id: &str, f: F)
+  where
+    F: FnOnce(Option<Cell>) -> Cell,
+  {
+    let data = self.body.data.clone();
+    let meta = self.body.meta.clone();
+    let mut txn = self.collab.transact_mut();
+    let cell = self
+      .body
+      .cells(&txn)
+      .and_then(|mut cells| cells.remove(field_id));
+    let new_cell = f(cell.clone());
+    // The stored cell also has the timestamps of the cell, which aren't part of the new cell
+    let is_unchanged = cell
+      .map(|cell| {
+        new_cell
+          .iter()
+          .all(|(key, value)| cell.get(key) == Some(value))
+      })
+      .unwrap_or(false);
+    if is_unchanged {
+      return;
+    }
+    let update = RowUpdate::new(&mut txn, data, meta).update_cells(|cells_update| {
+      cells_update.insert_cell(field_id, new_cell);
+    });
+    if let Some(uid) = self.uid {
+      update.set_modified_by(uid);
+    }
+  }
+
+  /// Writes the database id into the row if the row doesn't have one. The rows created by the
+  /// older versions might not have the database id. Returns true if the row was repaired.
+  pub fn repair_database_id(&mut self, database_id: &str) -> bool {
+    let data = self.body.data.clone();
+    let mut txn = self.collab.transact_mut();
+    let existing: Option<String> = data.get_with_txn(&txn, ROW_DATABASE_ID);
+    if existing.map(|id| !id.is_empty()).unwrap_or(false) {
+      return false;
+    }
+
+    data.insert(&mut txn, ROW_DATABASE_ID, database_id);
+    true
+  }
+
   pub fn update_meta<F>(&mut self, f: F)
   where
     F: FnOnce(RowMetaUpdate),
@@ -230,7 +348,6 @@ pub struct DatabaseRowBody {
   data: MapRef,
   #[allow(dead_code)]
   meta: MapRef,
-  #[allow(dead_code)]
   comments: ArrayRef,
 }
 
@@ -240,6 +357,13 @@ impl DatabaseRowBody {
     Ok(Self::create_with_data(row_id, collab, None))
   }
 
+  fn subscribe_change(&self, change_tx: Option<RowChangeSender>, dirty: Arc<AtomicBool>) {
+    subscribe_row_comment_change(&self.comments, self.data.clone(), change_tx.clone());
+    subscribe_row_data_change(self.row_id.clone(), &self.data, change_tx, dirty.clone());
+    subscribe_row_dirty_change(&self.meta, dirty.clone());
+    subscribe_row_dirty_change(&self.comments, dirty);
+  }
+
   pub fn create(row_id: RowId, collab: &mut Collab, row: Row) -> Self {
     Self::create_with_data(row_id, collab, Some(row))
   }
@@ -259,6 +383,8 @@ impl DatabaseRowBody {
             .set_visibility(row.visibility)
             .set_created_at(row.created_at)
             .set_last_modified(row.modified_at)
+            .set_created_by_if_not_none(row.created_by)
+            .set_modified_by_if_not_none(row.modified_by)
             .set_cells(row.cells);
         })
         .done();
@@ -388,6 +514,11 @@ pub struct Row {
   pub created_at: i64,
   #[serde(alias = "last_modified")]
   pub modified_at: i64,
+  /// The rows created before the creator was recorded don't have the uid
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created_by: Option<i64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub modified_by: Option<i64>,
 }
 
 fn default_visibility() -> bool {
@@ -431,6 +562,8 @@ impl Row {
       visibility: true,
       created_at: timestamp,
       modified_at: timestamp,
+      created_by: None,
+      modified_by: None,
     }
   }
 
@@ -443,6 +576,8 @@ impl Row {
       visibility: true,
       created_at: 0,
       modified_at: 0,
+      created_by: None,
+      modified_by: None,
     }
   }
 
@@ -533,6 +668,12 @@ impl<'a, 'b> RowUpdate<'a, 'b> {
     set_last_modified_if_not_none,
     LAST_MODIFIED
   );
+  impl_i64_update!(set_created_by, set_created_by_if_not_none, ROW_CREATED_BY);
+  impl_i64_update!(
+    set_modified_by,
+    set_modified_by_if_not_none,
+    ROW_MODIFIED_BY
+  );
 
   pub fn set_database_id(self, database_id: String) -> Self {
     self.map_ref.insert(self.txn, ROW_DATABASE_ID, database_id);
@@ -608,6 +749,8 @@ pub(crate) const ROW_VISIBILITY: &str = "visibility";
 
 pub const ROW_HEIGHT: &str = "height";
 pub const ROW_CELLS: &str = "cells";
+pub const ROW_CREATED_BY: &str = "created_by";
+pub const ROW_MODIFIED_BY: &str = "modified_by";
 
 /// Return row id and created_at from a [YrsValue]
 pub fn row_id_from_value<T: ReadTxn>(value: YrsValue, txn: &T) -> Option<(String, i64)> {
@@ -668,6 +811,21 @@ pub struct CreateRowParams {
   pub created_at: i64,
   #[serde(rename = "last_modified")]
   pub modified_at: i64,
+  #[serde(default)]
+  pub created_by: Option<i64>,
+  #[serde(default)]
+  pub modified_by: Option<i64>,
+}
+
+/// The result of duplicating a row. The [CreateRowParams] and the [RowMeta] are used to create
+/// the duplicated row with [crate::database::Database::create_row_with_meta].
+#[derive(Clone, Debug)]
+pub struct DuplicatedRow {
+  pub params: CreateRowParams,
+  pub meta: Option<RowMeta>,
+  /// The document id of the original row. The document id of a row is derived from the row id,
+  /// so the caller needs to copy the content of this document to the document of the new row.
+  pub source_document_id: String,
 }
 
 pub(crate) struct CreateRowParamsValidator;
@@ -702,9 +860,17 @@ impl CreateRowParams {
       row_position: OrderObjectPosition::default(),
       created_at: timestamp,
       modified_at: timestamp,
+      created_by: None,
+      modified_by: None,
     }
   }
 
+  pub fn with_created_by(mut self, uid: i64) -> Self {
+    self.created_by = Some(uid);
+    self.modified_by = Some(uid);
+    self
+  }
+
   pub fn with_cells(mut self, cells: Cells) -> Self {
     self.cells = cells;
     self
@@ -735,6 +901,8 @@ impl From<CreateRowParams> for Row {
       visibility: params.visibility,
       created_at: params.created_at,
       modified_at: params.modified_at,
+      created_by: params.created_by,
+      modified_by: params.modified_by,
     }
   }
 }
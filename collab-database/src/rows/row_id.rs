@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
 
@@ -16,6 +17,40 @@ impl RowId {
   pub fn into_inner(self) -> String {
     self.0
   }
+
+  /// Returns the unix timestamp in milliseconds embedded in the id. Only the ids generated by
+  /// [crate::database::gen_row_id] (UUIDv7) have the timestamp.
+  pub fn created_ms(&self) -> Option<i64> {
+    let uuid = uuid::Uuid::parse_str(&self.0).ok()?;
+    if uuid.get_version_num() != 7 {
+      return None;
+    }
+
+    let bytes = uuid.as_bytes();
+    let ms = bytes[..6]
+      .iter()
+      .fold(0_i64, |ms, byte| (ms << 8) | *byte as i64);
+    Some(ms)
+  }
+}
+
+/// The time-ordered ids are sorted by their creation. The other ids, for example the random
+/// UUIDv4 ids of the older rows, are sorted after them by their string value.
+impl Ord for RowId {
+  fn cmp(&self, other: &Self) -> Ordering {
+    match (self.created_ms(), other.created_ms()) {
+      (Some(left), Some(right)) => left.cmp(&right).then_with(|| self.0.cmp(&other.0)),
+      (Some(_), None) => Ordering::Less,
+      (None, Some(_)) => Ordering::Greater,
+      (None, None) => self.0.cmp(&other.0),
+    }
+  }
+}
+
+impl PartialOrd for RowId {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
 }
 
 impl Deref for RowId {
@@ -74,6 +74,25 @@ impl<'a, 'b> RowMetaUpdate<'a, 'b> {
     self
   }
 
+  pub fn remove_icon(self) -> Self {
+    let icon_id = meta_id_from_row_id(&self.row_id, RowMetaKey::IconId);
+    self.map_ref.remove(self.txn, &icon_id);
+    self
+  }
+
+  pub fn remove_cover(self) -> Self {
+    let cover_id = meta_id_from_row_id(&self.row_id, RowMetaKey::CoverId);
+    self.map_ref.remove(self.txn, &cover_id);
+    self
+  }
+
+  /// Removes the attachment count. The count of the row is 0 after the removal.
+  pub fn remove_attachment_count(self) -> Self {
+    let attachment_count_id = meta_id_from_row_id(&self.row_id, RowMetaKey::AttachmentCount);
+    self.map_ref.remove(self.txn, &attachment_count_id);
+    self
+  }
+
   pub fn update_is_document_empty(self, is_document_empty: bool) -> Self {
     let is_document_empty_id = meta_id_from_row_id(&self.row_id, RowMetaKey::IsDocumentEmpty);
     self
@@ -94,10 +113,31 @@ impl<'a, 'b> RowMetaUpdate<'a, 'b> {
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct RowCover {
   pub data: String,
+  #[serde(default)]
   pub upload_type: FileUploadType,
+  #[serde(default)]
   pub cover_type: CoverType,
 }
 
+impl RowCover {
+  /// Parses the value stored under the [RowMetaKey::CoverId] key. The rows created before the
+  /// cover got a type store the url of the cover image as a plain string.
+  fn from_meta_value(value: &str) -> Option<Self> {
+    if value.is_empty() {
+      return None;
+    }
+
+    match serde_json::from_str::<Option<RowCover>>(value) {
+      Ok(cover) => cover,
+      Err(_) => Some(Self {
+        data: value.to_string(),
+        upload_type: FileUploadType::NetworkFile,
+        cover_type: CoverType::FileCover,
+      }),
+    }
+  }
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize_repr, Deserialize_repr)]
 #[repr(u8)]
 pub enum CoverType {
@@ -111,11 +151,18 @@ pub enum CoverType {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RowMeta {
   pub icon_url: Option<String>,
+  #[serde(default)]
   pub cover: Option<RowCover>,
+  #[serde(default = "default_is_document_empty")]
   pub is_document_empty: bool,
+  #[serde(default)]
   pub attachment_count: i64,
 }
 
+fn default_is_document_empty() -> bool {
+  true
+}
+
 impl RowMeta {
   #[allow(dead_code)]
   pub(crate) fn empty() -> Self {
@@ -133,8 +180,11 @@ impl RowMeta {
       .unwrap_or_default();
 
     Self {
-      icon_url: map_ref.get_with_txn(txn, &meta_id_from_row_id(row_id, RowMetaKey::IconId)),
-      cover: serde_json::from_str(&cover_data).unwrap_or(None),
+      // The icon used to be removed by writing an empty string
+      icon_url: map_ref
+        .get_with_txn(txn, &meta_id_from_row_id(row_id, RowMetaKey::IconId))
+        .filter(|icon: &String| !icon.is_empty()),
+      cover: RowCover::from_meta_value(&cover_data),
       is_document_empty: map_ref
         .get_with_txn(
           txn,
@@ -163,5 +213,16 @@ impl RowMeta {
         serde_json::to_string(&cover).unwrap_or_default(),
       );
     }
+
+    map_ref.try_update(
+      txn,
+      meta_id_from_row_id(row_id, RowMetaKey::IsDocumentEmpty),
+      self.is_document_empty,
+    );
+    map_ref.try_update(
+      txn,
+      meta_id_from_row_id(row_id, RowMetaKey::AttachmentCount),
+      self.attachment_count,
+    );
   }
 }
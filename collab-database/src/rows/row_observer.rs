@@ -1,11 +1,13 @@
-use crate::rows::{Cell, Row, RowId, ROW_CELLS, ROW_HEIGHT, ROW_VISIBILITY};
+use crate::rows::{row_from_map_ref, Cell, Row, RowId, ROW_CELLS, ROW_HEIGHT, ROW_VISIBILITY};
 
-use collab::preclude::{DeepObservable, EntryChange, Event, MapRef, TransactionMut};
+use collab::preclude::{ArrayRef, DeepObservable, EntryChange, Event, MapRef, TransactionMut};
 use collab::preclude::{PathSegment, ToJson};
 use std::ops::Deref;
 
 use collab::preclude::map::MapEvent;
 use collab::util::AnyExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tokio::sync::broadcast;
 use tracing::trace;
 
@@ -22,28 +24,44 @@ pub enum RowChange {
     row_id: RowId,
     value: i32,
   },
+  /// The cell of the field is inserted or updated. The `old` is `None` if the cell is newly
+  /// inserted.
   DidUpdateCell {
     row_id: RowId,
     field_id: String,
-    value: Cell,
+    old: Option<Cell>,
+    new: Cell,
+  },
+  DidDeleteCell {
+    row_id: RowId,
+    key: String,
   },
   DidUpdateRowComment {
     row: Row,
   },
 }
 
+/// Observes the changes of the row data. The row is marked as dirty on every change, and the
+/// changes are sent to the `change_tx` if it's provided.
 pub(crate) fn subscribe_row_data_change(
   row_id: RowId,
   row_data_map: &MapRef,
-  change_tx: RowChangeSender,
+  change_tx: Option<RowChangeSender>,
+  dirty: Arc<AtomicBool>,
 ) {
   row_data_map.observe_deep_with("change", move |txn, events| {
+    dirty.store(true, Ordering::Release);
+    let change_tx = match &change_tx {
+      None => return,
+      Some(change_tx) => change_tx,
+    };
+
     for event in events.iter() {
       match event {
         Event::Text(_) => {},
         Event::Array(_) => {},
         Event::Map(map_event) => {
-          handle_map_event(&row_id, &change_tx, txn, event, map_event);
+          handle_map_event(&row_id, change_tx, txn, event, map_event);
         },
         Event::XmlFragment(_) => {},
         Event::XmlText(_) => {},
@@ -54,6 +72,31 @@ pub(crate) fn subscribe_row_data_change(
   });
 }
 
+/// Observes the comments of the row. A [RowChange::DidUpdateRowComment] is sent when a comment
+/// is added, updated or removed.
+pub(crate) fn subscribe_row_comment_change(
+  comments: &ArrayRef,
+  row_data_map: MapRef,
+  change_tx: Option<RowChangeSender>,
+) {
+  let change_tx = match change_tx {
+    None => return,
+    Some(change_tx) => change_tx,
+  };
+  comments.observe_deep_with("change", move |txn, _events| {
+    if let Some(row) = row_from_map_ref(&row_data_map, txn) {
+      let _ = change_tx.send(RowChange::DidUpdateRowComment { row });
+    }
+  });
+}
+
+/// Marks the row as dirty when the given shared type or any of its children change.
+pub(crate) fn subscribe_row_dirty_change<T: DeepObservable>(shared: &T, dirty: Arc<AtomicBool>) {
+  shared.observe_deep_with("dirty", move |_txn, _events| {
+    dirty.store(true, Ordering::Release);
+  });
+}
+
 fn handle_map_event(
   row_id: &RowId,
   change_tx: &RowChangeSender,
@@ -62,6 +105,12 @@ fn handle_map_event(
   map_event: &MapEvent,
 ) {
   let path = RowChangePath::from(event);
+  // The changes inside a cell have the path "/cells/{key}"
+  if matches!(path, RowChangePath::Cells) && event.path().len() > 1 {
+    handle_cell_content_change(row_id, change_tx, txn, event, map_event);
+    return;
+  }
+
   for (key, enctry_change) in map_event.keys(txn).iter() {
     match &path {
       RowChangePath::Unknown(_s) => {
@@ -107,32 +156,28 @@ fn handle_map_event(
               let _ = change_tx.send(RowChange::DidUpdateCell {
                 row_id: row_id.clone(),
                 field_id,
-                value: cell,
+                old: None,
+                new: cell,
               });
             }
           },
-          EntryChange::Updated(_, _) => {
-            // Processing an update to a cell's value:
-            // The event path for an updated cell value is structured as "/cells/{key}", where {key} is the unique identifier of the cell.
-            // The 'target' of the event represents the new, updated value of the cell.
-            // To accurately identify which cell has been updated, we need to extract its key from the event path.
-            // This extraction is achieved by removing the last segment of the path, which is "/{key}".
-            // After this removal, the remaining part of the path directly corresponds to the key of the cell.
-            // In the current implementation, this key is used as the identifier (ID) of the field within the cells map.
-            if let Some(PathSegment::Key(key)) = event.path().pop_back() {
-              if let Some(cell) = event.target().to_json(txn).into_map() {
-                let field_id = key.deref().to_string();
-                let _ = change_tx.send(RowChange::DidUpdateCell {
-                  row_id: row_id.clone(),
-                  field_id,
-                  value: cell,
-                });
-              }
+          EntryChange::Updated(old, new) => {
+            // The whole cell is replaced by another one
+            if let Some(cell) = new.to_json(txn).into_map() {
+              let _ = change_tx.send(RowChange::DidUpdateCell {
+                row_id: row_id.clone(),
+                field_id: key.to_string(),
+                old: old.to_json(txn).into_map(),
+                new: cell,
+              });
             }
-            //
           },
           EntryChange::Removed(_value) => {
             trace!("row observe delete: {}", key);
+            let _ = change_tx.send(RowChange::DidDeleteCell {
+              row_id: row_id.clone(),
+              key: key.to_string(),
+            });
           },
         }
       },
@@ -140,6 +185,50 @@ fn handle_map_event(
   }
 }
 
+/// Handles the changes of the key/value pairs inside a cell.
+/// The event path for an updated cell value is structured as "/cells/{key}", where {key} is the
+/// unique identifier of the cell. The 'target' of the event represents the new, updated value of
+/// the cell. The old value of the cell is restored by reverting the changed keys of the event.
+fn handle_cell_content_change(
+  row_id: &RowId,
+  change_tx: &RowChangeSender,
+  txn: &TransactionMut,
+  event: &Event,
+  map_event: &MapEvent,
+) {
+  let mut path = event.path();
+  if path.len() != 2 {
+    return;
+  }
+  let field_id = match path.pop_back() {
+    Some(PathSegment::Key(key)) => key.deref().to_string(),
+    _ => return,
+  };
+  let new = match event.target().to_json(txn).into_map() {
+    None => return,
+    Some(cell) => cell,
+  };
+
+  let mut old = new.clone();
+  for (key, entry_change) in map_event.keys(txn).iter() {
+    match entry_change {
+      EntryChange::Inserted(_) => {
+        old.remove(key.deref());
+      },
+      EntryChange::Updated(value, _) | EntryChange::Removed(value) => {
+        old.insert(key.to_string(), value.to_json(txn));
+      },
+    }
+  }
+
+  let _ = change_tx.send(RowChange::DidUpdateCell {
+    row_id: row_id.clone(),
+    field_id,
+    old: Some(old),
+    new,
+  });
+}
+
 enum RowChangePath {
   Unknown(String),
   Cells,
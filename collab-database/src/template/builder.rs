@@ -113,6 +113,7 @@ impl DatabaseTemplateBuilder {
       filters: vec![],
       group_settings: vec![],
       sorts: vec![],
+      field_settings: Default::default(),
     }];
 
     DatabaseTemplate {
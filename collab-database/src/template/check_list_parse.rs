@@ -24,8 +24,10 @@ impl ChecklistCellData {
       .collect()
   }
 
+  /// Returns the ratio of the selected options, between 0.0 and 1.0, rounded to two decimals.
+  /// The selected ids that don't have an option are ignored. An empty checklist is 0.0.
   pub fn percentage_complete(&self) -> f64 {
-    let selected_options = self.selected_option_ids.len();
+    let selected_options = self.selected_options().len();
     let total_options = self.options.len();
 
     if total_options == 0 {
@@ -33,6 +35,10 @@ impl ChecklistCellData {
     }
     ((selected_options as f64) / (total_options as f64) * 100.0).round() / 100.0
   }
+
+  pub fn is_complete(&self) -> bool {
+    !self.options.is_empty() && self.selected_options().len() == self.options.len()
+  }
 }
 
 impl From<&Cell> for ChecklistCellData {
@@ -97,7 +103,65 @@ impl ToCellString for ChecklistCellData {
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::rows::Cell;
+  use crate::rows::{Cell, CellsUpdate};
+  use collab::core::origin::CollabOrigin;
+  use collab::preclude::{Collab, Map, MapRef, ToJson};
+  use collab::util::AnyExt;
+
+  #[test]
+  fn empty_checklist_test() {
+    let checklist_data = ChecklistCellData::default();
+    assert_eq!(checklist_data.percentage_complete(), 0.0);
+    assert!(!checklist_data.is_complete());
+
+    // A cell without data is read as an empty checklist
+    let checklist_data = ChecklistCellData::from(&new_cell_builder(FieldType::Checklist));
+    assert!(checklist_data.options.is_empty());
+    assert_eq!(checklist_data.percentage_complete(), 0.0);
+  }
+
+  #[test]
+  fn checklist_percentage_complete_test() {
+    let names = vec!["A".to_string(), "B".to_string(), "C".to_string()];
+    let mut checklist_data = ChecklistCellData::from((names.clone(), vec!["A".to_string()]));
+    assert_eq!(checklist_data.percentage_complete(), 0.33);
+
+    // The selected ids without an option are ignored
+    checklist_data
+      .selected_option_ids
+      .push("unknown option id".to_string());
+    assert_eq!(checklist_data.percentage_complete(), 0.33);
+
+    let checklist_data = ChecklistCellData::from((names.clone(), names));
+    assert_eq!(checklist_data.percentage_complete(), 1.0);
+    assert!(checklist_data.is_complete());
+  }
+
+  #[test]
+  fn checklist_cell_map_round_trip_test() {
+    let checklist_data = ChecklistCellData::from((
+      vec!["A".to_string(), "B".to_string()],
+      vec!["B".to_string()],
+    ));
+    let mut collab = Collab::new_with_origin(CollabOrigin::Empty, "1", vec![], false);
+    let mut txn = collab.context.transact_mut();
+    let cells: MapRef = collab.data.get_or_init(&mut txn, "cells");
+    CellsUpdate::new(&mut txn, &cells).insert_checklist("f1", checklist_data.clone());
+    let cell: Cell = cells
+      .get(&txn, "f1")
+      .unwrap()
+      .to_json(&txn)
+      .into_map()
+      .unwrap();
+
+    let restored_data = ChecklistCellData::from(&cell);
+    assert_eq!(restored_data.options, checklist_data.options);
+    assert_eq!(
+      restored_data.selected_option_ids,
+      checklist_data.selected_option_ids
+    );
+    assert_eq!(restored_data.percentage_complete(), 0.5);
+  }
 
   #[test]
   fn test_checklist_cell_data_from_names_and_selected() {
@@ -2,6 +2,7 @@ use crate::database::{gen_database_id, gen_database_view_id};
 use crate::entity::FieldType;
 use crate::error::DatabaseError;
 use crate::template::builder::{DatabaseTemplateBuilder, FileUrlBuilder};
+use crate::template::csv_stream::CSVStreamTemplate;
 use crate::template::date_parse::cast_string_to_timestamp;
 use crate::template::entity::DatabaseTemplate;
 use percent_encoding::percent_decode_str;
@@ -19,8 +20,8 @@ pub struct CSVTemplate {
 }
 
 pub struct CSVField {
-  name: String,
-  field_type: FieldType,
+  pub(crate) name: String,
+  pub(crate) field_type: FieldType,
 }
 
 pub struct CSVResource {
@@ -29,39 +30,70 @@ pub struct CSVResource {
   pub files: Vec<String>,
 }
 
+pub const DEFAULT_INFERENCE_SAMPLE_SIZE: usize = 10;
+
+/// The options used to parse the CSV content. The default options parse a comma separated
+/// content whose first record is the header.
+#[derive(Debug, Clone, Copy)]
+pub struct CSVTemplateOptions {
+  /// The delimiter between the fields of a record, e.g. `b';'` or `b'\t'`
+  pub delimiter: u8,
+  /// If false, the first record is treated as a row and the fields are named "Column 1..N"
+  pub has_header: bool,
+  /// Trims the leading and trailing whitespace of the header and the cells
+  pub trim_fields: bool,
+  /// The number of non-empty cells of each column that are sampled to infer the type of the
+  /// field when the field types are detected automatically
+  pub inference_sample_size: usize,
+}
+
+impl Default for CSVTemplateOptions {
+  fn default() -> Self {
+    Self {
+      delimiter: b',',
+      has_header: true,
+      trim_fields: false,
+      inference_sample_size: DEFAULT_INFERENCE_SAMPLE_SIZE,
+    }
+  }
+}
+
 impl CSVTemplate {
   pub fn try_from_reader(
     reader: impl io::Read,
     auto_field_type: bool,
-    mut csv_resource: Option<CSVResource>,
+    csv_resource: Option<CSVResource>,
   ) -> Result<Self, DatabaseError> {
-    let mut fields: Vec<CSVField> = vec![];
-
-    let mut reader = csv::Reader::from_reader(reader);
-    if let Ok(headers) = reader.headers() {
-      for header in headers {
-        fields.push(CSVField {
-          name: header.to_string(),
-          field_type: FieldType::RichText,
-        });
-      }
-    } else {
-      return Err(DatabaseError::InvalidCSV("No header".to_string()));
-    }
+    Self::from_reader_with_options(
+      reader,
+      auto_field_type,
+      csv_resource,
+      CSVTemplateOptions::default(),
+    )
+  }
 
+  /// Same as [CSVTemplate::try_from_reader], but the content is parsed with the given options
+  pub fn from_reader_with_options(
+    reader: impl io::Read,
+    auto_field_type: bool,
+    mut csv_resource: Option<CSVResource>,
+    options: CSVTemplateOptions,
+  ) -> Result<Self, DatabaseError> {
+    let mut reader = csv_reader_with_options(reader, &options);
+    let mut fields = fields_from_headers(&mut reader, &options)?;
     let rows: Vec<Vec<String>> = reader
       .records()
       .flat_map(|r| r.ok())
-      .map(|record| {
-        record
-          .into_iter()
-          .filter_map(|s| Some(percent_decode_str(s).decode_utf8().ok()?.to_string()))
-          .collect::<Vec<String>>()
-      })
+      .map(decode_record)
       .collect();
 
     if auto_field_type {
-      auto_detect_field_type(&mut fields, &rows, &csv_resource);
+      auto_detect_field_type(
+        &mut fields,
+        &rows,
+        &csv_resource,
+        options.inference_sample_size,
+      );
     }
 
     // filter out resources that are not used
@@ -76,6 +108,22 @@ impl CSVTemplate {
     })
   }
 
+  /// Parses the header and samples the first rows to detect the field types. Unlike
+  /// [CSVTemplate::try_from_reader], the rest of the rows are only parsed when they are
+  /// consumed, so a large file doesn't need to be loaded into memory at once.
+  pub fn from_reader<R: io::Read>(
+    reader: R,
+    auto_field_type: bool,
+    csv_resource: Option<CSVResource>,
+  ) -> Result<CSVStreamTemplate<R>, DatabaseError> {
+    CSVStreamTemplate::new(
+      reader,
+      auto_field_type,
+      csv_resource,
+      CSVTemplateOptions::default(),
+    )
+  }
+
   pub fn reset_view_id(&mut self, view_id: String) {
     self.view_id = view_id;
   }
@@ -118,11 +166,60 @@ impl CSVTemplate {
   }
 }
 
-fn filter_out_resources(
-  fields: &[CSVField],
-  rows: &[Vec<String>],
-  resource: &mut Option<CSVResource>,
-) {
+pub(crate) fn csv_reader_with_options<R: io::Read>(
+  reader: R,
+  options: &CSVTemplateOptions,
+) -> csv::Reader<R> {
+  csv::ReaderBuilder::new()
+    .delimiter(options.delimiter)
+    .has_headers(options.has_header)
+    .trim(if options.trim_fields {
+      csv::Trim::All
+    } else {
+      csv::Trim::None
+    })
+    .from_reader(reader)
+}
+
+/// Creates the fields from the header of the content. All fields are text fields until their
+/// types are detected.
+pub(crate) fn fields_from_headers<R: io::Read>(
+  reader: &mut csv::Reader<R>,
+  options: &CSVTemplateOptions,
+) -> Result<Vec<CSVField>, DatabaseError> {
+  // When the content doesn't have a header, the headers are the first record, which is
+  // still returned by the records of the reader.
+  match reader.headers() {
+    Ok(headers) => Ok(
+      headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+          let name = if options.has_header {
+            header.to_string()
+          } else {
+            format!("Column {}", index + 1)
+          };
+          CSVField {
+            name,
+            field_type: FieldType::RichText,
+          }
+        })
+        .collect(),
+    ),
+    Err(_) => Err(DatabaseError::InvalidCSV("No header".to_string())),
+  }
+}
+
+pub(crate) fn decode_record(record: csv::StringRecord) -> Vec<String> {
+  record
+    .into_iter()
+    .filter_map(|s| Some(percent_decode_str(s).decode_utf8().ok()?.to_string()))
+    .collect()
+}
+
+/// Returns the resources that are referenced by the cells of the media fields
+pub(crate) fn media_cell_resources(fields: &[CSVField], rows: &[Vec<String>]) -> HashSet<String> {
   let mut cell_resources = HashSet::new();
   for (index, field) in fields.iter().enumerate() {
     if matches!(field.field_type, FieldType::Media) {
@@ -135,10 +232,16 @@ fn filter_out_resources(
       }
     }
   }
+  cell_resources
+}
 
+/// Only keeps the resources that end with one of the cell resources
+pub(crate) fn retain_cell_resources(
+  resource: &mut Option<CSVResource>,
+  cell_resources: &HashSet<String>,
+) {
   if let Some(resource) = resource {
     resource.files.retain(|file| {
-      // retain if resource end with one of the cell resources
       cell_resources
         .iter()
         .any(|cell_res| file.ends_with(cell_res))
@@ -146,25 +249,33 @@ fn filter_out_resources(
   }
 }
 
-fn auto_detect_field_type(
+fn filter_out_resources(
+  fields: &[CSVField],
+  rows: &[Vec<String>],
+  resource: &mut Option<CSVResource>,
+) {
+  let cell_resources = media_cell_resources(fields, rows);
+  retain_cell_resources(resource, &cell_resources);
+}
+
+pub(crate) fn auto_detect_field_type(
   fields: &mut Vec<CSVField>,
   rows: &[Vec<String>],
   resources: &Option<CSVResource>,
+  sample_size: usize,
 ) {
   let num_fields = fields.len();
   fields
     .par_iter_mut()
     .enumerate()
     .for_each(|(field_index, field)| {
+      // Only the first non-empty cells are sampled, so a large file doesn't need to be scanned
       let cells: Vec<&str> = rows
-        .par_iter()
-        .filter_map(|row| {
-          if row.len() != num_fields {
-            None
-          } else {
-            Some(row[field_index].as_str())
-          }
-        })
+        .iter()
+        .filter(|row| row.len() == num_fields)
+        .map(|row| row[field_index].as_str())
+        .filter(|cell| !cell.is_empty())
+        .take(sample_size)
         .collect();
 
       field.field_type = detect_field_type_from_cells_with_resource(&cells, resources);
@@ -183,7 +294,6 @@ fn detect_field_type_from_cells_with_resource(
   let cells = cells
     .iter()
     .filter(|cell| !cell.is_empty())
-    .take(10)
     .cloned()
     .collect::<Vec<&str>>();
 
@@ -0,0 +1,248 @@
+use crate::database::{
+  gen_database_id, gen_database_view_id, gen_row_id, Database, DatabaseContext,
+};
+use crate::entity::FieldType;
+use crate::error::DatabaseError;
+use crate::fields::select_type_option::{SelectOption, SelectOptionColor, SelectTypeOption};
+use crate::rows::new_cell_builder;
+use crate::template::builder::{DatabaseTemplateBuilder, FieldTemplateBuilder, FileUrlBuilder};
+use crate::template::csv::{
+  auto_detect_field_type, csv_reader_with_options, decode_record, fields_from_headers,
+  media_cell_resources, retain_cell_resources, CSVField, CSVResource, CSVTemplateOptions,
+};
+use crate::template::entity::{CellTemplateData, RowTemplate, CELL_DATA};
+use crate::template::option_parse::{
+  build_options_from_cells, replace_cells_with_options_id, SELECT_OPTION_SEPARATOR,
+};
+use crate::template::util::create_row_params_from_templates;
+
+use crate::workspace_database::NoPersistenceDatabaseCollabService;
+
+use collab::preclude::Any;
+use std::collections::HashSet;
+use std::io;
+use std::sync::Arc;
+
+/// The number of rows that are read up front to detect the field types
+const SAMPLE_ROWS: usize = 100;
+
+/// The default number of rows that are converted and inserted into the database at a time
+pub const DEFAULT_CSV_CHUNK_SIZE: usize = 1000;
+
+/// A CSV template whose rows are parsed lazily from the reader. It's created by
+/// [crate::template::csv::CSVTemplate::from_reader]. Iterating the template returns the
+/// remaining rows one by one.
+pub struct CSVStreamTemplate<R: io::Read> {
+  pub fields: Vec<CSVField>,
+  pub resource: Option<CSVResource>,
+  pub database_id: String,
+  pub view_id: String,
+  sampled_rows: std::vec::IntoIter<Vec<String>>,
+  records: csv::StringRecordsIntoIter<R>,
+}
+
+impl<R: io::Read> CSVStreamTemplate<R> {
+  pub(crate) fn new(
+    reader: R,
+    auto_field_type: bool,
+    csv_resource: Option<CSVResource>,
+    options: CSVTemplateOptions,
+  ) -> Result<Self, DatabaseError> {
+    let mut reader = csv_reader_with_options(reader, &options);
+    let mut fields = fields_from_headers(&mut reader, &options)?;
+    let mut records = reader.into_records();
+    let sampled_rows = records
+      .by_ref()
+      .take(SAMPLE_ROWS)
+      .flat_map(|r| r.ok())
+      .map(decode_record)
+      .collect::<Vec<_>>();
+
+    if auto_field_type {
+      auto_detect_field_type(
+        &mut fields,
+        &sampled_rows,
+        &csv_resource,
+        options.inference_sample_size,
+      );
+    }
+
+    Ok(Self {
+      fields,
+      resource: csv_resource,
+      database_id: gen_database_id(),
+      view_id: gen_database_view_id(),
+      sampled_rows: sampled_rows.into_iter(),
+      records,
+    })
+  }
+
+  pub fn reset_view_id(&mut self, view_id: String) {
+    self.view_id = view_id;
+  }
+
+  /// Returns at most `chunk_size` of the remaining rows. An empty vec is returned when all the
+  /// rows are consumed.
+  pub fn next_rows(&mut self, chunk_size: usize) -> Vec<Vec<String>> {
+    self.by_ref().take(chunk_size).collect()
+  }
+
+  /// Creates a database from the template without persistence, see
+  /// [CSVStreamTemplate::into_database_with_context].
+  pub async fn into_database(
+    self,
+    file_url_builder: Option<Box<dyn FileUrlBuilder>>,
+    chunk_size: usize,
+  ) -> Result<(Database, Option<CSVResource>), DatabaseError> {
+    let context = DatabaseContext::new(Arc::new(NoPersistenceDatabaseCollabService));
+    self
+      .into_database_with_context(file_url_builder, chunk_size, context)
+      .await
+  }
+
+  /// Creates a database from the template. The rows are converted into [RowTemplate]s and
+  /// inserted chunk by chunk, each chunk in one transaction. The imported rows are saved with
+  /// the persistence of the `context` instead of being kept in the row cache, so at most
+  /// `chunk_size` rows are kept in memory. Returns the database and the resources that are
+  /// referenced by the media cells.
+  pub async fn into_database_with_context(
+    mut self,
+    file_url_builder: Option<Box<dyn FileUrlBuilder>>,
+    chunk_size: usize,
+    context: DatabaseContext,
+  ) -> Result<(Database, Option<CSVResource>), DatabaseError> {
+    // The database is created without rows. The options of the select fields are collected
+    // while the rows are inserted and written to the fields at the end.
+    let mut builder =
+      DatabaseTemplateBuilder::new(self.database_id.clone(), self.view_id.clone(), None);
+    for (field_index, field) in self.fields.iter().enumerate() {
+      builder = builder
+        .create_field(
+          &self.resource,
+          &self.database_id,
+          &field.name,
+          field.field_type,
+          field_index == 0,
+          |field_builder| field_builder,
+        )
+        .await;
+    }
+    let template = builder.build();
+    let field_ids = template
+      .fields
+      .iter()
+      .map(|field| field.field_id.clone())
+      .collect::<Vec<_>>();
+    let mut database = Database::create_with_view(template.into_params(), context).await?;
+
+    let mut select_options = vec![vec![]; self.fields.len()];
+    let mut cell_resources = HashSet::new();
+    loop {
+      let rows = self.next_rows(chunk_size.max(1));
+      if rows.is_empty() {
+        break;
+      }
+      cell_resources.extend(media_cell_resources(&self.fields, &rows));
+
+      let mut row_templates = rows
+        .iter()
+        .map(|_| RowTemplate {
+          row_id: gen_row_id().to_string(),
+          height: 60,
+          visibility: true,
+          cells: Default::default(),
+        })
+        .collect::<Vec<_>>();
+      for (field_index, field) in self.fields.iter().enumerate() {
+        let cells = rows
+          .iter()
+          .map(|row| row.get(field_index).cloned().unwrap_or_default())
+          .collect::<Vec<_>>();
+        let cell_templates = match field.field_type {
+          FieldType::SingleSelect | FieldType::MultiSelect => {
+            select_cell_templates(field.field_type, cells, &mut select_options[field_index])
+          },
+          _ => {
+            let mut field_builder =
+              FieldTemplateBuilder::new(field.name.clone(), field.field_type, false);
+            for cell in cells {
+              field_builder = field_builder.create_cell(cell);
+            }
+            field_builder
+              .build(&self.resource, &self.database_id, &file_url_builder)
+              .await
+              .1
+          },
+        };
+
+        for (row_template, cell) in row_templates.iter_mut().zip(cell_templates) {
+          row_template
+            .cells
+            .insert(field_ids[field_index].clone(), cell);
+        }
+      }
+
+      let rows = create_row_params_from_templates(&self.database_id, row_templates).collect();
+      database.import_rows(rows).await?;
+    }
+
+    for (field_index, options) in select_options.into_iter().enumerate() {
+      if options.is_empty() {
+        continue;
+      }
+      let field_type = self.fields[field_index].field_type;
+      let type_option = SelectTypeOption {
+        options,
+        disable_color: false,
+      };
+      database.update_field(&field_ids[field_index], |update| {
+        update.set_type_option(field_type as i64, Some(type_option.into()));
+      });
+    }
+
+    retain_cell_resources(&mut self.resource, &cell_resources);
+    Ok((database, self.resource))
+  }
+}
+
+impl<R: io::Read> Iterator for CSVStreamTemplate<R> {
+  type Item = Vec<String>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if let Some(row) = self.sampled_rows.next() {
+      return Some(row);
+    }
+
+    // The records that can't be parsed are skipped
+    loop {
+      match self.records.next()? {
+        Ok(record) => return Some(decode_record(record)),
+        Err(_) => continue,
+      }
+    }
+  }
+}
+
+/// Replaces the cells with the ids of the options. The options that don't exist yet are appended
+/// to `options`, so the same name is mapped to the same option across the chunks.
+fn select_cell_templates(
+  field_type: FieldType,
+  cells: Vec<String>,
+  options: &mut Vec<SelectOption>,
+) -> Vec<CellTemplateData> {
+  for mut option in build_options_from_cells(&cells) {
+    if options.iter().all(|existing| existing.name != option.name) {
+      option.color = SelectOptionColor::from(options.len() % 8);
+      options.push(option);
+    }
+  }
+
+  replace_cells_with_options_id(cells, options, SELECT_OPTION_SEPARATOR)
+    .into_iter()
+    .map(|id| {
+      let mut map = new_cell_builder(field_type);
+      map.insert(CELL_DATA.to_string(), Any::from(id));
+      map
+    })
+    .collect()
+}
@@ -1,11 +1,15 @@
 use crate::entity::{CreateDatabaseParams, FieldType};
-use crate::views::{DatabaseLayout, LayoutSettings};
+use crate::views::{DatabaseLayout, FieldSettingsByFieldIdMap, LayoutSettings};
 use collab::preclude::Any;
 
 use crate::template::util::create_database_params_from_template;
 use std::collections::HashMap;
 
 pub const CELL_DATA: &str = "data";
+
+/// The template of a database. The database id, view id and row ids can be left empty, in which
+/// case new ids are generated each time the template is converted into [CreateDatabaseParams].
+#[derive(Clone)]
 pub struct DatabaseTemplate {
   pub database_id: String,
   pub view_id: String,
@@ -20,6 +24,7 @@ impl DatabaseTemplate {
   }
 }
 
+#[derive(Clone)]
 pub struct DatabaseViewTemplate {
   pub name: String,
   pub layout: DatabaseLayout,
@@ -27,8 +32,10 @@ pub struct DatabaseViewTemplate {
   pub filters: Vec<HashMap<String, Any>>,
   pub group_settings: Vec<HashMap<String, Any>>,
   pub sorts: Vec<HashMap<String, Any>>,
+  pub field_settings: FieldSettingsByFieldIdMap,
 }
 
+#[derive(Clone)]
 pub struct FieldTemplate {
   pub field_id: String,
   pub name: String,
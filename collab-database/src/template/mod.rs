@@ -2,6 +2,7 @@ pub mod builder;
 pub mod check_list_parse;
 pub mod checkbox_parse;
 pub mod csv;
+pub mod csv_stream;
 pub mod date_parse;
 pub mod entity;
 pub mod media_parse;
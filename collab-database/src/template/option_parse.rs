@@ -26,12 +26,14 @@ pub(crate) fn replace_cells_with_options_id(
 }
 
 pub fn build_options_from_cells(cells: &[String]) -> Vec<SelectOption> {
-  let mut option_names = HashSet::new();
+  // The options are created in the order they first appear in the cells
+  let mut option_names = vec![];
+  let mut seen_names = HashSet::new();
   for cell in cells {
     cell.split(SELECT_OPTION_SEPARATOR).for_each(|cell| {
       let trim_cell = cell.trim();
-      if !trim_cell.is_empty() {
-        option_names.insert(trim_cell.to_string());
+      if !trim_cell.is_empty() && seen_names.insert(trim_cell.to_string()) {
+        option_names.push(trim_cell.to_string());
       }
     });
   }
@@ -1,9 +1,11 @@
-use crate::database::{timestamp, Database, DatabaseContext};
+use crate::database::{
+  gen_database_id, gen_database_view_id, gen_row_id, timestamp, Database, DatabaseContext,
+};
 use crate::entity::{CreateDatabaseParams, CreateViewParams};
 use crate::error::DatabaseError;
 use crate::fields::Field;
 use crate::rows::{CreateRowParams, RowId};
-use crate::template::entity::DatabaseTemplate;
+use crate::template::entity::{DatabaseTemplate, RowTemplate};
 use crate::workspace_database::NoPersistenceDatabaseCollabService;
 use std::sync::Arc;
 
@@ -45,7 +47,16 @@ where
 pub(crate) fn create_database_params_from_template(
   template: DatabaseTemplate,
 ) -> CreateDatabaseParams {
-  let database_id = template.database_id;
+  let database_id = if template.database_id.is_empty() {
+    gen_database_id()
+  } else {
+    template.database_id
+  };
+  let view_id = if template.view_id.is_empty() {
+    gen_database_view_id()
+  } else {
+    template.view_id
+  };
   let timestamp = timestamp();
 
   let mut fields = vec![];
@@ -62,34 +73,24 @@ pub(crate) fn create_database_params_from_template(
     fields.push(field);
   }
 
-  let mut rows = vec![];
-  for row_template in template.rows {
-    rows.push(CreateRowParams {
-      id: RowId::from(row_template.row_id),
-      database_id: database_id.clone(),
-      cells: row_template.cells,
-      height: row_template.height,
-      visibility: row_template.visibility,
-      row_position: Default::default(),
-      created_at: timestamp,
-      modified_at: timestamp,
-    });
-  }
+  let rows = create_row_params_from_templates(&database_id, template.rows).collect();
 
   let mut views = vec![];
   for view_template in template.views {
     views.push(CreateViewParams {
       database_id: database_id.clone(),
-      view_id: template.view_id.clone(),
+      view_id: view_id.clone(),
       name: view_template.name,
+      description: String::new(),
       layout: view_template.layout,
       layout_settings: view_template.layout_settings,
       filters: view_template.filters,
       group_settings: view_template.group_settings,
       sorts: view_template.sorts,
-      field_settings: Default::default(),
+      field_settings: view_template.field_settings,
       created_at: timestamp,
       modified_at: timestamp,
+      created_by: None,
       deps_fields: vec![],
       deps_field_setting: vec![],
     });
@@ -100,5 +101,31 @@ pub(crate) fn create_database_params_from_template(
     fields,
     rows,
     views,
+    meta: Default::default(),
   }
 }
+
+/// Converts the row templates into [CreateRowParams] lazily, so the rows can be streamed into a
+/// database chunk by chunk without converting all the templates up front.
+pub fn create_row_params_from_templates<'a>(
+  database_id: &'a str,
+  rows: impl IntoIterator<Item = RowTemplate> + 'a,
+) -> impl Iterator<Item = CreateRowParams> + 'a {
+  let timestamp = timestamp();
+  rows.into_iter().map(move |row_template| CreateRowParams {
+    id: if row_template.row_id.is_empty() {
+      gen_row_id()
+    } else {
+      RowId::from(row_template.row_id)
+    },
+    database_id: database_id.to_string(),
+    cells: row_template.cells,
+    height: row_template.height,
+    visibility: row_template.visibility,
+    row_position: Default::default(),
+    created_at: timestamp,
+    modified_at: timestamp,
+    created_by: None,
+    modified_by: None,
+  })
+}
@@ -4,3 +4,49 @@ use std::collections::HashMap;
 pub type CalculationArray = Vec<Any>;
 pub type CalculationMap = HashMap<String, Any>;
 pub type CalculationMapBuilder = HashMap<String, Any>;
+
+/// The key of the [CalculationScope] of a calculation.
+pub const CALCULATION_SCOPE: &str = "scope";
+
+/// Decides which rows of the view a calculation is applied to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CalculationScope {
+  #[default]
+  AllRows = 0,
+  /// Only the rows that pass the filters of the view
+  FilteredRows = 1,
+}
+
+impl From<i64> for CalculationScope {
+  fn from(value: i64) -> Self {
+    match value {
+      1 => CalculationScope::FilteredRows,
+      _ => CalculationScope::AllRows,
+    }
+  }
+}
+
+impl From<CalculationScope> for Any {
+  fn from(scope: CalculationScope) -> Self {
+    Any::BigInt(scope as i64)
+  }
+}
+
+/// Returns the scope of the calculation. The calculations created before the scope was
+/// introduced apply to [CalculationScope::AllRows].
+pub fn calculation_scope(calculation: &CalculationMap) -> CalculationScope {
+  match calculation.get(CALCULATION_SCOPE) {
+    Some(Any::BigInt(value)) => CalculationScope::from(*value),
+    Some(Any::Number(value)) => CalculationScope::from(*value as i64),
+    _ => CalculationScope::default(),
+  }
+}
+
+/// Inserts the default scope into the calculation if the calculation doesn't have one.
+pub(crate) fn with_default_calculation_scope(mut calculation: CalculationMap) -> CalculationMap {
+  calculation
+    .entry(CALCULATION_SCOPE.to_string())
+    .or_insert_with(|| CalculationScope::default().into());
+  calculation
+}
@@ -1,5 +1,6 @@
 pub const VIEW_ID: &str = "id";
 pub const VIEW_NAME: &str = "name";
+pub const VIEW_DESCRIPTION: &str = "description";
 pub const VIEW_DATABASE_ID: &str = "database_id";
 pub const DATABASE_VIEW_LAYOUT: &str = "layout";
 pub const VIEW_LAYOUT_SETTINGS: &str = "layout_settings";
@@ -8,8 +9,12 @@ pub const DATABASE_VIEW_GROUPS: &str = "groups";
 pub const DATABASE_VIEW_SORTS: &str = "sorts";
 pub const DATABASE_VIEW_FIELD_SETTINGS: &str = "field_settings";
 pub const DATABASE_VIEW_ROW_ORDERS: &str = "row_orders";
+pub const DATABASE_VIEW_HIDDEN_ROWS: &str = "hidden_rows";
+pub const DATABASE_VIEW_ROW_COLORS: &str = "row_colors";
 pub const DATABASE_VIEW_FIELD_ORDERS: &str = "field_orders";
 pub const VIEW_CREATE_AT: &str = "created_at";
 pub const VIEW_MODIFY_AT: &str = "modified_at";
+pub const VIEW_CREATED_BY: &str = "created_by";
+pub const VIEW_MODIFIED_BY: &str = "modified_by";
 pub const IS_INLINE: &str = "is_inline";
 pub const VIEW_CALCULATIONS: &str = "calculations";
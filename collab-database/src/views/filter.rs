@@ -1,6 +1,150 @@
 use collab::preclude::Any;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub type FilterArray = Vec<Any>;
 pub type FilterMap = HashMap<String, Any>;
 pub type FilterMapBuilder = HashMap<String, Any>;
+
+pub const FILTER_ID: &str = "id";
+pub const FILTER_FIELD_ID: &str = "field_id";
+/// The key of the [FilterConditionType] of a filter group.
+pub const FILTER_CONDITION_TYPE: &str = "condition_type";
+/// The key of the nested filters of a filter group.
+pub const FILTER_CHILDREN: &str = "children";
+
+/// Decides how the children of a filter group are combined.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FilterConditionType {
+  #[default]
+  And = 0,
+  Or = 1,
+}
+
+impl From<i64> for FilterConditionType {
+  fn from(value: i64) -> Self {
+    match value {
+      1 => FilterConditionType::Or,
+      _ => FilterConditionType::And,
+    }
+  }
+}
+
+/// Creates a [FilterMap] that represents a group of filters. The children can be plain filters
+/// or other filter groups.
+pub fn filter_group_map(
+  id: &str,
+  condition_type: FilterConditionType,
+  children: Vec<FilterMap>,
+) -> FilterMap {
+  FilterMapBuilder::from([
+    (FILTER_ID.to_string(), Any::from(id)),
+    (
+      FILTER_CONDITION_TYPE.to_string(),
+      Any::BigInt(condition_type as i64),
+    ),
+    (FILTER_CHILDREN.to_string(), filters_to_any(children)),
+  ])
+}
+
+/// Returns true if the filter contains nested filters.
+pub fn is_filter_group(filter: &FilterMap) -> bool {
+  filter.contains_key(FILTER_CHILDREN)
+}
+
+pub fn filter_condition_type(filter: &FilterMap) -> FilterConditionType {
+  match filter.get(FILTER_CONDITION_TYPE) {
+    Some(Any::BigInt(value)) => FilterConditionType::from(*value),
+    Some(Any::Number(value)) => FilterConditionType::from(*value as i64),
+    _ => FilterConditionType::default(),
+  }
+}
+
+/// Returns the nested filters of the filter group. Returns an empty vec for a plain filter.
+pub fn filter_children(filter: &FilterMap) -> Vec<FilterMap> {
+  match filter.get(FILTER_CHILDREN) {
+    Some(Any::Array(children)) => children.iter().cloned().flat_map(filter_from_any).collect(),
+    _ => vec![],
+  }
+}
+
+/// Converts the [Any] into a [FilterMap]. The nested filters of a filter group are converted
+/// recursively, and the children that are not maps are dropped.
+pub(crate) fn filter_from_any(any: Any) -> Option<FilterMap> {
+  match any {
+    Any::Map(map) => {
+      let mut filter = map.as_ref().clone();
+      if is_filter_group(&filter) {
+        let children = filter_children(&filter);
+        filter.insert(FILTER_CHILDREN.to_string(), filters_to_any(children));
+      }
+      Some(filter)
+    },
+    _ => None,
+  }
+}
+
+fn filters_to_any(filters: Vec<FilterMap>) -> Any {
+  Any::Array(filters.into_iter().map(Any::from).collect())
+}
+
+/// Returns true if the filter or one of its nested filters has the given id.
+pub(crate) fn contains_filter(filter: &FilterMap, filter_id: &str) -> bool {
+  filter_id_matches(filter, filter_id)
+    || filter_children(filter)
+      .iter()
+      .any(|child| contains_filter(child, filter_id))
+}
+
+/// Calls `f` with the filter that has the given id, searching the nested filters recursively.
+/// Returns true if the filter was found.
+pub(crate) fn update_nested_filter<F>(
+  filter: &mut FilterMap,
+  filter_id: &str,
+  f: &mut Option<F>,
+) -> bool
+where
+  F: FnOnce(&mut FilterMap),
+{
+  if filter_id_matches(filter, filter_id) {
+    if let Some(f) = f.take() {
+      f(filter);
+    }
+    return true;
+  }
+
+  let mut children = filter_children(filter);
+  let found = children
+    .iter_mut()
+    .any(|child| update_nested_filter(child, filter_id, f));
+  if found {
+    filter.insert(FILTER_CHILDREN.to_string(), filters_to_any(children));
+  }
+  found
+}
+
+/// Removes the filters that reference a field that is not in the `field_ids`. The nested
+/// filters of a filter group are checked recursively. Returns None if the filter itself is
+/// stale.
+pub(crate) fn retain_valid_filter(
+  mut filter: FilterMap,
+  field_ids: &HashSet<String>,
+) -> Option<FilterMap> {
+  if is_filter_group(&filter) {
+    let children = filter_children(&filter)
+      .into_iter()
+      .flat_map(|child| retain_valid_filter(child, field_ids))
+      .collect();
+    filter.insert(FILTER_CHILDREN.to_string(), filters_to_any(children));
+    return Some(filter);
+  }
+
+  match filter.get(FILTER_FIELD_ID) {
+    Some(Any::String(field_id)) if !field_ids.contains(field_id.as_ref()) => None,
+    _ => Some(filter),
+  }
+}
+
+fn filter_id_matches(filter: &FilterMap, filter_id: &str) -> bool {
+  matches!(filter.get(FILTER_ID), Some(Any::String(id)) if id.as_ref() == filter_id)
+}
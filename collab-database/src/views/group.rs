@@ -12,6 +12,23 @@ pub type GroupSettingArrayUpdate = ArrayRef;
 
 /// [GroupSettingMap] contains list of key/value.
 /// One of the key/value represents as the [GroupMap]
+///
+/// A group setting groups the rows by one or more fields. The `field_id` is the primary field.
+/// When the rows are grouped by multiple fields, the `field_ids` contains the ordered list of
+/// the fields, starting with the primary field:
+///
+/// ```json
+/// {
+///     "id": "g1",
+///     "field_id": "status_field",
+///     "field_ids": ["status_field", "assignee_field"],
+///     "ty": 3,
+///     "groups": [],
+///     "content": ""
+/// }
+/// ```
+/// The group settings created before the multi-field grouping don't have the `field_ids`, and
+/// are grouped by the `field_id` only.
 pub type GroupSettingMap = HashMap<String, Any>;
 pub type GroupSettingBuilder = HashMap<String, Any>;
 
@@ -24,6 +41,10 @@ pub type GroupMapBuilder = HashMap<String, Any>;
 pub struct GroupSetting {
   pub id: String,
   pub field_id: String,
+  /// The ordered list of the fields to group by. Empty if the rows are grouped by the
+  /// [GroupSetting::field_id] only.
+  #[serde(default)]
+  pub field_ids: Vec<String>,
   #[serde(rename = "ty")]
   pub field_type: i64,
   #[serde(default)]
@@ -37,15 +58,39 @@ impl GroupSetting {
     Self {
       id: gen_database_group_id(),
       field_id,
+      field_ids: vec![],
       field_type,
       groups: vec![],
       content,
     }
   }
+
+  /// Create a group setting that groups the rows by the given fields. The first field is the
+  /// primary field.
+  pub fn new_with_field_ids(field_ids: Vec<String>, field_type: i64, content: String) -> Self {
+    Self {
+      id: gen_database_group_id(),
+      field_id: field_ids.first().cloned().unwrap_or_default(),
+      field_ids,
+      field_type,
+      groups: vec![],
+      content,
+    }
+  }
+
+  /// Returns the ordered list of the fields to group by, starting with the primary field.
+  pub fn field_ids(&self) -> Vec<String> {
+    if self.field_ids.is_empty() {
+      vec![self.field_id.clone()]
+    } else {
+      self.field_ids.clone()
+    }
+  }
 }
 
 const GROUP_ID: &str = "id";
 const FIELD_ID: &str = "field_id";
+const FIELD_IDS: &str = "field_ids";
 const FIELD_TYPE: &str = "ty";
 const GROUPS: &str = "groups";
 const CONTENT: &str = "content";
@@ -61,13 +106,50 @@ impl TryFrom<GroupSettingMap> for GroupSetting {
 impl From<GroupSetting> for GroupSettingMap {
   fn from(setting: GroupSetting) -> Self {
     let groups = to_any(&setting.groups).unwrap_or_else(|_| Any::Array(Arc::from([])));
-    GroupSettingBuilder::from([
+    let mut builder = GroupSettingBuilder::from([
       (GROUP_ID.into(), setting.id.into()),
       (FIELD_ID.into(), setting.field_id.into()),
       (FIELD_TYPE.into(), Any::BigInt(setting.field_type)),
       (GROUPS.into(), groups),
       (CONTENT.into(), setting.content.into()),
-    ])
+    ]);
+    if !setting.field_ids.is_empty() {
+      set_group_setting_field_ids(&mut builder, setting.field_ids);
+    }
+    builder
+  }
+}
+
+/// Set the ordered list of the fields to group by. The first field becomes the primary field,
+/// so the clients that only know the `field_id` keep grouping by the primary field.
+pub fn set_group_setting_field_ids(builder: &mut GroupSettingBuilder, field_ids: Vec<String>) {
+  if let Some(field_id) = field_ids.first() {
+    builder.insert(FIELD_ID.into(), field_id.as_str().into());
+  }
+  let field_ids = field_ids.into_iter().map(Any::from).collect::<Vec<_>>();
+  builder.insert(FIELD_IDS.into(), Any::Array(Arc::from(field_ids)));
+}
+
+/// Returns the ordered list of the fields to group by. The group settings without the
+/// `field_ids` are grouped by the `field_id` only.
+pub fn group_setting_field_ids(setting: &GroupSettingMap) -> Vec<String> {
+  let field_ids = match setting.get(FIELD_IDS) {
+    Some(Any::Array(field_ids)) => field_ids
+      .iter()
+      .flat_map(|field_id| match field_id {
+        Any::String(field_id) => Some(field_id.to_string()),
+        _ => None,
+      })
+      .collect(),
+    _ => vec![],
+  };
+  if !field_ids.is_empty() {
+    return field_ids;
+  }
+
+  match setting.get(FIELD_ID) {
+    Some(Any::String(field_id)) => vec![field_id.to_string()],
+    _ => vec![],
   }
 }
 
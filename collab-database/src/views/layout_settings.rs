@@ -3,6 +3,9 @@ use serde_repr::{Deserialize_repr, Serialize_repr};
 use yrs::{encoding::serde::from_any, Any};
 
 use super::{LayoutSetting, LayoutSettingBuilder};
+use crate::entity::FieldType;
+use crate::error::DatabaseError;
+use crate::fields::Field;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalendarLayoutSetting {
@@ -52,6 +55,47 @@ impl CalendarLayoutSetting {
       field_id,
     }
   }
+
+  /// Create a [CalendarLayoutSetting] from the given [LayoutSetting]. Unlike the [From]
+  /// conversion, it returns an error instead of panicking if the setting is malformed.
+  pub fn try_from_layout_setting(setting: LayoutSetting) -> Result<Self, DatabaseError> {
+    from_any(&Any::from(setting))
+      .map_err(|err| DatabaseError::InvalidLayoutSetting(err.to_string()))
+  }
+
+  /// Checks that the calendar is laid out by an existing date field and that the first day of
+  /// the week is valid, i.e. 0 (Sunday) to 6 (Saturday).
+  pub fn validate(&self, fields: &[Field]) -> Result<(), DatabaseError> {
+    if self.field_id.is_empty() {
+      return Err(DatabaseError::InvalidLayoutSetting(
+        "the calendar field is missing".to_string(),
+      ));
+    }
+
+    let field = fields
+      .iter()
+      .find(|field| field.id == self.field_id)
+      .ok_or_else(|| {
+        DatabaseError::InvalidLayoutSetting(format!(
+          "the calendar field {} is not found",
+          self.field_id
+        ))
+      })?;
+    if !FieldType::from(field.field_type).is_date() {
+      return Err(DatabaseError::InvalidLayoutSetting(format!(
+        "the calendar field {} is not a date field",
+        self.field_id
+      )));
+    }
+
+    if !(0..7).contains(&self.first_day_of_week) {
+      return Err(DatabaseError::InvalidLayoutSetting(format!(
+        "the first day of week {} is invalid",
+        self.first_day_of_week
+      )));
+    }
+    Ok(())
+  }
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Default, Serialize_repr, Deserialize_repr)]
@@ -90,12 +134,30 @@ pub struct BoardLayoutSetting {
   pub hide_ungrouped_column: bool,
   #[serde(default)]
   pub collapse_hidden_groups: bool,
+  /// The ids of the group columns that are collapsed in the board.
+  #[serde(default)]
+  pub collapsed_group_ids: Vec<String>,
 }
 
 impl BoardLayoutSetting {
   pub fn new() -> Self {
     Self::default()
   }
+
+  pub fn is_group_collapsed(&self, group_id: &str) -> bool {
+    self.collapsed_group_ids.iter().any(|id| id == group_id)
+  }
+
+  /// Collapse or expand the group column. The order of the collapsed groups is kept.
+  pub fn set_group_collapsed(&mut self, group_id: &str, collapsed: bool) {
+    if collapsed {
+      if !self.is_group_collapsed(group_id) {
+        self.collapsed_group_ids.push(group_id.to_string());
+      }
+    } else {
+      self.collapsed_group_ids.retain(|id| id != group_id);
+    }
+  }
 }
 
 impl From<LayoutSetting> for BoardLayoutSetting {
@@ -115,6 +177,45 @@ impl From<BoardLayoutSetting> for LayoutSetting {
         "collapse_hidden_groups".into(),
         setting.collapse_hidden_groups.into(),
       ),
+      (
+        "collapsed_group_ids".into(),
+        Any::Array(
+          setting
+            .collapsed_group_ids
+            .into_iter()
+            .map(Any::from)
+            .collect(),
+        ),
+      ),
     ])
   }
 }
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct GridLayoutSetting {
+  /// The number of the leading columns that stay visible when the grid is scrolled
+  /// horizontally. 0 means no column is frozen.
+  #[serde(default)]
+  pub frozen_field_count: u32,
+}
+
+impl GridLayoutSetting {
+  pub fn new() -> Self {
+    Self::default()
+  }
+}
+
+impl From<LayoutSetting> for GridLayoutSetting {
+  fn from(setting: LayoutSetting) -> Self {
+    from_any(&Any::from(setting)).unwrap()
+  }
+}
+
+impl From<GridLayoutSetting> for LayoutSetting {
+  fn from(setting: GridLayoutSetting) -> Self {
+    LayoutSettingBuilder::from([(
+      "frozen_field_count".into(),
+      Any::BigInt(setting.frozen_field_count as i64),
+    )])
+  }
+}
@@ -1,6 +1,16 @@
 use collab::preclude::Any;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub type SortArray = Vec<Any>;
 pub type SortMap = HashMap<String, Any>;
 pub type SortMapBuilder = HashMap<String, Any>;
+
+pub const SORT_FIELD_ID: &str = "field_id";
+
+/// Returns false if the sort references a field that is not in the `field_ids`.
+pub(crate) fn is_valid_sort(sort: &SortMap, field_ids: &HashSet<String>) -> bool {
+  match sort.get(SORT_FIELD_ID) {
+    Some(Any::String(field_id)) => field_ids.contains(field_id.as_ref()),
+    _ => true,
+  }
+}
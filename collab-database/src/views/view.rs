@@ -1,18 +1,19 @@
 use collab::preclude::{
   Any, Array, ArrayRef, FillRef, Map, MapExt, MapRef, ReadTxn, ToJson, TransactionMut, YrsValue,
 };
-use collab::util::AnyExt;
-use std::collections::HashMap;
+use collab::util::{AnyExt, ArrayExt};
+use std::collections::{HashMap, HashSet};
 use tracing::trace;
 
 use super::CalculationMap;
 
 use crate::entity::{DatabaseView, DatabaseViewMeta};
+use crate::rows::RowId;
 
 use crate::views::define::*;
 use crate::views::layout::{DatabaseLayout, LayoutSettings};
 use crate::views::{
-  FieldOrder, FieldOrderArray, FieldSettingsByFieldIdMap, FilterArray, FilterMap,
+  filter_from_any, FieldOrder, FieldOrderArray, FieldSettingsByFieldIdMap, FilterArray, FilterMap,
   GroupSettingArray, GroupSettingMap, LayoutSetting, RowOrder, RowOrderArray, SortArray, SortMap,
 };
 use crate::{impl_any_update, impl_i64_update, impl_order_update, impl_str_update};
@@ -43,6 +44,8 @@ pub enum OrderObjectPosition {
   Start,
   Before(String),
   After(String),
+  /// Insert at the given index. The index is clamped to the length of the array.
+  Index(u32),
   #[default]
   End,
 }
@@ -76,6 +79,17 @@ impl<'a, 'b> DatabaseViewUpdate<'a, 'b> {
   impl_i64_update!(set_created_at, set_created_at_if_not_none, VIEW_CREATE_AT);
   impl_i64_update!(set_modified_at, set_modified_at_if_not_none, VIEW_MODIFY_AT);
   impl_str_update!(set_name, set_name_if_not_none, VIEW_NAME);
+  impl_str_update!(
+    set_description,
+    set_description_if_not_none,
+    VIEW_DESCRIPTION
+  );
+  impl_i64_update!(set_created_by, set_created_by_if_not_none, VIEW_CREATED_BY);
+  impl_i64_update!(
+    set_modified_by,
+    set_modified_by_if_not_none,
+    VIEW_MODIFIED_BY
+  );
 
   impl_any_update!(
     set_layout_type,
@@ -86,7 +100,7 @@ impl<'a, 'b> DatabaseViewUpdate<'a, 'b> {
 
   impl_order_update!(
     set_row_orders,
-    remove_row_order,
+    remove_row_order_only,
     move_row_order,
     insert_row_order,
     iter_mut_row_order,
@@ -106,6 +120,143 @@ impl<'a, 'b> DatabaseViewUpdate<'a, 'b> {
     FieldOrderArray
   );
 
+  /// Move the row order with the given id to the given index. The index is clamped to the
+  /// bounds of the row orders.
+  pub fn move_row_order_to_index(self, row_id: &str, index: u32) -> Self {
+    if let Some(array) = self
+      .map_ref
+      .get_with_txn::<_, ArrayRef>(self.txn, DATABASE_VIEW_ROW_ORDERS)
+      .map(RowOrderArray::new)
+    {
+      array.move_to_index(self.txn, row_id, index);
+    }
+    self
+  }
+
+  /// Reorder the row orders of the current view to follow the given row ids. The heights of the
+  /// existing row orders are kept. The ids that aren't in the row orders are ignored and the row
+  /// orders that aren't in the given ids are appended to the end in their current order.
+  pub fn reorder_row_orders(self, ordered_row_ids: &[RowId]) -> Self {
+    if let Some(array) = self
+      .map_ref
+      .get_with_txn::<_, ArrayRef>(self.txn, DATABASE_VIEW_ROW_ORDERS)
+      .map(RowOrderArray::new)
+    {
+      let existing_row_orders = array.get_objects_with_txn(self.txn);
+      let mut row_orders_by_id = existing_row_orders
+        .iter()
+        .map(|row_order| (row_order.id.clone(), row_order.clone()))
+        .collect::<HashMap<_, _>>();
+      let mut row_orders = ordered_row_ids
+        .iter()
+        .flat_map(|row_id| row_orders_by_id.remove(row_id))
+        .collect::<Vec<_>>();
+      row_orders.extend(
+        existing_row_orders
+          .into_iter()
+          .filter(|row_order| row_orders_by_id.contains_key(&row_order.id)),
+      );
+
+      // The longest run of rows that already follow the new order stays in place, only the
+      // displaced rows are removed and inserted at their new index
+      let new_indexes = row_orders
+        .iter()
+        .enumerate()
+        .map(|(index, row_order)| (row_order.id.clone(), index))
+        .collect::<HashMap<_, _>>();
+      let stable_indexes = longest_increasing_subsequence(
+        &existing_row_orders
+          .iter()
+          .map(|row_order| new_indexes[&row_order.id])
+          .collect::<Vec<_>>(),
+      );
+      for (index, row_order) in existing_row_orders.iter().enumerate().rev() {
+        if !stable_indexes.contains(&new_indexes[&row_order.id]) {
+          array.array_ref().remove(self.txn, index as u32);
+        }
+      }
+      for (index, row_order) in row_orders.into_iter().enumerate() {
+        if !stable_indexes.contains(&index) {
+          array.array_ref().insert(self.txn, index as u32, row_order);
+        }
+      }
+    }
+    self
+  }
+
+  /// Remove the row order with the given id. The color and the hidden state of the row are
+  /// removed as well.
+  pub fn remove_row_order(self, row_id: &str) -> Self {
+    self
+      .remove_row_order_only(row_id)
+      .remove_row_color(row_id)
+      .unhide_row(&RowId::from(row_id.to_string()))
+  }
+
+  /// Set the colors of the rows in the current view
+  pub fn set_row_colors(self, row_colors: HashMap<RowId, String>) -> Self {
+    let map_ref: MapRef = self.map_ref.get_or_init(self.txn, DATABASE_VIEW_ROW_COLORS);
+    for (row_id, color) in row_colors {
+      map_ref.insert(self.txn, row_id.into_inner(), color);
+    }
+    self
+  }
+
+  /// Set the color of the row in the current view
+  pub fn set_row_color(self, row_id: &str, color: &str) -> Self {
+    let map_ref: MapRef = self.map_ref.get_or_init(self.txn, DATABASE_VIEW_ROW_COLORS);
+    map_ref.insert(self.txn, row_id, color);
+    self
+  }
+
+  /// Remove the color of the row in the current view
+  pub fn remove_row_color(self, row_id: &str) -> Self {
+    if let Some(map_ref) = self
+      .map_ref
+      .get_with_txn::<_, MapRef>(self.txn, DATABASE_VIEW_ROW_COLORS)
+    {
+      map_ref.remove(self.txn, row_id);
+    }
+    self
+  }
+
+  /// Set the rows that are hidden in the current view
+  pub fn set_hidden_rows(self, hidden_rows: Vec<RowId>) -> Self {
+    let array_ref: ArrayRef = self
+      .map_ref
+      .get_or_init(self.txn, DATABASE_VIEW_HIDDEN_ROWS);
+    array_ref.clear(self.txn);
+    for row_id in hidden_rows {
+      array_ref.push_back(self.txn, row_id.into_inner());
+    }
+    self
+  }
+
+  /// Hide the row in the current view. The row order is kept, so the row keeps its position
+  /// when it's shown again.
+  pub fn hide_row(self, row_id: &RowId) -> Self {
+    let array_ref: ArrayRef = self
+      .map_ref
+      .get_or_init(self.txn, DATABASE_VIEW_HIDDEN_ROWS);
+    if index_of_hidden_row(&array_ref, self.txn, row_id).is_none() {
+      array_ref.push_back(self.txn, row_id.as_str());
+    }
+    self
+  }
+
+  /// Show the row that was hidden in the current view
+  pub fn unhide_row(self, row_id: &RowId) -> Self {
+    if let Some(array_ref) = self
+      .map_ref
+      .get_with_txn::<_, ArrayRef>(self.txn, DATABASE_VIEW_HIDDEN_ROWS)
+    {
+      if let Some(index) = index_of_hidden_row(&array_ref, self.txn, row_id) {
+        array_ref.remove(self.txn, index);
+      }
+    }
+    self
+  }
+
   /// Set layout settings of the current view
   pub fn set_layout_settings(self, layout_settings: LayoutSettings) -> Self {
     let map_ref: MapRef = self.map_ref.get_or_init(self.txn, VIEW_LAYOUT_SETTINGS);
@@ -284,13 +435,29 @@ pub fn view_id_from_value<T: ReadTxn>(value: YrsValue, txn: &T) -> Option<String
 /// A [DatabaseViewMeta] is a subset of a [DatabaseView]
 pub fn view_meta_from_value<T: ReadTxn>(value: YrsValue, txn: &T) -> Option<DatabaseViewMeta> {
   let map_ref: MapRef = value.cast().ok()?;
+  view_meta_from_map_ref(&map_ref, txn)
+}
+
+/// Return a [DatabaseViewMeta] from a map ref
+/// Only the meta fields are read, so the row orders and the other settings of the view are
+/// not decoded.
+pub fn view_meta_from_map_ref<T: ReadTxn>(map_ref: &MapRef, txn: &T) -> Option<DatabaseViewMeta> {
   let id: String = map_ref.get_with_txn(txn, VIEW_ID)?;
   let name: String = map_ref.get_with_txn(txn, VIEW_NAME).unwrap_or_default();
   let is_inline = map_ref.get_with_txn(txn, IS_INLINE).unwrap_or_default();
+  let layout = map_ref
+    .get_with_txn::<_, i64>(txn, DATABASE_VIEW_LAYOUT)
+    .map(DatabaseLayout::from)
+    .unwrap_or_default();
+  let created_at: i64 = map_ref
+    .get_with_txn(txn, VIEW_CREATE_AT)
+    .unwrap_or_default();
   Some(DatabaseViewMeta {
     id,
     name,
     is_inline,
+    layout,
+    created_at,
   })
 }
 
@@ -300,7 +467,7 @@ pub fn view_from_value<T: ReadTxn>(value: YrsValue, txn: &T) -> Option<DatabaseV
   view_from_map_ref(&map_ref, txn)
 }
 
-fn array_of_maps(array_ref: ArrayRef, txn: &impl ReadTxn) -> Vec<HashMap<String, Any>> {
+pub(crate) fn array_of_maps(array_ref: ArrayRef, txn: &impl ReadTxn) -> Vec<HashMap<String, Any>> {
   array_ref
     .to_json(txn)
     .into_array()
@@ -335,10 +502,12 @@ pub fn calculations_from_map_ref<T: ReadTxn>(txn: &T, map_ref: &MapRef) -> Vec<C
 }
 
 /// Return a new list of [FilterMap]s from a map ref
+/// The nested filters of the filter groups are converted recursively.
 pub fn filters_from_map_ref<T: ReadTxn>(txn: &T, map_ref: &MapRef) -> Vec<FilterMap> {
   map_ref
     .get_with_txn::<_, ArrayRef>(txn, DATABASE_VIEW_FILTERS)
-    .map(|array_ref| array_of_maps(array_ref, txn))
+    .and_then(|array_ref| array_ref.to_json(txn).into_array())
+    .map(|filters| filters.into_iter().flat_map(filter_from_any).collect())
     .unwrap_or_default()
 }
 
@@ -397,6 +566,10 @@ pub fn view_from_map_ref<T: ReadTxn>(map_ref: &MapRef, txn: &T) -> Option<Databa
     .map(|array_ref| RowOrderArray::new(array_ref).get_objects_with_txn(txn))
     .unwrap_or_default();
 
+  let hidden_rows = hidden_rows_from_map_ref(txn, map_ref);
+
+  let row_colors = row_colors_from_map_ref(txn, map_ref);
+
   let field_orders = map_ref
     .get_with_txn::<_, ArrayRef>(txn, DATABASE_VIEW_FIELD_ORDERS)
     .map(|array_ref| FieldOrderArray::new(array_ref).get_objects_with_txn(txn))
@@ -410,6 +583,12 @@ pub fn view_from_map_ref<T: ReadTxn>(map_ref: &MapRef, txn: &T) -> Option<Databa
     .get_with_txn(txn, VIEW_MODIFY_AT)
     .unwrap_or_default();
 
+  let description: String = map_ref
+    .get_with_txn(txn, VIEW_DESCRIPTION)
+    .unwrap_or_default();
+  let created_by: Option<i64> = map_ref.get_with_txn(txn, VIEW_CREATED_BY);
+  let modified_by: Option<i64> = map_ref.get_with_txn(txn, VIEW_MODIFIED_BY);
+
   let field_settings = map_ref
     .get_with_txn::<_, MapRef>(txn, DATABASE_VIEW_FIELD_SETTINGS)
     .map(|map_ref| FieldSettingsByFieldIdMap::from((txn, &map_ref)))
@@ -421,24 +600,95 @@ pub fn view_from_map_ref<T: ReadTxn>(map_ref: &MapRef, txn: &T) -> Option<Databa
     id,
     database_id,
     name,
+    description,
     layout,
     layout_settings,
     filters,
     group_settings,
     sorts,
     row_orders,
+    hidden_rows,
+    row_colors,
     field_orders,
     field_settings,
     created_at,
     modified_at,
+    created_by,
+    modified_by,
     is_inline,
   })
 }
 
+/// Return the rows that are hidden in the view. The views created before the hidden rows were
+/// introduced don't have any hidden rows.
+pub fn hidden_rows_from_map_ref<T: ReadTxn>(txn: &T, map_ref: &MapRef) -> Vec<RowId> {
+  map_ref
+    .get_with_txn::<_, ArrayRef>(txn, DATABASE_VIEW_HIDDEN_ROWS)
+    .map(|array_ref| hidden_rows_from_array(&array_ref, txn))
+    .unwrap_or_default()
+}
+
+/// Return the colors of the rows in the view
+pub fn row_colors_from_map_ref<T: ReadTxn>(txn: &T, map_ref: &MapRef) -> HashMap<RowId, String> {
+  map_ref
+    .get_with_txn::<_, MapRef>(txn, DATABASE_VIEW_ROW_COLORS)
+    .map(|map_ref| {
+      map_ref
+        .iter(txn)
+        .flat_map(|(row_id, color)| {
+          let color = color.cast::<String>().ok()?;
+          Some((RowId::from(row_id.to_string()), color))
+        })
+        .collect()
+    })
+    .unwrap_or_default()
+}
+
+pub(crate) fn hidden_rows_from_array<T: ReadTxn>(array_ref: &ArrayRef, txn: &T) -> Vec<RowId> {
+  array_ref
+    .iter(txn)
+    .flat_map(|value| value.cast::<String>().ok())
+    .map(RowId::from)
+    .collect()
+}
+
+fn index_of_hidden_row<T: ReadTxn>(array_ref: &ArrayRef, txn: &T, row_id: &RowId) -> Option<u32> {
+  array_ref
+    .iter(txn)
+    .position(|value| matches!(value, YrsValue::Any(Any::String(id)) if &*id == row_id.as_str()))
+    .map(|index| index as u32)
+}
+
 pub trait OrderIdentifiable {
   fn identify_id(&self) -> String;
 }
 
+/// Returns the values of the longest strictly increasing subsequence of the given values
+fn longest_increasing_subsequence(values: &[usize]) -> HashSet<usize> {
+  // tails[n] is the position of the smallest last value of the subsequences of length n + 1
+  let mut tails: Vec<usize> = vec![];
+  let mut prev_positions: Vec<Option<usize>> = vec![None; values.len()];
+  for (position, value) in values.iter().enumerate() {
+    let len = tails.partition_point(|tail| values[*tail] < *value);
+    if len > 0 {
+      prev_positions[position] = Some(tails[len - 1]);
+    }
+    if len == tails.len() {
+      tails.push(position);
+    } else {
+      tails[len] = position;
+    }
+  }
+
+  let mut subsequence = HashSet::new();
+  let mut position = tails.last().copied();
+  while let Some(current) = position {
+    subsequence.insert(values[current]);
+    position = prev_positions[current];
+  }
+  subsequence
+}
+
 /// The [OrderArray] trait provides a set of methods to manipulate an array of [OrderIdentifiable] objects.
 pub trait OrderArray {
   type Object: OrderIdentifiable + Into<Any>;
@@ -467,6 +717,33 @@ pub trait OrderArray {
     self.array_ref().push_back(txn, object);
   }
 
+  /// Insert the given object to the array at the given position.
+  fn insert_with_txn(
+    &self,
+    txn: &mut TransactionMut,
+    object: Self::Object,
+    position: &OrderObjectPosition,
+  ) {
+    match position {
+      OrderObjectPosition::Start => self.push_front_with_txn(txn, object),
+      OrderObjectPosition::Before(next_object_id) => {
+        self.insert_before_with_txn(txn, object, next_object_id)
+      },
+      OrderObjectPosition::After(prev_object_id) => {
+        self.insert_after_with_txn(txn, object, prev_object_id)
+      },
+      OrderObjectPosition::Index(index) => self.insert_at_index_with_txn(txn, object, *index),
+      OrderObjectPosition::End => self.push_back_with_txn(txn, object),
+    }
+  }
+
+  /// Insert the given object to the array at the given index.
+  /// If the index is out of range, the object will be appended to the end of the array.
+  fn insert_at_index_with_txn(&self, txn: &mut TransactionMut, object: Self::Object, index: u32) {
+    let len = self.array_ref().len(txn);
+    self.array_ref().insert(txn, index.min(len), object);
+  }
+
   /// Insert the given object to the array before the given previous object.
   fn insert_before_with_txn(
     &self,
@@ -560,6 +837,23 @@ pub trait OrderArray {
     None
   }
 
+  /// Move the object with the given id to the given index. The object is removed and inserted
+  /// again in the same transaction. The index is clamped to the bounds of the array.
+  /// If the object is not found, nothing will happen.
+  fn move_to_index(&self, txn: &mut TransactionMut, id: &str, index: u32) -> Option<()> {
+    let from = self.get_position_with_txn(txn, id)?;
+    let last = self.array_ref().len(txn).saturating_sub(1);
+    if from == index.min(last) {
+      return None;
+    }
+
+    let object = self.get_object_at_index(txn, from)?;
+    trace!("Move object from {} to {}", from, index);
+    self.array_ref().remove(txn, from);
+    self.insert_at_index_with_txn(txn, object, index);
+    None
+  }
+
   /// Returns the position of the object with the given id.
   fn get_position_with_txn<T: ReadTxn>(&self, txn: &T, id: &str) -> Option<u32> {
     self
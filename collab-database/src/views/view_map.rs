@@ -4,19 +4,27 @@ use collab::preclude::{
 
 use crate::database::timestamp;
 use crate::entity::{DatabaseView, DatabaseViewMeta};
+use crate::error::DatabaseError;
 use crate::rows::RowId;
 use crate::views::define::*;
 use crate::views::{
   field_settings_from_map_ref, filters_from_map_ref, group_setting_from_map_ref,
-  layout_setting_from_map_ref, sorts_from_map_ref, subscribe_view_map_change, view_from_map_ref,
-  view_from_value, view_meta_from_value, CalculationMap, DatabaseLayout, DatabaseViewUpdate,
-  FieldOrder, FieldOrderArray, FieldSettingsByFieldIdMap, FilterMap, GroupSettingMap,
-  LayoutSetting, OrderArray, RowOrder, RowOrderArray, SortMap, ViewBuilder, ViewChangeSender,
+  hidden_rows_from_map_ref, layout_setting_from_map_ref, row_colors_from_map_ref,
+  sorts_from_map_ref, subscribe_view_map_change, view_from_map_ref, view_from_value,
+  view_meta_from_value, CalculationMap, DatabaseLayout, DatabaseViewUpdate, FieldOrder,
+  FieldOrderArray, FieldSettingsByFieldIdMap, FilterMap, GroupSettingMap, LayoutSetting,
+  OrderArray, RowOrder, RowOrderArray, SortMap, ViewBuilder, ViewChangeSender,
 };
 use collab::core::origin::CollabOrigin;
+use dashmap::DashMap;
+use std::collections::HashMap;
 use std::ops::Deref;
+use std::sync::Arc;
 
-use super::{calculations_from_map_ref, view_id_from_map_ref};
+use super::{
+  calculation_ids_from_view_map, calculations_from_map_ref, view_id_from_map_ref,
+  CalculationIdCache,
+};
 
 /// `ViewMap` manages views within a database.
 ///
@@ -37,10 +45,15 @@ use super::{calculations_from_map_ref, view_id_from_map_ref};
 ///
 pub struct DatabaseViews {
   container: MapRef,
+  /// Caches the position of each row in the row orders of each view. The positions of a view
+  /// are invalidated when the view changes and rebuilt lazily on the next lookup.
+  row_positions: RowPositionCache,
   #[allow(dead_code)]
-  view_map_subscription: Option<Subscription>,
+  view_map_subscription: Subscription,
 }
 
+pub(crate) type RowPositionCache = Arc<DashMap<String, HashMap<RowId, u32>>>;
+
 impl Deref for DatabaseViews {
   type Target = MapRef;
 
@@ -55,15 +68,54 @@ impl DatabaseViews {
     container: MapRef,
     view_change_sender: Option<ViewChangeSender>,
   ) -> Self {
-    let view_map_subscription = view_change_sender
-      .map(|sender| subscribe_view_map_change(origin, &container, sender.clone()));
+    Self::with_calculation_ids(
+      origin,
+      container,
+      view_change_sender,
+      CalculationIdCache::default(),
+    )
+  }
+
+  /// Same as [DatabaseViews::new], but reads the existing calculations of the views with the
+  /// given transaction, so the deletion of a calculation that existed before the views were
+  /// opened is notified with its id.
+  pub(crate) fn new_with_txn<T: ReadTxn>(
+    origin: CollabOrigin,
+    container: MapRef,
+    txn: &T,
+    view_change_sender: Option<ViewChangeSender>,
+  ) -> Self {
+    let calculation_ids = if view_change_sender.is_some() {
+      calculation_ids_from_view_map(&container, txn)
+    } else {
+      CalculationIdCache::default()
+    };
+    Self::with_calculation_ids(origin, container, view_change_sender, calculation_ids)
+  }
+
+  fn with_calculation_ids(
+    origin: CollabOrigin,
+    container: MapRef,
+    view_change_sender: Option<ViewChangeSender>,
+    calculation_ids: CalculationIdCache,
+  ) -> Self {
+    let row_positions = RowPositionCache::default();
+    let view_map_subscription = subscribe_view_map_change(
+      origin,
+      &container,
+      view_change_sender,
+      row_positions.clone(),
+      calculation_ids,
+    );
     Self {
       container,
+      row_positions,
       view_map_subscription,
     }
   }
 
   pub fn insert_view(&self, txn: &mut TransactionMut, view: DatabaseView) {
+    self.row_positions.remove(&view.id);
     let map_ref = self
       .container
       .insert(txn, view.id.as_str(), MapPrelim::default());
@@ -72,8 +124,11 @@ impl DatabaseViews {
         .set_view_id(&view.id)
         .set_database_id(view.database_id)
         .set_name(view.name)
+        .set_description(view.description)
         .set_created_at(view.created_at)
         .set_modified_at(view.modified_at)
+        .set_created_by_if_not_none(view.created_by)
+        .set_modified_by_if_not_none(view.modified_by)
         .set_layout_settings(view.layout_settings)
         .set_layout_type(view.layout)
         .set_field_settings(view.field_settings)
@@ -82,10 +137,28 @@ impl DatabaseViews {
         .set_sorts(view.sorts)
         .set_field_orders(view.field_orders)
         .set_row_orders(view.row_orders)
+        .set_hidden_rows(view.hidden_rows)
+        .set_row_colors(view.row_colors)
         .set_is_inline(view.is_inline);
     });
   }
 
+  pub fn get_hidden_rows<T: ReadTxn>(&self, txn: &T, view_id: &str) -> Vec<RowId> {
+    if let Some(map_ref) = self.container.get_with_txn(txn, view_id) {
+      hidden_rows_from_map_ref(txn, &map_ref)
+    } else {
+      vec![]
+    }
+  }
+
+  pub fn get_row_colors<T: ReadTxn>(&self, txn: &T, view_id: &str) -> HashMap<RowId, String> {
+    if let Some(map_ref) = self.container.get_with_txn(txn, view_id) {
+      row_colors_from_map_ref(txn, &map_ref)
+    } else {
+      HashMap::new()
+    }
+  }
+
   pub fn get_view_group_setting<T: ReadTxn>(&self, txn: &T, view_id: &str) -> Vec<GroupSettingMap> {
     if let Some(map_ref) = self.container.get_with_txn(txn, view_id) {
       group_setting_from_map_ref(txn, &map_ref)
@@ -150,6 +223,10 @@ impl DatabaseViews {
     view_from_map_ref(&map_ref, txn)
   }
 
+  pub fn contains_view<T: ReadTxn>(&self, txn: &T, view_id: &str) -> bool {
+    self.container.get(txn, view_id).is_some()
+  }
+
   pub fn get_all_views<T: ReadTxn>(&self, txn: &T) -> Vec<DatabaseView> {
     self
       .container
@@ -158,6 +235,12 @@ impl DatabaseViews {
       .collect::<Vec<_>>()
   }
 
+  /// Returns the meta of the view without decoding the rest of the view.
+  pub fn get_view_meta<T: ReadTxn>(&self, txn: &T, view_id: &str) -> Option<DatabaseViewMeta> {
+    let map_ref = self.container.get_with_txn(txn, view_id)?;
+    view_meta_from_map_ref(&map_ref, txn)
+  }
+
   pub fn get_all_views_meta<T: ReadTxn>(&self, txn: &T) -> Vec<DatabaseViewMeta> {
     self
       .container
@@ -215,6 +298,7 @@ impl DatabaseViews {
   where
     F: FnMut(&mut RowOrder),
   {
+    self.row_positions.remove(view_id);
     if let Some(row_order_map) = self
       .container
       .get_with_txn::<_, MapRef>(txn, view_id)
@@ -229,10 +313,28 @@ impl DatabaseViews {
     }
   }
 
+  /// Returns the index of the row in the row orders of the view.
+  /// The positions are looked up in the cache, which is rebuilt if the row orders of the view
+  /// have changed since the last lookup.
   pub fn get_row_index<T: ReadTxn>(&self, txn: &T, view_id: &str, row_id: &RowId) -> Option<u32> {
+    if let Some(positions) = self.row_positions.get(view_id) {
+      return positions.get(row_id).copied();
+    }
+
     let map: MapRef = self.container.get_with_txn(txn, view_id)?;
-    let row_order_array: ArrayRef = map.get_with_txn(txn, DATABASE_VIEW_ROW_ORDERS)?;
-    RowOrderArray::new(row_order_array).get_position_with_txn(txn, row_id.as_str())
+    let row_order_array = RowOrderArray::new(map.get_with_txn(txn, DATABASE_VIEW_ROW_ORDERS)?);
+    let positions = row_order_array
+      .array_ref()
+      .iter(txn)
+      .enumerate()
+      .flat_map(|(index, value)| {
+        let row_order = row_order_array.object_from_value(value, txn)?;
+        Some((row_order.id, index as u32))
+      })
+      .collect::<HashMap<_, _>>();
+    let index = positions.get(row_id).copied();
+    self.row_positions.insert(view_id.to_string(), positions);
+    index
   }
 
   pub fn get_field_orders<T: ReadTxn>(&self, txn: &T, view_id: &str) -> Vec<FieldOrder> {
@@ -248,26 +350,33 @@ impl DatabaseViews {
       .unwrap_or_default()
   }
 
-  pub fn update_database_view<F>(&self, txn: &mut TransactionMut, view_id: &str, f: F)
+  /// Update the view with the given id. Returns [DatabaseError::DatabaseViewNotExist] without
+  /// touching the document if the view doesn't exist.
+  pub fn update_database_view<F>(
+    &self,
+    txn: &mut TransactionMut,
+    view_id: &str,
+    f: F,
+  ) -> Result<(), DatabaseError>
   where
     F: FnOnce(DatabaseViewUpdate),
   {
-    if let Some(map_ref) = self.container.get_with_txn::<_, MapRef>(txn, view_id) {
-      let mut update = DatabaseViewUpdate::new(txn, &map_ref);
-      update = update.set_modified_at(timestamp());
-      f(update)
-    } else {
-      tracing::error!(
-        "Can't update the database view:{}. The view is not found",
-        view_id
-      )
-    }
+    let map_ref = self
+      .container
+      .get_with_txn::<_, MapRef>(txn, view_id)
+      .ok_or(DatabaseError::DatabaseViewNotExist)?;
+    self.row_positions.remove(view_id);
+    let mut update = DatabaseViewUpdate::new(txn, &map_ref);
+    update = update.set_modified_at(timestamp());
+    f(update);
+    Ok(())
   }
 
   pub fn update_all_views<F>(&self, txn: &mut TransactionMut, f: F)
   where
     F: Fn(String, DatabaseViewUpdate),
   {
+    self.row_positions.clear();
     let map_refs: Vec<_> = self
       .container
       .iter(txn)
@@ -283,10 +392,12 @@ impl DatabaseViews {
   }
 
   pub fn clear(&self, txn: &mut TransactionMut) {
+    self.row_positions.clear();
     self.container.clear(txn);
   }
 
   pub fn delete_view(&self, txn: &mut TransactionMut, view_id: &str) {
+    self.row_positions.remove(view_id);
     self.container.remove(txn, view_id);
   }
 }
@@ -1,17 +1,23 @@
 use crate::entity::DatabaseView;
+use crate::rows::RowId;
 use crate::views::define::*;
 use crate::views::{
-  row_order_from_value, view_from_map_ref, view_from_value, view_id_from_map_ref, DatabaseLayout,
-  FieldOrder, FilterMap, GroupMap, RowOrder, SortMap,
+  array_of_maps, hidden_rows_from_array, row_order_from_value, view_from_map_ref, view_from_value,
+  view_id_from_map_ref, CalculationMap, DatabaseLayout, FieldOrder, FilterMap, GroupMap, RowOrder,
+  RowPositionCache, SortMap,
 };
 use collab::core::origin::CollabOrigin;
 use collab::preclude::array::ArrayEvent;
 use collab::preclude::map::MapEvent;
+use collab::preclude::{Array, ArrayRef, Map, MapExt, ReadTxn, YrsValue};
 use collab::preclude::{Change, MapRef, Subscription, ToJson, TransactionMut};
-use collab::preclude::{DeepObservable, EntryChange, Event, PathSegment};
+use collab::preclude::{DeepObservable, EntryChange, Event, Path, PathSegment};
 use collab::util::AnyExt;
+use dashmap::DashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
 use std::str::FromStr;
+use std::sync::Arc;
 use tokio::sync::broadcast;
 use tracing::{trace, warn};
 
@@ -60,6 +66,18 @@ pub enum DatabaseViewChange {
   DidUpdateSort {
     view_id: String,
   },
+  // calculation
+  DidCreateCalculations {
+    view_id: String,
+    calculations: Vec<CalculationMap>,
+  },
+  DidUpdateCalculation {
+    view_id: String,
+  },
+  DidDeleteCalculation {
+    view_id: String,
+    calculation_id: String,
+  },
   // field order
   DidCreateFieldOrder {
     view_id: String,
@@ -69,34 +87,149 @@ pub enum DatabaseViewChange {
     view_id: String,
     field_order: FieldOrder,
   },
+  // row color
+  DidUpdateRowColor {
+    view_id: String,
+    row_id: RowId,
+  },
+  // hidden rows
+  DidUpdateHiddenRows {
+    view_id: String,
+    hidden_rows: Vec<RowId>,
+  },
+  // field settings
+  DidUpdateFieldSettings {
+    view_id: String,
+    field_ids: Vec<String>,
+    is_local_change: bool,
+  },
 }
 
 pub type ViewChangeSender = broadcast::Sender<DatabaseViewChange>;
 pub type ViewChangeReceiver = broadcast::Receiver<DatabaseViewChange>;
 
+/// Caches the calculation ids of each view. The removed items of an array event don't carry
+/// their content, so the ids are compared with the cached ones to find the deleted calculations.
+pub(crate) type CalculationIdCache = Arc<DashMap<String, Vec<String>>>;
+
+/// Seed the cache with the existing calculations of the views, so the first deletion of a
+/// calculation can be resolved to its id.
+pub(crate) fn calculation_ids_from_view_map<T: ReadTxn>(
+  view_map: &MapRef,
+  txn: &T,
+) -> CalculationIdCache {
+  let calculation_ids = CalculationIdCache::default();
+  for (view_id, value) in view_map.iter(txn) {
+    if let Ok(view) = value.cast::<MapRef>() {
+      cache_calculation_ids(&calculation_ids, view_id, &view, txn);
+    }
+  }
+  calculation_ids
+}
+
 pub(crate) fn subscribe_view_map_change(
   origin: CollabOrigin,
   view_map: &MapRef,
-  change_tx: ViewChangeSender,
+  change_tx: Option<ViewChangeSender>,
+  row_positions: RowPositionCache,
+  calculation_ids: CalculationIdCache,
 ) -> Subscription {
   view_map.observe_deep(move |txn, events| {
     let txn_origin = CollabOrigin::from(txn);
     let is_local = txn_origin == origin;
+    // The field settings of multiple fields can be updated in one transaction, so the field ids
+    // are collected and sent in one event per view.
+    let mut field_settings_changes: HashMap<String, Vec<String>> = HashMap::new();
     for event in events.iter() {
+      invalidate_row_positions(&row_positions, event);
+      let change_tx = match &change_tx {
+        None => continue,
+        Some(change_tx) => change_tx,
+      };
+
       match event {
         Event::Text(_) => {},
         Event::Array(array_event) => {
-          handle_array_event(&change_tx, txn, array_event, is_local);
+          handle_array_event(&change_tx, txn, array_event, is_local, &calculation_ids);
         },
         Event::Map(event) => {
-          handle_map_event(&change_tx, txn, event, is_local);
+          let path = event.path();
+          if is_filter_path(&path) {
+            // The filter or one of its nested filters was updated
+            if let Some(PathSegment::Key(view_id)) = path.front() {
+              let _ = change_tx.send(DatabaseViewChange::DidUpdateFilter {
+                view_id: view_id.to_string(),
+              });
+            }
+          } else if is_calculation_path(&path) {
+            // One of the calculations was updated
+            if let Some(PathSegment::Key(view_id)) = path.front() {
+              let _ = change_tx.send(DatabaseViewChange::DidUpdateCalculation {
+                view_id: view_id.to_string(),
+              });
+            }
+          } else if is_row_colors_path(&path) {
+            // The colors of the rows were set or removed
+            if let Some(PathSegment::Key(view_id)) = path.front() {
+              for row_id in event.keys(txn).keys() {
+                let _ = change_tx.send(DatabaseViewChange::DidUpdateRowColor {
+                  view_id: view_id.to_string(),
+                  row_id: RowId::from(row_id.to_string()),
+                });
+              }
+            }
+          } else if is_layout_settings_path(&path) {
+            // The layout setting of one of the layouts was inserted or updated
+            if let Some(PathSegment::Key(view_id)) = path.front() {
+              for layout_type in layout_types_from_map_event(txn, event, &path) {
+                let _ = change_tx.send(DatabaseViewChange::LayoutSettingChanged {
+                  view_id: view_id.to_string(),
+                  layout_type,
+                });
+              }
+            }
+          } else {
+            if let Some((view_id, field_ids)) = field_ids_from_map_event(txn, event, &path) {
+              let changed_field_ids = field_settings_changes.entry(view_id).or_default();
+              for field_id in field_ids {
+                if !changed_field_ids.contains(&field_id) {
+                  changed_field_ids.push(field_id);
+                }
+              }
+            }
+            if !is_field_settings_path(&path) {
+              handle_map_event(&change_tx, txn, event, is_local, &calculation_ids);
+            }
+          }
         },
         _ => {},
       }
     }
+
+    if let Some(change_tx) = &change_tx {
+      for (view_id, field_ids) in field_settings_changes {
+        let _ = change_tx.send(DatabaseViewChange::DidUpdateFieldSettings {
+          view_id,
+          field_ids,
+          is_local_change: is_local,
+        });
+      }
+    }
   })
 }
 
+/// Removes the cached row positions of the view that the event belongs to. The positions will
+/// be rebuilt on the next lookup. Events on the root map insert or remove views, so all cached
+/// positions are removed.
+fn invalidate_row_positions(row_positions: &RowPositionCache, event: &Event) {
+  match event.path().front() {
+    Some(PathSegment::Key(view_id)) => {
+      row_positions.remove(view_id.as_ref());
+    },
+    _ => row_positions.clear(),
+  }
+}
+
 /// Handles an array modification process consisting of retain and remove operations.
 ///
 /// # Process
@@ -124,9 +257,11 @@ fn handle_array_event(
   txn: &TransactionMut,
   array_event: &ArrayEvent,
   is_local_change: bool,
+  calculation_ids: &CalculationIdCache,
 ) {
   let mut offset = 0;
   let key = ArrayChangeKey::from(array_event);
+  let mut did_remove_calculation = false;
   let mut delete_row_indexes: Vec<u32> = vec![];
   let mut insert_row_orders: Vec<(RowOrder, u32)> = vec![];
   if let Some(PathSegment::Key(view_id)) = array_event.path().front() {
@@ -176,6 +311,19 @@ fn handle_array_event(
                 change_tx.send(DatabaseViewChange::DidCreateGroupSettings { view_id, groups });
             }
           },
+          ArrayChangeKey::HiddenRows => {},
+          ArrayChangeKey::Calculation => {
+            if let Some(view_id) = view_id_from_array_event(array_event) {
+              let calculations = values
+                .iter()
+                .flat_map(|value| value.to_json(txn).into_map())
+                .collect::<Vec<_>>();
+              let _ = change_tx.send(DatabaseViewChange::DidCreateCalculations {
+                view_id,
+                calculations,
+              });
+            }
+          },
           ArrayChangeKey::Unhandled(s) => {
             trace!("database view observe unknown insert: {}", s);
           },
@@ -206,6 +354,10 @@ fn handle_array_event(
                 let _ = change_tx.send(DatabaseViewChange::DidUpdateGroupSetting { view_id });
               }
             },
+            ArrayChangeKey::HiddenRows => {},
+            ArrayChangeKey::Calculation => {
+              did_remove_calculation = true;
+            },
             ArrayChangeKey::Unhandled(_s) => {
               #[cfg(feature = "verbose_log")]
               trace!("database view observe unknown remove: {}", _s);
@@ -220,6 +372,39 @@ fn handle_array_event(
       }
     });
 
+    if matches!(key, ArrayChangeKey::HiddenRows) {
+      let _ = change_tx.send(DatabaseViewChange::DidUpdateHiddenRows {
+        view_id: database_view_id.clone(),
+        hidden_rows: hidden_rows_from_array(array_event.target(), txn),
+      });
+    }
+
+    if matches!(key, ArrayChangeKey::Calculation) {
+      let current_ids = calculation_ids_from_array(array_event.target(), txn);
+      let prev_ids = calculation_ids.insert(database_view_id.clone(), current_ids.clone());
+      if did_remove_calculation {
+        match prev_ids {
+          Some(prev_ids) => {
+            let current_ids = current_ids.iter().collect::<HashSet<_>>();
+            for calculation_id in prev_ids {
+              if !current_ids.contains(&calculation_id) {
+                let _ = change_tx.send(DatabaseViewChange::DidDeleteCalculation {
+                  view_id: database_view_id.clone(),
+                  calculation_id,
+                });
+              }
+            }
+          },
+          None => {
+            // The calculations of the view were never observed, so the deleted ids are unknown
+            let _ = change_tx.send(DatabaseViewChange::DidUpdateCalculation {
+              view_id: database_view_id.clone(),
+            });
+          },
+        }
+      }
+    }
+
     if !insert_row_orders.is_empty() || !delete_row_indexes.is_empty() {
       let _ = change_tx.send(DatabaseViewChange::DidUpdateRowOrders {
         database_view_id,
@@ -246,15 +431,59 @@ fn handle_map_event(
   txn: &TransactionMut,
   event: &MapEvent,
   _is_local_change: bool,
+  calculation_ids: &CalculationIdCache,
 ) {
   let keys = event.keys(txn);
   for (key, value) in keys.iter() {
     let _change_tx = change_tx.clone();
     match value {
+      // The calculations array is created when the first calculation of the view is inserted
+      EntryChange::Inserted(value) if &**key == VIEW_CALCULATIONS => {
+        if let Ok(array_ref) = value.clone().cast::<ArrayRef>() {
+          let view_id = view_id_from_map_ref(event.target(), txn);
+          calculation_ids.insert(view_id.clone(), calculation_ids_from_array(&array_ref, txn));
+          let calculations = array_of_maps(array_ref, txn);
+          let _ = change_tx.send(DatabaseViewChange::DidCreateCalculations {
+            view_id,
+            calculations,
+          });
+        }
+      },
+      // The hidden rows array is created when the first row of the view is hidden
+      EntryChange::Inserted(value) if &**key == DATABASE_VIEW_HIDDEN_ROWS => {
+        if let Ok(array_ref) = value.clone().cast::<ArrayRef>() {
+          let _ = change_tx.send(DatabaseViewChange::DidUpdateHiddenRows {
+            view_id: view_id_from_map_ref(event.target(), txn),
+            hidden_rows: hidden_rows_from_array(&array_ref, txn),
+          });
+        }
+      },
+      // The row colors map is created when the first row of the view is colored
+      EntryChange::Inserted(YrsValue::YMap(map_ref)) if &**key == DATABASE_VIEW_ROW_COLORS => {
+        let view_id = view_id_from_map_ref(event.target(), txn);
+        for row_id in map_ref.keys(txn) {
+          let _ = change_tx.send(DatabaseViewChange::DidUpdateRowColor {
+            view_id: view_id.clone(),
+            row_id: RowId::from(row_id.to_string()),
+          });
+        }
+      },
+      // The views created before the description was introduced don't have the key, so the
+      // first description of the view is inserted rather than updated
+      EntryChange::Inserted(_) if &**key == VIEW_DESCRIPTION => {
+        if let Some(database_view) = view_from_map_ref(event.target(), txn) {
+          let _ = change_tx.send(DatabaseViewChange::DidUpdateView {
+            view: database_view,
+          });
+        }
+      },
       EntryChange::Inserted(value) => {
         let database_view = view_from_value(value.clone(), txn);
         // trace!("database view map inserted: {}:{:?}", key, database_view,);
         if let Some(database_view) = database_view {
+          if let Ok(view_map) = value.clone().cast::<MapRef>() {
+            cache_calculation_ids(calculation_ids, &database_view.id, &view_map, txn);
+          }
           let _ = change_tx.send(DatabaseViewChange::DidCreateView {
             view: database_view,
           });
@@ -304,17 +533,27 @@ enum ArrayChangeKey {
   Filter,
   Sort,
   Group,
+  Calculation,
+  HiddenRows,
 }
 
 impl From<&ArrayEvent> for ArrayChangeKey {
   fn from(event: &ArrayEvent) -> Self {
-    match event.path().pop_back() {
+    let mut path = event.path();
+    // The nested filters of the filter groups are stored under the filters array
+    if is_filter_path(&path) {
+      return Self::Filter;
+    }
+
+    match path.pop_back() {
       Some(segment) => match segment {
         PathSegment::Key(s) => match s.as_ref() {
           DATABASE_VIEW_ROW_ORDERS => Self::RowOrder,
           DATABASE_VIEW_FILTERS => Self::Filter,
           DATABASE_VIEW_SORTS => Self::Sort,
           DATABASE_VIEW_GROUPS => Self::Group,
+          VIEW_CALCULATIONS => Self::Calculation,
+          DATABASE_VIEW_HIDDEN_ROWS => Self::HiddenRows,
           _ => Self::Unhandled(s.deref().to_string()),
         },
         PathSegment::Index(_) => Self::Unhandled("index".to_string()),
@@ -324,6 +563,108 @@ impl From<&ArrayEvent> for ArrayChangeKey {
   }
 }
 
+/// Returns true if the path points to the filters array of a view or to one of its nested
+/// filters, e.g. `[view_id, filters, 0, children]`.
+fn is_filter_path(path: &Path) -> bool {
+  matches!(path.get(1), Some(PathSegment::Key(key)) if key.as_ref() == DATABASE_VIEW_FILTERS)
+}
+
+/// Returns true if the path points to one of the calculations of a view, e.g.
+/// `[view_id, calculations, 0]`.
+fn is_calculation_path(path: &Path) -> bool {
+  matches!(path.get(1), Some(PathSegment::Key(key)) if key.as_ref() == VIEW_CALCULATIONS)
+}
+
+/// Returns true if the path points to the row colors map of a view, e.g.
+/// `[view_id, row_colors]`.
+fn is_row_colors_path(path: &Path) -> bool {
+  matches!(path.get(1), Some(PathSegment::Key(key)) if key.as_ref() == DATABASE_VIEW_ROW_COLORS)
+}
+
+/// Returns true if the path points to the layout settings map of a view or to the setting of
+/// one of its layouts, e.g. `[view_id, layout_settings, 0]`.
+fn is_layout_settings_path(path: &Path) -> bool {
+  matches!(path.get(1), Some(PathSegment::Key(key)) if key.as_ref() == VIEW_LAYOUT_SETTINGS)
+}
+
+fn layout_types_from_map_event(
+  txn: &TransactionMut,
+  event: &MapEvent,
+  path: &Path,
+) -> Vec<DatabaseLayout> {
+  match path.get(2) {
+    Some(PathSegment::Key(layout)) => DatabaseLayout::from_str(layout).ok().into_iter().collect(),
+    Some(PathSegment::Index(_)) => vec![],
+    None => event
+      .keys(txn)
+      .keys()
+      .flat_map(|layout| DatabaseLayout::from_str(layout).ok())
+      .collect(),
+  }
+}
+
+/// Returns true if the path points to the field settings map of a view or to the settings of
+/// one of its fields, e.g. `[view_id, field_settings, field_id]`.
+fn is_field_settings_path(path: &Path) -> bool {
+  matches!(path.get(1), Some(PathSegment::Key(key)) if key.as_ref() == DATABASE_VIEW_FIELD_SETTINGS)
+}
+
+/// Returns the view id and the ids of the fields whose settings were changed by the map event.
+/// The field settings map of a view is created when the settings of a field are set for the
+/// first time, so the inserted field settings map is handled as well.
+fn field_ids_from_map_event(
+  txn: &TransactionMut,
+  event: &MapEvent,
+  path: &Path,
+) -> Option<(String, Vec<String>)> {
+  let view_id = match path.front() {
+    Some(PathSegment::Key(view_id)) => view_id.to_string(),
+    _ => return None,
+  };
+
+  if is_field_settings_path(path) {
+    let field_ids = match path.get(2) {
+      Some(PathSegment::Key(field_id)) => vec![field_id.to_string()],
+      Some(PathSegment::Index(_)) => return None,
+      None => event.keys(txn).keys().map(|key| key.to_string()).collect(),
+    };
+    Some((view_id, field_ids))
+  } else if path.len() == 1 {
+    match event.keys(txn).get(DATABASE_VIEW_FIELD_SETTINGS)? {
+      EntryChange::Inserted(YrsValue::YMap(map_ref)) => {
+        let field_ids = map_ref.keys(txn).map(|key| key.to_string()).collect();
+        Some((view_id, field_ids))
+      },
+      _ => None,
+    }
+  } else {
+    None
+  }
+}
+
+fn cache_calculation_ids<T: ReadTxn>(
+  calculation_ids: &CalculationIdCache,
+  view_id: &str,
+  view_map: &MapRef,
+  txn: &T,
+) {
+  if let Some(array_ref) = view_map.get_with_txn::<_, ArrayRef>(txn, VIEW_CALCULATIONS) {
+    calculation_ids.insert(
+      view_id.to_string(),
+      calculation_ids_from_array(&array_ref, txn),
+    );
+  }
+}
+
+fn calculation_ids_from_array<T: ReadTxn>(array_ref: &ArrayRef, txn: &T) -> Vec<String> {
+  array_ref
+    .iter(txn)
+    .flat_map(|value| value.cast::<MapRef>().ok())
+    .flat_map(|map_ref| map_ref.get_id(txn))
+    .map(|id| id.to_string())
+    .collect()
+}
+
 fn view_id_from_array_event(event: &ArrayEvent) -> Option<String> {
   let path = event.path();
   if path.len() > 1 {
@@ -1,17 +1,27 @@
 use crate::database::timestamp;
+use crate::entity::FieldType;
 use crate::error::DatabaseError;
+use crate::fields::relation_type_option::RelationTypeOption;
+use crate::fields::Field;
+use crate::workspace_database::{
+  subscribe_workspace_database_change, WorkspaceDatabaseChangeReceiver,
+  WorkspaceDatabaseChangeSender,
+};
 use anyhow::anyhow;
 use collab::core::collab::DataSource;
 use collab::core::origin::CollabOrigin;
 use collab::entity::EncodedCollab;
 use collab::preclude::{
-  Array, ArrayPrelim, ArrayRef, Collab, Map, MapExt, MapPrelim, MapRef, ReadTxn, TransactionMut,
-  YrsValue,
+  Array, ArrayPrelim, ArrayRef, Collab, Map, MapExt, MapPrelim, MapRef, ReadTxn, Subscription,
+  TransactionMut, YrsValue,
 };
 use collab_entity::define::WORKSPACE_DATABASES;
 use collab_entity::CollabType;
+use dashmap::DashMap;
 use std::borrow::{Borrow, BorrowMut};
 use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use tokio::sync::broadcast;
 
 /// Used to store list of [DatabaseMeta].
 pub struct WorkspaceDatabase {
@@ -53,6 +63,11 @@ impl WorkspaceDatabase {
     self.collab.remove_all_plugins();
   }
 
+  /// Subscribes to the changes of the [DatabaseMeta]s, see [WorkspaceDatabaseBody::subscribe_change]
+  pub fn subscribe_change(&self) -> WorkspaceDatabaseChangeReceiver {
+    self.body.subscribe_change()
+  }
+
   /// Create a new [DatabaseMeta] for the given database id and view id
   /// use [Self::update_database] to attach more views to the existing database.
   ///
@@ -73,6 +88,13 @@ impl WorkspaceDatabase {
     txn
   }
 
+  /// Registers all the given databases within a single transaction, so only one update is
+  /// generated. Returns the ids of the databases that were skipped because they already exist.
+  pub fn add_databases(&mut self, metas: Vec<(String, Vec<String>)>) -> Vec<String> {
+    let mut txn = self.collab.transact_mut();
+    self.body.add_databases(&mut txn, metas)
+  }
+
   /// Update the database by the given id
   pub fn update_database(
     &mut self,
@@ -84,6 +106,18 @@ impl WorkspaceDatabase {
     txn
   }
 
+  /// Move the database with the given id to the trash
+  pub fn trash_database(&mut self, database_id: &str) -> bool {
+    let mut txn = self.collab.transact_mut();
+    self.body.trash_database(&mut txn, database_id)
+  }
+
+  /// Move the database with the given id out of the trash
+  pub fn restore_database(&mut self, database_id: &str) -> bool {
+    let mut txn = self.collab.transact_mut();
+    self.body.restore_database(&mut txn, database_id)
+  }
+
   /// Delete the database by the given id
   pub fn delete_database(&mut self, database_id: &str) -> TransactionMut {
     let mut txn = self.collab.transact_mut();
@@ -91,38 +125,78 @@ impl WorkspaceDatabase {
     txn
   }
 
+  /// Remove the view from the linked views of the database with the given id, see
+  /// [WorkspaceDatabaseBody::detach_view].
+  pub fn detach_view(&mut self, database_id: &str, view_id: &str) -> DetachResult {
+    let mut txn = self.collab.transact_mut();
+    self.body.detach_view(&mut txn, database_id, view_id)
+  }
+
   /// Test if the database with the given id exists
   pub fn contains(&self, database_id: &str) -> bool {
     let txn = self.collab.transact();
     self.body.contains_database(&txn, database_id)
   }
 
-  /// Return all databases with a Transaction
+  /// Return all databases with a Transaction, including the ones in the trash
   pub fn get_all_database_meta(&self) -> Vec<DatabaseMeta> {
     let txn = self.collab.transact();
     self.body.get_all_meta(&txn)
   }
 
+  /// Return all databases, the ones in the trash are filtered out unless `include_trashed` is true
+  pub fn get_all_database_meta_with_trash(&self, include_trashed: bool) -> Vec<DatabaseMeta> {
+    let txn = self.collab.transact();
+    self.body.get_all_meta_with_trash(&txn, include_trashed)
+  }
+
   /// Return the a [DatabaseMeta] with the given view id
   pub fn get_database_meta_with_view_id(&self, view_id: &str) -> Option<DatabaseMeta> {
-    let all = self.get_all_database_meta();
-    all
-      .into_iter()
-      .find(|record| record.linked_views.iter().any(|id| id == view_id))
+    let txn = self.collab.transact();
+    self.body.get_database_meta_with_view_id(&txn, view_id)
   }
 
   pub fn get_database_meta(&self, database_id: &str) -> Option<DatabaseMeta> {
-    let all = self.get_all_database_meta();
-    all
-      .into_iter()
-      .find(|record| record.database_id == database_id)
+    let txn = self.collab.transact();
+    self.body.get_database_meta(&txn, database_id)
+  }
+
+  /// Returns the [DatabaseMeta] of the database that the relation field links to. Returns None
+  /// if the field is not a relation field or the related database is not in the workspace.
+  pub fn get_related_database_meta(&self, field: &Field) -> Option<DatabaseMeta> {
+    if FieldType::from(field.field_type) != FieldType::Relation {
+      return None;
+    }
+    let type_option = field.get_type_option::<RelationTypeOption>(FieldType::Relation.type_id())?;
+    self.get_database_meta(&type_option.database_id)
   }
 
   pub fn validate(&self) -> Result<(), DatabaseError> {
+    self.validate_with_duplicates(false)
+  }
+
+  /// Same as [Self::validate], but also returns [DatabaseError::DuplicateDatabaseMeta] if
+  /// `report_duplicates` is true and there are duplicated [DatabaseMeta]s. The duplicates are not
+  /// fixed, use [Self::dedup_database_meta] for that.
+  pub fn validate_with_duplicates(&self, report_duplicates: bool) -> Result<(), DatabaseError> {
     CollabType::WorkspaceDatabase.validate_require_data(&self.collab)?;
+    if report_duplicates {
+      let txn = self.collab.transact();
+      let database_ids = self.body.duplicate_database_ids(&txn);
+      if !database_ids.is_empty() {
+        return Err(DatabaseError::DuplicateDatabaseMeta(database_ids));
+      }
+    }
     Ok(())
   }
 
+  /// Merges the [DatabaseMeta]s with the same database id, see
+  /// [WorkspaceDatabaseBody::dedup_database_meta].
+  pub fn dedup_database_meta(&mut self) -> usize {
+    let mut txn = self.collab.transact_mut();
+    self.body.dedup_database_meta(&mut txn)
+  }
+
   pub fn encode_collab_v1(&self) -> Result<EncodedCollab, DatabaseError> {
     self.validate()?;
     self
@@ -131,20 +205,41 @@ impl WorkspaceDatabase {
   }
 }
 
+/// The result of [WorkspaceDatabaseBody::detach_view]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetachResult {
+  /// There is no [DatabaseMeta] with the given database id
+  DatabaseNotFound,
+  /// The view is detached and the database still has linked views
+  Detached,
+  /// The last view is detached, so the [DatabaseMeta] is removed. The caller should delete the
+  /// collab of the database.
+  DatabaseRemoved,
+}
+
 /// [DatabaseMeta] is a structure used to manage and track the metadata of views associated with a particular database.
 /// It's primarily used to maintain a record of all views that are attached to a database, facilitating easier tracking and management.
 ///
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DatabaseMeta {
   pub database_id: String,
   pub created_at: i64,
   /// The first view should be the inline view
   pub linked_views: Vec<String>,
+  /// The time the database was moved to the trash. `None` if the database is not in the trash.
+  pub deleted_at: Option<i64>,
+}
+
+impl DatabaseMeta {
+  pub fn is_trashed(&self) -> bool {
+    self.deleted_at.is_some()
+  }
 }
 
 const DATABASE_TRACKER_ID: &str = "database_id";
 const DATABASE_RECORD_CREATED_AT: &str = "created_at";
 const DATABASE_RECORD_VIEWS: &str = "views";
+const DATABASE_RECORD_DELETED_AT: &str = "deleted_at";
 
 impl DatabaseMeta {
   fn fill_map_ref(self, txn: &mut TransactionMut, map_ref: &MapRef) {
@@ -155,6 +250,9 @@ impl DatabaseMeta {
       DATABASE_RECORD_VIEWS,
       ArrayPrelim::from_iter(self.linked_views),
     );
+    if let Some(deleted_at) = self.deleted_at {
+      map_ref.insert(txn, DATABASE_RECORD_DELETED_AT, deleted_at);
+    }
   }
 
   fn from_map_ref<T: ReadTxn>(txn: &T, map_ref: &MapRef) -> Option<Self> {
@@ -167,11 +265,14 @@ impl DatabaseMeta {
       .iter(txn)
       .map(|value| value.to_string(txn))
       .collect();
+    // The metas created before the trash was introduced don't have the key
+    let deleted_at: Option<i64> = map_ref.get_with_txn(txn, DATABASE_RECORD_DELETED_AT);
 
     Some(Self {
       database_id,
       created_at,
       linked_views,
+      deleted_at,
     })
   }
 }
@@ -198,8 +299,62 @@ impl BorrowMut<Collab> for WorkspaceDatabase {
   }
 }
 
+pub(crate) fn all_meta_from_array_ref<T: ReadTxn>(
+  txn: &T,
+  array_ref: &ArrayRef,
+) -> Vec<DatabaseMeta> {
+  all_meta_with_index(txn, array_ref)
+    .map(|(_, meta)| meta)
+    .collect()
+}
+
+/// Returns the [DatabaseMeta]s along with their index in the array
+fn all_meta_with_index<'a, T: ReadTxn>(
+  txn: &'a T,
+  array_ref: &ArrayRef,
+) -> impl Iterator<Item = (u32, DatabaseMeta)> + 'a {
+  array_ref.iter(txn).enumerate().flat_map(|(index, value)| {
+    let map_ref: MapRef = value.cast().ok()?;
+    let meta = DatabaseMeta::from_map_ref(txn, &map_ref)?;
+    Some((index as u32, meta))
+  })
+}
+
+/// Maps the view ids to the index of the first [DatabaseMeta] that links the view. The index is
+/// built on the first lookup and invalidated whenever the array of [DatabaseMeta]s changes.
+#[derive(Default)]
+pub(crate) struct DatabaseViewIndex(RwLock<Option<HashMap<String, u32>>>);
+
+impl DatabaseViewIndex {
+  pub(crate) fn invalidate(&self) {
+    if let Ok(mut index) = self.0.write() {
+      *index = None;
+    }
+  }
+
+  fn get_or_build<T: ReadTxn>(&self, txn: &T, array_ref: &ArrayRef, view_id: &str) -> Option<u32> {
+    if let Some(index) = self.0.read().ok()?.as_ref() {
+      return index.get(view_id).cloned();
+    }
+
+    let mut index = HashMap::new();
+    for (i, meta) in all_meta_with_index(txn, array_ref) {
+      for view_id in meta.linked_views {
+        index.entry(view_id).or_insert(i);
+      }
+    }
+    let i = index.get(view_id).cloned();
+    *self.0.write().ok()? = Some(index);
+    i
+  }
+}
+
 pub struct WorkspaceDatabaseBody {
   array_ref: ArrayRef,
+  view_index: Arc<DatabaseViewIndex>,
+  change_tx: WorkspaceDatabaseChangeSender,
+  #[allow(dead_code)]
+  subscription: Subscription,
 }
 
 impl WorkspaceDatabaseBody {
@@ -209,22 +364,87 @@ impl WorkspaceDatabaseBody {
       .data
       .get_with_txn(&txn, WORKSPACE_DATABASES)
       .ok_or_else(|| DatabaseError::NoRequiredData(WORKSPACE_DATABASES.to_string()))?;
-    Ok(Self { array_ref })
+    let metas = all_meta_from_array_ref(&txn, &array_ref);
+    Ok(Self::new(array_ref, metas))
   }
 
   pub fn create(collab: &mut Collab) -> Self {
     let mut txn = collab.context.transact_mut();
-    let array_ref = collab.data.get_or_init(&mut txn, WORKSPACE_DATABASES);
+    let array_ref: ArrayRef = collab.data.get_or_init(&mut txn, WORKSPACE_DATABASES);
+    let metas = all_meta_from_array_ref(&txn, &array_ref);
     drop(txn);
-    Self { array_ref }
+    Self::new(array_ref, metas)
+  }
+
+  fn new(array_ref: ArrayRef, metas: Vec<DatabaseMeta>) -> Self {
+    let (change_tx, _) = broadcast::channel(100);
+    let metas = Arc::new(
+      metas
+        .into_iter()
+        .map(|meta| (meta.database_id.clone(), meta))
+        .collect::<DashMap<_, _>>(),
+    );
+    let view_index = Arc::new(DatabaseViewIndex::default());
+    let subscription =
+      subscribe_workspace_database_change(&array_ref, metas, view_index.clone(), change_tx.clone());
+    Self {
+      array_ref,
+      view_index,
+      change_tx,
+      subscription,
+    }
+  }
+
+  /// Subscribes to the changes of the [DatabaseMeta]s, made either locally or by applying the
+  /// updates from other devices.
+  pub fn subscribe_change(&self) -> WorkspaceDatabaseChangeReceiver {
+    self.change_tx.subscribe()
   }
 
   pub fn push_back(&self, txn: &mut TransactionMut, value: DatabaseMeta) -> MapRef {
+    self.view_index.invalidate();
     let map_ref: MapRef = self.array_ref.push_back(txn, MapPrelim::default());
     value.fill_map_ref(txn, &map_ref);
     map_ref
   }
 
+  /// Returns the first [DatabaseMeta] with the given database id
+  pub fn get_database_meta<T: ReadTxn>(&self, txn: &T, database_id: &str) -> Option<DatabaseMeta> {
+    let index = self.index_of_database(txn, database_id)?;
+    self.get_meta_at(txn, index)
+  }
+
+  /// Returns the first [DatabaseMeta] that links the view with the given id
+  pub fn get_database_meta_with_view_id<T: ReadTxn>(
+    &self,
+    txn: &T,
+    view_id: &str,
+  ) -> Option<DatabaseMeta> {
+    let index = self
+      .view_index
+      .get_or_build(txn, &self.array_ref, view_id)?;
+    self.get_meta_at(txn, index)
+  }
+
+  /// Returns all the [DatabaseMeta]s. The ones in the trash are only included if
+  /// `include_trashed` is true.
+  pub fn get_all_meta_with_trash<T: ReadTxn>(
+    &self,
+    txn: &T,
+    include_trashed: bool,
+  ) -> Vec<DatabaseMeta> {
+    let mut metas = self.get_all_meta(txn);
+    if !include_trashed {
+      metas.retain(|meta| !meta.is_trashed());
+    }
+    metas
+  }
+
+  fn get_meta_at<T: ReadTxn>(&self, txn: &T, index: u32) -> Option<DatabaseMeta> {
+    let map_ref: MapRef = self.array_ref.get(txn, index)?.cast().ok()?;
+    DatabaseMeta::from_map_ref(txn, &map_ref)
+  }
+
   pub fn index_of_database<T: ReadTxn>(&self, txn: &T, database_id: &str) -> Option<u32> {
     self
       .array_ref
@@ -238,22 +458,11 @@ impl WorkspaceDatabaseBody {
   }
 
   pub fn get_all_meta<T: ReadTxn>(&self, txn: &T) -> Vec<DatabaseMeta> {
-    self
-      .array_ref
-      .iter(txn)
-      .flat_map(|value| {
-        let map_ref: MapRef = value.cast().ok()?;
-        DatabaseMeta::from_map_ref(txn, &map_ref)
-      })
-      .collect()
+    all_meta_from_array_ref(txn, &self.array_ref)
   }
 
   pub fn contains_database<T: ReadTxn>(&self, txn: &T, database_id: &str) -> bool {
-    self.array_ref.iter(txn).any(|value| {
-      database_id_from_value(txn, value)
-        .map(|id| id == database_id)
-        .unwrap_or(false)
-    })
+    self.index_of_database(txn, database_id).is_some()
   }
 
   pub fn add_database(&self, txn: &mut TransactionMut, database_id: &str, view_ids: Vec<String>) {
@@ -262,6 +471,7 @@ impl WorkspaceDatabaseBody {
       database_id: database_id.to_string(),
       created_at: timestamp(),
       linked_views: linked_views.into_iter().collect(),
+      deleted_at: None,
     };
     self.push_back(txn, record);
   }
@@ -277,17 +487,159 @@ impl WorkspaceDatabaseBody {
         database_id,
         created_at: timestamp(),
         linked_views: linked_views.into_iter().collect(),
+        deleted_at: None,
       };
       self.push_back(txn, record);
     }
   }
 
+  /// Adds the databases that don't exist yet and returns the ids of the skipped ones. A database
+  /// id that appears more than once in `metas` is only added once.
+  pub fn add_databases(
+    &mut self,
+    txn: &mut TransactionMut,
+    metas: Vec<(String, Vec<String>)>,
+  ) -> Vec<String> {
+    let mut database_ids: HashSet<String> = self
+      .get_all_meta(txn)
+      .into_iter()
+      .map(|meta| meta.database_id)
+      .collect();
+    let mut skipped_database_ids = vec![];
+    for (database_id, view_ids) in metas {
+      if !database_ids.insert(database_id.clone()) {
+        skipped_database_ids.push(database_id);
+        continue;
+      }
+      self.add_database(txn, &database_id, view_ids);
+    }
+    skipped_database_ids
+  }
+
+  /// Moves the database to the trash. The meta is kept, so the database can be restored with
+  /// [Self::restore_database]. Returns false if the database doesn't exist.
+  pub fn trash_database(&self, txn: &mut TransactionMut, database_id: &str) -> bool {
+    match self.get_meta_map_ref(txn, database_id) {
+      None => false,
+      Some(map_ref) => {
+        map_ref.insert(txn, DATABASE_RECORD_DELETED_AT, timestamp());
+        true
+      },
+    }
+  }
+
+  /// Moves the database out of the trash. Returns false if the database doesn't exist.
+  pub fn restore_database(&self, txn: &mut TransactionMut, database_id: &str) -> bool {
+    match self.get_meta_map_ref(txn, database_id) {
+      None => false,
+      Some(map_ref) => {
+        map_ref.remove(txn, DATABASE_RECORD_DELETED_AT);
+        true
+      },
+    }
+  }
+
+  fn get_meta_map_ref<T: ReadTxn>(&self, txn: &T, database_id: &str) -> Option<MapRef> {
+    let index = self.index_of_database(txn, database_id)?;
+    self.array_ref.get(txn, index)?.cast().ok()
+  }
+
+  /// Removes the [DatabaseMeta] permanently. Use [Self::trash_database] to move the database to
+  /// the trash instead.
   pub fn delete_database(&self, txn: &mut TransactionMut, database_id: &str) {
     if let Some(index) = self.index_of_database(txn, database_id) {
+      self.view_index.invalidate();
       self.array_ref.remove(txn, index);
     }
   }
 
+  /// Removes all the occurrences of the view from the linked views of the database. The whole
+  /// [DatabaseMeta] is removed when it no longer has any linked views.
+  pub fn detach_view(
+    &mut self,
+    txn: &mut TransactionMut,
+    database_id: &str,
+    view_id: &str,
+  ) -> DetachResult {
+    let is_empty = match self.get_database_meta(txn, database_id) {
+      None => return DetachResult::DatabaseNotFound,
+      Some(meta) => meta.linked_views.iter().all(|id| id == view_id),
+    };
+
+    if is_empty {
+      self.delete_database(txn, database_id);
+      DetachResult::DatabaseRemoved
+    } else {
+      self.update_database(txn, database_id, |record| {
+        record.linked_views.retain(|id| id != view_id);
+      });
+      DetachResult::Detached
+    }
+  }
+
+  /// Returns the ids of the databases that have more than one [DatabaseMeta]
+  pub fn duplicate_database_ids<T: ReadTxn>(&self, txn: &T) -> Vec<String> {
+    let mut database_ids = HashSet::new();
+    let mut duplicate_ids = vec![];
+    for meta in self.get_all_meta(txn) {
+      if !database_ids.insert(meta.database_id.clone())
+        && !duplicate_ids.contains(&meta.database_id)
+      {
+        duplicate_ids.push(meta.database_id);
+      }
+    }
+    duplicate_ids
+  }
+
+  /// Merges the [DatabaseMeta]s with the same database id into the first one of them. The merged
+  /// meta has the union of the linked views and the earliest created_at. It stays in the trash
+  /// only if all the duplicates are in the trash. Returns the number of removed entries.
+  pub fn dedup_database_meta(&mut self, txn: &mut TransactionMut) -> usize {
+    let mut merged_metas: Vec<DatabaseMeta> = vec![];
+    let mut merged_database_ids = HashSet::new();
+    let mut duplicate_indexes = vec![];
+    for (index, meta) in all_meta_with_index(txn, &self.array_ref).collect::<Vec<_>>() {
+      match merged_metas
+        .iter_mut()
+        .find(|merged| merged.database_id == meta.database_id)
+      {
+        None => merged_metas.push(meta),
+        Some(merged) => {
+          for view_id in meta.linked_views {
+            if !merged.linked_views.contains(&view_id) {
+              merged.linked_views.push(view_id);
+            }
+          }
+          merged.created_at = merged.created_at.min(meta.created_at);
+          merged.deleted_at = match (merged.deleted_at, meta.deleted_at) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            _ => None,
+          };
+          merged_database_ids.insert(meta.database_id);
+          duplicate_indexes.push(index);
+        },
+      }
+    }
+
+    if duplicate_indexes.is_empty() {
+      return 0;
+    }
+
+    // Remove from the end, so the indexes of the remaining entries don't change
+    self.view_index.invalidate();
+    for index in duplicate_indexes.iter().rev() {
+      self.array_ref.remove(txn, *index);
+    }
+    for merged in merged_metas
+      .into_iter()
+      .filter(|meta| merged_database_ids.contains(&meta.database_id))
+    {
+      let database_id = merged.database_id.clone();
+      self.update_database(txn, &database_id, |record| *record = merged.clone());
+    }
+    duplicate_indexes.len()
+  }
+
   pub fn update_database(
     &mut self,
     txn: &mut TransactionMut,
@@ -304,6 +656,7 @@ impl WorkspaceDatabaseBody {
       {
         if let Some(mut record) = DatabaseMeta::from_map_ref(txn, &map_ref) {
           f(&mut record);
+          self.view_index.invalidate();
           self.array_ref.remove(txn, index);
           let map_ref = self.array_ref.insert(txn, index, MapPrelim::default());
           record.fill_map_ref(txn, &map_ref);
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use collab::preclude::{ArrayRef, DeepObservable, Subscription};
+use dashmap::DashMap;
+use tokio::sync::broadcast;
+
+use crate::workspace_database::{all_meta_from_array_ref, DatabaseMeta, DatabaseViewIndex};
+
+pub type WorkspaceDatabaseChangeSender = broadcast::Sender<WorkspaceDatabaseChange>;
+pub type WorkspaceDatabaseChangeReceiver = broadcast::Receiver<WorkspaceDatabaseChange>;
+
+#[derive(Clone, Debug)]
+pub enum WorkspaceDatabaseChange {
+  DidAddDatabase { meta: DatabaseMeta },
+  DidUpdateDatabase { meta: DatabaseMeta },
+  DidDeleteDatabase { database_id: String },
+}
+
+/// Observes the array of [DatabaseMeta]s. The removed items of the array can't be read in the
+/// observer, so the changes are computed by comparing the metas with the ones of the previous
+/// transaction. Updating a [DatabaseMeta] replaces the whole item, which is reported as
+/// [WorkspaceDatabaseChange::DidUpdateDatabase] instead of a delete followed by an add.
+pub(crate) fn subscribe_workspace_database_change(
+  array_ref: &ArrayRef,
+  metas: Arc<DashMap<String, DatabaseMeta>>,
+  view_index: Arc<DatabaseViewIndex>,
+  change_tx: WorkspaceDatabaseChangeSender,
+) -> Subscription {
+  let cloned_array_ref = array_ref.clone();
+  array_ref.observe_deep(move |txn, _events| {
+    view_index.invalidate();
+    let new_metas = all_meta_from_array_ref(txn, &cloned_array_ref);
+    let mut changes = vec![];
+    for meta in new_metas.iter() {
+      match metas.get(&meta.database_id) {
+        None => changes.push(WorkspaceDatabaseChange::DidAddDatabase { meta: meta.clone() }),
+        Some(old) if *old != *meta => {
+          changes.push(WorkspaceDatabaseChange::DidUpdateDatabase { meta: meta.clone() })
+        },
+        Some(_) => {},
+      }
+    }
+
+    let deleted_database_ids = metas
+      .iter()
+      .map(|entry| entry.key().clone())
+      .filter(|database_id| {
+        !new_metas
+          .iter()
+          .any(|meta| &meta.database_id == database_id)
+      })
+      .collect::<Vec<_>>();
+    for database_id in deleted_database_ids {
+      metas.remove(&database_id);
+      changes.push(WorkspaceDatabaseChange::DidDeleteDatabase { database_id });
+    }
+    for meta in new_metas {
+      metas.insert(meta.database_id.clone(), meta);
+    }
+
+    for change in changes {
+      let _ = change_tx.send(change);
+    }
+  })
+}
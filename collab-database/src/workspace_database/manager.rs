@@ -1,13 +1,17 @@
 use crate::database::{try_fixing_database, Database, DatabaseContext, DatabaseData};
 
+use crate::blocks::FetchRowRetryPolicy;
 use crate::error::DatabaseError;
-use crate::workspace_database::body::{DatabaseMeta, WorkspaceDatabase};
+use crate::fields::Field;
+use crate::workspace_database::body::{DatabaseMeta, DetachResult, WorkspaceDatabase};
+use crate::workspace_database::WorkspaceDatabaseChangeReceiver;
 use async_trait::async_trait;
 use collab::core::collab::DataSource;
-use collab::preclude::Collab;
+use collab::preclude::updates::decoder::Decode;
+use collab::preclude::{Collab, Update};
 use collab_entity::CollabType;
 
-use collab::entity::EncodedCollab;
+use collab::entity::{EncodedCollab, EncoderVersion};
 
 use crate::entity::{CreateDatabaseParams, CreateViewParams, CreateViewParamsValidator};
 
@@ -46,6 +50,16 @@ pub trait DatabaseCollabService: Send + Sync + 'static {
   ) -> Result<EncodeCollabByOid, DatabaseError>;
 
   fn persistence(&self) -> Option<Arc<dyn DatabaseCollabPersistenceService>>;
+
+  /// The uid of the current user. It's recorded as the creator and the last modifier of the
+  /// rows. Returns `None` if the user is unknown.
+  fn uid(&self) -> Option<i64>;
+
+  /// The retry policy of fetching a row that can't be built or opened, for example, when the
+  /// row is not synced from the remote yet.
+  fn fetch_row_retry_policy(&self) -> FetchRowRetryPolicy {
+    FetchRowRetryPolicy::default()
+  }
 }
 
 pub struct NoPersistenceDatabaseCollabService;
@@ -120,6 +134,137 @@ impl DatabaseCollabService for NoPersistenceDatabaseCollabService {
   fn persistence(&self) -> Option<Arc<dyn DatabaseCollabPersistenceService>> {
     None
   }
+
+  fn uid(&self) -> Option<i64> {
+    None
+  }
+}
+
+/// A [DatabaseCollabService] that keeps the encoded collabs in memory. Unlike
+/// [NoPersistenceDatabaseCollabService], the rows can be written to the persistence and removed
+/// from the row cache, e.g. by [crate::database::Database::import_rows] and
+/// [crate::database::Database::evict_rows], so only the encoded rows are kept instead of the
+/// opened ones.
+#[derive(Default)]
+pub struct InMemoryDatabaseCollabService {
+  persistence: Arc<InMemoryDatabaseCollabPersistence>,
+}
+
+#[async_trait]
+impl DatabaseCollabService for InMemoryDatabaseCollabService {
+  async fn build_collab(
+    &self,
+    object_id: &str,
+    _object_type: CollabType,
+    encoded_collab: Option<(EncodedCollab, bool)>,
+  ) -> Result<Collab, DatabaseError> {
+    let data_source = encoded_collab
+      .map(|(encoded_collab, _)| encoded_collab)
+      .or_else(|| self.persistence.get(object_id))
+      .map(DataSource::from)
+      .unwrap_or(DataSource::Disk(None));
+    Collab::new_with_source(CollabOrigin::Empty, object_id, data_source, vec![], false)
+      .map_err(|err| DatabaseError::Internal(err.into()))
+  }
+
+  async fn get_collabs(
+    &self,
+    object_ids: Vec<String>,
+    _collab_type: CollabType,
+  ) -> Result<EncodeCollabByOid, DatabaseError> {
+    Ok(
+      object_ids
+        .into_iter()
+        .filter_map(|object_id| {
+          let encoded_collab = self.persistence.get(&object_id)?;
+          Some((object_id, encoded_collab))
+        })
+        .collect(),
+    )
+  }
+
+  fn persistence(&self) -> Option<Arc<dyn DatabaseCollabPersistenceService>> {
+    Some(self.persistence.clone())
+  }
+
+  fn uid(&self) -> Option<i64> {
+    None
+  }
+}
+
+#[derive(Default)]
+struct InMemoryDatabaseCollabPersistence {
+  collabs: DashMap<String, EncodedCollab>,
+}
+
+impl InMemoryDatabaseCollabPersistence {
+  fn get(&self, object_id: &str) -> Option<EncodedCollab> {
+    self
+      .collabs
+      .get(object_id)
+      .map(|entry| entry.value().clone())
+  }
+}
+
+impl DatabaseCollabPersistenceService for InMemoryDatabaseCollabPersistence {
+  fn load_collab(&self, collab: &mut Collab) {
+    let encoded_collab = match self.get(collab.object_id()) {
+      None => return,
+      Some(encoded_collab) => encoded_collab,
+    };
+    let update = match encoded_collab.version {
+      EncoderVersion::V1 => Update::decode_v1(&encoded_collab.doc_state),
+      EncoderVersion::V2 => Update::decode_v2(&encoded_collab.doc_state),
+    };
+    match update {
+      Ok(update) => {
+        if let Err(err) = collab.apply_update(update) {
+          error!(
+            "Failed to load collab:{}, error: {}",
+            collab.object_id(),
+            err
+          );
+        }
+      },
+      Err(err) => error!(
+        "Failed to decode collab:{}, error: {}",
+        collab.object_id(),
+        err
+      ),
+    }
+  }
+
+  fn get_encoded_collab(&self, object_id: &str, _collab_type: CollabType) -> Option<EncodedCollab> {
+    self.get(object_id)
+  }
+
+  fn delete_collab(&self, object_id: &str) -> Result<(), DatabaseError> {
+    self.collabs.remove(object_id);
+    Ok(())
+  }
+
+  fn save_collab(
+    &self,
+    object_id: &str,
+    encoded_collab: EncodedCollab,
+  ) -> Result<(), DatabaseError> {
+    self.collabs.insert(object_id.to_string(), encoded_collab);
+    Ok(())
+  }
+
+  fn is_collab_exist(&self, object_id: &str) -> bool {
+    self.collabs.contains_key(object_id)
+  }
+
+  fn flush_collabs(
+    &self,
+    encoded_collabs: Vec<(String, EncodedCollab)>,
+  ) -> Result<(), DatabaseError> {
+    for (object_id, encoded_collab) in encoded_collabs {
+      self.collabs.insert(object_id, encoded_collab);
+    }
+    Ok(())
+  }
 }
 
 pub trait DatabaseCollabPersistenceService: Send + Sync + 'static {
@@ -367,6 +512,19 @@ impl WorkspaceDatabaseManager {
     self.databases.remove(database_id);
   }
 
+  /// Move the database with the given database id to the trash. Unlike [Self::delete_database],
+  /// the collab of the database is kept, so it can be restored.
+  pub fn trash_database(&mut self, database_id: &str) -> bool {
+    self.body.trash_database(database_id)
+  }
+
+  pub fn restore_database(&mut self, database_id: &str) -> bool {
+    self.body.restore_database(database_id)
+  }
+
+  /// Close the database with the given database id. The rows of the database are loaded in the
+  /// future of the caller instead of background tasks, so there is no pending row fetch to
+  /// cancel: the fetches stop when their futures are dropped.
   pub fn close_database(&self, database_id: &str) {
     let _ = self.databases.remove(database_id);
   }
@@ -375,25 +533,59 @@ impl WorkspaceDatabaseManager {
     self.body.add_database(database_id, database_view_ids);
   }
 
+  /// Tracks all the given databases at once, see [WorkspaceDatabase::add_databases]. Returns the
+  /// ids of the databases that are already tracked.
+  pub fn track_databases(&mut self, metas: Vec<(String, Vec<String>)>) -> Vec<String> {
+    self.body.add_databases(metas)
+  }
+
+  /// Subscribes to the [DatabaseMeta]s added, updated or deleted in the workspace, including the
+  /// ones made by other devices.
+  pub fn subscribe_database_meta_change(&self) -> WorkspaceDatabaseChangeReceiver {
+    self.body.subscribe_change()
+  }
+
   /// Return all the database records.
   pub fn get_all_database_meta(&self) -> Vec<DatabaseMeta> {
     self.body.get_all_database_meta()
   }
 
+  /// Return all the database records, the ones in the trash are only included if
+  /// `include_trashed` is true.
+  pub fn get_all_database_meta_with_trash(&self, include_trashed: bool) -> Vec<DatabaseMeta> {
+    self.body.get_all_database_meta_with_trash(include_trashed)
+  }
+
   pub fn get_database_meta(&self, database_id: &str) -> Option<DatabaseMeta> {
     self.body.get_database_meta(database_id)
   }
 
+  /// Returns the [DatabaseMeta] of the database that the relation field links to, see
+  /// [WorkspaceDatabase::get_related_database_meta].
+  pub fn get_related_database_meta(&self, field: &Field) -> Option<DatabaseMeta> {
+    self.body.get_related_database_meta(field)
+  }
+
   /// Delete the view from the database with the given view id.
   /// If the view is the inline view, the database will be deleted too.
   pub async fn delete_view(&mut self, database_id: &str, view_id: &str) {
     if let Ok(database) = self.get_or_init_database(database_id).await {
       let mut lock = database.write().await;
-      lock.delete_view(view_id);
-      if lock.is_inline_view(view_id) {
-        drop(lock);
+      let is_inline_view = lock.is_inline_view(view_id);
+      let deleted_view_ids = lock.delete_view(view_id);
+      drop(lock);
+
+      if is_inline_view {
         // Delete the database if the view is the inline view.
         self.delete_database(database_id);
+      } else {
+        for deleted_view_id in deleted_view_ids {
+          if self.body.detach_view(database_id, &deleted_view_id) == DetachResult::DatabaseRemoved {
+            // The database doesn't have any view left
+            self.delete_database(database_id);
+            break;
+          }
+        }
       }
     }
   }
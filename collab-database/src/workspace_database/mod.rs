@@ -1,7 +1,9 @@
 pub use body::*;
+pub use body_observer::*;
 pub use manager::*;
 pub use relation::*;
 
 mod body;
+mod body_observer;
 mod manager;
 mod relation;
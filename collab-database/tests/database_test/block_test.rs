@@ -1,6 +1,23 @@
-use collab_database::rows::CreateRowParams;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use collab::entity::EncodedCollab;
+use collab::preclude::Collab;
+use collab_database::blocks::{BlockEvent, BlockMetrics, FetchRowRetryPolicy};
+use collab_database::database::{gen_row_id, Database, DatabaseContext};
+use collab_database::entity::{CreateDatabaseParams, CreateViewParams};
+use collab_database::error::DatabaseError;
+use collab_database::rows::{CreateRowParams, RowId};
+use collab_database::workspace_database::{
+  DatabaseCollabPersistenceService, DatabaseCollabService, EncodeCollabByOid,
+};
+use collab_entity::CollabType;
 
 use crate::database_test::helper::create_database;
+use crate::helper::make_rocks_db;
+use crate::user_test::helper::TestUserDatabaseServiceImpl;
 
 #[tokio::test]
 async fn create_rows_test() {
@@ -15,3 +32,364 @@ async fn create_rows_test() {
   let rows = database_test.get_rows_for_view("v1").await;
   assert_eq!(rows.len(), 100);
 }
+
+#[tokio::test]
+async fn fetch_row_retry_test() {
+  let (database, collab_service, row_id) = create_database_with_uncached_row().await;
+  let mut block_event_rx = database.subscribe_block_event();
+
+  // The row is fetched on the third attempt
+  collab_service.remaining_failures.store(2, Ordering::SeqCst);
+  assert!(database.get_or_init_database_row(&row_id).await.is_some());
+  assert_eq!(collab_service.remaining_failures.load(Ordering::SeqCst), 0);
+  match block_event_rx.try_recv().unwrap() {
+    BlockEvent::DidFetchRow(row_details) => assert_eq!(row_details[0].row.id, row_id),
+    event => panic!("unexpected event: {:?}", event),
+  }
+}
+
+#[tokio::test]
+async fn fetch_row_failed_after_all_attempts_test() {
+  let (database, collab_service, row_id) = create_database_with_uncached_row().await;
+  let mut block_event_rx = database.subscribe_block_event();
+
+  collab_service.remaining_failures.store(5, Ordering::SeqCst);
+  assert!(database.get_or_init_database_row(&row_id).await.is_none());
+  // Only the attempts of the retry policy are made
+  assert_eq!(collab_service.remaining_failures.load(Ordering::SeqCst), 2);
+  match block_event_rx.try_recv().unwrap() {
+    BlockEvent::DidFetchRowFailed { row_ids, .. } => assert_eq!(row_ids, vec![row_id]),
+    event => panic!("unexpected event: {:?}", event),
+  }
+}
+
+#[tokio::test]
+async fn init_database_rows_retry_test() {
+  let (database, collab_service, row_id) = create_database_with_uncached_row().await;
+  let mut block_event_rx = database.subscribe_block_event();
+
+  // The rows are fetched on the third attempt
+  collab_service
+    .remaining_batch_failures
+    .store(2, Ordering::SeqCst);
+  let rows = database
+    .body
+    .block
+    .init_database_rows(vec![row_id.clone()])
+    .await
+    .unwrap();
+  assert_eq!(rows.len(), 1);
+  assert_eq!(
+    collab_service
+      .remaining_batch_failures
+      .load(Ordering::SeqCst),
+    0
+  );
+  match block_event_rx.try_recv().unwrap() {
+    BlockEvent::DidFetchRow(row_details) => assert_eq!(row_details[0].row.id, row_id),
+    event => panic!("unexpected event: {:?}", event),
+  }
+  match block_event_rx.try_recv().unwrap() {
+    BlockEvent::DidLoadRowsProgress { loaded, total } => assert_eq!((loaded, total), (1, 1)),
+    event => panic!("unexpected event: {:?}", event),
+  }
+}
+
+#[tokio::test]
+async fn init_database_rows_failed_after_all_attempts_test() {
+  let (database, collab_service, row_id) = create_database_with_uncached_row().await;
+  let mut block_event_rx = database.subscribe_block_event();
+
+  collab_service
+    .remaining_batch_failures
+    .store(5, Ordering::SeqCst);
+  assert!(database
+    .body
+    .block
+    .init_database_rows(vec![row_id.clone()])
+    .await
+    .is_err());
+  // Only the attempts of the retry policy are made
+  assert_eq!(
+    collab_service
+      .remaining_batch_failures
+      .load(Ordering::SeqCst),
+    2
+  );
+  match block_event_rx.try_recv().unwrap() {
+    BlockEvent::DidFetchRowFailed { row_ids, .. } => assert_eq!(row_ids, vec![row_id]),
+    event => panic!("unexpected event: {:?}", event),
+  }
+  assert_eq!(database.block_metrics().failed_fetches, 1);
+}
+
+#[tokio::test]
+async fn batch_load_rows_progress_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let mut row_ids = vec![];
+  for _ in 0..10 {
+    let row_order = database_test
+      .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+      .await
+      .unwrap();
+    database_test.body.block.row_mem_cache.remove(&row_order.id);
+    row_ids.push(row_order.id);
+  }
+
+  let mut block_event_rx = database_test.subscribe_block_event();
+  database_test
+    .body
+    .block
+    .batch_load_rows(row_ids.clone(), 3)
+    .await
+    .unwrap();
+
+  let mut progress = vec![];
+  loop {
+    match block_event_rx.try_recv().unwrap() {
+      BlockEvent::DidLoadRowsProgress { loaded, total } => {
+        assert_eq!(total, 10);
+        progress.push(loaded);
+      },
+      BlockEvent::DidFetchRow(row_details) => {
+        assert_eq!(row_details.len(), 10);
+        break;
+      },
+      event => panic!("unexpected event: {:?}", event),
+    }
+  }
+  assert_eq!(progress, vec![3, 6, 9, 10]);
+  let cached_rows = row_ids
+    .iter()
+    .map(|row_id| {
+      database_test
+        .body
+        .block
+        .row_mem_cache
+        .get(row_id)
+        .unwrap()
+        .value()
+        .clone()
+    })
+    .collect::<Vec<_>>();
+
+  // The cached rows are skipped and keep their instances
+  database_test
+    .body
+    .block
+    .batch_load_rows(row_ids.clone(), 3)
+    .await
+    .unwrap();
+  assert!(block_event_rx.try_recv().is_err());
+  for (row_id, cached_row) in row_ids.iter().zip(cached_rows) {
+    let row = database_test.body.block.row_mem_cache.get(row_id).unwrap();
+    assert!(Arc::ptr_eq(row.value(), &cached_row));
+  }
+}
+
+#[tokio::test]
+async fn prefetch_rows_for_linked_view_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let mut row_ids = vec![];
+  for _ in 0..6 {
+    let row_order = database_test
+      .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+      .await
+      .unwrap();
+    row_ids.push(row_order.id);
+  }
+  database_test
+    .create_linked_view(CreateViewParams {
+      database_id: database_id.clone(),
+      view_id: "v2".to_string(),
+      ..Default::default()
+    })
+    .unwrap();
+  let reversed_row_ids = row_ids.iter().rev().cloned().collect::<Vec<_>>();
+  database_test
+    .set_row_orders("v2", reversed_row_ids.clone())
+    .unwrap();
+  for row_id in &row_ids {
+    database_test.body.block.row_mem_cache.remove(row_id);
+  }
+
+  database_test.prefetch_rows_for_view("v2", 2).await.unwrap();
+  let cached_row_ids = row_ids
+    .iter()
+    .filter(|row_id| database_test.body.block.row_mem_cache.contains_key(*row_id))
+    .cloned()
+    .collect::<Vec<_>>();
+  assert_eq!(cached_row_ids, vec![row_ids[4].clone(), row_ids[5].clone()]);
+
+  // The cached rows are skipped
+  let mut block_event_rx = database_test.subscribe_block_event();
+  database_test.prefetch_rows_for_view("v2", 2).await.unwrap();
+  assert!(block_event_rx.try_recv().is_err());
+
+  database_test.prefetch_rows_for_view("v2", 3).await.unwrap();
+  assert!(database_test
+    .body
+    .block
+    .row_mem_cache
+    .contains_key(&row_ids[3]));
+  assert_eq!(database_test.body.block.row_mem_cache.len(), 3);
+}
+
+#[tokio::test]
+async fn block_metrics_cold_then_warm_read_test() {
+  let (database, _collab_service, row_id) = create_database_with_uncached_row().await;
+  database.reset_block_metrics();
+  assert_eq!(database.block_metrics(), BlockMetrics::default());
+
+  // The first read loads the row, the second one hits the cache
+  assert!(database.get_or_init_database_row(&row_id).await.is_some());
+  let metrics = database.block_metrics();
+  assert_eq!(metrics.cache_hits, 0);
+  assert_eq!(metrics.disk_loads + metrics.remote_fetches, 1);
+  assert_eq!(metrics.cache_size, 1);
+
+  assert!(database.get_or_init_database_row(&row_id).await.is_some());
+  let metrics = database.block_metrics();
+  assert_eq!(metrics.cache_hits, 1);
+  assert_eq!(metrics.disk_loads + metrics.remote_fetches, 1);
+  assert_eq!(metrics.failed_fetches, 0);
+
+  database.reset_block_metrics();
+  let metrics = database.block_metrics();
+  assert_eq!(metrics.cache_hits, 0);
+  assert_eq!(metrics.disk_loads + metrics.remote_fetches, 0);
+  // The cache is kept when the counters are reset
+  assert_eq!(metrics.cache_size, 1);
+}
+
+#[tokio::test]
+async fn block_metrics_failed_fetch_test() {
+  let (database, collab_service, row_id) = create_database_with_uncached_row().await;
+  database.reset_block_metrics();
+  collab_service.remaining_failures.store(5, Ordering::SeqCst);
+  assert!(database.get_or_init_database_row(&row_id).await.is_none());
+  let metrics = database.block_metrics();
+  assert_eq!(metrics.failed_fetches, 1);
+  assert_eq!(metrics.cache_size, 0);
+}
+
+#[tokio::test]
+async fn fetch_not_exist_row_without_retry_test() {
+  let (database, collab_service, _) = create_database_with_uncached_row().await;
+  let mut block_event_rx = database.subscribe_block_event();
+
+  let row_id = gen_row_id();
+  assert!(database.get_or_init_database_row(&row_id).await.is_none());
+  // The row doesn't exist, so it's neither retried nor reported as a failure
+  assert_eq!(collab_service.row_build_attempts.load(Ordering::SeqCst), 1);
+  assert!(block_event_rx.try_recv().is_err());
+}
+
+async fn create_database_with_uncached_row() -> (Database, Arc<FlakyCollabService>, RowId) {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let collab_service = Arc::new(FlakyCollabService {
+    inner: TestUserDatabaseServiceImpl {
+      uid: 1,
+      workspace_id: uuid::Uuid::new_v4().to_string(),
+      db: make_rocks_db(),
+    },
+    remaining_failures: AtomicUsize::new(0),
+    row_build_attempts: AtomicUsize::new(0),
+    remaining_batch_failures: AtomicUsize::new(0),
+  });
+  let params = CreateDatabaseParams {
+    database_id: database_id.clone(),
+    views: vec![CreateViewParams {
+      database_id: database_id.clone(),
+      view_id: "v1".to_string(),
+      ..Default::default()
+    }],
+    ..Default::default()
+  };
+  let mut database =
+    Database::create_with_view(params, DatabaseContext::new(collab_service.clone()))
+      .await
+      .unwrap();
+  let row_order = database
+    .create_row(CreateRowParams::new(gen_row_id(), database_id))
+    .await
+    .unwrap();
+
+  // Remove the row from the cache, so it has to be fetched again
+  database.body.block.row_mem_cache.remove(&row_order.id);
+  (database, collab_service, row_order.id)
+}
+
+/// Fails to build the collab of a row that isn't in memory until `remaining_failures` reaches 0,
+/// and fails to fetch the collabs of the rows at once until `remaining_batch_failures` reaches 0
+struct FlakyCollabService {
+  inner: TestUserDatabaseServiceImpl,
+  remaining_failures: AtomicUsize,
+  row_build_attempts: AtomicUsize,
+  remaining_batch_failures: AtomicUsize,
+}
+
+#[async_trait]
+impl DatabaseCollabService for FlakyCollabService {
+  async fn build_collab(
+    &self,
+    object_id: &str,
+    object_type: CollabType,
+    encoded_collab: Option<(EncodedCollab, bool)>,
+  ) -> Result<Collab, DatabaseError> {
+    if object_type == CollabType::DatabaseRow && encoded_collab.is_none() {
+      self.row_build_attempts.fetch_add(1, Ordering::SeqCst);
+    }
+    if object_type == CollabType::DatabaseRow
+      && encoded_collab.is_none()
+      && self
+        .remaining_failures
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+        .is_ok()
+    {
+      return Err(DatabaseError::Internal(anyhow::anyhow!(
+        "Can't fetch the row from remote"
+      )));
+    }
+
+    self
+      .inner
+      .build_collab(object_id, object_type, encoded_collab)
+      .await
+  }
+
+  async fn get_collabs(
+    &self,
+    object_ids: Vec<String>,
+    collab_type: CollabType,
+  ) -> Result<EncodeCollabByOid, DatabaseError> {
+    if self
+      .remaining_batch_failures
+      .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+      .is_ok()
+    {
+      return Err(DatabaseError::Internal(anyhow::anyhow!(
+        "Can't fetch the rows from remote"
+      )));
+    }
+    self.inner.get_collabs(object_ids, collab_type).await
+  }
+
+  fn persistence(&self) -> Option<Arc<dyn DatabaseCollabPersistenceService>> {
+    self.inner.persistence()
+  }
+
+  fn uid(&self) -> Option<i64> {
+    self.inner.uid()
+  }
+
+  fn fetch_row_retry_policy(&self) -> FetchRowRetryPolicy {
+    FetchRowRetryPolicy {
+      max_attempts: 3,
+      initial_backoff: Duration::from_millis(1),
+      max_backoff: Duration::from_millis(10),
+    }
+  }
+}
@@ -0,0 +1,78 @@
+use crate::database_test::helper::create_database_with_default_data;
+use collab::preclude::Any;
+use collab_database::views::{
+  calculation_scope, CalculationMap, CalculationMapBuilder, CalculationScope, CALCULATION_SCOPE,
+};
+
+#[tokio::test]
+async fn update_calculation_scope_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database_with_default_data(1, &database_id).await;
+  let mut calculation = calculation_map("c1", "f1", "sum");
+  calculation.insert(
+    CALCULATION_SCOPE.to_string(),
+    CalculationScope::FilteredRows.into(),
+  );
+  database_test.update_calculation("v1", calculation).unwrap();
+
+  let calculation = database_test
+    .get_calculation::<CalculationMap>("v1", "f1")
+    .unwrap();
+  assert_eq!(
+    calculation_scope(&calculation),
+    CalculationScope::FilteredRows
+  );
+
+  // Upserting the calculation without a scope keeps the existing scope
+  database_test
+    .update_calculation("v1", calculation_map("c1", "f1", "average"))
+    .unwrap();
+  let calculations = database_test.get_all_calculations::<CalculationMap>("v1");
+  assert_eq!(calculations.len(), 1);
+  assert_eq!(calculations[0].get("type"), Some(&Any::from("average")));
+  assert_eq!(
+    calculation_scope(&calculations[0]),
+    CalculationScope::FilteredRows
+  );
+
+  let mut calculation = calculation_map("c1", "f1", "average");
+  calculation.insert(
+    CALCULATION_SCOPE.to_string(),
+    CalculationScope::AllRows.into(),
+  );
+  database_test.update_calculation("v1", calculation).unwrap();
+  let calculations = database_test.get_all_calculations::<CalculationMap>("v1");
+  assert_eq!(
+    calculation_scope(&calculations[0]),
+    CalculationScope::AllRows
+  );
+}
+
+#[tokio::test]
+async fn calculation_without_scope_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database_with_default_data(1, &database_id).await;
+  // The calculations created before the scope was introduced don't have the key
+  database_test
+    .update_calculation("v1", calculation_map("c1", "f1", "sum"))
+    .unwrap();
+
+  let calculations = database_test.get_all_calculations::<CalculationMap>("v1");
+  assert_eq!(calculations.len(), 1);
+  assert_eq!(
+    calculations[0].get(CALCULATION_SCOPE),
+    Some(&Any::BigInt(CalculationScope::AllRows as i64))
+  );
+  assert_eq!(
+    calculation_scope(&calculation_map("c2", "f2", "count")),
+    CalculationScope::AllRows
+  );
+}
+
+fn calculation_map(id: &str, field_id: &str, ty: &str) -> CalculationMap {
+  CalculationMapBuilder::from([
+    ("id".to_string(), Any::from(id)),
+    ("field_id".to_string(), Any::from(field_id)),
+    ("type".to_string(), Any::from(ty)),
+  ])
+}
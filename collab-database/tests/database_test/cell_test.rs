@@ -45,7 +45,8 @@ async fn update_cell_for_field_test() {
         cells_update.insert("f1", TestTextCell("hello world".to_string()));
       });
     })
-    .await;
+    .await
+    .unwrap();
 
   let cells = database_test.get_cells_for_field("v1", "f1").await;
   assert_eq!(
@@ -68,7 +69,8 @@ async fn update_empty_cell_for_field_test() {
         cells_update.insert("f2", TestTextCell("hello world".to_string()));
       });
     })
-    .await;
+    .await
+    .unwrap();
 
   let cells = database_test.get_cells_for_field("v1", "f2").await;
   assert_eq!(cells.len(), 3);
@@ -78,6 +80,40 @@ async fn update_empty_cell_for_field_test() {
   );
 }
 
+#[tokio::test]
+async fn remove_cell_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let first_row_id = database_test.pre_define_row_ids[0].clone();
+  database_test
+    .update_row(first_row_id.clone(), |row_update| {
+      row_update.update_cells(|cells_update| {
+        cells_update.remove("f1");
+      });
+    })
+    .await
+    .unwrap();
+
+  let row = database_test.get_row(&first_row_id).await;
+  assert!(!row.cells.contains_key("f1"));
+  assert!(row.cells.contains_key("f2"));
+
+  // Clearing a cell keeps the key in the cells
+  database_test
+    .update_row(first_row_id.clone(), |row_update| {
+      row_update.update_cells(|cells_update| {
+        cells_update.clear("f2");
+      });
+    })
+    .await
+    .unwrap();
+  let row = database_test.get_row(&first_row_id).await;
+  assert!(row.cells.contains_key("f2"));
+
+  let cell = database_test.get_cell("f1", &first_row_id).await;
+  assert!(cell.cell.is_none());
+}
+
 #[test]
 fn cells_serde_test() {
   let mut cells = Cells::new();
@@ -0,0 +1,83 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use collab::util::AnyMapExt;
+use collab_database::database::gen_row_id;
+use collab_database::database_state::DatabaseChange;
+use collab_database::fields::{Field, FieldChange};
+use collab_database::rows::{Cell, CreateRowParams, RowChange};
+use collab_database::views::{DatabaseViewChange, OrderObjectPosition};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::timeout;
+
+use crate::database_test::helper::{create_database, default_field_settings_by_layout};
+
+#[tokio::test]
+async fn observe_all_changes_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let row_id = gen_row_id();
+  database_test
+    .create_row(CreateRowParams::new(row_id.clone(), database_id.clone()))
+    .await
+    .unwrap();
+
+  let mut change_rx = database_test.subscribe_all_changes().unwrap();
+  database_test
+    .update_row(row_id, |row| {
+      row.update_cells(|cells| {
+        cells.insert_cell("f1", Cell::from([("level".into(), 1.into())]));
+      });
+    })
+    .await
+    .unwrap();
+
+  database_test.create_field(
+    None,
+    Field::new("f2".to_string(), "text field".to_string(), 0, false),
+    &OrderObjectPosition::default(),
+    default_field_settings_by_layout(),
+  );
+
+  database_test
+    .update_database_view("v1", |update| {
+      update.set_name("hello");
+    })
+    .unwrap();
+
+  // The changes are merged from different channels, so they may arrive in any order.
+  let mut received = HashSet::new();
+  while received.len() < 3 {
+    let change = timeout(Duration::from_secs(5), change_rx.recv())
+      .await
+      .unwrap()
+      .unwrap();
+    match change {
+      DatabaseChange::Row(RowChange::DidUpdateCell { field_id, new, .. })
+        if field_id == "f1" && new.get_as::<i64>("level") == Some(1) =>
+      {
+        received.insert("row")
+      },
+      DatabaseChange::Field(FieldChange::DidCreateField { field }) if field.id == "f2" => {
+        received.insert("field")
+      },
+      DatabaseChange::View(DatabaseViewChange::DidUpdateView { view }) if view.name == "hello" => {
+        received.insert("view")
+      },
+      _ => {},
+    }
+  }
+  assert_eq!(received, HashSet::from(["row", "field", "view"]));
+
+  // The forwarding task stops after the database is dropped
+  drop(database_test);
+  loop {
+    match timeout(Duration::from_secs(5), change_rx.recv())
+      .await
+      .unwrap()
+    {
+      Err(RecvError::Closed) => break,
+      _ => continue,
+    }
+  }
+}
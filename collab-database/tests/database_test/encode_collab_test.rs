@@ -1,7 +1,12 @@
-use crate::database_test::helper::create_database_with_default_data;
+use crate::database_test::helper::{
+  create_database_with_db, create_database_with_default_data, restore_database_from_db,
+};
 use assert_json_diff::assert_json_eq;
 use collab::core::origin::CollabOrigin;
 use collab::preclude::Collab;
+use collab_database::database::gen_row_id;
+use collab_database::entity::EncodeDatabaseOptions;
+use collab_database::rows::CreateRowParams;
 
 #[tokio::test]
 async fn encode_database_collab_test() {
@@ -32,3 +37,31 @@ async fn encode_database_collab_test() {
     assert_json_eq!(json, expected_json);
   }
 }
+
+#[tokio::test]
+async fn encode_database_collab_with_options_test() {
+  let workspace_id = uuid::Uuid::new_v4().to_string();
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let (db, mut database_test) = create_database_with_db(1, &workspace_id, &database_id).await;
+  let mut row_ids = vec![];
+  for _ in 0..300 {
+    let row_order = database_test
+      .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+      .await
+      .unwrap();
+    row_ids.push(row_order.id);
+  }
+  drop(database_test);
+
+  // Reopen the database, so the rows need to be initialized while encoding
+  let database_test = restore_database_from_db(1, &workspace_id, &database_id, db).await;
+  let encoded_database = database_test
+    .encode_database_collabs_with_options(EncodeDatabaseOptions { concurrency: 8 })
+    .await
+    .unwrap();
+  assert!(encoded_database.failed_rows.is_empty());
+  assert_eq!(encoded_database.encoded_row_collabs.len(), 300);
+  for (encoded_info, row_id) in encoded_database.encoded_row_collabs.iter().zip(row_ids) {
+    assert_eq!(encoded_info.object_id, row_id.to_string());
+  }
+}
@@ -13,7 +13,11 @@ async fn observe_field_update_and_delete_test() {
   let database_id = uuid::Uuid::new_v4().to_string();
   let database_test = create_database_with_default_data(1, &database_id).await;
 
-  let field = database_test.get_fields(None).pop().unwrap();
+  let field = database_test
+    .get_fields(None)
+    .into_iter()
+    .find(|field| !field.is_primary)
+    .unwrap();
 
   // Update
   let cloned_field = field.clone();
@@ -41,7 +45,7 @@ async fn observe_field_update_and_delete_test() {
   tokio::spawn(async move {
     sleep(Duration::from_millis(300)).await;
     let mut db = cloned_database_test.lock().await;
-    db.delete_field(&cloned_field.id);
+    db.delete_field(&cloned_field.id).unwrap();
   });
 
   let cloned_field = field.clone();
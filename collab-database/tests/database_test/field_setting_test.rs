@@ -50,7 +50,7 @@ async fn remove_field_remove_field_setting_test() {
   database_test.create_linked_view(params).unwrap();
 
   // Delete a field
-  database_test.delete_field("f3");
+  database_test.delete_field("f3").unwrap();
 
   let field_settings_map: HashMap<String, TestFieldSetting> =
     database_test.get_field_settings("v1", None);
@@ -78,7 +78,9 @@ async fn update_field_setting_for_some_fields_test() {
   database_test.create_linked_view(params).unwrap();
 
   // Update field settings for one field
-  database_test.update_field_settings("v1", Some(vec!["f1".to_string()]), field_settings.clone());
+  database_test
+    .update_field_settings("v1", Some(vec!["f1".to_string()]), field_settings.clone())
+    .unwrap();
 
   // on v1, the field settings for f1 should change
   let field_settings_map: HashMap<String, TestFieldSetting> =
@@ -93,7 +95,9 @@ async fn update_field_setting_for_some_fields_test() {
   assert_eq!(test_field_settings.to_owned().visibility, 0);
 
   // Update field settings for all fields
-  database_test.update_field_settings("v1", None, field_settings);
+  database_test
+    .update_field_settings("v1", None, field_settings)
+    .unwrap();
 }
 
 #[tokio::test]
@@ -106,7 +110,9 @@ async fn update_field_setting_test() {
   };
 
   // Update field settings for one field
-  database_test.update_field_settings("v1", Some(vec!["f1".to_string()]), field_settings);
+  database_test
+    .update_field_settings("v1", Some(vec!["f1".to_string()]), field_settings)
+    .unwrap();
 
   // the field settings for f1 should change
   let field_settings_map: HashMap<String, TestFieldSetting> =
@@ -126,7 +132,9 @@ async fn duplicate_view_duplicates_field_settings_test() {
   };
 
   // Update field settings for one field
-  database_test.update_field_settings("v1", Some(vec!["f1".to_string()]), field_settings);
+  database_test
+    .update_field_settings("v1", Some(vec!["f1".to_string()]), field_settings)
+    .unwrap();
 
   // the field settings for f1 should change
   let field_settings_map: HashMap<String, TestFieldSetting> =
@@ -135,7 +143,7 @@ async fn duplicate_view_duplicates_field_settings_test() {
   assert_eq!(test_field_settings.visibility, 1);
 
   // duplicate view v1
-  let duplicate_view = database_test.duplicate_linked_view("v1").unwrap();
+  let (_, duplicate_view) = database_test.duplicate_linked_view("v1").unwrap();
 
   // on the duplicate view, the field settings for f1 should be the same
   let field_settings_map: HashMap<String, TestFieldSetting> =
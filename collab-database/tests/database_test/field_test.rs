@@ -1,7 +1,16 @@
 use crate::database_test::helper::{
   create_database, create_database_with_default_data, default_field_settings_by_layout,
 };
-use collab_database::entity::CreateViewParams;
+use crate::helper::TestTextCell;
+use collab_database::database::gen_row_id;
+use collab_database::entity::{CreateViewParams, FieldType};
+use collab_database::error::DatabaseError;
+use collab_database::fields::select_type_option::{
+  SelectOptionColor, SelectOptionIds, SingleSelectTypeOption,
+};
+use collab_database::fields::{FieldChange, TypeOptionDataBuilder};
+use collab_database::rows::{Cells, CreateRowParams, RowChange};
+use collab_database::template::number_parse::NumberCellData;
 use collab_database::{fields::Field, views::OrderObjectPosition};
 
 #[tokio::test]
@@ -123,6 +132,28 @@ async fn create_field_in_view_test() {
   assert_eq!(fields[3].id, "f2");
 }
 
+#[tokio::test]
+async fn create_field_at_index_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database_with_default_data(1, &database_id).await;
+  database_test.create_field(
+    None,
+    Field::new("f4".to_string(), "text field 4".to_string(), 0, false),
+    &OrderObjectPosition::Index(1),
+    default_field_settings_by_layout(),
+  );
+  database_test.create_field(
+    None,
+    Field::new("f5".to_string(), "text field 5".to_string(), 0, false),
+    &OrderObjectPosition::Index(100),
+    default_field_settings_by_layout(),
+  );
+
+  let fields = database_test.get_fields_in_view("v1", None);
+  let field_ids = fields.into_iter().map(|field| field.id).collect::<Vec<_>>();
+  assert_eq!(field_ids, vec!["f1", "f4", "f2", "f3", "f5"]);
+}
+
 #[tokio::test]
 async fn delete_field_test() {
   let database_id = uuid::Uuid::new_v4().to_string();
@@ -130,13 +161,13 @@ async fn delete_field_test() {
   for i in 0..3 {
     database_test.create_field(
       None,
-      Field::new(format!("f{}", i), format!("text field {}", i), 0, true),
+      Field::new(format!("f{}", i), format!("text field {}", i), 0, false),
       &OrderObjectPosition::default(),
       default_field_settings_by_layout(),
     );
   }
-  database_test.delete_field("f0");
-  database_test.delete_field("f1");
+  database_test.delete_field("f0").unwrap();
+  database_test.delete_field("f1").unwrap();
   let fields = database_test.get_all_fields();
   assert_eq!(fields.len(), 1);
 }
@@ -148,7 +179,7 @@ async fn delete_field_in_views_test() {
   for i in 0..3 {
     database_test.create_field(
       None,
-      Field::new(format!("f{}", i), format!("text field {}", i), 0, true),
+      Field::new(format!("f{}", i), format!("text field {}", i), 0, false),
       &OrderObjectPosition::default(),
       default_field_settings_by_layout(),
     );
@@ -160,7 +191,7 @@ async fn delete_field_in_views_test() {
     ..Default::default()
   };
   database_test.create_linked_view(params).unwrap();
-  database_test.delete_field("f0");
+  database_test.delete_field("f0").unwrap();
 
   let fields = database_test.get_all_fields();
   assert_eq!(fields.len(), 2);
@@ -213,9 +244,11 @@ async fn get_field_in_order_test() {
   assert_eq!(fields[1].id, "f1");
   assert_eq!(fields[2].id, "f2");
 
-  database_test.update_database_view("v1", |update| {
-    update.move_field_order("f0", "f2");
-  });
+  database_test
+    .update_database_view("v1", |update| {
+      update.move_field_order("f0", "f2");
+    })
+    .unwrap();
   let fields = database_test.get_fields_in_view("v1", None);
   assert_eq!(fields[0].id, "f1");
   assert_eq!(fields[1].id, "f2");
@@ -242,9 +275,11 @@ async fn move_field_test() {
     );
   }
 
-  database_test.update_database_view("v1", |update| {
-    update.move_field_order("f2", "f0");
-  });
+  database_test
+    .update_database_view("v1", |update| {
+      update.move_field_order("f2", "f0");
+    })
+    .unwrap();
 
   let view_1 = database_test.get_view("v1").unwrap();
   assert_eq!(view_1.field_orders[0].id, "f2");
@@ -270,19 +305,346 @@ async fn move_field_to_out_of_index_test() {
     );
   }
 
-  database_test.update_database_view("v1", |update| {
-    update.move_field_order("f2", "f10");
-  });
+  database_test
+    .update_database_view("v1", |update| {
+      update.move_field_order("f2", "f10");
+    })
+    .unwrap();
   let view_1 = database_test.get_view("v1").unwrap();
   assert_eq!(view_1.field_orders[0].id, "f0");
   assert_eq!(view_1.field_orders[1].id, "f1");
   assert_eq!(view_1.field_orders[2].id, "f2");
 
-  database_test.update_database_view("v1", |update| {
-    update.move_field_order("f10", "f1");
-  });
+  database_test
+    .update_database_view("v1", |update| {
+      update.move_field_order("f10", "f1");
+    })
+    .unwrap();
   let view_1 = database_test.get_view("v1").unwrap();
   assert_eq!(view_1.field_orders[0].id, "f0");
   assert_eq!(view_1.field_orders[1].id, "f1");
   assert_eq!(view_1.field_orders[2].id, "f2");
 }
+
+#[tokio::test]
+async fn delete_primary_field_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database_with_default_data(1, &database_id).await;
+  let primary_field = database_test.get_primary_field().unwrap();
+  assert_eq!(primary_field.id, "f1");
+
+  let err = database_test.delete_field("f1").unwrap_err();
+  assert!(matches!(err, DatabaseError::CannotDeletePrimaryField));
+  assert_eq!(database_test.get_all_fields().len(), 3);
+  assert_eq!(database_test.get_view("v1").unwrap().field_orders.len(), 3);
+}
+
+#[tokio::test]
+async fn reassign_primary_field_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database_with_default_data(1, &database_id).await;
+  database_test.set_primary_field("f2").unwrap();
+
+  let primary_field = database_test.get_primary_field().unwrap();
+  assert_eq!(primary_field.id, "f2");
+  assert!(!database_test.get_field("f1").unwrap().is_primary);
+
+  database_test.delete_field("f1").unwrap();
+  let fields = database_test.get_all_fields();
+  assert_eq!(fields.len(), 2);
+  assert_eq!(fields.iter().filter(|field| field.is_primary).count(), 1);
+
+  let err = database_test.set_primary_field("f1").unwrap_err();
+  assert!(matches!(err, DatabaseError::RecordNotFound));
+}
+
+#[tokio::test]
+async fn insert_and_delete_select_option_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let field = Field::from_field_type("status", FieldType::SingleSelect, false);
+  let field_id = field.id.clone();
+  database_test.create_field(
+    None,
+    field,
+    &OrderObjectPosition::default(),
+    default_field_settings_by_layout(),
+  );
+  assert!(database_test
+    .get_field(&field_id)
+    .unwrap()
+    .get_select_options()
+    .is_empty());
+
+  database_test.update_field(&field_id, |update| {
+    update
+      .insert_select_option("Todo", SelectOptionColor::Blue)
+      .insert_select_option("Done", SelectOptionColor::Green);
+  });
+  let options = database_test
+    .get_field(&field_id)
+    .unwrap()
+    .get_select_options();
+  assert_eq!(options.len(), 2);
+  assert_eq!(options[0].name, "Todo");
+  assert_eq!(options[0].color, SelectOptionColor::Blue);
+  assert_eq!(options[1].name, "Done");
+  assert_ne!(options[0].id, options[1].id);
+
+  database_test.update_field(&field_id, |update| {
+    update.delete_select_option(&options[0].id);
+  });
+  let field = database_test.get_field(&field_id).unwrap();
+  assert_eq!(field.get_select_options(), vec![options[1].clone()]);
+  let type_option = field
+    .get_type_option::<SingleSelectTypeOption>(FieldType::SingleSelect)
+    .unwrap();
+  assert_eq!(type_option.options, vec![options[1].clone()]);
+}
+
+#[tokio::test]
+async fn insert_select_option_to_text_field_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let field = Field::from_field_type("name", FieldType::RichText, true);
+  let field_id = field.id.clone();
+  database_test.create_field(
+    None,
+    field,
+    &OrderObjectPosition::default(),
+    default_field_settings_by_layout(),
+  );
+
+  // The options are only stored for the select fields
+  database_test.update_field(&field_id, |update| {
+    update.insert_select_option("Todo", SelectOptionColor::Blue);
+  });
+  let field = database_test.get_field(&field_id).unwrap();
+  assert!(field.get_select_options().is_empty());
+  assert!(field.get_any_type_option(FieldType::SingleSelect).is_none());
+}
+
+#[tokio::test]
+async fn convert_text_field_to_select_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let field = Field::from_field_type("status", FieldType::RichText, false);
+  let field_id = field.id.clone();
+  database_test.create_field(
+    None,
+    field,
+    &OrderObjectPosition::default(),
+    default_field_settings_by_layout(),
+  );
+  let mut row_ids = vec![];
+  for text in ["Todo", "Done", "Todo, Done", "", "Todo"] {
+    let params =
+      CreateRowParams::new(gen_row_id(), database_id.clone()).with_cells(Cells::from([(
+        field_id.clone(),
+        TestTextCell::from(text).into(),
+      )]));
+    row_ids.push(database_test.create_row(params).await.unwrap().id);
+  }
+
+  let mut field_change_rx = database_test.subscribe_field_change().unwrap();
+  let mut row_change_rx = database_test.subscribe_row_change().unwrap();
+  let num_of_converted_cells = database_test
+    .convert_text_field_to_select(&field_id, FieldType::MultiSelect)
+    .await
+    .unwrap();
+  // The empty text has no option, so its cell isn't converted
+  assert_eq!(num_of_converted_cells, 4);
+
+  let field = database_test.get_field(&field_id).unwrap();
+  assert_eq!(field.field_type, i64::from(FieldType::MultiSelect));
+  let options = field.get_select_options();
+  let mut option_names = options
+    .iter()
+    .map(|option| option.name.clone())
+    .collect::<Vec<_>>();
+  option_names.sort();
+  assert_eq!(option_names, vec!["Done".to_string(), "Todo".to_string()]);
+  let option_id = |name: &str| {
+    options
+      .iter()
+      .find(|option| option.name == name)
+      .unwrap()
+      .id
+      .clone()
+  };
+
+  let expected_option_ids = [
+    Some(vec![option_id("Todo")]),
+    Some(vec![option_id("Done")]),
+    Some(vec![option_id("Todo"), option_id("Done")]),
+    None,
+    Some(vec![option_id("Todo")]),
+  ];
+  for (row_id, expected_option_ids) in row_ids.iter().zip(expected_option_ids) {
+    let cell = database_test
+      .get_cell(&field_id, row_id)
+      .await
+      .cell
+      .unwrap();
+    match expected_option_ids {
+      Some(expected_option_ids) => {
+        assert_eq!(
+          SelectOptionIds::from(&cell).into_inner(),
+          expected_option_ids
+        )
+      },
+      // The cell that can't be converted is kept
+      None => assert_eq!(TestTextCell::from(cell).0, ""),
+    }
+  }
+
+  let mut did_update_field = false;
+  while let Ok(change) = field_change_rx.try_recv() {
+    if let FieldChange::DidUpdateField { field } = change {
+      did_update_field |= field.field_type == i64::from(FieldType::MultiSelect);
+    }
+  }
+  assert!(did_update_field);
+  let (mut num_of_updated_cells, mut num_of_deleted_cells) = (0, 0);
+  while let Ok(change) = row_change_rx.try_recv() {
+    match change {
+      RowChange::DidUpdateCell { field_id: id, .. } if id == field_id => num_of_updated_cells += 1,
+      RowChange::DidDeleteCell { key, .. } if key == field_id => num_of_deleted_cells += 1,
+      _ => {},
+    }
+  }
+  assert_eq!(num_of_updated_cells, 4);
+  assert_eq!(num_of_deleted_cells, 0);
+}
+
+#[tokio::test]
+async fn convert_text_field_to_number_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let field = Field::from_field_type("price", FieldType::RichText, false);
+  let field_id = field.id.clone();
+  database_test.create_field(
+    None,
+    field,
+    &OrderObjectPosition::default(),
+    default_field_settings_by_layout(),
+  );
+  let mut row_ids = vec![];
+  for text in [" 12.5 ", "abc"] {
+    let params =
+      CreateRowParams::new(gen_row_id(), database_id.clone()).with_cells(Cells::from([(
+        field_id.clone(),
+        TestTextCell::from(text).into(),
+      )]));
+    row_ids.push(database_test.create_row(params).await.unwrap().id);
+  }
+
+  let num_of_converted_cells = database_test
+    .convert_text_field_to_number(&field_id)
+    .await
+    .unwrap();
+  assert_eq!(num_of_converted_cells, 1);
+  let field = database_test.get_field(&field_id).unwrap();
+  assert_eq!(field.field_type, i64::from(FieldType::Number));
+  assert!(field.get_any_type_option(FieldType::Number).is_some());
+  let cell = database_test
+    .get_cell(&field_id, &row_ids[0])
+    .await
+    .cell
+    .unwrap();
+  assert_eq!(NumberCellData::from(&cell).0, "12.5");
+  // The text that is not a number is kept
+  let cell = database_test
+    .get_cell(&field_id, &row_ids[1])
+    .await
+    .cell
+    .unwrap();
+  assert_eq!(TestTextCell::from(cell).0, "abc");
+}
+
+#[tokio::test]
+async fn get_fields_ordered_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let positions = [
+    OrderObjectPosition::End,
+    OrderObjectPosition::Start,
+    OrderObjectPosition::Index(1),
+    OrderObjectPosition::Before("f1".to_string()),
+  ];
+  for (i, position) in positions.iter().enumerate() {
+    database_test.create_field(
+      None,
+      Field::new(format!("f{}", i), format!("field {}", i), 0, i == 0),
+      position,
+      default_field_settings_by_layout(),
+    );
+  }
+  // A field that doesn't have a field order in the views
+  {
+    let mut txn = database_test.collab.transact_mut();
+    database_test.body.fields.insert_field(
+      &mut txn,
+      Field::new("f4".to_string(), "field 4".to_string(), 0, false),
+    );
+  }
+
+  let field_order_ids = database_test
+    .get_all_field_orders()
+    .into_iter()
+    .map(|order| order.id)
+    .collect::<Vec<_>>();
+  assert_eq!(field_order_ids, vec!["f3", "f1", "f2", "f0"]);
+
+  let field_ids = |fields: Vec<Field>| fields.into_iter().map(|field| field.id).collect::<Vec<_>>();
+  assert_eq!(
+    field_ids(database_test.get_fields_ordered(None, None)),
+    vec!["f3", "f1", "f2", "f0", "f4"]
+  );
+  assert_eq!(
+    field_ids(database_test.get_fields_ordered(Some("v1"), None)),
+    vec!["f3", "f1", "f2", "f0", "f4"]
+  );
+  assert_eq!(
+    field_ids(database_test.get_fields_ordered(
+      Some("v1"),
+      Some(vec!["f4".to_string(), "f0".to_string(), "f2".to_string()])
+    )),
+    vec!["f2", "f0", "f4"]
+  );
+}
+
+#[tokio::test]
+async fn get_field_ids_and_names_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let large_type_option =
+    TypeOptionDataBuilder::from([("content".into(), "x".repeat(8 * 1024).into())]);
+  database_test.create_field(
+    None,
+    Field::new("f1".to_string(), "large field".to_string(), 0, true)
+      .with_type_option_data("0", large_type_option),
+    &OrderObjectPosition::default(),
+    default_field_settings_by_layout(),
+  );
+  database_test.create_field(
+    None,
+    Field::new("f2".to_string(), "text field".to_string(), 0, false),
+    &OrderObjectPosition::default(),
+    default_field_settings_by_layout(),
+  );
+
+  let mut field_ids = database_test.get_field_ids();
+  field_ids.sort();
+  assert_eq!(field_ids, vec!["f1", "f2"]);
+
+  let mut field_names = database_test.get_field_names();
+  field_names.sort();
+  assert_eq!(
+    field_names,
+    vec![
+      ("f1".to_string(), "large field".to_string()),
+      ("f2".to_string(), "text field".to_string())
+    ]
+  );
+}
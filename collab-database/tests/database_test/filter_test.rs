@@ -1,5 +1,11 @@
 use crate::database_test::helper::{create_database_with_default_data, DatabaseTest};
 use crate::helper::{TestFieldType, TestFilter, FILTER_CONTENT};
+use collab::preclude::Any;
+use collab_database::error::DatabaseError;
+use collab_database::views::{
+  filter_children, filter_condition_type, filter_group_map, is_filter_group, FilterConditionType,
+  FilterMap, FILTER_CONDITION_TYPE,
+};
 
 #[tokio::test]
 async fn create_database_view_with_filter_test() {
@@ -19,9 +25,11 @@ async fn create_database_view_with_filter_test() {
 async fn insert_or_update_database_view_filter_test() {
   let mut database_test = create_database_with_two_filters().await;
   // Update
-  database_test.update_filter("v1", "filter_1", |update| {
-    update.insert(FILTER_CONTENT.into(), "Text filter".into());
-  });
+  database_test
+    .update_filter("v1", "filter_1", |update| {
+      update.insert(FILTER_CONTENT.into(), "Text filter".into());
+    })
+    .unwrap();
 
   let filter_1 = database_test
     .get_filter::<TestFilter>("v1", "filter_1")
@@ -29,16 +37,18 @@ async fn insert_or_update_database_view_filter_test() {
   assert_eq!(filter_1.content, "Text filter");
 
   // Insert
-  database_test.insert_filter(
-    "v1",
-    TestFilter {
-      id: "filter_1".to_string(),
-      field_id: "".to_string(),
-      field_type: Default::default(),
-      condition: 0,
-      content: "Override the existing filter".to_string(),
-    },
-  );
+  database_test
+    .insert_filter(
+      "v1",
+      TestFilter {
+        id: "filter_1".to_string(),
+        field_id: "".to_string(),
+        field_type: Default::default(),
+        condition: 0,
+        content: "Override the existing filter".to_string(),
+      },
+    )
+    .unwrap();
 
   let filter_1 = database_test
     .get_filter::<TestFilter>("v1", "filter_1")
@@ -51,16 +61,18 @@ async fn insert_database_view_filter_to_filtering_field_id_test() {
   let mut database_test = create_database_with_two_filters().await;
 
   // Filter with id "filter_1" already filters based on "f1"
-  database_test.insert_filter(
-    "v1",
-    TestFilter {
-      id: "filter_3".to_string(),
-      field_id: "f1".to_string(),
-      field_type: Default::default(),
-      condition: 0,
-      content: "Another filter".to_string(),
-    },
-  );
+  database_test
+    .insert_filter(
+      "v1",
+      TestFilter {
+        id: "filter_3".to_string(),
+        field_id: "f1".to_string(),
+        field_type: Default::default(),
+        condition: 0,
+        content: "Another filter".to_string(),
+      },
+    )
+    .unwrap();
 
   let filter_3 = database_test
     .get_filter::<TestFilter>("v1", "filter_3")
@@ -71,11 +83,197 @@ async fn insert_database_view_filter_to_filtering_field_id_test() {
 #[tokio::test]
 async fn remove_database_view_filter_test() {
   let mut database_test = create_database_with_two_filters().await;
-  database_test.remove_filter("v1", "filter_1");
+  database_test.remove_filter("v1", "filter_1").unwrap();
   let filter_1 = database_test.get_filter::<TestFilter>("v1", "filter_1");
   assert!(filter_1.is_none());
 }
 
+#[tokio::test]
+async fn flat_filters_are_not_filter_groups_test() {
+  let database_test = create_database_with_two_filters().await;
+  let filters = database_test.get_all_filters::<FilterMap>("v1");
+  assert_eq!(filters.len(), 2);
+  for filter in &filters {
+    assert!(!is_filter_group(filter));
+    assert!(filter_children(filter).is_empty());
+    assert_eq!(filter.len(), 5);
+  }
+
+  // The filters stored before the filter groups were introduced are read as before
+  let filter_1 = TestFilter::try_from(filters[0].clone()).unwrap();
+  assert_eq!(filter_1.id, "filter_1");
+  assert_eq!(filter_1.content, "hello filter");
+  let filter_2 = TestFilter::try_from(filters[1].clone()).unwrap();
+  assert_eq!(filter_2.field_type, TestFieldType::Number);
+}
+
+#[tokio::test]
+async fn insert_nested_filter_group_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  // (f1 = Done OR f1 = Archived) AND f2 = High
+  let status_group = filter_group_map(
+    "g2",
+    FilterConditionType::Or,
+    vec![
+      text_filter("filter_done", "f1", "Done"),
+      text_filter("filter_archived", "f1", "Archived"),
+    ],
+  );
+  let root_group = filter_group_map(
+    "g1",
+    FilterConditionType::And,
+    vec![status_group, text_filter("filter_high", "f2", "High")],
+  );
+  database_test
+    .insert_filter_group("v1", None, root_group)
+    .unwrap();
+
+  let filters = database_test.get_all_filters::<FilterMap>("v1");
+  assert_eq!(filters.len(), 1);
+  assert!(is_filter_group(&filters[0]));
+  assert_eq!(filter_condition_type(&filters[0]), FilterConditionType::And);
+  let children = filter_children(&filters[0]);
+  assert_eq!(children.len(), 2);
+  assert_eq!(filter_condition_type(&children[0]), FilterConditionType::Or);
+  let status_filters = filter_children(&children[0]);
+  assert_eq!(status_filters.len(), 2);
+  let filter = TestFilter::try_from(status_filters[1].clone()).unwrap();
+  assert_eq!(filter.content, "Archived");
+  let filter = TestFilter::try_from(children[1].clone()).unwrap();
+  assert_eq!(filter.content, "High");
+
+  // Insert into the top-level group and the nested group
+  database_test
+    .insert_filter_group("v1", Some("g1"), text_filter("filter_low", "f2", "Low"))
+    .unwrap();
+  database_test
+    .insert_filter_group("v1", Some("g2"), text_filter("filter_todo", "f1", "Todo"))
+    .unwrap();
+  let filters = database_test.get_all_filters::<FilterMap>("v1");
+  let children = filter_children(&filters[0]);
+  assert_eq!(children.len(), 3);
+  assert_eq!(filter_children(&children[0]).len(), 3);
+  let filter = TestFilter::try_from(children[2].clone()).unwrap();
+  assert_eq!(filter.content, "Low");
+
+  let err = database_test
+    .insert_filter_group("v1", Some("g100"), text_filter("filter", "f1", ""))
+    .unwrap_err();
+  assert!(matches!(err, DatabaseError::RecordNotFound));
+}
+
+#[tokio::test]
+async fn update_nested_filter_group_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let status_group = filter_group_map(
+    "g2",
+    FilterConditionType::Or,
+    vec![text_filter("filter_done", "f1", "Done")],
+  );
+  database_test
+    .insert_filter_group(
+      "v1",
+      None,
+      filter_group_map("g1", FilterConditionType::And, vec![status_group]),
+    )
+    .unwrap();
+
+  database_test
+    .update_filter_group("v1", "g2", |group| {
+      group.insert(
+        FILTER_CONDITION_TYPE.to_string(),
+        Any::BigInt(FilterConditionType::And as i64),
+      );
+    })
+    .unwrap();
+  database_test
+    .update_filter_group("v1", "filter_done", |filter| {
+      filter.insert(FILTER_CONTENT.to_string(), "Archived".into());
+    })
+    .unwrap();
+
+  let filters = database_test.get_all_filters::<FilterMap>("v1");
+  let status_group = filter_children(&filters[0]).remove(0);
+  assert_eq!(
+    filter_condition_type(&status_group),
+    FilterConditionType::And
+  );
+  let filter = TestFilter::try_from(filter_children(&status_group).remove(0)).unwrap();
+  assert_eq!(filter.content, "Archived");
+
+  let err = database_test
+    .update_filter_group("v1", "filter_100", |_| {})
+    .unwrap_err();
+  assert!(matches!(err, DatabaseError::RecordNotFound));
+}
+
+#[tokio::test]
+async fn delete_field_removes_stale_filters_test() {
+  let mut database_test = create_database_with_two_filters().await;
+  database_test
+    .insert_filter_group(
+      "v1",
+      None,
+      filter_group_map(
+        "g1",
+        FilterConditionType::Or,
+        vec![
+          text_filter("filter_3", "f2", "a"),
+          text_filter("filter_4", "f3", "b"),
+        ],
+      ),
+    )
+    .unwrap();
+
+  database_test.delete_field("f2").unwrap();
+  let filters = database_test.get_all_filters::<FilterMap>("v1");
+  assert_eq!(filters.len(), 2);
+  assert!(database_test
+    .get_filter::<TestFilter>("v1", "filter_2")
+    .is_none());
+
+  // The stale filter nested in the group is removed as well
+  let children = filter_children(&filters[1]);
+  assert_eq!(children.len(), 1);
+  assert_eq!(
+    TestFilter::try_from(children[0].clone()).unwrap().id,
+    "filter_4"
+  );
+}
+
+#[tokio::test]
+async fn get_validated_filters_test() {
+  let mut database_test = create_database_with_two_filters().await;
+  database_test
+    .insert_filter("v1", text_filter("filter_3", "f100", ""))
+    .unwrap();
+
+  assert_eq!(database_test.get_all_filters::<TestFilter>("v1").len(), 3);
+  let filters = database_test.get_all_filters_validated::<TestFilter>("v1");
+  assert_eq!(filters.len(), 2);
+  assert!(filters.iter().all(|filter| filter.id != "filter_3"));
+
+  database_test.remove_stale_view_settings("v1").unwrap();
+  assert_eq!(database_test.get_all_filters::<TestFilter>("v1").len(), 2);
+
+  let err = database_test
+    .remove_stale_view_settings("v100")
+    .unwrap_err();
+  assert!(matches!(err, DatabaseError::DatabaseViewNotExist));
+}
+
+fn text_filter(id: &str, field_id: &str, content: &str) -> FilterMap {
+  FilterMap::from(TestFilter {
+    id: id.to_string(),
+    field_id: field_id.to_string(),
+    field_type: TestFieldType::RichText,
+    condition: 0,
+    content: content.to_string(),
+  })
+}
+
 async fn create_database_with_two_filters() -> DatabaseTest {
   let database_id = uuid::Uuid::new_v4();
   let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
@@ -94,8 +292,8 @@ async fn create_database_with_two_filters() -> DatabaseTest {
     content: "".to_string(),
   };
 
-  database_test.insert_filter("v1", filter_1);
-  database_test.insert_filter("v1", filter_2);
+  database_test.insert_filter("v1", filter_1).unwrap();
+  database_test.insert_filter("v1", filter_2).unwrap();
 
   database_test
 }
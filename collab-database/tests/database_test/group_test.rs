@@ -1,7 +1,10 @@
 use collab::preclude::Any;
 use collab::util::{AnyExt, AnyMapExt};
 use collab_database::entity::CreateViewParams;
-use collab_database::views::{DatabaseLayout, GroupMap};
+use collab_database::views::{
+  group_setting_field_ids, DatabaseLayout, GroupMap, GroupSetting, GroupSettingMap,
+};
+use serde_json::json;
 
 use crate::database_test::helper::{create_database_with_default_data, DatabaseTest};
 use crate::helper::{TestGroup, TestGroupSetting, CONTENT, GROUPS};
@@ -46,7 +49,9 @@ async fn create_database_view_with_group_test2() {
     ],
     content: "".to_string(),
   };
-  database_test.insert_group_setting("v1", group_setting);
+  database_test
+    .insert_group_setting("v1", group_setting)
+    .unwrap();
 
   let view = database_test.get_view("v1").unwrap();
   assert_eq!(view.group_settings.len(), 1);
@@ -84,7 +89,9 @@ async fn get_single_database_group_test() {
     ],
     content: "test group".to_string(),
   };
-  database_test.insert_group_setting("v1", group_setting);
+  database_test
+    .insert_group_setting("v1", group_setting)
+    .unwrap();
   let settings = database_test.get_all_group_setting::<TestGroupSetting>("v1");
   assert_eq!(settings.len(), 1);
   assert_eq!(settings[0].id, "g1");
@@ -123,8 +130,12 @@ async fn get_multiple_database_group_test() {
     groups: vec![],
     content: "test group 2".to_string(),
   };
-  database_test.insert_group_setting("v1", group_setting_1);
-  database_test.insert_group_setting("v1", group_setting_2);
+  database_test
+    .insert_group_setting("v1", group_setting_1)
+    .unwrap();
+  database_test
+    .insert_group_setting("v1", group_setting_2)
+    .unwrap();
 
   let settings = database_test.get_all_group_setting::<TestGroupSetting>("v1");
   assert_eq!(settings.len(), 2);
@@ -136,19 +147,21 @@ async fn get_multiple_database_group_test() {
 #[tokio::test]
 async fn extend_database_view_group_test() {
   let mut database_test = create_database_with_two_groups().await;
-  database_test.update_group_setting("v1", "g1", |object| {
-    object.insert(CONTENT.into(), "hello world".into());
-    let mut groups = object
-      .remove(GROUPS)
-      .and_then(|any| any.into_array())
-      .unwrap_or_default();
-    groups.push(Any::from(GroupMap::from(TestGroup {
-      id: "group_item3".to_string(),
-      name: "group item 3".to_string(),
-      visible: false,
-    })));
-    object.insert(GROUPS.into(), Any::from(groups));
-  });
+  database_test
+    .update_group_setting("v1", "g1", |object| {
+      object.insert(CONTENT.into(), "hello world".into());
+      let mut groups = object
+        .remove(GROUPS)
+        .and_then(|any| any.into_array())
+        .unwrap_or_default();
+      groups.push(Any::from(GroupMap::from(TestGroup {
+        id: "group_item3".to_string(),
+        name: "group item 3".to_string(),
+        visible: false,
+      })));
+      object.insert(GROUPS.into(), Any::from(groups));
+    })
+    .unwrap();
 
   let view = database_test.get_view("v1").unwrap();
   assert_eq!(view.group_settings.len(), 2);
@@ -168,18 +181,20 @@ async fn extend_database_view_group_test() {
 #[tokio::test]
 async fn remove_database_view_group_test() {
   let mut database_test = create_database_with_two_groups().await;
-  database_test.update_group_setting("v1", "g1", |object| {
-    let mut groups = object
-      .remove(GROUPS)
-      .and_then(|any| any.into_array())
-      .unwrap_or_default();
-    let index = groups
-      .iter()
-      .position(|group| group.get_as::<String>("id").as_deref() == Some("group_item1"))
-      .unwrap();
-    groups.remove(index);
-    object.insert(GROUPS.into(), groups.into());
-  });
+  database_test
+    .update_group_setting("v1", "g1", |object| {
+      let mut groups = object
+        .remove(GROUPS)
+        .and_then(|any| any.into_array())
+        .unwrap_or_default();
+      let index = groups
+        .iter()
+        .position(|group| group.get_as::<String>("id").as_deref() == Some("group_item1"))
+        .unwrap();
+      groups.remove(index);
+      object.insert(GROUPS.into(), groups.into());
+    })
+    .unwrap();
 
   let view = database_test.get_view("v1").unwrap();
   let group_settings = view
@@ -193,6 +208,87 @@ async fn remove_database_view_group_test() {
   assert_eq!(group_settings[0].groups[0].id, "group_item2");
 }
 
+#[tokio::test]
+async fn group_by_multiple_fields_test() {
+  let mut database_test = create_database_with_two_groups().await;
+  let group_setting = GroupSetting::new_with_field_ids(
+    vec!["f1".to_string(), "f2".to_string()],
+    Default::default(),
+    "".to_string(),
+  );
+  let setting_id = group_setting.id.clone();
+  database_test
+    .insert_group_setting("v1", group_setting)
+    .unwrap();
+
+  let group_settings = database_test.get_all_group_setting::<GroupSetting>("v1");
+  assert_eq!(group_settings.len(), 3);
+  let group_setting = group_settings
+    .iter()
+    .find(|setting| setting.id == setting_id)
+    .unwrap();
+  // The primary field is kept in the field_id for the clients that only know a single field
+  assert_eq!(group_setting.field_id, "f1");
+  assert_eq!(group_setting.field_ids(), vec!["f1", "f2"]);
+
+  // Replace the fields of an existing group setting
+  let group_setting = TestGroupSetting {
+    id: "g2".to_string(),
+    field_id: "f2".to_string(),
+    field_type: Default::default(),
+    groups: vec![],
+    content: "".to_string(),
+  };
+  database_test
+    .insert_group_setting_with_field_ids(
+      "v1",
+      group_setting,
+      vec!["f3".to_string(), "f1".to_string()],
+    )
+    .unwrap();
+  let group_settings = database_test.get_all_group_setting::<GroupSettingMap>("v1");
+  assert_eq!(group_settings.len(), 3);
+  assert_eq!(
+    group_setting_field_ids(&group_settings[1]),
+    vec!["f3", "f1"]
+  );
+  let group_setting = TestGroupSetting::try_from(&group_settings[1]).unwrap();
+  assert_eq!(group_setting.field_id, "f3");
+}
+
+#[tokio::test]
+async fn single_field_group_setting_back_compat_test() {
+  let database_test = create_database_with_two_groups().await;
+  let group_settings = database_test.get_all_group_setting::<GroupSetting>("v1");
+  assert_eq!(group_settings.len(), 2);
+  assert!(group_settings[0].field_ids.is_empty());
+  assert_eq!(group_settings[0].field_ids(), vec!["f1"]);
+  assert_eq!(group_settings[0].groups.len(), 2);
+  assert_eq!(group_settings[1].field_ids(), vec!["f2"]);
+
+  // The group settings serialized before the multi-field grouping was introduced
+  let group_setting: GroupSettingMap = serde_json::from_value(json!({
+    "id": "g1",
+    "field_id": "f1",
+    "ty": 0,
+    "groups": [],
+    "content": ""
+  }))
+  .unwrap();
+  assert_eq!(group_setting_field_ids(&group_setting), vec!["f1"]);
+
+  let group_setting: GroupSettingMap = serde_json::from_value(json!({
+    "id": "g1",
+    "field_id": "f1",
+    "field_ids": ["f1", "f2"],
+    "ty": 0,
+    "groups": [],
+    "content": ""
+  }))
+  .unwrap();
+  assert_eq!(group_setting_field_ids(&group_setting), vec!["f1", "f2"]);
+}
+
 async fn create_database_with_two_groups() -> DatabaseTest {
   let database_id = uuid::Uuid::new_v4();
   let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
@@ -32,7 +32,10 @@ pub struct DatabaseTest {
 
 impl DatabaseTest {
   pub async fn get_rows_for_view(&self, view_id: &str) -> Vec<Row> {
-    let rows_stream = self.database.get_rows_for_view(view_id, 10, None).await;
+    let rows_stream = self
+      .database
+      .get_rows_for_view(view_id, 10, None, true)
+      .await;
     let rows: Vec<Row> = rows_stream
       .filter_map(|result| async move { result.ok() })
       .collect()
@@ -108,6 +111,7 @@ pub fn create_row(uid: i64, workspace_id: &str, row_id: RowId) -> DatabaseRow {
     collab,
     Some(row_change_tx),
     Row::new(row_id, "1"),
+    Some(uid),
     collab_builder,
   )
 }
@@ -276,8 +280,13 @@ pub async fn create_database_with_default_data(uid: i64, database_id: &str) -> D
   database_test.create_row(row_3).await.unwrap();
 
   let field_1 = Field::new("f1".to_string(), "text field".to_string(), 0, true);
-  let field_2 = Field::new("f2".to_string(), "single select field".to_string(), 2, true);
-  let field_3 = Field::new("f3".to_string(), "checkbox field".to_string(), 1, true);
+  let field_2 = Field::new(
+    "f2".to_string(),
+    "single select field".to_string(),
+    2,
+    false,
+  );
+  let field_3 = Field::new("f3".to_string(), "checkbox field".to_string(), 1, false);
 
   let field_settings_by_layout = default_field_settings_by_layout();
 
@@ -300,7 +309,9 @@ pub async fn create_database_with_default_data(uid: i64, database_id: &str) -> D
     field_settings_by_layout,
   );
 
-  database_test.set_field_settings("v1", field_settings_for_default_database());
+  database_test
+    .set_field_settings("v1", field_settings_for_default_database())
+    .unwrap();
 
   database_test
 }
@@ -1,5 +1,12 @@
+use collab_database::database::DatabaseData;
+use collab_database::entity::FieldType;
+use collab_database::error::DatabaseError;
 use collab_database::fields::Field;
-use collab_database::views::DatabaseLayout;
+use collab_database::views::{
+  BoardLayoutSetting, CalendarLayout, CalendarLayoutSetting, DatabaseLayout, GridLayoutSetting,
+  LayoutSetting, LayoutSettingBuilder, OrderObjectPosition,
+};
+use std::collections::HashMap;
 
 use crate::database_test::helper::{
   create_database_with_default_data, DatabaseTest, DatabaseTestBuilder,
@@ -44,9 +51,11 @@ async fn create_database_view_with_layout_setting_test() {
 #[tokio::test]
 async fn remove_layout_setting_test() {
   let mut database_test = create_database_with_two_layout_settings().await;
-  database_test.update_database_view("v1", |view| {
-    view.remove_layout_setting(&DatabaseLayout::Board);
-  });
+  database_test
+    .update_database_view("v1", |view| {
+      view.remove_layout_setting(&DatabaseLayout::Board);
+    })
+    .unwrap();
 
   let layout_setting =
     database_test.get_layout_setting::<TestCalendarLayoutSetting>("v1", &DatabaseLayout::Board);
@@ -66,7 +75,9 @@ async fn update_layout_setting_test() {
   let mut layout_setting = TestCalendarLayoutSetting::new("f1".to_string());
   layout_setting.show_weekends = false;
   layout_setting.first_day_of_week = 2;
-  database_test.insert_layout_setting("v1", &DatabaseLayout::Board, layout_setting);
+  database_test
+    .insert_layout_setting("v1", &DatabaseLayout::Board, layout_setting)
+    .unwrap();
 
   //
   let layout_setting = database_test
@@ -76,14 +87,218 @@ async fn update_layout_setting_test() {
   assert!(!layout_setting.show_weekends);
 }
 
+#[tokio::test]
+async fn grid_frozen_columns_layout_setting_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  assert!(database_test.get_grid_layout_setting("v1").is_none());
+
+  database_test
+    .insert_layout_setting(
+      "v1",
+      &DatabaseLayout::Grid,
+      GridLayoutSetting {
+        frozen_field_count: 2,
+      },
+    )
+    .unwrap();
+  let layout_setting = database_test.get_grid_layout_setting("v1").unwrap();
+  assert_eq!(layout_setting.frozen_field_count, 2);
+
+  // The duplicated view keeps the frozen columns
+  let (_, duplicated_view) = database_test.duplicate_linked_view("v1").unwrap();
+  let layout_setting = database_test
+    .get_grid_layout_setting(&duplicated_view.id)
+    .unwrap();
+  assert_eq!(layout_setting.frozen_field_count, 2);
+
+  // The frozen columns are included in the serialized database data
+  let json = database_test.get_database_data().await.to_json().unwrap();
+  let database_data = DatabaseData::from_json(&json).unwrap();
+  let view = database_data
+    .views
+    .into_iter()
+    .find(|view| view.id == "v1")
+    .unwrap();
+  let layout_setting = GridLayoutSetting::from(view.layout_settings[&DatabaseLayout::Grid].clone());
+  assert_eq!(layout_setting.frozen_field_count, 2);
+}
+
+#[tokio::test]
+async fn validate_calendar_layout_setting_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  database_test.create_field(
+    None,
+    Field::new(
+      "f4".to_string(),
+      "date field".to_string(),
+      FieldType::DateTime.into(),
+      false,
+    ),
+    &OrderObjectPosition::default(),
+    HashMap::new(),
+  );
+
+  // The calendar field is missing, not found or not a date field
+  for field_id in ["", "f100", "f1"] {
+    let err = database_test
+      .insert_layout_setting_with_validation(
+        "v1",
+        &DatabaseLayout::Calendar,
+        CalendarLayoutSetting::new(field_id.to_string()),
+      )
+      .unwrap_err();
+    assert!(matches!(err, DatabaseError::InvalidLayoutSetting(_)));
+  }
+
+  let mut layout_setting = CalendarLayoutSetting::new("f4".to_string());
+  layout_setting.first_day_of_week = 7;
+  let err = database_test
+    .insert_layout_setting_with_validation("v1", &DatabaseLayout::Calendar, layout_setting)
+    .unwrap_err();
+  assert!(matches!(err, DatabaseError::InvalidLayoutSetting(_)));
+  assert!(database_test
+    .get_layout_setting::<CalendarLayoutSetting>("v1", &DatabaseLayout::Calendar)
+    .is_none());
+
+  database_test
+    .insert_layout_setting_with_validation(
+      "v1",
+      &DatabaseLayout::Calendar,
+      CalendarLayoutSetting::new("f4".to_string()),
+    )
+    .unwrap();
+  let layout_setting = database_test
+    .get_layout_setting::<CalendarLayoutSetting>("v1", &DatabaseLayout::Calendar)
+    .unwrap();
+  assert_eq!(layout_setting.field_id, "f4");
+
+  // The other layout settings aren't validated
+  database_test
+    .insert_layout_setting_with_validation(
+      "v1",
+      &DatabaseLayout::Board,
+      CalendarLayoutSetting::new("f100".to_string()),
+    )
+    .unwrap();
+}
+
+#[test]
+fn calendar_layout_setting_serde_test() {
+  let mut layout_setting = CalendarLayoutSetting::new("f1".to_string());
+  layout_setting.layout_ty = CalendarLayout::Week;
+  layout_setting.first_day_of_week = 1;
+  layout_setting.show_weekends = false;
+
+  let layout_setting = CalendarLayoutSetting::from(LayoutSetting::from(layout_setting));
+  assert_eq!(layout_setting.layout_ty, CalendarLayout::Week);
+  assert_eq!(layout_setting.first_day_of_week, 1);
+  assert!(!layout_setting.show_weekends);
+  assert!(layout_setting.show_week_numbers);
+  assert_eq!(layout_setting.field_id, "f1");
+
+  // The missing values fall back to the defaults
+  let layout_setting =
+    CalendarLayoutSetting::try_from_layout_setting(LayoutSettingBuilder::new()).unwrap();
+  assert_eq!(layout_setting.layout_ty, CalendarLayout::Month);
+  assert!(layout_setting.field_id.is_empty());
+
+  let malformed = LayoutSettingBuilder::from([("first_day_of_week".into(), "monday".into())]);
+  let err = CalendarLayoutSetting::try_from_layout_setting(malformed).unwrap_err();
+  assert!(matches!(err, DatabaseError::InvalidLayoutSetting(_)));
+}
+
+#[tokio::test]
+async fn board_collapsed_groups_layout_setting_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  database_test
+    .update_board_layout_setting("v1", |setting| {
+      setting.hide_ungrouped_column = true;
+      setting.set_group_collapsed("group_1", true);
+      setting.set_group_collapsed("group_2", true);
+      setting.set_group_collapsed("group_1", true);
+    })
+    .unwrap();
+
+  let layout_setting = database_test.get_board_layout_setting("v1").unwrap();
+  assert!(layout_setting.hide_ungrouped_column);
+  assert_eq!(
+    layout_setting.collapsed_group_ids,
+    vec!["group_1", "group_2"]
+  );
+
+  database_test
+    .update_board_layout_setting("v1", |setting| {
+      setting.set_group_collapsed("group_1", false);
+    })
+    .unwrap();
+  let layout_setting = database_test.get_board_layout_setting("v1").unwrap();
+  assert!(layout_setting.hide_ungrouped_column);
+  assert_eq!(layout_setting.collapsed_group_ids, vec!["group_2"]);
+
+  // The duplicated view keeps the collapsed groups
+  let (_, duplicated_view) = database_test.duplicate_linked_view("v1").unwrap();
+  let layout_setting = database_test
+    .get_board_layout_setting(&duplicated_view.id)
+    .unwrap();
+  assert_eq!(layout_setting.collapsed_group_ids, vec!["group_2"]);
+}
+
+#[tokio::test]
+async fn board_layout_setting_serde_test() {
+  let mut layout_setting = BoardLayoutSetting::new();
+  layout_setting.collapse_hidden_groups = true;
+  layout_setting.collapsed_group_ids = vec!["group_1".to_string(), "group_2".to_string()];
+  let layout_setting = BoardLayoutSetting::from(LayoutSetting::from(layout_setting));
+  assert!(layout_setting.collapse_hidden_groups);
+  assert!(!layout_setting.hide_ungrouped_column);
+  assert_eq!(
+    layout_setting.collapsed_group_ids,
+    vec!["group_1", "group_2"]
+  );
+
+  // The board layout settings stored before the collapsed groups were introduced
+  let layout_setting = BoardLayoutSetting::from(LayoutSettingBuilder::from([(
+    "hide_ungrouped_column".into(),
+    true.into(),
+  )]));
+  assert!(layout_setting.hide_ungrouped_column);
+  assert!(layout_setting.collapsed_group_ids.is_empty());
+
+  // The collapsed groups are included in the serialized database data
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  database_test
+    .update_board_layout_setting("v1", |setting| {
+      setting.set_group_collapsed("group_1", true);
+    })
+    .unwrap();
+  let json = database_test.get_database_data().await.to_json().unwrap();
+  let database_data = DatabaseData::from_json(&json).unwrap();
+  let view = database_data
+    .views
+    .into_iter()
+    .find(|view| view.id == "v1")
+    .unwrap();
+  let layout_setting =
+    BoardLayoutSetting::from(view.layout_settings[&DatabaseLayout::Board].clone());
+  assert_eq!(layout_setting.collapsed_group_ids, vec!["group_1"]);
+}
+
 async fn create_database_with_two_layout_settings() -> DatabaseTest {
   let database_id = uuid::Uuid::new_v4();
   let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
   let layout_setting_1 = TestCalendarLayoutSetting::new("f1".to_string());
   let layout_setting_2 = TestCalendarLayoutSetting::new("f2".to_string());
 
-  database_test.insert_layout_setting("v1", &DatabaseLayout::Board, layout_setting_1);
-  database_test.insert_layout_setting("v1", &DatabaseLayout::Grid, layout_setting_2);
+  database_test
+    .insert_layout_setting("v1", &DatabaseLayout::Board, layout_setting_1)
+    .unwrap();
+  database_test
+    .insert_layout_setting("v1", &DatabaseLayout::Grid, layout_setting_2)
+    .unwrap();
 
   database_test
 }
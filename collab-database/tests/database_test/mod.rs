@@ -1,6 +1,8 @@
 mod block_test;
+mod calculation_test;
 mod cell_test;
 mod cell_type_option_test;
+mod database_observe_test;
 mod encode_collab_test;
 mod field_observe_test;
 mod field_setting_test;
@@ -9,6 +11,7 @@ mod filter_test;
 mod group_test;
 pub mod helper;
 mod layout_test;
+mod repair_test;
 mod restore_test;
 mod row_observe_test;
 mod row_test;
@@ -0,0 +1,126 @@
+use crate::database_test::helper::{
+  create_database_with_db, default_field_settings_by_layout, restore_database_from_db,
+  wait_for_specific_event,
+};
+use collab_database::database::gen_row_id;
+use collab_database::entity::RepairAction;
+use collab_database::fields::Field;
+use collab_database::rows::CreateRowParams;
+use collab_database::views::{DatabaseViewChange, FieldOrder, OrderObjectPosition};
+use collab_plugins::local_storage::kv::doc::CollabKVAction;
+use collab_plugins::local_storage::kv::KVTransactionDB;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn validate_and_repair_inconsistent_database_test() {
+  let workspace_id = Uuid::new_v4().to_string();
+  let database_id = Uuid::new_v4().to_string();
+  let (db, mut database_test) = create_database_with_db(1, &workspace_id, &database_id).await;
+  for i in 0..3 {
+    database_test.create_field(
+      None,
+      Field::new(format!("f{}", i), format!("text field {}", i), 0, i == 0),
+      &OrderObjectPosition::default(),
+      default_field_settings_by_layout(),
+    );
+  }
+  let row_1 = gen_row_id();
+  let row_2 = gen_row_id();
+  database_test
+    .create_row(CreateRowParams::new(row_1.clone(), database_id.clone()))
+    .await
+    .unwrap();
+  database_test
+    .create_row(CreateRowParams::new(row_2.clone(), database_id.clone()))
+    .await
+    .unwrap();
+
+  // Drop the field order of f1 and add a field order for a field that doesn't exist
+  database_test
+    .update_database_view("v1", |update| {
+      update.remove_field_order("f1").insert_field_order(
+        FieldOrder::new("ghost".to_string()),
+        &OrderObjectPosition::End,
+      );
+    })
+    .unwrap();
+  drop(database_test);
+
+  let mut database_test = restore_database_from_db(1, &workspace_id, &database_id, db).await;
+  // Delete the row collab of row_2 from disk
+  database_test
+    .collab_service
+    .persistence()
+    .unwrap()
+    .delete_collab(&row_2)
+    .unwrap();
+
+  let report = database_test.validate_and_repair();
+  assert_eq!(report.actions.len(), 3);
+  assert!(report.actions.contains(&RepairAction::RemoveFieldOrder {
+    view_id: "v1".to_string(),
+    field_id: "ghost".to_string(),
+  }));
+  assert!(report.actions.contains(&RepairAction::AddFieldOrder {
+    view_id: "v1".to_string(),
+    field_id: "f1".to_string(),
+  }));
+  assert!(report.actions.contains(&RepairAction::RemoveRowOrder {
+    view_id: "v1".to_string(),
+    row_id: row_2.clone(),
+  }));
+
+  let view = database_test.get_view("v1").unwrap();
+  let field_ids = view
+    .field_orders
+    .iter()
+    .map(|field_order| field_order.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(field_ids, vec!["f0", "f2", "f1"]);
+  assert_eq!(view.row_orders.len(), 1);
+  assert_eq!(view.row_orders[0].id, row_1);
+
+  // The database is consistent now
+  assert!(database_test.validate_and_repair().is_empty());
+}
+
+#[tokio::test]
+async fn prune_orphan_row_orders_test() {
+  let workspace_id = Uuid::new_v4().to_string();
+  let database_id = Uuid::new_v4().to_string();
+  let (db, mut database_test) = create_database_with_db(1, &workspace_id, &database_id).await;
+  let mut row_ids = vec![];
+  for _ in 0..3 {
+    let row_order = database_test
+      .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+      .await
+      .unwrap();
+    row_ids.push(row_order.id);
+  }
+  drop(database_test);
+
+  // Delete the row doc directly from the kv store
+  let write_txn = db.write_txn();
+  write_txn.delete_doc(1, &workspace_id, &row_ids[1]).unwrap();
+  write_txn.commit_transaction().unwrap();
+
+  let mut database_test = restore_database_from_db(1, &workspace_id, &database_id, db).await;
+  let view_change_rx = database_test.subscribe_view_change().unwrap();
+  let pruned_row_ids = database_test.prune_orphan_row_orders();
+  assert_eq!(pruned_row_ids, vec![row_ids[1].clone()]);
+
+  wait_for_specific_event(view_change_rx, |event| match event {
+    DatabaseViewChange::DidUpdateRowOrders {
+      delete_row_indexes, ..
+    } => delete_row_indexes == &vec![1],
+    _ => false,
+  })
+  .await
+  .unwrap();
+
+  let row_orders = database_test.get_row_orders_for_view("v1");
+  assert_eq!(row_orders.len(), 2);
+  assert_eq!(row_orders[0].id, row_ids[0]);
+  assert_eq!(row_orders[1].id, row_ids[2]);
+  assert!(database_test.prune_orphan_row_orders().is_empty());
+}
@@ -1,15 +1,29 @@
 use crate::database_test::helper::{
-  create_database_with_db, restore_database_from_db, DatabaseTest,
+  create_database_with_db, default_field_settings_by_layout, restore_database_from_db, DatabaseTest,
 };
-use crate::helper::unzip_history_database_db;
+use crate::helper::{make_rocks_db, unzip_history_database_db};
+use crate::user_test::helper::TestUserDatabaseServiceImpl;
 use assert_json_diff::{assert_json_eq, assert_json_include};
 
+use async_trait::async_trait;
 use collab::core::origin::CollabOrigin;
 use collab::entity::EncodedCollab;
-use collab::preclude::Collab;
-use collab_database::rows::CreateRowParams;
+use collab::preclude::{Any, Collab, Map, MapExt, MapRef};
+use collab_database::database::{gen_row_id, Database, DatabaseContext};
+use collab_database::entity::{CreateDatabaseParams, CreateViewParams, FieldType};
+use collab_database::error::DatabaseError;
+use collab_database::fields::Field;
+use collab_database::meta::DatabaseMetaData;
+use collab_database::rows::{new_cell_builder, Cells, CreateRowParams};
+use collab_database::views::OrderObjectPosition;
+use collab_database::workspace_database::{
+  DatabaseCollabPersistenceService, DatabaseCollabService, EncodeCollabByOid,
+};
+use collab_entity::define::DATABASE;
+use collab_entity::CollabType;
 use collab_plugins::CollabKVDB;
 use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 #[tokio::test]
@@ -101,6 +115,11 @@ async fn open_020_history_database_test() {
     db,
   )
   .await;
+  // Databases created before the database metadata was introduced don't have it
+  assert_eq!(
+    database_test.get_database_meta(),
+    DatabaseMetaData::default()
+  );
   let actual_1 = database_test.to_json_value().await;
   assert_json_include!(expected: expected_json(), actual: actual_1);
 
@@ -573,3 +592,375 @@ fn expected_database_json() -> Value {
     }
   })
 }
+
+#[tokio::test]
+async fn rebuild_database_from_rows_test() {
+  let workspace_id = Uuid::new_v4().to_string();
+  let database_id = Uuid::new_v4().to_string();
+  let (db, mut database_test) = create_database_with_db(1, &workspace_id, &database_id).await;
+
+  let mut text_cell = new_cell_builder(FieldType::RichText);
+  text_cell.insert("data".to_string(), Any::String("hello".into()));
+  let mut number_cell = new_cell_builder(FieldType::Number);
+  number_cell.insert("data".to_string(), Any::String("1".into()));
+
+  let mut row_1 = CreateRowParams::new(gen_row_id(), database_id.clone())
+    .with_cells(Cells::from([("a".to_string(), text_cell)]));
+  row_1.created_at = 200;
+  let mut row_2 = CreateRowParams::new(gen_row_id(), database_id.clone())
+    .with_cells(Cells::from([("b".to_string(), number_cell)]));
+  row_2.created_at = 100;
+  database_test.create_row(row_1.clone()).await.unwrap();
+  database_test.create_row(row_2.clone()).await.unwrap();
+  drop(database_test);
+
+  // Corrupt the database by removing all the views and the fields
+  let collab_service = Arc::new(TestUserDatabaseServiceImpl {
+    uid: 1,
+    workspace_id: workspace_id.clone(),
+    db: db.clone(),
+  });
+  {
+    let mut collab = collab_service
+      .build_collab(&database_id, CollabType::Database, None)
+      .await
+      .unwrap();
+    let mut txn = collab.context.transact_mut();
+    let views: MapRef = collab
+      .data
+      .get_with_path(&txn, [DATABASE, "views"])
+      .unwrap();
+    views.clear(&mut txn);
+    let fields: MapRef = collab
+      .data
+      .get_with_path(&txn, [DATABASE, "fields"])
+      .unwrap();
+    fields.clear(&mut txn);
+  }
+
+  let row_ids = vec![row_1.id.clone(), row_2.id.clone()];
+  let database = Database::rebuild_from_rows(
+    &database_id,
+    DatabaseContext::new(collab_service.clone()),
+    row_ids.clone(),
+    false,
+  )
+  .await
+  .unwrap();
+
+  let row_orders = database.get_inline_row_orders();
+  assert_eq!(row_orders.len(), 2);
+  assert_eq!(row_orders[0].id, row_2.id);
+  assert_eq!(row_orders[1].id, row_1.id);
+
+  let fields = database.get_fields_in_view(&database.get_inline_view_id(), None);
+  assert_eq!(fields.len(), 2);
+  assert_eq!(fields[0].id, "b");
+  assert_eq!(fields[0].name, "Field 1");
+  assert_eq!(fields[0].field_type, i64::from(FieldType::Number));
+  assert!(fields[0].is_primary);
+  assert_eq!(fields[1].id, "a");
+  assert_eq!(fields[1].name, "Field 2");
+  assert_eq!(fields[1].field_type, i64::from(FieldType::RichText));
+  drop(database);
+
+  // The database has views again, so rebuilding without force is rejected
+  let result = Database::rebuild_from_rows(
+    &database_id,
+    DatabaseContext::new(collab_service.clone()),
+    row_ids.clone(),
+    false,
+  )
+  .await;
+  assert!(matches!(result, Err(DatabaseError::RecordAlreadyExist)));
+
+  let database = Database::rebuild_from_rows(
+    &database_id,
+    DatabaseContext::new(collab_service),
+    row_ids,
+    true,
+  )
+  .await
+  .unwrap();
+  assert_eq!(database.get_inline_row_orders().len(), 2);
+}
+
+#[tokio::test]
+async fn rebuild_database_promotes_primary_field_test() {
+  let workspace_id = Uuid::new_v4().to_string();
+  let database_id = Uuid::new_v4().to_string();
+  let (db, mut database_test) = create_database_with_db(1, &workspace_id, &database_id).await;
+
+  // The field of the oldest row survives, but it's not the primary field
+  database_test.create_field(
+    None,
+    Field::new(
+      "b".to_string(),
+      "number".to_string(),
+      FieldType::Number.into(),
+      false,
+    ),
+    &OrderObjectPosition::default(),
+    default_field_settings_by_layout(),
+  );
+  let mut text_cell = new_cell_builder(FieldType::RichText);
+  text_cell.insert("data".to_string(), Any::String("hello".into()));
+  let mut number_cell = new_cell_builder(FieldType::Number);
+  number_cell.insert("data".to_string(), Any::String("1".into()));
+
+  let mut row_1 = CreateRowParams::new(gen_row_id(), database_id.clone())
+    .with_cells(Cells::from([("a".to_string(), text_cell)]));
+  row_1.created_at = 200;
+  let mut row_2 = CreateRowParams::new(gen_row_id(), database_id.clone())
+    .with_cells(Cells::from([("b".to_string(), number_cell)]));
+  row_2.created_at = 100;
+  database_test.create_row(row_1.clone()).await.unwrap();
+  database_test.create_row(row_2.clone()).await.unwrap();
+  drop(database_test);
+
+  let collab_service = Arc::new(TestUserDatabaseServiceImpl {
+    uid: 1,
+    workspace_id: workspace_id.clone(),
+    db: db.clone(),
+  });
+  {
+    let mut collab = collab_service
+      .build_collab(&database_id, CollabType::Database, None)
+      .await
+      .unwrap();
+    let mut txn = collab.context.transact_mut();
+    let views: MapRef = collab
+      .data
+      .get_with_path(&txn, [DATABASE, "views"])
+      .unwrap();
+    views.clear(&mut txn);
+  }
+
+  let database = Database::rebuild_from_rows(
+    &database_id,
+    DatabaseContext::new(collab_service),
+    vec![row_1.id.clone(), row_2.id.clone()],
+    false,
+  )
+  .await
+  .unwrap();
+
+  let primary_field = database.get_primary_field().unwrap();
+  assert_eq!(primary_field.id, "b");
+  assert_eq!(primary_field.name, "number");
+  let fields = database.get_fields_in_view(&database.get_inline_view_id(), None);
+  assert_eq!(fields.len(), 2);
+  assert_eq!(fields.iter().filter(|field| field.is_primary).count(), 1);
+}
+
+#[tokio::test]
+async fn write_only_dirty_rows_to_disk_test() {
+  let workspace_id = Uuid::new_v4().to_string();
+  let database_id = Uuid::new_v4().to_string();
+  let flushed_object_ids = Arc::new(Mutex::new(vec![]));
+  let collab_service = Arc::new(FlushRecordCollabService {
+    inner: TestUserDatabaseServiceImpl {
+      uid: 1,
+      workspace_id,
+      db: make_rocks_db(),
+    },
+    flushed_object_ids: flushed_object_ids.clone(),
+  });
+  let params = CreateDatabaseParams {
+    database_id: database_id.clone(),
+    views: vec![CreateViewParams {
+      database_id: database_id.clone(),
+      view_id: "v1".to_string(),
+      ..Default::default()
+    }],
+    ..Default::default()
+  };
+  let mut database = Database::create_with_view(params, DatabaseContext::new(collab_service))
+    .await
+    .unwrap();
+  let mut row_ids = vec![];
+  for _ in 0..3 {
+    let row_order = database
+      .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+      .await
+      .unwrap();
+    row_ids.push(row_order.id);
+  }
+  let database = tokio::task::spawn_blocking(move || {
+    database.write_to_disk_all().unwrap();
+    database
+  })
+  .await
+  .unwrap();
+  assert_eq!(flushed_object_ids.lock().unwrap().len(), 4);
+
+  // Only the database and the edited row are written
+  flushed_object_ids.lock().unwrap().clear();
+  let mut database = database;
+  database
+    .update_row(row_ids[1].clone(), |row| {
+      row.set_height(100);
+    })
+    .await
+    .unwrap();
+  let database = tokio::task::spawn_blocking(move || {
+    database.write_to_disk().unwrap();
+    database
+  })
+  .await
+  .unwrap();
+  assert_eq!(
+    flushed_object_ids.lock().unwrap().clone(),
+    vec![database_id.clone(), row_ids[1].to_string()]
+  );
+
+  // No rows changed since the last write
+  flushed_object_ids.lock().unwrap().clear();
+  let database = tokio::task::spawn_blocking(move || {
+    database.write_to_disk().unwrap();
+    database
+  })
+  .await
+  .unwrap();
+  assert_eq!(
+    flushed_object_ids.lock().unwrap().clone(),
+    vec![database_id]
+  );
+
+  flushed_object_ids.lock().unwrap().clear();
+  tokio::task::spawn_blocking(move || {
+    database.write_to_disk_all().unwrap();
+  })
+  .await
+  .unwrap();
+  assert_eq!(flushed_object_ids.lock().unwrap().len(), 4);
+}
+
+struct FlushRecordCollabService {
+  inner: TestUserDatabaseServiceImpl,
+  flushed_object_ids: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl DatabaseCollabService for FlushRecordCollabService {
+  async fn build_collab(
+    &self,
+    object_id: &str,
+    object_type: CollabType,
+    encoded_collab: Option<(EncodedCollab, bool)>,
+  ) -> Result<Collab, DatabaseError> {
+    self
+      .inner
+      .build_collab(object_id, object_type, encoded_collab)
+      .await
+  }
+
+  async fn get_collabs(
+    &self,
+    object_ids: Vec<String>,
+    collab_type: CollabType,
+  ) -> Result<EncodeCollabByOid, DatabaseError> {
+    self.inner.get_collabs(object_ids, collab_type).await
+  }
+
+  fn persistence(&self) -> Option<Arc<dyn DatabaseCollabPersistenceService>> {
+    Some(Arc::new(FlushRecordPersistence {
+      inner: self.inner.persistence()?,
+      flushed_object_ids: self.flushed_object_ids.clone(),
+    }))
+  }
+
+  fn uid(&self) -> Option<i64> {
+    self.inner.uid()
+  }
+}
+
+struct FlushRecordPersistence {
+  inner: Arc<dyn DatabaseCollabPersistenceService>,
+  flushed_object_ids: Arc<Mutex<Vec<String>>>,
+}
+
+impl DatabaseCollabPersistenceService for FlushRecordPersistence {
+  fn load_collab(&self, collab: &mut Collab) {
+    self.inner.load_collab(collab)
+  }
+
+  fn get_encoded_collab(&self, object_id: &str, collab_type: CollabType) -> Option<EncodedCollab> {
+    self.inner.get_encoded_collab(object_id, collab_type)
+  }
+
+  fn delete_collab(&self, object_id: &str) -> Result<(), DatabaseError> {
+    self.inner.delete_collab(object_id)
+  }
+
+  fn save_collab(
+    &self,
+    object_id: &str,
+    encoded_collab: EncodedCollab,
+  ) -> Result<(), DatabaseError> {
+    self.inner.save_collab(object_id, encoded_collab)
+  }
+
+  fn is_collab_exist(&self, object_id: &str) -> bool {
+    self.inner.is_collab_exist(object_id)
+  }
+
+  fn flush_collabs(
+    &self,
+    encoded_collabs: Vec<(String, EncodedCollab)>,
+  ) -> Result<(), DatabaseError> {
+    self.flushed_object_ids.lock().unwrap().extend(
+      encoded_collabs
+        .iter()
+        .map(|(object_id, _)| object_id.clone()),
+    );
+    self.inner.flush_collabs(encoded_collabs)
+  }
+}
+
+#[tokio::test]
+async fn database_meta_test() {
+  let workspace_id = Uuid::new_v4().to_string();
+  let database_id = Uuid::new_v4().to_string();
+  let db = make_rocks_db();
+  let collab_service = Arc::new(TestUserDatabaseServiceImpl {
+    uid: 1,
+    workspace_id: workspace_id.clone(),
+    db: db.clone(),
+  });
+  let params = CreateDatabaseParams {
+    database_id: database_id.clone(),
+    views: vec![CreateViewParams {
+      database_id: database_id.clone(),
+      view_id: "v1".to_string(),
+      ..Default::default()
+    }],
+    meta: DatabaseMetaData {
+      name: Some("my database".to_string()),
+      icon: Some("🎉".to_string()),
+      created_at: None,
+    },
+    ..Default::default()
+  };
+  let mut database = Database::create_with_view(params, DatabaseContext::new(collab_service))
+    .await
+    .unwrap();
+  let meta = database.get_database_meta();
+  assert_eq!(meta.name.as_deref(), Some("my database"));
+  assert_eq!(meta.icon.as_deref(), Some("🎉"));
+  assert!(meta.created_at.is_some());
+
+  database.update_database_meta(|update| {
+    update.set_name("renamed database").remove_icon();
+  });
+  let database_data = database.get_database_data().await;
+  assert_eq!(database_data.meta.name.as_deref(), Some("renamed database"));
+  assert_eq!(database_data.meta.icon, None);
+  drop(database);
+
+  let database_test = restore_database_from_db(1, &workspace_id, &database_id, db).await;
+  let restored_meta = database_test.get_database_meta();
+  assert_eq!(restored_meta.name.as_deref(), Some("renamed database"));
+  assert_eq!(restored_meta.icon, None);
+  assert_eq!(restored_meta.created_at, meta.created_at);
+}
@@ -4,12 +4,19 @@ use std::time::Duration;
 use collab::lock::Mutex;
 use tokio::time::sleep;
 
+use collab::core::origin::CollabOrigin;
+use collab::preclude::updates::decoder::Decode;
+use collab::preclude::{Collab, ReadTxn, StateVector, Update};
 use collab::util::AnyMapExt;
 use collab_database::database::gen_row_id;
-use collab_database::rows::{new_cell_builder, Cell, CreateRowParams, RowChange};
+use collab_database::rows::{
+  new_cell_builder, Cell, CreateRowParams, DatabaseRow, RowChange, RowComment,
+};
 use collab_database::views::DatabaseViewChange;
 
 use crate::database_test::helper::{create_database, wait_for_specific_event};
+use crate::helper::make_rocks_db;
+use crate::user_test::helper::TestUserDatabaseServiceImpl;
 
 #[tokio::test]
 async fn observer_create_new_row_test() {
@@ -71,15 +78,17 @@ async fn observer_row_cell_test() {
         );
       });
     })
-    .await;
+    .await
+    .unwrap();
   });
 
   wait_for_specific_event(row_change_rx, |event| match event {
     RowChange::DidUpdateCell {
       row_id: _,
       field_id,
-      value,
-    } => field_id == "f1" && value.get_as::<i64>("level") == Some(1),
+      old,
+      new,
+    } => field_id == "f1" && old.is_none() && new.get_as::<i64>("level") == Some(1),
     _ => false,
   })
   .await
@@ -106,15 +115,63 @@ async fn observer_row_cell_test() {
         });
       });
     })
-    .await;
+    .await
+    .unwrap();
   });
 
   wait_for_specific_event(row_change_rx, |event| match event {
     RowChange::DidUpdateCell {
       row_id: _,
       field_id,
-      value,
-    } => field_id == "f1" && value.get_as::<i64>("level") == Some(2),
+      old,
+      new,
+    } => {
+      field_id == "f1"
+        && old.as_ref().and_then(|old| old.get_as::<i64>("level")) == Some(1)
+        && new.get_as::<i64>("level") == Some(2)
+    },
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn observer_delete_row_cell_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let row_id = gen_row_id();
+  database_test
+    .create_row(CreateRowParams::new(row_id.clone(), database_id.clone()))
+    .await
+    .unwrap();
+  database_test
+    .update_row(row_id.clone(), |row| {
+      row.update_cells(|cells| {
+        cells.insert("f1", new_cell_builder(1));
+      });
+    })
+    .await
+    .unwrap();
+
+  let row_change_rx = database_test.subscribe_row_change().unwrap();
+  let database_test = Arc::new(Mutex::from(database_test));
+  let cloned_database_test = database_test.clone();
+  let cloned_row_id = row_id.clone();
+  tokio::spawn(async move {
+    sleep(Duration::from_millis(300)).await;
+    let mut db = cloned_database_test.lock().await;
+    db.update_row(cloned_row_id, |row| {
+      row.update_cells(|cells| {
+        cells.remove("f1");
+      });
+    })
+    .await
+    .unwrap();
+  });
+
+  wait_for_specific_event(row_change_rx, |event| match event {
+    RowChange::DidDeleteCell { row_id: id, key } => id == &row_id && key == "f1",
     _ => false,
   })
   .await
@@ -140,7 +197,8 @@ async fn observer_update_row_test() {
     db.update_row(row_id, |row| {
       row.set_height(1000);
     })
-    .await;
+    .await
+    .unwrap();
   });
 
   wait_for_specific_event(row_change_rx, |event| match event {
@@ -150,3 +208,93 @@ async fn observer_update_row_test() {
   .await
   .unwrap();
 }
+
+#[tokio::test]
+async fn observer_row_comment_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let row_id = gen_row_id();
+  database_test
+    .create_row(CreateRowParams::new(row_id.clone(), database_id.clone()))
+    .await
+    .unwrap();
+  let mut row_change_rx = database_test.subscribe_row_change().unwrap();
+
+  let comment = RowComment::new(1, "hello".to_string());
+  database_test
+    .add_row_comment(&row_id, comment.clone())
+    .await
+    .unwrap();
+  database_test
+    .update_row_comment(&row_id, &comment.id, "hello world")
+    .await
+    .unwrap();
+  database_test
+    .delete_row_comment(&row_id, &comment.id)
+    .await
+    .unwrap();
+
+  let mut comment_changes = 0;
+  while let Ok(change) = row_change_rx.try_recv() {
+    if let RowChange::DidUpdateRowComment { row } = change {
+      assert_eq!(row.id, row_id);
+      comment_changes += 1;
+    }
+  }
+  assert_eq!(comment_changes, 3);
+}
+
+#[tokio::test]
+async fn observer_remote_row_comment_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let row_id = gen_row_id();
+  database_test
+    .create_row(CreateRowParams::new(row_id.clone(), database_id.clone()))
+    .await
+    .unwrap();
+  let row_change_rx = database_test.subscribe_row_change().unwrap();
+  let database_row = database_test.get_database_row(&row_id).await.unwrap();
+
+  // Add the comment on another client
+  let encoded_collab = database_row.read().await.encoded_collab().unwrap();
+  let collab = Collab::new_with_source(
+    CollabOrigin::Empty,
+    &row_id,
+    encoded_collab.into(),
+    vec![],
+    false,
+  )
+  .unwrap();
+  let collab_service = Arc::new(TestUserDatabaseServiceImpl {
+    uid: 2,
+    workspace_id: database_test.workspace_id.clone(),
+    db: make_rocks_db(),
+  });
+  let mut remote_row =
+    DatabaseRow::open(row_id.clone(), collab, None, Some(2), collab_service).unwrap();
+  let comment = RowComment::new(2, "remote comment".to_string());
+  remote_row.add_comment(comment.clone());
+  let update = remote_row
+    .transact()
+    .encode_state_as_update_v1(&StateVector::default());
+
+  let cloned_database_row = database_row.clone();
+  tokio::spawn(async move {
+    sleep(Duration::from_millis(300)).await;
+    cloned_database_row
+      .write()
+      .await
+      .apply_update(Update::decode_v1(&update).unwrap())
+      .unwrap();
+  });
+
+  let cloned_row_id = row_id.clone();
+  wait_for_specific_event(row_change_rx, |event| match event {
+    RowChange::DidUpdateRowComment { row } => row.id == cloned_row_id,
+    _ => false,
+  })
+  .await
+  .unwrap();
+  assert_eq!(database_row.read().await.get_comments(), vec![comment]);
+}
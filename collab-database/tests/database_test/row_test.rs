@@ -1,12 +1,33 @@
 use crate::database_test::helper::{
-  create_database, create_database_with_default_data, create_row,
+  create_database, create_database_with_db, create_database_with_default_data, create_row,
+  restore_database_from_db,
 };
-use collab_database::database::gen_row_id;
-use collab_database::entity::{CreateViewParams, FileUploadType};
+use crate::helper::make_rocks_db;
+use crate::user_test::helper::TestUserDatabaseServiceImpl;
+use async_trait::async_trait;
+use collab::core::origin::CollabOrigin;
+use collab::entity::EncodedCollab;
+use collab::preclude::updates::decoder::Decode;
+use collab::preclude::{
+  Array, ArrayRef, Collab, Map, MapExt, MapRef, ReadTxn, StateVector, Update,
+};
+use collab_database::database::{gen_row_id, Database, DatabaseContext};
+use collab_database::entity::{CreateDatabaseParams, CreateViewParams, FileUploadType};
+use collab_database::error::DatabaseError;
 use collab_database::rows::{
-  meta_id_from_row_id, CoverType, CreateRowParams, RowCover, RowId, RowMetaKey,
+  meta_id_from_row_id, CoverType, CreateRowParams, DatabaseRow, Row, RowComment, RowCover, RowId,
+  RowMeta, RowMetaKey, ROW_DATABASE_ID,
 };
+use collab_database::views::define::DATABASE_VIEW_ROW_ORDERS;
 use collab_database::views::OrderObjectPosition;
+use collab_database::workspace_database::{
+  DatabaseCollabPersistenceService, DatabaseCollabService, EncodeCollabByOid,
+};
+use collab_entity::CollabType;
+use futures::{pin_mut, StreamExt};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 #[tokio::test]
@@ -77,18 +98,22 @@ async fn move_row_in_view_test() {
   assert_eq!(rows[1].id, second_row_id);
   assert_eq!(rows[2].id, third_row_id);
 
-  database_test.update_database_view("v1", |update| {
-    update.move_row_order(third_row_id.as_str(), second_row_id.as_str());
-  });
+  database_test
+    .update_database_view("v1", |update| {
+      update.move_row_order(third_row_id.as_str(), second_row_id.as_str());
+    })
+    .unwrap();
 
   let rows2 = database_test.get_rows_for_view("v1").await;
   assert_eq!(rows2[0].id, first_row_id);
   assert_eq!(rows2[1].id, third_row_id);
   assert_eq!(rows2[2].id, second_row_id);
 
-  database_test.update_database_view("v1", |update| {
-    update.move_row_order(second_row_id.as_str(), first_row_id.as_str());
-  });
+  database_test
+    .update_database_view("v1", |update| {
+      update.move_row_order(second_row_id.as_str(), first_row_id.as_str());
+    })
+    .unwrap();
 
   let row3 = database_test.get_rows_for_view("v1").await;
   assert_eq!(row3[0].id, second_row_id);
@@ -109,9 +134,11 @@ async fn move_row_in_view_test2() {
   assert_eq!(rows[1].id, second_row_id);
   assert_eq!(rows[2].id, third_row_id);
 
-  database_test.update_database_view("v1", |update| {
-    update.move_row_order(first_row_id.as_str(), third_row_id.as_str());
-  });
+  database_test
+    .update_database_view("v1", |update| {
+      update.move_row_order(first_row_id.as_str(), third_row_id.as_str());
+    })
+    .unwrap();
 
   let rows2 = database_test.get_rows_for_view("v1").await;
   assert_eq!(rows2[0].id, second_row_id);
@@ -134,9 +161,11 @@ async fn move_row_in_views_test() {
   let second_row_id = database_test.pre_define_row_ids[1].clone();
   let third_row_id = database_test.pre_define_row_ids[2].clone();
 
-  database_test.update_database_view("v1", |update| {
-    update.move_row_order(third_row_id.as_str(), second_row_id.as_str());
-  });
+  database_test
+    .update_database_view("v1", |update| {
+      update.move_row_order(third_row_id.as_str(), second_row_id.as_str());
+    })
+    .unwrap();
 
   let rows_1 = database_test.get_rows_for_view("v1").await;
   assert_eq!(rows_1[0].id, first_row_id);
@@ -240,9 +269,9 @@ async fn duplicate_row_test() {
   let second_row_id = database_test.pre_define_row_ids[1].clone();
   let third_row_id = database_test.pre_define_row_ids[2].clone();
 
-  let params = database_test.duplicate_row(&second_row_id).await.unwrap();
+  let duplicated_row = database_test.duplicate_row(&second_row_id).await.unwrap();
   let (index, row_order) = database_test
-    .create_row_in_view("v1", params)
+    .create_row_in_view("v1", duplicated_row.params)
     .await
     .unwrap();
   assert_eq!(index, 2);
@@ -263,12 +292,12 @@ async fn duplicate_last_row_test() {
   let rows = database_test.get_rows_for_view("v1").await;
   assert_eq!(rows.len(), 3);
 
-  let params = database_test
+  let duplicated_row = database_test
     .duplicate_row(&database_test.pre_define_row_ids[2].clone())
     .await
     .unwrap();
   let (index, row_order) = database_test
-    .create_row_in_view("v1", params)
+    .create_row_in_view("v1", duplicated_row.params)
     .await
     .unwrap();
   assert_eq!(index, 3);
@@ -278,6 +307,52 @@ async fn duplicate_last_row_test() {
   assert_eq!(rows[3].id, row_order.id);
 }
 
+#[tokio::test]
+async fn duplicate_row_with_meta_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let first_row_id = database_test.pre_define_row_ids[0].clone();
+  let second_row_id = database_test.pre_define_row_ids[1].clone();
+  database_test
+    .update_row_meta(&first_row_id, |meta_update| {
+      meta_update
+        .insert_icon("icon")
+        .insert_cover(&RowCover {
+          data: "cover".to_string(),
+          upload_type: FileUploadType::NetworkFile,
+          cover_type: CoverType::FileCover,
+        })
+        .update_is_document_empty(false);
+    })
+    .await;
+
+  let duplicated_row = database_test.duplicate_row(&first_row_id).await.unwrap();
+  let original_row = database_test.get_row(&first_row_id).await;
+  assert_eq!(
+    duplicated_row.source_document_id,
+    original_row.document_id()
+  );
+
+  let row_order = database_test
+    .create_row_with_meta(duplicated_row.params, duplicated_row.meta)
+    .await
+    .unwrap();
+  let rows = database_test.get_rows_for_view("v1").await;
+  assert_eq!(rows.len(), 4);
+  assert_eq!(rows[1].id, row_order.id);
+  assert_eq!(rows[2].id, second_row_id);
+  assert_eq!(rows[1].cells, original_row.cells);
+
+  let row_meta = database_test.get_row_meta(&row_order.id).await.unwrap();
+  assert_eq!(row_meta.icon_url, Some("icon".to_string()));
+  assert_eq!(row_meta.cover.unwrap().data, "cover");
+  assert!(!row_meta.is_document_empty);
+
+  // The new row has its own document
+  let new_row = database_test.get_row(&row_order.id).await;
+  assert_ne!(new_row.document_id(), original_row.document_id());
+}
+
 #[tokio::test]
 async fn document_id_of_row_test() {
   let database_id = uuid::Uuid::new_v4().to_string();
@@ -332,6 +407,230 @@ async fn update_row_meta_test() {
   assert!(!row_meta.is_document_empty);
 }
 
+#[tokio::test]
+async fn update_row_meta_cover_and_attachment_count_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let row_order = database_test
+    .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+    .await
+    .unwrap();
+
+  let row_meta = database_test.get_row_meta(&row_order.id).await.unwrap();
+  assert!(row_meta.cover.is_none());
+  assert_eq!(row_meta.attachment_count, 0);
+
+  database_test
+    .update_row_meta(&row_order.id, |meta_update| {
+      meta_update
+        .insert_cover_if_not_none(Some(RowCover {
+          data: "#FF0000".to_string(),
+          upload_type: FileUploadType::LocalFile,
+          cover_type: CoverType::ColorCover,
+        }))
+        .update_attachment_count_if_not_none(Some(3));
+    })
+    .await;
+
+  let row_meta = database_test.get_row_meta(&row_order.id).await.unwrap();
+  let cover = row_meta.cover.unwrap();
+  assert_eq!(cover.data, "#FF0000");
+  assert_eq!(cover.cover_type, CoverType::ColorCover);
+  assert_eq!(row_meta.attachment_count, 3);
+}
+
+#[tokio::test]
+async fn remove_row_meta_icon_and_cover_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let row_id = Uuid::new_v4();
+  let row_order = database_test
+    .create_row(CreateRowParams::new(row_id, database_id.clone()))
+    .await
+    .unwrap();
+
+  database_test
+    .update_row_meta(&row_order.id, |meta_update| {
+      meta_update
+        .insert_icon("icon")
+        .insert_cover(&RowCover {
+          data: "cover".to_string(),
+          upload_type: FileUploadType::NetworkFile,
+          cover_type: CoverType::FileCover,
+        })
+        .update_attachment_count(2);
+    })
+    .await;
+  let row_meta = database_test.get_row_meta(&row_order.id).await.unwrap();
+  assert!(row_meta.cover.is_some());
+
+  database_test
+    .update_row_meta(&row_order.id, |meta_update| {
+      meta_update
+        .remove_icon()
+        .remove_cover()
+        .remove_attachment_count();
+    })
+    .await;
+  let row_meta = database_test.get_row_meta(&row_order.id).await.unwrap();
+  assert!(row_meta.icon_url.is_none());
+  assert!(row_meta.cover.is_none());
+  assert_eq!(row_meta.attachment_count, 0);
+
+  // An empty icon written by the older versions is read as no icon
+  database_test
+    .update_row_meta(&row_order.id, |meta_update| {
+      meta_update.insert_icon("");
+    })
+    .await;
+  let row_meta = database_test.get_row_meta(&row_order.id).await.unwrap();
+  assert!(row_meta.icon_url.is_none());
+}
+
+#[tokio::test]
+async fn read_legacy_row_cover_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let row_id = Uuid::new_v4();
+  let row_order = database_test
+    .create_row(CreateRowParams::new(row_id, database_id.clone()))
+    .await
+    .unwrap();
+
+  // The rows created before the cover got a type store the url of the cover as a plain string
+  {
+    let database_row = database_test.get_database_row(&row_order.id).await.unwrap();
+    let mut database_row = database_row.write().await;
+    let meta = database_row.body.get_meta().clone();
+    let mut txn = database_row.collab.transact_mut();
+    meta.insert(
+      &mut txn,
+      meta_id_from_row_id(&row_id, RowMetaKey::CoverId),
+      "https://appflowy.io/cover.png",
+    );
+  }
+
+  let row_meta = database_test.get_row_meta(&row_order.id).await.unwrap();
+  let cover = row_meta.cover.unwrap();
+  assert_eq!(cover.data, "https://appflowy.io/cover.png");
+  assert_eq!(cover.cover_type, CoverType::FileCover);
+  assert_eq!(cover.upload_type, FileUploadType::NetworkFile);
+}
+
+#[test]
+fn row_meta_without_new_keys_deserialize_test() {
+  let json = r#"{"icon_url":"icon"}"#;
+  let row_meta: RowMeta = serde_json::from_str(json).unwrap();
+  assert_eq!(row_meta.icon_url, Some("icon".to_string()));
+  assert!(row_meta.cover.is_none());
+  assert!(row_meta.is_document_empty);
+  assert_eq!(row_meta.attachment_count, 0);
+
+  let json = r#"{"icon_url":null,"cover":{"data":"cover"},"is_document_empty":false}"#;
+  let row_meta: RowMeta = serde_json::from_str(json).unwrap();
+  let cover = row_meta.cover.unwrap();
+  assert_eq!(cover.data, "cover");
+  assert_eq!(cover.cover_type, CoverType::ColorCover);
+  assert!(!row_meta.is_document_empty);
+}
+
+#[tokio::test]
+async fn row_created_by_and_modified_by_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let row_order = database_test
+    .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()).with_created_by(2))
+    .await
+    .unwrap();
+  let row = database_test.get_row(&row_order.id).await;
+  assert_eq!(row.created_by, Some(2));
+  assert_eq!(row.modified_by, Some(2));
+
+  // An update that doesn't change the row isn't stamped
+  database_test
+    .update_row(row_order.id.clone(), |row_update| {
+      row_update.set_height(row.height);
+    })
+    .await
+    .unwrap();
+  assert_eq!(
+    database_test.get_row(&row_order.id).await.modified_by,
+    Some(2)
+  );
+
+  // The uid of the database user is stamped as the last modifier of the row
+  database_test
+    .update_row(row_order.id.clone(), |row_update| {
+      row_update.set_height(100);
+    })
+    .await
+    .unwrap();
+  let row = database_test.get_row(&row_order.id).await;
+  assert_eq!(row.height, 100);
+  assert_eq!(row.created_by, Some(2));
+  assert_eq!(row.modified_by, Some(1));
+}
+
+#[test]
+fn row_without_created_by_deserialize_test() {
+  let json = r#"{"id":"1","cells":{},"height":60,"created_at":0,"last_modified":0}"#;
+  let row: Row = serde_json::from_str(json).unwrap();
+  assert!(row.created_by.is_none());
+  assert!(row.modified_by.is_none());
+
+  let json = serde_json::to_string(&row).unwrap();
+  assert!(!json.contains("created_by"));
+}
+
+#[tokio::test]
+async fn update_not_exist_row_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let row_id = gen_row_id();
+  let result = database_test
+    .update_row(row_id.clone(), |row_update| {
+      row_update.set_height(100);
+    })
+    .await;
+  match result {
+    Err(DatabaseError::DatabaseRowNotFound { row_id: id, .. }) => assert_eq!(id, row_id),
+    _ => panic!("expected DatabaseRowNotFound, got {:?}", result),
+  }
+}
+
+#[tokio::test]
+async fn backfill_row_database_id_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let row_order = database_test
+    .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+    .await
+    .unwrap();
+
+  // The rows created by the older versions don't have the database id
+  {
+    let database_row = database_test.get_database_row(&row_order.id).await.unwrap();
+    let mut database_row = database_row.write().await;
+    let data = database_row.body.get_data().clone();
+    let mut txn = database_row.collab.transact_mut();
+    data.remove(&mut txn, ROW_DATABASE_ID);
+  }
+  assert!(database_test
+    .get_row(&row_order.id)
+    .await
+    .database_id
+    .is_empty());
+
+  assert_eq!(database_test.backfill_row_database_ids().await, 1);
+  assert_eq!(
+    database_test.get_row(&row_order.id).await.database_id,
+    database_id
+  );
+
+  // The backfill is idempotent
+  assert_eq!(database_test.backfill_row_database_ids().await, 0);
+}
+
 #[tokio::test]
 async fn update_row_id_test() {
   let database_id = uuid::Uuid::new_v4().to_string();
@@ -364,7 +663,8 @@ async fn update_row_id_test() {
     .update_row(row_order.id, |row_update| {
       row_update.set_row_id(new_row_id.clone().into());
     })
-    .await;
+    .await
+    .unwrap();
 
   // cannot find the old row because id has changed
   assert!(database_test
@@ -386,6 +686,35 @@ async fn update_row_id_test() {
   assert_eq!(new_row_meta.attachment_count, row_meta.attachment_count);
 }
 
+#[test]
+fn time_ordered_row_id_test() {
+  let now = chrono::Utc::now().timestamp_millis();
+  let row_ids = (0..100).map(|_| gen_row_id()).collect::<Vec<_>>();
+  let created_ms = row_ids[0].created_ms().unwrap();
+  assert!((created_ms - now).abs() < 10_000);
+
+  let mut sorted_row_ids = row_ids.clone();
+  sorted_row_ids.reverse();
+  sorted_row_ids.sort();
+  assert_eq!(sorted_row_ids, row_ids);
+
+  // The ids that don't have a timestamp are sorted after the time-ordered ids
+  let v4_row_id = RowId::from(Uuid::new_v4());
+  assert!(v4_row_id.created_ms().is_none());
+  let legacy_row_id = RowId::from("1".to_string());
+  let mut mixed_row_ids = vec![
+    legacy_row_id.clone(),
+    row_ids[1].clone(),
+    v4_row_id.clone(),
+    row_ids[0].clone(),
+  ];
+  mixed_row_ids.sort();
+  let mut expected_legacy_row_ids = vec![legacy_row_id, v4_row_id];
+  expected_legacy_row_ids.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+  assert_eq!(mixed_row_ids[..2], row_ids[..2]);
+  assert_eq!(mixed_row_ids[2..], expected_legacy_row_ids[..]);
+}
+
 #[test]
 fn row_document_id_test() {
   for _ in 0..10 {
@@ -404,3 +733,326 @@ async fn validate_row_test() {
   let row = create_row(1, &workspace_id, RowId::from(1));
   row.validate().unwrap();
 }
+
+#[tokio::test]
+async fn stream_all_rows_test() {
+  let database_id = Uuid::new_v4().to_string();
+  let collab_service = Arc::new(SlowCollabService {
+    inner: TestUserDatabaseServiceImpl {
+      uid: 1,
+      workspace_id: Uuid::new_v4().to_string(),
+      db: make_rocks_db(),
+    },
+    delay: Duration::from_millis(50),
+    fetch_calls: AtomicUsize::new(0),
+  });
+  let params = CreateDatabaseParams {
+    database_id: database_id.clone(),
+    views: vec![CreateViewParams {
+      database_id: database_id.clone(),
+      view_id: "v1".to_string(),
+      ..Default::default()
+    }],
+    ..Default::default()
+  };
+  let mut database =
+    Database::create_with_view(params, DatabaseContext::new(collab_service.clone()))
+      .await
+      .unwrap();
+  let mut row_ids = vec![];
+  for _ in 0..70 {
+    let row_order = database
+      .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+      .await
+      .unwrap();
+    row_ids.push(row_order.id);
+  }
+
+  // Remove the rows from the cache, so they have to be fetched from the collab service
+  database.body.block.row_mem_cache.clear();
+
+  let stream = database.stream_all_rows();
+  pin_mut!(stream);
+  let mut streamed_row_ids = vec![];
+  while let Some(row) = stream.next().await {
+    // The next chunk of rows is only fetched after all the rows of the previous chunk are
+    // yielded, so the rows arrive before the whole database is loaded.
+    let chunk_index = streamed_row_ids.len() / 30;
+    assert_eq!(
+      collab_service.fetch_calls.load(Ordering::SeqCst),
+      chunk_index + 1
+    );
+    assert!(database.body.block.row_mem_cache.len() <= (chunk_index + 1) * 30);
+    streamed_row_ids.push(row.id);
+  }
+  assert_eq!(streamed_row_ids, row_ids);
+  assert_eq!(collab_service.fetch_calls.load(Ordering::SeqCst), 3);
+}
+
+/// Delays fetching the collabs of the rows, like a slow remote server.
+struct SlowCollabService {
+  inner: TestUserDatabaseServiceImpl,
+  delay: Duration,
+  fetch_calls: AtomicUsize,
+}
+
+#[async_trait]
+impl DatabaseCollabService for SlowCollabService {
+  async fn build_collab(
+    &self,
+    object_id: &str,
+    object_type: CollabType,
+    encoded_collab: Option<(EncodedCollab, bool)>,
+  ) -> Result<Collab, DatabaseError> {
+    self
+      .inner
+      .build_collab(object_id, object_type, encoded_collab)
+      .await
+  }
+
+  async fn get_collabs(
+    &self,
+    object_ids: Vec<String>,
+    collab_type: CollabType,
+  ) -> Result<EncodeCollabByOid, DatabaseError> {
+    self.fetch_calls.fetch_add(1, Ordering::SeqCst);
+    tokio::time::sleep(self.delay).await;
+    self.inner.get_collabs(object_ids, collab_type).await
+  }
+
+  fn persistence(&self) -> Option<Arc<dyn DatabaseCollabPersistenceService>> {
+    self.inner.persistence()
+  }
+
+  fn uid(&self) -> Option<i64> {
+    self.inner.uid()
+  }
+}
+
+#[tokio::test]
+async fn get_row_details_test() {
+  let workspace_id = Uuid::new_v4().to_string();
+  let database_id = Uuid::new_v4().to_string();
+  let (db, mut database_test) = create_database_with_db(1, &workspace_id, &database_id).await;
+  let mut row_ids = vec![];
+  for _ in 0..3 {
+    let row_order = database_test
+      .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+      .await
+      .unwrap();
+    row_ids.push(row_order.id);
+  }
+  drop(database_test);
+
+  let database_test = restore_database_from_db(1, &workspace_id, &database_id, db).await;
+  let row_details = database_test
+    .get_row_details(&[row_ids[2].clone(), gen_row_id(), row_ids[0].clone()])
+    .await;
+  assert_eq!(row_details.len(), 2);
+  assert_eq!(row_details[0].row.id, row_ids[2]);
+  assert_eq!(row_details[1].row.id, row_ids[0]);
+}
+
+#[tokio::test]
+async fn row_position_cache_test() {
+  let database_id = Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  for _ in 0..500 {
+    database_test
+      .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+      .await
+      .unwrap();
+  }
+
+  fn assert_row_positions(database: &Database) {
+    let row_orders = database.get_row_orders_for_view("v1");
+    for (index, row_order) in row_orders.iter().enumerate() {
+      assert!(database.contains_row("v1", &row_order.id));
+      assert_eq!(database.index_of_row("v1", &row_order.id), Some(index));
+    }
+  }
+  assert_row_positions(&database_test);
+
+  // create
+  let row_id = gen_row_id();
+  let params = CreateRowParams::new(row_id.clone(), database_id.clone())
+    .with_row_position(OrderObjectPosition::Start);
+  database_test
+    .create_row_in_view("v1", params)
+    .await
+    .unwrap();
+  assert_eq!(database_test.index_of_row("v1", &row_id), Some(0));
+  assert_row_positions(&database_test);
+
+  // move
+  let row_orders = database_test.get_row_orders_for_view("v1");
+  database_test
+    .move_row(&row_orders[0].id, &row_orders[250].id)
+    .await;
+  assert_eq!(database_test.index_of_row("v1", &row_id), Some(250));
+  assert_row_positions(&database_test);
+
+  // remove
+  let removed_row_ids = vec![row_orders[1].id.clone(), row_orders[100].id.clone()];
+  database_test.remove_rows(&removed_row_ids).await;
+  for removed_row_id in &removed_row_ids {
+    assert!(!database_test.contains_row("v1", removed_row_id));
+    assert_eq!(database_test.index_of_row("v1", removed_row_id), None);
+  }
+  assert_row_positions(&database_test);
+
+  // Modify the row orders without going through the database, like a remote update does
+  let first_row_id = database_test.get_row_orders_for_view("v1")[0].id.clone();
+  let database: &mut Database = &mut database_test;
+  {
+    let mut txn = database.collab.transact_mut();
+    let view: MapRef = database.body.views.get_with_txn(&txn, "v1").unwrap();
+    let row_orders: ArrayRef = view.get_with_txn(&txn, DATABASE_VIEW_ROW_ORDERS).unwrap();
+    row_orders.remove(&mut txn, 0);
+  }
+  assert!(!database_test.contains_row("v1", &first_row_id));
+  assert_row_positions(&database_test);
+}
+
+#[tokio::test]
+async fn set_row_orders_in_view_test() {
+  let database_id = Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let mut row_ids = vec![];
+  for height in [10, 20, 30, 40] {
+    let row_id = gen_row_id();
+    database_test
+      .create_row(CreateRowParams::new(row_id.clone(), database_id.clone()).with_height(height))
+      .await
+      .unwrap();
+    row_ids.push(row_id);
+  }
+
+  // A subset of the rows with an unknown row id. The unknown row id is ignored and the
+  // missing rows are appended to the end in their current order.
+  database_test
+    .set_row_orders(
+      "v1",
+      vec![row_ids[3].clone(), gen_row_id(), row_ids[1].clone()],
+    )
+    .unwrap();
+  let row_orders = database_test.get_row_orders_for_view("v1");
+  let ordered = row_orders
+    .iter()
+    .map(|row_order| (row_order.id.clone(), row_order.height))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    ordered,
+    vec![
+      (row_ids[3].clone(), 40),
+      (row_ids[1].clone(), 20),
+      (row_ids[0].clone(), 10),
+      (row_ids[2].clone(), 30),
+    ]
+  );
+  assert_eq!(database_test.index_of_row("v1", &row_ids[2]), Some(3));
+
+  let err = database_test.set_row_orders("v100", row_ids).unwrap_err();
+  assert!(matches!(err, DatabaseError::DatabaseViewNotExist));
+}
+
+#[tokio::test]
+async fn row_comments_test() {
+  let database_id = Uuid::new_v4().to_string();
+  let mut database_test = create_database_with_default_data(1, &database_id).await;
+  let row_id = database_test.pre_define_row_ids[0].clone();
+  let comment_1 = RowComment::new(1, "first comment".to_string());
+  let comment_2 = RowComment::new(2, "second comment".to_string());
+  database_test
+    .add_row_comment(&row_id, comment_1.clone())
+    .await
+    .unwrap();
+  database_test
+    .add_row_comment(&row_id, comment_2.clone())
+    .await
+    .unwrap();
+  assert_eq!(
+    database_test.get_row_comments(&row_id).await,
+    vec![comment_1.clone(), comment_2.clone()]
+  );
+
+  database_test
+    .update_row_comment(&row_id, &comment_1.id, "edited comment")
+    .await
+    .unwrap();
+  let comments = database_test.get_row_comments(&row_id).await;
+  assert_eq!(comments[0].content, "edited comment");
+  assert_eq!(comments[0].uid, 1);
+  assert_eq!(comments[0].created_at, comment_1.created_at);
+
+  database_test
+    .delete_row_comment(&row_id, &comment_1.id)
+    .await
+    .unwrap();
+  assert_eq!(
+    database_test.get_row_comments(&row_id).await,
+    vec![comment_2]
+  );
+
+  let err = database_test
+    .update_row_comment(&row_id, &comment_1.id, "")
+    .await
+    .unwrap_err();
+  assert!(matches!(err, DatabaseError::RecordNotFound));
+  let err = database_test
+    .delete_row_comment(&row_id, &comment_1.id)
+    .await
+    .unwrap_err();
+  assert!(matches!(err, DatabaseError::RecordNotFound));
+}
+
+#[tokio::test]
+async fn concurrent_row_comments_test() {
+  let workspace_id = Uuid::new_v4().to_string();
+  let row_id = gen_row_id();
+  let mut row_1 = create_row(1, &workspace_id, row_id.clone());
+  let collab = Collab::new_with_source(
+    CollabOrigin::Empty,
+    &row_id,
+    row_1.encoded_collab().unwrap().into(),
+    vec![],
+    false,
+  )
+  .unwrap();
+  let collab_service = Arc::new(TestUserDatabaseServiceImpl {
+    uid: 2,
+    workspace_id,
+    db: make_rocks_db(),
+  });
+  let mut row_2 = DatabaseRow::open(row_id, collab, None, Some(2), collab_service).unwrap();
+
+  // Both clients append comments before they are synced
+  let comment_1 = RowComment::new(1, "comment 1".to_string());
+  let comment_2 = RowComment::new(1, "comment 2".to_string());
+  let comment_3 = RowComment::new(2, "comment 3".to_string());
+  row_1.add_comment(comment_1.clone());
+  row_1.add_comment(comment_2.clone());
+  row_2.add_comment(comment_3.clone());
+
+  let update_1 = row_1
+    .transact()
+    .encode_state_as_update_v1(&StateVector::default());
+  let update_2 = row_2
+    .transact()
+    .encode_state_as_update_v1(&StateVector::default());
+  row_1
+    .apply_update(Update::decode_v1(&update_2).unwrap())
+    .unwrap();
+  row_2
+    .apply_update(Update::decode_v1(&update_1).unwrap())
+    .unwrap();
+
+  // All the comments are kept, both clients see the same order, and the comments of each
+  // client keep the order they were added in
+  let comments = row_1.get_comments();
+  assert_eq!(comments.len(), 3);
+  assert_eq!(comments, row_2.get_comments());
+  let position = |id: &str| comments.iter().position(|comment| comment.id == id);
+  assert!(position(&comment_1.id) < position(&comment_2.id));
+  assert!(position(&comment_3.id).is_some());
+}
@@ -1,7 +1,7 @@
 use crate::database_test::helper::{create_database_with_default_data, DatabaseTest};
 use crate::helper::{SortCondition, TestSort};
 use collab_database::entity::CreateViewParams;
-use collab_database::views::DatabaseLayout;
+use collab_database::views::{DatabaseLayout, DatabaseViewChange};
 
 #[tokio::test]
 async fn create_database_view_with_sort_test() {
@@ -16,15 +16,17 @@ async fn create_database_view_with_sort_test() {
 async fn get_database_view_sort_test() {
   let mut database_test = create_database_with_two_sorts().await;
 
-  database_test.insert_sort(
-    "v1",
-    TestSort {
-      id: "s3".to_string(),
-      field_id: "f1".to_string(),
-      field_type: 0,
-      condition: Default::default(),
-    },
-  );
+  database_test
+    .insert_sort(
+      "v1",
+      TestSort {
+        id: "s3".to_string(),
+        field_id: "f1".to_string(),
+        field_type: 0,
+        condition: Default::default(),
+      },
+    )
+    .unwrap();
 
   let sort = database_test.get_sort::<TestSort>("v1", "s3");
   assert!(sort.is_some());
@@ -39,7 +41,7 @@ async fn update_database_view_sort_test() {
     field_type: Default::default(),
     condition: SortCondition::Ascending,
   };
-  database_test.insert_sort("v1", sort_1);
+  database_test.insert_sort("v1", sort_1).unwrap();
 
   let sorts = database_test
     .get_view("v1")
@@ -55,7 +57,7 @@ async fn update_database_view_sort_test() {
 #[tokio::test]
 async fn remove_all_database_view_sort_test() {
   let mut database_test = create_database_with_two_sorts().await;
-  database_test.remove_all_sorts("v1");
+  database_test.remove_all_sorts("v1").unwrap();
 
   let view = database_test.get_view("v1").unwrap();
   assert!(view.sorts.is_empty());
@@ -64,7 +66,7 @@ async fn remove_all_database_view_sort_test() {
 #[tokio::test]
 async fn remove_database_view_sort_test() {
   let mut database_test = create_database_with_two_sorts().await;
-  database_test.remove_sort("v1", "s1");
+  database_test.remove_sort("v1", "s1").unwrap();
 
   let view = database_test.get_view("v1").unwrap();
   assert_eq!(view.sorts.len(), 1);
@@ -73,7 +75,7 @@ async fn remove_database_view_sort_test() {
 #[tokio::test]
 async fn reorder_database_view_sort_test() {
   let mut database_test = create_database_with_two_sorts().await;
-  database_test.move_sort("v1", "s2", "s1");
+  database_test.move_sort("v1", "s2", "s1").unwrap();
 
   let sorts = database_test
     .get_view("v1")
@@ -88,6 +90,49 @@ async fn reorder_database_view_sort_test() {
   assert_eq!(sorts[1].id, "s1");
 }
 
+#[tokio::test]
+async fn delete_field_removes_stale_sorts_test() {
+  let mut database_test = create_database_with_two_sorts().await;
+  let mut view_change_rx = database_test.subscribe_view_change().unwrap();
+  database_test.delete_field("f2").unwrap();
+
+  let sorts = database_test.get_all_sorts::<TestSort>("v1");
+  assert_eq!(sorts.len(), 1);
+  assert_eq!(sorts[0].id, "s1");
+
+  let mut did_update_sort = false;
+  while let Ok(change) = view_change_rx.try_recv() {
+    if matches!(change, DatabaseViewChange::DidUpdateSort { view_id } if view_id == "v1") {
+      did_update_sort = true;
+    }
+  }
+  assert!(did_update_sort);
+}
+
+#[tokio::test]
+async fn get_validated_sorts_test() {
+  let mut database_test = create_database_with_two_sorts().await;
+  database_test
+    .insert_sort(
+      "v1",
+      TestSort {
+        id: "s3".to_string(),
+        field_id: "f100".to_string(),
+        field_type: Default::default(),
+        condition: SortCondition::Ascending,
+      },
+    )
+    .unwrap();
+
+  assert_eq!(database_test.get_all_sorts::<TestSort>("v1").len(), 3);
+  let sorts = database_test.get_all_sorts_validated::<TestSort>("v1");
+  assert_eq!(sorts.len(), 2);
+  assert!(sorts.iter().all(|sort| sort.id != "s3"));
+
+  database_test.remove_stale_view_settings("v1").unwrap();
+  assert_eq!(database_test.get_all_sorts::<TestSort>("v1").len(), 2);
+}
+
 async fn create_database_with_two_sorts() -> DatabaseTest {
   let database_id = uuid::Uuid::new_v4();
   let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
@@ -1,14 +1,19 @@
 use crate::database_test::helper::{
-  create_database, restore_database_from_db, wait_for_specific_event,
+  create_database, create_database_with_default_data, restore_database_from_db,
+  wait_for_specific_event,
 };
-use crate::helper::setup_log;
-use collab_database::database::gen_row_id;
+use crate::helper::{setup_log, TestFieldSetting};
+use collab_database::database::{gen_row_id, Database};
 
 use collab::lock::Mutex;
+use collab::preclude::updates::decoder::Decode;
+use collab::preclude::{Map, MapExt, MapRef, ReadTxn, Update};
 use collab_database::entity::CreateViewParams;
 use collab_database::rows::CreateRowParams;
+use collab_database::views::define::VIEW_DESCRIPTION;
 use collab_database::views::{
-  DatabaseLayout, DatabaseViewChange, FilterMapBuilder, GroupSettingBuilder, SortMapBuilder,
+  filter_group_map, CalculationMap, DatabaseLayout, DatabaseViewChange, FilterConditionType,
+  FilterMapBuilder, GridLayoutSetting, GroupSettingBuilder, OrderObjectPosition, SortMapBuilder,
 };
 use std::sync::Arc;
 use std::time::Duration;
@@ -372,7 +377,8 @@ async fn observe_update_view_test() {
     let mut db = cloned_database_test.lock().await;
     db.update_database_view(&view_id, |update| {
       update.set_name("hello");
-    });
+    })
+    .unwrap();
   });
 
   wait_for_specific_event(view_change_rx, |event| match event {
@@ -451,7 +457,8 @@ async fn observe_database_view_layout_test() {
     let mut db = cloned_database_test.lock().await;
     db.update_database_view(&cloned_update_view_id, |update| {
       update.set_layout_type(DatabaseLayout::Calendar);
-    });
+    })
+    .unwrap();
   });
 
   wait_for_specific_event(view_change_rx, |event| match event {
@@ -484,7 +491,8 @@ async fn observe_database_view_filter_create_delete_test() {
     db.update_database_view(&cloned_update_view_id, |update| {
       let filter = FilterMapBuilder::from([("filter_id".into(), "123".into())]);
       update.set_filters(vec![filter]);
-    });
+    })
+    .unwrap();
   });
 
   wait_for_specific_event(view_change_rx, |event| match event {
@@ -504,7 +512,8 @@ async fn observe_database_view_filter_create_delete_test() {
     let mut db = cloned_database_test.lock().await;
     db.update_database_view(&cloned_update_view_id, |update| {
       update.set_filters(vec![]);
-    });
+    })
+    .unwrap();
   });
 
   let view_change_rx = database_test
@@ -521,6 +530,185 @@ async fn observe_database_view_filter_create_delete_test() {
   .unwrap();
 }
 
+#[tokio::test]
+async fn observe_database_view_calculation_create_delete_test() {
+  setup_log();
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let database_test = create_database(1, &database_id);
+  let view_change_rx = database_test.subscribe_view_change().unwrap();
+  let update_view_id = database_test.get_inline_view_id();
+
+  let database_test = Arc::new(Mutex::from(database_test));
+
+  // create calculation
+  let cloned_database_test = database_test.clone();
+  let cloned_update_view_id = update_view_id.clone();
+  tokio::spawn(async move {
+    sleep(Duration::from_millis(300)).await;
+    let mut db = cloned_database_test.lock().await;
+    let calculation =
+      CalculationMap::from([("id".into(), "c1".into()), ("field_id".into(), "f1".into())]);
+    db.update_calculation(&cloned_update_view_id, calculation)
+      .unwrap();
+  });
+
+  wait_for_specific_event(view_change_rx, |event| match event {
+    DatabaseViewChange::DidCreateCalculations {
+      view_id,
+      calculations,
+    } => calculations.len() == 1 && &update_view_id == view_id,
+    _ => false,
+  })
+  .await
+  .unwrap();
+
+  // update calculation
+  let cloned_update_view_id = update_view_id.clone();
+  let cloned_database_test = database_test.clone();
+  tokio::spawn(async move {
+    sleep(Duration::from_millis(300)).await;
+    let mut db = cloned_database_test.lock().await;
+    let calculation = CalculationMap::from([
+      ("id".into(), "c1".into()),
+      ("field_id".into(), "f1".into()),
+      ("value".into(), "10".into()),
+    ]);
+    db.update_calculation(&cloned_update_view_id, calculation)
+      .unwrap();
+  });
+
+  let view_change_rx = database_test
+    .lock()
+    .await
+    .database
+    .subscribe_view_change()
+    .unwrap();
+  wait_for_specific_event(view_change_rx, |event| match event {
+    DatabaseViewChange::DidUpdateCalculation { view_id } => &update_view_id == view_id,
+    _ => false,
+  })
+  .await
+  .unwrap();
+
+  // delete calculation
+  let cloned_update_view_id = update_view_id.clone();
+  let cloned_database_test = database_test.clone();
+  tokio::spawn(async move {
+    sleep(Duration::from_millis(300)).await;
+    let mut db = cloned_database_test.lock().await;
+    db.remove_calculation(&cloned_update_view_id, "c1").unwrap();
+  });
+
+  let view_change_rx = database_test
+    .lock()
+    .await
+    .database
+    .subscribe_view_change()
+    .unwrap();
+  wait_for_specific_event(view_change_rx, |event| match event {
+    DatabaseViewChange::DidDeleteCalculation {
+      view_id,
+      calculation_id,
+    } => &update_view_id == view_id && calculation_id == "c1",
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn observe_delete_calculation_when_reopen_a_database_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let view_id = database_test.get_inline_view_id();
+  let calculation =
+    CalculationMap::from([("id".into(), "c1".into()), ("field_id".into(), "f1".into())]);
+  database_test
+    .update_calculation(&view_id, calculation)
+    .unwrap();
+  let object_id = database_test.database.object_id().to_string();
+  let db = database_test.collab_db.clone();
+  let workspace_id = database_test.workspace_id.clone();
+
+  // The calculations that exist when the database is opened are known to the observer, so the
+  // first deletion is resolved to the id of the deleted calculation
+  let database_test = restore_database_from_db(1, &workspace_id, &object_id, db).await;
+  let view_change_rx = database_test.subscribe_view_change().unwrap();
+  let database_test = Arc::new(Mutex::from(database_test));
+  let cloned_database_test = database_test.clone();
+  let cloned_view_id = view_id.clone();
+  tokio::spawn(async move {
+    sleep(Duration::from_millis(300)).await;
+    let mut db = cloned_database_test.lock().await;
+    db.remove_calculation(&cloned_view_id, "c1").unwrap();
+  });
+
+  wait_for_specific_event(view_change_rx, |event| match event {
+    DatabaseViewChange::DidDeleteCalculation {
+      view_id: deleted_view_id,
+      calculation_id,
+    } => deleted_view_id == &view_id && calculation_id == "c1",
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn observe_nested_filter_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  database_test
+    .insert_filter_group(
+      "v1",
+      None,
+      filter_group_map("g1", FilterConditionType::And, vec![]),
+    )
+    .unwrap();
+
+  let view_change_rx = database_test.subscribe_view_change().unwrap();
+  let database_test = Arc::new(Mutex::from(database_test));
+  let cloned_database_test = database_test.clone();
+  tokio::spawn(async move {
+    sleep(Duration::from_millis(300)).await;
+    let mut db = cloned_database_test.lock().await;
+    let filter = FilterMapBuilder::from([("id".into(), "filter_1".into())]);
+    db.insert_filter_group("v1", Some("g1"), filter).unwrap();
+  });
+
+  wait_for_specific_event(view_change_rx, |event| match event {
+    DatabaseViewChange::DidCreateFilters { view_id, filters } => {
+      view_id == "v1" && filters.len() == 1 && filters[0].get("id") == Some(&"filter_1".into())
+    },
+    _ => false,
+  })
+  .await
+  .unwrap();
+
+  let view_change_rx = database_test
+    .lock()
+    .await
+    .database
+    .subscribe_view_change()
+    .unwrap();
+  let cloned_database_test = database_test.clone();
+  tokio::spawn(async move {
+    sleep(Duration::from_millis(300)).await;
+    let mut db = cloned_database_test.lock().await;
+    db.update_filter_group("v1", "filter_1", |filter| {
+      filter.insert("content".into(), "hello".into());
+    })
+    .unwrap();
+  });
+
+  wait_for_specific_event(view_change_rx, |event| match event {
+    DatabaseViewChange::DidUpdateFilter { view_id } => view_id == "v1",
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
+
 #[tokio::test]
 async fn observe_database_view_sort_create_delete_test() {
   setup_log();
@@ -543,7 +731,8 @@ async fn observe_database_view_sort_create_delete_test() {
         ("desc".into(), "true".into()),
       ]);
       update.set_sorts(vec![filter]);
-    });
+    })
+    .unwrap();
   });
 
   wait_for_specific_event(view_change_rx, |event| match event {
@@ -563,7 +752,8 @@ async fn observe_database_view_sort_create_delete_test() {
     let mut db = cloned_database_test.lock().await;
     db.update_database_view(&cloned_update_view_id, |update| {
       update.set_sorts(vec![]);
-    });
+    })
+    .unwrap();
   });
 
   let view_change_rx = database_test
@@ -602,7 +792,8 @@ async fn observe_database_view_group_create_delete_test() {
         ("desc".into(), "true".into()),
       ]);
       update.set_groups(vec![group_setting]);
-    });
+    })
+    .unwrap();
   });
 
   wait_for_specific_event(view_change_rx, |event| match event {
@@ -622,7 +813,8 @@ async fn observe_database_view_group_create_delete_test() {
     let mut db = cloned_database_test.lock().await;
     db.update_database_view(&cloned_update_view_id, |update| {
       update.set_groups(vec![]);
-    });
+    })
+    .unwrap();
   });
 
   let view_change_rx = database_test
@@ -638,3 +830,434 @@ async fn observe_database_view_group_create_delete_test() {
   .await
   .unwrap();
 }
+
+#[tokio::test]
+async fn observe_insert_row_at_index_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  for _ in 0..3 {
+    database_test
+      .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+      .await
+      .unwrap();
+  }
+  let view_change_rx = database_test.subscribe_view_change().unwrap();
+
+  let row_id = gen_row_id();
+  let cloned_row_id = row_id.clone();
+  let database_test = Arc::new(Mutex::from(database_test));
+  let cloned_database_test = database_test.clone();
+  tokio::spawn(async move {
+    sleep(Duration::from_millis(300)).await;
+    let params = CreateRowParams::new(cloned_row_id, database_id.clone())
+      .with_row_position(OrderObjectPosition::Index(1));
+    let (index, _) = cloned_database_test
+      .lock()
+      .await
+      .create_row_in_view("v1", params)
+      .await
+      .unwrap();
+    assert_eq!(index, 1);
+  });
+
+  wait_for_specific_event(view_change_rx, |event| match event {
+    DatabaseViewChange::DidUpdateRowOrders {
+      insert_row_orders, ..
+    } => {
+      insert_row_orders.len() == 1
+        && insert_row_orders[0].0.id == row_id
+        && insert_row_orders[0].1 == 1
+    },
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn observe_insert_row_at_out_of_range_index_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  for _ in 0..3 {
+    database_test
+      .create_row(CreateRowParams::new(gen_row_id(), database_id.clone()))
+      .await
+      .unwrap();
+  }
+  let view_change_rx = database_test.subscribe_view_change().unwrap();
+
+  let row_id = gen_row_id();
+  let cloned_row_id = row_id.clone();
+  let database_test = Arc::new(Mutex::from(database_test));
+  let cloned_database_test = database_test.clone();
+  tokio::spawn(async move {
+    sleep(Duration::from_millis(300)).await;
+    let params = CreateRowParams::new(cloned_row_id, database_id.clone())
+      .with_row_position(OrderObjectPosition::Index(100));
+    cloned_database_test
+      .lock()
+      .await
+      .create_row_in_view("v1", params)
+      .await
+      .unwrap();
+  });
+
+  // The index is clamped to the length of the row orders
+  wait_for_specific_event(view_change_rx, |event| match event {
+    DatabaseViewChange::DidUpdateRowOrders {
+      insert_row_orders, ..
+    } => {
+      insert_row_orders.len() == 1
+        && insert_row_orders[0].0.id == row_id
+        && insert_row_orders[0].1 == 3
+    },
+    _ => false,
+  })
+  .await
+  .unwrap();
+
+  let view = database_test.lock().await.get_view("v1").unwrap();
+  assert_eq!(view.row_orders.last().unwrap().id, row_id);
+}
+
+#[tokio::test]
+async fn observe_move_row_to_last_index_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database(1, &database_id);
+  let view_change_rx = database_test.subscribe_view_change().unwrap();
+
+  let mut row_ids = vec![];
+  for _ in 0..4 {
+    let row_id = gen_row_id();
+    database_test
+      .create_row(CreateRowParams::new(row_id.clone(), database_id.clone()))
+      .await
+      .unwrap();
+    row_ids.push(row_id);
+  }
+
+  let database_test = Arc::new(Mutex::from(database_test));
+  let cloned_database_test = database_test.clone();
+  let cloned_row_id = row_ids[0].clone();
+  tokio::spawn(async move {
+    sleep(Duration::from_millis(500)).await;
+    let mut db = cloned_database_test.lock().await;
+    // The index is out of range, the row will be moved to the last position
+    db.move_row_to_index("v1", &cloned_row_id, 10).unwrap();
+  });
+
+  let moved_row_id = row_ids[0].clone();
+  wait_for_specific_event(view_change_rx, |event| match event {
+    DatabaseViewChange::DidUpdateRowOrders {
+      insert_row_orders,
+      delete_row_indexes,
+      ..
+    } => {
+      if delete_row_indexes.len() == 1 {
+        assert_eq!(delete_row_indexes[0], 0);
+        assert_eq!(insert_row_orders.len(), 1);
+        assert_eq!(insert_row_orders[0].0.id, moved_row_id);
+        assert_eq!(insert_row_orders[0].1, 3);
+        true
+      } else {
+        false
+      }
+    },
+    _ => false,
+  })
+  .await
+  .unwrap();
+
+  let view = database_test.lock().await.get_view("v1").unwrap();
+  let view_row_ids = view
+    .row_orders
+    .into_iter()
+    .map(|row_order| row_order.id)
+    .collect::<Vec<_>>();
+  assert_eq!(
+    view_row_ids,
+    vec![
+      row_ids[1].clone(),
+      row_ids[2].clone(),
+      row_ids[3].clone(),
+      row_ids[0].clone()
+    ]
+  );
+}
+
+#[tokio::test]
+async fn observe_set_row_orders_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let mut view_change_rx = database_test.subscribe_view_change().unwrap();
+  let mut row_ids = database_test.pre_define_row_ids.clone();
+  row_ids.reverse();
+  database_test.set_row_orders("v1", row_ids.clone()).unwrap();
+
+  // All the rows are reordered in one transaction, so only one change is sent
+  let mut changes = vec![];
+  while let Ok(change) = view_change_rx.try_recv() {
+    if let DatabaseViewChange::DidUpdateRowOrders {
+      database_view_id,
+      insert_row_orders,
+      delete_row_indexes,
+      ..
+    } = change
+    {
+      assert_eq!(database_view_id, "v1");
+      changes.push((insert_row_orders, delete_row_indexes));
+    }
+  }
+  // Only the displaced rows are moved, the last row stays in place
+  assert_eq!(changes.len(), 1);
+  let inserted_row_ids = changes[0]
+    .0
+    .iter()
+    .map(|(row_order, _)| row_order.id.clone())
+    .collect::<Vec<_>>();
+  assert_eq!(inserted_row_ids, row_ids[1..].to_vec());
+  assert_eq!(changes[0].1.len(), 2);
+
+  // Moving the first row to the end only moves that row
+  row_ids.rotate_left(1);
+  database_test.set_row_orders("v1", row_ids.clone()).unwrap();
+  let mut changes = vec![];
+  while let Ok(change) = view_change_rx.try_recv() {
+    if let DatabaseViewChange::DidUpdateRowOrders {
+      insert_row_orders,
+      delete_row_indexes,
+      ..
+    } = change
+    {
+      changes.push((insert_row_orders, delete_row_indexes));
+    }
+  }
+  assert_eq!(changes.len(), 1);
+  assert_eq!(changes[0].0.len(), 1);
+  assert_eq!(changes[0].0[0].0.id, row_ids[2]);
+  assert_eq!(changes[0].1.len(), 1);
+  let view_row_ids = database_test
+    .get_row_orders_for_view("v1")
+    .into_iter()
+    .map(|row_order| row_order.id)
+    .collect::<Vec<_>>();
+  assert_eq!(view_row_ids, row_ids);
+}
+
+#[tokio::test]
+async fn observe_field_settings_visibility_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let mut view_change_rx = database_test.subscribe_view_change().unwrap();
+
+  // Hide the field f1
+  let field_settings = TestFieldSetting {
+    width: 100,
+    visibility: 2,
+  };
+  database_test
+    .update_field_settings("v1", Some(vec!["f1".to_string()]), field_settings)
+    .unwrap();
+
+  let mut changes = vec![];
+  while let Ok(change) = view_change_rx.try_recv() {
+    if let DatabaseViewChange::DidUpdateFieldSettings {
+      view_id,
+      field_ids,
+      is_local_change,
+    } = change
+    {
+      changes.push((view_id, field_ids, is_local_change));
+    }
+  }
+  assert_eq!(
+    changes,
+    vec![("v1".to_string(), vec!["f1".to_string()], true)]
+  );
+}
+
+#[tokio::test]
+async fn observe_view_description_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  // Remove the description like the views created before the description was introduced
+  {
+    let database: &mut Database = &mut database_test;
+    let mut txn = database.collab.transact_mut();
+    let view: MapRef = database.body.views.get_with_txn(&txn, "v1").unwrap();
+    view.remove(&mut txn, VIEW_DESCRIPTION);
+  }
+  let mut view_change_rx = database_test.subscribe_view_change().unwrap();
+
+  database_test
+    .update_database_view("v1", |update| {
+      update.set_description("first description");
+    })
+    .unwrap();
+  database_test
+    .update_database_view("v1", |update| {
+      update.set_description("second description");
+    })
+    .unwrap();
+
+  let mut descriptions = vec![];
+  while let Ok(change) = view_change_rx.try_recv() {
+    if let DatabaseViewChange::DidUpdateView { view } = change {
+      if !descriptions.contains(&view.description) {
+        descriptions.push(view.description);
+      }
+    }
+  }
+  assert_eq!(
+    descriptions,
+    vec![
+      "first description".to_string(),
+      "second description".to_string()
+    ]
+  );
+}
+
+#[tokio::test]
+async fn observe_hidden_rows_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let mut view_change_rx = database_test.subscribe_view_change().unwrap();
+  let row_id = database_test.pre_define_row_ids[1].clone();
+
+  database_test.set_row_hidden("v1", &row_id, true).unwrap();
+  database_test.set_row_hidden("v1", &row_id, false).unwrap();
+
+  let mut hidden_rows_changes = vec![];
+  while let Ok(change) = view_change_rx.try_recv() {
+    if let DatabaseViewChange::DidUpdateHiddenRows {
+      view_id,
+      hidden_rows,
+    } = change
+    {
+      assert_eq!(view_id, "v1");
+      hidden_rows_changes.push(hidden_rows);
+    }
+  }
+  assert_eq!(hidden_rows_changes, vec![vec![row_id], vec![]]);
+}
+
+#[tokio::test]
+async fn observe_grid_layout_setting_from_remote_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut client_1 = create_database(1, &database_id);
+  let object_id = client_1.database.object_id().to_string();
+  let db = client_1.collab_db.clone();
+  let workspace_id = client_1.workspace_id.clone();
+  let mut client_2 = restore_database_from_db(1, &workspace_id, &object_id, db).await;
+  let mut view_change_rx_1 = client_1.subscribe_view_change().unwrap();
+  let mut view_change_rx_2 = client_2.subscribe_view_change().unwrap();
+
+  // Freeze the first two columns on the first client and sync the change to the second one
+  client_1
+    .insert_layout_setting(
+      "v1",
+      &DatabaseLayout::Grid,
+      GridLayoutSetting {
+        frozen_field_count: 2,
+      },
+    )
+    .unwrap();
+  let state_vector = client_2.database.collab.transact().state_vector();
+  let update = client_1
+    .database
+    .collab
+    .transact()
+    .encode_state_as_update_v1(&state_vector);
+  client_2
+    .database
+    .collab
+    .apply_update(Update::decode_v1(&update).unwrap())
+    .unwrap();
+
+  for view_change_rx in [&mut view_change_rx_1, &mut view_change_rx_2] {
+    let mut did_change = false;
+    while let Ok(change) = view_change_rx.try_recv() {
+      if let DatabaseViewChange::LayoutSettingChanged {
+        view_id,
+        layout_type,
+      } = change
+      {
+        assert_eq!(view_id, "v1");
+        assert_eq!(layout_type, DatabaseLayout::Grid);
+        did_change = true;
+      }
+    }
+    assert!(did_change);
+  }
+
+  for client in [&client_1, &client_2] {
+    let layout_setting = client.get_grid_layout_setting("v1").unwrap();
+    assert_eq!(layout_setting.frozen_field_count, 2);
+  }
+}
+
+#[tokio::test]
+async fn observe_row_color_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let mut view_change_rx = database_test.subscribe_view_change().unwrap();
+  let row_id = database_test.pre_define_row_ids[0].clone();
+
+  database_test
+    .set_row_color("v1", &row_id, Some("#FF0000"))
+    .unwrap();
+  database_test
+    .set_row_color("v1", &row_id, Some("#0000FF"))
+    .unwrap();
+
+  let mut row_color_changes = 0;
+  while let Ok(change) = view_change_rx.try_recv() {
+    if let DatabaseViewChange::DidUpdateRowColor {
+      view_id,
+      row_id: changed_row_id,
+    } = change
+    {
+      assert_eq!(view_id, "v1");
+      assert_eq!(changed_row_id, row_id);
+      row_color_changes += 1;
+    }
+  }
+  assert_eq!(row_color_changes, 2);
+}
+
+#[tokio::test]
+async fn observe_board_collapsed_groups_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let mut view_change_rx = database_test.subscribe_view_change().unwrap();
+
+  database_test
+    .update_board_layout_setting("v1", |setting| {
+      setting.set_group_collapsed("group_1", true);
+    })
+    .unwrap();
+  database_test
+    .update_board_layout_setting("v1", |setting| {
+      setting.set_group_collapsed("group_2", true);
+    })
+    .unwrap();
+
+  let mut layout_changes = 0;
+  while let Ok(change) = view_change_rx.try_recv() {
+    if let DatabaseViewChange::LayoutSettingChanged {
+      view_id,
+      layout_type,
+    } = change
+    {
+      assert_eq!(view_id, "v1");
+      assert_eq!(layout_type, DatabaseLayout::Board);
+      layout_changes += 1;
+    }
+  }
+  assert_eq!(layout_changes, 2);
+  let layout_setting = database_test.get_board_layout_setting("v1").unwrap();
+  assert_eq!(
+    layout_setting.collapsed_group_ids,
+    vec!["group_1", "group_2"]
+  );
+}
@@ -2,20 +2,21 @@ use std::sync::Arc;
 
 use assert_json_diff::assert_json_eq;
 use collab::core::origin::CollabOrigin;
-use collab::preclude::{Any, Collab};
+use collab::preclude::{Any, Collab, ReadTxn};
 use collab::util::AnyMapExt;
-use collab_database::database::{gen_row_id, DatabaseBody, DatabaseData};
-use collab_database::entity::CreateViewParams;
+use collab_database::database::{gen_row_id, timestamp, Database, DatabaseBody, DatabaseData};
+use collab_database::entity::{CreateViewParams, DatabaseView};
+use collab_database::error::DatabaseError;
 use collab_database::fields::Field;
 use collab_database::rows::{CreateRowParams, Row};
-use collab_database::views::{DatabaseLayout, LayoutSettingBuilder, OrderObjectPosition};
+use collab_database::views::{DatabaseLayout, LayoutSettingBuilder, OrderObjectPosition, RowOrder};
 use futures::StreamExt;
 use nanoid::nanoid;
 
 use crate::database_test::helper::{
   create_database, create_database_with_default_data, default_field_settings_by_layout,
 };
-use crate::helper::TestFilter;
+use crate::helper::{TestFilter, TestGroupSetting, TestSort};
 
 #[tokio::test]
 async fn create_initial_database_test() {
@@ -71,6 +72,32 @@ async fn get_database_views_meta_test() {
   assert_eq!(view.name, "my first database view");
 }
 
+#[tokio::test]
+async fn get_single_database_view_meta_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  // The row orders of the view are not read when getting the meta
+  let row_orders = (0..5000)
+    .map(|_| RowOrder::new(gen_row_id(), 60))
+    .collect::<Vec<_>>();
+  database_test
+    .update_database_view("v1", |update| {
+      update
+        .set_row_orders(row_orders)
+        .set_layout_type(DatabaseLayout::Board)
+        .set_created_at(1700000000);
+    })
+    .unwrap();
+
+  let meta = database_test.get_view_meta("v1").unwrap();
+  assert_eq!(meta.id, "v1");
+  assert_eq!(meta.name, "my first database view");
+  assert_eq!(meta.layout, DatabaseLayout::Board);
+  assert_eq!(meta.created_at, 1700000000);
+  assert!(!meta.is_inline);
+  assert!(database_test.get_view_meta("v100").is_none());
+}
+
 #[tokio::test]
 async fn create_same_database_view_twice_test() {
   let database_id = uuid::Uuid::new_v4();
@@ -226,16 +253,196 @@ async fn duplicate_database_view_test() {
   assert_eq!(views.len(), 1);
 
   let view = database_test.get_view("v1").unwrap();
-  let duplicated_view = database_test.duplicate_linked_view("v1").unwrap();
+  let (index, duplicated_view) = database_test.duplicate_linked_view("v1").unwrap();
 
   let views = database_test.get_all_views();
   assert_eq!(views.len(), 2);
 
+  assert_eq!(index, 1);
   assert_eq!(duplicated_view.name, format!("{}-copy", view.name));
   assert_ne!(view.id, duplicated_view.id);
   // modified and created time should also be different but the test completes within one second.
 }
 
+#[tokio::test]
+async fn duplicate_database_view_repeatedly_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  database_test
+    .update_database_view("v1", |update| {
+      update.set_name("Grid");
+    })
+    .unwrap();
+
+  let (index, view) = database_test.duplicate_linked_view("v1").unwrap();
+  assert_eq!(index, 1);
+  assert_eq!(view.name, "Grid-copy");
+
+  let (index, view) = database_test.duplicate_linked_view("v1").unwrap();
+  assert_eq!(index, 2);
+  assert_eq!(view.name, "Grid-copy 2");
+
+  // Duplicating a copy continues the numbering of the original name
+  let (index, view) = database_test.duplicate_linked_view(&view.id).unwrap();
+  assert_eq!(index, 3);
+  assert_eq!(view.name, "Grid-copy 3");
+  assert_eq!(database_test.get_all_views().len(), 4);
+}
+
+#[tokio::test]
+async fn duplicate_database_view_index_by_creation_time_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let (index, first_copy) = database_test.duplicate_linked_view("v1").unwrap();
+  assert_eq!(index, 1);
+
+  // A view that claims to be created in the future is ordered after the new copy
+  database_test
+    .update_database_view(&first_copy.id, |update| {
+      update.set_created_at(timestamp() + 3600);
+    })
+    .unwrap();
+  let (index, view) = database_test.duplicate_linked_view("v1").unwrap();
+  assert_eq!(index, 1);
+  assert_eq!(view.name, format!("{} 2", first_copy.name));
+}
+
+#[tokio::test]
+async fn duplicate_database_view_with_unicode_name_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  database_test
+    .update_database_view("v1", |update| {
+      update.set_name("表格 🎉-copy 2");
+    })
+    .unwrap();
+
+  let (_, view) = database_test.duplicate_linked_view("v1").unwrap();
+  assert_eq!(view.name, "表格 🎉-copy");
+
+  let (_, view) = database_test.duplicate_linked_view("v1").unwrap();
+  assert_eq!(view.name, "表格 🎉-copy 3");
+
+  // A suffix that is not a number is kept as part of the name
+  database_test
+    .update_database_view("v1", |update| {
+      update.set_name("表格-copy 二");
+    })
+    .unwrap();
+  let (_, view) = database_test.duplicate_linked_view("v1").unwrap();
+  assert_eq!(view.name, "表格-copy 二-copy");
+}
+
+#[tokio::test]
+async fn set_inline_view_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let row_orders = database_test.get_inline_row_orders();
+  assert_eq!(row_orders.len(), 3);
+
+  database_test
+    .create_linked_view(CreateViewParams {
+      database_id: database_id.to_string(),
+      view_id: "v2".to_string(),
+      ..Default::default()
+    })
+    .unwrap();
+  // Make the row and field orders of v2 incomplete
+  database_test
+    .update_database_view("v2", |update| {
+      update
+        .remove_row_order(&row_orders[1].id)
+        .remove_field_order("f3");
+    })
+    .unwrap();
+
+  database_test.set_inline_view("v2").unwrap();
+  assert_eq!(database_test.get_inline_view_id(), "v2");
+  assert!(database_test.is_inline_view("v2"));
+  assert!(database_test.get_view("v2").unwrap().is_inline);
+  assert!(!database_test.get_view("v1").unwrap().is_inline);
+
+  // The missing orders are copied from the previous inline view
+  let inline_row_ids = database_test
+    .get_inline_row_orders()
+    .into_iter()
+    .map(|row_order| row_order.id)
+    .collect::<Vec<_>>();
+  assert_eq!(
+    inline_row_ids,
+    vec![
+      row_orders[0].id.clone(),
+      row_orders[2].id.clone(),
+      row_orders[1].id.clone()
+    ]
+  );
+  let field_ids = database_test
+    .get_view("v2")
+    .unwrap()
+    .field_orders
+    .into_iter()
+    .map(|field_order| field_order.id)
+    .collect::<Vec<_>>();
+  assert_eq!(field_ids, vec!["f1", "f2", "f3"]);
+
+  // The row orders of the other views are untouched
+  assert_eq!(database_test.get_row_orders_for_view("v1"), row_orders);
+
+  let err = database_test.set_inline_view("v100").unwrap_err();
+  assert!(matches!(err, DatabaseError::DatabaseViewNotExist));
+  assert_eq!(database_test.get_inline_view_id(), "v2");
+}
+
+#[tokio::test]
+async fn update_non_exist_view_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let state_vector = database_test.transact().state_vector();
+
+  let err = database_test
+    .update_database_view("v100", |update| {
+      update.set_name("hello");
+    })
+    .unwrap_err();
+  assert!(matches!(err, DatabaseError::DatabaseViewNotExist));
+
+  let filter = TestFilter {
+    id: "filter_1".to_string(),
+    field_id: "f1".to_string(),
+    field_type: Default::default(),
+    condition: 0,
+    content: "".to_string(),
+  };
+  let err = database_test.insert_filter("v100", filter).unwrap_err();
+  assert!(matches!(err, DatabaseError::DatabaseViewNotExist));
+
+  let sort = TestSort {
+    id: "s1".to_string(),
+    field_id: "f1".to_string(),
+    field_type: 0,
+    condition: Default::default(),
+  };
+  let err = database_test.insert_sort("v100", sort).unwrap_err();
+  assert!(matches!(err, DatabaseError::DatabaseViewNotExist));
+
+  let group_setting = TestGroupSetting {
+    id: "g1".to_string(),
+    field_id: "f1".to_string(),
+    field_type: 0,
+    groups: vec![],
+    content: "".to_string(),
+  };
+  let err = database_test
+    .insert_group_setting("v100", group_setting)
+    .unwrap_err();
+  assert!(matches!(err, DatabaseError::DatabaseViewNotExist));
+
+  // Nothing is written to the document
+  assert_eq!(database_test.transact().state_vector(), state_vector);
+  assert!(database_test.get_view("v100").is_none());
+  assert_eq!(database_test.get_all_views().len(), 1);
+}
+
 #[tokio::test]
 async fn database_data_serde_test() {
   let database_id = uuid::Uuid::new_v4();
@@ -248,6 +455,34 @@ async fn database_data_serde_test() {
   assert_eq!(database_data.rows.len(), database_data2.rows.len());
 }
 
+#[tokio::test]
+async fn database_data_with_row_limit_test() {
+  let database_id = uuid::Uuid::new_v4();
+  let database_test = create_database_with_default_data(1, &database_id.to_string()).await;
+  let database_data = database_test.get_database_data_with_limit(Some(2)).await;
+  assert_eq!(database_data.rows.len(), 2);
+  assert_eq!(database_data.row_count, 3);
+  assert_eq!(
+    database_data.rows[0].id,
+    database_test.pre_define_row_ids[0]
+  );
+  assert_eq!(
+    database_data.rows[1].id,
+    database_test.pre_define_row_ids[1]
+  );
+
+  let json = database_data.to_json().unwrap();
+  let database_data2 = DatabaseData::from_json(&json).unwrap();
+  assert_eq!(database_data2.rows.len(), 2);
+  assert_eq!(database_data2.row_count, 3);
+
+  // The data serialized without row_count uses the number of rows
+  let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+  value.as_object_mut().unwrap().remove("row_count");
+  let database_data3 = DatabaseData::from_json(&value.to_string()).unwrap();
+  assert_eq!(database_data3.row_count, 2);
+}
+
 #[tokio::test]
 async fn get_database_view_layout_test() {
   let database_id = uuid::Uuid::new_v4();
@@ -261,9 +496,11 @@ async fn get_database_view_layout_test() {
 async fn update_database_view_layout_test() {
   let database_id = uuid::Uuid::new_v4();
   let mut database_test = create_database_with_default_data(1, &database_id.to_string()).await;
-  database_test.update_database_view("v1", |update| {
-    update.set_layout_type(DatabaseLayout::Calendar);
-  });
+  database_test
+    .update_database_view("v1", |update| {
+      update.set_layout_type(DatabaseLayout::Calendar);
+    })
+    .unwrap();
 
   let layout = database_test.get_database_view_layout("v1");
   assert_eq!(layout, DatabaseLayout::Calendar);
@@ -275,3 +512,162 @@ async fn validate_database_test() {
   let database_test = create_database_with_default_data(1, &database_id.to_string()).await;
   assert!(database_test.database.validate().is_ok())
 }
+
+#[tokio::test]
+async fn hide_row_in_view_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database_with_default_data(1, &database_id).await;
+  let params = CreateViewParams {
+    database_id: database_id.clone(),
+    view_id: "v2".to_string(),
+    ..Default::default()
+  };
+  database_test.create_linked_view(params).unwrap();
+
+  let row_id = database_test.pre_define_row_ids[0].clone();
+  database_test.set_row_hidden("v1", &row_id, true).unwrap();
+  // Hiding a hidden row again doesn't duplicate it
+  database_test.set_row_hidden("v1", &row_id, true).unwrap();
+  assert_eq!(database_test.get_hidden_rows("v1"), vec![row_id.clone()]);
+  assert!(database_test.get_hidden_rows("v2").is_empty());
+
+  let rows = rows_for_view(&database_test, "v1", false).await;
+  assert_eq!(rows.len(), 2);
+  assert!(rows.iter().all(|row| row.id != row_id));
+  assert_eq!(rows_for_view(&database_test, "v1", true).await.len(), 3);
+  assert_eq!(rows_for_view(&database_test, "v2", false).await.len(), 3);
+
+  // The row keeps its position when it's shown again
+  database_test.set_row_hidden("v1", &row_id, false).unwrap();
+  assert!(database_test.get_hidden_rows("v1").is_empty());
+  let rows = rows_for_view(&database_test, "v1", false).await;
+  assert_eq!(rows[0].id, row_id);
+
+  let err = database_test
+    .set_row_hidden("v100", &row_id, true)
+    .unwrap_err();
+  assert!(matches!(err, DatabaseError::DatabaseViewNotExist));
+
+  // Removing the row order of a hidden row removes its hidden state as well
+  database_test.set_row_hidden("v1", &row_id, true).unwrap();
+  database_test
+    .update_database_view("v1", |update| {
+      update.remove_row_order(&row_id);
+    })
+    .unwrap();
+  assert!(database_test.get_hidden_rows("v1").is_empty());
+}
+
+async fn rows_for_view(database: &Database, view_id: &str, include_hidden: bool) -> Vec<Row> {
+  database
+    .get_rows_for_view(view_id, 10, None, include_hidden)
+    .await
+    .filter_map(|result| async move { result.ok() })
+    .collect()
+    .await
+}
+
+#[tokio::test]
+async fn view_without_hidden_rows_deserialize_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let database_test = create_database_with_default_data(1, &database_id).await;
+  let view = database_test.get_view("v1").unwrap();
+  let mut value = serde_json::to_value(&view).unwrap();
+  value.as_object_mut().unwrap().remove("hidden_rows");
+
+  // The views serialized before the hidden rows were introduced don't have any hidden rows
+  let view = serde_json::from_value::<DatabaseView>(value).unwrap();
+  assert!(view.hidden_rows.is_empty());
+  assert_eq!(view.row_orders.len(), 3);
+}
+
+#[tokio::test]
+async fn row_color_in_view_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database_with_default_data(1, &database_id).await;
+  let row_id_1 = database_test.pre_define_row_ids[0].clone();
+  let row_id_2 = database_test.pre_define_row_ids[1].clone();
+  database_test
+    .set_row_color("v1", &row_id_1, Some("#FF0000"))
+    .unwrap();
+  database_test
+    .set_row_color("v1", &row_id_2, Some("#00FF00"))
+    .unwrap();
+  let row_colors = database_test.get_row_colors("v1");
+  assert_eq!(row_colors.len(), 2);
+  assert_eq!(row_colors[&row_id_1], "#FF0000");
+
+  // The row colors are included in the serialized database data
+  let json = database_test.get_database_data().await.to_json().unwrap();
+  let database_data = DatabaseData::from_json(&json).unwrap();
+  let view = database_data
+    .views
+    .into_iter()
+    .find(|view| view.id == "v1")
+    .unwrap();
+  assert_eq!(view.row_colors, row_colors);
+
+  database_test.set_row_color("v1", &row_id_2, None).unwrap();
+  assert!(!database_test.get_row_colors("v1").contains_key(&row_id_2));
+
+  // The color is removed with the row order
+  database_test.remove_row(&row_id_1).await;
+  assert!(database_test.get_row_colors("v1").is_empty());
+}
+
+#[tokio::test]
+async fn view_description_and_creator_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let mut database_test = create_database_with_default_data(1, &database_id).await;
+  database_test
+    .create_linked_view(CreateViewParams {
+      database_id: database_id.clone(),
+      view_id: "v2".to_string(),
+      name: "my second grid".to_string(),
+      description: "All the tasks of the team".to_string(),
+      created_by: Some(1),
+      ..Default::default()
+    })
+    .unwrap();
+  let view = database_test.get_view("v2").unwrap();
+  assert_eq!(view.description, "All the tasks of the team");
+  assert_eq!(view.created_by, Some(1));
+  assert_eq!(view.modified_by, Some(1));
+
+  database_test
+    .update_database_view("v2", |update| {
+      update
+        .set_description("The tasks of this week")
+        .set_modified_by(2);
+    })
+    .unwrap();
+  let view = database_test.get_view("v2").unwrap();
+  assert_eq!(view.description, "The tasks of this week");
+  assert_eq!(view.created_by, Some(1));
+  assert_eq!(view.modified_by, Some(2));
+
+  // The views created without the metadata keep the defaults
+  let view = database_test.get_view("v1").unwrap();
+  assert!(view.description.is_empty());
+  assert_eq!(view.created_by, None);
+  assert_eq!(view.modified_by, None);
+}
+
+#[tokio::test]
+async fn view_without_description_deserialize_test() {
+  let database_id = uuid::Uuid::new_v4().to_string();
+  let database_test = create_database_with_default_data(1, &database_id).await;
+  let view = database_test.get_view("v1").unwrap();
+
+  // The empty metadata isn't serialized, so the serialized view is the same as before
+  let value = serde_json::to_value(&view).unwrap();
+  let object = value.as_object().unwrap();
+  assert!(!object.contains_key("description"));
+  assert!(!object.contains_key("created_by"));
+  assert!(!object.contains_key("modified_by"));
+
+  let view = serde_json::from_value::<DatabaseView>(value).unwrap();
+  assert!(view.description.is_empty());
+  assert_eq!(view.created_by, None);
+  assert_eq!(view.modified_by, None);
+}
@@ -1,8 +1,11 @@
-use collab_database::database::{gen_database_id, gen_database_view_id, Database};
+use crate::database_test::helper::create_database_with_default_data;
+use collab_database::database::{gen_database_id, gen_database_view_id, gen_row_id, Database};
 use collab_database::entity::FieldType;
+use collab_database::error::DatabaseError;
 use collab_database::rows::Row;
 use collab_database::template::builder::DatabaseTemplateBuilder;
 use collab_database::template::entity::CELL_DATA;
+use collab_database::views::RowOrder;
 use futures::StreamExt;
 
 #[tokio::test]
@@ -147,3 +150,70 @@ async fn create_template_test() {
     println!("\n");
   }
 }
+
+#[tokio::test]
+async fn database_to_template_round_trip_test() {
+  let database_id = gen_database_id();
+  let database_test = create_database_with_default_data(1, &database_id).await;
+  let template = database_test.to_template("v1").await.unwrap();
+  assert!(template.database_id.is_empty());
+  assert!(template.rows.iter().all(|row| row.row_id.is_empty()));
+  assert_eq!(template.fields.len(), 3);
+
+  let expected_rows = rows_of_database(&database_test).await;
+
+  // The template can be instantiated repeatedly, each time with fresh ids
+  let database_1 = Database::create_with_template(template.clone())
+    .await
+    .unwrap();
+  let database_2 = Database::create_with_template(template).await.unwrap();
+  assert_ne!(database_1.get_database_id(), database_id);
+  assert_ne!(database_1.get_database_id(), database_2.get_database_id());
+
+  for database in [&database_1, &database_2] {
+    let fields = database.get_fields_in_view(&database.get_inline_view_id(), None);
+    let expected_fields = database_test.get_fields_in_view("v1", None);
+    assert_eq!(fields.len(), expected_fields.len());
+    for (field, expected_field) in fields.iter().zip(expected_fields.iter()) {
+      assert_eq!(field.id, expected_field.id);
+      assert_eq!(field.name, expected_field.name);
+      assert_eq!(field.field_type, expected_field.field_type);
+    }
+
+    let rows = rows_of_database(database).await;
+    assert_eq!(rows.len(), expected_rows.len());
+    for (row, expected_row) in rows.iter().zip(expected_rows.iter()) {
+      assert_ne!(row.id, expected_row.id);
+      assert_eq!(row.cells, expected_row.cells);
+    }
+  }
+}
+
+#[tokio::test]
+async fn database_to_template_with_missing_row_test() {
+  let database_id = gen_database_id();
+  let mut database_test = create_database_with_default_data(1, &database_id).await;
+  let missing_row_id = gen_row_id();
+  database_test
+    .update_database_view("v1", |update| {
+      update.set_row_orders(vec![RowOrder::new(missing_row_id.clone(), 60)]);
+    })
+    .unwrap();
+
+  // The row that can't be loaded isn't silently left out of the template
+  let err = database_test.to_template("v1").await.unwrap_err();
+  match err {
+    DatabaseError::DatabaseRowNotFound { row_id, .. } => assert_eq!(row_id, missing_row_id),
+    err => panic!("unexpected error: {:?}", err),
+  }
+}
+
+async fn rows_of_database(database: &Database) -> Vec<Row> {
+  let view_id = database.get_inline_view_id();
+  database
+    .get_rows_for_view(&view_id, 10, None, true)
+    .await
+    .filter_map(|result| async move { result.ok() })
+    .collect()
+    .await
+}
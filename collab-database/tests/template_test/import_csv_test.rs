@@ -1,9 +1,18 @@
-use collab_database::database::Database;
+use std::sync::Arc;
+
+use collab_database::database::{Database, DatabaseContext};
+use collab_database::entity::FieldType;
+use collab_database::fields::select_type_option::SelectTypeOption;
 use collab_database::rows::Row;
-use collab_database::template::csv::CSVTemplate;
+use collab_database::template::csv::{CSVTemplate, CSVTemplateOptions};
+use collab_database::template::csv_stream::DEFAULT_CSV_CHUNK_SIZE;
 use collab_database::template::entity::CELL_DATA;
+use collab_database::workspace_database::InMemoryDatabaseCollabService;
 use futures::StreamExt;
 
+use crate::helper::make_rocks_db;
+use crate::user_test::helper::TestUserDatabaseServiceImpl;
+
 #[tokio::test]
 async fn import_csv_test() {
   let csv_data = include_str!("../asset/selected-services-march-2024-quarter-csv.csv");
@@ -43,6 +52,12 @@ async fn import_csv_test() {
   assert_eq!(rows.len(), csv_rows.len());
   assert_eq!(rows.len(), 1200);
 
+  // The row ids are ordered by their creation, so sorting by id keeps the order of the file
+  let row_ids = rows.iter().map(|row| row.id.clone()).collect::<Vec<_>>();
+  let mut sorted_row_ids = row_ids.clone();
+  sorted_row_ids.sort();
+  assert_eq!(row_ids, sorted_row_ids);
+
   assert_eq!(fields.len(), csv_fields.len());
   assert_eq!(fields.len(), 14);
 
@@ -69,3 +84,301 @@ async fn import_csv_test() {
     }
   }
 }
+
+async fn field_names_of_template(csv_template: CSVTemplate) -> Vec<String> {
+  csv_template
+    .try_into_database_template(None)
+    .await
+    .unwrap()
+    .fields
+    .into_iter()
+    .map(|field| field.name)
+    .collect()
+}
+
+#[tokio::test]
+async fn import_csv_with_semicolon_delimiter_test() {
+  let csv_data = "name;price\nApple;\"1,5\"\nPear;\"2;5\"\n";
+  let options = CSVTemplateOptions {
+    delimiter: b';',
+    ..Default::default()
+  };
+  let csv_template =
+    CSVTemplate::from_reader_with_options(csv_data.as_bytes(), false, None, options).unwrap();
+  // The quoted values keep the delimiter
+  assert_eq!(
+    csv_template.rows,
+    vec![
+      vec!["Apple".to_string(), "1,5".to_string()],
+      vec!["Pear".to_string(), "2;5".to_string()],
+    ]
+  );
+  assert_eq!(
+    field_names_of_template(csv_template).await,
+    vec!["name", "price"]
+  );
+}
+
+#[tokio::test]
+async fn import_csv_with_tab_delimiter_test() {
+  let csv_data = "name\tstatus\nTask 1\tDone\nTask 2\t\"In\tProgress\"\n";
+  let options = CSVTemplateOptions {
+    delimiter: b'\t',
+    ..Default::default()
+  };
+  let csv_template =
+    CSVTemplate::from_reader_with_options(csv_data.as_bytes(), false, None, options).unwrap();
+  assert_eq!(
+    csv_template.rows,
+    vec![
+      vec!["Task 1".to_string(), "Done".to_string()],
+      vec!["Task 2".to_string(), "In\tProgress".to_string()],
+    ]
+  );
+  assert_eq!(
+    field_names_of_template(csv_template).await,
+    vec!["name", "status"]
+  );
+}
+
+#[tokio::test]
+async fn import_csv_without_header_test() {
+  let csv_data = " Apple , 1\nPear, 2\n";
+  let options = CSVTemplateOptions {
+    has_header: false,
+    trim_fields: true,
+    ..Default::default()
+  };
+  let csv_template =
+    CSVTemplate::from_reader_with_options(csv_data.as_bytes(), false, None, options).unwrap();
+  // The first record is a row when the content doesn't have a header
+  assert_eq!(
+    csv_template.rows,
+    vec![
+      vec!["Apple".to_string(), "1".to_string()],
+      vec!["Pear".to_string(), "2".to_string()],
+    ]
+  );
+  assert_eq!(
+    field_names_of_template(csv_template).await,
+    vec!["Column 1", "Column 2"]
+  );
+}
+
+#[tokio::test]
+async fn import_csv_with_inferred_field_types_test() {
+  let csv_data = "name,amount,due,done,status,notes
+Task 1,10,2024-08-22,Yes,Done,first
+Task 2,2.5,2024-08-23,No,In Progress,second note
+Task 3,7,2024-08-24,yes,Done,third
+Task 4,,2024-08-25,No,In Progress,fourth
+";
+  let csv_template = CSVTemplate::try_from_reader(csv_data.as_bytes(), true, None).unwrap();
+  let template = csv_template.try_into_database_template(None).await.unwrap();
+  let field_types = template
+    .fields
+    .iter()
+    .map(|field| field.field_type)
+    .collect::<Vec<_>>();
+  assert_eq!(
+    field_types,
+    vec![
+      FieldType::RichText,
+      FieldType::Number,
+      FieldType::DateTime,
+      FieldType::Checkbox,
+      FieldType::SingleSelect,
+      // Falls back to text
+      FieldType::RichText,
+    ]
+  );
+
+  let cell_data = |row_index: usize, field_index: usize| {
+    template.rows[row_index]
+      .cells
+      .get(&template.fields[field_index].field_id)
+      .and_then(|cell| cell.get(CELL_DATA))
+      .and_then(|data| data.clone().cast::<String>().ok())
+      .unwrap_or_default()
+  };
+  assert_eq!(cell_data(1, 1), "2.5");
+  assert!(cell_data(0, 2).parse::<i64>().is_ok());
+  // The checkbox cells keep the text of the file, which is read as a boolean
+  assert_eq!(cell_data(0, 3), "Yes");
+  assert_eq!(cell_data(1, 3), "No");
+
+  // The select cells store the ids of the options created from the cells
+  let type_option =
+    SelectTypeOption::from(template.fields[4].type_options[&FieldType::SingleSelect].clone());
+  let option_names = type_option
+    .options
+    .iter()
+    .map(|option| option.name.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(option_names, vec!["Done", "In Progress"]);
+  let done_option = &type_option.options[0];
+  assert_eq!(cell_data(0, 4), done_option.id);
+  assert_eq!(cell_data(2, 4), done_option.id);
+  assert_eq!(cell_data(1, 5), "second note");
+}
+
+#[test]
+fn stream_large_csv_test() {
+  let num_rows = 100_000;
+  let mut csv_data = String::from("name,amount\n");
+  for i in 0..num_rows {
+    csv_data.push_str(&format!("row {},{}\n", i, i % 100));
+  }
+
+  let mut csv_template = CSVTemplate::from_reader(csv_data.as_bytes(), true, None).unwrap();
+  assert_eq!(csv_template.fields.len(), 2);
+  let mut count = 0;
+  loop {
+    let rows = csv_template.next_rows(DEFAULT_CSV_CHUNK_SIZE);
+    if rows.is_empty() {
+      break;
+    }
+    assert!(rows.len() <= DEFAULT_CSV_CHUNK_SIZE);
+    for row in rows {
+      if count % 9973 == 0 {
+        assert_eq!(
+          row,
+          vec![format!("row {}", count), (count % 100).to_string()]
+        );
+      }
+      count += 1;
+    }
+  }
+  assert_eq!(count, num_rows);
+}
+
+#[tokio::test]
+async fn stream_csv_into_database_test() {
+  let csv_data = "name,status
+Task 1,Done
+Task 2,In Progress
+Task 3,Done
+Task 4,Blocked
+Task 5,In Progress
+";
+  let csv_template = CSVTemplate::from_reader(csv_data.as_bytes(), true, None).unwrap();
+  // Inserts the rows two by two, so the options are created across the chunks
+  let (database, _) = csv_template.into_database(None, 2).await.unwrap();
+
+  let fields = database.get_fields_in_view(&database.get_inline_view_id(), None);
+  assert_eq!(fields.len(), 2);
+  assert_eq!(
+    FieldType::from(fields[1].field_type),
+    FieldType::SingleSelect
+  );
+  let option_names = fields[1]
+    .get_select_options()
+    .into_iter()
+    .map(|option| option.name)
+    .collect::<Vec<_>>();
+  assert_eq!(option_names, vec!["Done", "In Progress", "Blocked"]);
+
+  let rows: Vec<Row> = database
+    .get_all_rows(20, None)
+    .await
+    .filter_map(|result| async move { result.ok() })
+    .collect()
+    .await;
+  assert_eq!(rows.len(), 5);
+  let reader = database.get_cell_reader(&fields[1].id).unwrap();
+  let statuses = rows
+    .iter()
+    .map(|row| reader.stringify_cell(row.cells.get(&fields[1].id).unwrap()))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    statuses,
+    vec!["Done", "In Progress", "Done", "Blocked", "In Progress"]
+  );
+}
+
+#[tokio::test]
+async fn stream_csv_into_database_without_row_cache_test() {
+  let mut csv_data = "name,count\n".to_string();
+  for i in 0..25 {
+    csv_data.push_str(&format!("row {},{}\n", i, i));
+  }
+  let collab_service = Arc::new(TestUserDatabaseServiceImpl {
+    uid: 1,
+    workspace_id: uuid::Uuid::new_v4().to_string(),
+    db: make_rocks_db(),
+  });
+  let csv_template = CSVTemplate::from_reader(csv_data.as_bytes(), true, None).unwrap();
+  let (database, _) = csv_template
+    .into_database_with_context(None, 10, DatabaseContext::new(collab_service))
+    .await
+    .unwrap();
+
+  // The imported rows are saved to disk instead of being kept in memory
+  assert!(database.body.block.row_mem_cache.is_empty());
+  let view_id = database.get_inline_view_id();
+  assert_eq!(database.get_row_orders_for_view(&view_id).len(), 25);
+
+  let rows: Vec<Row> = database
+    .get_all_rows(20, None)
+    .await
+    .filter_map(|result| async move { result.ok() })
+    .collect()
+    .await;
+  assert_eq!(rows.len(), 25);
+  let primary_field = database.get_primary_field().unwrap();
+  let reader = database.get_cell_reader(&primary_field.id).unwrap();
+  assert_eq!(
+    reader.stringify_cell(rows[24].cells.get(&primary_field.id).unwrap()),
+    "row 24"
+  );
+}
+
+#[tokio::test]
+async fn stream_csv_into_in_memory_database_keeps_row_cache_within_chunk_test() {
+  let mut csv_data = "name,count\n".to_string();
+  for i in 0..25 {
+    csv_data.push_str(&format!("row {},{}\n", i, i));
+  }
+  let context = DatabaseContext::new(Arc::new(InMemoryDatabaseCollabService::default()));
+  let csv_template = CSVTemplate::from_reader(csv_data.as_bytes(), true, None).unwrap();
+  let (database, _) = csv_template
+    .into_database_with_context(None, 10, context)
+    .await
+    .unwrap();
+  assert!(database.body.block.row_mem_cache.is_empty());
+
+  // Read the rows chunk by chunk and evict each chunk afterwards
+  let view_id = database.get_inline_view_id();
+  let row_orders = database.get_row_orders_for_view(&view_id);
+  let mut names = vec![];
+  let primary_field = database.get_primary_field().unwrap();
+  let reader = database.get_cell_reader(&primary_field.id).unwrap();
+  for row_orders in row_orders.chunks(10) {
+    let rows: Vec<Row> = database
+      .get_rows_from_row_orders(row_orders, 10, None)
+      .await
+      .filter_map(|result| async move { result.ok() })
+      .collect()
+      .await;
+    assert!(database.body.block.row_mem_cache.len() <= 10);
+    names.extend(
+      rows
+        .iter()
+        .map(|row| reader.stringify_cell(row.cells.get(&primary_field.id).unwrap())),
+    );
+
+    let row_ids = row_orders
+      .iter()
+      .map(|row_order| row_order.id.clone())
+      .collect::<Vec<_>>();
+    database.evict_rows(&row_ids).await.unwrap();
+    assert!(database.body.block.row_mem_cache.is_empty());
+  }
+  assert_eq!(names.len(), 25);
+  assert_eq!(names[24], "row 24");
+
+  // The evicted rows are encoded without being opened again
+  let encoded_database = database.encode_database_collabs().await.unwrap();
+  assert_eq!(encoded_database.encoded_row_collabs.len(), 25);
+  assert!(database.body.block.row_mem_cache.is_empty());
+}
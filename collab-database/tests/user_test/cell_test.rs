@@ -26,7 +26,8 @@ async fn insert_cell_test() {
         });
       });
     })
-    .await;
+    .await
+    .unwrap();
 
   let row = database.read().await.get_row(&1.into()).await;
   let cell = row.cells.get("f1").unwrap();
@@ -51,7 +52,8 @@ async fn update_cell_test() {
       });
     });
   })
-  .await;
+  .await
+  .unwrap();
 
   db.update_row(1.into(), |row_update| {
     row_update.update_cells(|cells_update| {
@@ -63,7 +65,8 @@ async fn update_cell_test() {
       });
     });
   })
-  .await;
+  .await
+  .unwrap();
 
   let row = db.get_row(&1.into()).await;
   let cell = row.cells.get("f1").unwrap();
@@ -96,7 +99,7 @@ async fn update_not_exist_row_test() {
     .unwrap();
 
   let mut db = database.write().await;
-  db.update_row(1.into(), |_row_update| {}).await;
+  db.update_row(1.into(), |_row_update| {}).await.unwrap();
   let row = db.get_row(&1.into()).await;
   // If the row with the given id does not exist, the get_row method will return a empty Row
   assert!(row.is_empty())
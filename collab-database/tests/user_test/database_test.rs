@@ -1,11 +1,31 @@
 use crate::user_test::helper::{
   make_default_grid, random_uid, user_database_test_with_db, user_database_test_with_default_data,
-  workspace_database_test,
+  workspace_database_test, TestUserDatabaseServiceImpl, WorkspaceDatabaseTest,
 };
-use collab_database::database::gen_database_view_id;
-use collab_database::entity::{CreateDatabaseParams, CreateViewParams, FileUploadType};
+use async_trait::async_trait;
+use collab::entity::EncodedCollab;
+use collab::preclude::Collab;
+use collab_database::database::{gen_database_view_id, gen_row_id};
+use collab_database::entity::{CreateDatabaseParams, CreateViewParams, FieldType, FileUploadType};
+use collab_database::error::DatabaseError;
+use collab_database::fields::relation_type_option::RelationTypeOption;
+use collab_database::fields::Field;
 use collab_database::rows::{CoverType, CreateRowParams, Row, RowCover};
+use collab_database::template::relation_parse::RelationCellData;
+use collab_database::views::OrderObjectPosition;
+use collab_database::workspace_database::{
+  DatabaseCollabPersistenceService, DatabaseCollabService, EncodeCollabByOid,
+  WorkspaceDatabaseManager,
+};
+use collab_entity::CollabType;
+use collab_plugins::local_storage::kv::doc::CollabKVAction;
+use collab_plugins::local_storage::kv::KVTransactionDB;
 use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
 use uuid::Uuid;
 
 #[tokio::test]
@@ -130,7 +150,7 @@ async fn duplicate_database_inline_view_test() {
     .unwrap();
 
   assert_eq!(
-    db.get_rows_for_view(&duplicated_view_id, 20, None)
+    db.get_rows_for_view(&duplicated_view_id, 20, None, true)
       .await
       .count()
       .await,
@@ -140,7 +160,7 @@ async fn duplicate_database_inline_view_test() {
     database
       .read()
       .await
-      .get_rows_for_view("v1", 10, None)
+      .get_rows_for_view("v1", 10, None, true)
       .await
       .count()
       .await,
@@ -178,20 +198,26 @@ async fn duplicate_database_view_test() {
 
   // Duplicate the linked view.
   let mut db = database.write().await;
-  let duplicated_view = db.duplicate_linked_view("v2").unwrap();
+  let (_, duplicated_view) = db.duplicate_linked_view("v2").unwrap();
   db.create_row(CreateRowParams::new(1, database_id.to_string()))
     .await
     .unwrap();
 
   // Duplicated database should have the same rows as the original database
   assert_eq!(
-    db.get_rows_for_view(&duplicated_view.id, 10, None)
+    db.get_rows_for_view(&duplicated_view.id, 10, None, true)
+      .await
+      .count()
+      .await,
+    1
+  );
+  assert_eq!(
+    db.get_rows_for_view("v1", 10, None, true)
       .await
       .count()
       .await,
     1
   );
-  assert_eq!(db.get_rows_for_view("v1", 10, None).await.count().await, 1);
 }
 
 #[tokio::test]
@@ -270,6 +296,51 @@ async fn delete_database_inline_view_test() {
   assert_eq!(views.len(), 3);
 }
 
+#[tokio::test]
+async fn delete_view_update_database_meta_test() {
+  let mut test = workspace_database_test(random_uid()).await;
+  let database_id = Uuid::new_v4().to_string();
+  let database = test
+    .create_database(CreateDatabaseParams {
+      database_id: database_id.clone(),
+      views: vec![CreateViewParams {
+        database_id: database_id.clone(),
+        view_id: "v1".to_string(),
+        ..Default::default()
+      }],
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+  test
+    .create_database_linked_view(CreateViewParams {
+      database_id: database_id.clone(),
+      view_id: "v2".to_string(),
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+  let meta = test.get_database_meta(&database_id).unwrap();
+  assert!(meta.linked_views.contains(&"v2".to_string()));
+
+  // Deleting a linked view only detaches the view from the database meta
+  test.delete_view(&database_id, "v2").await;
+  let meta = test.get_database_meta(&database_id).unwrap();
+  assert!(!meta.linked_views.contains(&"v2".to_string()));
+  assert!(meta.linked_views.contains(&"v1".to_string()));
+  assert!(test.get_database_id_with_view_id("v2").is_none());
+  assert_eq!(
+    test.get_database_id_with_view_id("v1").unwrap(),
+    database_id
+  );
+
+  // Deleting the inline view removes the database meta
+  let inline_view_id = database.read().await.get_inline_view_id();
+  test.delete_view(&database_id, &inline_view_id).await;
+  assert!(test.get_database_meta(&database_id).is_none());
+  assert!(test.get_database_id_with_view_id("v1").is_none());
+}
+
 #[tokio::test]
 async fn duplicate_database_data_test() {
   let mut test = user_database_test_with_default_data(random_uid()).await;
@@ -283,14 +354,14 @@ async fn duplicate_database_data_test() {
 
   // compare rows
   let original_rows: Vec<Row> = original
-    .get_rows_for_view("v1", 10, None)
+    .get_rows_for_view("v1", 10, None, true)
     .await
     .filter_map(|result| async { result.ok() })
     .collect()
     .await;
 
   let duplicate_rows: Vec<Row> = duplicate
-    .get_rows_for_view(duplicated_view_id, 10, None)
+    .get_rows_for_view(duplicated_view_id, 10, None, true)
     .await
     .filter_map(|result| async { result.ok() })
     .collect()
@@ -420,3 +491,260 @@ async fn reopen_database_test() {
   }
   let _ = database.read().await.to_json_value().await;
 }
+
+#[tokio::test]
+async fn close_database_with_pending_row_fetch_test() {
+  let uid = random_uid();
+  let mut test = workspace_database_test(uid).await;
+  let view_id = gen_database_view_id();
+  let params = make_default_grid(&view_id, "first view");
+  let database_id = params.database_id.clone();
+  test.create_database(params).await.unwrap();
+  let db = test.collab_db.clone();
+  let workspace_id = test.workspace_id.clone();
+  drop(test);
+
+  // Reopen the workspace, so the rows of the database aren't in memory
+  let collab_service = PendingRowFetchService {
+    inner: TestUserDatabaseServiceImpl {
+      uid,
+      workspace_id,
+      db,
+    },
+    is_pending: Arc::new(AtomicBool::new(false)),
+    started: Arc::new(Notify::new()),
+    release: Arc::new(Notify::new()),
+    completed: Arc::new(AtomicUsize::new(0)),
+  };
+  let is_pending = collab_service.is_pending.clone();
+  let started = collab_service.started.clone();
+  let release = collab_service.release.clone();
+  let completed = collab_service.completed.clone();
+  let workspace_database_id = "database_views_aggregate_id";
+  let collab = collab_service
+    .build_collab(workspace_database_id, CollabType::WorkspaceDatabase, None)
+    .await
+    .unwrap();
+  let manager =
+    WorkspaceDatabaseManager::create(workspace_database_id, collab, collab_service).unwrap();
+  let database = manager.get_database_with_view_id(&view_id).await.unwrap();
+  let weak_database = Arc::downgrade(&database);
+
+  is_pending.store(true, Ordering::SeqCst);
+  let fetch = tokio::spawn(async move {
+    let database = database.read().await;
+    database
+      .get_rows_for_view(&view_id, 10, None, true)
+      .await
+      .collect::<Vec<_>>()
+      .await
+  });
+  started.notified().await;
+
+  // The rows are fetched in the future of the caller, so closing the database and dropping the
+  // future leaves no task behind that completes the fetch
+  manager.close_database(&database_id);
+  fetch.abort();
+  assert!(fetch.await.unwrap_err().is_cancelled());
+  release.notify_waiters();
+  tokio::time::sleep(Duration::from_millis(100)).await;
+  assert_eq!(completed.load(Ordering::SeqCst), 0);
+  assert!(weak_database.upgrade().is_none());
+}
+
+/// Holds the batch fetches of the rows until `release` is notified
+struct PendingRowFetchService {
+  inner: TestUserDatabaseServiceImpl,
+  is_pending: Arc<AtomicBool>,
+  started: Arc<Notify>,
+  release: Arc<Notify>,
+  completed: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl DatabaseCollabService for PendingRowFetchService {
+  async fn build_collab(
+    &self,
+    object_id: &str,
+    object_type: CollabType,
+    encoded_collab: Option<(EncodedCollab, bool)>,
+  ) -> Result<Collab, DatabaseError> {
+    self
+      .inner
+      .build_collab(object_id, object_type, encoded_collab)
+      .await
+  }
+
+  async fn get_collabs(
+    &self,
+    object_ids: Vec<String>,
+    collab_type: CollabType,
+  ) -> Result<EncodeCollabByOid, DatabaseError> {
+    if collab_type == CollabType::DatabaseRow && self.is_pending.load(Ordering::SeqCst) {
+      self.started.notify_one();
+      self.release.notified().await;
+      self.completed.fetch_add(1, Ordering::SeqCst);
+    }
+    self.inner.get_collabs(object_ids, collab_type).await
+  }
+
+  fn persistence(&self) -> Option<Arc<dyn DatabaseCollabPersistenceService>> {
+    self.inner.persistence()
+  }
+
+  fn uid(&self) -> Option<i64> {
+    self.inner.uid()
+  }
+}
+
+#[tokio::test]
+async fn track_databases_in_one_update_test() {
+  let uid = random_uid();
+  let mut test = workspace_database_test(uid).await;
+  let number_of_updates = |test: &WorkspaceDatabaseTest| {
+    test
+      .collab_db
+      .read_txn()
+      .get_all_updates(
+        uid,
+        test.workspace_id.as_str(),
+        "database_views_aggregate_id",
+      )
+      .unwrap()
+      .len()
+  };
+  let updates_before = number_of_updates(&test);
+
+  let metas = (0..50)
+    .map(|i| (format!("d{}", i), vec![format!("v{}", i)]))
+    .collect::<Vec<_>>();
+  let skipped_database_ids = test.track_databases(metas);
+  assert!(skipped_database_ids.is_empty());
+  assert_eq!(test.get_all_database_meta().len(), 50);
+  assert_eq!(number_of_updates(&test), updates_before + 1);
+
+  // The databases that are already tracked are skipped
+  let skipped_database_ids = test.track_databases(vec![
+    ("d0".to_string(), vec!["v0".to_string()]),
+    ("d50".to_string(), vec!["v50".to_string()]),
+    ("d50".to_string(), vec!["v51".to_string()]),
+  ]);
+  assert_eq!(
+    skipped_database_ids,
+    vec!["d0".to_string(), "d50".to_string()]
+  );
+  let all_databases = test.get_all_database_meta();
+  assert_eq!(all_databases.len(), 51);
+  assert_eq!(all_databases[50].linked_views, vec!["v50".to_string()]);
+}
+
+#[tokio::test]
+async fn link_rows_between_databases_test() {
+  let uid = random_uid();
+  let mut test = workspace_database_test(uid).await;
+  let database_id_1 = Uuid::new_v4().to_string();
+  let database_id_2 = Uuid::new_v4().to_string();
+  let database_1 = test
+    .create_database(CreateDatabaseParams {
+      database_id: database_id_1.clone(),
+      views: vec![CreateViewParams {
+        database_id: database_id_1.clone(),
+        view_id: "v1".to_string(),
+        ..Default::default()
+      }],
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+  let database_2 = test
+    .create_database(CreateDatabaseParams {
+      database_id: database_id_2.clone(),
+      views: vec![CreateViewParams {
+        database_id: database_id_2.clone(),
+        view_id: "v2".to_string(),
+        ..Default::default()
+      }],
+      ..Default::default()
+    })
+    .await
+    .unwrap();
+
+  let related_row_ids = {
+    let mut db_2 = database_2.write().await;
+    let mut row_ids = vec![];
+    for _ in 0..2 {
+      let row_order = db_2
+        .create_row(CreateRowParams::new(gen_row_id(), database_id_2.clone()))
+        .await
+        .unwrap();
+      row_ids.push(row_order.id);
+    }
+    row_ids
+  };
+
+  let mut db_1 = database_1.write().await;
+  let field = Field::from_field_type("relation", FieldType::Relation, false).with_type_option_data(
+    FieldType::Relation.type_id(),
+    RelationTypeOption {
+      database_id: database_id_2.clone(),
+    }
+    .into(),
+  );
+  db_1.create_field(
+    None,
+    field.clone(),
+    &OrderObjectPosition::default(),
+    HashMap::new(),
+  );
+  let row_id = db_1
+    .create_row(CreateRowParams::new(gen_row_id(), database_id_1.clone()))
+    .await
+    .unwrap()
+    .id;
+
+  for related_row_id in related_row_ids.iter().chain(related_row_ids.iter()) {
+    db_1
+      .add_related_row(&field.id, &row_id, related_row_id.clone())
+      .await
+      .unwrap();
+  }
+  let cell = db_1.get_cell(&field.id, &row_id).await.cell.unwrap();
+  // Linking a row twice doesn't duplicate it
+  assert_eq!(RelationCellData::from(&cell).row_ids, related_row_ids);
+
+  // Linking a row that is already linked doesn't write the cell nor stamp the row
+  let mut row_change_rx = db_1.subscribe_row_change().unwrap();
+  db_1
+    .add_related_row(&field.id, &row_id, related_row_ids[0].clone())
+    .await
+    .unwrap();
+  assert!(row_change_rx.try_recv().is_err());
+
+  db_1
+    .remove_related_row(&field.id, &row_id, &related_row_ids[0])
+    .await
+    .unwrap();
+  let cell = db_1.get_cell(&field.id, &row_id).await.cell.unwrap();
+  assert_eq!(
+    RelationCellData::from(&cell).row_ids,
+    vec![related_row_ids[1].clone()]
+  );
+
+  let meta = test.get_related_database_meta(&field).unwrap();
+  assert_eq!(meta.database_id, database_id_2);
+  assert!(meta.linked_views.contains(&"v2".to_string()));
+
+  // Only the relation fields can link rows
+  let text_field = Field::from_field_type("text", FieldType::RichText, false);
+  db_1.create_field(
+    None,
+    text_field.clone(),
+    &OrderObjectPosition::default(),
+    HashMap::new(),
+  );
+  assert!(db_1
+    .add_related_row(&text_field.id, &row_id, related_row_ids[0].clone())
+    .await
+    .is_err());
+  assert!(test.get_related_database_meta(&text_field).is_none());
+}
@@ -13,7 +13,7 @@ use collab_database::rows::{Cells, CreateRowParams};
 use collab_database::views::DatabaseLayout;
 use collab_database::workspace_database::{
   DatabaseCollabPersistenceService, DatabaseCollabService, EncodeCollabByOid, RowRelationChange,
-  RowRelationUpdateReceiver, WorkspaceDatabaseManager,
+  RowRelationUpdateReceiver, WorkspaceDatabase, WorkspaceDatabaseManager,
 };
 use collab_entity::CollabType;
 use collab_plugins::local_storage::CollabPersistenceConfig;
@@ -231,6 +231,16 @@ impl DatabaseCollabService for TestUserDatabaseServiceImpl {
       db: self.db.clone(),
     }))
   }
+
+  fn uid(&self) -> Option<i64> {
+    Some(self.uid)
+  }
+}
+
+/// Creates an in-memory [WorkspaceDatabase] without any database
+pub fn create_workspace_database() -> WorkspaceDatabase {
+  let collab = Collab::new_with_origin(CollabOrigin::Empty, "w1", vec![], false);
+  WorkspaceDatabase::create(collab)
 }
 
 pub async fn workspace_database_test(uid: i64) -> WorkspaceDatabaseTest {
@@ -352,6 +362,7 @@ fn create_database_params(database_id: &str) -> CreateDatabaseParams {
     }],
     rows: vec![row_1, row_2, row_3],
     fields: vec![field_1, field_2, field_3],
+    ..Default::default()
   }
 }
 
@@ -397,6 +408,7 @@ pub fn make_default_grid(view_id: &str, name: &str) -> CreateDatabaseParams {
       CreateRowParams::new(gen_row_id(), database_id.clone()),
     ],
     fields: vec![text_field, single_select_field, checkbox_field],
+    ..Default::default()
   }
 }
 
@@ -5,3 +5,5 @@ pub mod helper;
 // mod snapshot_test;
 // mod async_test;
 mod type_option_test;
+mod workspace_database_observe_test;
+mod workspace_database_test;
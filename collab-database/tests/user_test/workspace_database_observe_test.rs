@@ -0,0 +1,119 @@
+use std::future::Future;
+use std::time::Duration;
+
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::updates::decoder::Decode;
+use collab::preclude::{ReadTxn, StateVector, Update};
+use collab_database::workspace_database::{
+  WorkspaceDatabase, WorkspaceDatabaseChange, WorkspaceDatabaseChangeReceiver,
+};
+
+use crate::user_test::helper::create_workspace_database;
+
+#[tokio::test]
+async fn add_database_callback_test() {
+  let mut workspace_database = create_workspace_database();
+  let change_rx = workspace_database.subscribe_change();
+  tokio::spawn(async move {
+    workspace_database.add_database("d1", vec!["v1".to_string()]);
+  });
+
+  let changes = timeout(collect_changes(change_rx, 1)).await;
+  match &changes[0] {
+    WorkspaceDatabaseChange::DidAddDatabase { meta } => {
+      assert_eq!(meta.database_id, "d1");
+      assert_eq!(meta.linked_views, vec!["v1".to_string()]);
+    },
+    change => panic!("unexpected change: {:?}", change),
+  }
+}
+
+#[tokio::test]
+async fn update_and_delete_database_callback_test() {
+  let mut workspace_database = create_workspace_database();
+  workspace_database.add_database("d1", vec!["v1".to_string()]);
+  let change_rx = workspace_database.subscribe_change();
+  tokio::spawn(async move {
+    workspace_database.update_database("d1", |meta| meta.linked_views.push("v2".to_string()));
+    workspace_database.delete_database("d1");
+  });
+
+  let changes = timeout(collect_changes(change_rx, 2)).await;
+  match &changes[0] {
+    WorkspaceDatabaseChange::DidUpdateDatabase { meta } => {
+      assert_eq!(meta.database_id, "d1");
+      assert_eq!(meta.linked_views, vec!["v1".to_string(), "v2".to_string()]);
+    },
+    change => panic!("unexpected change: {:?}", change),
+  }
+  match &changes[1] {
+    WorkspaceDatabaseChange::DidDeleteDatabase { database_id } => assert_eq!(database_id, "d1"),
+    change => panic!("unexpected change: {:?}", change),
+  }
+}
+
+#[tokio::test]
+async fn remote_database_change_callback_test() {
+  let mut workspace_database_1 = create_workspace_database();
+  // The second device opens the workspace database created by the first one
+  let encoded_collab = workspace_database_1.encode_collab_v1().unwrap();
+  let mut workspace_database_2 = WorkspaceDatabase::from_collab_doc_state(
+    "w1",
+    CollabOrigin::Empty,
+    DataSource::DocStateV1(encoded_collab.doc_state.to_vec()),
+  )
+  .unwrap();
+  let change_rx = workspace_database_2.subscribe_change();
+
+  workspace_database_1.add_database("d1", vec!["v1".to_string()]);
+  workspace_database_1.add_database("d2", vec!["v2".to_string()]);
+  sync(&workspace_database_1, &mut workspace_database_2);
+  workspace_database_1.delete_database("d1");
+  sync(&workspace_database_1, &mut workspace_database_2);
+
+  let changes = timeout(collect_changes(change_rx, 3)).await;
+  let added_database_ids = changes[..2]
+    .iter()
+    .map(|change| match change {
+      WorkspaceDatabaseChange::DidAddDatabase { meta } => meta.database_id.clone(),
+      change => panic!("unexpected change: {:?}", change),
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(added_database_ids, vec!["d1".to_string(), "d2".to_string()]);
+  match &changes[2] {
+    WorkspaceDatabaseChange::DidDeleteDatabase { database_id } => assert_eq!(database_id, "d1"),
+    change => panic!("unexpected change: {:?}", change),
+  }
+  assert!(!workspace_database_2.contains("d1"));
+  assert!(workspace_database_2.contains("d2"));
+}
+
+/// Applies the updates of the first workspace database to the second one
+fn sync(from: &WorkspaceDatabase, to: &mut WorkspaceDatabase) {
+  let state_vector = to.collab.transact().state_vector();
+  let update = from
+    .collab
+    .transact()
+    .encode_state_as_update_v1(&state_vector);
+  to.collab
+    .apply_update(Update::decode_v1(&update).unwrap())
+    .unwrap();
+}
+
+async fn collect_changes(
+  mut rx: WorkspaceDatabaseChangeReceiver,
+  count: usize,
+) -> Vec<WorkspaceDatabaseChange> {
+  let mut changes = vec![];
+  while changes.len() < count {
+    changes.push(rx.recv().await.unwrap());
+  }
+  changes
+}
+
+async fn timeout<F: Future>(f: F) -> F::Output {
+  tokio::time::timeout(Duration::from_secs(2), f)
+    .await
+    .unwrap()
+}
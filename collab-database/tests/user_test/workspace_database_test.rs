@@ -0,0 +1,264 @@
+use collab::core::collab::DataSource;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::{Array, ArrayPrelim, ArrayRef, Collab, Map, MapPrelim, MapRef};
+use collab_database::error::DatabaseError;
+use collab_database::workspace_database::{DetachResult, WorkspaceDatabase};
+use collab_entity::define::WORKSPACE_DATABASES;
+
+use crate::user_test::helper::create_workspace_database;
+
+#[test]
+fn get_database_meta_test() {
+  let mut workspace_database = create_workspace_database();
+  let metas = (0..300)
+    .map(|i| {
+      (
+        format!("d{}", i),
+        vec![format!("v{}", i), format!("linked_v{}", i)],
+      )
+    })
+    .collect::<Vec<_>>();
+  workspace_database.add_databases(metas);
+
+  for i in [0, 1, 150, 299] {
+    let database_id = format!("d{}", i);
+    assert!(workspace_database.contains(&database_id));
+    let meta = workspace_database.get_database_meta(&database_id).unwrap();
+    assert_eq!(meta.database_id, database_id);
+
+    for view_id in [format!("v{}", i), format!("linked_v{}", i)] {
+      let meta = workspace_database
+        .get_database_meta_with_view_id(&view_id)
+        .unwrap();
+      assert_eq!(meta.database_id, database_id);
+    }
+  }
+  assert!(!workspace_database.contains("d300"));
+  assert!(workspace_database.get_database_meta("d300").is_none());
+  assert!(workspace_database
+    .get_database_meta_with_view_id("v300")
+    .is_none());
+}
+
+#[test]
+fn get_database_meta_with_view_id_after_update_test() {
+  let mut workspace_database = create_workspace_database();
+  workspace_database.add_database("d1", vec!["v1".to_string()]);
+  workspace_database.add_database("d2", vec!["v2".to_string()]);
+  assert_eq!(
+    workspace_database
+      .get_database_meta_with_view_id("v1")
+      .unwrap()
+      .database_id,
+    "d1"
+  );
+
+  // The lookup reflects the views linked after the first lookup
+  workspace_database.update_database("d2", |meta| meta.linked_views.push("v3".to_string()));
+  assert_eq!(
+    workspace_database
+      .get_database_meta_with_view_id("v3")
+      .unwrap()
+      .database_id,
+    "d2"
+  );
+  workspace_database.delete_database("d1");
+  assert!(workspace_database
+    .get_database_meta_with_view_id("v1")
+    .is_none());
+  assert_eq!(
+    workspace_database
+      .get_database_meta_with_view_id("v2")
+      .unwrap()
+      .database_id,
+    "d2"
+  );
+}
+
+#[test]
+fn get_duplicated_database_meta_test() {
+  let mut workspace_database = create_workspace_database();
+  workspace_database.add_database("d1", vec!["v1".to_string()]);
+  workspace_database.add_database("d2", vec!["v1".to_string()]);
+  workspace_database.add_database("d1", vec!["v2".to_string()]);
+
+  // The first meta wins
+  assert_eq!(
+    workspace_database
+      .get_database_meta_with_view_id("v1")
+      .unwrap()
+      .database_id,
+    "d1"
+  );
+  assert_eq!(
+    workspace_database
+      .get_database_meta("d1")
+      .unwrap()
+      .linked_views,
+    vec!["v1".to_string()]
+  );
+  let meta = workspace_database
+    .get_database_meta_with_view_id("v2")
+    .unwrap();
+  assert_eq!(meta.database_id, "d1");
+  assert_eq!(meta.linked_views, vec!["v2".to_string()]);
+}
+
+#[test]
+fn detach_view_test() {
+  let mut workspace_database = create_workspace_database();
+  workspace_database.add_database("d1", vec!["v1".to_string()]);
+  workspace_database.update_database("d1", |meta| {
+    meta.linked_views.push("v2".to_string());
+    meta.linked_views.push("v2".to_string());
+  });
+
+  // All the occurrences of the view are removed
+  assert_eq!(
+    workspace_database.detach_view("d1", "v2"),
+    DetachResult::Detached
+  );
+  assert_eq!(
+    workspace_database
+      .get_database_meta("d1")
+      .unwrap()
+      .linked_views,
+    vec!["v1".to_string()]
+  );
+  assert_eq!(
+    workspace_database.detach_view("d2", "v1"),
+    DetachResult::DatabaseNotFound
+  );
+}
+
+#[test]
+fn detach_last_view_remove_database_meta_test() {
+  let mut workspace_database = create_workspace_database();
+  workspace_database.add_database("d1", vec!["v1".to_string()]);
+  workspace_database.add_database("d2", vec!["v2".to_string()]);
+  workspace_database.update_database("d1", |meta| meta.linked_views.push("v1".to_string()));
+
+  assert_eq!(
+    workspace_database.detach_view("d1", "v1"),
+    DetachResult::DatabaseRemoved
+  );
+  assert!(!workspace_database.contains("d1"));
+  assert!(workspace_database
+    .get_database_meta_with_view_id("v1")
+    .is_none());
+  assert_eq!(workspace_database.get_all_database_meta().len(), 1);
+}
+
+#[test]
+fn trash_and_restore_database_test() {
+  let mut workspace_database = create_workspace_database();
+  workspace_database.add_database("d1", vec!["v1".to_string()]);
+  workspace_database.add_database("d2", vec!["v2".to_string()]);
+
+  assert!(workspace_database.trash_database("d1"));
+  assert!(!workspace_database.trash_database("d3"));
+  assert!(workspace_database
+    .get_database_meta("d1")
+    .unwrap()
+    .is_trashed());
+  let metas = workspace_database.get_all_database_meta_with_trash(false);
+  assert_eq!(metas.len(), 1);
+  assert_eq!(metas[0].database_id, "d2");
+  assert_eq!(
+    workspace_database
+      .get_all_database_meta_with_trash(true)
+      .len(),
+    2
+  );
+  assert_eq!(workspace_database.get_all_database_meta().len(), 2);
+
+  assert!(workspace_database.restore_database("d1"));
+  let meta = workspace_database.get_database_meta("d1").unwrap();
+  assert_eq!(meta.deleted_at, None);
+  assert_eq!(meta.linked_views, vec!["v1".to_string()]);
+  assert_eq!(
+    workspace_database
+      .get_all_database_meta_with_trash(false)
+      .len(),
+    2
+  );
+
+  // Deleting a database is still permanent
+  workspace_database.trash_database("d1");
+  workspace_database.delete_database("d1");
+  assert!(!workspace_database.contains("d1"));
+  assert!(!workspace_database.restore_database("d1"));
+}
+
+#[test]
+fn open_database_meta_without_deleted_at_test() {
+  // Build the doc state the way the metas were stored before the trash was introduced
+  let mut collab = Collab::new_with_origin(CollabOrigin::Empty, "w1", vec![], false);
+  {
+    let mut txn = collab.context.transact_mut();
+    let array_ref: ArrayRef = collab.data.get_or_init(&mut txn, WORKSPACE_DATABASES);
+    let map_ref: MapRef = array_ref.push_back(&mut txn, MapPrelim::default());
+    map_ref.insert(&mut txn, "database_id", "d1");
+    map_ref.insert(&mut txn, "created_at", 1_700_000_000_i64);
+    map_ref.insert(
+      &mut txn,
+      "views",
+      ArrayPrelim::from_iter(vec!["v1".to_string()]),
+    );
+  }
+  let encoded_collab = collab
+    .encode_collab_v1(|_| Ok::<_, DatabaseError>(()))
+    .unwrap();
+
+  let mut workspace_database = WorkspaceDatabase::from_collab_doc_state(
+    "w1",
+    CollabOrigin::Empty,
+    DataSource::from(encoded_collab),
+  )
+  .unwrap();
+  let meta = workspace_database.get_database_meta("d1").unwrap();
+  assert_eq!(meta.created_at, 1_700_000_000);
+  assert_eq!(meta.linked_views, vec!["v1".to_string()]);
+  assert_eq!(meta.deleted_at, None);
+  assert_eq!(
+    workspace_database
+      .get_all_database_meta_with_trash(false)
+      .len(),
+    1
+  );
+
+  assert!(workspace_database.trash_database("d1"));
+  assert!(workspace_database
+    .get_all_database_meta_with_trash(false)
+    .is_empty());
+}
+
+#[test]
+fn dedup_database_meta_test() {
+  let mut workspace_database = create_workspace_database();
+  workspace_database.add_database("d1", vec!["v1".to_string()]);
+  workspace_database.add_database("d2", vec!["v3".to_string()]);
+  workspace_database.add_database("d1", vec!["v1".to_string(), "v2".to_string()]);
+  workspace_database.update_database("d1", |meta| meta.created_at = 100);
+  assert!(workspace_database.validate().is_ok());
+  match workspace_database.validate_with_duplicates(true) {
+    Err(DatabaseError::DuplicateDatabaseMeta(database_ids)) => {
+      assert_eq!(database_ids, vec!["d1".to_string()])
+    },
+    result => panic!("unexpected result: {:?}", result),
+  }
+  // Reporting the duplicates doesn't fix them
+  assert_eq!(workspace_database.get_all_database_meta().len(), 3);
+
+  assert_eq!(workspace_database.dedup_database_meta(), 1);
+  let metas = workspace_database.get_all_database_meta();
+  assert_eq!(metas.len(), 2);
+  assert_eq!(metas[0].database_id, "d1");
+  assert_eq!(metas[0].created_at, 100);
+  let mut linked_views = metas[0].linked_views.clone();
+  linked_views.sort();
+  assert_eq!(linked_views, vec!["v1".to_string(), "v2".to_string()]);
+  assert_eq!(metas[1].database_id, "d2");
+  assert!(workspace_database.validate_with_duplicates(true).is_ok());
+  assert_eq!(workspace_database.dedup_database_meta(), 0);
+}
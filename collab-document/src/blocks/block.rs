@@ -11,9 +11,9 @@ pub const EXTERNAL_TYPE_MAP: &str = "map";
 
 const ID: &str = "id";
 const TYPE: &str = "ty";
-const PARENT: &str = "parent";
+pub(crate) const PARENT: &str = "parent";
 const CHILDREN: &str = "children";
-const DATA: &str = "data";
+pub(crate) const DATA: &str = "data";
 const EXTERNAL_ID: &str = "external_id";
 const EXTERNAL_TYPE: &str = "external_type";
 
@@ -143,10 +143,40 @@ impl BlockOperation {
     }
     Ok(())
   }
+
+  /// Merge the `patch` into the data of the block with the given id. The keys whose value is
+  /// null are removed, the other keys are kept as they are.
+  ///
+  /// The data is stored as one json string that the other clients read as it is, so the patch is
+  /// merged into the local data and the concurrent updates of the same block resolve to one of
+  /// the merged versions.
+  pub fn merge_block_data_with_txn(
+    &self,
+    txn: &mut TransactionMut,
+    id: &str,
+    patch: HashMap<String, Value>,
+  ) -> Result<(), DocumentError> {
+    let map: MapRef = self
+      .root
+      .get_with_txn(txn, id)
+      .ok_or(DocumentError::BlockIsNotFound)?;
+
+    let json_str: String = map.get_with_txn(txn, DATA).unwrap_or_default();
+    let mut data = json_str_to_hashmap(&json_str).unwrap_or_default();
+    for (key, value) in patch {
+      if value.is_null() {
+        data.remove(&key);
+      } else {
+        data.insert(key, value);
+      }
+    }
+    map.try_update(txn, DATA, hashmap_to_json_str(data)?);
+    Ok(())
+  }
 }
 
 /// Build the block from the [MapRef]
-fn block_from_map<T: ReadTxn>(txn: &T, map: MapRef) -> Block {
+pub(crate) fn block_from_map<T: ReadTxn>(txn: &T, map: MapRef) -> Block {
   let id: String = map.get_with_txn(txn, ID).unwrap_or_default();
   let ty: String = map.get_with_txn(txn, TYPE).unwrap_or_default();
   let parent: String = map.get_with_txn(txn, PARENT).unwrap_or_default();
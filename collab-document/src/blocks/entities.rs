@@ -33,6 +33,39 @@ pub struct DocumentMeta {
   /// - @key: [Block]'s `external_id`
   /// - @value: text delta json string - "\[ { "insert": "Hello World!", "attributes": { "bold": true } } \]"
   pub text_map: Option<HashMap<String, String>>,
+  /// The cover and icon of the page. The documents created before they were introduced don't
+  /// have them.
+  #[serde(flatten)]
+  pub page_meta: PageMeta,
+}
+
+/// The page level meta of the document.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct PageMeta {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub cover: Option<DocumentCover>,
+  /// The emoji or the icon name of the page.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub icon: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct DocumentCover {
+  #[serde(rename = "type")]
+  pub ty: CoverType,
+  /// The color, the name of the built-in image or the url of the image, depending on the `ty`.
+  pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CoverType {
+  Color,
+  Gradient,
+  BuiltInImage,
+  CustomImage,
+  LocalImage,
+  UnsplashImage,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
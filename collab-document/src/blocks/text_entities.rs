@@ -135,7 +135,7 @@ impl<'de> Deserialize<'de> for TextDelta {
         // Initialize variables to store deserialized fields
         let mut delta_type: Option<String> = None;
         let mut content: Option<String> = None;
-        let mut len: Option<usize> = None;
+        let mut len: Option<u32> = None;
         let mut attrs: Option<HashMap<Arc<str>, Any>> = None;
 
         // Deserialize each key-value pair in the map
@@ -209,11 +209,11 @@ impl<'de> Deserialize<'de> for TextDelta {
             },
             // If "delta_type" is "delete," construct a "Deleted" TextDelta variant
             FIELD_DELETE => Ok(TextDelta::Deleted(
-              len.ok_or_else(|| de::Error::missing_field(FIELD_DELETE))? as u32,
+              len.ok_or_else(|| de::Error::missing_field(FIELD_DELETE))?,
             )),
             // If "delta_type" is "retain," construct a "Retain" TextDelta variant
             FIELD_RETAIN => Ok(TextDelta::Retain(
-              len.ok_or_else(|| de::Error::missing_field(FIELD_RETAIN))? as u32,
+              len.ok_or_else(|| de::Error::missing_field(FIELD_RETAIN))?,
               attrs,
             )),
             // If "delta_type" is an unknown variant, return an error
@@ -148,3 +148,9 @@ pub fn get_delta_with_text_ref<T: ReadTxn>(text_ref: &TextRef, txn: &T) -> Vec<D
 pub fn deserialize_text_delta(delta: &str) -> serde_json::Result<Vec<TextDelta>> {
   serde_json::from_str::<Vec<TextDelta>>(delta)
 }
+
+/// Parse the delta json string, e.g. `[{"insert": "Hello", "attributes": {"bold": true}}]`.
+/// Returns [DocumentError::InvalidTextDelta] with the reason if the delta is malformed.
+pub fn parse_text_delta(delta: &str) -> Result<Vec<TextDelta>, DocumentError> {
+  deserialize_text_delta(delta).map_err(|err| DocumentError::InvalidTextDelta(err.to_string()))
+}
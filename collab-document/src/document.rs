@@ -8,20 +8,27 @@ use collab_entity::CollabType;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::borrow::{Borrow, BorrowMut};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::{Deref, DerefMut};
+use std::time::Duration;
 use std::vec;
+use tokio::sync::broadcast;
 
 use crate::blocks::{
-  deserialize_text_delta, parse_event, Block, BlockAction, BlockActionPayload, BlockActionType,
-  BlockEvent, BlockOperation, ChildrenOperation, DocumentData, DocumentMeta, TextDelta,
-  TextOperation, EXTERNAL_TYPE_TEXT,
+  parse_event, parse_text_delta, Block, BlockAction, BlockActionPayload, BlockActionType,
+  BlockEvent, BlockOperation, ChildrenOperation, CoverType, DocumentCover, DocumentData,
+  DocumentMeta, PageMeta, TextDelta, TextOperation, EXTERNAL_TYPE_TEXT,
 };
 use crate::document_awareness::DocumentAwarenessState;
+use crate::document_observer::{
+  subscribe_block_change, subscribe_meta_change, DocumentChangeReceiver, DocumentChangeSender,
+};
 use crate::error::DocumentError;
+use crate::exporter::md_exporter::document_data_to_markdown;
 use crate::importer::define::BlockType;
 use crate::utils::{
-  get_delta_from_block_data, get_delta_from_external_text_id, push_deltas_to_str,
+  count_text, find_matches, get_delta_from_block_data, get_delta_from_external_text_id,
+  push_deltas_to_str, table_to_plain_text,
 };
 
 /// The page_id is a reference that points to the block’s id.
@@ -34,14 +41,19 @@ const BLOCKS: &str = "blocks";
 const META: &str = "meta";
 /// [Block]'s relation map. And it's also in [META].
 /// The key is the parent block's children_id, and the value is the children block's id.
-const CHILDREN_MAP: &str = "children_map";
+pub(crate) const CHILDREN_MAP: &str = "children_map";
 /// [Block]'s yText map. And it's also in [META].
 /// The key is the text block's external_id, and the value is the text block's yText.
-const TEXT_MAP: &str = "text_map";
+pub(crate) const TEXT_MAP: &str = "text_map";
+/// The page's [DocumentCover] json string. And it's also in [META].
+pub(crate) const COVER: &str = "cover";
+/// The page's icon. And it's also in [META].
+pub(crate) const ICON: &str = "icon";
 
 pub struct Document {
   collab: Collab,
   body: DocumentBody,
+  change_tx: DocumentChangeSender,
 }
 
 impl Document {
@@ -50,7 +62,7 @@ impl Document {
   pub fn open(mut collab: Collab) -> Result<Self, DocumentError> {
     CollabType::Document.validate_require_data(&collab)?;
     let body = DocumentBody::new(&mut collab, None)?;
-    Ok(Self { collab, body })
+    Ok(Self::new_with_body(collab, body))
   }
 
   /// Opening a document with given [DataSource]
@@ -67,7 +79,7 @@ impl Document {
 
   pub fn create_with_data(mut collab: Collab, data: DocumentData) -> Result<Self, DocumentError> {
     let body = DocumentBody::new(&mut collab, Some(data))?;
-    Ok(Self { collab, body })
+    Ok(Self::new_with_body(collab, body))
   }
 
   pub fn create(document_id: &str, data: DocumentData) -> Result<Self, DocumentError> {
@@ -75,6 +87,24 @@ impl Document {
     Self::create_with_data(collab, data)
   }
 
+  fn new_with_body(collab: Collab, body: DocumentBody) -> Self {
+    let (change_tx, _) = broadcast::channel(1000);
+    {
+      let txn = collab.transact();
+      if let Some(blocks) = body.root.get_with_txn::<_, MapRef>(&txn, BLOCKS) {
+        subscribe_block_change(&txn, &blocks, change_tx.clone());
+      }
+      if let Some(meta) = body.root.get_with_txn::<_, MapRef>(&txn, META) {
+        subscribe_meta_change(&meta, change_tx.clone());
+      }
+    }
+    Self {
+      collab,
+      body,
+      change_tx,
+    }
+  }
+
   #[inline]
   pub fn split(self) -> (Collab, DocumentBody) {
     (self.collab, self.body)
@@ -114,6 +144,12 @@ impl Document {
     });
   }
 
+  /// Subscribe to the [DocumentChange]s of the blocks, the children and the texts. The changes
+  /// of the remote updates are received the same way as the local edits.
+  pub fn subscribe_block_changes(&self) -> DocumentChangeReceiver {
+    self.change_tx.subscribe()
+  }
+
   /// Get document data.
   pub fn get_document_data(&self) -> Result<DocumentData, DocumentError> {
     let txn = self.collab.transact();
@@ -127,24 +163,33 @@ impl Document {
   }
 
   #[deprecated(note = "use apply_text_delta instead")]
-  pub fn create_text(&mut self, text_id: &str, delta: String) {
-    self.apply_text_delta(text_id, delta);
+  pub fn create_text(&mut self, text_id: &str, delta: String) -> Result<(), DocumentError> {
+    self.apply_text_delta(text_id, &delta)
   }
 
   /// Create a yText for incremental synchronization.
   /// Apply a delta to the yText.
   /// - @param text_id: The text block's external_id.
   /// - @param delta: The text block's delta. "\[{"insert": "Hello", "attributes": { "bold": true, "italic": true } }, {"insert": " World!"}]".
-  pub fn apply_text_delta(&mut self, text_id: &str, delta: String) {
-    let mut txn = self.collab.transact_mut();
-    let delta = deserialize_text_delta(&delta).ok().unwrap_or_default();
+  ///
+  /// The delta must be an array of `insert`, `retain` or `delete` operations. An empty delta is
+  /// treated as `[]`. If it's malformed, [DocumentError::InvalidTextDelta] is returned and
+  /// nothing is applied.
+  pub fn apply_text_delta(&mut self, text_id: &str, delta: &str) -> Result<(), DocumentError> {
+    let delta = if delta.trim().is_empty() {
+      vec![]
+    } else {
+      parse_text_delta(delta)?
+    };
     #[cfg(feature = "verbose_log")]
     tracing::trace!("apply_text_delta: text_id: {}, delta: {:?}", text_id, delta);
 
+    let mut txn = self.collab.transact_mut();
     self
       .body
       .text_operation
       .apply_delta(&mut txn, text_id, delta);
+    Ok(())
   }
 
   /// Apply actions to the document.
@@ -343,6 +388,26 @@ impl Document {
       .update_block_data(&mut txn, block_id, data, None, None)
   }
 
+  /// Merge the `patch` into the data of the block key by key, instead of replacing the whole
+  /// data like [Document::update_block]. The keys whose value is null are removed.
+  ///
+  /// The data is kept as one json string, so the clients that don't know about the merge can
+  /// still read it. A concurrent update of the same block from another collaborator may win
+  /// over the merged data.
+  pub fn update_block_data(
+    &mut self,
+    block_id: &str,
+    patch: HashMap<String, Value>,
+  ) -> Result<(), DocumentError> {
+    let mut txn = self.collab.transact_mut();
+    self.body.merge_block_data(&mut txn, block_id, patch)
+  }
+
+  /// Move the block to the new parent, after the `prev_id` block. The block keeps its id, data,
+  /// text and children. If the `parent_id` is None, the block is moved within its current
+  /// parent. If the `prev_id` is None or not found, the block is moved to the first position.
+  ///
+  /// Moving the block into itself or its descendants is rejected.
   pub fn move_block(
     &mut self,
     block_id: &str,
@@ -353,14 +418,40 @@ impl Document {
     self.body.move_block(&mut txn, block_id, parent_id, prev_id)
   }
 
+  /// Enables undo/redo for the changes made by the transactions with the `origin_scope`,
+  /// usually the origin of the local [Collab]. The changes of other origins, e.g. the updates
+  /// applied from the remote collaborators, are never undone. The changes made within
+  /// `capture_timeout` of each other are grouped into a single undo/redo action.
+  ///
+  /// It replaces the undo history that was recorded before.
+  pub fn enable_undo(&mut self, origin_scope: CollabOrigin, capture_timeout: Duration) {
+    let options = collab::preclude::undo::Options {
+      capture_timeout_millis: capture_timeout.as_millis() as u64,
+      ..Default::default()
+    };
+    self
+      .collab
+      .enable_undo_redo_with_options(origin_scope, options);
+  }
+
+  /// Redo the last undone change. Returns false if there is nothing to redo.
   pub fn redo(&mut self) -> bool {
     self.collab.redo().unwrap_or(false)
   }
 
+  /// Undo the last change. Returns false if there is nothing to undo.
   pub fn undo(&mut self) -> bool {
     self.collab.undo().unwrap_or(false)
   }
 
+  pub fn can_redo(&self) -> bool {
+    self.collab.can_redo()
+  }
+
+  pub fn can_undo(&self) -> bool {
+    self.collab.can_undo()
+  }
+
   /// Set the local state of the awareness.
   /// It will override the previous state.
   pub fn set_awareness_local_state(&self, state: DocumentAwarenessState) {
@@ -420,6 +511,63 @@ impl Document {
       .body
       .to_plain_text(txn, new_line_each_paragraph, empty_space_each_delta)
   }
+
+  /// Search the text of the blocks for the `query`. The matches are returned in the order of the
+  /// blocks in the document, and the `start` and `end` of each match are the offsets in
+  /// characters within the text of the block.
+  pub fn search(&self, query: &str, case_sensitive: bool) -> Vec<SearchMatch> {
+    let txn = self.collab.transact();
+    self.body.search(txn, query, case_sensitive)
+  }
+
+  /// Count the words and characters of the text blocks. See [DocumentCounters] for how the
+  /// words are counted.
+  pub fn word_count(&self) -> DocumentCounters {
+    let txn = self.collab.transact();
+    self.body.word_count(txn)
+  }
+
+  /// Count the words and characters of the block. Returns None if the block is not found.
+  pub fn block_word_count(&self, block_id: &str) -> Option<DocumentCounters> {
+    self.get_block(block_id)?;
+    let mut counters = DocumentCounters::default();
+    if let Some(text) = self.get_plain_text_from_block(block_id) {
+      count_text(&mut counters, &text);
+    }
+    Some(counters)
+  }
+
+  /// Set the cover of the page. It's stored in the meta of the document.
+  pub fn set_cover(&mut self, ty: CoverType, value: String) -> Result<(), DocumentError> {
+    let mut txn = self.collab.transact_mut();
+    self
+      .body
+      .set_cover(&mut txn, Some(DocumentCover { ty, value }))
+  }
+
+  pub fn remove_cover(&mut self) -> Result<(), DocumentError> {
+    let mut txn = self.collab.transact_mut();
+    self.body.set_cover(&mut txn, None)
+  }
+
+  /// Set the icon of the page, e.g. an emoji. The icon is removed if the `icon` is None.
+  pub fn set_icon(&mut self, icon: Option<String>) -> Result<(), DocumentError> {
+    let mut txn = self.collab.transact_mut();
+    self.body.set_icon(&mut txn, icon)
+  }
+
+  /// Get the cover and icon of the page.
+  pub fn get_document_meta(&self) -> PageMeta {
+    let txn = self.collab.transact();
+    self.body.get_page_meta(&txn)
+  }
+
+  /// Get the markdown of the document. Importing the markdown with
+  /// [crate::importer::md_importer::MDImporter] produces the same blocks.
+  pub fn to_markdown(&self) -> Result<String, DocumentError> {
+    let document_data = self.get_document_data()?;
+    document_data_to_markdown(&document_data)
+  }
 }
 
 impl Deref for Document {
@@ -460,6 +608,28 @@ impl TryFrom<Collab> for Document {
   }
 }
 
+/// A match of [Document::search]. The `start` is inclusive and the `end` is exclusive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchMatch {
+  pub block_id: String,
+  pub start: usize,
+  pub end: usize,
+}
+
+/// The counters of [Document::word_count].
+///
+/// A word is a run of characters between whitespaces that contains at least one letter or digit.
+/// Chinese and Japanese text isn't separated by whitespaces, so each Han, Hiragana or Katakana
+/// character is counted as one word. The characters are the unicode scalar values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentCounters {
+  pub words: usize,
+  pub characters: usize,
+  pub characters_no_spaces: usize,
+  /// The number of blocks that have text.
+  pub blocks: usize,
+}
+
 pub struct DocumentBody {
   pub root: MapRef,
   pub children_operation: ChildrenOperation,
@@ -539,6 +709,10 @@ impl DocumentBody {
         text_operation.apply_delta(txn, &id, delta)
       }
     }
+
+    let meta = root.get_or_init_map(txn, META);
+    set_cover_with_txn(txn, &meta, data.meta.page_meta.cover)?;
+    set_icon_with_txn(txn, &meta, data.meta.page_meta.icon);
     Ok(())
   }
 
@@ -623,6 +797,21 @@ impl DocumentBody {
     // do a depth-first scan of the document blocks
     while let Some(block_id) = stack.pop() {
       if let Some(block) = blocks.get(block_id) {
+        // the table is written as tab-separated rows instead of the text of each cell
+        if BlockType::from_block_ty(&block.ty) == BlockType::Table {
+          buf.push_str(&table_to_plain_text(
+            block,
+            &blocks,
+            &children_map,
+            &mut text_map,
+            empty_space_each_delta,
+          ));
+          if new_line_each_paragraph && !stack.is_empty() {
+            buf.push('\n');
+          }
+          continue;
+        }
+
         if let Some(deltas) = get_delta_from_block_data(block) {
           push_deltas_to_str(&mut buf, deltas, empty_space_each_delta);
         } else if let Some(deltas) = get_delta_from_external_text_id(block, &mut text_map) {
@@ -645,6 +834,77 @@ impl DocumentBody {
     Ok(buf)
   }
 
+  pub fn search<T: ReadTxn>(&self, txn: T, query: &str, case_sensitive: bool) -> Vec<SearchMatch> {
+    let page_id: String = match self.root.get_with_txn(&txn, PAGE_ID) {
+      Some(page_id) => page_id,
+      None => return vec![],
+    };
+    let query = query.chars().collect::<Vec<_>>();
+
+    let mut text_map = self.text_operation.all_text_delta(&txn);
+    let blocks = self.block_operation.get_all_blocks(&txn);
+    let children_map = self.children_operation.get_all_children(&txn);
+    let mut matches = vec![];
+    let mut stack = vec![&page_id];
+    while let Some(block_id) = stack.pop() {
+      if let Some(block) = blocks.get(block_id) {
+        let deltas = get_delta_from_block_data(block)
+          .or_else(|| get_delta_from_external_text_id(block, &mut text_map));
+        if let Some(deltas) = deltas {
+          // the inserts are joined, so the matches can span the attribute boundaries
+          let text = deltas
+            .iter()
+            .filter_map(|delta| match delta {
+              TextDelta::Inserted(text, _) => Some(text.chars()),
+              _ => None,
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+          matches.extend(find_matches(&text, &query, case_sensitive).into_iter().map(
+            |(start, end)| SearchMatch {
+              block_id: block.id.clone(),
+              start,
+              end,
+            },
+          ));
+        }
+
+        if let Some(children) = children_map.get(&block.children) {
+          stack.extend(children.iter().rev());
+        }
+      }
+    }
+    matches
+  }
+
+  pub fn word_count<T: ReadTxn>(&self, txn: T) -> DocumentCounters {
+    // the texts of the deleted blocks are kept in the text map, so only the texts that are
+    // referenced by a block are counted
+    let text_ids = self
+      .block_operation
+      .get_all_blocks(&txn)
+      .into_values()
+      .filter(|block| block.external_type.as_deref() == Some(EXTERNAL_TYPE_TEXT))
+      .filter_map(|block| block.external_id)
+      .collect::<HashSet<_>>();
+
+    let mut counters = DocumentCounters::default();
+    for (text_id, deltas) in self.text_operation.all_text_delta(&txn) {
+      if !text_ids.contains(&text_id) {
+        continue;
+      }
+      let text = deltas
+        .into_iter()
+        .filter_map(|delta| match delta {
+          TextDelta::Inserted(text, _) => Some(text),
+          _ => None,
+        })
+        .collect::<String>();
+      count_text(&mut counters, &text);
+    }
+    counters
+  }
+
   fn insert_block(
     &self,
     txn: &mut TransactionMut,
@@ -769,6 +1029,18 @@ impl DocumentBody {
     )
   }
 
+  /// Merge the `patch` into the data of the block. The keys whose value is null are removed.
+  pub fn merge_block_data(
+    &self,
+    txn: &mut TransactionMut,
+    block_id: &str,
+    patch: HashMap<String, Value>,
+  ) -> Result<(), DocumentError> {
+    self
+      .block_operation
+      .merge_block_data_with_txn(txn, block_id, patch)
+  }
+
   pub fn get_document_data<T: ReadTxn>(&self, txn: &T) -> Result<DocumentData, DocumentError> {
     let page_id = self
       .root
@@ -785,11 +1057,48 @@ impl DocumentBody {
       meta: DocumentMeta {
         children_map,
         text_map: Some(text_map),
+        page_meta: self.get_page_meta(txn),
       },
     };
     Ok(document_data)
   }
 
+  /// Get the cover and icon of the page. They are None if they were never set.
+  pub fn get_page_meta<T: ReadTxn>(&self, txn: &T) -> PageMeta {
+    self
+      .root
+      .get_with_txn::<T, MapRef>(txn, META)
+      .map(|meta| page_meta_from_map(txn, &meta))
+      .unwrap_or_default()
+  }
+
+  /// Set the cover of the page. The cover is removed if the `cover` is None.
+  pub fn set_cover(
+    &self,
+    txn: &mut TransactionMut,
+    cover: Option<DocumentCover>,
+  ) -> Result<(), DocumentError> {
+    let meta: MapRef = self
+      .root
+      .get_with_txn(txn, META)
+      .ok_or(DocumentError::NoRequiredData)?;
+    set_cover_with_txn(txn, &meta, cover)
+  }
+
+  /// Set the icon of the page. The icon is removed if the `icon` is None.
+  pub fn set_icon(
+    &self,
+    txn: &mut TransactionMut,
+    icon: Option<String>,
+  ) -> Result<(), DocumentError> {
+    let meta: MapRef = self
+      .root
+      .get_with_txn(txn, META)
+      .ok_or(DocumentError::NoRequiredData)?;
+    set_icon_with_txn(txn, &meta, icon);
+    Ok(())
+  }
+
   /// move the block to the new parent.
   pub fn move_block(
     &self,
@@ -798,20 +1107,25 @@ impl DocumentBody {
     parent_id: Option<String>,
     prev_id: Option<String>,
   ) -> Result<(), DocumentError> {
-    // If the parent is not found, return an error.
-    let new_parent = match parent_id {
-      Some(parent_id) => match self.block_operation.get_block_with_txn(txn, &parent_id) {
-        Some(parent) => parent,
-        None => return Err(DocumentError::ParentIsNotFound),
-      },
-      None => return Err(DocumentError::ParentIsNotFound),
-    };
-
     let block = match self.block_operation.get_block_with_txn(txn, block_id) {
       Some(block) => block,
       None => return Err(DocumentError::BlockIsNotFound),
     };
 
+    // If the parent is not given, the block is moved within its current parent.
+    // If the parent is not found, return an error.
+    let parent_id = parent_id.unwrap_or_else(|| block.parent.clone());
+    let new_parent = match self.block_operation.get_block_with_txn(txn, &parent_id) {
+      Some(parent) => parent,
+      None => return Err(DocumentError::ParentIsNotFound),
+    };
+
+    // The block can't be moved into itself or its descendants, which would detach the block
+    // from the document.
+    if self.is_descendant_of(txn, &new_parent.id, block_id) {
+      return Err(DocumentError::MoveIntoDescendant);
+    }
+
     // If the old parent is not found, return an error.
     let old_parent = match self.block_operation.get_block_with_txn(txn, &block.parent) {
       Some(parent) => parent,
@@ -844,15 +1158,29 @@ impl DocumentBody {
       .children_operation
       .insert_child_with_txn(txn, &new_parent_children_id, block_id, index);
 
-    // Update the parent of the block.
-    self.block_operation.set_block_with_txn(
-      txn,
-      block_id,
-      Some(block.data),
-      Some(&new_parent.id),
-      None,
-      None,
-    )
+    // Update the parent of the block. The data and the external id are kept.
+    self
+      .block_operation
+      .set_block_with_txn(txn, block_id, None, Some(&new_parent.id), None, None)
+  }
+
+  /// Returns true if the block is the ancestor or one of the descendants of the ancestor.
+  fn is_descendant_of<T: ReadTxn>(&self, txn: &T, block_id: &str, ancestor_id: &str) -> bool {
+    let mut visited = HashSet::new();
+    let mut current_id = block_id.to_string();
+    loop {
+      if current_id == ancestor_id {
+        return true;
+      }
+      // Stop at the root or at the broken parent relations
+      if !visited.insert(current_id.clone()) {
+        return false;
+      }
+      match self.block_operation.get_block_with_txn(txn, &current_id) {
+        Some(block) if !block.parent.is_empty() => current_id = block.parent,
+        _ => return false,
+      }
+    }
   }
 
   fn handle_insert_action(
@@ -917,7 +1245,7 @@ impl DocumentBody {
   ) -> Result<(), DocumentError> {
     if let Some(text_id) = payload.text_id {
       if let Some(delta) = payload.delta {
-        let delta = deserialize_text_delta(&delta).ok().unwrap_or_default();
+        let delta = parse_text_delta(&delta)?;
         self.text_operation.apply_delta(txn, &text_id, delta);
         Ok(())
       } else {
@@ -929,6 +1257,44 @@ impl DocumentBody {
   }
 }
 
+/// Read the [PageMeta] from the meta map. The documents created before the cover and icon were
+/// introduced don't have the keys, so they are read as None.
+pub(crate) fn page_meta_from_map<T: ReadTxn>(txn: &T, meta: &MapRef) -> PageMeta {
+  let cover = meta
+    .get_with_txn::<T, String>(txn, COVER)
+    .and_then(|json| serde_json::from_str::<DocumentCover>(&json).ok());
+  let icon = meta.get_with_txn(txn, ICON);
+  PageMeta { cover, icon }
+}
+
+fn set_cover_with_txn(
+  txn: &mut TransactionMut,
+  meta: &MapRef,
+  cover: Option<DocumentCover>,
+) -> Result<(), DocumentError> {
+  match cover {
+    Some(cover) => {
+      let json = serde_json::to_string(&cover).map_err(|_| DocumentError::ConvertDataError)?;
+      meta.try_update(txn, COVER, json);
+    },
+    None => {
+      meta.remove(txn, COVER);
+    },
+  }
+  Ok(())
+}
+
+fn set_icon_with_txn(txn: &mut TransactionMut, meta: &MapRef, icon: Option<String>) {
+  match icon {
+    Some(icon) => {
+      meta.try_update(txn, ICON, icon);
+    },
+    None => {
+      meta.remove(txn, ICON);
+    },
+  }
+}
+
 /// Represents a the index content of a document.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DocumentIndexContent {
@@ -79,6 +79,7 @@ pub fn default_document_data(document_id: &str) -> DocumentData {
     meta: DocumentMeta {
       children_map,
       text_map: Some(text_map),
+      page_meta: Default::default(),
     },
   }
 }
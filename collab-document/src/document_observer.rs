@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use collab::preclude::map::MapEvent;
+use collab::preclude::{
+  Array, ArrayRef, DeepObservable, EntryChange, Event, Map, MapExt, MapRef, Out, PathSegment,
+  ReadTxn, TransactionMut,
+};
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+use crate::blocks::{block_from_map, json_str_to_hashmap, Block, PageMeta, DATA, PARENT};
+use crate::document::{page_meta_from_map, CHILDREN_MAP, COVER, ICON, TEXT_MAP};
+
+pub type DocumentChangeSender = broadcast::Sender<DocumentChange>;
+pub type DocumentChangeReceiver = broadcast::Receiver<DocumentChange>;
+
+/// The changes of the document. They are emitted for the local edits and the remote updates
+/// alike.
+#[derive(Debug, Clone)]
+pub enum DocumentChange {
+  DidInsertBlock {
+    block: Block,
+  },
+  DidDeleteBlock {
+    block_id: String,
+    parent_id: String,
+  },
+  DidUpdateBlockData {
+    block_id: String,
+    data: HashMap<String, Value>,
+  },
+  /// The children of a block are inserted, removed or reordered. The `children` are the ids of
+  /// the children blocks after the change.
+  DidUpdateChildren {
+    children_id: String,
+    children: Vec<String>,
+  },
+  DidUpdateText {
+    text_id: String,
+  },
+  /// The cover or the icon of the page is updated.
+  DidUpdateMeta {
+    meta: PageMeta,
+  },
+}
+
+/// Observes the blocks map and sends the [DocumentChange]s of the blocks.
+///
+/// The content of a deleted block can't be read anymore, so the parent of every block is
+/// tracked here in order to fill the `parent_id` of [DocumentChange::DidDeleteBlock]. Only the
+/// parent of the existing blocks is read when subscribing, their data isn't parsed.
+pub(crate) fn subscribe_block_change<T: ReadTxn>(
+  txn: &T,
+  blocks: &MapRef,
+  change_tx: DocumentChangeSender,
+) {
+  let parents = blocks
+    .iter(txn)
+    .filter_map(|(block_id, value)| match value {
+      Out::YMap(map) => {
+        let parent_id: String = map.get_with_txn(txn, PARENT).unwrap_or_default();
+        Some((block_id.to_string(), parent_id))
+      },
+      _ => None,
+    })
+    .collect::<HashMap<String, String>>();
+  let parents = Mutex::new(parents);
+
+  blocks.observe_deep_with("change", move |txn, events| {
+    let mut parents = parents.lock().unwrap_or_else(|err| err.into_inner());
+    for event in events.iter() {
+      if let Event::Map(map_event) = event {
+        handle_block_event(&change_tx, &mut parents, txn, event, map_event);
+      }
+    }
+  });
+}
+
+/// Observes the meta map and sends the [DocumentChange]s of the page meta, the children and the
+/// texts.
+pub(crate) fn subscribe_meta_change(meta: &MapRef, change_tx: DocumentChangeSender) {
+  meta.observe_deep_with("change", move |txn, events| {
+    for event in events.iter() {
+      let path = event.path();
+      let (map_key, id) = match (path.front(), path.get(1)) {
+        (Some(PathSegment::Key(map_key)), id) => (map_key.to_string(), id),
+        (None, _) => {
+          // The cover or icon is updated
+          if let Event::Map(map_event) = event {
+            let keys = map_event.keys(txn);
+            if keys.contains_key(COVER) || keys.contains_key(ICON) {
+              let _ = change_tx.send(DocumentChange::DidUpdateMeta {
+                meta: page_meta_from_map(txn, map_event.target()),
+              });
+            }
+          }
+          continue;
+        },
+        _ => continue,
+      };
+
+      match (map_key.as_str(), id, event) {
+        // The children of the block are updated
+        (CHILDREN_MAP, Some(PathSegment::Key(children_id)), Event::Array(array_event)) => {
+          let _ = change_tx.send(DocumentChange::DidUpdateChildren {
+            children_id: children_id.to_string(),
+            children: children_from_array(txn, array_event.target()),
+          });
+        },
+        // A children array is created with the children of a new block
+        (CHILDREN_MAP, None, Event::Map(map_event)) => {
+          for (children_id, change) in map_event.keys(txn).iter() {
+            if let EntryChange::Inserted(Out::YArray(array)) = change {
+              let children = children_from_array(txn, array);
+              if !children.is_empty() {
+                let _ = change_tx.send(DocumentChange::DidUpdateChildren {
+                  children_id: children_id.to_string(),
+                  children,
+                });
+              }
+            }
+          }
+        },
+        (TEXT_MAP, Some(PathSegment::Key(text_id)), Event::Text(_)) => {
+          let _ = change_tx.send(DocumentChange::DidUpdateText {
+            text_id: text_id.to_string(),
+          });
+        },
+        // A text is created with its initial delta
+        (TEXT_MAP, None, Event::Map(map_event)) => {
+          for (text_id, change) in map_event.keys(txn).iter() {
+            if let EntryChange::Inserted(_) = change {
+              let _ = change_tx.send(DocumentChange::DidUpdateText {
+                text_id: text_id.to_string(),
+              });
+            }
+          }
+        },
+        _ => {},
+      }
+    }
+  });
+}
+
+fn handle_block_event(
+  change_tx: &DocumentChangeSender,
+  parents: &mut HashMap<String, String>,
+  txn: &TransactionMut,
+  event: &Event,
+  map_event: &MapEvent,
+) {
+  let path = event.path();
+  match path.front() {
+    // The blocks are inserted into or removed from the blocks map
+    None => {
+      for (block_id, change) in map_event.keys(txn).iter() {
+        match change {
+          EntryChange::Inserted(Out::YMap(map)) | EntryChange::Updated(_, Out::YMap(map)) => {
+            let block = block_from_map(txn, map.clone());
+            parents.insert(block.id.clone(), block.parent.clone());
+            let _ = change_tx.send(DocumentChange::DidInsertBlock { block });
+          },
+          EntryChange::Removed(_) => {
+            let parent_id = parents.remove(block_id.as_ref()).unwrap_or_default();
+            let _ = change_tx.send(DocumentChange::DidDeleteBlock {
+              block_id: block_id.to_string(),
+              parent_id,
+            });
+          },
+          _ => {},
+        }
+      }
+    },
+    // The fields of the block are updated
+    Some(PathSegment::Key(block_id)) => {
+      let block = map_event.target();
+      for key in map_event.keys(txn).keys() {
+        match key.as_ref() {
+          DATA => {
+            let json_str: String = block.get_with_txn(txn, DATA).unwrap_or_default();
+            let _ = change_tx.send(DocumentChange::DidUpdateBlockData {
+              block_id: block_id.to_string(),
+              data: json_str_to_hashmap(&json_str).unwrap_or_default(),
+            });
+          },
+          PARENT => {
+            let parent_id: String = block.get_with_txn(txn, PARENT).unwrap_or_default();
+            parents.insert(block_id.to_string(), parent_id);
+          },
+          _ => {},
+        }
+      }
+    },
+    Some(PathSegment::Index(_)) => {},
+  }
+}
+
+fn children_from_array(txn: &TransactionMut, array: &ArrayRef) -> Vec<String> {
+  array.iter(txn).map(|value| value.to_string(txn)).collect()
+}
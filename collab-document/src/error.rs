@@ -26,6 +26,9 @@ pub enum DocumentError {
   #[error("The parent is not found")]
   ParentIsNotFound,
 
+  #[error("The block can't be moved into itself or its descendants")]
+  MoveIntoDescendant,
+
   #[error("Could not create the root block due to an unspecified error")]
   CreateRootBlockError,
 
@@ -35,6 +38,9 @@ pub enum DocumentError {
   #[error("text_id or delta is empty")]
   TextActionParamsError,
 
+  #[error("Invalid text delta: {0}")]
+  InvalidTextDelta(String),
+
   #[error("Lack of document required data")]
   NoRequiredData,
 
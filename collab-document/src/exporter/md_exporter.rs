@@ -0,0 +1,548 @@
+use crate::blocks::{Block, DocumentData};
+use crate::error::DocumentError;
+use crate::importer::define::*;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// Converts the document data to markdown. It's the reverse of
+/// [crate::importer::md_importer::MDImporter], so importing the markdown again produces the same
+/// blocks and deltas.
+///
+/// The blocks that don't have a markdown representation are exported as paragraphs of their text.
+pub fn document_data_to_markdown(document_data: &DocumentData) -> Result<String, DocumentError> {
+  let page = document_data
+    .blocks
+    .get(&document_data.page_id)
+    .ok_or(DocumentError::PageIdIsEmpty)?;
+  let exporter = MDExporter::new(document_data);
+  Ok(exporter.write_block(page, 1))
+}
+
+struct MDExporter<'a> {
+  blocks: &'a HashMap<String, Block>,
+  children_map: &'a HashMap<String, Vec<String>>,
+  text_map: HashMap<String, Vec<Value>>,
+}
+
+impl<'a> MDExporter<'a> {
+  fn new(document_data: &'a DocumentData) -> Self {
+    let text_map = document_data
+      .meta
+      .text_map
+      .iter()
+      .flatten()
+      .map(|(text_id, delta)| {
+        let ops = serde_json::from_str::<Vec<Value>>(delta).unwrap_or_default();
+        (text_id.clone(), ops)
+      })
+      .collect();
+    Self {
+      blocks: &document_data.blocks,
+      children_map: &document_data.meta.children_map,
+      text_map,
+    }
+  }
+
+  fn children(&self, block: &Block) -> Vec<&'a Block> {
+    self
+      .children_map
+      .get(&block.children)
+      .map(|ids| ids.iter().flat_map(|id| self.blocks.get(id)).collect())
+      .unwrap_or_default()
+  }
+
+  /// Returns the delta of the block. The delta is stored in the text map, or in the `delta` of
+  /// the block data by the old versions.
+  fn delta(&self, block: &Block) -> Vec<Value> {
+    if let Some(ops) = block
+      .external_id
+      .as_ref()
+      .and_then(|text_id| self.text_map.get(text_id))
+    {
+      return ops.clone();
+    }
+    match block.data.get("delta") {
+      Some(Value::Array(ops)) => ops.clone(),
+      _ => vec![],
+    }
+  }
+
+  /// Writes the blocks that have the same parent. The list items are separated by a line break,
+  /// the other blocks by an empty line.
+  fn write_blocks(&self, blocks: &[&Block]) -> String {
+    let mut buf = String::new();
+    let mut prev_block_type: Option<BlockType> = None;
+    let mut number = 1;
+    for block in blocks {
+      let block_type = BlockType::from_block_ty(&block.ty);
+      if block_type == BlockType::NumberedList {
+        number = match prev_block_type {
+          Some(BlockType::NumberedList) => number + 1,
+          _ => block
+            .data
+            .get(START_NUMBER_FIELD)
+            .and_then(Value::as_u64)
+            .unwrap_or(1),
+        };
+      }
+
+      let markdown = self.write_block(block, number);
+      if !markdown.is_empty() {
+        if let Some(prev_block_type) = &prev_block_type {
+          if is_list(prev_block_type) && is_list(&block_type) {
+            buf.push('\n');
+          } else {
+            buf.push_str("\n\n");
+          }
+        }
+        buf.push_str(&markdown);
+      }
+      prev_block_type = Some(block_type);
+    }
+    buf
+  }
+
+  fn write_block(&self, block: &Block, number: u64) -> String {
+    let children = self.children(block);
+    match BlockType::from_block_ty(&block.ty) {
+      BlockType::Page => self.write_blocks(&children),
+      BlockType::Heading => {
+        let level = block
+          .data
+          .get(LEVEL_FIELD)
+          .and_then(Value::as_u64)
+          .unwrap_or(1)
+          .clamp(1, 6) as usize;
+        let text = self.write_text(block).replace('\n', " ");
+        let heading = format!("{} {}", "#".repeat(level), text);
+        self.with_children(heading, &children)
+      },
+      BlockType::Quote => {
+        let content = self.with_children(self.write_text(block), &children);
+        prefix_lines(&content, "> ", "> ")
+      },
+      BlockType::Callout => {
+        let icon = block
+          .data
+          .get(ICON_FIELD)
+          .and_then(Value::as_str)
+          .unwrap_or_default();
+        let text = self.write_text(block);
+        let content = match (icon.is_empty(), text.is_empty()) {
+          (true, _) => text,
+          (false, true) => icon.to_string(),
+          (false, false) => format!("{} {}", icon, text),
+        };
+        let content = self.with_children(content, &children);
+        prefix_lines(&content, "> ", "> ")
+      },
+      BlockType::BulletedList => self.write_list_item(block, "- ", &children),
+      BlockType::TodoList => {
+        let checked = block
+          .data
+          .get(CHECKED_FIELD)
+          .and_then(Value::as_bool)
+          .unwrap_or(false);
+        let marker = if checked { "- [x] " } else { "- [ ] " };
+        self.write_list_item(block, marker, &children)
+      },
+      BlockType::NumberedList => {
+        let marker = format!("{}. ", number);
+        self.write_list_item(block, &marker, &children)
+      },
+      BlockType::ToggleList => self.write_toggle(block, &children),
+      BlockType::Code => {
+        let language = block
+          .data
+          .get(LANGUAGE_FIELD)
+          .and_then(Value::as_str)
+          .unwrap_or_default();
+        let code = delta_to_plain_text(&self.delta(block));
+        let fence = "`".repeat((longest_run(&code, '`') + 1).max(3));
+        let code_block = format!("{}{}\n{}\n{}", fence, language, code, fence);
+        self.with_children(code_block, &children)
+      },
+      BlockType::Divider => "---".to_string(),
+      BlockType::Image => match block.data.get(URL_FIELD).and_then(Value::as_str) {
+        Some(url) if !url.is_empty() => format!("![]({})", link_destination(url)),
+        _ => String::new(),
+      },
+      BlockType::LinkPreview => match block.data.get(URL_FIELD).and_then(Value::as_str) {
+        Some(url) if !url.is_empty() => {
+          format!("[{}]({})", escape_text(url), link_destination(url))
+        },
+        _ => String::new(),
+      },
+      BlockType::MathEquation => match block.data.get(FORMULA_FIELD).and_then(Value::as_str) {
+        Some(formula) if !formula.is_empty() => format!("$$\n{}\n$$", formula),
+        _ => String::new(),
+      },
+      BlockType::Table => self.write_table(block, &children),
+      BlockType::TableCell => self.write_blocks(&children),
+      BlockType::Paragraph | BlockType::Text | BlockType::Custom(_) => {
+        self.with_children(self.write_text(block), &children)
+      },
+    }
+  }
+
+  /// Appends the children after the content of the block. They are written at the same level as
+  /// the block, because markdown can't nest them under a paragraph or a heading.
+  fn with_children(&self, content: String, children: &[&Block]) -> String {
+    let children = self.write_blocks(children);
+    match (content.is_empty(), children.is_empty()) {
+      (_, true) => content,
+      (true, false) => children,
+      (false, false) => format!("{}\n\n{}", content, children),
+    }
+  }
+
+  /// Writes the list item and its children, which are indented to the content of the item
+  fn write_list_item(&self, block: &Block, marker: &str, children: &[&Block]) -> String {
+    let mut content = self.write_text(block);
+    let children_markdown = self.write_blocks(children);
+    if !children_markdown.is_empty() {
+      let is_list_child = children
+        .first()
+        .map(|child| is_list(&BlockType::from_block_ty(&child.ty)))
+        .unwrap_or(false);
+      // A paragraph right after the item would be a part of the text of the item
+      content.push_str(if is_list_child { "\n" } else { "\n\n" });
+      content.push_str(&children_markdown);
+    }
+    // The content of the todo item starts after the bullet, not after the checkbox
+    let indent = if marker.starts_with("- ") {
+      2
+    } else {
+      marker.len()
+    };
+    prefix_lines(&content, marker, &" ".repeat(indent))
+  }
+
+  /// Writes the toggle heading as a heading with the [COLLAPSED_MARKER] that is followed by the
+  /// indented children. The other toggles are written as `<details>` html, or as list items if
+  /// they don't have the collapsed state.
+  fn write_toggle(&self, block: &Block, children: &[&Block]) -> String {
+    let text = self.write_text(block).replace('\n', " ");
+    let level = block.data.get(LEVEL_FIELD).and_then(Value::as_u64);
+    let collapsed = block.data.get(COLLAPSED_FIELD).and_then(Value::as_bool);
+    match (level, collapsed) {
+      (Some(level), _) => {
+        let heading = format!(
+          "{} {} {}",
+          "#".repeat(level.clamp(1, 6) as usize),
+          text,
+          COLLAPSED_MARKER
+        );
+        let children = self.write_blocks(children);
+        if children.is_empty() {
+          heading
+        } else {
+          format!("{}\n\n{}", heading, prefix_lines(&children, "    ", "    "))
+        }
+      },
+      (None, Some(collapsed)) => {
+        let tag = if collapsed {
+          "<details>"
+        } else {
+          "<details open>"
+        };
+        let children = self.write_blocks(children);
+        if children.is_empty() {
+          format!("{}\n<summary>{}</summary>\n</details>", tag, text)
+        } else {
+          format!(
+            "{}\n<summary>{}</summary>\n\n{}\n\n</details>",
+            tag, text, children
+          )
+        }
+      },
+      (None, None) => self.write_list_item(block, "- ", children),
+    }
+  }
+
+  fn write_table(&self, table: &Block, cells: &[&Block]) -> String {
+    let mut rows: Vec<Vec<String>> = vec![];
+    let mut align: Vec<&str> = vec![];
+    for cell in cells {
+      let position = |key: &str| cell.data.get(key).and_then(Value::as_u64).unwrap_or(0) as usize;
+      let (row, col) = (position(ROW_POSITION_FIELD), position(COL_POSITION_FIELD));
+      if rows.len() <= row {
+        rows.resize(row + 1, vec![]);
+      }
+      if rows[row].len() <= col {
+        rows[row].resize(col + 1, String::new());
+      }
+      rows[row][col] = self
+        .children(cell)
+        .iter()
+        .map(|child| self.write_text(child))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('\n', " ")
+        .replace('|', "\\|");
+      if row == 0 {
+        if align.len() <= col {
+          align.resize(col + 1, ALIGN_LEFT);
+        }
+        align[col] = cell
+          .data
+          .get(ALIGN_FIELD)
+          .and_then(Value::as_str)
+          .unwrap_or(ALIGN_LEFT);
+      }
+    }
+
+    let data_len = |key: &str| table.data.get(key).and_then(Value::as_u64).unwrap_or(0) as usize;
+    let rows_len = rows.len().max(data_len(ROWS_LEN_FIELD));
+    let cols_len = rows
+      .iter()
+      .map(|row| row.len())
+      .max()
+      .unwrap_or(0)
+      .max(data_len(COLS_LEN_FIELD));
+    if rows_len == 0 || cols_len == 0 {
+      return String::new();
+    }
+
+    let mut lines = vec![];
+    for row_index in 0..rows_len {
+      let row = rows.get(row_index);
+      let cells = (0..cols_len)
+        .map(|col| {
+          row
+            .and_then(|row| row.get(col))
+            .map(|cell| cell.as_str())
+            .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+      lines.push(format!("| {} |", cells.join(" | ")));
+
+      if row_index == 0 {
+        let delimiters = (0..cols_len)
+          .map(|col| match align.get(col) {
+            Some(&ALIGN_CENTER) => ":---:",
+            Some(&ALIGN_RIGHT) => "---:",
+            // The cells without alignment are imported as left aligned
+            _ => "---",
+          })
+          .collect::<Vec<_>>();
+        lines.push(format!("| {} |", delimiters.join(" | ")));
+      }
+    }
+    lines.join("\n")
+  }
+
+  fn write_text(&self, block: &Block) -> String {
+    escape_line_start(&delta_to_markdown(&self.delta(block)))
+  }
+}
+
+fn is_list(block_type: &BlockType) -> bool {
+  matches!(
+    block_type,
+    BlockType::BulletedList | BlockType::NumberedList | BlockType::TodoList
+  )
+}
+
+/// The styles that are written around the text, ordered from the outermost to the innermost
+#[derive(Debug, Clone, PartialEq)]
+enum InlineMark {
+  Link(String),
+  Bold,
+  Italic,
+  Strikethrough,
+}
+
+impl InlineMark {
+  fn marks_from_attributes(attributes: Option<&Map<String, Value>>) -> Vec<InlineMark> {
+    let mut marks = vec![];
+    let attributes = match attributes {
+      None => return marks,
+      Some(attributes) => attributes,
+    };
+    let is_set = |key: &str| attributes.get(key).and_then(Value::as_bool) == Some(true);
+    if let Some(href) = attributes.get(HREF_ATTR).and_then(Value::as_str) {
+      marks.push(InlineMark::Link(href.to_string()));
+    }
+    if is_set(BOLD_ATTR) {
+      marks.push(InlineMark::Bold);
+    }
+    if is_set(ITALIC_ATTR) {
+      marks.push(InlineMark::Italic);
+    }
+    if is_set(STRIKETHROUGH_ATTR) {
+      marks.push(InlineMark::Strikethrough);
+    }
+    marks
+  }
+
+  fn open(&self) -> &str {
+    match self {
+      InlineMark::Link(_) => "[",
+      InlineMark::Bold => "**",
+      InlineMark::Italic => "*",
+      InlineMark::Strikethrough => "~~",
+    }
+  }
+
+  fn close(&self) -> String {
+    match self {
+      InlineMark::Link(url) => format!("]({})", link_destination(url)),
+      _ => self.open().to_string(),
+    }
+  }
+}
+
+/// Converts the delta to inline markdown. The marks that are shared by the adjacent inserts are
+/// opened once, and the whitespaces are moved out of the marks, because `** bold**` isn't bold
+/// in markdown.
+fn delta_to_markdown(ops: &[Value]) -> String {
+  let mut buf = String::new();
+  let mut open_marks: Vec<InlineMark> = vec![];
+  let mut pending_spaces = String::new();
+  for op in ops {
+    let text = match op.get("insert").and_then(Value::as_str) {
+      Some(text) if !text.is_empty() => text,
+      _ => continue,
+    };
+    let attributes = op.get("attributes").and_then(Value::as_object);
+    let formula = attributes
+      .and_then(|attributes| attributes.get(FORMULA_ATTR))
+      .and_then(Value::as_str);
+    let is_code = attributes
+      .and_then(|attributes| attributes.get(CODE_ATTR))
+      .and_then(Value::as_bool)
+      == Some(true);
+
+    let (leading, content, trailing) = match (formula, is_code) {
+      (Some(formula), _) => ("", format!("${}$", formula), ""),
+      (None, true) => ("", inline_code(text), ""),
+      (None, false) => {
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+          pending_spaces.push_str(text);
+          continue;
+        }
+        let start = text.len() - text.trim_start().len();
+        let end = start + trimmed.len();
+        (&text[..start], escape_text(trimmed), &text[end..])
+      },
+    };
+
+    let marks = InlineMark::marks_from_attributes(attributes);
+    let kept = open_marks
+      .iter()
+      .zip(marks.iter())
+      .take_while(|(open_mark, mark)| open_mark == mark)
+      .count();
+    while open_marks.len() > kept {
+      if let Some(mark) = open_marks.pop() {
+        buf.push_str(&mark.close());
+      }
+    }
+    buf.push_str(&pending_spaces);
+    pending_spaces.clear();
+    buf.push_str(leading);
+    for mark in &marks[kept..] {
+      buf.push_str(mark.open());
+      open_marks.push(mark.clone());
+    }
+    buf.push_str(&content);
+    pending_spaces.push_str(trailing);
+  }
+
+  while let Some(mark) = open_marks.pop() {
+    buf.push_str(&mark.close());
+  }
+  buf.push_str(&pending_spaces);
+  buf
+}
+
+fn delta_to_plain_text(ops: &[Value]) -> String {
+  ops
+    .iter()
+    .flat_map(|op| op.get("insert").and_then(Value::as_str))
+    .collect()
+}
+
+fn inline_code(text: &str) -> String {
+  let fence = "`".repeat(longest_run(text, '`') + 1);
+  if text.starts_with('`') || text.ends_with('`') {
+    format!("{} {} {}", fence, text, fence)
+  } else {
+    format!("{}{}{}", fence, text, fence)
+  }
+}
+
+/// Escapes the characters that would be parsed as inline markdown
+fn escape_text(text: &str) -> String {
+  let mut escaped = String::with_capacity(text.len());
+  for c in text.chars() {
+    if matches!(
+      c,
+      '\\' | '*' | '_' | '`' | '~' | '[' | ']' | '$' | '<' | '>' | '&'
+    ) {
+      escaped.push('\\');
+    }
+    escaped.push(c);
+  }
+  escaped
+}
+
+/// Escapes the start of the lines that would be parsed as a heading, a list item or a quote
+fn escape_line_start(text: &str) -> String {
+  text
+    .split('\n')
+    .map(|line| {
+      let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+      if line.starts_with(['#', '-', '+']) {
+        format!("\\{}", line)
+      } else if digits > 0 && line[digits..].starts_with(['.', ')']) {
+        format!("{}\\{}", &line[..digits], &line[digits..])
+      } else {
+        line.to_string()
+      }
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Prefixes the first line with `first_prefix` and the other lines with `prefix`. The prefixes
+/// of the empty lines are trimmed.
+fn prefix_lines(text: &str, first_prefix: &str, prefix: &str) -> String {
+  text
+    .split('\n')
+    .enumerate()
+    .map(|(index, line)| {
+      let prefix = if index == 0 { first_prefix } else { prefix };
+      if line.is_empty() {
+        prefix.trim_end().to_string()
+      } else {
+        format!("{}{}", prefix, line)
+      }
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+fn link_destination(url: &str) -> String {
+  if url.contains([' ', '(', ')']) {
+    format!("<{}>", url)
+  } else {
+    url.to_string()
+  }
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+  let mut longest = 0;
+  let mut current = 0;
+  for ch in text.chars() {
+    if ch == c {
+      current += 1;
+      longest = longest.max(current);
+    } else {
+      current = 0;
+    }
+  }
+  longest
+}
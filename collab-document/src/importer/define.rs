@@ -11,6 +11,8 @@ pub enum BlockType {
   TodoList,
   NumberedList,
   BulletedList,
+  ToggleList,
+  Callout,
   Image,
   LinkPreview,
   Code,
@@ -32,6 +34,8 @@ impl BlockType {
       BlockType::TodoList => "todo_list",
       BlockType::NumberedList => "numbered_list",
       BlockType::BulletedList => "bulleted_list",
+      BlockType::ToggleList => "toggle_list",
+      BlockType::Callout => "callout",
       BlockType::Image => "image",
       BlockType::LinkPreview => "link_preview",
       BlockType::Code => "code",
@@ -53,6 +57,8 @@ impl BlockType {
       "todo_list" => BlockType::TodoList,
       "numbered_list" => BlockType::NumberedList,
       "bulleted_list" => BlockType::BulletedList,
+      "toggle_list" => BlockType::ToggleList,
+      "callout" => BlockType::Callout,
       "image" => BlockType::Image,
       "link_preview" => BlockType::LinkPreview,
       "code" => BlockType::Code,
@@ -120,6 +126,7 @@ pub const HREF_ATTR: &str = "href";
 pub const CODE_ATTR: &str = "code";
 pub const FORMULA_ATTR: &str = "formula";
 pub const STRIKETHROUGH_ATTR: &str = "strikethrough";
+pub const UNDERLINE_ATTR: &str = "underline";
 pub const INLINE_MATH_SYMBOL: &str = "$";
 
 // Table Keys
@@ -134,4 +141,13 @@ pub const COL_POSITION_FIELD: &str = "colPosition";
 pub const CHECKED_FIELD: &str = "checked";
 pub const START_NUMBER_FIELD: &str = "number";
 
+// Toggle Keys
+pub const COLLAPSED_FIELD: &str = "collapsed";
+/// The html comment after the text of a heading or a bulleted list item that makes it a
+/// collapsible toggle
+pub const COLLAPSED_MARKER: &str = "<!-- collapsed -->";
+
+// Callout Keys
+pub const ICON_FIELD: &str = "icon";
+
 pub const ALIGN_FIELD: &str = "align";
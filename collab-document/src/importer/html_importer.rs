@@ -0,0 +1,621 @@
+use crate::blocks::{Block, DocumentData, DocumentMeta};
+use crate::document_data::generate_id;
+use crate::error::DocumentError;
+use crate::importer::define::*;
+use crate::importer::delta::Delta;
+use crate::importer::md_importer::create_image_block;
+use crate::importer::util::{insert_delta_to_text_map, BlockData};
+use scraper::{ElementRef, Html, Node};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::trace;
+
+/// The tags whose content is never displayed
+const SKIPPED_TAGS: [&str; 8] = [
+  "head", "link", "meta", "noscript", "script", "style", "template", "title",
+];
+
+/// The tags that only group other blocks. Their children are imported as the siblings of the
+/// blocks around them.
+const CONTAINER_TAGS: [&str; 15] = [
+  "article",
+  "aside",
+  "body",
+  "center",
+  "details",
+  "div",
+  "dl",
+  "figcaption",
+  "figure",
+  "footer",
+  "header",
+  "html",
+  "main",
+  "nav",
+  "section",
+];
+
+/// The tags that are part of the text of a block
+const INLINE_TAGS: [&str; 32] = [
+  "a", "abbr", "b", "bdi", "bdo", "br", "cite", "code", "data", "del", "dfn", "em", "font", "i",
+  "input", "ins", "kbd", "label", "mark", "q", "s", "samp", "small", "span", "strike", "strong",
+  "sub", "sup", "time", "u", "var", "wbr",
+];
+
+#[derive(Default)]
+pub struct HTMLImporter;
+
+impl HTMLImporter {
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Converts the html to document blocks. The html is imported into the same block types as
+  /// [crate::importer::md_importer::MDImporter] emits. The unknown tags are imported as
+  /// paragraphs of their text content.
+  pub fn import(&self, document_id: &str, html: String) -> Result<DocumentData, DocumentError> {
+    let html = Html::parse_document(&html);
+
+    let mut document_data = DocumentData {
+      page_id: document_id.to_string(),
+      blocks: HashMap::new(),
+      meta: DocumentMeta {
+        children_map: HashMap::new(),
+        text_map: Some(HashMap::new()),
+        page_meta: Default::default(),
+      },
+    };
+    document_data.blocks.insert(
+      document_id.to_string(),
+      Block {
+        id: document_id.to_string(),
+        ty: BlockType::Page.to_string(),
+        data: BlockData::new(),
+        parent: "".to_string(),
+        children: document_id.to_string(),
+        external_id: Some(document_id.to_string()),
+        external_type: Some(BlockType::Text.to_string()),
+      },
+    );
+
+    process_block_children(&mut document_data, document_id, html.root_element());
+    Ok(document_data)
+  }
+}
+
+enum HtmlNode<'a> {
+  Text(&'a str),
+  Element(ElementRef<'a>),
+}
+
+fn child_nodes(element: ElementRef) -> Vec<HtmlNode> {
+  element
+    .children()
+    .filter_map(|node| match node.value() {
+      Node::Text(text) => Some(HtmlNode::Text(&**text)),
+      Node::Element(_) => ElementRef::wrap(node).map(HtmlNode::Element),
+      _ => None,
+    })
+    .collect()
+}
+
+fn tag_name<'a>(element: &ElementRef<'a>) -> &'a str {
+  element.value().name()
+}
+
+fn is_inline_tag(name: &str) -> bool {
+  INLINE_TAGS.contains(&name)
+}
+
+fn process_block_children(document_data: &mut DocumentData, parent_id: &str, element: ElementRef) {
+  process_block_nodes(document_data, parent_id, &child_nodes(element));
+}
+
+/// Processes the nodes as the children of the parent block. The adjacent text and inline nodes
+/// are imported as one paragraph.
+fn process_block_nodes(document_data: &mut DocumentData, parent_id: &str, nodes: &[HtmlNode]) {
+  let mut text = InlineText::default();
+  for node in nodes {
+    match node {
+      HtmlNode::Text(s) => text.push(s, &[]),
+      HtmlNode::Element(element) if is_inline_tag(tag_name(element)) => {
+        collect_inline(&mut text, *element, &[]);
+      },
+      HtmlNode::Element(element) => {
+        insert_paragraph(document_data, parent_id, std::mem::take(&mut text));
+        process_block_element(document_data, parent_id, *element);
+      },
+    }
+  }
+  insert_paragraph(document_data, parent_id, text);
+}
+
+fn process_block_element(document_data: &mut DocumentData, parent_id: &str, element: ElementRef) {
+  let name = tag_name(&element);
+  match name {
+    "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+      let level = name[1..].parse::<u32>().unwrap_or(1);
+      let mut data = BlockData::new();
+      data.insert(LEVEL_FIELD.to_string(), level.into());
+      let block_id = create_block(document_data, parent_id, BlockType::Heading, data);
+      insert_text(document_data, &block_id, inline_text(element));
+    },
+    "p" => match image_urls(element) {
+      Some(urls) => {
+        for url in urls {
+          insert_image(document_data, parent_id, url);
+        }
+      },
+      None => {
+        let block_id = create_block(
+          document_data,
+          parent_id,
+          BlockType::Paragraph,
+          BlockData::new(),
+        );
+        insert_text(document_data, &block_id, inline_text(element));
+      },
+    },
+    "ul" | "ol" => process_list(document_data, parent_id, element),
+    "blockquote" => {
+      let block_id = create_block(document_data, parent_id, BlockType::Quote, BlockData::new());
+      process_block_with_children(document_data, &block_id, element);
+    },
+    "pre" => process_code(document_data, parent_id, element),
+    "img" => {
+      if let Some(url) = element.value().attr("src") {
+        insert_image(document_data, parent_id, url.to_string());
+      }
+    },
+    "hr" => {
+      create_block(
+        document_data,
+        parent_id,
+        BlockType::Divider,
+        BlockData::new(),
+      );
+    },
+    "table" => process_table(document_data, parent_id, element),
+    _ if CONTAINER_TAGS.contains(&name) => {
+      process_block_children(document_data, parent_id, element)
+    },
+    _ if SKIPPED_TAGS.contains(&name) => {},
+    _ => {
+      trace!("Unhandled tag: {}, fallback to paragraph", name);
+      let mut text = InlineText::default();
+      text.push(&element.text().collect::<String>(), &[]);
+      insert_paragraph(document_data, parent_id, text);
+    },
+  }
+}
+
+/// Processes the block that has children, like the list item and the quote. The leading inline
+/// content, or the first paragraph, is the text of the block. The rest of the nodes are the
+/// children of the block.
+fn process_block_with_children(
+  document_data: &mut DocumentData,
+  block_id: &str,
+  element: ElementRef,
+) {
+  let nodes = child_nodes(element);
+  let mut text = InlineText::default();
+  let mut rest_index = nodes.len();
+  for (index, node) in nodes.iter().enumerate() {
+    match node {
+      HtmlNode::Text(s) => text.push(s, &[]),
+      HtmlNode::Element(child) if is_inline_tag(tag_name(child)) => {
+        collect_inline(&mut text, *child, &[]);
+      },
+      HtmlNode::Element(child) => {
+        if text.is_empty() && tag_name(child) == "p" {
+          collect_inline(&mut text, *child, &[]);
+          rest_index = index + 1;
+        } else {
+          rest_index = index;
+        }
+        break;
+      },
+    }
+  }
+
+  insert_text(document_data, block_id, text);
+  process_block_nodes(document_data, block_id, &nodes[rest_index..]);
+}
+
+fn process_list(document_data: &mut DocumentData, parent_id: &str, list: ElementRef) {
+  // Same as the markdown importer, all the items of the ordered list have the start number
+  let start_number = (tag_name(&list) == "ol").then(|| {
+    list
+      .value()
+      .attr("start")
+      .and_then(|start| start.trim().parse::<u32>().ok())
+      .unwrap_or(1)
+  });
+
+  let mut last_item_id: Option<String> = None;
+  for node in child_nodes(list) {
+    let element = match node {
+      HtmlNode::Element(element) => element,
+      HtmlNode::Text(_) => continue,
+    };
+
+    match tag_name(&element) {
+      "li" => {
+        let checked = find_checkbox(element);
+        let mut data = BlockData::new();
+        let block_type = match (checked, start_number) {
+          (Some(checked), _) => {
+            data.insert(CHECKED_FIELD.to_string(), checked.into());
+            BlockType::TodoList
+          },
+          (None, Some(_)) => BlockType::NumberedList,
+          (None, None) => BlockType::BulletedList,
+        };
+        if let Some(start_number) = start_number {
+          data.insert(START_NUMBER_FIELD.to_string(), start_number.into());
+        }
+
+        let block_id = create_block(document_data, parent_id, block_type, data);
+        process_block_with_children(document_data, &block_id, element);
+        last_item_id = Some(block_id);
+      },
+      // The list that is put in the list directly belongs to the previous item
+      "ul" | "ol" => {
+        let parent_id = last_item_id.as_deref().unwrap_or(parent_id);
+        process_list(document_data, parent_id, element);
+      },
+      _ => process_block_element(document_data, parent_id, element),
+    }
+  }
+}
+
+/// Returns whether the checkbox at the start of the list item is checked. Returns None if the
+/// item doesn't have a checkbox.
+fn find_checkbox(item: ElementRef) -> Option<bool> {
+  for node in child_nodes(item) {
+    match node {
+      HtmlNode::Text(s) if s.trim().is_empty() => continue,
+      HtmlNode::Element(element) => match tag_name(&element) {
+        "input" => {
+          let input = element.value();
+          return (input.attr("type") == Some("checkbox")).then(|| input.attr("checked").is_some());
+        },
+        "p" | "label" => return find_checkbox(element),
+        _ => return None,
+      },
+      HtmlNode::Text(_) => return None,
+    }
+  }
+  None
+}
+
+fn process_code(document_data: &mut DocumentData, parent_id: &str, pre: ElementRef) {
+  let code = child_nodes(pre).into_iter().find_map(|node| match node {
+    HtmlNode::Element(element) if tag_name(&element) == "code" => Some(element),
+    _ => None,
+  });
+  let language = code
+    .iter()
+    .chain(std::iter::once(&pre))
+    .flat_map(|element| element.value().classes())
+    .find_map(|class| {
+      class
+        .strip_prefix("language-")
+        .or_else(|| class.strip_prefix("lang-"))
+    })
+    .unwrap_or_default();
+
+  let mut data = BlockData::new();
+  data.insert(LANGUAGE_FIELD.to_string(), language.into());
+  let block_id = create_block(document_data, parent_id, BlockType::Code, data);
+
+  let text = pre.text().collect::<String>();
+  let text = text.strip_suffix('\n').unwrap_or(&text);
+  if !text.is_empty() {
+    let mut delta = Delta::new();
+    delta.insert(text.to_string(), Vec::new());
+    insert_delta_to_text_map(document_data, &block_id, delta);
+  }
+}
+
+fn process_table(document_data: &mut DocumentData, parent_id: &str, table: ElementRef) {
+  let mut rows = vec![];
+  for node in child_nodes(table) {
+    if let HtmlNode::Element(element) = node {
+      match tag_name(&element) {
+        "tr" => rows.push(element),
+        "thead" | "tbody" | "tfoot" => rows.extend(child_nodes(element).into_iter().filter_map(
+          |node| match node {
+            HtmlNode::Element(row) if tag_name(&row) == "tr" => Some(row),
+            _ => None,
+          },
+        )),
+        _ => {},
+      }
+    }
+  }
+
+  let rows = rows
+    .into_iter()
+    .map(|row| {
+      child_nodes(row)
+        .into_iter()
+        .filter_map(|node| match node {
+          HtmlNode::Element(cell) if matches!(tag_name(&cell), "td" | "th") => Some(cell),
+          _ => None,
+        })
+        .collect::<Vec<_>>()
+    })
+    .filter(|cells| !cells.is_empty())
+    .collect::<Vec<_>>();
+  if rows.is_empty() {
+    return;
+  }
+
+  let cols_len = rows.iter().map(|cells| cells.len()).max().unwrap_or(0);
+  let mut data = BlockData::new();
+  data.insert(ROWS_LEN_FIELD.to_string(), rows.len().into());
+  data.insert(COLS_LEN_FIELD.to_string(), cols_len.into());
+  data.insert(
+    COL_DEFAULT_WIDTH_FIELD.to_string(),
+    DEFAULT_COL_WIDTH.into(),
+  );
+  data.insert(
+    ROW_DEFAULT_HEIGHT_FIELD.to_string(),
+    DEFAULT_ROW_HEIGHT.into(),
+  );
+  let table_id = create_block(document_data, parent_id, BlockType::Table, data);
+
+  for (row_index, cells) in rows.into_iter().enumerate() {
+    for (col_index, cell) in cells.into_iter().enumerate() {
+      let mut cell_data = BlockData::new();
+      cell_data.insert(ROW_POSITION_FIELD.to_string(), row_index.into());
+      cell_data.insert(COL_POSITION_FIELD.to_string(), col_index.into());
+      cell_data.insert(
+        ALIGN_FIELD.to_string(),
+        Value::String(cell_align(cell).to_string()),
+      );
+      let cell_id = create_block(document_data, &table_id, BlockType::TableCell, cell_data);
+
+      let paragraph_id = create_block(
+        document_data,
+        &cell_id,
+        BlockType::Paragraph,
+        BlockData::new(),
+      );
+      insert_text(document_data, &paragraph_id, inline_text(cell));
+    }
+  }
+}
+
+fn cell_align(cell: ElementRef) -> &'static str {
+  let align = cell
+    .value()
+    .attr("align")
+    .map(|align| align.trim().to_lowercase())
+    .or_else(|| style_value(cell, "text-align"));
+  match align.as_deref() {
+    Some("center") => ALIGN_CENTER,
+    Some("right") => ALIGN_RIGHT,
+    _ => ALIGN_LEFT,
+  }
+}
+
+/// Returns the image urls if the paragraph only contains images
+fn image_urls(paragraph: ElementRef) -> Option<Vec<String>> {
+  let mut urls = vec![];
+  for node in child_nodes(paragraph) {
+    match node {
+      HtmlNode::Text(s) if s.trim().is_empty() => {},
+      HtmlNode::Element(element) if tag_name(&element) == "img" => {
+        urls.push(element.value().attr("src")?.to_string());
+      },
+      _ => return None,
+    }
+  }
+  (!urls.is_empty()).then_some(urls)
+}
+
+fn create_block(
+  document_data: &mut DocumentData,
+  parent_id: &str,
+  block_type: BlockType,
+  data: BlockData,
+) -> String {
+  let id = generate_id();
+  let block = Block {
+    id: id.clone(),
+    ty: block_type.to_string(),
+    data,
+    parent: parent_id.to_string(),
+    children: id.clone(),
+    external_id: Some(id.clone()),
+    external_type: Some(BlockType::Text.to_string()),
+  };
+  document_data.blocks.insert(id.clone(), block);
+  document_data
+    .meta
+    .children_map
+    .entry(parent_id.to_string())
+    .or_default()
+    .push(id.clone());
+  id
+}
+
+fn insert_image(document_data: &mut DocumentData, parent_id: &str, url: String) {
+  if url.is_empty() {
+    return;
+  }
+  let id = generate_id();
+  let block = create_image_block(&id, url, parent_id);
+  document_data.blocks.insert(id.clone(), block);
+  document_data
+    .meta
+    .children_map
+    .entry(parent_id.to_string())
+    .or_default()
+    .push(id);
+}
+
+/// Inserts the text as a paragraph. The text that only contains whitespaces is skipped.
+fn insert_paragraph(document_data: &mut DocumentData, parent_id: &str, text: InlineText) {
+  if text.is_empty() {
+    return;
+  }
+  let block_id = create_block(
+    document_data,
+    parent_id,
+    BlockType::Paragraph,
+    BlockData::new(),
+  );
+  insert_text(document_data, &block_id, text);
+}
+
+fn insert_text(document_data: &mut DocumentData, block_id: &str, text: InlineText) {
+  if !text.is_empty() {
+    insert_delta_to_text_map(document_data, block_id, text.into_delta());
+  }
+}
+
+fn inline_text(element: ElementRef) -> InlineText {
+  let mut text = InlineText::default();
+  collect_inline(&mut text, element, &[]);
+  text
+}
+
+/// Collects the text of the element and its descendants. The attributes of the delta come from
+/// the inline tags and the inline styles.
+fn collect_inline(text: &mut InlineText, element: ElementRef, attributes: &[(String, Value)]) {
+  let name = tag_name(&element);
+  if SKIPPED_TAGS.contains(&name) {
+    return;
+  }
+  if name == "br" {
+    text.push_line_break(attributes);
+    return;
+  }
+
+  let mut attributes = attributes.to_vec();
+  for (key, value) in inline_attributes(element) {
+    if attributes.iter().all(|(existing, _)| existing != &key) {
+      attributes.push((key, value));
+    }
+  }
+
+  for node in child_nodes(element) {
+    match node {
+      HtmlNode::Text(s) => text.push(s, &attributes),
+      HtmlNode::Element(child) => collect_inline(text, child, &attributes),
+    }
+  }
+}
+
+fn inline_attributes(element: ElementRef) -> Vec<(String, Value)> {
+  let mut attributes = vec![];
+  let mut push = |key: &str, value: Value| attributes.push((key.to_string(), value));
+  match tag_name(&element) {
+    "b" | "strong" => push(BOLD_ATTR, Value::Bool(true)),
+    "i" | "em" | "cite" | "dfn" | "var" => push(ITALIC_ATTR, Value::Bool(true)),
+    "u" | "ins" => push(UNDERLINE_ATTR, Value::Bool(true)),
+    "s" | "strike" | "del" => push(STRIKETHROUGH_ATTR, Value::Bool(true)),
+    "code" | "kbd" | "samp" => push(CODE_ATTR, Value::Bool(true)),
+    "a" => {
+      if let Some(href) = element.value().attr("href") {
+        push(HREF_ATTR, Value::String(href.to_string()));
+      }
+    },
+    _ => {},
+  }
+
+  if let Some(weight) = style_value(element, "font-weight") {
+    let is_bold = weight == "bold"
+      || weight == "bolder"
+      || weight.parse::<u32>().map_or(false, |weight| weight >= 600);
+    if is_bold {
+      push(BOLD_ATTR, Value::Bool(true));
+    }
+  }
+  if style_value(element, "font-style").as_deref() == Some("italic") {
+    push(ITALIC_ATTR, Value::Bool(true));
+  }
+  for key in ["text-decoration", "text-decoration-line"] {
+    if let Some(decoration) = style_value(element, key) {
+      if decoration.contains("underline") {
+        push(UNDERLINE_ATTR, Value::Bool(true));
+      }
+      if decoration.contains("line-through") {
+        push(STRIKETHROUGH_ATTR, Value::Bool(true));
+      }
+    }
+  }
+  attributes
+}
+
+/// Returns the lowercased value of the property in the inline style of the element
+fn style_value(element: ElementRef, property: &str) -> Option<String> {
+  element
+    .value()
+    .attr("style")?
+    .split(';')
+    .filter_map(|declaration| declaration.split_once(':'))
+    .find(|(key, _)| key.trim().eq_ignore_ascii_case(property))
+    .map(|(_, value)| value.trim().to_lowercase())
+}
+
+/// The text of a block. The whitespaces are collapsed the same way the browsers render them.
+#[derive(Default)]
+struct InlineText {
+  ops: Vec<(String, Vec<(String, Value)>)>,
+}
+
+impl InlineText {
+  fn push(&mut self, s: &str, attributes: &[(String, Value)]) {
+    let mut prev_is_space = self
+      .ops
+      .last()
+      .map_or(true, |(insert, _)| insert.ends_with([' ', '\n']));
+    let mut insert = String::with_capacity(s.len());
+    for c in s.chars() {
+      if c.is_ascii_whitespace() {
+        if !prev_is_space {
+          insert.push(' ');
+        }
+        prev_is_space = true;
+      } else {
+        insert.push(c);
+        prev_is_space = false;
+      }
+    }
+    if !insert.is_empty() {
+      self.ops.push((insert, attributes.to_vec()));
+    }
+  }
+
+  fn push_line_break(&mut self, attributes: &[(String, Value)]) {
+    if let Some((insert, _)) = self.ops.last_mut() {
+      if insert.ends_with(' ') {
+        insert.pop();
+      }
+    }
+    self.ops.push(("\n".to_string(), attributes.to_vec()));
+  }
+
+  fn is_empty(&self) -> bool {
+    self.ops.iter().all(|(insert, _)| insert.trim().is_empty())
+  }
+
+  fn into_delta(mut self) -> Delta {
+    if let Some((insert, _)) = self.ops.last_mut() {
+      if insert.ends_with(' ') {
+        insert.pop();
+      }
+    }
+    let mut delta = Delta::new();
+    for (insert, attributes) in self.ops {
+      if !insert.is_empty() {
+        delta.insert(insert, attributes);
+      }
+    }
+    delta
+  }
+}
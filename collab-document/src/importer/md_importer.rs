@@ -24,17 +24,7 @@ pub struct MDImporter {
 
 impl MDImporter {
   pub fn new(parse_options: Option<ParseOptions>) -> Self {
-    let parse_options = parse_options.unwrap_or_else(|| ParseOptions {
-      gfm_strikethrough_single_tilde: true,
-      constructs: Constructs {
-        math_text: true,
-        math_flow: true,
-        autolink: true,
-        ..Constructs::gfm()
-      },
-      ..ParseOptions::gfm()
-    });
-
+    let parse_options = parse_options.unwrap_or_else(default_parse_options);
     Self { parse_options }
   }
 
@@ -48,6 +38,7 @@ impl MDImporter {
       meta: DocumentMeta {
         children_map: HashMap::new(),
         text_map: Some(HashMap::new()),
+        page_meta: Default::default(),
       },
     };
 
@@ -64,6 +55,19 @@ impl MDImporter {
   }
 }
 
+fn default_parse_options() -> ParseOptions {
+  ParseOptions {
+    gfm_strikethrough_single_tilde: true,
+    constructs: Constructs {
+      math_text: true,
+      math_flow: true,
+      autolink: true,
+      ..Constructs::gfm()
+    },
+    ..ParseOptions::gfm()
+  }
+}
+
 /// This function will recursively process the mdast node and convert it to document blocks
 /// The document blocks will be stored in the document data
 fn process_mdast_node(
@@ -105,6 +109,16 @@ fn process_mdast_node(
     }
   }
 
+  // The blockquote that starts with an emoji or an admonition marker is a callout
+  if let mdast::Node::Blockquote(quote) = node {
+    if let Some((icon, children)) = parse_callout(quote) {
+      if let Some(parent_id) = parent_id {
+        process_callout(document_data, &parent_id, icon, &children);
+      }
+      return;
+    }
+  }
+
   // Process other nodes as normal nodes
   let id = block_id.unwrap_or_else(generate_id);
 
@@ -146,31 +160,7 @@ fn process_mdast_node(
     // handle the blockquote and list item node
     mdast::Node::Blockquote(_) | mdast::Node::ListItem(_) => {
       if let Some(children) = get_mdast_node_children(node) {
-        if children.is_empty() {
-          return;
-        }
-
-        if let Some((first, rest)) = children.split_first() {
-          // use the first node as the content of the block
-          if let mdast::Node::Paragraph(para) = first {
-            process_mdast_node_children(
-              document_data,
-              Some(id.clone()),
-              &para.children,
-              None,
-              start_number,
-            );
-          }
-
-          // continue to process the rest of the nodes
-          process_mdast_node_children(
-            document_data,
-            Some(id.clone()),
-            rest,
-            list_type,
-            start_number,
-          );
-        }
+        process_content_and_children(document_data, &id, children, list_type, start_number);
       }
     },
     mdast::Node::Code(code) => {
@@ -191,6 +181,62 @@ fn process_mdast_node(
   }
 }
 
+/// Use the first paragraph as the content of the block, and the rest of the nodes as the
+/// children of the block
+fn process_content_and_children(
+  document_data: &mut DocumentData,
+  block_id: &str,
+  children: &[mdast::Node],
+  list_type: Option<&str>,
+  start_number: Option<u32>,
+) {
+  if let Some((first, rest)) = children.split_first() {
+    // use the first node as the content of the block
+    if let mdast::Node::Paragraph(para) = first {
+      process_mdast_node_children(
+        document_data,
+        Some(block_id.to_string()),
+        &strip_collapsed_marker(&para.children),
+        None,
+        start_number,
+      );
+    }
+
+    // continue to process the rest of the nodes
+    process_mdast_node_children(
+      document_data,
+      Some(block_id.to_string()),
+      rest,
+      list_type,
+      start_number,
+    );
+  }
+}
+
+fn process_callout(
+  document_data: &mut DocumentData,
+  parent_id: &str,
+  icon: String,
+  children: &[mdast::Node],
+) {
+  let id = generate_id();
+  let mut data = BlockData::new();
+  data.insert(ICON_FIELD.to_string(), icon.into());
+  let block = Block {
+    id: id.clone(),
+    ty: BlockType::Callout.to_string(),
+    data,
+    parent: parent_id.to_string(),
+    children: id.clone(),
+    external_id: Some(id.clone()),
+    external_type: Some(BlockType::Text.to_string()),
+  };
+  document_data.blocks.insert(id.clone(), block);
+  update_children_map(document_data, Some(parent_id.to_string()), &id);
+
+  process_content_and_children(document_data, &id, children, None, None);
+}
+
 fn create_block(
   id: &str,
   node: &mdast::Node,
@@ -352,14 +398,194 @@ fn process_mdast_node_children(
   list_type: Option<&str>,
   start_number: Option<u32>,
 ) {
-  for child in children {
+  let mut index = 0;
+  while index < children.len() {
+    if let Some(parent_id) = parent_id.as_deref() {
+      if let Some(len) = process_toggle_nodes(document_data, parent_id, &children[index..]) {
+        index += len;
+        continue;
+      }
+    }
+
     process_mdast_node(
       document_data,
-      child,
+      &children[index],
       parent_id.clone(),
       None,
       list_type,
       start_number,
     );
+    index += 1;
+  }
+}
+
+/// Process the toggle that starts at the first node, and return the number of nodes that belong
+/// to the toggle. Return None if the first node doesn't start a toggle.
+///
+/// The toggles are exported as `<details>` html fragments, or as headings with the
+/// [COLLAPSED_MARKER] that are followed by the indented content.
+fn process_toggle_nodes(
+  document_data: &mut DocumentData,
+  parent_id: &str,
+  nodes: &[mdast::Node],
+) -> Option<usize> {
+  match nodes.first()? {
+    mdast::Node::Html(html) => {
+      let details = DetailsHtml::parse(&html.value)?;
+      let mut data = BlockData::new();
+      data.insert(COLLAPSED_FIELD.to_string(), (!details.open).into());
+      let toggle_id = create_toggle_block(document_data, parent_id, data);
+      process_markdown_inline(document_data, &toggle_id, &details.summary);
+      process_markdown(document_data, &toggle_id, &details.content);
+      if details.closed {
+        return Some(1);
+      }
+
+      // The content between the opening and the closing html are the children of the toggle
+      let rest = &nodes[1..];
+      let end = find_details_end(rest);
+      process_mdast_node_children(document_data, Some(toggle_id), &rest[..end], None, None);
+      Some((end + 2).min(nodes.len()))
+    },
+    mdast::Node::Heading(heading) if is_collapsed_heading(heading) => {
+      let mut data = BlockData::new();
+      data.insert(LEVEL_FIELD.to_string(), heading.depth.clamp(1, 6).into());
+      data.insert(COLLAPSED_FIELD.to_string(), true.into());
+      let toggle_id = create_toggle_block(document_data, parent_id, data);
+
+      let children = strip_collapsed_marker(&heading.children);
+      process_mdast_node_children(
+        document_data,
+        Some(toggle_id.clone()),
+        &children,
+        None,
+        None,
+      );
+
+      match nodes.get(1) {
+        Some(mdast::Node::Code(code)) if is_indented_code(code) => {
+          process_markdown(document_data, &toggle_id, &code.value);
+          Some(2)
+        },
+        _ => Some(1),
+      }
+    },
+    _ => None,
+  }
+}
+
+fn create_toggle_block(
+  document_data: &mut DocumentData,
+  parent_id: &str,
+  data: BlockData,
+) -> String {
+  let id = generate_id();
+  let block = Block {
+    id: id.clone(),
+    ty: BlockType::ToggleList.to_string(),
+    data,
+    parent: parent_id.to_string(),
+    children: id.clone(),
+    external_id: Some(id.clone()),
+    external_type: Some(BlockType::Text.to_string()),
+  };
+  document_data.blocks.insert(id.clone(), block);
+  update_children_map(document_data, Some(parent_id.to_string()), &id);
+  id
+}
+
+/// Process the markdown as the children of the parent block
+fn process_markdown(document_data: &mut DocumentData, parent_id: &str, markdown: &str) {
+  if markdown.trim().is_empty() {
+    return;
+  }
+  if let Ok(mdast::Node::Root(root)) = to_mdast(markdown, &default_parse_options()) {
+    process_mdast_node_children(
+      document_data,
+      Some(parent_id.to_string()),
+      &root.children,
+      None,
+      None,
+    );
+  }
+}
+
+/// Process the markdown as the text of the block. Only the inline nodes of the first paragraph
+/// are kept.
+fn process_markdown_inline(document_data: &mut DocumentData, block_id: &str, markdown: &str) {
+  if let Ok(mdast::Node::Root(root)) = to_mdast(markdown, &default_parse_options()) {
+    if let Some(mdast::Node::Paragraph(para)) = root.children.first() {
+      for child in para.children.iter().filter(|node| is_inline_node(node)) {
+        process_inline_mdast_node(document_data, child, Some(block_id.to_string()));
+      }
+    }
+  }
+}
+
+/// Return the index of the node that closes the `<details>`. The nested `<details>` are skipped.
+fn find_details_end(nodes: &[mdast::Node]) -> usize {
+  let mut depth = 0;
+  for (index, node) in nodes.iter().enumerate() {
+    if let mdast::Node::Html(html) = node {
+      let value = html.value.to_ascii_lowercase();
+      depth += value.matches("<details").count();
+      let closes = value.matches("</details>").count();
+      if closes > depth {
+        return index;
+      }
+      depth -= closes;
+    }
+  }
+  nodes.len()
+}
+
+/// The opening html of the `<details>` element
+struct DetailsHtml {
+  open: bool,
+  summary: String,
+  /// The content after the summary
+  content: String,
+  /// Whether the html contains the closing tag
+  closed: bool,
+}
+
+impl DetailsHtml {
+  fn parse(html: &str) -> Option<Self> {
+    let html = html.trim();
+    // The ascii lowercase keeps the byte offsets of the html
+    let lowercase = html.to_ascii_lowercase();
+    if !lowercase.starts_with("<details") {
+      return None;
+    }
+    let tag_end = lowercase.find('>')?;
+    let open = lowercase[..tag_end]
+      .split_whitespace()
+      .any(|attr| attr == "open" || attr.starts_with("open="));
+
+    let mut content_start = tag_end + 1;
+    let mut summary = String::new();
+    if lowercase[content_start..]
+      .trim_start()
+      .starts_with("<summary")
+    {
+      let summary_start = content_start + lowercase[content_start..].find('>')? + 1;
+      let summary_end = summary_start + lowercase[summary_start..].find("</summary>")?;
+      summary = html[summary_start..summary_end].trim().to_string();
+      content_start = summary_end + "</summary>".len();
+    }
+
+    let rest = &lowercase[content_start..];
+    let closed = rest.matches("</details>").count() > rest.matches("<details").count();
+    let content_end = if closed {
+      content_start + rest.rfind("</details>")?
+    } else {
+      html.len()
+    };
+    Some(Self {
+      open,
+      summary,
+      content: html[content_start..content_end].to_string(),
+      closed,
+    })
   }
 }
@@ -1,4 +1,5 @@
 pub mod define;
 mod delta;
+pub mod html_importer;
 pub mod md_importer;
 mod util;
@@ -26,7 +26,7 @@ pub(crate) fn mdast_node_type_to_block_type(node: &mdast::Node, list_type: Optio
       if list.checked.is_some() {
         BlockType::TodoList
       } else {
-        match list_type {
+        let ty = match list_type {
           None => BlockType::BulletedList,
           Some(s) => {
             let ty = BlockType::from_block_ty(s);
@@ -36,6 +36,11 @@ pub(crate) fn mdast_node_type_to_block_type(node: &mdast::Node, list_type: Optio
               ty
             }
           },
+        };
+        if ty == BlockType::BulletedList && is_toggle_list_item(list) {
+          BlockType::ToggleList
+        } else {
+          ty
         }
       }
     },
@@ -109,6 +114,10 @@ pub(crate) fn mdast_node_to_block_data(node: &mdast::Node, start_number: Option<
       if let Some(start_number) = start_number {
         data.insert(START_NUMBER_FIELD.to_string(), start_number.into());
       }
+
+      if is_toggle_list_item(list) {
+        data.insert(COLLAPSED_FIELD.to_string(), true.into());
+      }
     },
     mdast::Node::Definition(defi) => {
       let url = defi.url.to_string();
@@ -132,6 +141,177 @@ pub(crate) fn is_image_url(url: &str) -> bool {
     .any(|ext| url.to_lowercase().ends_with(ext))
 }
 
+/// Check if the list item is a toggle, i.e. its text has the [COLLAPSED_MARKER]. The indented
+/// content of the toggle becomes its children. The other list items with the indented content
+/// are kept as the bulleted lists.
+pub(crate) fn is_toggle_list_item(list: &mdast::ListItem) -> bool {
+  match list.children.first() {
+    Some(mdast::Node::Paragraph(para)) => para.children.iter().any(is_collapsed_marker),
+    _ => false,
+  }
+}
+
+/// Check if the heading has the [COLLAPSED_MARKER]
+pub(crate) fn is_collapsed_heading(heading: &mdast::Heading) -> bool {
+  heading.children.iter().any(is_collapsed_marker)
+}
+
+/// Remove the [COLLAPSED_MARKER] and the whitespaces before it from the inline nodes
+pub(crate) fn strip_collapsed_marker(nodes: &[mdast::Node]) -> Vec<mdast::Node> {
+  let mut nodes = nodes
+    .iter()
+    .filter(|node| !is_collapsed_marker(node))
+    .cloned()
+    .collect::<Vec<_>>();
+  if let Some(mdast::Node::Text(text)) = nodes.last_mut() {
+    text.value = text.value.trim_end().to_string();
+  }
+  nodes
+}
+
+pub(crate) fn is_collapsed_marker(node: &mdast::Node) -> bool {
+  match node {
+    mdast::Node::Html(html) => {
+      html.value.split_whitespace().collect::<String>() == COLLAPSED_MARKER.replace(' ', "")
+    },
+    _ => false,
+  }
+}
+
+/// Check if the code is indented instead of fenced. The indented code doesn't have the fence
+/// lines, so the node spans the same number of lines as the code.
+pub(crate) fn is_indented_code(code: &mdast::Code) -> bool {
+  code.lang.is_none()
+    && code.position.as_ref().map_or(false, |position| {
+      position.end.line - position.start.line + 1 == code.value.lines().count()
+    })
+}
+
+/// Parse the blockquote that starts with an emoji, like `> 💡 text`, or with an admonition
+/// marker, like `> [!NOTE] text`, as a callout. Return the icon of the callout and the children
+/// of the blockquote without the marker.
+pub(crate) fn parse_callout(quote: &mdast::Blockquote) -> Option<(String, Vec<mdast::Node>)> {
+  let mut children = quote.children.clone();
+  let para = match children.first_mut()? {
+    mdast::Node::Paragraph(para) => para,
+    _ => return None,
+  };
+
+  // The marker might be split into multiple text nodes
+  let leading_text = para
+    .children
+    .iter()
+    .map_while(|node| match node {
+      mdast::Node::Text(text) => Some(text.value.as_str()),
+      _ => None,
+    })
+    .collect::<String>();
+  let (icon, marker_len) = parse_callout_marker(&leading_text)?;
+  let after_marker = &leading_text[marker_len..];
+  let mut remove_len = marker_len + after_marker.len() - after_marker.trim_start().len();
+  para.children.retain_mut(|node| {
+    if remove_len == 0 {
+      return true;
+    }
+    match node {
+      mdast::Node::Text(text) => {
+        let len = remove_len.min(text.value.len());
+        text.value.drain(..len);
+        remove_len -= len;
+        !text.value.is_empty()
+      },
+      _ => true,
+    }
+  });
+
+  if para.children.is_empty() {
+    children.remove(0);
+  }
+  Some((icon, children))
+}
+
+/// Return the icon and the length of the callout marker at the start of the text
+fn parse_callout_marker(text: &str) -> Option<(String, usize)> {
+  let (icon, marker_len) = match text.strip_prefix("[!") {
+    Some(rest) => {
+      let end = rest.find(']')?;
+      let callout_type = &rest[..end];
+      if callout_type.is_empty() || !callout_type.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+      }
+      (default_callout_icon(callout_type).to_string(), end + 3)
+    },
+    None => {
+      let emoji = leading_emoji(text)?;
+      (emoji.to_string(), emoji.len())
+    },
+  };
+
+  // The marker must be separated from the text
+  let is_separated = text[marker_len..]
+    .chars()
+    .next()
+    .map_or(true, char::is_whitespace);
+  is_separated.then_some((icon, marker_len))
+}
+
+/// Return the icon of the GitHub admonition type
+fn default_callout_icon(callout_type: &str) -> &'static str {
+  match callout_type.to_ascii_uppercase().as_str() {
+    "NOTE" => "📝",
+    "TIP" => "💡",
+    "IMPORTANT" => "❗",
+    "WARNING" => "⚠️",
+    "CAUTION" => "🛑",
+    _ => "📌",
+  }
+}
+
+/// Return the emoji at the start of the text. The emoji might be a sequence of the code points,
+/// like the emoji with a skin tone or a flag.
+fn leading_emoji(text: &str) -> Option<&str> {
+  let mut chars = text.char_indices();
+  let (_, first) = chars.next()?;
+  if !is_pictographic(first) {
+    return None;
+  }
+
+  let mut end = first.len_utf8();
+  let mut prev = first;
+  for (index, c) in chars {
+    let is_part = matches!(
+      c,
+      '\u{FE0F}' | '\u{200D}' | '\u{20E3}' | '\u{1F3FB}'..='\u{1F3FF}'
+    ) || (prev == '\u{200D}' && is_pictographic(c))
+      || (is_regional_indicator(prev) && is_regional_indicator(c) && end == first.len_utf8());
+    if !is_part {
+      break;
+    }
+    end = index + c.len_utf8();
+    prev = c;
+  }
+  Some(&text[..end])
+}
+
+fn is_pictographic(c: char) -> bool {
+  matches!(
+    c as u32,
+    0x1F000..=0x1FAFF
+      | 0x2600..=0x27BF
+      | 0x2300..=0x23FF
+      | 0x2B00..=0x2BFF
+      | 0x2139
+      | 0x203C
+      | 0x2049
+      | 0x3297
+      | 0x3299
+  )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+  ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
 /// Check if the node is an inline node
 pub(crate) fn is_inline_node(node: &mdast::Node) -> bool {
   matches!(
@@ -2,6 +2,8 @@ pub mod blocks;
 pub mod document;
 pub mod document_awareness;
 pub mod document_data;
+pub mod document_observer;
 pub mod error;
+pub mod exporter;
 pub mod importer;
 mod utils;
@@ -1,4 +1,8 @@
 use crate::blocks::{Block, TextDelta};
+use crate::document::DocumentCounters;
+use crate::importer::define::{
+  COLS_LEN_FIELD, COL_POSITION_FIELD, ROWS_LEN_FIELD, ROW_POSITION_FIELD,
+};
 use std::collections::HashMap;
 
 #[inline]
@@ -21,6 +25,79 @@ pub(crate) fn push_deltas_to_str(
   }
 }
 
+/// Returns the non-overlapping matches of the `query` in the `text` as the character ranges.
+/// The characters are compared one by one, so the lowercase of a character that expands to
+/// several characters doesn't shift the offsets.
+pub(crate) fn find_matches(
+  text: &[char],
+  query: &[char],
+  case_sensitive: bool,
+) -> Vec<(usize, usize)> {
+  let eq = |a: char, b: char| {
+    if case_sensitive {
+      a == b
+    } else {
+      a == b || a.to_lowercase().eq(b.to_lowercase())
+    }
+  };
+
+  let mut matches = vec![];
+  if query.is_empty() {
+    return matches;
+  }
+  let mut start = 0;
+  while start + query.len() <= text.len() {
+    let end = start + query.len();
+    if text[start..end].iter().zip(query).all(|(a, b)| eq(*a, *b)) {
+      matches.push((start, end));
+      start = end;
+    } else {
+      start += 1;
+    }
+  }
+  matches
+}
+
+/// Adds the words and characters of the text to the counters. The empty text isn't counted as a
+/// block. See [DocumentCounters] for the heuristic of the words.
+pub(crate) fn count_text(counters: &mut DocumentCounters, text: &str) {
+  if text.is_empty() {
+    return;
+  }
+
+  counters.blocks += 1;
+  let mut in_word = false;
+  for c in text.chars() {
+    counters.characters += 1;
+    if c.is_whitespace() {
+      in_word = false;
+      continue;
+    }
+
+    counters.characters_no_spaces += 1;
+    if is_cjk(c) {
+      counters.words += 1;
+      in_word = false;
+    } else if c.is_alphanumeric() && !in_word {
+      counters.words += 1;
+      in_word = true;
+    }
+  }
+}
+
+/// Returns true if the character is a Han, Hiragana or Katakana character.
+fn is_cjk(c: char) -> bool {
+  matches!(
+    c as u32,
+    0x3040..=0x309F // Hiragana
+      | 0x30A0..=0x30FF // Katakana
+      | 0x3400..=0x4DBF // CJK Unified Ideographs Extension A
+      | 0x4E00..=0x9FFF // CJK Unified Ideographs
+      | 0xF900..=0xFAFF // CJK Compatibility Ideographs
+      | 0x20000..=0x323AF // CJK Unified Ideographs Extension B to H
+  )
+}
+
 /// Try to retrieve deltas from `block.data.delta`.
 #[inline]
 pub(crate) fn get_delta_from_block_data(block: &Block) -> Option<Vec<TextDelta>> {
@@ -47,3 +124,101 @@ pub(crate) fn get_delta_from_external_text_id(
   }
   None
 }
+
+/// Convert the table to tab-separated rows, one line per row. The cells are placed by the
+/// `rowPosition` and `colPosition` of their data, and the missing cells are empty columns. The
+/// cells whose position is outside of the `rowsLen` and `colsLen` of the table are skipped.
+pub(crate) fn table_to_plain_text(
+  table: &Block,
+  blocks: &HashMap<String, Block>,
+  children_map: &HashMap<String, Vec<String>>,
+  text_map: &mut HashMap<String, Vec<TextDelta>>,
+  empty_space_each_delta: bool,
+) -> String {
+  let position = |block: &Block, key: &str| {
+    block
+      .data
+      .get(key)
+      .and_then(|value| value.as_u64())
+      .unwrap_or(0) as usize
+  };
+
+  let mut rows: Vec<Vec<String>> = vec![];
+  let cells = children_map
+    .get(&table.children)
+    .cloned()
+    .unwrap_or_default();
+  // The table without the lengths can't have more rows or columns than cells
+  let max_len = |key: &str| {
+    table
+      .data
+      .get(key)
+      .and_then(|value| value.as_u64())
+      .map(|len| len as usize)
+      .unwrap_or(cells.len())
+  };
+  let (max_rows, max_cols) = (max_len(ROWS_LEN_FIELD), max_len(COLS_LEN_FIELD));
+  for cell in cells.iter().flat_map(|id| blocks.get(id)) {
+    let (row, col) = (
+      position(cell, ROW_POSITION_FIELD),
+      position(cell, COL_POSITION_FIELD),
+    );
+    if row >= max_rows || col >= max_cols {
+      continue;
+    }
+    if rows.len() <= row {
+      rows.resize(row + 1, vec![]);
+    }
+    if rows[row].len() <= col {
+      rows[row].resize(col + 1, String::new());
+    }
+    rows[row][col] =
+      cell_to_plain_text(cell, blocks, children_map, text_map, empty_space_each_delta);
+  }
+
+  let rows_len = rows.len().max(position(table, ROWS_LEN_FIELD));
+  let cols_len = rows
+    .iter()
+    .map(|row| row.len())
+    .max()
+    .unwrap_or(0)
+    .max(position(table, COLS_LEN_FIELD));
+  rows.resize(rows_len, vec![]);
+  rows
+    .into_iter()
+    .map(|mut row| {
+      row.resize(cols_len, String::new());
+      row.join("\t")
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Join the text of the blocks in the cell with spaces. The tabs and the line breaks are
+/// replaced, so the text stays in one column.
+fn cell_to_plain_text(
+  cell: &Block,
+  blocks: &HashMap<String, Block>,
+  children_map: &HashMap<String, Vec<String>>,
+  text_map: &mut HashMap<String, Vec<TextDelta>>,
+  empty_space_each_delta: bool,
+) -> String {
+  let mut texts = vec![];
+  let mut stack = vec![cell];
+  while let Some(block) = stack.pop() {
+    let deltas =
+      get_delta_from_block_data(block).or_else(|| get_delta_from_external_text_id(block, text_map));
+    if let Some(deltas) = deltas {
+      let mut text = String::new();
+      push_deltas_to_str(&mut text, deltas, empty_space_each_delta);
+      let text = text.replace(['\t', '\n'], " ");
+      if !text.trim().is_empty() {
+        texts.push(text.trim().to_string());
+      }
+    }
+    if let Some(children) = children_map.get(&block.children) {
+      stack.extend(children.iter().rev().flat_map(|id| blocks.get(id)));
+    }
+  }
+  texts.join(" ")
+}
@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use collab::core::collab::{Collab, DataSource};
+use collab::preclude::{MapExt, MapRef};
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab_document::blocks::DeltaType;
+use collab_document::document::Document;
+use collab_document::document_observer::DocumentChange;
+use collab_entity::define::DOCUMENT_ROOT;
+use serde_json::{json, Value};
+
+use crate::blocks::block_test_core::BlockTestCore;
+use crate::util::{sync_from_remote, wait_for_specific_event};
+
+fn patch(values: Value) -> HashMap<String, Value> {
+  serde_json::from_value(values).unwrap()
+}
+
+#[test]
+fn merge_block_data_test() {
+  let mut test = BlockTestCore::new();
+  let page = test.get_page();
+  let block = test.insert_text_block("Todo".to_string(), &page.id, None);
+  test.update_block_data(&block.id, patch(json!({ "checked": false, "level": 1 })));
+
+  test
+    .document
+    .update_block_data(&block.id, patch(json!({ "checked": true })))
+    .unwrap();
+  assert_eq!(
+    test.get_block(&block.id).data,
+    patch(json!({ "checked": true, "level": 1 }))
+  );
+
+  // the keys whose value is null are removed
+  test
+    .document
+    .update_block_data(&block.id, patch(json!({ "level": null, "color": "red" })))
+    .unwrap();
+  assert_eq!(
+    test.get_block(&block.id).data,
+    patch(json!({ "checked": true, "color": "red" }))
+  );
+  assert_eq!(
+    test.get_document_data().blocks[&block.id].data,
+    patch(json!({ "checked": true, "color": "red" }))
+  );
+
+  // updating the block still replaces the whole data
+  test.update_block_data(&block.id, patch(json!({ "level": 2 })));
+  assert_eq!(test.get_block(&block.id).data, patch(json!({ "level": 2 })));
+}
+
+#[test]
+fn merge_block_data_of_not_exist_block_test() {
+  let mut test = BlockTestCore::new();
+  assert!(test
+    .document
+    .update_block_data("not_exist", patch(json!({ "checked": true })))
+    .is_err());
+}
+
+#[test]
+fn merged_block_data_is_readable_by_pre_merge_reader_test() {
+  let mut test = BlockTestCore::new();
+  let page = test.get_page();
+  let block = test.insert_text_block("Todo".to_string(), &page.id, None);
+  test
+    .document
+    .update_block_data(&block.id, patch(json!({ "checked": true, "level": 1 })))
+    .unwrap();
+
+  // read the block the way the clients that don't know about the merge do: the data is one
+  // json string
+  let collab = Collab::new_with_source(
+    CollabOrigin::Empty,
+    "1",
+    DataSource::from(test.document.encode_collab().unwrap()),
+    vec![],
+    false,
+  )
+  .unwrap();
+  let txn = collab.transact();
+  let root: MapRef = collab.data.get_with_txn(&txn, DOCUMENT_ROOT).unwrap();
+  let blocks: MapRef = root.get_with_txn(&txn, "blocks").unwrap();
+  let block_map: MapRef = blocks.get_with_txn(&txn, &block.id).unwrap();
+  let json_str: String = block_map.get_with_txn(&txn, "data").unwrap();
+  let data: HashMap<String, Value> = serde_json::from_str(&json_str).unwrap();
+  assert_eq!(data, patch(json!({ "checked": true, "level": 1 })));
+}
+
+#[test]
+fn concurrent_merge_block_data_test() {
+  let mut test = BlockTestCore::new();
+  let page = test.get_page();
+  let block = test.insert_text_block("Todo".to_string(), &page.id, None);
+  test.update_block_data(&block.id, patch(json!({ "level": 1 })));
+  let mut remote = Document::open_with_options(
+    CollabOrigin::Client(CollabClient::new(2, "2")),
+    DataSource::from(test.document.encode_collab().unwrap()),
+    "1",
+    vec![],
+  )
+  .unwrap();
+
+  // the two collaborators merge different keys into the block at the same time
+  test
+    .document
+    .update_block_data(&block.id, patch(json!({ "checked": true })))
+    .unwrap();
+  remote
+    .update_block_data(&block.id, patch(json!({ "color": "red" })))
+    .unwrap();
+  sync_from_remote(&mut test.document, &remote);
+  sync_from_remote(&mut remote, &test.document);
+
+  // the data is one json string, so one of the merged versions wins on both collaborators
+  let data = test.get_block(&block.id).data;
+  assert_eq!(remote.get_block(&block.id).unwrap().data, data);
+  assert!(
+    data == patch(json!({ "checked": true, "level": 1 }))
+      || data == patch(json!({ "color": "red", "level": 1 }))
+  );
+}
+
+#[test]
+fn subscribe_merge_block_data_test() {
+  let mut test = BlockTestCore::new();
+  let page = test.get_page();
+  let block = test.insert_text_block("Todo".to_string(), &page.id, None);
+  let events = Arc::new(Mutex::new(vec![]));
+  let cloned_events = events.clone();
+  test.subscribe("merge", move |block_events, _| {
+    let mut events = cloned_events.lock().unwrap();
+    events.extend(block_events.iter().flat_map(|event| event.iter().cloned()));
+  });
+
+  test
+    .document
+    .update_block_data(&block.id, patch(json!({ "checked": true })))
+    .unwrap();
+
+  // the block data is sent as one json string
+  let events = events.lock().unwrap();
+  assert_eq!(events.len(), 1);
+  assert_eq!(events[0].id, block.id);
+  assert_eq!(events[0].path, vec!["blocks".to_string(), block.id.clone()]);
+  assert_eq!(events[0].command, DeltaType::Updated);
+  let value: HashMap<String, String> = serde_json::from_str(&events[0].value).unwrap();
+  let data: HashMap<String, Value> = serde_json::from_str(&value["data"]).unwrap();
+  assert_eq!(data, patch(json!({ "checked": true })));
+}
+
+#[tokio::test]
+async fn observe_merge_block_data_test() {
+  let mut test = BlockTestCore::new();
+  let page = test.get_page();
+  let block = test.insert_text_block("Todo".to_string(), &page.id, None);
+  test.update_block_data(&block.id, patch(json!({ "checked": false, "level": 1 })));
+
+  let change_rx = test.document.subscribe_block_changes();
+  test
+    .document
+    .update_block_data(&block.id, patch(json!({ "checked": true })))
+    .unwrap();
+  let expected = patch(json!({ "checked": true, "level": 1 }));
+  wait_for_specific_event(change_rx, |change| match change {
+    DocumentChange::DidUpdateBlockData { block_id, data } => {
+      block_id == &block.id && data == &expected
+    },
+    _ => false,
+  })
+  .await
+  .unwrap();
+
+  let change_rx = test.document.subscribe_block_changes();
+  test
+    .document
+    .update_block_data(&block.id, patch(json!({ "level": null })))
+    .unwrap();
+  let expected = patch(json!({ "checked": true }));
+  wait_for_specific_event(change_rx, |change| match change {
+    DocumentChange::DidUpdateBlockData { block_id, data } => {
+      block_id == &block.id && data == &expected
+    },
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use collab::core::collab::DataSource;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab_document::blocks::Block;
+use collab_document::document::Document;
+use collab_document::document_observer::DocumentChange;
+use serde_json::json;
+
+use crate::blocks::block_test_core::{generate_id, BlockTestCore, TEXT_BLOCK_TYPE};
+use crate::util::{sync_from_remote, wait_for_specific_event};
+
+#[tokio::test]
+async fn observe_insert_block_test() {
+  let mut test = BlockTestCore::new();
+  let change_rx = test.document.subscribe_block_changes();
+  let page = test.get_page();
+  let block = test.insert_text_block("Hello World".to_string(), &page.id, None);
+
+  let block_id = block.id.clone();
+  wait_for_specific_event(change_rx, |change| match change {
+    DocumentChange::DidInsertBlock { block } => block.id == block_id,
+    _ => false,
+  })
+  .await
+  .unwrap();
+
+  let change_rx = test.document.subscribe_block_changes();
+  let block = test.insert_text_block("Second".to_string(), &page.id, Some(block.id));
+  let page_children = test.get_block_children(&page.id);
+  wait_for_specific_event(change_rx, |change| match change {
+    DocumentChange::DidUpdateChildren {
+      children_id,
+      children,
+    } => {
+      children_id == &page.children
+        && children.len() == page_children.len()
+        && children[1] == block.id
+    },
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn observe_update_block_data_test() {
+  let mut test = BlockTestCore::new();
+  let page = test.get_page();
+  let block = test.insert_text_block("Hello World".to_string(), &page.id, None);
+  let change_rx = test.document.subscribe_block_changes();
+
+  let mut data = HashMap::new();
+  data.insert("checked".to_string(), json!(true));
+  test.update_block_data(&block.id, data.clone());
+
+  wait_for_specific_event(change_rx, |change| match change {
+    DocumentChange::DidUpdateBlockData {
+      block_id,
+      data: new_data,
+    } => block_id == &block.id && new_data == &data,
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn observe_delete_block_test() {
+  let mut test = BlockTestCore::new();
+  let page = test.get_page();
+  let parent = test.insert_text_block("Parent".to_string(), &page.id, None);
+  let block = test.insert_text_block("Child".to_string(), &parent.id, None);
+  // the parent of the deleted block is the one after the move
+  test.move_block(&block.id, &page.id, None);
+  let change_rx = test.document.subscribe_block_changes();
+
+  test.delete_block(&block.id);
+  wait_for_specific_event(change_rx, |change| match change {
+    DocumentChange::DidDeleteBlock {
+      block_id,
+      parent_id,
+    } => block_id == &block.id && parent_id == &page.id,
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn observe_update_text_test() {
+  let mut test = BlockTestCore::new();
+  let page = test.get_page();
+  let block = test.insert_text_block("Hello".to_string(), &page.id, None);
+  let change_rx = test.document.subscribe_block_changes();
+
+  let text_id = block.external_id.unwrap();
+  let delta = json!([{ "retain": 5 }, { "insert": " World" }]).to_string();
+  test.document.apply_text_delta(&text_id, &delta).unwrap();
+
+  wait_for_specific_event(change_rx, |change| match change {
+    DocumentChange::DidUpdateText { text_id: id } => id == &text_id,
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
+
+#[tokio::test]
+async fn observe_remote_update_test() {
+  let mut test = BlockTestCore::new();
+  let page = test.get_page();
+  let mut remote = Document::open_with_options(
+    CollabOrigin::Client(CollabClient::new(2, "2")),
+    DataSource::from(test.document.encode_collab().unwrap()),
+    "1",
+    vec![],
+  )
+  .unwrap();
+
+  // the remote collaborator inserts a block and updates the data of the page
+  let text_id = generate_id();
+  remote
+    .apply_text_delta(&text_id, &json!([{ "insert": "Remote" }]).to_string())
+    .unwrap();
+  let block = remote
+    .insert_block(
+      Block {
+        id: generate_id(),
+        ty: TEXT_BLOCK_TYPE.to_string(),
+        parent: page.id.clone(),
+        children: generate_id(),
+        external_id: Some(text_id.clone()),
+        external_type: Some("text".to_string()),
+        data: HashMap::new(),
+      },
+      None,
+    )
+    .unwrap();
+  let mut data = HashMap::new();
+  data.insert("cover".to_string(), json!("remote"));
+  remote.update_block(&page.id, data.clone()).unwrap();
+
+  let change_rx = test.document.subscribe_block_changes();
+  sync_from_remote(&mut test.document, &remote);
+  let mut did_insert_block = false;
+  let mut did_update_text = false;
+  let mut did_update_data = false;
+  wait_for_specific_event(change_rx, |change| {
+    match change {
+      DocumentChange::DidInsertBlock { block: new_block } => {
+        did_insert_block |= new_block == &block
+      },
+      DocumentChange::DidUpdateText { text_id: id } => did_update_text |= id == &text_id,
+      DocumentChange::DidUpdateBlockData {
+        block_id,
+        data: new_data,
+      } => did_update_data |= block_id == &page.id && new_data == &data,
+      _ => {},
+    }
+    did_insert_block && did_update_text && did_update_data
+  })
+  .await
+  .unwrap();
+
+  // the remote collaborator deletes the block
+  remote.delete_block(&block.id).unwrap();
+  let change_rx = test.document.subscribe_block_changes();
+  sync_from_remote(&mut test.document, &remote);
+  wait_for_specific_event(change_rx, |change| match change {
+    DocumentChange::DidDeleteBlock {
+      block_id,
+      parent_id,
+    } => block_id == &block.id && parent_id == &page.id,
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
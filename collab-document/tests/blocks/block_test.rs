@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use collab_document::blocks::{Block, BlockAction, BlockActionPayload, BlockActionType};
+use collab_document::error::DocumentError;
 use serde_json::json;
 
 use crate::blocks::block_test_core::{generate_id, BlockTestCore, TEXT_BLOCK_TYPE};
@@ -162,6 +163,93 @@ fn move_block_test() {
   try_decode_from_encode_collab(&test.document);
 }
 
+#[test]
+fn move_block_keeps_text_and_children_test() {
+  let mut test = BlockTestCore::new();
+  let page = test.get_page();
+  let page_id = page.id.as_str();
+  let toggle = test.insert_text_block("Toggle".to_string(), page_id, None);
+  let paragraph = test.insert_text_block("Paragraph".to_string(), page_id, Some(toggle.id.clone()));
+  let nested = test.insert_text_block("Nested".to_string(), &paragraph.id, None);
+
+  let children_map = test.get_document_data().meta.children_map;
+  assert_eq!(
+    children_map.get(&page.children).unwrap()[..2],
+    [toggle.id.clone(), paragraph.id.clone()]
+  );
+  assert!(children_map.get(&toggle.children).unwrap().is_empty());
+
+  // move the paragraph with its child into the toggle
+  test
+    .document
+    .move_block(&paragraph.id, Some(toggle.id.clone()), None)
+    .unwrap();
+
+  let children_map = test.get_document_data().meta.children_map;
+  assert!(!children_map
+    .get(&page.children)
+    .unwrap()
+    .contains(&paragraph.id));
+  assert_eq!(
+    children_map.get(&toggle.children).unwrap(),
+    &vec![paragraph.id.clone()]
+  );
+  assert_eq!(
+    children_map.get(&paragraph.children).unwrap(),
+    &vec![nested.id.clone()]
+  );
+
+  let moved = test.get_block(&paragraph.id);
+  assert_eq!(moved.parent, toggle.id);
+  assert_eq!(moved.external_id, paragraph.external_id);
+  assert_eq!(moved.data, paragraph.data);
+  assert_eq!(
+    test
+      .document
+      .get_plain_text_from_block(&paragraph.id)
+      .unwrap(),
+    "Paragraph"
+  );
+  assert_eq!(
+    test.document.get_plain_text_from_block(&nested.id).unwrap(),
+    "Nested"
+  );
+
+  // move the paragraph within the toggle without the parent id
+  let second = test.insert_text_block("Second".to_string(), &toggle.id, None);
+  test
+    .document
+    .move_block(&paragraph.id, None, Some(second.id.clone()))
+    .unwrap();
+  let toggle_children = test.get_block_children(&toggle.id);
+  assert_eq!(toggle_children[0].id, second.id);
+  assert_eq!(toggle_children[1].id, paragraph.id);
+
+  try_decode_from_encode_collab(&test.document);
+}
+
+#[test]
+fn move_block_into_descendant_test() {
+  let mut test = BlockTestCore::new();
+  let page = test.get_page();
+  let page_id = page.id.as_str();
+  let parent = test.insert_text_block("Parent".to_string(), page_id, None);
+  let child = test.insert_text_block("Child".to_string(), &parent.id, None);
+  let grandchild = test.insert_text_block("Grandchild".to_string(), &child.id, None);
+
+  for target in [&parent.id, &child.id, &grandchild.id] {
+    let result = test
+      .document
+      .move_block(&parent.id, Some(target.clone()), None);
+    assert!(matches!(result, Err(DocumentError::MoveIntoDescendant)));
+  }
+
+  // nothing is changed
+  assert_eq!(test.get_block(&parent.id).parent, page.id);
+  assert_eq!(test.get_block_children(&parent.id)[0].id, child.id);
+  assert_eq!(test.get_block_children(page_id)[0].id, parent.id);
+}
+
 #[test]
 fn update_block_data_test() {
   let mut test = BlockTestCore::new();
@@ -109,6 +109,7 @@ impl BlockTestCore {
     let meta = DocumentMeta {
       children_map,
       text_map: Some(text_map),
+      page_meta: Default::default(),
     };
     DocumentData {
       page_id,
@@ -164,7 +165,10 @@ impl BlockTestCore {
 
   pub fn create_text(&mut self, delta: String) -> String {
     let external_id = generate_id();
-    self.document.apply_text_delta(&external_id, delta);
+    self
+      .document
+      .apply_text_delta(&external_id, &delta)
+      .unwrap_or_else(|e| panic!("apply text delta error: {:?}", e));
     external_id
   }
 
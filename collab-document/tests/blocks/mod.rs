@@ -1,3 +1,4 @@
+mod block_observe_test;
 mod block_test;
 mod block_test_core;
 mod text_test;
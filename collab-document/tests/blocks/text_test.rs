@@ -3,6 +3,7 @@ use collab::preclude::{Attrs, Delta, YrsValue};
 use collab_document::blocks::{
   deserialize_text_delta, BlockAction, BlockActionPayload, BlockActionType, TextDelta,
 };
+use collab_document::error::DocumentError;
 
 use crate::util::try_decode_from_encode_collab;
 use serde_json::json;
@@ -38,7 +39,7 @@ fn apply_empty_delta_test() {
   let text_id = test.create_text(origin_delta);
   let origin_delta = test.get_text_delta_with_text_id(&text_id);
   let delta = "".to_string();
-  test.document.apply_text_delta(&text_id, delta);
+  test.document.apply_text_delta(&text_id, &delta).unwrap();
   let delta = test.get_text_delta_with_text_id(&text_id);
   assert_eq!(
     deserialize_text_delta(&delta).unwrap(),
@@ -68,7 +69,10 @@ fn apply_retain_delta_test() {
 
   // retain text
   let retain_delta = json!([{ "retain": length }]).to_string();
-  test.document.apply_text_delta(&text_id, retain_delta);
+  test
+    .document
+    .apply_text_delta(&text_id, &retain_delta)
+    .unwrap();
   let delta = test.get_text_delta_with_text_id(&text_id);
   assert_eq!(
     deserialize_text_delta(&delta).unwrap(),
@@ -80,7 +84,10 @@ fn apply_retain_delta_test() {
     {"retain": length, "attributes": { "bold": true, "italic": true }}
   ])
   .to_string();
-  test.document.apply_text_delta(&text_id, format_delta);
+  test
+    .document
+    .apply_text_delta(&text_id, &format_delta)
+    .unwrap();
   let delta = test.get_text_delta_with_text_id(&text_id);
   let expect = json!(
     [{"insert": "Hello World", "attributes": { "bold": true, "italic": true }}]
@@ -96,7 +103,10 @@ fn apply_retain_delta_test() {
     {"retain": length, "attributes": { "bold": null, "italic": null }}
   ])
   .to_string();
-  test.document.apply_text_delta(&text_id, clear_format_delta);
+  test
+    .document
+    .apply_text_delta(&text_id, &clear_format_delta)
+    .unwrap();
   let delta = test.get_text_delta_with_text_id(&text_id);
   let expect = json!(
     [{"insert": "Hello World"}]
@@ -118,7 +128,10 @@ fn apply_delete_delta_test() {
     {"delete": 5},
   ])
   .to_string();
-  test.document.apply_text_delta(&text_id, delete_delta);
+  test
+    .document
+    .apply_text_delta(&text_id, &delete_delta)
+    .unwrap();
   let delta = test.get_text_delta_with_text_id(&text_id);
   let expect = json!([{"insert": "Hello ", "attributes": { "bold": true }}]).to_string();
 
@@ -138,7 +151,7 @@ fn apply_mark_delta_test() {
     {"insert": "*"},
   ])
   .to_string();
-  test.document.apply_text_delta(&text_id, delta);
+  test.document.apply_text_delta(&text_id, &delta).unwrap();
 
   let delta = json!([
     {"retain": 3},
@@ -146,7 +159,7 @@ fn apply_mark_delta_test() {
     {"insert": "4", "attributes": { "bold": true }},
   ])
   .to_string();
-  test.document.apply_text_delta(&text_id, delta);
+  test.document.apply_text_delta(&text_id, &delta).unwrap();
 
   let delta = test.get_text_delta_with_text_id(&text_id);
   let expect = json!([{
@@ -180,7 +193,7 @@ fn apply_chinese_ime_delta_test() {
     json!([{"insert": "中文"}, {"delete": 9}]).to_string(),
   ];
   for delta in deltas {
-    test.document.apply_text_delta(&text_id, delta);
+    test.document.apply_text_delta(&text_id, &delta).unwrap();
   }
   let delta = test.get_text_delta_with_text_id(&text_id);
   let expect = json!([{"insert": "中文"}]).to_string();
@@ -202,7 +215,10 @@ fn apply_delete_chinese_delta_test() {
     {"delete": 1},
   ])
   .to_string();
-  test.document.apply_text_delta(&text_id, delete_delta);
+  test
+    .document
+    .apply_text_delta(&text_id, &delete_delta)
+    .unwrap();
   let delta = test.get_text_delta_with_text_id(&text_id);
   let expect = json!([{"insert": "Hello World ", "attributes": { "bold": true }}]).to_string();
   assert_eq!(
@@ -237,7 +253,10 @@ fn apply_insert_delta_test() {
     "insert": " ",
   }])
   .to_string();
-  test.document.apply_text_delta(&text_id, insert_delta);
+  test
+    .document
+    .apply_text_delta(&text_id, &insert_delta)
+    .unwrap();
   let delta = test.get_text_delta_with_text_id(&text_id);
   let expect = json!([
     { "insert": "A s soon as you type " },
@@ -275,7 +294,7 @@ fn subscribe_apply_delta_test() {
     "insert": "World ",
   }])
   .to_string();
-  test.document.apply_text_delta(&text_id, delta);
+  test.document.apply_text_delta(&text_id, &delta).unwrap();
   try_decode_from_encode_collab(&test.document);
 }
 
@@ -445,3 +464,57 @@ fn apply_text_actions_without_params_test() {
   assert_eq!(document_data, test.get_document_data());
   try_decode_from_encode_collab(&test.document);
 }
+
+#[test]
+fn apply_invalid_text_delta_test() {
+  let mut test = BlockTestCore::new();
+  let origin_delta = json!([{"insert": "Hello World"}]).to_string();
+  let text_id = test.create_text(origin_delta.clone());
+
+  let invalid_deltas = [
+    // malformed json
+    r#"[{"insert": "Hello"}"#,
+    // not an array of operations
+    r#"{"insert": "Hello"}"#,
+    // unknown operation
+    r#"[{"replace": "Hello"}]"#,
+    // negative length
+    r#"[{"retain": -1}]"#,
+    r#"[{"retain": 6}, {"delete": -5}]"#,
+    // the insert must be a string
+    r#"[{"insert": 1}]"#,
+    // one operation per object
+    r#"[{"insert": "Hello", "retain": 1}]"#,
+    // the valid operations before the invalid one are not applied either
+    r#"[{"retain": 5}, {"insert": "!"}, {"format": 1}]"#,
+  ];
+  for delta in invalid_deltas {
+    let result = test.document.apply_text_delta(&text_id, delta);
+    assert!(
+      matches!(result, Err(DocumentError::InvalidTextDelta(_))),
+      "{} should be rejected",
+      delta
+    );
+    assert_eq!(
+      deserialize_text_delta(&test.get_text_delta_with_text_id(&text_id)).unwrap(),
+      deserialize_text_delta(&origin_delta).unwrap()
+    );
+  }
+}
+
+#[test]
+fn apply_invalid_text_delta_action_test() {
+  let mut test = BlockTestCore::new();
+  let text_id = test.create_text(json!([{"insert": "Hello"}]).to_string());
+  let result = test.document.apply_action(vec![BlockAction {
+    action: BlockActionType::ApplyTextDelta,
+    payload: BlockActionPayload {
+      block: None,
+      prev_id: None,
+      parent_id: None,
+      delta: Some(r#"[{"retain": -1}]"#.to_string()),
+      text_id: Some(text_id),
+    },
+  }]);
+  assert!(matches!(result, Err(DocumentError::InvalidTextDelta(_))));
+}
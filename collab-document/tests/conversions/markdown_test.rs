@@ -0,0 +1,110 @@
+use collab_document::blocks::DocumentData;
+use serde_json::{json, Value};
+
+use crate::util::markdown_to_document;
+
+/// Returns the blocks as a tree of their types, data and deltas, so the documents can be compared
+/// regardless of the generated block ids.
+fn document_tree(data: &DocumentData) -> Value {
+  fn block_tree(data: &DocumentData, block_id: &str) -> Value {
+    let block = data.blocks.get(block_id).unwrap();
+    let delta = block
+      .external_id
+      .as_ref()
+      .and_then(|text_id| data.meta.text_map.as_ref()?.get(text_id))
+      .map(|delta| serde_json::from_str::<Vec<Value>>(delta).unwrap())
+      .unwrap_or_default()
+      .into_iter()
+      .filter(|op| op["insert"].as_str() != Some(""))
+      .collect::<Vec<_>>();
+    let children: Vec<Value> = data
+      .meta
+      .children_map
+      .get(&block.children)
+      .map(|ids| ids.iter().map(|id| block_tree(data, id)).collect())
+      .unwrap_or_default();
+    json!({
+      "type": block.ty,
+      "data": block.data,
+      "delta": delta,
+      "children": children,
+    })
+  }
+  block_tree(data, &data.page_id)
+}
+
+fn assert_round_trip(markdown: &str) {
+  let document = markdown_to_document(markdown);
+  let exported = document.to_markdown().unwrap();
+  let reimported = markdown_to_document(&exported);
+  assert_eq!(
+    document_tree(&document.get_document_data().unwrap()),
+    document_tree(&reimported.get_document_data().unwrap()),
+    "exported markdown:\n{}",
+    exported
+  );
+}
+
+#[test]
+fn markdown_round_trip_test() {
+  let markdowns = [
+    "# Heading 1\n\n## Heading 2\n\n###### Heading 6",
+    "Hello **bold** *italic* ~~strike~~ `code` world",
+    "***bold italic*** and **bold *nested italic***",
+    "[AppFlowy](https://appflowy.io) and [**bold link**](https://appflowy.io/docs)",
+    "The formula $E = mc^2$ is inline",
+    "Escaped \\*stars\\*, \\_underscores\\_ and \\[brackets\\]",
+    "1. one\n2. two\n3. three",
+    "3. three\n4. four",
+    "- a\n  - b\n    - c\n- d",
+    "- [ ] todo\n- [x] done",
+    "> quote line\n>\n> - list in quote",
+    "```rust\nfn main() {\n    println!(\"Hello, world!\");\n}\n```",
+    "Before\n\n---\n\nAfter",
+    "![](https://appflowy.io/logo.png)",
+    "$$\nE = mc^2\n$$",
+    "| Left | Center | Right |\n| :--- | :---: | ---: |\n| a | b | c |\n| 1 | 2 | 3 |",
+  ];
+  for markdown in markdowns {
+    assert_round_trip(markdown);
+  }
+}
+
+#[test]
+fn nested_bulleted_list_to_markdown_test() {
+  let document = markdown_to_document("- a\n  - b\n    - c\n- d");
+  assert_eq!(document.to_markdown().unwrap(), "- a\n  - b\n    - c\n- d");
+}
+
+#[test]
+fn nested_numbered_list_to_markdown_test() {
+  let document = markdown_to_document("1. a\n   1. b\n2. c");
+  assert_eq!(document.to_markdown().unwrap(), "1. a\n   1. b\n2. c");
+}
+
+#[test]
+fn table_to_markdown_test() {
+  let document = markdown_to_document("| a \\| b | c |\n| --- | --- |\n| 1 | 2 |");
+  assert_eq!(
+    document.to_markdown().unwrap(),
+    "| a \\| b | c |\n| --- | --- |\n| 1 | 2 |"
+  );
+}
+
+#[test]
+fn escape_markdown_syntax_test() {
+  let document = markdown_to_document("\\# not a heading\n\n1\\. not a list");
+  assert_eq!(
+    document.to_markdown().unwrap(),
+    "\\# not a heading\n\n1\\. not a list"
+  );
+}
+
+#[test]
+fn link_preview_to_markdown_test() {
+  let document = markdown_to_document("[link]: https://appflowy.io");
+  assert_eq!(
+    document.to_markdown().unwrap(),
+    "[https://appflowy.io](https://appflowy.io)"
+  );
+}
@@ -1 +1,2 @@
+mod markdown_test;
 mod plain_text_test;
@@ -1,5 +1,7 @@
+use collab_document::importer::md_importer::MDImporter;
 use collab_document::{blocks::Block, document::Document};
 use nanoid::nanoid;
+use serde_json::json;
 
 use crate::util::DocumentTest;
 
@@ -31,6 +33,71 @@ fn plain_text_1_test() {
   }
 }
 
+#[test]
+fn plain_text_with_table_test() {
+  let markdown = r#"Before the table
+
+| Header 1 | Header 2 | Header 3 |
+| --- | --- | --- |
+| Row 1, Col 0 | Row 1, Col 1 | Row 1, Col 2 |
+| Row 2, Col 0 | | Row 2, Col 2 |
+
+After the table"#;
+  let data = MDImporter::new(None)
+    .import("1", markdown.to_string())
+    .unwrap();
+  let document = Document::create("1", data).unwrap();
+
+  let plain_text = document.to_plain_text(true, false).unwrap();
+  let lines = plain_text.trim().split('\n').collect::<Vec<&str>>();
+  assert_eq!(
+    lines,
+    vec![
+      "Before the table",
+      "Header 1\tHeader 2\tHeader 3",
+      "Row 1, Col 0\tRow 1, Col 1\tRow 1, Col 2",
+      "Row 2, Col 0\t\tRow 2, Col 2",
+      "After the table",
+    ]
+  );
+}
+
+#[test]
+fn plain_text_with_table_cell_out_of_range_test() {
+  let markdown = r#"| Header 1 | Header 2 |
+| --- | --- |
+| Row 1, Col 0 | Row 1, Col 1 |
+
+After the table"#;
+  let data = MDImporter::new(None)
+    .import("1", markdown.to_string())
+    .unwrap();
+  let mut document = Document::create("1", data).unwrap();
+
+  // The position of the cell is far outside of the table
+  let cell = document
+    .get_document_data()
+    .unwrap()
+    .blocks
+    .into_values()
+    .find(|block| {
+      block.ty == "table/cell"
+        && block.data.get("rowPosition") == Some(&json!(1))
+        && block.data.get("colPosition") == Some(&json!(1))
+    })
+    .unwrap();
+  let mut data = cell.data.clone();
+  data.insert("rowPosition".to_string(), json!(u32::MAX));
+  document.update_block(&cell.id, data).unwrap();
+
+  let plain_text = document.to_plain_text(true, false).unwrap();
+  let lines = plain_text.trim().split('\n').collect::<Vec<&str>>();
+  assert_eq!(
+    lines,
+    vec!["Header 1\tHeader 2", "Row 1, Col 0\t", "After the table"]
+  );
+}
+
 fn insert_paragraphs(document: &mut Document, paragraphs: Vec<String>) {
   let page_id = document.get_page_id().unwrap();
   let mut prev_id = "".to_string();
@@ -50,6 +117,8 @@ fn insert_paragraphs(document: &mut Document, paragraphs: Vec<String>) {
     document.insert_block(block, Some(prev_id.clone())).unwrap();
     prev_id.clone_from(&block_id);
 
-    document.apply_text_delta(&text_id, format!(r#"[{{"insert": "{}"}}]"#, paragraph));
+    document
+      .apply_text_delta(&text_id, &format!(r#"[{{"insert": "{}"}}]"#, paragraph))
+      .unwrap();
   }
 }
@@ -0,0 +1,133 @@
+use collab_document::blocks::{CoverType, DocumentCover, DocumentData, DocumentMeta, PageMeta};
+use collab_document::document::Document;
+use collab_document::document_data::default_document_data;
+use collab_document::document_observer::DocumentChange;
+use collab_plugins::CollabKVDB;
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::util::{open_document_with_db, unzip_history_document_db, wait_for_specific_event};
+
+fn create_document() -> Document {
+  let data = default_document_data("1");
+  Document::create("1", data).unwrap()
+}
+
+#[test]
+fn set_cover_and_icon_test() {
+  let mut document = create_document();
+  assert_eq!(document.get_document_meta(), PageMeta::default());
+
+  document
+    .set_cover(
+      CoverType::UnsplashImage,
+      "https://unsplash.com/1.png".to_string(),
+    )
+    .unwrap();
+  document.set_icon(Some("🚀".to_string())).unwrap();
+  let expected = PageMeta {
+    cover: Some(DocumentCover {
+      ty: CoverType::UnsplashImage,
+      value: "https://unsplash.com/1.png".to_string(),
+    }),
+    icon: Some("🚀".to_string()),
+  };
+  assert_eq!(document.get_document_meta(), expected);
+  assert_eq!(
+    document.get_document_data().unwrap().meta.page_meta,
+    expected
+  );
+
+  document.remove_cover().unwrap();
+  document.set_icon(None).unwrap();
+  assert_eq!(document.get_document_meta(), PageMeta::default());
+}
+
+#[test]
+fn document_data_with_cover_and_icon_test() {
+  let mut data = default_document_data("1");
+  data.meta.page_meta = PageMeta {
+    cover: Some(DocumentCover {
+      ty: CoverType::Color,
+      value: "#FF0000".to_string(),
+    }),
+    icon: Some("📝".to_string()),
+  };
+
+  let json = serde_json::to_value(&data).unwrap();
+  assert_eq!(
+    json["meta"]["cover"],
+    json!({ "type": "color", "value": "#FF0000" })
+  );
+  assert_eq!(json["meta"]["icon"], json!("📝"));
+  let deserialized: DocumentData = serde_json::from_value(json).unwrap();
+  assert_eq!(deserialized.meta.page_meta, data.meta.page_meta);
+
+  let document = Document::create("1", data.clone()).unwrap();
+  assert_eq!(document.get_document_meta(), data.meta.page_meta);
+}
+
+#[test]
+fn document_meta_without_cover_and_icon_test() {
+  let meta: DocumentMeta = serde_json::from_value(json!({
+    "children_map": {},
+    "text_map": {},
+  }))
+  .unwrap();
+  assert_eq!(meta.page_meta, PageMeta::default());
+
+  let json = serde_json::to_value(&meta).unwrap();
+  assert!(json.get("cover").is_none());
+  assert!(json.get("icon").is_none());
+}
+
+#[test]
+fn open_020_history_document_meta_test() {
+  let workspace_id = Uuid::new_v4().to_string();
+  let (_cleaner, db_path) = unzip_history_document_db("020_document").unwrap();
+  let db = std::sync::Arc::new(CollabKVDB::open(db_path).unwrap());
+  let document = open_document_with_db(
+    221439819971039232,
+    &workspace_id,
+    "631584ec-af71-42c3-94f4-89dcfdafb988",
+    db,
+  );
+  assert_eq!(document.get_document_meta(), PageMeta::default());
+  assert_eq!(
+    document.get_document_data().unwrap().meta.page_meta,
+    PageMeta::default()
+  );
+}
+
+#[tokio::test]
+async fn observe_update_document_meta_test() {
+  let mut document = create_document();
+  let change_rx = document.subscribe_block_changes();
+  document.set_icon(Some("🚀".to_string())).unwrap();
+  wait_for_specific_event(change_rx, |change| match change {
+    DocumentChange::DidUpdateMeta { meta } => {
+      meta.icon.as_deref() == Some("🚀") && meta.cover.is_none()
+    },
+    _ => false,
+  })
+  .await
+  .unwrap();
+
+  let change_rx = document.subscribe_block_changes();
+  document
+    .set_cover(CoverType::Gradient, "appflowy".to_string())
+    .unwrap();
+  wait_for_specific_event(change_rx, |change| match change {
+    DocumentChange::DidUpdateMeta { meta } => {
+      meta.cover
+        == Some(DocumentCover {
+          ty: CoverType::Gradient,
+          value: "appflowy".to_string(),
+        })
+        && meta.icon.as_deref() == Some("🚀")
+    },
+    _ => false,
+  })
+  .await
+  .unwrap();
+}
@@ -95,10 +95,9 @@ fn document_index_data_from_document() {
   };
 
   document.insert_block(block, None).unwrap();
-  document.apply_text_delta(
-    &text_id,
-    r#"[{"insert": "Hello "}, {"insert": "world!"}]"#.to_owned(),
-  );
+  document
+    .apply_text_delta(&text_id, r#"[{"insert": "Hello "}, {"insert": "world!"}]"#)
+    .unwrap();
 
   let index_content = DocumentIndexContent::from(&document);
   assert_eq!(index_content.page_id, page_id);
@@ -1,5 +1,8 @@
 mod awareness_test;
 mod document_data_test;
+mod document_meta_test;
 mod document_test;
 mod redo_undo_test;
 mod restore_test;
+mod search_test;
+mod word_count_test;
@@ -2,6 +2,11 @@ use std::thread::sleep;
 use std::time::Duration;
 
 use crate::util::{insert_block_for_page, open_document_with_db, DocumentTest};
+use collab::core::collab::DataSource;
+use collab::core::origin::{CollabClient, CollabOrigin};
+use collab::preclude::updates::decoder::Decode;
+use collab::preclude::{ReadTxn, Update};
+use collab_document::document::Document;
 use nanoid::nanoid;
 use serde_json::to_value;
 
@@ -195,3 +200,62 @@ fn undo_redo_after_reopen_document() {
   let block = document.get_block(&block_id).unwrap();
   assert_eq!(block.data, data);
 }
+
+#[test]
+fn enable_undo_with_local_origin_test() {
+  let doc_id = "1";
+  let test = DocumentTest::new(1, doc_id);
+  let mut document = test.document;
+  let local_origin = document.origin().clone();
+  document.enable_undo(local_origin, Duration::ZERO);
+  assert!(!document.can_undo());
+
+  let first_id = nanoid!(10);
+  let second_id = nanoid!(10);
+  insert_block_for_page(&mut document, first_id.clone());
+  insert_block_for_page(&mut document, second_id.clone());
+  assert!(page_children(&document).contains(&first_id));
+  assert!(page_children(&document).contains(&second_id));
+
+  assert!(document.undo());
+  assert!(page_children(&document).contains(&first_id));
+  assert!(!page_children(&document).contains(&second_id));
+  assert!(document.undo());
+  assert!(!page_children(&document).contains(&first_id));
+  assert!(!document.can_undo());
+
+  assert!(document.can_redo());
+  assert!(document.redo());
+  assert!(page_children(&document).contains(&first_id));
+
+  // the remote collaborator inserts another paragraph
+  let mut remote = Document::open_with_options(
+    CollabOrigin::Client(CollabClient::new(2, "2")),
+    DataSource::from(document.encode_collab().unwrap()),
+    doc_id,
+    vec![],
+  )
+  .unwrap();
+  let remote_id = nanoid!(10);
+  insert_block_for_page(&mut remote, remote_id.clone());
+  let state_vector = document.transact().state_vector();
+  let update = remote.transact().encode_state_as_update_v1(&state_vector);
+  let remote_origin = remote.origin().clone();
+  document
+    .apply_update_with_origin(Update::decode_v1(&update).unwrap(), remote_origin)
+    .unwrap();
+  assert!(page_children(&document).contains(&first_id));
+  assert!(page_children(&document).contains(&remote_id));
+
+  // undo only reverts the local change
+  assert!(document.undo());
+  assert!(!page_children(&document).contains(&first_id));
+  assert!(page_children(&document).contains(&remote_id));
+  assert!(!document.can_undo());
+  assert!(!document.undo());
+  assert!(page_children(&document).contains(&remote_id));
+}
+
+fn page_children(document: &Document) -> Vec<String> {
+  document.get_block_children_ids(&document.get_page_id().unwrap())
+}
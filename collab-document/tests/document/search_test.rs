@@ -0,0 +1,102 @@
+use collab_document::document::{Document, SearchMatch};
+
+use crate::util::markdown_to_document;
+
+fn paragraph_ids(document: &Document) -> Vec<String> {
+  let data = document.get_document_data().unwrap();
+  let page = &data.blocks[&data.page_id];
+  data.meta.children_map[&page.children].clone()
+}
+
+#[test]
+fn search_multiple_matches_in_one_block_test() {
+  let document = markdown_to_document("AppFlowy is open source. appflowy is fast.\n\nNo match");
+  let block_id = paragraph_ids(&document)[0].clone();
+
+  assert_eq!(
+    document.search("AppFlowy", false),
+    vec![
+      SearchMatch {
+        block_id: block_id.clone(),
+        start: 0,
+        end: 8,
+      },
+      SearchMatch {
+        block_id: block_id.clone(),
+        start: 25,
+        end: 33,
+      },
+    ]
+  );
+  assert_eq!(
+    document.search("AppFlowy", true),
+    vec![SearchMatch {
+      block_id,
+      start: 0,
+      end: 8,
+    }]
+  );
+  assert!(document.search("", false).is_empty());
+  assert!(document.search("missing", false).is_empty());
+}
+
+#[test]
+fn search_across_attributes_test() {
+  let document = markdown_to_document("Hel**lo Wo**rld");
+  let block_id = paragraph_ids(&document)[0].clone();
+
+  assert_eq!(
+    document.search("lo world", false),
+    vec![SearchMatch {
+      block_id,
+      start: 3,
+      end: 11,
+    }]
+  );
+}
+
+#[test]
+fn search_unicode_text_test() {
+  let document = markdown_to_document("First\n\n你好，世界 🌍 和 世界\n\nÀ la CAFÉ, café");
+  let ids = paragraph_ids(&document);
+
+  // the offsets are counted in characters instead of bytes
+  assert_eq!(
+    document.search("世界", true),
+    vec![
+      SearchMatch {
+        block_id: ids[1].clone(),
+        start: 3,
+        end: 5,
+      },
+      SearchMatch {
+        block_id: ids[1].clone(),
+        start: 10,
+        end: 12,
+      },
+    ]
+  );
+  assert_eq!(
+    document.search("🌍 和", true),
+    vec![SearchMatch {
+      block_id: ids[1].clone(),
+      start: 6,
+      end: 9,
+    }]
+  );
+  assert_eq!(
+    document.search("café", false),
+    vec![
+      SearchMatch {
+        block_id: ids[2].clone(),
+        start: 5,
+        end: 9,
+      },
+      SearchMatch {
+        block_id: ids[2].clone(),
+        start: 11,
+        end: 15,
+      },
+    ]
+  );
+}
@@ -0,0 +1,58 @@
+use collab_document::document::{Document, DocumentCounters};
+use collab_document::document_data::default_document_data;
+use collab_document::importer::md_importer::MDImporter;
+
+#[test]
+fn mixed_english_and_cjk_word_count_test() {
+  let markdown = "Hello world, 你好世界!\n\n日本語のテキスト and more";
+  let data = MDImporter::new(None)
+    .import("1", markdown.to_string())
+    .unwrap();
+  let document = Document::create("1", data).unwrap();
+
+  // each CJK character is counted as one word
+  assert_eq!(
+    document.word_count(),
+    DocumentCounters {
+      words: 16,
+      characters: 35,
+      characters_no_spaces: 31,
+      blocks: 2,
+    }
+  );
+
+  let data = document.get_document_data().unwrap();
+  let page = &data.blocks[&data.page_id];
+  let second_block_id = &data.meta.children_map[&page.children][1];
+  assert_eq!(
+    document.block_word_count(second_block_id),
+    Some(DocumentCounters {
+      words: 10,
+      characters: 17,
+      characters_no_spaces: 15,
+      blocks: 1,
+    })
+  );
+  assert_eq!(document.block_word_count("not_exist"), None);
+}
+
+#[test]
+fn deleted_block_is_not_counted_test() {
+  let data = MDImporter::new(None)
+    .import("1", "First block\n\nSecond block".to_string())
+    .unwrap();
+  let mut document = Document::create("1", data).unwrap();
+  let data = document.get_document_data().unwrap();
+  let page = &data.blocks[&data.page_id];
+  let first_block_id = &data.meta.children_map[&page.children][0];
+
+  document.delete_block(first_block_id).unwrap();
+  assert_eq!(document.word_count().words, 2);
+  assert_eq!(document.word_count().blocks, 1);
+}
+
+#[test]
+fn empty_document_word_count_test() {
+  let document = Document::create("1", default_document_data("1")).unwrap();
+  assert_eq!(document.word_count(), DocumentCounters::default());
+}
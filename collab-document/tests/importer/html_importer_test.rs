@@ -0,0 +1,291 @@
+use collab_document::document::{gen_document_id, Document};
+use serde_json::json;
+
+use crate::importer::util::{
+  get_block_by_type, get_children_blocks, get_delta_json, get_page_block, html_to_document_data,
+};
+
+#[test]
+fn test_import_html_document() {
+  let html = r#"<!DOCTYPE html>
+<html>
+<head><title>AppFlowy</title><style>p { color: red; }</style></head>
+<body>
+  <h1>Welcome to AppFlowy</h1>
+  <p>Here are the <b>basics</b></p>
+  <ul>
+    <li>Click anywhere</li>
+    <li>Start typing</li>
+  </ul>
+  <hr>
+</body>
+</html>"#;
+
+  let result = html_to_document_data(html);
+  let page = get_page_block(&result);
+  let children = get_children_blocks(&result, &page.id);
+  let types = children.iter().map(|b| b.ty.as_str()).collect::<Vec<_>>();
+  assert_eq!(
+    types,
+    vec![
+      "heading",
+      "paragraph",
+      "bulleted_list",
+      "bulleted_list",
+      "divider"
+    ]
+  );
+
+  let doc = Document::create(&gen_document_id(), result).unwrap();
+  let plain_txt = doc.to_plain_text(true, false).unwrap();
+  assert!(plain_txt.contains("Welcome to AppFlowy"));
+  assert!(plain_txt.contains("Here are the basics"));
+  assert!(!plain_txt.contains("color: red"));
+}
+
+#[test]
+fn test_inline_elements() {
+  let html = r#"<p>This is <strong>bold</strong>, <em>italic</em>, <s>delete</s>, <u>underline</u>,
+    <code>code</code> and <a href="https://example.com">a link</a>.</p>"#;
+
+  let result = html_to_document_data(html);
+
+  assert_eq!(result.blocks.len(), 2); // 1 page + 1 paragraph
+
+  let paragraph = get_block_by_type(&result, "paragraph");
+  let delta_json = get_delta_json(&result, &paragraph.id);
+
+  let expected_delta = json!([
+      {"insert": "This is "},
+      {"insert": "bold", "attributes": {"bold": true}},
+      {"insert": ", "},
+      {"insert": "italic", "attributes": {"italic": true}},
+      {"insert": ", "},
+      {"insert": "delete", "attributes": {"strikethrough": true}},
+      {"insert": ", "},
+      {"insert": "underline", "attributes": {"underline": true}},
+      {"insert": ", "},
+      {"insert": "code", "attributes": {"code": true}},
+      {"insert": " and "},
+      {"insert": "a link", "attributes": {"href": "https://example.com"}},
+      {"insert": "."}
+  ]);
+
+  assert_eq!(delta_json, expected_delta);
+}
+
+#[test]
+fn test_nested_inline_elements() {
+  let html = r#"<p>This is <b>bold with <span style="font-style: italic">nested italic</span> text</b>.</p>"#;
+
+  let result = html_to_document_data(html);
+  let paragraph = get_block_by_type(&result, "paragraph");
+  let delta_json = get_delta_json(&result, &paragraph.id);
+
+  let expected_delta = json!([
+      {"insert": "This is "},
+      {"insert": "bold with ", "attributes": {"bold": true}},
+      {"insert": "nested italic", "attributes": {"bold": true, "italic": true}},
+      {"insert": " text", "attributes": {"bold": true}},
+      {"insert": "."}
+  ]);
+
+  assert_eq!(delta_json, expected_delta);
+}
+
+#[test]
+fn test_headings() {
+  let html = "<h1>Heading 1</h1><h2>Heading 2</h2><h3>Heading 3</h3><h6>Heading 6</h6>";
+
+  let result = html_to_document_data(html);
+  let page = get_page_block(&result);
+  let headings = get_children_blocks(&result, &page.id);
+
+  assert_eq!(headings.len(), 4);
+  for (heading, level) in headings.iter().zip([1, 2, 3, 6]) {
+    assert_eq!(heading.ty, "heading");
+    assert_eq!(heading.data["level"], level);
+    let delta_json = get_delta_json(&result, &heading.id);
+    assert_eq!(
+      delta_json,
+      json!([{"insert": format!("Heading {}", level)}])
+    );
+  }
+}
+
+#[test]
+fn test_nested_list() {
+  let html = r#"<ol start="3">
+  <li><p>First item</p>
+    <ul>
+      <li>Nested item</li>
+    </ul>
+  </li>
+  <li>Second item</li>
+</ol>"#;
+
+  let result = html_to_document_data(html);
+  let page = get_page_block(&result);
+  let list = get_children_blocks(&result, &page.id);
+
+  assert_eq!(list.len(), 2);
+  for item in list.iter() {
+    assert_eq!(item.ty, "numbered_list");
+    assert_eq!(item.data["number"], 3);
+  }
+  assert_eq!(
+    get_delta_json(&result, &list[0].id),
+    json!([{"insert": "First item"}])
+  );
+
+  let nested = get_children_blocks(&result, &list[0].id);
+  assert_eq!(nested.len(), 1);
+  assert_eq!(nested[0].ty, "bulleted_list");
+  assert_eq!(
+    get_delta_json(&result, &nested[0].id),
+    json!([{"insert": "Nested item"}])
+  );
+}
+
+#[test]
+fn test_checkbox() {
+  let html = r#"<ul>
+  <li><input type="checkbox" checked> Done</li>
+  <li><input type="checkbox"> Todo</li>
+</ul>"#;
+
+  let result = html_to_document_data(html);
+  let page = get_page_block(&result);
+  let list = get_children_blocks(&result, &page.id);
+
+  assert_eq!(list.len(), 2);
+  for (item, (text, checked)) in list.iter().zip([("Done", true), ("Todo", false)]) {
+    assert_eq!(item.ty, "todo_list");
+    assert_eq!(item.data["checked"], checked);
+    assert_eq!(get_delta_json(&result, &item.id), json!([{"insert": text}]));
+  }
+}
+
+#[test]
+fn test_quote() {
+  let html = "<blockquote><p>Quoted text</p><p>Second paragraph</p></blockquote>";
+
+  let result = html_to_document_data(html);
+  let quote = get_block_by_type(&result, "quote");
+  assert_eq!(
+    get_delta_json(&result, &quote.id),
+    json!([{"insert": "Quoted text"}])
+  );
+
+  let children = get_children_blocks(&result, &quote.id);
+  assert_eq!(children.len(), 1);
+  assert_eq!(children[0].ty, "paragraph");
+}
+
+#[test]
+fn test_code_block() {
+  let html = r#"<pre><code class="language-rust">fn main() {
+    println!("Hello, world!");
+}
+</code></pre>"#;
+
+  let result = html_to_document_data(html);
+  let code = get_block_by_type(&result, "code");
+
+  assert_eq!(code.data["language"], "rust");
+  assert_eq!(
+    get_delta_json(&result, &code.id),
+    json!([
+      {"insert": "fn main() {\n    println!(\"Hello, world!\");\n}"}
+    ])
+  );
+}
+
+#[test]
+fn test_image() {
+  let html = r#"<p><img src="https://example.com/image.png" alt="image"></p>
+<img src="https://example.com/image2.png">"#;
+
+  let result = html_to_document_data(html);
+  let page = get_page_block(&result);
+  let images = get_children_blocks(&result, &page.id);
+
+  assert_eq!(images.len(), 2);
+  assert_eq!(images[0].ty, "image");
+  assert_eq!(images[0].data["url"], "https://example.com/image.png");
+  assert_eq!(images[1].ty, "image");
+  assert_eq!(images[1].data["url"], "https://example.com/image2.png");
+}
+
+#[test]
+fn test_table() {
+  let html = r#"<table>
+  <thead>
+    <tr><th>Header 1</th><th align="center">Header 2</th><th style="text-align: right">Header 3</th></tr>
+  </thead>
+  <tbody>
+    <tr><td>Row 1, Col 0</td><td>Row 1, Col 1</td><td>Row 1, Col 2</td></tr>
+    <tr><td>Row 2, Col 0</td><td>Row 2, Col 1</td><td>Row 2, Col 2</td></tr>
+  </tbody>
+</table>"#;
+
+  let result = html_to_document_data(html);
+  let table = get_block_by_type(&result, "table");
+
+  assert_eq!(table.data["rowsLen"], 3);
+  assert_eq!(table.data["colsLen"], 3);
+
+  let table_cells = get_children_blocks(&result, &table.id);
+  assert_eq!(table_cells.len(), 9);
+
+  for cell in table_cells.iter() {
+    assert_eq!(cell.ty, "table/cell");
+    let paragraph_block_id = get_children_blocks(&result, &cell.id)
+      .first()
+      .unwrap()
+      .id
+      .clone();
+    let delta_json = get_delta_json(&result, &paragraph_block_id);
+
+    let row_position = cell.data["rowPosition"].as_u64().unwrap();
+    let col_position = cell.data["colPosition"].as_u64().unwrap();
+
+    if row_position == 0 {
+      let expected_delta = json!([
+          {"insert": format!("Header {}", col_position + 1)}
+      ]);
+      assert_eq!(delta_json, expected_delta);
+      let expected_align = ["left", "center", "right"][col_position as usize];
+      assert_eq!(cell.data["align"], expected_align);
+    } else {
+      let expected_delta = json!([
+          {"insert": format!("Row {}, Col {}", row_position, col_position)}
+      ]);
+      assert_eq!(delta_json, expected_delta);
+    }
+  }
+}
+
+#[test]
+fn test_unknown_tag() {
+  let html = "<div>Loose <i>text</i></div><custom-tag>Custom <b>content</b></custom-tag>";
+
+  let result = html_to_document_data(html);
+  let page = get_page_block(&result);
+  let paragraphs = get_children_blocks(&result, &page.id);
+
+  assert_eq!(paragraphs.len(), 2);
+  assert_eq!(paragraphs[0].ty, "paragraph");
+  assert_eq!(
+    get_delta_json(&result, &paragraphs[0].id),
+    json!([
+      {"insert": "Loose "},
+      {"insert": "text", "attributes": {"italic": true}}
+    ])
+  );
+  assert_eq!(paragraphs[1].ty, "paragraph");
+  assert_eq!(
+    get_delta_json(&result, &paragraphs[1].id),
+    json!([{"insert": "Custom content"}])
+  );
+}
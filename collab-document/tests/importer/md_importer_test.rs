@@ -532,3 +532,197 @@ fn test_aside() {
   ]);
   assert_eq!(delta_json, expected_delta);
 }
+
+#[test]
+fn test_toggle_list() {
+  let markdown = r#"- Toggle item <!-- collapsed -->
+
+  Hidden content
+
+- Loose item
+
+  Following paragraph
+
+- Bulleted item
+  - Nested item"#;
+
+  let result = markdown_to_document_data(markdown);
+  let page = get_page_block(&result);
+  let list = get_children_blocks(&result, &page.id);
+
+  assert_eq!(list.len(), 3);
+
+  let toggle = &list[0];
+  assert_eq!(toggle.ty, "toggle_list");
+  assert_eq!(toggle.data["collapsed"], true);
+  assert_eq!(
+    get_delta_json(&result, &toggle.id),
+    json!([{"insert": "Toggle item"}])
+  );
+  let children = get_children_blocks(&result, &toggle.id);
+  assert_eq!(children.len(), 1);
+  assert_eq!(children[0].ty, "paragraph");
+  assert_eq!(
+    get_delta_json(&result, &children[0].id),
+    json!([{"insert": "Hidden content"}])
+  );
+
+  // The list items without the marker aren't toggles, even if they have the indented content
+  assert_eq!(list[1].ty, "bulleted_list");
+  assert_eq!(
+    get_delta_json(&result, &list[1].id),
+    json!([{"insert": "Loose item"}])
+  );
+  let children = get_children_blocks(&result, &list[1].id);
+  assert_eq!(children.len(), 1);
+  assert_eq!(children[0].ty, "paragraph");
+  assert_eq!(list[2].ty, "bulleted_list");
+}
+
+#[test]
+fn test_details_toggle() {
+  let markdown = r#"<details>
+<summary>**Toggle** title</summary>
+
+First paragraph
+
+- Nested item
+
+</details>
+
+<details open><summary>Open toggle</summary>Inline content</details>
+
+After"#;
+
+  let result = markdown_to_document_data(markdown);
+  let page = get_page_block(&result);
+  let children = get_children_blocks(&result, &page.id);
+
+  assert_eq!(children.len(), 3);
+
+  let toggle = &children[0];
+  assert_eq!(toggle.ty, "toggle_list");
+  assert_eq!(toggle.data["collapsed"], true);
+  assert_eq!(
+    get_delta_json(&result, &toggle.id),
+    json!([
+      {"insert": "Toggle", "attributes": {"bold": true}},
+      {"insert": " title"}
+    ])
+  );
+  let toggle_children = get_children_blocks(&result, &toggle.id);
+  assert_eq!(toggle_children.len(), 2);
+  assert_eq!(toggle_children[0].ty, "paragraph");
+  assert_eq!(
+    get_delta_json(&result, &toggle_children[0].id),
+    json!([{"insert": "First paragraph"}])
+  );
+  assert_eq!(toggle_children[1].ty, "bulleted_list");
+
+  let open_toggle = &children[1];
+  assert_eq!(open_toggle.ty, "toggle_list");
+  assert_eq!(open_toggle.data["collapsed"], false);
+  assert_eq!(
+    get_delta_json(&result, &open_toggle.id),
+    json!([{"insert": "Open toggle"}])
+  );
+  let open_toggle_children = get_children_blocks(&result, &open_toggle.id);
+  assert_eq!(open_toggle_children.len(), 1);
+  assert_eq!(
+    get_delta_json(&result, &open_toggle_children[0].id),
+    json!([{"insert": "Inline content"}])
+  );
+
+  assert_eq!(children[2].ty, "paragraph");
+}
+
+#[test]
+fn test_collapsed_heading() {
+  let markdown = r#"## Collapsed heading <!-- collapsed -->
+
+    Hidden paragraph
+
+    - Hidden item
+
+## Normal heading"#;
+
+  let result = markdown_to_document_data(markdown);
+  let page = get_page_block(&result);
+  let children = get_children_blocks(&result, &page.id);
+
+  assert_eq!(children.len(), 2);
+
+  let toggle = &children[0];
+  assert_eq!(toggle.ty, "toggle_list");
+  assert_eq!(toggle.data["level"], 2);
+  assert_eq!(toggle.data["collapsed"], true);
+  assert_eq!(
+    get_delta_json(&result, &toggle.id),
+    json!([{"insert": "Collapsed heading"}])
+  );
+
+  let toggle_children = get_children_blocks(&result, &toggle.id);
+  assert_eq!(toggle_children.len(), 2);
+  assert_eq!(toggle_children[0].ty, "paragraph");
+  assert_eq!(
+    get_delta_json(&result, &toggle_children[0].id),
+    json!([{"insert": "Hidden paragraph"}])
+  );
+  assert_eq!(toggle_children[1].ty, "bulleted_list");
+
+  assert_eq!(children[1].ty, "heading");
+}
+
+#[test]
+fn test_emoji_callout() {
+  let markdown = "> 💡 **Tip:** Use the slash menu";
+
+  let result = markdown_to_document_data(markdown);
+  let callout = get_block_by_type(&result, "callout");
+
+  assert_eq!(callout.data["icon"], "💡");
+  let delta_json = get_delta_json(&result, &callout.id);
+  let expected_delta = json!([
+      {"insert": "Tip:", "attributes": {"bold": true}},
+      {"insert": " Use the slash menu"}
+  ]);
+  assert_eq!(delta_json, expected_delta);
+}
+
+#[test]
+fn test_github_admonition_callout() {
+  let markdown = r#"> [!WARNING]
+> Back up the workspace
+>
+> - before the upgrade"#;
+
+  let result = markdown_to_document_data(markdown);
+  let callout = get_block_by_type(&result, "callout");
+
+  assert_eq!(callout.data["icon"], "⚠️");
+  let delta_json = get_delta_json(&result, &callout.id);
+  assert_eq!(delta_json, json!([{"insert": "Back up the workspace"}]));
+
+  let children = get_children_blocks(&result, &callout.id);
+  assert_eq!(children.len(), 1);
+  assert_eq!(children[0].ty, "bulleted_list");
+}
+
+#[test]
+fn test_plain_quote_is_not_callout() {
+  let markdown = "> Plain quote with [a link](https://example.com)";
+
+  let result = markdown_to_document_data(markdown);
+  let page = get_page_block(&result);
+  let children = get_children_blocks(&result, &page.id);
+
+  assert_eq!(children.len(), 1);
+  assert_eq!(children[0].ty, "quote");
+  assert!(children[0].data.get("icon").is_none());
+  let delta_json = get_delta_json(&result, &children[0].id);
+  let expected_delta = json!([
+      {"insert": "Plain quote with "},
+      {"insert": "a link", "attributes": {"href": "https://example.com"}}
+  ]);
+  assert_eq!(delta_json, expected_delta);
+}
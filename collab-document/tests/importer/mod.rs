@@ -1,3 +1,4 @@
+mod html_importer_test;
 mod md_importer_customer_test;
 mod md_importer_test;
 mod util;
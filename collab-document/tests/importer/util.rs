@@ -1,4 +1,5 @@
 use collab_document::blocks::{Block, DocumentData};
+use collab_document::importer::html_importer::HTMLImporter;
 use collab_document::importer::md_importer::MDImporter;
 use serde_json::Value;
 
@@ -8,6 +9,12 @@ pub(crate) fn markdown_to_document_data<T: ToString>(md: T) -> DocumentData {
   result.unwrap()
 }
 
+pub(crate) fn html_to_document_data<T: ToString>(html: T) -> DocumentData {
+  let importer = HTMLImporter::new();
+  let result = importer.import("test_document", html.to_string());
+  result.unwrap()
+}
+
 pub(crate) fn parse_json(s: &str) -> Value {
   serde_json::from_str(s).unwrap()
 }
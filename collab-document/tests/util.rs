@@ -6,11 +6,14 @@ use std::io::copy;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Once};
+use std::time::Duration;
 
 use collab::core::origin::CollabOrigin;
-use collab::preclude::{Collab, CollabBuilder};
+use collab::preclude::updates::decoder::Decode;
+use collab::preclude::{Collab, CollabBuilder, ReadTxn, Update};
 use collab_document::blocks::{Block, BlockAction, DocumentData, DocumentMeta};
 use collab_document::document::Document;
+use collab_document::importer::md_importer::MDImporter;
 use collab_entity::CollabType;
 use collab_plugins::local_storage::rocksdb::rocksdb_plugin::RocksdbDiskPlugin;
 use collab_plugins::local_storage::rocksdb::util::KVDBCollabPersistenceImpl;
@@ -18,6 +21,7 @@ use collab_plugins::CollabKVDB;
 use nanoid::nanoid;
 use serde_json::json;
 use tempfile::TempDir;
+use tokio::time::timeout;
 use tracing_subscriber::{fmt::Subscriber, util::SubscriberInitExt, EnvFilter};
 use uuid::Uuid;
 use zip::ZipArchive;
@@ -97,6 +101,7 @@ impl DocumentTest {
     let meta = DocumentMeta {
       children_map,
       text_map: Some(text_map),
+      page_meta: Default::default(),
     };
     let document_data = DocumentData {
       page_id,
@@ -209,6 +214,13 @@ pub fn insert_block_for_page(document: &mut Document, block_id: String) -> Block
   document.insert_block(block, None).unwrap()
 }
 
+pub fn markdown_to_document(markdown: &str) -> Document {
+  let data = MDImporter::new(None)
+    .import("test_document", markdown.to_string())
+    .unwrap();
+  Document::create("test_document", data).unwrap()
+}
+
 pub struct Cleaner(PathBuf);
 
 impl Cleaner {
@@ -267,3 +279,44 @@ pub fn try_decode_from_encode_collab(document: &Document) {
   let data = document.encode_collab().unwrap();
   let _ = Collab::new_with_source(CollabOrigin::Empty, "1", data.into(), vec![], false).unwrap();
 }
+
+/// Apply the updates of the `remote` document that the `document` hasn't seen yet.
+pub fn sync_from_remote(document: &mut Document, remote: &Document) {
+  let state_vector = document.transact().state_vector();
+  let update = remote.transact().encode_state_as_update_v1(&state_vector);
+  document
+    .apply_update(Update::decode_v1(&update).unwrap())
+    .unwrap();
+}
+
+pub async fn wait_for_specific_event<F, T>(
+  mut change_rx: tokio::sync::broadcast::Receiver<T>,
+  mut condition: F,
+) -> Result<(), String>
+where
+  F: FnMut(&T) -> bool,
+  T: Clone,
+{
+  loop {
+    let result = timeout(Duration::from_secs(5), change_rx.recv()).await;
+
+    match result {
+      Ok(Ok(event)) if condition(&event) => {
+        // If the event matches the condition
+        return Ok(());
+      },
+      Ok(Ok(_)) => {
+        // If it's any other event, continue the loop
+        continue;
+      },
+      Ok(Err(e)) => {
+        // Channel error
+        return Err(format!("Channel error: {}", e));
+      },
+      Err(e) => {
+        // Timeout occurred
+        return Err(format!("Timeout occurred: {}", e));
+      },
+    }
+  }
+}
@@ -1,11 +1,18 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
-use crate::{SectionsByUid, View, Workspace};
+use crate::{SectionsByUid, UserId, View, Workspace};
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub struct FolderData {
   pub workspace: Workspace,
+  /// The current view of the user who exported the folder data. It's kept for the clients that
+  /// don't read [FolderData::current_view_by_uid].
   pub current_view: String,
+  /// The current view of each user
+  #[serde(default)]
+  pub current_view_by_uid: HashMap<UserId, String>,
   pub views: Vec<View>,
   #[serde(default)]
   pub favorites: SectionsByUid,
@@ -15,6 +22,9 @@ pub struct FolderData {
   pub trash: SectionsByUid,
   #[serde(default)]
   pub private: SectionsByUid,
+  /// Only contains the access times of the user who exported the folder data
+  #[serde(default)]
+  pub view_access_times: SectionsByUid,
 }
 
 impl FolderData {
@@ -22,15 +32,29 @@ impl FolderData {
     Self {
       workspace,
       current_view: "".to_string(),
+      current_view_by_uid: HashMap::new(),
       views: vec![],
       favorites: SectionsByUid::new(),
       recent: SectionsByUid::new(),
       trash: SectionsByUid::new(),
       private: SectionsByUid::new(),
+      view_access_times: SectionsByUid::new(),
     }
   }
 }
 
+/// The options of [crate::Folder::get_folder_data_filtered]
+#[derive(Clone, Debug, Default)]
+pub struct FolderDataFilter {
+  /// Leaves out the views that are effectively trashed for the current user
+  pub exclude_trash: bool,
+  /// Only keeps the sections of the current user, e.g. the favorites of the other users are left
+  /// out
+  pub only_current_user_sections: bool,
+  /// Only keeps the view and its descendants. The view becomes the only child of the workspace.
+  pub root_view_id: Option<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct TrashInfo {
   pub id: String,
@@ -1,5 +1,5 @@
 use std::borrow::{Borrow, BorrowMut};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
 
@@ -11,16 +11,21 @@ use collab::util::any_to_json_value;
 use collab_entity::define::{FOLDER, FOLDER_META, FOLDER_WORKSPACE_ID};
 use collab_entity::CollabType;
 use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
 use tracing::error;
 
 use crate::error::FolderError;
-use crate::folder_observe::ViewChangeSender;
-use crate::hierarchy_builder::{FlattedViews, ParentChildViews};
+use crate::folder_observe::{
+  subscribe_workspace_change, ViewChangeSender, WorkspaceChangeReceiver, WorkspaceChangeSender,
+};
+use crate::hierarchy_builder::{FlattedViews, ParentChildViews, ViewExtraBuilder};
 use crate::section::{Section, SectionItem, SectionMap};
 use crate::view::view_from_map_ref;
 use crate::{
-  impl_section_op, subscribe_folder_change, FolderData, ParentChildRelations, SectionChangeSender,
-  TrashInfo, View, ViewUpdate, ViewsMap, Workspace,
+  impl_section_op, subscribe_folder_change, timestamp, FolderData, FolderDataFilter,
+  ParentChildRelations, RepeatedViewIdentifier, SectionChangeSender, SpaceInfo, SpacePermission,
+  TrashInfo, View, ViewDescription, ViewIdentifier, ViewLayout, ViewNameMatch, ViewUpdate,
+  ViewsMap, Workspace, SPACE_PERMISSION_KEY,
 };
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
@@ -54,10 +59,14 @@ impl AsRef<str> for UserId {
 const VIEWS: &str = "views";
 const PARENT_CHILD_VIEW_RELATION: &str = "relation";
 const CURRENT_VIEW: &str = "current_view";
+const CURRENT_VIEW_FOR_USER: &str = "current_view_for_user";
 
 pub(crate) const FAVORITES_V1: &str = "favorites";
 const SECTION: &str = "section";
 
+/// The number of recent views that are shown by default
+pub const DEFAULT_MAX_RECENT_VIEWS: usize = 50;
+
 #[derive(Clone)]
 pub struct FolderNotify {
   pub view_change_tx: ViewChangeSender,
@@ -171,6 +180,158 @@ impl Folder {
     self.body.get_folder_data(&txn, workspace_id)
   }
 
+  /// Same as [Self::get_folder_data], but the views that are effectively trashed for the
+  /// current user are left out, as well as their ids in the children of the other views and in
+  /// the sections.
+  pub fn get_folder_data_without_trashed_views(&self, workspace_id: &str) -> Option<FolderData> {
+    self.get_folder_data_filtered(
+      workspace_id,
+      FolderDataFilter {
+        exclude_trash: true,
+        ..Default::default()
+      },
+    )
+  }
+
+  /// Returns the folder data for exporting or sharing the workspace. The views are filtered by
+  /// the `filter`, and the children, the sections and the current view only refer to the views
+  /// that are kept. Returns `None` if the `root_view_id` of the filter doesn't exist.
+  pub fn get_folder_data_filtered(
+    &self,
+    workspace_id: &str,
+    filter: FolderDataFilter,
+  ) -> Option<FolderData> {
+    let txn = self.collab.transact();
+    let mut folder_data = self.body.get_folder_data(&txn, workspace_id)?;
+    if let Some(root_view_id) = filter.root_view_id {
+      let mut views = self.body.get_view_recursively_with_txn(&txn, &root_view_id);
+      let root_view = views.first_mut()?;
+      root_view.parent_view_id = workspace_id.to_string();
+      folder_data.workspace.child_views =
+        RepeatedViewIdentifier::new(vec![ViewIdentifier::new(root_view_id)]);
+      folder_data.views = views;
+    }
+
+    if filter.exclude_trash {
+      let trash_ids = self.body.get_my_trash_ids_with_txn(&txn);
+      folder_data
+        .views
+        .retain(|view| !self.body.is_view_in_or_under(&txn, &view.id, &trash_ids));
+    }
+
+    if filter.only_current_user_sections {
+      for sections in [
+        &mut folder_data.favorites,
+        &mut folder_data.recent,
+        &mut folder_data.trash,
+        &mut folder_data.private,
+        &mut folder_data.view_access_times,
+      ] {
+        sections.retain(|uid, _| uid == self.uid());
+      }
+      folder_data
+        .current_view_by_uid
+        .retain(|uid, _| uid == self.uid());
+    }
+
+    // Removes the references to the views that are left out
+    let view_ids = folder_data
+      .views
+      .iter()
+      .map(|view| view.id.clone())
+      .collect::<HashSet<_>>();
+    for view in folder_data.views.iter_mut() {
+      view
+        .children
+        .items
+        .retain(|child| view_ids.contains(&child.id));
+    }
+    folder_data
+      .workspace
+      .child_views
+      .items
+      .retain(|child| view_ids.contains(&child.id));
+    for sections in [
+      &mut folder_data.favorites,
+      &mut folder_data.recent,
+      &mut folder_data.trash,
+      &mut folder_data.private,
+      &mut folder_data.view_access_times,
+    ] {
+      for items in sections.values_mut() {
+        items.retain(|item| view_ids.contains(&item.id));
+      }
+    }
+    if !view_ids.contains(&folder_data.current_view) {
+      folder_data.current_view = "".to_string();
+    }
+    folder_data
+      .current_view_by_uid
+      .retain(|_, view_id| view_ids.contains(view_id));
+    Some(folder_data)
+  }
+
+  /// Same as [Self::get_folder_data], but the views returned by [Self::find_orphan_views] and
+  /// their descendants are included as the children of the workspace, so they appear in the
+  /// clients that build the sidebar from the folder data. The folder itself is not changed.
+  pub fn get_folder_data_with_orphan_views(&self, workspace_id: &str) -> Option<FolderData> {
+    let txn = self.collab.transact();
+    let mut folder_data = self.body.get_folder_data(&txn, workspace_id)?;
+    for orphan_view in self.body.find_orphan_views_with_txn(&txn) {
+      let mut views = self
+        .body
+        .get_view_recursively_with_txn(&txn, &orphan_view.id);
+      if let Some(view) = views.first_mut() {
+        view.parent_view_id = workspace_id.to_string();
+      }
+      folder_data
+        .workspace
+        .child_views
+        .items
+        .push(ViewIdentifier::new(orphan_view.id.clone()));
+      folder_data.views.extend(views);
+    }
+    Some(folder_data)
+  }
+
+  /// Returns the ids of the views whose parent doesn't exist, e.g. the parent was removed by a
+  /// partial sync. These views can't be reached from the workspace. See
+  /// [Self::repair_orphan_views].
+  pub fn find_orphan_views(&self) -> Vec<String> {
+    let txn = self.collab.transact();
+    self
+      .body
+      .find_orphan_views_with_txn(&txn)
+      .into_iter()
+      .map(|view| view.id.clone())
+      .collect()
+  }
+
+  /// Moves the views returned by [Self::find_orphan_views] to the end of the children of
+  /// `target_parent` in one transaction. Returns the ids of the repaired views, which is empty
+  /// if `target_parent` doesn't exist.
+  pub fn repair_orphan_views(&mut self, target_parent: &str) -> Vec<String> {
+    let mut txn = self.collab.transact_mut();
+    self.body.repair_orphan_views(&mut txn, target_parent)
+  }
+
+  /// Returns true if moving the view under `new_parent_id` makes the view an ancestor of itself,
+  /// i.e. `new_parent_id` is the view itself or one of its descendants.
+  pub fn would_create_cycle(&self, view_id: &str, new_parent_id: &str) -> bool {
+    let txn = self.collab.transact();
+    self
+      .body
+      .would_create_cycle_with_txn(&txn, view_id, new_parent_id)
+  }
+
+  /// Breaks the cycles in the parent-child relations of the views, which might be created by
+  /// concurrent moves. One view of each cycle is detached from its parent and moved to the end of
+  /// the children of the workspace, together with its subtree. Returns the ids of the moved views.
+  pub fn break_cycles(&mut self) -> Vec<String> {
+    let mut txn = self.collab.transact_mut();
+    self.body.break_cycles(&mut txn)
+  }
+
   /// Fetches the current workspace.
   ///
   /// This function fetches the ID of the current workspace from the meta object,
@@ -186,11 +347,98 @@ impl Folder {
     self.body.get_workspace_id(&txn)
   }
 
+  /// Returns a receiver of the changes of the workspace, e.g. the workspace is renamed by
+  /// [Folder::update_view] with the workspace id.
+  pub fn subscribe_workspace_change(&self) -> WorkspaceChangeReceiver {
+    self.body.workspace_change_tx.subscribe()
+  }
+
   pub fn get_all_views(&self) -> Vec<Arc<View>> {
     let txn = self.collab.transact();
     self.body.views.get_all_views(&txn)
   }
 
+  /// Returns at most `limit` views whose name matches the `query`, ignoring the case. The
+  /// workspace and the views that are effectively trashed for the current user are skipped.
+  pub fn search_views(
+    &self,
+    query: &str,
+    match_mode: ViewNameMatch,
+    limit: usize,
+  ) -> Vec<ViewDescription> {
+    let txn = self.collab.transact();
+    let excluded_view_ids = self
+      .body
+      .get_workspace_id_with_txn(&txn)
+      .into_iter()
+      .collect::<Vec<_>>();
+    let mut views = self.body.views.search_views_with_txn(
+      &txn,
+      query,
+      match_mode,
+      usize::MAX,
+      &excluded_view_ids,
+    );
+    let trash_ids = self.body.get_my_trash_ids_with_txn(&txn);
+    views.retain(|view| !self.body.is_view_in_or_under(&txn, &view.id, &trash_ids));
+    views.truncate(limit);
+    views
+  }
+
+  /// Returns true if the view or one of its ancestors is in the trash of the current user
+  pub fn is_view_effectively_trashed(&self, view_id: &str) -> bool {
+    let txn = self.collab.transact();
+    self
+      .body
+      .is_view_effectively_trashed_with_txn(&txn, view_id)
+  }
+
+  /// The favorites whose view is effectively trashed are hidden. The items are kept in the
+  /// section, so they show up again when the view is restored.
+  fn visible_section_items<T: ReadTxn>(
+    &self,
+    txn: &T,
+    section: Section,
+    items: Vec<SectionItem>,
+  ) -> Vec<SectionItem> {
+    match section {
+      Section::Favorite => self.body.exclude_trashed_section_items(txn, items),
+      _ => items,
+    }
+  }
+
+  /// Returns the ancestors of the view, ordered from its parent to the workspace. See
+  /// [FolderBody::get_view_ancestors_with_txn].
+  pub fn get_view_ancestors(&self, view_id: &str) -> Vec<Arc<View>> {
+    let txn = self.collab.transact();
+    self.body.get_view_ancestors_with_txn(&txn, view_id)
+  }
+
+  /// Returns true if the view or one of its ancestors is locked. Returns false if the view
+  /// doesn't exist.
+  pub fn is_view_locked(&self, view_id: &str) -> bool {
+    let txn = self.collab.transact();
+    match self.body.views.get_view_with_txn(&txn, view_id) {
+      None => false,
+      Some(view) => {
+        view.is_locked
+          || self
+            .body
+            .get_view_ancestors_with_txn(&txn, view_id)
+            .iter()
+            .any(|ancestor| ancestor.is_locked)
+      },
+    }
+  }
+
+  /// Returns true if `ancestor_id` is one of the ancestors of the view
+  pub fn is_ancestor_of(&self, ancestor_id: &str, view_id: &str) -> bool {
+    self
+      .get_view_ancestors(view_id)
+      .iter()
+      .any(|ancestor| ancestor.id == ancestor_id)
+  }
+
   pub fn get_views<T: AsRef<str>>(&self, view_ids: &[T]) -> Vec<Arc<View>> {
     let txn = self.collab.transact();
     self.body.views.get_views(&txn, view_ids)
@@ -223,6 +471,8 @@ impl Folder {
   /// * `new_parent_id` - A string slice that holds the id of the new parent view.
   /// * `prev_view_id` - An `Option<String>` that holds the id of the view after which the `view_id` should be positioned.
   ///
+  /// Returns `None` without moving the view if the move would make the view an ancestor of
+  /// itself, see [Self::would_create_cycle].
   pub fn move_nested_view(
     &mut self,
     view_id: &str,
@@ -235,11 +485,35 @@ impl Folder {
       .move_nested_view(&mut txn, view_id, new_parent_id, prev_view_id)
   }
 
+  /// Moves the views to `new_parent_id` in one transaction. The views are removed from their
+  /// current parents and inserted next to each other in the given order at `index` of the
+  /// children of the new parent, or appended if `index` is `None`. The `index` refers to the
+  /// children that are left after the views are removed, so the views that are already under
+  /// the new parent are just reordered.
+  ///
+  /// Returns the moved views. The views that don't exist are skipped. Nothing is moved if one of
+  /// the views would become an ancestor of itself, see [Self::would_create_cycle].
+  pub fn move_views(
+    &mut self,
+    view_ids: Vec<String>,
+    new_parent_id: &str,
+    index: Option<u32>,
+  ) -> Vec<Arc<View>> {
+    let mut txn = self.collab.transact_mut();
+    self
+      .body
+      .move_views(&mut txn, view_ids, new_parent_id, index)
+  }
+
+  /// Sets the current view of the current user. Each user of the workspace has its own current
+  /// view.
   pub fn set_current_view(&mut self, view_id: String) {
     let mut txn = self.collab.transact_mut();
     self.body.set_current_view(&mut txn, view_id);
   }
 
+  /// Returns the current view of the current user. The current view that was shared by all the
+  /// users before is returned if the user hasn't set one.
   pub fn get_current_view(&self) -> Option<String> {
     let txn = self.collab.transact();
     self.body.get_current_view(&txn)
@@ -270,6 +544,15 @@ impl Folder {
     remove_all_my_favorite_sections
   );
 
+  /// Moves the favorite `from_id` to the position of the favorite `to_id` in the favorites of
+  /// the current user. Nothing happens if either of them isn't a favorite.
+  pub fn move_favorite(&mut self, from_id: &str, to_id: &str) {
+    let mut txn = self.collab.transact_mut();
+    if let Some(op) = self.body.section.section_op(&txn, Section::Favorite) {
+      op.move_section_item_with_txn(&mut txn, from_id, to_id);
+    }
+  }
+
   // Recent
   impl_section_op!(
     Section::Recent,
@@ -281,6 +564,63 @@ impl Folder {
     remove_all_my_recent_sections
   );
 
+  /// Returns at most `limit` of the current user's recent views, the most recently visited
+  /// first. Adding a view that is already in the recent section moves it to the front.
+  /// [DEFAULT_MAX_RECENT_VIEWS] is used by the clients that don't configure the limit.
+  pub fn get_my_latest_recent_sections(&self, limit: usize) -> Vec<SectionItem> {
+    let mut items = self.get_my_recent_sections();
+    items.reverse();
+    items.truncate(limit);
+    items
+  }
+
+  /// Records that the current user opened the view now. The access times are stored per user
+  /// like the favorites, so they are synced across the devices of the user.
+  pub fn set_last_viewed(&mut self, view_id: &str) {
+    let mut txn = self.collab.transact_mut();
+    if let Some(op) = self.body.section.section_op(&txn, Section::ViewAccessTimes) {
+      op.delete_section_items_with_txn(&mut txn, vec![view_id]);
+      op.add_sections_item(&mut txn, vec![SectionItem::new(view_id.to_string())]);
+    }
+  }
+
+  /// Returns the time the current user last opened the view, or `None` if the user never
+  /// opened it.
+  pub fn get_last_viewed(&self, view_id: &str) -> Option<i64> {
+    let txn = self.collab.transact();
+    self
+      .body
+      .section
+      .section_op(&txn, Section::ViewAccessTimes)?
+      .get_all_section_item(&txn)
+      .into_iter()
+      .filter(|item| item.id == view_id)
+      .map(|item| item.timestamp)
+      .max()
+  }
+
+  /// Returns at most `limit` of the views the current user opened, the most recently opened
+  /// first.
+  pub fn get_recently_viewed(&self, limit: usize) -> Vec<SectionItem> {
+    let txn = self.collab.transact();
+    let mut items = self
+      .body
+      .section
+      .section_op(&txn, Section::ViewAccessTimes)
+      .map(|op| op.get_all_section_item(&txn))
+      .unwrap_or_default();
+    // The latest access is at the end of the section, it goes first if two views are opened
+    // within the same second
+    items.reverse();
+    items.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+
+    // The same view might be recorded by two devices at the same time
+    let mut view_ids = HashSet::new();
+    items.retain(|item| view_ids.insert(item.id.clone()));
+    items.truncate(limit);
+    items
+  }
+
   // Trash
   impl_section_op!(
     Section::Trash,
@@ -303,6 +643,117 @@ impl Folder {
     remove_all_my_private_sections
   );
 
+  /// Creates a space at the end of the children of the workspace. The space info is written to
+  /// the extra of the view with the keys defined in [crate::space_info].
+  pub fn create_space(
+    &mut self,
+    name: &str,
+    icon: Option<String>,
+    icon_color: Option<String>,
+    permission: SpacePermission,
+  ) -> Option<View> {
+    let workspace_id = self.get_workspace_id()?;
+    let extra = ViewExtraBuilder::new()
+      .with_space_info(SpaceInfo {
+        is_space: true,
+        space_permission: permission,
+        space_created_at: timestamp(),
+        space_icon: icon,
+        space_icon_color: icon_color,
+      })
+      .build();
+    let mut view = View::new(
+      uuid::Uuid::new_v4().to_string(),
+      workspace_id,
+      name.to_string(),
+      ViewLayout::Document,
+      Some(self.uid().as_i64()),
+    );
+    view.extra = Some(extra.to_string());
+    self.insert_view(view.clone(), None);
+    Some(view)
+  }
+
+  /// Updates the permission of the space. The other keys of the extra are kept. Returns `None`
+  /// if the view doesn't exist or isn't a space.
+  pub fn update_space_permission(
+    &mut self,
+    view_id: &str,
+    permission: SpacePermission,
+  ) -> Option<Arc<View>> {
+    let mut txn = self.collab.transact_mut();
+    let view = self.body.views.get_view_with_txn(&txn, view_id)?;
+    if !view.is_space() {
+      return None;
+    }
+    // Only the permission key is replaced, the other keys of the extra are kept as they are
+    let mut extra = view
+      .extra
+      .as_deref()
+      .and_then(|extra| serde_json::from_str::<serde_json::Map<_, _>>(extra).ok())?;
+    extra.insert(
+      SPACE_PERMISSION_KEY.to_string(),
+      serde_json::json!(permission as u8),
+    );
+    let extra = serde_json::to_string(&extra).ok()?;
+    self
+      .body
+      .views
+      .update_view(&mut txn, view_id, |update| update.set_extra(extra).done())
+  }
+
+  /// Returns the spaces in the order of the children of the workspace
+  pub fn get_all_spaces(&self) -> Vec<Arc<View>> {
+    let txn = self.collab.transact();
+    let workspace_id = self.body.get_workspace_id(&txn).unwrap_or_default();
+    self
+      .body
+      .views
+      .get_views_belong_to(&txn, &workspace_id)
+      .into_iter()
+      .filter(|view| view.is_space())
+      .collect()
+  }
+
+  /// Copies the view and all its descendants with the ids returned by `id_generator`, in one
+  /// transaction. The copy is inserted right after the view, and the children of each copy keep
+  /// the order of the original children. Returns the pairs of the original id and the id of its
+  /// copy, so the caller can duplicate the documents or databases of the views.
+  pub fn duplicate_view_recursively(
+    &mut self,
+    view_id: &str,
+    id_generator: impl Fn() -> String,
+  ) -> Vec<(String, String)> {
+    let mut txn = self.collab.transact_mut();
+    self
+      .body
+      .duplicate_view_recursively(&mut txn, view_id, id_generator)
+  }
+
+  /// Moves the view and all its descendants to the trash in one transaction. The view is removed
+  /// from the children of its parent, but keeps its `parent_view_id`, so it can be reattached by
+  /// [Folder::restore_from_trash]. The descendants stay attached to the view. Returns the ids of
+  /// the trashed views, the view itself first.
+  pub fn move_view_to_trash(&mut self, view_id: &str) -> Vec<String> {
+    let mut txn = self.collab.transact_mut();
+    self.body.move_view_to_trash(&mut txn, view_id)
+  }
+
+  /// Reattaches the view that was moved to the trash by [Folder::move_view_to_trash] and the
+  /// descendants that were trashed with it. Returns the ids of the restored views.
+  pub fn restore_from_trash(&mut self, view_id: &str) -> Vec<String> {
+    let mut txn = self.collab.transact_mut();
+    self.body.restore_from_trash(&mut txn, view_id)
+  }
+
+  /// Restores the given views from the trash. Each view is reattached to the parent it had when
+  /// it was trashed, or to the workspace if that parent was deleted or is still in the trash.
+  /// Returns the ids of the restored views.
+  pub fn restore_trash(&mut self, view_ids: Vec<String>) -> Vec<String> {
+    let mut txn = self.collab.transact_mut();
+    self.body.restore_trash(&mut txn, view_ids)
+  }
+
   pub fn get_my_trash_info(&self) -> Vec<TrashInfo> {
     let txn = self.collab.transact();
     self
@@ -344,7 +795,10 @@ impl Folder {
     self.body.views.insert(&mut txn, view, index);
   }
 
-  /// Insert a list of views at the end of its parent view
+  /// Insert a list of views at the end of its parent view. All the views are written in one
+  /// transaction, so a single update is generated and the observers receive a single
+  /// [crate::ViewChange::DidCreateViews] after the [crate::ViewChange::DidCreateView] of each
+  /// view. The views of the same parent keep the input order.
   pub fn insert_views(&mut self, views: Vec<View>) {
     let mut txn = self.collab.transact_mut();
     for view in views {
@@ -484,6 +938,9 @@ pub struct FolderBody {
   subscription: Subscription,
   #[allow(dead_code)]
   notifier: Option<FolderNotify>,
+  workspace_change_tx: WorkspaceChangeSender,
+  #[allow(dead_code)]
+  workspace_subscription: Subscription,
 }
 
 impl FolderBody {
@@ -509,9 +966,12 @@ impl FolderBody {
     let subscription = subscribe_folder_change(&mut folder);
 
     // create the folder data
-    let views: MapRef = folder.get_or_init(&mut txn, VIEWS);
+    let mut views: MapRef = folder.get_or_init(&mut txn, VIEWS);
     let section: MapRef = folder.get_or_init(&mut txn, SECTION);
     let meta: MapRef = folder.get_or_init(&mut txn, FOLDER_META);
+    let (workspace_change_tx, _) = broadcast::channel(100);
+    let workspace_subscription =
+      subscribe_workspace_change(&mut views, meta.clone(), workspace_change_tx.clone());
     let parent_child_relations = Arc::new(ParentChildRelations::new(
       folder.get_or_init(&mut txn, PARENT_CHILD_VIEW_RELATION),
     ));
@@ -546,7 +1006,16 @@ impl FolderBody {
       }
 
       meta.insert(&mut txn, FOLDER_WORKSPACE_ID, workspace_id);
-      meta.insert(&mut txn, CURRENT_VIEW, folder_data.current_view);
+      meta.insert(&mut txn, CURRENT_VIEW, folder_data.current_view.clone());
+      let current_views = meta.get_or_init_map(&mut txn, CURRENT_VIEW_FOR_USER);
+      let mut current_view_by_uid = folder_data.current_view_by_uid;
+      // The folder data exported by the old versions only has the shared current view
+      if current_view_by_uid.is_empty() && !folder_data.current_view.is_empty() {
+        current_view_by_uid.insert(uid.clone(), folder_data.current_view);
+      }
+      for (uid, view_id) in current_view_by_uid {
+        current_views.insert(&mut txn, uid.as_ref(), view_id);
+      }
 
       if let Some(fav_section) = section.section_op(&txn, Section::Favorite) {
         for (uid, sections) in folder_data.favorites {
@@ -559,6 +1028,12 @@ impl FolderBody {
           trash_section.add_sections_for_user_with_txn(&mut txn, &uid, sections);
         }
       }
+
+      if let Some(access_time_section) = section.section_op(&txn, Section::ViewAccessTimes) {
+        for (uid, sections) in folder_data.view_access_times {
+          access_time_section.add_sections_for_user_with_txn(&mut txn, &uid, sections);
+        }
+      }
     }
     Self {
       uid,
@@ -568,6 +1043,8 @@ impl FolderBody {
       meta,
       subscription,
       notifier,
+      workspace_change_tx,
+      workspace_subscription,
     }
   }
 
@@ -575,6 +1052,87 @@ impl FolderBody {
     self.meta.get_with_txn(txn, FOLDER_WORKSPACE_ID)
   }
 
+  /// Returns true if the view or one of its ancestors is in the trash of the current user. The
+  /// descendants of a trashed view are hidden even though they are not in the trash themselves.
+  pub fn is_view_effectively_trashed_with_txn<T: ReadTxn>(&self, txn: &T, view_id: &str) -> bool {
+    let trash_ids = self.get_my_trash_ids_with_txn(txn);
+    self.is_view_in_or_under(txn, view_id, &trash_ids)
+  }
+
+  /// Removes the items whose view is effectively trashed. See
+  /// [Self::is_view_effectively_trashed_with_txn].
+  pub fn exclude_trashed_section_items<T: ReadTxn>(
+    &self,
+    txn: &T,
+    items: Vec<SectionItem>,
+  ) -> Vec<SectionItem> {
+    let trash_ids = self.get_my_trash_ids_with_txn(txn);
+    items
+      .into_iter()
+      .filter(|item| !self.is_view_in_or_under(txn, &item.id, &trash_ids))
+      .collect()
+  }
+
+  pub(crate) fn get_my_trash_ids_with_txn<T: ReadTxn>(&self, txn: &T) -> HashSet<String> {
+    self
+      .section
+      .section_op(txn, Section::Trash)
+      .map(|op| op.get_all_section_item(txn))
+      .unwrap_or_default()
+      .into_iter()
+      .map(|item| item.id)
+      .collect()
+  }
+
+  pub(crate) fn is_view_in_or_under<T: ReadTxn>(
+    &self,
+    txn: &T,
+    view_id: &str,
+    view_ids: &HashSet<String>,
+  ) -> bool {
+    if view_ids.is_empty() {
+      return false;
+    }
+    view_ids.contains(view_id)
+      || self
+        .get_view_ancestors_with_txn(txn, view_id)
+        .iter()
+        .any(|ancestor| view_ids.contains(&ancestor.id))
+  }
+
+  /// Returns the ancestors of the view, from its parent up to the workspace. The walk stops at a
+  /// view without parent, at a missing parent or when a view is visited twice, so a corrupted
+  /// relation can't loop forever.
+  pub fn get_view_ancestors_with_txn<T: ReadTxn>(&self, txn: &T, view_id: &str) -> Vec<Arc<View>> {
+    let mut ancestors: Vec<Arc<View>> = vec![];
+    let mut current = match self.views.get_view_with_txn(txn, view_id) {
+      None => return ancestors,
+      Some(view) => view,
+    };
+    loop {
+      let parent_id = current.parent_view_id.as_str();
+      // The workspace doesn't have a parent and the parent of an orphan view is itself
+      if parent_id.is_empty() || parent_id == current.id {
+        break;
+      }
+      if parent_id == view_id || ancestors.iter().any(|ancestor| ancestor.id == parent_id) {
+        tracing::warn!("Cycle detected in the ancestors of view: {}", view_id);
+        break;
+      }
+      match self.views.get_view_with_txn(txn, parent_id) {
+        None => {
+          tracing::warn!("Missing parent view: {} of view: {}", parent_id, current.id);
+          break;
+        },
+        Some(parent) => {
+          ancestors.push(parent.clone());
+          current = parent;
+        },
+      }
+    }
+    ancestors
+  }
+
   /// Recursively retrieves all views associated with the provided `view_id` using a transaction.
   ///
   /// The function begins by attempting to retrieve the parent view associated with the `view_id`.
@@ -592,18 +1150,31 @@ impl FolderBody {
   /// # Returns
   ///
   /// * `Vec<View>`: A vector of `View` objects that includes the parent view and all of its child views.
+  ///
+  /// Each view is returned once, so the children that refer back to one of their ancestors don't
+  /// make it loop forever.
   pub fn get_view_recursively_with_txn<T: ReadTxn>(&self, txn: &T, view_id: &str) -> Vec<View> {
+    let mut visited = HashSet::new();
+    self.get_view_recursively_with_visited(txn, view_id, &mut visited)
+  }
+
+  fn get_view_recursively_with_visited<T: ReadTxn>(
+    &self,
+    txn: &T,
+    view_id: &str,
+    visited: &mut HashSet<String>,
+  ) -> Vec<View> {
+    if !visited.insert(view_id.to_string()) {
+      tracing::warn!("Cycle detected in the children of view: {}", view_id);
+      return vec![];
+    }
     match self.views.get_view_with_txn(txn, view_id) {
       None => vec![],
       Some(parent_view) => {
         let mut views = vec![parent_view.as_ref().clone()];
-        let child_views = parent_view
-          .children
-          .items
-          .iter()
-          .flat_map(|child| self.get_view_recursively_with_txn(txn, &child.id))
-          .collect::<Vec<_>>();
-        views.extend(child_views);
+        for child in parent_view.children.items.iter() {
+          views.extend(self.get_view_recursively_with_visited(txn, &child.id, visited));
+        }
         views
       },
     }
@@ -631,6 +1202,7 @@ impl FolderBody {
     }
     let workspace = Workspace::from(self.views.get_view_with_txn(txn, workspace_id)?.as_ref());
     let current_view = self.get_current_view(txn).unwrap_or_default();
+    let current_view_by_uid = self.get_all_current_views(txn);
     let mut views = vec![];
     let orphan_views = self
       .views
@@ -660,20 +1232,50 @@ impl FolderBody {
       .map(|op| op.get_sections(txn))
       .unwrap_or_default();
 
+    // The views moved to the trash by [Self::move_view_to_trash] are detached from their parents,
+    // so they are not reachable from the workspace
+    let mut view_ids = views
+      .iter()
+      .map(|view| view.id.clone())
+      .collect::<HashSet<_>>();
+    for item in trash.values().flatten() {
+      if view_ids.contains(&item.id) {
+        continue;
+      }
+      for view in self.get_view_recursively_with_txn(txn, &item.id) {
+        if view_ids.insert(view.id.clone()) {
+          views.push(view);
+        }
+      }
+    }
+
     let private = self
       .section
       .section_op(txn, Section::Private)
       .map(|op| op.get_sections(txn))
       .unwrap_or_default();
 
+    // The access times are the browsing history of each user, so the ones of the other users
+    // are not exported
+    let view_access_times = self
+      .section
+      .section_op(txn, Section::ViewAccessTimes)
+      .map(|op| op.get_sections(txn))
+      .unwrap_or_default()
+      .into_iter()
+      .filter(|(uid, _)| uid == &self.uid)
+      .collect();
+
     Some(FolderData {
       workspace,
       current_view,
+      current_view_by_uid,
       views,
       favorites,
       recent,
       trash,
       private,
+      view_access_times,
     })
   }
 
@@ -714,6 +1316,14 @@ impl FolderBody {
       return None;
     }
 
+    if self.would_create_cycle_with_txn(txn, view_id, new_parent_id) {
+      error!(
+        "Can't move view: {} under its descendant: {}",
+        view_id, new_parent_id
+      );
+      return None;
+    }
+
     // dissociate the child from its parent
     self
       .views
@@ -732,12 +1342,415 @@ impl FolderBody {
     Some(view)
   }
 
+  pub fn move_views(
+    &self,
+    txn: &mut TransactionMut,
+    view_ids: Vec<String>,
+    new_parent_id: &str,
+    index: Option<u32>,
+  ) -> Vec<Arc<View>> {
+    let current_workspace_id = match self.get_workspace_id_with_txn(txn) {
+      None => return vec![],
+      Some(workspace_id) => workspace_id,
+    };
+    if new_parent_id != current_workspace_id
+      && self.views.get_view_with_txn(txn, new_parent_id).is_none()
+    {
+      tracing::warn!("Unsupported move out current workspace: {:?}", view_ids);
+      return vec![];
+    }
+
+    let mut views: Vec<Arc<View>> = vec![];
+    for view_id in view_ids {
+      if view_id == current_workspace_id || views.iter().any(|view| view.id == view_id) {
+        continue;
+      }
+      // None of the views is moved if one of them would become an ancestor of itself
+      if self.would_create_cycle_with_txn(txn, &view_id, new_parent_id) {
+        error!(
+          "Can't move view: {} under its descendant: {}",
+          view_id, new_parent_id
+        );
+        return vec![];
+      }
+      if let Some(view) = self.views.get_view_with_txn(txn, &view_id) {
+        views.push(view);
+      }
+    }
+
+    for view in &views {
+      self
+        .views
+        .dissociate_parent_child_with_txn(txn, &view.parent_view_id, &view.id);
+    }
+    let children = views
+      .iter()
+      .map(|view| ViewIdentifier::new(view.id.clone()))
+      .collect::<Vec<_>>();
+    self
+      .views
+      .update_view_with_txn(&self.uid, txn, new_parent_id, |update| {
+        update.add_children(children, index).done()
+      });
+
+    views
+      .iter()
+      .flat_map(|view| {
+        self
+          .views
+          .update_view_with_txn(&self.uid, txn, &view.id, |update| {
+            update.set_bid(new_parent_id).done()
+          })
+      })
+      .collect()
+  }
+
+  pub fn duplicate_view_recursively<F>(
+    &self,
+    txn: &mut TransactionMut,
+    view_id: &str,
+    id_generator: F,
+  ) -> Vec<(String, String)>
+  where
+    F: Fn() -> String,
+  {
+    // The views are ordered from the parent to its children, so the parent of a copy is always
+    // inserted before the copy itself.
+    let views = self.get_view_recursively_with_txn(txn, view_id);
+    let mut id_mapping: Vec<(String, String)> = vec![];
+    for view in views {
+      let new_view_id = id_generator();
+      let (parent_view_id, index) = if view.id == view_id {
+        // The copy of the root view is inserted right after it
+        let index = self
+          .views
+          .get_view_with_txn(txn, &view.parent_view_id)
+          .and_then(|parent| {
+            parent
+              .children
+              .items
+              .iter()
+              .position(|child| child.id == view.id)
+          })
+          .map(|index| index as u32 + 1);
+        (view.parent_view_id.clone(), index)
+      } else {
+        let parent_view_id = id_mapping
+          .iter()
+          .find(|(old_id, _)| old_id == &view.parent_view_id)
+          .map(|(_, new_id)| new_id.clone())
+          .unwrap_or_else(|| view.parent_view_id.clone());
+        (parent_view_id, None)
+      };
+
+      let time = timestamp();
+      let new_view = View {
+        id: new_view_id.clone(),
+        parent_view_id,
+        children: Default::default(),
+        created_at: time,
+        created_by: None,
+        is_favorite: false,
+        last_edited_time: time,
+        last_edited_by: None,
+        ..view
+      };
+      self.views.insert(txn, new_view, index);
+      id_mapping.push((view.id, new_view_id));
+    }
+    id_mapping
+  }
+
+  pub fn move_view_to_trash(&self, txn: &mut TransactionMut, view_id: &str) -> Vec<String> {
+    let views = self.get_view_recursively_with_txn(txn, view_id);
+    let view = match views.first() {
+      None => return vec![],
+      Some(view) => view,
+    };
+
+    // Only the view itself is detached, the descendants stay attached to it
+    self
+      .views
+      .dissociate_parent_child_with_txn(txn, &view.parent_view_id, &view.id);
+
+    if let Some(op) = self.section.section_op(txn, Section::Trash) {
+      let items = views
+        .iter()
+        .map(|view| {
+          SectionItem::with_original_parent_id(view.id.clone(), view.parent_view_id.clone())
+        })
+        .collect();
+      op.add_sections_item(txn, items);
+    }
+    views.into_iter().map(|view| view.id).collect()
+  }
+
+  pub fn restore_from_trash(&self, txn: &mut TransactionMut, view_id: &str) -> Vec<String> {
+    let trash_items = match self.section.section_op(txn, Section::Trash) {
+      None => return vec![],
+      Some(op) => op.get_all_section_item(txn),
+    };
+    if !trash_items.iter().any(|item| item.id == view_id) {
+      return vec![];
+    }
+
+    // The descendants are trashed right after their parent, so a single pass over the trash
+    // finds the whole subtree.
+    let mut restored_ids = vec![view_id.to_string()];
+    for item in trash_items.iter().filter(|item| item.id != view_id) {
+      if let Some(view) = self.views.get_view_with_txn(txn, &item.id) {
+        if restored_ids.contains(&view.parent_view_id) {
+          restored_ids.push(view.id.clone());
+        }
+      }
+    }
+    self.restore_trash(txn, restored_ids)
+  }
+
+  /// Removes the views from the trash and appends each of them to the children of the parent it
+  /// had when it was trashed. The view is moved to the workspace if that parent doesn't exist
+  /// anymore or is still in the trash. The ids that are not in the trash are ignored.
+  pub fn restore_trash(&self, txn: &mut TransactionMut, view_ids: Vec<String>) -> Vec<String> {
+    let op = match self.section.section_op(txn, Section::Trash) {
+      None => return vec![],
+      Some(op) => op,
+    };
+    let (trash_items, remaining_items): (Vec<_>, Vec<_>) = op
+      .get_all_section_item(txn)
+      .into_iter()
+      .partition(|item| view_ids.contains(&item.id));
+    let remaining_ids = remaining_items
+      .into_iter()
+      .map(|item| item.id)
+      .collect::<Vec<_>>();
+    let workspace_id = self.get_workspace_id_with_txn(txn).unwrap_or_default();
+
+    let mut restored_ids = vec![];
+    for item in trash_items {
+      let view = match self.views.get_view_with_txn(txn, &item.id) {
+        None => continue,
+        Some(view) => view,
+      };
+      let original_parent_id = item
+        .original_parent_id
+        .unwrap_or_else(|| view.parent_view_id.clone());
+      let parent_id = if !remaining_ids.contains(&original_parent_id)
+        && self
+          .views
+          .get_view_with_txn(txn, &original_parent_id)
+          .is_some()
+      {
+        original_parent_id
+      } else {
+        workspace_id.clone()
+      };
+
+      // The views that were trashed without being removed from their parent keep their position
+      let parent_children = self
+        .views
+        .get_view_with_txn(txn, &parent_id)
+        .map(|parent| parent.children.clone())
+        .unwrap_or_default();
+      if !parent_children
+        .items
+        .iter()
+        .any(|child| child.id == view.id)
+      {
+        self
+          .views
+          .dissociate_parent_child_with_txn(txn, &view.parent_view_id, &view.id);
+        // Appends the view to the end of the children of its parent
+        let prev_view_id = parent_children.items.last().map(|child| child.id.clone());
+        self
+          .views
+          .associate_parent_child_with_txn(txn, &parent_id, &view.id, prev_view_id);
+      }
+      if parent_id != view.parent_view_id {
+        self
+          .views
+          .update_view_with_txn(&self.uid, txn, &view.id, |update| {
+            update.set_bid(&parent_id).done()
+          });
+      }
+      restored_ids.push(view.id.clone());
+    }
+    op.delete_section_items_with_txn(txn, restored_ids.clone());
+    restored_ids
+  }
+
+  /// Returns the views whose parent is neither a view nor the workspace, sorted by id. The orphan
+  /// views whose parent is themselves, see [ViewsMap::get_orphan_views_with_txn], are not
+  /// included because they are not expected to have a parent.
+  pub fn find_orphan_views_with_txn<T: ReadTxn>(&self, txn: &T) -> Vec<Arc<View>> {
+    let workspace_id = self.get_workspace_id_with_txn(txn).unwrap_or_default();
+    let all_views = self.views.get_all_views(txn);
+    let view_ids = all_views
+      .iter()
+      .map(|view| view.id.as_str())
+      .collect::<HashSet<_>>();
+    let mut orphan_views = all_views
+      .iter()
+      .filter(|view| {
+        view.id != workspace_id
+          && view.parent_view_id != workspace_id
+          && !view_ids.contains(view.parent_view_id.as_str())
+      })
+      .cloned()
+      .collect::<Vec<_>>();
+    orphan_views.sort_by(|a, b| a.id.cmp(&b.id));
+    orphan_views
+  }
+
+  pub fn repair_orphan_views(&self, txn: &mut TransactionMut, target_parent: &str) -> Vec<String> {
+    if self.views.get_view_with_txn(txn, target_parent).is_none() {
+      error!(
+        "Can't repair the orphan views, {} doesn't exist",
+        target_parent
+      );
+      return vec![];
+    }
+
+    let mut repaired_ids = vec![];
+    for view in self.find_orphan_views_with_txn(txn) {
+      if view.id == target_parent {
+        continue;
+      }
+      // Removes the view from the children of the missing parent, whose relation might be kept
+      self.reattach_view_with_txn(txn, &view, target_parent);
+      repaired_ids.push(view.id.clone());
+    }
+    repaired_ids
+  }
+
+  /// Moves the view from its current parent to the end of the children of `target_parent`
+  fn reattach_view_with_txn(&self, txn: &mut TransactionMut, view: &View, target_parent: &str) {
+    self
+      .views
+      .dissociate_parent_child_with_txn(txn, &view.parent_view_id, &view.id);
+    let prev_view_id = self
+      .views
+      .get_view_with_txn(txn, target_parent)
+      .and_then(|parent| parent.children.items.last().map(|child| child.id.clone()));
+    self
+      .views
+      .associate_parent_child_with_txn(txn, target_parent, &view.id, prev_view_id);
+    self
+      .views
+      .update_view_with_txn(&self.uid, txn, &view.id, |update| {
+        update.set_bid(target_parent).done()
+      });
+  }
+
+  pub fn would_create_cycle_with_txn<T: ReadTxn>(
+    &self,
+    txn: &T,
+    view_id: &str,
+    new_parent_id: &str,
+  ) -> bool {
+    view_id == new_parent_id
+      || self
+        .get_view_ancestors_with_txn(txn, new_parent_id)
+        .iter()
+        .any(|ancestor| ancestor.id == view_id)
+  }
+
+  /// Returns the cycles in the parent ids of the views. Each cycle is the list of the view ids
+  /// in it, starting from the smallest id.
+  pub fn find_cycles_with_txn<T: ReadTxn>(&self, txn: &T) -> Vec<Vec<String>> {
+    let parent_ids = self
+      .views
+      .get_all_views(txn)
+      .iter()
+      .map(|view| (view.id.clone(), view.parent_view_id.clone()))
+      .collect::<HashMap<_, _>>();
+    let mut sorted_ids = parent_ids.keys().collect::<Vec<_>>();
+    sorted_ids.sort();
+
+    let mut checked_ids: HashSet<&String> = HashSet::new();
+    let mut cycles = vec![];
+    for view_id in sorted_ids {
+      let mut path: Vec<&String> = vec![];
+      let mut current = view_id;
+      loop {
+        if checked_ids.contains(current) {
+          break;
+        }
+        if let Some(index) = path.iter().position(|id| *id == current) {
+          let mut cycle = path[index..]
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+          let min_index = cycle
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, id)| *id)
+            .map(|(index, _)| index)
+            .unwrap_or(0);
+          cycle.rotate_left(min_index);
+          cycles.push(cycle);
+          break;
+        }
+        path.push(current);
+        match parent_ids.get_key_value(&parent_ids[current]) {
+          // The parent of the orphan views is themselves
+          Some((parent_id, _)) if parent_id != current => current = parent_id,
+          _ => break,
+        }
+      }
+      checked_ids.extend(path);
+    }
+    cycles
+  }
+
+  pub fn break_cycles(&self, txn: &mut TransactionMut) -> Vec<String> {
+    let workspace_id = match self.get_workspace_id_with_txn(txn) {
+      None => return vec![],
+      Some(workspace_id) => workspace_id,
+    };
+
+    let mut moved_ids = vec![];
+    for cycle in self.find_cycles_with_txn(txn) {
+      // The view with the smallest id is moved, so the clients that repair the same cycle
+      // concurrently move the same view
+      if let Some(view) = self.views.get_view_with_txn(txn, &cycle[0]) {
+        tracing::warn!("Break the cycle: {:?}", cycle);
+        self.reattach_view_with_txn(txn, &view, &workspace_id);
+        moved_ids.push(view.id.clone());
+      }
+    }
+    moved_ids
+  }
+
   pub fn get_current_view<T: ReadTxn>(&self, txn: &T) -> Option<String> {
-    self.meta.get_with_txn(txn, CURRENT_VIEW)
+    self
+      .meta
+      .get_with_txn::<_, MapRef>(txn, CURRENT_VIEW_FOR_USER)
+      .and_then(|current_views| current_views.get_with_txn(txn, self.uid.as_ref()))
+      // The current view that was written before it was stored per user
+      .or_else(|| self.meta.get_with_txn(txn, CURRENT_VIEW))
   }
 
   pub fn set_current_view(&self, txn: &mut TransactionMut, view: String) {
-    self.meta.try_update(txn, CURRENT_VIEW, view);
+    let current_views = self.meta.get_or_init_map(txn, CURRENT_VIEW_FOR_USER);
+    current_views.try_update(txn, self.uid.as_ref(), view);
+  }
+
+  fn get_all_current_views<T: ReadTxn>(&self, txn: &T) -> HashMap<UserId, String> {
+    match self
+      .meta
+      .get_with_txn::<_, MapRef>(txn, CURRENT_VIEW_FOR_USER)
+    {
+      None => HashMap::new(),
+      Some(current_views) => current_views
+        .iter(txn)
+        .flat_map(|(uid, value)| match value {
+          YrsValue::Any(Any::String(view_id)) => {
+            Some((UserId(uid.to_string()), view_id.to_string()))
+          },
+          _ => None,
+        })
+        .collect(),
+    }
   }
 }
 
@@ -754,10 +1767,12 @@ pub fn default_folder_data(workspace_id: &str) -> FolderData {
   FolderData {
     workspace,
     current_view: "".to_string(),
+    current_view_by_uid: HashMap::new(),
     views: vec![],
     favorites: HashMap::new(),
     recent: HashMap::new(),
     trash: HashMap::new(),
     private: HashMap::new(),
+    view_access_times: HashMap::new(),
   }
 }
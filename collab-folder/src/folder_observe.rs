@@ -3,24 +3,83 @@ use std::sync::Arc;
 
 use collab::core::collab::{IndexContent, IndexContentSender};
 use collab::preclude::{
-  DeepObservable, EntryChange, Event, MapRef, Subscription, ToJson, YrsValue,
+  Any, DeepObservable, EntryChange, Event, MapExt, MapRef, Subscription, ToJson, YrsValue,
 };
+use collab_entity::define::FOLDER_WORKSPACE_ID;
 use serde_json::json;
 use tokio::sync::broadcast;
 
 use crate::section::SectionMap;
-use crate::{view_from_map_ref, ParentChildRelations, UserId, View, ViewIndexContent};
+use crate::view::{
+  FOLDER_VIEW_ID, FOLDER_VIEW_NAME, VIEW_DESC, VIEW_EXTRA, VIEW_ICON, VIEW_IS_LOCKED,
+  VIEW_PARENT_ID,
+};
+use crate::{view_from_map_ref, ParentChildRelations, UserId, View, ViewIcon, ViewIndexContent};
 
 #[derive(Debug, Clone)]
 pub enum ViewChange {
-  DidCreateView { view: View },
-  DidDeleteView { views: Vec<Arc<View>> },
-  DidUpdate { view: View },
+  DidCreateView {
+    view: View,
+  },
+  /// The views that were created in the same transaction, e.g. by [crate::Folder::insert_views].
+  /// It's sent in addition to the [ViewChange::DidCreateView] of each view.
+  DidCreateViews {
+    views: Vec<View>,
+  },
+  DidDeleteView {
+    views: Vec<Arc<View>>,
+  },
+  DidUpdate {
+    view: View,
+  },
+  /// The view was moved from `old_parent_id` to `new_parent_id`. It's sent for both the local
+  /// and the remote moves. `index` is the position of the view in the children of the new parent.
+  DidMoveView {
+    view_id: String,
+    old_parent_id: String,
+    new_parent_id: String,
+    index: Option<u32>,
+  },
+  /// The views that were moved in the same transaction, e.g. by [crate::Folder::move_views]
+  DidMoveViews {
+    moves: Vec<ViewMove>,
+  },
+  /// The icon of the view was changed. It's sent for both the local and the remote changes, in
+  /// addition to [ViewChange::DidUpdate].
+  DidUpdateViewIcon {
+    view_id: String,
+    icon: Option<ViewIcon>,
+  },
+  /// The view was locked or unlocked, see [View::is_locked]. It's sent for both the local and the
+  /// remote changes, in addition to [ViewChange::DidUpdate].
+  DidUpdateViewLock {
+    view_id: String,
+    is_locked: bool,
+  },
+}
+
+/// A view that was moved from `old_parent_id` to `new_parent_id`, see [ViewChange::DidMoveView]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViewMove {
+  pub view_id: String,
+  pub old_parent_id: String,
+  pub new_parent_id: String,
+  pub index: Option<u32>,
 }
 
 pub type ViewChangeSender = broadcast::Sender<ViewChange>;
 pub type ViewChangeReceiver = broadcast::Receiver<ViewChange>;
 
+#[derive(Debug, Clone)]
+pub enum WorkspaceChange {
+  /// The name or the other metadata of the workspace was changed, either locally or by a remote
+  /// update
+  DidUpdateWorkspace { id: String, name: String },
+}
+
+pub type WorkspaceChangeSender = broadcast::Sender<WorkspaceChange>;
+pub type WorkspaceChangeReceiver = broadcast::Receiver<WorkspaceChange>;
+
 pub(crate) fn subscribe_folder_change(root: &mut MapRef) -> Subscription {
   root.observe_deep(move |txn, events| {
     for deep_event in events.iter() {
@@ -53,6 +112,49 @@ pub(crate) fn subscribe_folder_change(root: &mut MapRef) -> Subscription {
   })
 }
 
+/// The keys of the workspace view that trigger a [WorkspaceChange::DidUpdateWorkspace].
+const WORKSPACE_METADATA_KEYS: [&str; 4] = [FOLDER_VIEW_NAME, VIEW_ICON, VIEW_DESC, VIEW_EXTRA];
+
+/// Observes the view of the workspace, which is stored in the views map like the other views.
+pub(crate) fn subscribe_workspace_change(
+  root: &mut MapRef,
+  meta: MapRef,
+  change_tx: WorkspaceChangeSender,
+) -> Subscription {
+  root.observe_deep(move |txn, events| {
+    let workspace_id: String = match meta.get_with_txn(txn, FOLDER_WORKSPACE_ID) {
+      None => return,
+      Some(workspace_id) => workspace_id,
+    };
+    for deep_event in events.iter() {
+      if let Event::Map(event) = deep_event {
+        let view_id: Option<String> = event.target().get_with_txn(txn, FOLDER_VIEW_ID);
+        if view_id.as_ref() != Some(&workspace_id) {
+          continue;
+        }
+        // Editing the workspace only stamps the last edited time and user, which isn't a change
+        // of the workspace itself
+        let is_metadata_changed = event
+          .keys(txn)
+          .keys()
+          .any(|key| WORKSPACE_METADATA_KEYS.contains(&&**key));
+        if !is_metadata_changed {
+          continue;
+        }
+
+        let name: String = event
+          .target()
+          .get_with_txn(txn, FOLDER_VIEW_NAME)
+          .unwrap_or_default();
+        let _ = change_tx.send(WorkspaceChange::DidUpdateWorkspace {
+          id: workspace_id.clone(),
+          name,
+        });
+      }
+    }
+  })
+}
+
 pub(crate) fn subscribe_view_change(
   _uid: &UserId,
   root: &mut MapRef,
@@ -63,12 +165,14 @@ pub(crate) fn subscribe_view_change(
   index_sender: IndexContentSender,
 ) -> Subscription {
   root.observe_deep(move |txn, events| {
+    let mut created_views = vec![];
+    let mut moved_views = vec![];
     for deep_event in events.iter() {
       match deep_event {
         Event::Text(_) => {},
         Event::Array(_) => {},
         Event::Map(event) => {
-          for c in event.keys(txn).values() {
+          for (key, c) in event.keys(txn).iter() {
             let change_tx = change_tx.clone();
             match c {
               EntryChange::Inserted(v) => {
@@ -80,12 +184,22 @@ pub(crate) fn subscribe_view_change(
                     // Send indexing view
                     let index_content = ViewIndexContent::from(&view);
                     let _ = index_sender.send(IndexContent::Create(json!(index_content)));
-
-                    let _ = change_tx.send(ViewChange::DidCreateView { view });
+                    created_views.push(view);
+                  }
+                } else if key.as_ref() == VIEW_ICON || key.as_ref() == VIEW_IS_LOCKED {
+                  // The views might not have these keys, e.g. the views created by the old
+                  // versions don't have the icon key
+                  if let Some(view) =
+                    view_from_map_ref(event.target(), txn, &view_relations, &section_map)
+                  {
+                    view_cache.insert(view.id.clone(), Arc::new(view.clone()));
+                    if let Some(change) = view_property_change(key, &view) {
+                      let _ = change_tx.send(change);
+                    }
                   }
                 }
               },
-              EntryChange::Updated(_, _) => {
+              EntryChange::Updated(old_value, new_value) => {
                 if let Some(view) =
                   view_from_map_ref(event.target(), txn, &view_relations, &section_map)
                 {
@@ -95,7 +209,46 @@ pub(crate) fn subscribe_view_change(
                   let index_content = ViewIndexContent::from(&view);
                   let _ = index_sender.send(IndexContent::Update(json!(index_content)));
 
+                  // The parent id of the view is changed when the view is moved to another parent
+                  let moved_view = match (key.as_ref(), old_value, new_value) {
+                    (
+                      VIEW_PARENT_ID,
+                      YrsValue::Any(Any::String(old_parent_id)),
+                      YrsValue::Any(Any::String(new_parent_id)),
+                    ) if old_parent_id != new_parent_id => {
+                      let index = view_relations
+                        .get_children_with_txn(txn, new_parent_id)
+                        .and_then(|children| {
+                          children
+                            .get_children_with_txn(txn)
+                            .items
+                            .iter()
+                            .position(|child| child.id == view.id)
+                        })
+                        .map(|index| index as u32);
+                      Some(ViewMove {
+                        view_id: view.id.clone(),
+                        old_parent_id: old_parent_id.to_string(),
+                        new_parent_id: new_parent_id.to_string(),
+                        index,
+                      })
+                    },
+                    _ => None,
+                  };
+                  let property_change = match (old_value, new_value) {
+                    (YrsValue::Any(old_value), YrsValue::Any(new_value))
+                      if old_value != new_value =>
+                    {
+                      view_property_change(key, &view)
+                    },
+                    _ => None,
+                  };
+
                   let _ = change_tx.send(ViewChange::DidUpdate { view });
+                  moved_views.extend(moved_view);
+                  if let Some(property_change) = property_change {
+                    let _ = change_tx.send(property_change);
+                  }
                 }
               },
               EntryChange::Removed(_) => {
@@ -122,5 +275,42 @@ pub(crate) fn subscribe_view_change(
         _ => {},
       }
     }
+
+    for view in created_views.iter() {
+      let _ = change_tx.send(ViewChange::DidCreateView { view: view.clone() });
+    }
+    // The views that are created in one transaction are also sent in one notification
+    if created_views.len() > 1 {
+      let _ = change_tx.send(ViewChange::DidCreateViews {
+        views: created_views,
+      });
+    }
+
+    // The same for the views that are moved in one transaction
+    if moved_views.len() > 1 {
+      let _ = change_tx.send(ViewChange::DidMoveViews { moves: moved_views });
+    } else if let Some(moved_view) = moved_views.pop() {
+      let _ = change_tx.send(ViewChange::DidMoveView {
+        view_id: moved_view.view_id,
+        old_parent_id: moved_view.old_parent_id,
+        new_parent_id: moved_view.new_parent_id,
+        index: moved_view.index,
+      });
+    }
   })
 }
+
+/// Returns the dedicated change of the view property `key`, if there is one
+fn view_property_change(key: &str, view: &View) -> Option<ViewChange> {
+  match key {
+    VIEW_ICON => Some(ViewChange::DidUpdateViewIcon {
+      view_id: view.id.clone(),
+      icon: view.icon.clone(),
+    }),
+    VIEW_IS_LOCKED => Some(ViewChange::DidUpdateViewLock {
+      view_id: view.id.clone(),
+      is_locked: view.is_locked,
+    }),
+    _ => None,
+  }
+}
@@ -220,6 +220,7 @@ impl NestedChildViewBuilder {
       ),
       last_edited_by: Some(self.uid),
       extra: self.extra,
+      is_locked: false,
     };
     ParentChildViews {
       view,
@@ -7,6 +7,7 @@ pub use section::*;
 // pub use trash::*;
 pub use space_info::*;
 pub use view::*;
+pub use view_extra::*;
 pub use workspace::*;
 
 mod entities;
@@ -15,6 +16,7 @@ mod relation;
 mod section;
 // mod trash;
 mod view;
+mod view_extra;
 mod workspace;
 
 #[macro_use]
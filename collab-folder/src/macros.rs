@@ -130,18 +130,19 @@ macro_rules! impl_section_op {
     // Get all section items for the current user
     pub fn $get_my_fn(&self) -> Vec<SectionItem> {
       let txn = self.collab.transact();
-      self
+      let items = self
         .body
         .section
         .section_op(&txn, $section_type)
         .map(|op| op.get_all_section_item(&txn))
-        .unwrap_or_default()
+        .unwrap_or_default();
+      self.visible_section_items(&txn, $section_type, items)
     }
 
     // Get all sections
     pub fn $get_all_fn(&self) -> Vec<SectionItem> {
       let txn = self.collab.transact();
-      self
+      let items = self
         .body
         .section
         .section_op(&txn, $section_type)
@@ -149,7 +150,8 @@ macro_rules! impl_section_op {
         .unwrap_or_default()
         .into_iter()
         .flat_map(|(_user_id, items)| items)
-        .collect()
+        .collect();
+      self.visible_section_items(&txn, $section_type, items)
     }
 
     // Clear all items in a section
@@ -7,7 +7,7 @@ use collab::preclude::{deserialize_i64_from_numeric, ArrayRef, MapExt};
 use collab::preclude::{
   Any, AnyMut, Array, Map, MapRef, ReadTxn, Subscription, TransactionMut, YrsValue,
 };
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use tokio::sync::broadcast;
 
 pub struct SectionMap {
@@ -67,6 +67,8 @@ pub enum Section {
   Recent,
   Trash,
   Private,
+  /// The time each view was last opened by the user
+  ViewAccessTimes,
   Custom(String),
 }
 
@@ -76,6 +78,7 @@ pub(crate) fn predefined_sections() -> Vec<Section> {
     Section::Recent,
     Section::Trash,
     Section::Private,
+    Section::ViewAccessTimes,
   ]
 }
 
@@ -93,6 +96,7 @@ impl AsRef<str> for Section {
       Section::Recent => "recent",
       Section::Trash => "trash",
       Section::Private => "private",
+      Section::ViewAccessTimes => "view_access_times",
       Section::Custom(s) => s.as_str(),
     }
   }
@@ -101,6 +105,7 @@ impl AsRef<str> for Section {
 #[derive(Clone, Debug)]
 pub enum SectionChange {
   Trash(TrashSectionChange),
+  Favorite(FavoriteSectionChange),
 }
 
 pub type SectionChangeSender = broadcast::Sender<SectionChange>;
@@ -112,6 +117,12 @@ pub enum TrashSectionChange {
   TrashItemRemoved { ids: Vec<String> },
 }
 
+#[derive(Clone, Debug)]
+pub enum FavoriteSectionChange {
+  /// The favorite `id` was moved to the position of the favorite `to_id`
+  FavoriteItemMoved { id: String, to_id: String },
+}
+
 pub type SectionsByUid = HashMap<UserId, Vec<SectionItem>>;
 
 pub struct SectionOperation<'a> {
@@ -221,6 +232,7 @@ impl<'a> SectionOperation<'a> {
           },
           Section::Custom(_) => {},
           Section::Private => {},
+          Section::ViewAccessTimes => {},
         }
       }
     }
@@ -240,8 +252,52 @@ impl<'a> SectionOperation<'a> {
         },
         Section::Custom(_) => {},
         Section::Private => {},
+        Section::ViewAccessTimes => {},
+      }
+    }
+  }
+
+  /// Moves the item `from_id` to the position of the item `to_id`. Returns false if either of
+  /// them isn't in the section.
+  pub fn move_section_item_with_txn(
+    &self,
+    txn: &mut TransactionMut,
+    from_id: &str,
+    to_id: &str,
+  ) -> bool {
+    let array = match self
+      .container()
+      .get_with_txn::<_, ArrayRef>(txn, self.uid().as_ref())
+    {
+      None => return false,
+      Some(array) => array,
+    };
+    let items = self.get_all_section_item(txn);
+    let from = items.iter().position(|item| item.id == from_id);
+    let to = items.iter().position(|item| item.id == to_id);
+    let (from, to) = match (from, to) {
+      (Some(from), Some(to)) => (from as u32, to as u32),
+      _ => return false,
+    };
+    if from == to {
+      return true;
+    }
+
+    if let Some(YrsValue::Any(value)) = array.get(txn, from) {
+      array.remove(txn, from);
+      array.insert(txn, to, value);
+    }
+    if let Some(change_tx) = self.change_tx.as_ref() {
+      if self.section == Section::Favorite {
+        let _ = change_tx.send(SectionChange::Favorite(
+          FavoriteSectionChange::FavoriteItemMoved {
+            id: from_id.to_string(),
+            to_id: to_id.to_string(),
+          },
+        ));
       }
     }
+    true
   }
 
   pub fn add_sections_for_user_with_txn(
@@ -268,11 +324,52 @@ impl<'a> SectionOperation<'a> {
   }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+/// An item of a section. The items written by the old versions are bare view ids, they are read
+/// with a `timestamp` of 0.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
 pub struct SectionItem {
   pub id: String,
-  #[serde(deserialize_with = "deserialize_i64_from_numeric")]
   pub timestamp: i64,
+  /// The parent of the view when it was moved to the trash. It's only set for the items of the
+  /// trash section and is `None` for the items that were trashed before it was introduced.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub original_parent_id: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for SectionItem {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    #[derive(Deserialize)]
+    struct SectionItemMap {
+      id: String,
+      #[serde(deserialize_with = "deserialize_i64_from_numeric")]
+      timestamp: i64,
+      #[serde(default)]
+      original_parent_id: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SectionItemValue {
+      Id(String),
+      Item(SectionItemMap),
+    }
+
+    Ok(match SectionItemValue::deserialize(deserializer)? {
+      SectionItemValue::Id(id) => SectionItem {
+        id,
+        timestamp: 0,
+        original_parent_id: None,
+      },
+      SectionItemValue::Item(item) => SectionItem {
+        id: item.id,
+        timestamp: item.timestamp,
+        original_parent_id: item.original_parent_id,
+      },
+    })
+  }
 }
 
 impl SectionItem {
@@ -280,6 +377,14 @@ impl SectionItem {
     Self {
       id,
       timestamp: timestamp(),
+      original_parent_id: None,
+    }
+  }
+
+  pub fn with_original_parent_id(id: String, original_parent_id: String) -> Self {
+    Self {
+      original_parent_id: Some(original_parent_id),
+      ..Self::new(id)
     }
   }
 }
@@ -296,13 +401,20 @@ impl TryFrom<Any> for SectionItem {
 
 impl From<SectionItem> for HashMap<String, AnyMut> {
   fn from(item: SectionItem) -> Self {
-    HashMap::from([
+    let mut map = HashMap::from([
       ("id".to_string(), AnyMut::String(item.id)),
       (
         "timestamp".to_string(),
         AnyMut::Number(item.timestamp as f64),
       ),
-    ])
+    ]);
+    if let Some(original_parent_id) = item.original_parent_id {
+      map.insert(
+        "original_parent_id".to_string(),
+        AnyMut::String(original_parent_id),
+      );
+    }
+    map
   }
 }
 
@@ -14,21 +14,26 @@ use tracing::{instrument, trace};
 use crate::folder_observe::ViewChangeSender;
 
 use crate::section::{Section, SectionItem, SectionMap};
-use crate::space_info::SpaceInfo;
-use crate::{impl_any_update, impl_i64_update, impl_option_i64_update, impl_str_update, UserId};
+use crate::space_info::{SpaceInfo, SPACE_IS_SPACE_KEY};
+use crate::view_extra::ViewExtra;
+use crate::{
+  impl_any_update, impl_bool_update, impl_i64_update, impl_option_i64_update, impl_str_update,
+  UserId,
+};
 use crate::{subscribe_view_change, ParentChildRelations, RepeatedViewIdentifier, ViewIdentifier};
 
 pub(crate) const FOLDER_VIEW_ID: &str = "id";
 pub(crate) const FOLDER_VIEW_NAME: &str = "name";
-const VIEW_PARENT_ID: &str = "bid";
-const VIEW_DESC: &str = "desc";
+pub(crate) const VIEW_PARENT_ID: &str = "bid";
+pub(crate) const VIEW_DESC: &str = "desc";
 const VIEW_LAYOUT: &str = "layout";
 const VIEW_CREATE_AT: &str = "created_at";
 const VIEW_CREATED_BY: &str = "created_by";
-const VIEW_ICON: &str = "icon";
+pub(crate) const VIEW_ICON: &str = "icon";
 const VIEW_LAST_EDITED_TIME: &str = "last_edited_time";
 const VIEW_LAST_EDITED_BY: &str = "last_edited_by";
-const VIEW_EXTRA: &str = "extra";
+pub(crate) const VIEW_EXTRA: &str = "extra";
+pub(crate) const VIEW_IS_LOCKED: &str = "is_locked";
 // const VIEW_LAST_VIEWED_TIME: &str = "last_viewed_time";
 
 pub fn timestamp() -> i64 {
@@ -256,6 +261,54 @@ impl ViewsMap {
     view
   }
 
+  /// Returns the views whose name matches the `query` case-insensitively, sorted by name. Only
+  /// the name and the parent id of each view are read, and the views in `excluded_view_ids` are
+  /// skipped. An empty query matches nothing.
+  pub fn search_views_with_txn<T: ReadTxn>(
+    &self,
+    txn: &T,
+    query: &str,
+    match_mode: ViewNameMatch,
+    limit: usize,
+    excluded_view_ids: &[String],
+  ) -> Vec<ViewDescription> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+      return vec![];
+    }
+
+    let mut views = self
+      .container
+      .iter(txn)
+      .filter(|(view_id, _)| !excluded_view_ids.iter().any(|id| id == view_id))
+      .flat_map(|(view_id, value)| {
+        let map_ref = match value {
+          YrsValue::YMap(map_ref) => map_ref,
+          _ => return None,
+        };
+        let name: String = map_ref.get_with_txn(txn, FOLDER_VIEW_NAME)?;
+        let lowercase_name = name.to_lowercase();
+        let is_match = match match_mode {
+          ViewNameMatch::Prefix => lowercase_name.starts_with(&query),
+          ViewNameMatch::Contains => lowercase_name.contains(&query),
+        };
+        if !is_match {
+          return None;
+        }
+        Some(ViewDescription {
+          id: view_id.to_string(),
+          parent_view_id: map_ref
+            .get_with_txn(txn, VIEW_PARENT_ID)
+            .unwrap_or_default(),
+          name,
+        })
+      })
+      .collect::<Vec<_>>();
+    views.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    views.truncate(limit);
+    views
+  }
+
   pub fn get_view_name_with_txn<T: ReadTxn>(&self, txn: &T, view_id: &str) -> Option<String> {
     let map_ref: MapRef = self.container.get_with_txn(txn, view_id)?;
     map_ref.get_with_txn(txn, FOLDER_VIEW_NAME)
@@ -324,6 +377,7 @@ impl ViewsMap {
         .set_last_edited_time(last_edited_time)
         .set_last_edited_by(Some(last_edited_by))
         .set_extra_if_not_none(view.extra)
+        .set_locked_if_not_none(view.is_locked.then_some(true))
         .done()
     })
     .done();
@@ -455,6 +509,7 @@ pub(crate) fn view_from_map_ref<T: ReadTxn>(
     .unwrap_or(timestamp());
   let last_edited_by = map_ref.get_with_txn(txn, VIEW_LAST_EDITED_BY);
   let extra = map_ref.get_with_txn(txn, VIEW_EXTRA);
+  let is_locked = map_ref.get_with_txn(txn, VIEW_IS_LOCKED).unwrap_or(false);
 
   Some(View {
     id,
@@ -469,6 +524,7 @@ pub(crate) fn view_from_map_ref<T: ReadTxn>(
     last_edited_time,
     last_edited_by,
     extra,
+    is_locked,
   })
 }
 
@@ -549,6 +605,7 @@ impl<'a, 'b, 'c> ViewUpdate<'a, 'b, 'c> {
   );
   impl_option_i64_update!(set_last_edited_by, VIEW_LAST_EDITED_BY);
   impl_str_update!(set_extra, set_extra_if_not_none, VIEW_EXTRA);
+  impl_bool_update!(set_locked, set_locked_if_not_none, VIEW_IS_LOCKED);
 
   pub fn new(
     uid: &'a UserId,
@@ -640,7 +697,16 @@ impl<'a, 'b, 'c> ViewUpdate<'a, 'b, 'c> {
   pub fn set_trash(self, is_trash: bool) -> Self {
     if let Some(trash_section) = self.section_map.section_op(self.txn, Section::Trash) {
       if is_trash {
-        trash_section.add_sections_item(self.txn, vec![SectionItem::new(self.view_id.to_string())]);
+        let item = match self
+          .map_ref
+          .get_with_txn::<_, String>(self.txn, VIEW_PARENT_ID)
+        {
+          None => SectionItem::new(self.view_id.to_string()),
+          Some(parent_view_id) => {
+            SectionItem::with_original_parent_id(self.view_id.to_string(), parent_view_id)
+          },
+        };
+        trash_section.add_sections_item(self.txn, vec![item]);
       } else {
         trash_section.delete_section_items_with_txn(self.txn, vec![self.view_id.to_string()]);
       }
@@ -649,6 +715,22 @@ impl<'a, 'b, 'c> ViewUpdate<'a, 'b, 'c> {
     self
   }
 
+  /// Writes the extra as JSON. Use [View::get_extra_typed] to read the current extra first, so
+  /// the keys that are unknown to [ViewExtra] are written back.
+  pub fn set_extra_typed(self, extra: ViewExtra) -> Self {
+    match serde_json::to_string(&extra) {
+      Ok(extra) => self.set_extra(extra),
+      Err(err) => {
+        tracing::error!(
+          "Failed to serialize the extra of view: {}, {}",
+          self.view_id,
+          err
+        );
+        self
+      },
+    }
+  }
+
   pub fn add_children(self, children: Vec<ViewIdentifier>, index: Option<u32>) -> Self {
     self
       .children_map
@@ -691,6 +773,10 @@ pub struct View {
   /// - line_height_layout: "small" or "normal" or "large"
   /// - font_layout: "small", or "normal", or "large"
   pub extra: Option<String>,
+  /// The locked view can't be edited by the clients. The descendants of a locked view are
+  /// locked as well, see [crate::Folder::is_view_locked].
+  #[serde(default)]
+  pub is_locked: bool,
 }
 
 impl View {
@@ -714,6 +800,7 @@ impl View {
       last_edited_time: 0,
       last_edited_by: None,
       extra: None,
+      is_locked: false,
     }
   }
 
@@ -731,8 +818,29 @@ impl View {
       last_edited_time: 0,
       last_edited_by: None,
       extra: None,
+      is_locked: false,
+    }
+  }
+  /// Parses the `extra` of the view. An empty [ViewExtra] is returned if the view doesn't have
+  /// extra data, and an error if the extra data isn't a JSON object.
+  pub fn get_extra_typed(&self) -> Result<ViewExtra, serde_json::Error> {
+    match self.extra.as_ref() {
+      None => Ok(ViewExtra::default()),
+      Some(extra) => serde_json::from_str(extra),
     }
   }
+
+  /// Returns true if the view is a space, see [SpaceInfo]. Only the [SPACE_IS_SPACE_KEY] of the
+  /// extra is read, so the other keys of the extra don't need to be valid.
+  pub fn is_space(&self) -> bool {
+    self
+      .extra
+      .as_ref()
+      .and_then(|extra| serde_json::from_str::<serde_json::Value>(extra).ok())
+      .and_then(|extra| extra.get(SPACE_IS_SPACE_KEY)?.as_bool())
+      .unwrap_or(false)
+  }
+
   pub fn space_info(&self) -> Option<SpaceInfo> {
     let extra = self.extra.as_ref()?;
     serde_json::from_str::<SpaceInfo>(extra).ok()
@@ -763,6 +871,23 @@ impl From<&View> for ViewIndexContent {
   }
 }
 
+/// A view that matches a search. See [Folder::search_views](crate::Folder::search_views).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ViewDescription {
+  pub id: String,
+  pub parent_view_id: String,
+  pub name: String,
+}
+
+/// How the name of a view is matched against the query of a search
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ViewNameMatch {
+  /// The name starts with the query
+  Prefix,
+  /// The name contains the query
+  Contains,
+}
+
 #[derive(Eq, PartialEq, Debug, Hash, Clone, Serialize_repr, Deserialize_repr)]
 #[repr(u8)]
 pub enum IconType {
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+/// The typed form of the `extra` of a [crate::View].
+///
+/// The keys that are not known by this struct, e.g. the space permission and icon of a space
+/// view, are kept in `others`. So reading, modifying and writing back the extra doesn't drop the
+/// keys that were written by other clients.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ViewExtra {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub cover: Option<ViewCover>,
+
+  /// "small", "normal" or "large"
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub line_height_layout: Option<String>,
+
+  /// "small", "normal" or "large"
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub font_layout: Option<String>,
+
+  /// Stored under [crate::SPACE_IS_SPACE_KEY]
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub is_space: Option<bool>,
+
+  #[serde(flatten)]
+  pub others: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The cover of a document view
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ViewCover {
+  /// "0" represents normal color, "1" gradient color, "2" built-in image, "3" custom image,
+  /// "4" local image and "5" unsplash image
+  #[serde(rename = "type")]
+  pub ty: String,
+  pub value: String,
+}
@@ -57,6 +57,7 @@ impl From<Workspace> for View {
       last_edited_time: value.last_edited_time,
       last_edited_by: value.last_edited_by,
       extra: None,
+      is_locked: false,
     }
   }
 }
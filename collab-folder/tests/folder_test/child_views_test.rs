@@ -303,3 +303,139 @@ fn create_orphan_child_views_test() {
         })
   );
 }
+
+#[test]
+fn find_and_repair_orphan_views_test() {
+  let uid = UserId::from(1);
+  let workspace_id = "w1".to_string();
+  let folder_test = create_folder_with_workspace(uid.clone(), &workspace_id);
+  let mut folder = folder_test.folder;
+  folder.insert_view(make_test_view("v1", &workspace_id, vec![]), None);
+  folder.insert_view(make_test_view("v2", &workspace_id, vec![]), None);
+  folder.insert_view(make_test_view("v1_1", "v1", vec![]), None);
+  folder.insert_view(make_test_view("v1_2", "v1", vec![]), None);
+  folder.insert_view(make_test_view("v1_1_1", "v1_1", vec![]), None);
+  // The view whose parent is itself is not an orphan of a missing parent
+  folder.insert_view(make_test_view("v3", "v3", vec![]), None);
+  assert!(folder.find_orphan_views().is_empty());
+
+  // Remove the parent without removing its children, like a partial sync does
+  {
+    let mut txn = folder.collab.transact_mut();
+    folder.body.views.delete_views(&mut txn, vec!["v1"]);
+  }
+  assert_eq!(folder.find_orphan_views(), vec!["v1_1", "v1_2"]);
+
+  // The orphan views and their children are listed under the workspace
+  let folder_data = folder.get_folder_data(&workspace_id).unwrap();
+  assert!(!folder_data.views.iter().any(|view| view.id == "v1_1"));
+  let folder_data = folder
+    .get_folder_data_with_orphan_views(&workspace_id)
+    .unwrap();
+  let child_ids = folder_data
+    .workspace
+    .child_views
+    .iter()
+    .map(|child| child.id.clone())
+    .collect::<Vec<_>>();
+  assert_eq!(child_ids, vec!["v2", "v1_1", "v1_2"]);
+  let view = folder_data
+    .views
+    .iter()
+    .find(|view| view.id == "v1_1")
+    .unwrap();
+  assert_eq!(view.parent_view_id, workspace_id);
+  assert!(folder_data.views.iter().any(|view| view.id == "v1_1_1"));
+  assert_eq!(folder.find_orphan_views().len(), 2);
+
+  // The parent must exist
+  assert!(folder.repair_orphan_views("v1").is_empty());
+  assert_eq!(folder.repair_orphan_views("v2"), vec!["v1_1", "v1_2"]);
+  assert!(folder.find_orphan_views().is_empty());
+  let child_ids = folder
+    .get_views_belong_to("v2")
+    .iter()
+    .map(|view| view.id.clone())
+    .collect::<Vec<_>>();
+  assert_eq!(child_ids, vec!["v1_1", "v1_2"]);
+  assert_eq!(folder.get_view("v1_1").unwrap().parent_view_id, "v2");
+  assert_eq!(folder.get_views_belong_to("v1_1")[0].id, "v1_1_1");
+}
+
+#[test]
+fn reject_move_that_creates_cycle_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  folder.insert_view(make_test_view("v1", "w1", vec![]), None);
+  folder.insert_view(make_test_view("v2", "w1", vec![]), None);
+  folder.insert_view(make_test_view("v1_1", "v1", vec![]), None);
+  folder.insert_view(make_test_view("v1_1_1", "v1_1", vec![]), None);
+
+  assert!(folder.would_create_cycle("v1", "v1"));
+  assert!(folder.would_create_cycle("v1", "v1_1_1"));
+  assert!(!folder.would_create_cycle("v1_1", "v2"));
+  assert!(!folder.would_create_cycle("v1_1_1", "w1"));
+
+  assert!(folder.move_nested_view("v1", "v1_1_1", None).is_none());
+  assert_eq!(folder.get_view("v1").unwrap().parent_view_id, "w1");
+
+  // None of the views is moved if one of them can't be moved
+  assert!(folder
+    .move_views(vec!["v2".to_string(), "v1".to_string()], "v1_1", None)
+    .is_empty());
+  assert_eq!(folder.get_view("v2").unwrap().parent_view_id, "w1");
+  assert_eq!(folder.get_view("v1").unwrap().parent_view_id, "w1");
+}
+
+#[test]
+fn find_and_break_cycles_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  folder.insert_view(make_test_view("v1", "w1", vec![]), None);
+  // c1 and c2 are the parent of each other, like two concurrent moves can leave them
+  folder.insert_view(make_test_view("c2", "c1", vec![]), None);
+  folder.insert_view(make_test_view("c1", "c2", vec![]), None);
+  folder.insert_view(make_test_view("c1_1", "c1", vec![]), None);
+  folder.insert_view(make_test_view("c3", "c3", vec![]), None);
+  // c2 is inserted before its parent, so it has to be added to the children of c1
+  {
+    let mut txn = folder.collab.transact_mut();
+    folder
+      .body
+      .views
+      .associate_parent_child_with_txn(&mut txn, "c1", "c2", None);
+  }
+
+  let cycles = {
+    let txn = folder.collab.transact();
+    folder.body.find_cycles_with_txn(&txn)
+  };
+  assert_eq!(cycles, vec![vec!["c1".to_string(), "c2".to_string()]]);
+
+  // The traversal visits each view once
+  let view_ids = folder
+    .get_view_recursively("c1")
+    .into_iter()
+    .map(|view| view.id)
+    .collect::<Vec<_>>();
+  assert_eq!(view_ids, vec!["c1", "c2", "c1_1"]);
+
+  assert_eq!(folder.break_cycles(), vec!["c1"]);
+  assert_eq!(folder.get_view("c1").unwrap().parent_view_id, "w1");
+  let child_ids = folder
+    .get_views_belong_to("w1")
+    .iter()
+    .map(|view| view.id.clone())
+    .collect::<Vec<_>>();
+  assert_eq!(child_ids, vec!["v1", "c1"]);
+  let ancestor_ids = folder
+    .get_view_ancestors("c2")
+    .iter()
+    .map(|view| view.id.clone())
+    .collect::<Vec<_>>();
+  assert_eq!(ancestor_ids, vec!["c1", "w1"]);
+  assert!(folder.get_views_belong_to("c2").is_empty());
+  assert!(folder.break_cycles().is_empty());
+}
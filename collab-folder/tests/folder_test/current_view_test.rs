@@ -0,0 +1,69 @@
+use collab::core::origin::CollabOrigin;
+use collab::preclude::updates::decoder::Decode;
+use collab::preclude::{ReadTxn, Update};
+use collab_folder::{Folder, FolderData, UserId};
+
+use crate::util::{create_folder_with_data, create_folder_with_workspace, make_test_view};
+
+#[test]
+fn current_view_per_user_test() {
+  let folder_test = create_folder_with_workspace(UserId::from(1), "w1");
+  let mut folder = folder_test.folder;
+  for view_id in ["v1", "v2"] {
+    folder.insert_view(make_test_view(view_id, "w1", vec![]), None);
+  }
+  folder.set_current_view("v1".to_string());
+
+  // The other user opens the same folder and sets its own current view
+  let mut remote_folder = Folder::from_collab_doc_state(
+    UserId::from(2),
+    CollabOrigin::Empty,
+    folder.encode_collab().unwrap().into(),
+    "w1",
+    vec![],
+  )
+  .unwrap();
+  // The current view of the other user is not shared
+  assert_eq!(remote_folder.get_current_view().unwrap(), "");
+  remote_folder.set_current_view("v2".to_string());
+  let update = {
+    let state_vector = folder.collab.transact().state_vector();
+    remote_folder
+      .collab
+      .transact()
+      .encode_state_as_update_v1(&state_vector)
+  };
+  folder
+    .collab
+    .apply_update(Update::decode_v1(&update).unwrap())
+    .unwrap();
+
+  assert_eq!(folder.get_current_view().unwrap(), "v1");
+  assert_eq!(remote_folder.get_current_view().unwrap(), "v2");
+
+  let folder_data = folder.get_folder_data("w1").unwrap();
+  assert_eq!(folder_data.current_view, "v1");
+  assert_eq!(folder_data.current_view_by_uid.len(), 2);
+  assert_eq!(folder_data.current_view_by_uid[&UserId::from(2)], "v2");
+}
+
+#[test]
+fn load_legacy_current_view_test() {
+  let uid = UserId::from(1);
+  let mut folder_data = create_folder_with_workspace(uid.clone(), "w1")
+    .get_folder_data("w1")
+    .unwrap();
+  folder_data.views = vec![make_test_view("v1", "w1", vec![])];
+
+  // The folder data exported by the old versions doesn't have the current view of each user
+  let mut value = serde_json::to_value(folder_data).unwrap();
+  value.as_object_mut().unwrap().remove("current_view_by_uid");
+  value["current_view"] = "v1".into();
+  let folder_data: FolderData = serde_json::from_value(value).unwrap();
+  assert!(folder_data.current_view_by_uid.is_empty());
+
+  let folder_test = create_folder_with_data(uid.clone(), "w1", folder_data);
+  assert_eq!(folder_test.get_current_view().unwrap(), "v1");
+  let folder_data = folder_test.get_folder_data("w1").unwrap();
+  assert_eq!(folder_data.current_view_by_uid[&uid], "v1");
+}
@@ -3,7 +3,8 @@ use crate::util::{
   unzip_history_folder_db,
 };
 use assert_json_diff::assert_json_include;
-use collab_folder::{FolderData, UserId};
+use collab::preclude::{Array, ArrayRef, Map, MapExt, MapRef};
+use collab_folder::{timestamp, FavoriteSectionChange, FolderData, Section, SectionChange, UserId};
 use serde_json::json;
 use uuid::Uuid;
 
@@ -374,3 +375,151 @@ fn migrate_favorite_v1_test() {
     })
   );
 }
+
+#[test]
+fn move_favorite_test() {
+  let uid = UserId::from(1);
+  let mut folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut section_rx = folder_test.section_rx.take().unwrap();
+  let workspace_id = folder_test.get_workspace_id().unwrap();
+
+  let mut folder = folder_test.folder;
+  for id in ["1", "2", "3", "4"] {
+    folder.insert_view(make_test_view(id, workspace_id.as_str(), vec![]), None);
+  }
+  folder.add_favorite_view_ids(vec!["1".to_string(), "2".to_string(), "3".to_string()]);
+  let favorite_ids = |folder: &collab_folder::Folder| {
+    folder
+      .get_all_favorites_sections()
+      .into_iter()
+      .map(|item| item.id)
+      .collect::<Vec<_>>()
+  };
+
+  folder.move_favorite("3", "1");
+  assert_eq!(favorite_ids(&folder), vec!["3", "1", "2"]);
+  match section_rx.try_recv().unwrap() {
+    SectionChange::Favorite(FavoriteSectionChange::FavoriteItemMoved { id, to_id }) => {
+      assert_eq!(id, "3");
+      assert_eq!(to_id, "1");
+    },
+    change => panic!("unexpected change: {:?}", change),
+  }
+
+  folder.move_favorite("3", "2");
+  assert_eq!(favorite_ids(&folder), vec!["1", "2", "3"]);
+
+  // Moving a view that isn't a favorite does nothing
+  section_rx.try_recv().unwrap();
+  folder.move_favorite("4", "1");
+  folder.move_favorite("1", "4");
+  assert_eq!(favorite_ids(&folder), vec!["1", "2", "3"]);
+  assert!(section_rx.try_recv().is_err());
+}
+
+#[test]
+fn legacy_favorite_data_serde_test() {
+  let uid = UserId::from(1);
+  let workspace_id = "w1".to_string();
+  let folder_test = create_folder_with_workspace(uid.clone(), &workspace_id);
+  let mut folder = folder_test.folder;
+  folder.insert_view(make_test_view("1", workspace_id.as_str(), vec![]), None);
+  folder.insert_view(make_test_view("2", workspace_id.as_str(), vec![]), None);
+
+  // The favorites used to be serialized as an array of view ids
+  let mut value = serde_json::to_value(folder.get_folder_data(&workspace_id).unwrap()).unwrap();
+  value["favorites"] = json!({ "1": ["1", "2"] });
+  let folder_data = serde_json::from_value::<FolderData>(value).unwrap();
+  let favorites = &folder_data.favorites[&uid];
+  assert_eq!(favorites.len(), 2);
+  assert_eq!(favorites[0].id, "1");
+  assert_eq!(favorites[0].timestamp, 0);
+
+  let folder_test = create_folder_with_data(uid.clone(), "w1", folder_data);
+  let favorites = folder_test.get_my_favorite_sections();
+  assert_eq!(favorites.len(), 2);
+  assert_eq!(favorites[1].id, "2");
+  assert_eq!(favorites[1].timestamp, 0);
+}
+
+#[test]
+fn read_legacy_favorite_ids_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  for id in ["1", "2"] {
+    folder.insert_view(make_test_view(id, "w1", vec![]), None);
+  }
+  let time = timestamp();
+  folder.add_favorite_view_ids(vec!["1".to_string()]);
+
+  // Writes a bare view id like the old versions did
+  {
+    let mut txn = folder.collab.transact_mut();
+    let section: MapRef = folder.body.root.get_with_txn(&txn, "section").unwrap();
+    let favorite: MapRef = section.get_with_txn(&txn, "favorite").unwrap();
+    let array: ArrayRef = favorite.get_with_txn(&txn, uid.as_ref()).unwrap();
+    array.push_back(&mut txn, "2");
+  }
+
+  let favorites = folder.get_my_favorite_sections();
+  assert_eq!(favorites.len(), 2);
+  assert_eq!(favorites[0].id, "1");
+  assert!(favorites[0].timestamp >= time);
+  assert_eq!(favorites[1].id, "2");
+  assert_eq!(favorites[1].timestamp, 0);
+
+  folder.delete_favorite_view_ids(vec!["2".to_string()]);
+  let favorites = folder.get_my_favorite_sections();
+  assert_eq!(favorites.len(), 1);
+  assert_eq!(favorites[0].id, "1");
+}
+
+#[test]
+fn migrate_favorite_v1_with_timestamp_test() {
+  let workspace_id = Uuid::new_v4().to_string();
+  let (_cleaner, db_path) = unzip_history_folder_db(FOLDER_WITH_FAV_V1).unwrap();
+  let folder_test = open_folder_with_db(
+    254954554859196416.into(),
+    &workspace_id,
+    "835f64ab-9efc-4365-8055-1e66ee03c555",
+    db_path,
+  );
+  let mut folder = folder_test.folder;
+
+  let time = timestamp();
+  let favorites = folder.get_favorite_v1();
+  folder.add_favorite_view_ids(favorites.into_iter().map(|fav| fav.id).collect::<Vec<_>>());
+  // The migrated favorites are stamped with the time of the migration
+  let favorites = folder.get_my_favorite_sections();
+  assert_eq!(favorites.len(), 2);
+  assert!(favorites.iter().all(|favorite| favorite.timestamp >= time));
+
+  // The favorites are only migrated once
+  assert!(folder.get_favorite_v1().is_empty());
+}
+
+#[test]
+fn hide_favorite_under_trashed_view_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  folder.insert_view(make_test_view("1", "w1", vec![]), None);
+  folder.insert_view(make_test_view("1_1", "1", vec![]), None);
+  folder.insert_view(make_test_view("2", "w1", vec![]), None);
+  folder.add_favorite_view_ids(vec!["1_1".to_string(), "2".to_string()]);
+
+  // The child of the trashed view is hidden from the favorites, but stays in the section
+  folder.add_trash_view_ids(vec!["1".to_string()]);
+  let favorites = folder.get_my_favorite_sections();
+  assert_eq!(favorites.len(), 1);
+  assert_eq!(favorites[0].id, "2");
+  assert_eq!(folder.get_all_favorites_sections().len(), 1);
+  assert!(folder.is_view_in_section(Section::Favorite, "1_1"));
+  assert!(folder.get_view("1_1").is_some());
+
+  folder.delete_trash_view_ids(vec!["1".to_string()]);
+  let favorites = folder.get_my_favorite_sections();
+  assert_eq!(favorites.len(), 2);
+  assert_eq!(favorites[0].id, "1_1");
+}
@@ -1,4 +1,5 @@
 mod child_views_test;
+mod current_view_test;
 mod custom_section;
 mod favorite_test;
 mod load_disk;
@@ -7,5 +8,6 @@ mod serde_test;
 mod space_info_test;
 mod trash_test;
 mod util;
+mod view_access_time_test;
 mod view_test;
 mod workspace_test;
@@ -1,5 +1,5 @@
 use assert_json_diff::assert_json_include;
-use collab_folder::{timestamp, FolderData, Section, UserId};
+use collab_folder::{timestamp, FolderData, Section, UserId, DEFAULT_MAX_RECENT_VIEWS};
 use serde_json::json;
 
 use crate::util::{create_folder_with_data, create_folder_with_workspace, make_test_view};
@@ -193,3 +193,35 @@ fn delete_recent_test() {
   let recent = folder.get_my_recent_sections();
   assert_eq!(recent.len(), 0);
 }
+
+#[test]
+fn latest_recent_views_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let workspace_id = folder_test.get_workspace_id().unwrap();
+
+  let mut folder = folder_test.folder;
+  let view_ids = (0..DEFAULT_MAX_RECENT_VIEWS + 5)
+    .map(|i| format!("view_{}", i))
+    .collect::<Vec<_>>();
+  for view_id in view_ids.iter() {
+    folder.insert_view(make_test_view(view_id, workspace_id.as_str(), vec![]), None);
+  }
+  folder.add_recent_view_ids(view_ids.clone());
+
+  let recent = folder.get_my_latest_recent_sections(DEFAULT_MAX_RECENT_VIEWS);
+  assert_eq!(recent.len(), DEFAULT_MAX_RECENT_VIEWS);
+  assert_eq!(recent[0].id, view_ids[DEFAULT_MAX_RECENT_VIEWS + 4]);
+  assert_eq!(recent[DEFAULT_MAX_RECENT_VIEWS - 1].id, view_ids[5]);
+
+  // Visiting a view again moves it to the front
+  folder.add_recent_view_ids(vec![view_ids[0].clone()]);
+  let recent = folder.get_my_latest_recent_sections(2);
+  assert_eq!(recent.len(), 2);
+  assert_eq!(recent[0].id, view_ids[0]);
+  assert_eq!(recent[1].id, view_ids[DEFAULT_MAX_RECENT_VIEWS + 4]);
+  assert_eq!(
+    folder.get_my_recent_sections().len(),
+    DEFAULT_MAX_RECENT_VIEWS + 5
+  );
+}
@@ -1,11 +1,11 @@
 use collab::core::origin::CollabOrigin;
 use collab::preclude::{Collab, ReadTxn};
-use collab_folder::{timestamp, Folder, FolderData, UserId};
+use collab_folder::{timestamp, Folder, FolderData, UserId, View};
 use serde_json::json;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use crate::util::{create_folder, make_test_view};
+use crate::util::{create_folder, make_test_view, open_folder_with_db, unzip_history_folder_db};
 
 #[test]
 fn folder_json_serde() {
@@ -251,6 +251,35 @@ async fn deserialize_folder_data() {
   }
 }
 
+#[test]
+fn deserialize_view_without_lock_test() {
+  let json = include_str!("../folder_test/history_folder/folder_data.json");
+  let folder_data: FolderData = serde_json::from_str(json).unwrap();
+  assert!(!folder_data.views.is_empty());
+  assert!(folder_data.views.iter().all(|view| !view.is_locked));
+
+  let mut view = make_test_view("v1", "w1", vec![]);
+  view.is_locked = true;
+  let value = serde_json::to_value(&view).unwrap();
+  assert_eq!(value["is_locked"], true);
+  assert_eq!(serde_json::from_value::<View>(value).unwrap(), view);
+}
+
+#[test]
+fn open_history_folder_without_lock_test() {
+  let (_cleaner, db_path) = unzip_history_folder_db("folder_without_fav").unwrap();
+  let folder_test = open_folder_with_db(
+    221439819971039232.into(),
+    "w1",
+    "49af3b85-9343-447a-946d-038f63883399",
+    db_path,
+  );
+  let txn = folder_test.collab.transact();
+  let views = folder_test.body.views.get_all_views(&txn);
+  assert!(!views.is_empty());
+  assert!(views.iter().all(|view| !view.is_locked));
+}
+
 fn get_view_ids_should_be_filtered(folder: &Folder) -> Vec<String> {
   let trash_ids = get_all_trash_ids(folder);
   let other_private_view_ids = get_other_private_view_ids(folder);
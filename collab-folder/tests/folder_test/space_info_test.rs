@@ -1,9 +1,11 @@
 use collab_folder::{
-  hierarchy_builder::ViewExtraBuilder, timestamp, SpacePermission, SPACE_CREATED_AT_KEY,
+  hierarchy_builder::ViewExtraBuilder, timestamp, SpacePermission, UserId, SPACE_CREATED_AT_KEY,
   SPACE_ICON_COLOR_KEY, SPACE_ICON_KEY, SPACE_IS_SPACE_KEY, SPACE_PERMISSION_KEY,
 };
 use serde_json::json;
 
+use crate::util::{create_folder_with_workspace, make_test_view};
+
 #[test]
 fn create_public_space_test() {
   let builder = ViewExtraBuilder::new();
@@ -76,3 +78,95 @@ fn create_non_space_test() {
   let space_info_json = serde_json::to_value(space_info).unwrap();
   assert_json_diff::assert_json_eq!(space_info_json, json!({}),);
 }
+
+#[test]
+fn create_space_in_folder_test() {
+  let uid = UserId::from(1);
+  let mut folder = create_folder_with_workspace(uid.clone(), "w1").folder;
+  folder.insert_view(make_test_view("v1", "w1", vec![]), None);
+  let space = folder
+    .create_space(
+      "General",
+      Some("interface_essential/home-3".to_string()),
+      Some("0xFFA34AFD".to_string()),
+      SpacePermission::PublicToAll,
+    )
+    .unwrap();
+  assert!(space.is_space());
+  assert_eq!(space.parent_view_id, "w1");
+
+  let view = folder.get_view(&space.id).unwrap();
+  assert!(view.is_space());
+  let space_info = view.space_info().unwrap();
+  assert!(matches!(
+    space_info.space_permission,
+    SpacePermission::PublicToAll
+  ));
+  assert_eq!(
+    space_info.space_icon.as_deref(),
+    Some("interface_essential/home-3")
+  );
+  assert!(!folder.get_view("v1").unwrap().is_space());
+
+  // A space whose other extra keys can't be parsed as the typed extra is still a space
+  let mut view = make_test_view("space_2", "w1", vec![]);
+  view.extra = Some(r#"{"is_space":true,"cover":"red","line_height_layout":1}"#.to_string());
+  assert!(view.is_space());
+
+  let spaces = folder.get_all_spaces();
+  assert_eq!(spaces.len(), 1);
+  assert_eq!(spaces[0].id, space.id);
+}
+
+#[test]
+fn update_space_permission_test() {
+  let uid = UserId::from(1);
+  let mut folder = create_folder_with_workspace(uid.clone(), "w1").folder;
+
+  // The extra written by the current clients
+  let mut space = make_test_view("space_1", "w1", vec![]);
+  space.extra = Some(
+    r#"{"is_space":true,"space_icon":"interface_essential/home-3","space_icon_color":"0xFFA34AFD","space_permission":0,"space_created_at":1714971235,"cover":{"type":"0","value":"red"}}"#
+      .to_string(),
+  );
+  folder.insert_view(space, None);
+  folder.insert_view(make_test_view("v1", "w1", vec![]), None);
+  assert!(folder.get_view("space_1").unwrap().is_space());
+  let space_ids = folder
+    .get_all_spaces()
+    .iter()
+    .map(|view| view.id.clone())
+    .collect::<Vec<_>>();
+  assert_eq!(space_ids, vec!["space_1"]);
+
+  let view = folder
+    .update_space_permission("space_1", SpacePermission::Private)
+    .unwrap();
+  let space_info = view.space_info().unwrap();
+  assert!(matches!(
+    space_info.space_permission,
+    SpacePermission::Private
+  ));
+  assert_eq!(space_info.space_created_at, 1714971235);
+  assert_eq!(space_info.space_icon_color.as_deref(), Some("0xFFA34AFD"));
+  let extra: serde_json::Value = serde_json::from_str(view.extra.as_ref().unwrap()).unwrap();
+  assert_eq!(extra[SPACE_PERMISSION_KEY], 1);
+  assert_eq!(extra["cover"]["value"], "red");
+
+  // The extra keys that aren't valid for the typed extra are kept
+  let mut space = make_test_view("space_2", "w1", vec![]);
+  space.extra = Some(r#"{"is_space":true,"space_permission":0,"cover":"red"}"#.to_string());
+  folder.insert_view(space, None);
+  let view = folder
+    .update_space_permission("space_2", SpacePermission::Private)
+    .unwrap();
+  let extra: serde_json::Value = serde_json::from_str(view.extra.as_ref().unwrap()).unwrap();
+  assert_eq!(extra[SPACE_PERMISSION_KEY], 1);
+  assert_eq!(extra["cover"], "red");
+
+  // Only the spaces have a permission
+  assert!(folder
+    .update_space_permission("v1", SpacePermission::Private)
+    .is_none());
+  assert!(folder.get_view("v1").unwrap().extra.is_none());
+}
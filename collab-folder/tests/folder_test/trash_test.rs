@@ -1,9 +1,16 @@
 use std::future::Future;
 use std::time::Duration;
 
-use collab_folder::{SectionChange, SectionChangeReceiver, TrashSectionChange, UserId};
+use collab_folder::{
+  Folder, FolderDataFilter, Section, SectionChange, SectionChangeReceiver, SectionItem,
+  TrashSectionChange, UserId, ViewNameMatch,
+};
+use uuid::Uuid;
 
-use crate::util::{create_folder_with_workspace, make_test_view};
+use crate::util::{
+  create_folder_with_data, create_folder_with_workspace, make_test_view, open_folder_with_db,
+  unzip_history_folder_db,
+};
 
 #[test]
 fn create_trash_test() {
@@ -69,6 +76,7 @@ async fn create_trash_callback_test() {
       },
       TrashSectionChange::TrashItemRemoved { .. } => {},
     },
+    SectionChange::Favorite(_) => {},
   }))
   .await;
 }
@@ -92,10 +100,338 @@ async fn delete_trash_view_ids_callback_test() {
         assert_eq!(ids, vec!["1", "2"]);
       },
     },
+    SectionChange::Favorite(_) => {},
   }))
   .await;
 }
 
+#[tokio::test]
+async fn move_view_with_descendants_to_trash_test() {
+  let uid = UserId::from(1);
+  let mut folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut section_rx = folder_test.section_rx.take().unwrap();
+  let folder = &mut folder_test.folder;
+  folder.insert_view(make_test_view("v1", "w1", vec![]), None);
+  folder.insert_view(make_test_view("v2", "w1", vec![]), None);
+  folder.insert_view(make_test_view("v1_1", "v1", vec![]), None);
+  folder.insert_view(make_test_view("v1_2", "v1", vec![]), None);
+  folder.insert_view(make_test_view("v1_1_1", "v1_1", vec![]), None);
+
+  let trashed_ids = folder.move_view_to_trash("v1");
+  assert_eq!(trashed_ids, vec!["v1", "v1_1", "v1_1_1", "v1_2"]);
+  let trash_ids = folder
+    .get_my_trash_sections()
+    .into_iter()
+    .map(|item| item.id)
+    .collect::<Vec<_>>();
+  assert_eq!(trash_ids, trashed_ids);
+  // Only the trashed view is detached, its descendants stay attached to it
+  assert_eq!(child_ids(folder, "w1"), vec!["v2"]);
+  assert_eq!(child_ids(folder, "v1"), vec!["v1_1", "v1_2"]);
+  assert_eq!(child_ids(folder, "v1_1"), vec!["v1_1_1"]);
+  assert_eq!(folder.get_view("v1").unwrap().parent_view_id, "w1");
+
+  // The trashed views are still exported
+  let folder_data = folder.get_folder_data("w1").unwrap();
+  for id in trashed_ids.iter() {
+    assert!(folder_data.views.iter().any(|view| &view.id == id));
+  }
+
+  match section_rx.try_recv().unwrap() {
+    SectionChange::Trash(TrashSectionChange::TrashItemAdded { ids }) => {
+      assert_eq!(ids, trashed_ids)
+    },
+    change => panic!("unexpected change: {:?}", change),
+  }
+
+  // Restoring a view that is not in the trash does nothing
+  assert!(folder.restore_from_trash("v3").is_empty());
+
+  let restored_ids = folder.restore_from_trash("v1");
+  assert_eq!(restored_ids, trashed_ids);
+  assert!(folder.get_my_trash_sections().is_empty());
+  assert_eq!(child_ids(folder, "w1"), vec!["v2", "v1"]);
+  assert_eq!(child_ids(folder, "v1"), vec!["v1_1", "v1_2"]);
+  assert_eq!(child_ids(folder, "v1_1"), vec!["v1_1_1"]);
+}
+
+#[test]
+fn restore_trash_to_original_parent_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  folder.insert_view(make_test_view("v1", "w1", vec![]), None);
+  folder.insert_view(make_test_view("v2", "w1", vec![]), None);
+  folder.insert_view(make_test_view("v1_1", "v1", vec![]), None);
+
+  // The view that is trashed by updating it stays in its parent
+  folder.update_view("v1_1", |update| update.set_trash(true).done());
+  folder.move_view_to_trash("v2");
+  let trash = folder.get_my_trash_sections();
+  assert_eq!(trash[0].original_parent_id.as_deref(), Some("v1"));
+  assert_eq!(trash[1].original_parent_id.as_deref(), Some("w1"));
+
+  // The ids that are not in the trash are ignored
+  let restored_ids =
+    folder.restore_trash(vec!["v1_1".to_string(), "v2".to_string(), "v3".to_string()]);
+  assert_eq!(restored_ids, vec!["v1_1", "v2"]);
+  assert!(folder.get_my_trash_sections().is_empty());
+  assert_eq!(child_ids(&folder, "w1"), vec!["v1", "v2"]);
+  assert_eq!(child_ids(&folder, "v1"), vec!["v1_1"]);
+}
+
+#[test]
+fn restore_trash_without_original_parent_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  folder.insert_view(make_test_view("v1", "w1", vec![]), None);
+  folder.insert_view(make_test_view("v2", "w1", vec![]), None);
+  folder.insert_view(make_test_view("v1_1", "v1", vec![]), None);
+  folder.insert_view(make_test_view("v2_1", "v2", vec![]), None);
+
+  // The parent is still in the trash
+  folder.move_view_to_trash("v1_1");
+  folder.move_view_to_trash("v1");
+  assert_eq!(folder.restore_trash(vec!["v1_1".to_string()]), vec!["v1_1"]);
+  assert_eq!(folder.get_view("v1_1").unwrap().parent_view_id, "w1");
+  assert_eq!(child_ids(&folder, "w1"), vec!["v2", "v1_1"]);
+
+  // The parent was deleted
+  folder.move_view_to_trash("v2_1");
+  folder.delete_views(vec!["v2"]);
+  assert_eq!(folder.restore_trash(vec!["v2_1".to_string()]), vec!["v2_1"]);
+  assert_eq!(folder.get_view("v2_1").unwrap().parent_view_id, "w1");
+  assert_eq!(child_ids(&folder, "w1"), vec!["v1_1", "v2_1"]);
+
+  let trash_ids = folder
+    .get_my_trash_sections()
+    .into_iter()
+    .map(|item| item.id)
+    .collect::<Vec<_>>();
+  assert_eq!(trash_ids, vec!["v1"]);
+}
+
+#[test]
+fn restore_trash_in_history_folder_test() {
+  const GETTING_STARTED_VIEW_ID: &str = "ddf06dcf-1a01-4d0d-b973-9d6a892f68b5";
+  const DOCUMENT_VIEW_ID: &str = "9330d783-d10d-4a15-84d3-1fa4fa2e8cc4";
+
+  let (_cleaner, db_path) = unzip_history_folder_db("folder_with_fav_v1").unwrap();
+  let folder_test = open_folder_with_db(
+    254954554859196416.into(),
+    &Uuid::new_v4().to_string(),
+    "835f64ab-9efc-4365-8055-1e66ee03c555",
+    db_path,
+  );
+  let mut folder = folder_test.folder;
+  folder
+    .body
+    .migrate_workspace_to_view(&mut folder.collab.transact_mut());
+
+  // The trash items written by the old versions don't have the original parent
+  assert!(folder
+    .get_my_trash_sections()
+    .iter()
+    .all(|item| item.original_parent_id.is_none()));
+
+  folder.move_view_to_trash(DOCUMENT_VIEW_ID);
+  let trash = folder.get_my_trash_sections();
+  let item = trash
+    .iter()
+    .find(|item| item.id == DOCUMENT_VIEW_ID)
+    .unwrap();
+  assert_eq!(
+    item.original_parent_id.as_deref(),
+    Some(GETTING_STARTED_VIEW_ID)
+  );
+  assert!(!child_ids(&folder, GETTING_STARTED_VIEW_ID).contains(&DOCUMENT_VIEW_ID.to_string()));
+
+  assert_eq!(
+    folder.restore_trash(vec![DOCUMENT_VIEW_ID.to_string()]),
+    vec![DOCUMENT_VIEW_ID]
+  );
+  assert_eq!(
+    folder.get_view(DOCUMENT_VIEW_ID).unwrap().parent_view_id,
+    GETTING_STARTED_VIEW_ID
+  );
+  assert!(child_ids(&folder, GETTING_STARTED_VIEW_ID).contains(&DOCUMENT_VIEW_ID.to_string()));
+  assert!(folder
+    .get_my_trash_sections()
+    .iter()
+    .all(|item| item.id != DOCUMENT_VIEW_ID));
+}
+
+#[test]
+fn view_under_trashed_view_is_effectively_trashed_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  for (view_id, parent_id) in [
+    ("v1", "w1"),
+    ("v1_1", "v1"),
+    ("v1_1_1", "v1_1"),
+    ("v2", "w1"),
+  ] {
+    let mut view = make_test_view(view_id, parent_id, vec![]);
+    view.name = format!("note {}", view_id);
+    folder.insert_view(view, None);
+  }
+
+  folder.add_trash_view_ids(vec!["v1_1".to_string()]);
+  assert!(!folder.is_view_effectively_trashed("v1"));
+  assert!(folder.is_view_effectively_trashed("v1_1"));
+  assert!(folder.is_view_effectively_trashed("v1_1_1"));
+  assert!(!folder.is_view_effectively_trashed("v2"));
+
+  let found_ids = folder
+    .search_views("note", ViewNameMatch::Prefix, 10)
+    .into_iter()
+    .map(|view| view.id)
+    .collect::<Vec<_>>();
+  assert_eq!(found_ids, vec!["v1", "v2"]);
+
+  // The trashed views are only left out of the folder data on demand
+  let folder_data = folder.get_folder_data("w1").unwrap();
+  assert_eq!(folder_data.views.len(), 4);
+  let folder_data = folder.get_folder_data_without_trashed_views("w1").unwrap();
+  let view_ids = folder_data
+    .views
+    .iter()
+    .map(|view| view.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(view_ids, vec!["v1", "v2"]);
+  assert!(folder_data.views[0].children.items.is_empty());
+
+  // The views are not removed from the folder
+  assert!(folder.get_view("v1_1_1").is_some());
+  assert_eq!(child_ids(&folder, "v1_1"), vec!["v1_1_1"]);
+}
+
+#[test]
+fn export_folder_data_without_trash_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  for (view_id, parent_id) in [
+    ("v1", "w1"),
+    ("v1_1", "v1"),
+    ("v1_1_1", "v1_1"),
+    ("v1_2", "v1"),
+    ("v2", "w1"),
+  ] {
+    folder.insert_view(make_test_view(view_id, parent_id, vec![]), None);
+  }
+  folder.add_favorite_view_ids(vec!["v1_1_1".to_string(), "v2".to_string()]);
+  folder.add_trash_view_ids(vec!["v1_1".to_string()]);
+  folder.set_current_view("v1_1_1".to_string());
+
+  // The favorites of the other users are kept in the folder
+  {
+    let mut txn = folder.collab.transact_mut();
+    let op = folder
+      .body
+      .section
+      .section_op(&txn, Section::Favorite)
+      .unwrap();
+    op.add_sections_for_user_with_txn(
+      &mut txn,
+      &UserId::from(2),
+      vec![SectionItem::new("v1".to_string())],
+    );
+  }
+  let folder_data = folder.get_folder_data("w1").unwrap();
+  assert_eq!(folder_data.favorites.len(), 2);
+
+  let folder_data = folder
+    .get_folder_data_filtered(
+      "w1",
+      FolderDataFilter {
+        exclude_trash: true,
+        only_current_user_sections: true,
+        root_view_id: None,
+      },
+    )
+    .unwrap();
+  let view_ids = folder_data
+    .views
+    .iter()
+    .map(|view| view.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(view_ids, vec!["v1", "v1_2", "v2"]);
+  let child_ids = folder_data.views[0]
+    .children
+    .iter()
+    .map(|child| child.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(child_ids, vec!["v1_2"]);
+
+  // The sections and the current view don't refer to the trashed views
+  assert_eq!(folder_data.favorites.len(), 1);
+  let favorite_ids = folder_data.favorites[&uid]
+    .iter()
+    .map(|item| item.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(favorite_ids, vec!["v2"]);
+  assert!(folder_data.trash[&uid].is_empty());
+  assert_eq!(folder_data.current_view, "");
+}
+
+#[test]
+fn export_subtree_folder_data_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  for (view_id, parent_id) in [
+    ("v1", "w1"),
+    ("v1_1", "v1"),
+    ("v1_1_1", "v1_1"),
+    ("v1_2", "v1"),
+    ("v2", "w1"),
+  ] {
+    folder.insert_view(make_test_view(view_id, parent_id, vec![]), None);
+  }
+  folder.add_favorite_view_ids(vec!["v1_1_1".to_string(), "v2".to_string()]);
+  folder.add_trash_view_ids(vec!["v1_2".to_string()]);
+
+  let filter = FolderDataFilter {
+    exclude_trash: true,
+    root_view_id: Some("v1_1".to_string()),
+    ..Default::default()
+  };
+  let folder_data = folder.get_folder_data_filtered("w1", filter).unwrap();
+  let view_ids = folder_data
+    .views
+    .iter()
+    .map(|view| view.id.as_str())
+    .collect::<Vec<_>>();
+  assert_eq!(view_ids, vec!["v1_1", "v1_1_1"]);
+  assert_eq!(folder_data.views[0].parent_view_id, "w1");
+  assert_eq!(folder_data.workspace.child_views.items[0].id, "v1_1");
+  assert_eq!(folder_data.workspace.child_views.len(), 1);
+  assert_eq!(folder_data.favorites[&uid].len(), 1);
+  assert_eq!(folder_data.favorites[&uid][0].id, "v1_1_1");
+
+  // The exported data can be used to create a folder
+  let folder_test = create_folder_with_data(uid.clone(), "w1", folder_data);
+  assert_eq!(child_ids(&folder_test, "w1"), vec!["v1_1"]);
+  assert_eq!(child_ids(&folder_test, "v1_1"), vec!["v1_1_1"]);
+
+  let filter = FolderDataFilter {
+    root_view_id: Some("not_exist".to_string()),
+    ..Default::default()
+  };
+  assert!(folder.get_folder_data_filtered("w1", filter).is_none());
+}
+
+fn child_ids(folder: &Folder, parent_id: &str) -> Vec<String> {
+  folder
+    .get_views_belong_to(parent_id)
+    .iter()
+    .map(|view| view.id.clone())
+    .collect()
+}
+
 async fn poll_tx(mut rx: SectionChangeReceiver, callback: impl Fn(SectionChange)) {
   while let Ok(change) = rx.recv().await {
     callback(change)
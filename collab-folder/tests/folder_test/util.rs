@@ -23,13 +23,13 @@ pub struct FolderTest {
   pub folder: Folder,
 
   #[allow(dead_code)]
-  db: Arc<CollabKVDB>,
+  pub(crate) db: Arc<CollabKVDB>,
 
   #[allow(dead_code)]
   cleaner: Cleaner,
 
   #[allow(dead_code)]
-  view_rx: ViewChangeReceiver,
+  pub(crate) view_rx: ViewChangeReceiver,
 
   #[allow(dead_code)]
   pub(crate) section_rx: Option<SectionChangeReceiver>,
@@ -149,6 +149,7 @@ pub fn make_test_view(view_id: &str, parent_view_id: &str, belongings: Vec<Strin
     last_edited_time: 0,
     last_edited_by: None,
     extra: None,
+    is_locked: false,
   }
 }
 
@@ -0,0 +1,95 @@
+use assert_json_diff::assert_json_include;
+use collab_folder::{timestamp, FolderData, UserId};
+use serde_json::json;
+
+use crate::util::{create_folder_with_data, create_folder_with_workspace, make_test_view};
+
+#[test]
+fn set_last_viewed_test() {
+  let uid = UserId::from(1);
+  let mut folder = create_folder_with_workspace(uid.clone(), "w1").folder;
+  for id in ["1", "2", "3"] {
+    folder.insert_view(make_test_view(id, "w1", vec![]), None);
+  }
+  assert!(folder.get_last_viewed("1").is_none());
+  assert!(folder.get_recently_viewed(10).is_empty());
+
+  let time = timestamp();
+  folder.set_last_viewed("1");
+  folder.set_last_viewed("2");
+  folder.set_last_viewed("3");
+  assert!(folder.get_last_viewed("1").unwrap() >= time);
+
+  // Opening a view again moves it to the front
+  folder.set_last_viewed("1");
+  let ids = folder
+    .get_recently_viewed(10)
+    .into_iter()
+    .map(|item| item.id)
+    .collect::<Vec<_>>();
+  assert_eq!(ids, vec!["1", "3", "2"]);
+
+  let items = folder.get_recently_viewed(2);
+  assert_eq!(items.len(), 2);
+  assert_eq!(items[0].id, "1");
+}
+
+#[test]
+fn view_access_times_serde_test() {
+  let uid = UserId::from(1);
+  let mut folder = create_folder_with_workspace(uid.clone(), "w1").folder;
+  folder.insert_view(make_test_view("1", "w1", vec![]), None);
+  folder.set_last_viewed("1");
+  let last_viewed = folder.get_last_viewed("1").unwrap();
+
+  let folder_data = folder.get_folder_data("w1").unwrap();
+  let value = serde_json::to_value(&folder_data).unwrap();
+  assert_json_include!(
+    actual: value,
+    expected: json!({
+      "view_access_times": {
+        "1": [
+          {
+            "id": "1",
+            "timestamp": last_viewed,
+          }
+        ]
+      }
+    })
+  );
+  let folder_data = serde_json::from_value::<FolderData>(value).unwrap();
+  let folder = create_folder_with_data(uid, "w1", folder_data);
+  assert_eq!(folder.get_last_viewed("1"), Some(last_viewed));
+
+  // The folder data exported before the access times were introduced
+  let mut value = serde_json::to_value(folder.get_folder_data("w1").unwrap()).unwrap();
+  value.as_object_mut().unwrap().remove("view_access_times");
+  let folder_data = serde_json::from_value::<FolderData>(value).unwrap();
+  assert!(folder_data.view_access_times.is_empty());
+}
+
+#[test]
+fn view_access_times_of_multiple_users_test() {
+  let uid_1 = UserId::from(1);
+  let mut folder_1 = create_folder_with_workspace(uid_1.clone(), "w1").folder;
+  folder_1.insert_view(make_test_view("1", "w1", vec![]), None);
+  folder_1.insert_view(make_test_view("2", "w1", vec![]), None);
+  folder_1.set_last_viewed("1");
+
+  // User 2 doesn't receive the access times of user 1
+  let folder_data = folder_1.get_folder_data("w1").unwrap();
+  let uid_2 = UserId::from(2);
+  let mut folder_2 = create_folder_with_data(uid_2.clone(), "w1", folder_data).folder;
+  assert!(folder_2.get_last_viewed("1").is_none());
+  assert!(folder_2.get_recently_viewed(10).is_empty());
+
+  folder_2.set_last_viewed("2");
+  assert!(folder_2.get_last_viewed("2").is_some());
+  let folder_data = folder_2.get_folder_data("w1").unwrap();
+  assert!(!folder_data.view_access_times.contains_key(&uid_1));
+  assert_eq!(folder_data.view_access_times[&uid_2].len(), 1);
+
+  // User 1's access times are not changed by user 2
+  assert!(folder_1.get_last_viewed("2").is_none());
+  assert_eq!(folder_1.get_recently_viewed(10).len(), 1);
+}
@@ -1,7 +1,20 @@
-use crate::util::{create_folder_with_workspace, make_test_view, setup_log};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use crate::util::{create_folder_with_workspace, make_test_view, setup_log, FolderTest};
 use collab::core::collab::IndexContent;
+use collab::core::origin::CollabOrigin;
+use collab::preclude::updates::decoder::Decode;
+use collab::preclude::{ReadTxn, Update};
 use collab_folder::folder_diff::FolderViewChange;
-use collab_folder::{timestamp, IconType, UserId, ViewIcon, ViewIndexContent};
+use collab_folder::{
+  timestamp, Folder, IconType, UserId, ViewChange, ViewChangeReceiver, ViewCover, ViewExtra,
+  ViewIcon, ViewIndexContent, ViewLayout, ViewNameMatch, SPACE_CREATED_AT_KEY, SPACE_ICON_KEY,
+  SPACE_IS_SPACE_KEY,
+};
+use collab_plugins::local_storage::kv::doc::CollabKVAction;
+use collab_plugins::local_storage::kv::KVTransactionDB;
+use tokio::sync::broadcast::error::TryRecvError;
 
 #[test]
 fn create_view_test() {
@@ -160,6 +173,59 @@ fn update_view_icon_test() {
   assert!(r_view.last_edited_time >= time);
 }
 
+#[test]
+fn update_view_extra_typed_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+
+  let mut folder = folder_test.folder;
+  let mut txn = folder.collab.transact_mut();
+
+  let mut o_view = make_test_view("v1", "w1", vec![]);
+  o_view.extra = Some(
+    serde_json::json!({
+      SPACE_IS_SPACE_KEY: true,
+      SPACE_ICON_KEY: "interface_essential/home-3",
+      SPACE_CREATED_AT_KEY: 1,
+      "unknown_key": {"a": 1},
+    })
+    .to_string(),
+  );
+  folder.body.views.insert(&mut txn, o_view, None);
+
+  let r_view = folder.body.views.get_view(&txn, "v1").unwrap();
+  let mut extra = r_view.get_extra_typed().unwrap();
+  assert_eq!(extra.is_space, Some(true));
+  assert!(extra.cover.is_none());
+  extra.cover = Some(ViewCover {
+    ty: "0".to_string(),
+    value: "#FFFFFF".to_string(),
+  });
+  extra.font_layout = Some("large".to_string());
+  folder
+    .body
+    .views
+    .update_view(&mut txn, "v1", |update| {
+      update.set_extra_typed(extra.clone()).done()
+    })
+    .unwrap();
+
+  // The keys that are unknown to ViewExtra are kept
+  let r_view = folder.body.views.get_view(&txn, "v1").unwrap();
+  assert_eq!(r_view.get_extra_typed().unwrap(), extra);
+  let json: serde_json::Value = serde_json::from_str(r_view.extra.as_ref().unwrap()).unwrap();
+  assert_eq!(json[SPACE_IS_SPACE_KEY], true);
+  assert_eq!(json[SPACE_ICON_KEY], "interface_essential/home-3");
+  assert_eq!(json["unknown_key"]["a"], 1);
+  assert_eq!(json["cover"]["type"], "0");
+  assert_eq!(json["font_layout"], "large");
+  assert!(r_view.space_info().is_some());
+
+  // A view without extra returns an empty ViewExtra
+  let view = make_test_view("v2", "w1", vec![]);
+  assert_eq!(view.get_extra_typed().unwrap(), ViewExtra::default());
+}
+
 #[test]
 fn different_icon_ty_test() {
   let uid = UserId::from(1);
@@ -511,3 +577,516 @@ fn compare_diff_view_test() {
     view_id: "v2".to_string(),
   }));
 }
+
+#[test]
+fn insert_views_in_one_update_test() {
+  let uid = UserId::from(1);
+  let mut folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let number_of_updates = |folder_test: &FolderTest| {
+    folder_test
+      .db
+      .read_txn()
+      .get_all_updates(uid.as_i64(), "w1", "w1")
+      .unwrap()
+      .len()
+  };
+  let updates_before = number_of_updates(&folder_test);
+
+  // The parent is inserted before its children
+  let parent_view_ids = (0..150).map(|i| format!("v{}", i)).collect::<Vec<_>>();
+  let child_view_ids = (0..50).map(|i| format!("v0_{}", i)).collect::<Vec<_>>();
+  let views = parent_view_ids
+    .iter()
+    .map(|view_id| make_test_view(view_id, "w1", vec![]))
+    .chain(
+      child_view_ids
+        .iter()
+        .map(|view_id| make_test_view(view_id, "v0", vec![])),
+    )
+    .collect::<Vec<_>>();
+  folder_test.insert_views(views);
+  assert_eq!(number_of_updates(&folder_test), updates_before + 1);
+
+  let child_ids = |parent_id: &str| {
+    folder_test
+      .get_views_belong_to(parent_id)
+      .iter()
+      .map(|view| view.id.clone())
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(child_ids("w1"), parent_view_ids);
+  assert_eq!(child_ids("v0"), child_view_ids);
+
+  // The receiver lags behind the single notifications, the batch one is sent last
+  let mut created_views = vec![];
+  loop {
+    match folder_test.view_rx.try_recv() {
+      Ok(ViewChange::DidCreateViews { views }) => created_views.push(views),
+      Ok(_) | Err(TryRecvError::Lagged(_)) => {},
+      Err(_) => break,
+    }
+  }
+  assert_eq!(created_views.len(), 1);
+  assert_eq!(created_views[0].len(), 200);
+}
+
+#[test]
+fn insert_views_notify_each_created_view_test() {
+  let uid = UserId::from(1);
+  let mut folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let views = ["v1", "v2", "v3"]
+    .iter()
+    .map(|view_id| make_test_view(view_id, "w1", vec![]))
+    .collect::<Vec<_>>();
+  folder_test.insert_views(views);
+
+  let mut created_view_ids = vec![];
+  let mut created_views = vec![];
+  while let Ok(change) = folder_test.view_rx.try_recv() {
+    match change {
+      ViewChange::DidCreateView { view } => created_view_ids.push(view.id),
+      ViewChange::DidCreateViews { views } => created_views.push(views),
+      _ => {},
+    }
+  }
+  assert_eq!(created_view_ids, vec!["v1", "v2", "v3"]);
+  assert_eq!(created_views.len(), 1);
+  assert_eq!(
+    created_views[0]
+      .iter()
+      .map(|view| view.id.as_str())
+      .collect::<Vec<_>>(),
+    vec!["v1", "v2", "v3"]
+  );
+}
+
+#[test]
+fn search_views_by_name_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  for (view_id, name) in [
+    ("v1", "Getting started"),
+    ("v2", "Café menu"),
+    ("v3", "CAFÉ notes"),
+    ("v4", "日本語のノート"),
+    ("v5", "Old café"),
+  ] {
+    let mut view = make_test_view(view_id, "w1", vec![]);
+    view.name = name.to_string();
+    folder.insert_view(view, None);
+  }
+
+  let search = |query: &str, match_mode: ViewNameMatch, limit: usize| {
+    folder
+      .search_views(query, match_mode, limit)
+      .into_iter()
+      .map(|view| view.id)
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(
+    search("café", ViewNameMatch::Contains, 10),
+    vec!["v3", "v2", "v5"]
+  );
+  assert_eq!(search("Café", ViewNameMatch::Prefix, 10), vec!["v3", "v2"]);
+  assert_eq!(search("café", ViewNameMatch::Contains, 1), vec!["v3"]);
+  assert_eq!(search("ノート", ViewNameMatch::Contains, 10), vec!["v4"]);
+  assert!(search("ノート", ViewNameMatch::Prefix, 10).is_empty());
+  assert!(search("  ", ViewNameMatch::Contains, 10).is_empty());
+
+  let views = folder.search_views("getting", ViewNameMatch::Prefix, 10);
+  assert_eq!(views[0].name, "Getting started");
+  assert_eq!(views[0].parent_view_id, "w1");
+}
+
+#[test]
+fn search_views_skip_trash_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  for view_id in ["v1", "v2"] {
+    let mut view = make_test_view(view_id, "w1", vec![]);
+    view.name = format!("Note {}", view_id);
+    folder.insert_view(view, None);
+  }
+
+  folder.add_trash_view_ids(vec!["v1".to_string()]);
+  let views = folder.search_views("note", ViewNameMatch::Contains, 10);
+  assert_eq!(views.len(), 1);
+  assert_eq!(views[0].id, "v2");
+
+  folder.delete_trash_view_ids(vec!["v1".to_string()]);
+  assert_eq!(
+    folder
+      .search_views("note", ViewNameMatch::Contains, 10)
+      .len(),
+    2
+  );
+}
+
+#[test]
+fn get_view_ancestors_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  folder.insert_view(make_test_view("v1", "w1", vec![]), None);
+  folder.insert_view(make_test_view("v2", "v1", vec![]), None);
+  folder.insert_view(make_test_view("v3", "v2", vec![]), None);
+  folder.insert_view(make_test_view("v4", "v3", vec![]), None);
+
+  let ancestor_ids = |view_id: &str| {
+    folder
+      .get_view_ancestors(view_id)
+      .iter()
+      .map(|view| view.id.clone())
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(ancestor_ids("v4"), vec!["v3", "v2", "v1", "w1"]);
+  assert_eq!(ancestor_ids("v1"), vec!["w1"]);
+  assert!(ancestor_ids("w1").is_empty());
+  assert!(ancestor_ids("unknown").is_empty());
+
+  assert!(folder.is_ancestor_of("w1", "v4"));
+  assert!(folder.is_ancestor_of("v2", "v4"));
+  assert!(!folder.is_ancestor_of("v4", "v2"));
+  assert!(!folder.is_ancestor_of("v4", "v4"));
+}
+
+#[test]
+fn get_view_ancestors_with_cycle_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  // c1 and c2 are the parent of each other
+  folder.insert_view(make_test_view("c1", "c2", vec![]), None);
+  folder.insert_view(make_test_view("c2", "c1", vec![]), None);
+  folder.insert_view(make_test_view("v1", "c1", vec![]), None);
+  folder.insert_view(make_test_view("v2", "missing", vec![]), None);
+
+  let ancestor_ids = |view_id: &str| {
+    folder
+      .get_view_ancestors(view_id)
+      .iter()
+      .map(|view| view.id.clone())
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(ancestor_ids("c1"), vec!["c2"]);
+  assert_eq!(ancestor_ids("v1"), vec!["c1", "c2"]);
+  assert!(ancestor_ids("v2").is_empty());
+  assert!(!folder.is_ancestor_of("w1", "v1"));
+}
+
+#[tokio::test]
+async fn move_view_to_other_parent_event_test() {
+  let uid = UserId::from(1);
+  let mut folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  folder_test.insert_view(make_test_view("v1", "w1", vec![]), None);
+  folder_test.insert_view(make_test_view("v2", "w1", vec![]), None);
+  folder_test.insert_view(make_test_view("v2_1", "v2", vec![]), None);
+  folder_test.insert_view(make_test_view("v1_1", "v1", vec![]), None);
+
+  folder_test.move_nested_view("v1_1", "v2", Some("v2_1".to_string()));
+  let (view_id, old_parent_id, new_parent_id, index) =
+    wait_for_move_view_event(&mut folder_test.view_rx).await;
+  assert_eq!(view_id, "v1_1");
+  assert_eq!(old_parent_id, "v1");
+  assert_eq!(new_parent_id, "v2");
+  assert_eq!(index, Some(1));
+
+  // The move that is applied from a remote update is reported too
+  let mut remote_folder = Folder::from_collab_doc_state(
+    uid.clone(),
+    CollabOrigin::Empty,
+    folder_test.encode_collab().unwrap().into(),
+    "w1",
+    vec![],
+  )
+  .unwrap();
+  remote_folder.move_nested_view("v1_1", "w1", None);
+  let update = {
+    let state_vector = folder_test.collab.transact().state_vector();
+    remote_folder
+      .collab
+      .transact()
+      .encode_state_as_update_v1(&state_vector)
+  };
+  folder_test
+    .collab
+    .apply_update(Update::decode_v1(&update).unwrap())
+    .unwrap();
+  let (view_id, old_parent_id, new_parent_id, index) =
+    wait_for_move_view_event(&mut folder_test.view_rx).await;
+  assert_eq!(view_id, "v1_1");
+  assert_eq!(old_parent_id, "v2");
+  assert_eq!(new_parent_id, "w1");
+  assert_eq!(index, Some(0));
+  assert_eq!(folder_test.get_view("v1_1").unwrap().parent_view_id, "w1");
+}
+
+async fn wait_for_move_view_event(
+  view_rx: &mut ViewChangeReceiver,
+) -> (String, String, String, Option<u32>) {
+  tokio::time::timeout(Duration::from_secs(2), async {
+    loop {
+      if let ViewChange::DidMoveView {
+        view_id,
+        old_parent_id,
+        new_parent_id,
+        index,
+      } = view_rx.recv().await.unwrap()
+      {
+        return (view_id, old_parent_id, new_parent_id, index);
+      }
+    }
+  })
+  .await
+  .unwrap()
+}
+
+#[tokio::test]
+async fn move_views_test() {
+  let uid = UserId::from(1);
+  let mut folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  for (view_id, parent_id) in [
+    ("v1", "w1"),
+    ("v2", "w1"),
+    ("v3", "w1"),
+    ("v1_1", "v1"),
+    ("v1_2", "v1"),
+    ("v2_1", "v2"),
+  ] {
+    folder_test.insert_view(make_test_view(view_id, parent_id, vec![]), None);
+  }
+  while folder_test.view_rx.try_recv().is_ok() {}
+
+  // The views come from different parents, and v1_1 is already under the new parent
+  let moved_views = folder_test.move_views(
+    vec![
+      "v2_1".to_string(),
+      "v1_1".to_string(),
+      "v3".to_string(),
+      "missing".to_string(),
+    ],
+    "v1",
+    Some(0),
+  );
+  assert_eq!(moved_views.len(), 3);
+  let child_ids = |folder: &Folder, parent_id: &str| {
+    folder
+      .get_view(parent_id)
+      .unwrap()
+      .children
+      .iter()
+      .map(|child| child.id.clone())
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(
+    child_ids(&folder_test.folder, "v1"),
+    vec!["v2_1", "v1_1", "v3", "v1_2"]
+  );
+  assert!(child_ids(&folder_test.folder, "v2").is_empty());
+  assert_eq!(child_ids(&folder_test.folder, "w1"), vec!["v1", "v2"]);
+  for view_id in ["v2_1", "v1_1", "v3"] {
+    assert_eq!(folder_test.get_view(view_id).unwrap().parent_view_id, "v1");
+  }
+
+  // The views that changed their parent are reported in one event
+  let moves = tokio::time::timeout(Duration::from_secs(2), async {
+    loop {
+      if let ViewChange::DidMoveViews { moves } = folder_test.view_rx.recv().await.unwrap() {
+        return moves;
+      }
+    }
+  })
+  .await
+  .unwrap();
+  let moves = moves
+    .into_iter()
+    .map(|moved_view| {
+      (
+        moved_view.view_id,
+        moved_view.old_parent_id,
+        moved_view.index,
+      )
+    })
+    .collect::<Vec<_>>();
+  assert_eq!(moves.len(), 2);
+  assert!(moves.contains(&("v2_1".to_string(), "v2".to_string(), Some(0))));
+  assert!(moves.contains(&("v3".to_string(), "w1".to_string(), Some(2))));
+
+  // Moving the views under the same parent only reorders them
+  folder_test.move_views(vec!["v1_2".to_string(), "v2_1".to_string()], "v1", None);
+  assert_eq!(
+    child_ids(&folder_test.folder, "v1"),
+    vec!["v1_1", "v3", "v1_2", "v2_1"]
+  );
+}
+
+#[tokio::test]
+async fn update_view_icon_event_test() {
+  let uid = UserId::from(1);
+  let mut folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  folder_test.insert_view(make_test_view("v1", "w1", vec![]), None);
+  while folder_test.view_rx.try_recv().is_ok() {}
+
+  let icon = ViewIcon {
+    ty: IconType::Emoji,
+    value: "🎉".to_string(),
+  };
+  folder_test.update_view("v1", |update| update.set_icon(Some(icon.clone())).done());
+  assert_eq!(
+    drain_icon_events(&mut folder_test.view_rx),
+    vec![("v1".to_string(), Some(icon.clone()))]
+  );
+
+  // Updating the other properties doesn't report the icon
+  folder_test.update_view("v1", |update| {
+    update
+      .set_name("new name")
+      .set_layout(ViewLayout::Grid)
+      .done()
+  });
+  assert!(drain_icon_events(&mut folder_test.view_rx).is_empty());
+
+  // The icon that is changed by a remote update is reported too
+  let mut remote_folder = Folder::from_collab_doc_state(
+    uid.clone(),
+    CollabOrigin::Empty,
+    folder_test.encode_collab().unwrap().into(),
+    "w1",
+    vec![],
+  )
+  .unwrap();
+  remote_folder.update_view("v1", |update| update.set_icon(None).done());
+  let update = {
+    let state_vector = folder_test.collab.transact().state_vector();
+    remote_folder
+      .collab
+      .transact()
+      .encode_state_as_update_v1(&state_vector)
+  };
+  folder_test
+    .collab
+    .apply_update(Update::decode_v1(&update).unwrap())
+    .unwrap();
+  assert_eq!(
+    drain_icon_events(&mut folder_test.view_rx),
+    vec![("v1".to_string(), None)]
+  );
+}
+
+fn drain_icon_events(view_rx: &mut ViewChangeReceiver) -> Vec<(String, Option<ViewIcon>)> {
+  let mut icon_events = vec![];
+  while let Ok(change) = view_rx.try_recv() {
+    if let ViewChange::DidUpdateViewIcon { view_id, icon } = change {
+      icon_events.push((view_id, icon));
+    }
+  }
+  icon_events
+}
+
+#[tokio::test]
+async fn lock_view_test() {
+  let uid = UserId::from(1);
+  let mut folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  folder_test.insert_view(make_test_view("v1", "w1", vec![]), None);
+  folder_test.insert_view(make_test_view("v1_1", "v1", vec![]), None);
+  folder_test.insert_view(make_test_view("v2", "w1", vec![]), None);
+  assert!(!folder_test.get_view("v1").unwrap().is_locked);
+  assert!(!folder_test.is_view_locked("v1_1"));
+  while folder_test.view_rx.try_recv().is_ok() {}
+
+  // Locking a view locks its descendants
+  folder_test.update_view("v1", |update| update.set_locked(true).done());
+  assert!(folder_test.get_view("v1").unwrap().is_locked);
+  assert!(!folder_test.get_view("v1_1").unwrap().is_locked);
+  assert!(folder_test.is_view_locked("v1"));
+  assert!(folder_test.is_view_locked("v1_1"));
+  assert!(!folder_test.is_view_locked("v2"));
+  assert!(!folder_test.is_view_locked("not_exist"));
+  assert_eq!(
+    drain_lock_events(&mut folder_test.view_rx),
+    vec![("v1".to_string(), true)]
+  );
+
+  folder_test.update_view("v1", |update| update.set_name("v1 name").done());
+  assert!(drain_lock_events(&mut folder_test.view_rx).is_empty());
+
+  folder_test.update_view("v1", |update| update.set_locked(false).done());
+  assert!(!folder_test.is_view_locked("v1_1"));
+  assert_eq!(
+    drain_lock_events(&mut folder_test.view_rx),
+    vec![("v1".to_string(), false)]
+  );
+}
+
+fn drain_lock_events(view_rx: &mut ViewChangeReceiver) -> Vec<(String, bool)> {
+  let mut lock_events = vec![];
+  while let Ok(change) = view_rx.try_recv() {
+    if let ViewChange::DidUpdateViewLock { view_id, is_locked } = change {
+      lock_events.push((view_id, is_locked));
+    }
+  }
+  lock_events
+}
+
+#[test]
+fn duplicate_view_recursively_test() {
+  let uid = UserId::from(1);
+  let folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut folder = folder_test.folder;
+  folder.insert_view(make_test_view("v1", "w1", vec![]), None);
+  folder.insert_view(make_test_view("v2", "w1", vec![]), None);
+  let mut view = make_test_view("v1_1", "v1", vec![]);
+  view.name = "grid".to_string();
+  view.layout = ViewLayout::Grid;
+  view.icon = Some(ViewIcon {
+    ty: IconType::Emoji,
+    value: "🎉".to_string(),
+  });
+  view.extra = Some(r#"{"cover":{"type":"0","value":"red"}}"#.to_string());
+  folder.insert_view(view, None);
+  folder.insert_view(make_test_view("v1_2", "v1", vec![]), None);
+  folder.insert_view(make_test_view("v1_1_1", "v1_1", vec![]), None);
+
+  let next_id = AtomicUsize::new(0);
+  let id_mapping = folder.duplicate_view_recursively("v1", || {
+    format!("copy_{}", next_id.fetch_add(1, Ordering::SeqCst))
+  });
+  let expected = [
+    ("v1", "copy_0"),
+    ("v1_1", "copy_1"),
+    ("v1_1_1", "copy_2"),
+    ("v1_2", "copy_3"),
+  ]
+  .iter()
+  .map(|(old_id, new_id)| (old_id.to_string(), new_id.to_string()))
+  .collect::<Vec<_>>();
+  assert_eq!(id_mapping, expected);
+
+  let child_ids = |parent_id: &str| {
+    folder
+      .get_views_belong_to(parent_id)
+      .iter()
+      .map(|view| view.id.clone())
+      .collect::<Vec<_>>()
+  };
+  assert_eq!(child_ids("w1"), vec!["v1", "copy_0", "v2"]);
+  assert_eq!(child_ids("copy_0"), vec!["copy_1", "copy_3"]);
+  assert_eq!(child_ids("copy_1"), vec!["copy_2"]);
+  // The original views are not changed
+  assert_eq!(child_ids("v1"), vec!["v1_1", "v1_2"]);
+  assert_eq!(child_ids("v1_1"), vec!["v1_1_1"]);
+
+  let original = folder.get_view("v1_1").unwrap();
+  let copy = folder.get_view("copy_1").unwrap();
+  assert_eq!(copy.parent_view_id, "copy_0");
+  assert_eq!(copy.name, original.name);
+  assert_eq!(copy.layout, original.layout);
+  assert_eq!(copy.icon, original.icon);
+  assert_eq!(copy.extra, original.extra);
+
+  assert!(folder
+    .duplicate_view_recursively("unknown", || "id".to_string())
+    .is_empty());
+}
@@ -1,6 +1,13 @@
+use std::time::Duration;
+
 use collab::core::origin::CollabOrigin;
-use collab::preclude::Collab;
-use collab_folder::{check_folder_is_valid, Folder, FolderData, UserId, Workspace};
+use collab::preclude::updates::decoder::Decode;
+use collab::preclude::{Collab, ReadTxn, Update};
+use collab_folder::{
+  check_folder_is_valid, Folder, FolderData, UserId, Workspace, WorkspaceChange,
+};
+
+use crate::util::{create_folder_with_workspace, make_test_view};
 
 #[test]
 fn test_workspace_is_ready() {
@@ -26,3 +33,89 @@ fn validate_folder_data() {
   let result = Folder::open(1, collab, None);
   assert!(result.is_err());
 }
+
+#[tokio::test]
+async fn rename_workspace_callback_test() {
+  let uid = UserId::from(1);
+  let mut folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut workspace_rx = folder_test.subscribe_workspace_change();
+
+  tokio::spawn(async move {
+    folder_test.update_view("w1", |update| update.set_name("My workspace").done());
+  });
+
+  let change = tokio::time::timeout(Duration::from_secs(2), workspace_rx.recv())
+    .await
+    .unwrap()
+    .unwrap();
+  match change {
+    WorkspaceChange::DidUpdateWorkspace { id, name } => {
+      assert_eq!(id, "w1");
+      assert_eq!(name, "My workspace");
+    },
+  }
+}
+
+#[tokio::test]
+async fn edit_workspace_without_metadata_change_test() {
+  let uid = UserId::from(1);
+  let mut folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  let mut workspace_rx = folder_test.subscribe_workspace_change();
+
+  // Only the last edited time and user of the workspace are changed
+  folder_test.update_view("w1", |update| update.done());
+  folder_test.update_view("w1", |update| update.set_last_edited_time(100).done());
+  assert!(workspace_rx.try_recv().is_err());
+
+  folder_test.update_view("w1", |update| update.set_name("My workspace").done());
+  match workspace_rx.try_recv().unwrap() {
+    WorkspaceChange::DidUpdateWorkspace { id, name } => {
+      assert_eq!(id, "w1");
+      assert_eq!(name, "My workspace");
+    },
+  }
+}
+
+#[tokio::test]
+async fn rename_workspace_from_remote_test() {
+  let uid = UserId::from(1);
+  let mut folder_test = create_folder_with_workspace(uid.clone(), "w1");
+  folder_test.insert_view(make_test_view("v1", "w1", vec![]), None);
+  let mut workspace_rx = folder_test.subscribe_workspace_change();
+
+  // Updating the other views doesn't change the workspace
+  folder_test.update_view("v1", |update| update.set_name("v1 name").done());
+  assert!(workspace_rx.try_recv().is_err());
+
+  let mut remote_folder = Folder::from_collab_doc_state(
+    uid.clone(),
+    CollabOrigin::Empty,
+    folder_test.encode_collab().unwrap().into(),
+    "w1",
+    vec![],
+  )
+  .unwrap();
+  remote_folder.update_view("w1", |update| update.set_name("Remote name").done());
+  let update = {
+    let state_vector = folder_test.collab.transact().state_vector();
+    remote_folder
+      .collab
+      .transact()
+      .encode_state_as_update_v1(&state_vector)
+  };
+  folder_test
+    .collab
+    .apply_update(Update::decode_v1(&update).unwrap())
+    .unwrap();
+
+  match workspace_rx.try_recv().unwrap() {
+    WorkspaceChange::DidUpdateWorkspace { id, name } => {
+      assert_eq!(id, "w1");
+      assert_eq!(name, "Remote name");
+    },
+  }
+  assert_eq!(
+    folder_test.get_workspace_info("w1").unwrap().name,
+    "Remote name"
+  );
+}
@@ -1,8 +1,9 @@
 use crate::error::ImporterError;
 use crate::imported_collab::{ImportType, ImportedCollab, ImportedCollabInfo};
 
-use collab_database::database::{get_row_document_id, Database};
+use collab_database::database::{get_row_document_id, Database, DatabaseContext};
 use collab_database::template::csv::{CSVResource, CSVTemplate};
+use collab_database::template::csv_stream::DEFAULT_CSV_CHUNK_SIZE;
 use collab_document::blocks::{mention_block_data, mention_block_delta, TextDelta};
 use collab_document::document::Document;
 use collab_document::importer::define::{BlockType, URL_FIELD};
@@ -14,8 +15,9 @@ use crate::notion::file::NotionFile;
 use crate::notion::walk_dir::{extract_delta_link, extract_external_links};
 use crate::notion::{CSVRelation, ImportedCollabInfoStream};
 use crate::util::{upload_file_url, FileId};
-use collab_database::rows::RowId;
+use collab_database::rows::{Row, RowCell, RowId};
 use collab_database::template::builder::FileUrlBuilder;
+use collab_database::workspace_database::InMemoryDatabaseCollabService;
 use collab_document::document_data::default_document_data;
 use percent_encoding::percent_decode_str;
 use serde::Serialize;
@@ -23,7 +25,9 @@ use serde_json::json;
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 use std::future::Future;
+use std::io::Cursor;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::fs;
 use tracing::error;
 
@@ -493,7 +497,6 @@ impl NotionPage {
         row_documents,
         ..
       } => {
-        let content = fs::read_to_string(file_path).await?;
         let files = self
           .notion_file
           .upload_files()
@@ -507,9 +510,12 @@ impl NotionPage {
           files,
         };
 
-        // create csv template, we need to set the view id as csv template view id
+        // create csv template, we need to set the view id as csv template view id. The rows
+        // are parsed from the content while they are inserted, so only one chunk of the rows
+        // is converted at a time.
+        let content = fs::read(file_path).await?;
         let mut csv_template =
-          CSVTemplate::try_from_reader(content.as_bytes(), true, Some(csv_resource))?;
+          CSVTemplate::from_reader(Cursor::new(content), true, Some(csv_resource))?;
         csv_template.reset_view_id(self.view_id.clone());
         let database_id = csv_template.database_id.clone();
 
@@ -518,30 +524,50 @@ impl NotionPage {
           workspace_id: self.workspace_id.clone(),
         };
 
-        let files = csv_template.resource.as_ref().unwrap().files.clone();
-        let database_template = csv_template
-          .try_into_database_template(Some(Box::new(file_url_builder)))
-          .await
-          .unwrap();
-        let mut database = Database::create_with_template(database_template).await?;
+        // The imported rows are kept encoded instead of being opened in the row cache
+        let context = DatabaseContext::new(Arc::new(InMemoryDatabaseCollabService::default()));
+        let (mut database, resource) = csv_template
+          .into_database_with_context(
+            Some(Box::new(file_url_builder)),
+            DEFAULT_CSV_CHUNK_SIZE,
+            context,
+          )
+          .await?;
+        let files = resource.map(|resource| resource.files).unwrap_or_default();
         let mut row_documents = row_documents.clone();
 
         if let Some(field) = database.get_primary_field() {
           let view_id = database.get_inline_view_id();
-          let row_cells = database.get_cells_for_field(&view_id, &field.id).await;
-          for row_cell in row_cells {
-            for row_document in row_documents.iter_mut() {
-              if let Some(text) = row_cell.text() {
-                if row_document.page.notion_name == text {
-                  row_document.set_row_document_id(&row_cell.row_id);
-                  database
-                    .update_row_meta(&row_cell.row_id, |meta| {
-                      meta.update_is_document_empty(false);
-                    })
-                    .await;
+          let row_orders = database.get_row_orders_for_view(&view_id);
+          // The rows are opened chunk by chunk and removed from the row cache afterwards
+          for row_orders in row_orders.chunks(DEFAULT_CSV_CHUNK_SIZE) {
+            let rows: Vec<Row> = database
+              .get_rows_from_row_orders(row_orders, DEFAULT_CSV_CHUNK_SIZE, None)
+              .await
+              .filter_map(|result| async move { result.ok() })
+              .collect()
+              .await;
+            for row in rows {
+              let row_cell = RowCell::new(row.id, row.cells.get(&field.id).cloned());
+              for row_document in row_documents.iter_mut() {
+                if let Some(text) = row_cell.text() {
+                  if row_document.page.notion_name == text {
+                    row_document.set_row_document_id(&row_cell.row_id);
+                    database
+                      .update_row_meta(&row_cell.row_id, |meta| {
+                        meta.update_is_document_empty(false);
+                      })
+                      .await;
+                  }
                 }
               }
             }
+
+            let row_ids = row_orders
+              .iter()
+              .map(|row_order| row_order.id.clone())
+              .collect::<Vec<_>>();
+            database.evict_rows(&row_ids).await?;
           }
         }
 
@@ -185,6 +185,18 @@ impl CollabContext {
     Ok(())
   }
 
+  /// Applies the update within a transaction of the given origin. Applying the updates of the
+  /// remote peers with their own origin keeps them out of the local [UndoManager].
+  pub fn apply_update_with_origin(
+    &mut self,
+    update: Update,
+    origin: CollabOrigin,
+  ) -> Result<(), CollabError> {
+    let mut txn = self.doc().transact_mut_with(origin);
+    txn.apply_update(update)?;
+    Ok(())
+  }
+
   pub fn clean_awareness_state(&mut self) {
     self.awareness.clean_local_state();
   }
@@ -480,12 +492,20 @@ impl Collab {
     // a frequent case includes establishing a new transaction for every user key stroke. Meanwhile
     // we may decide to use different granularity of undo/redo actions. These are grouped together
     // on time-based ranges (configurable in undo::Options, which is 500ms by default).
-    let mut undo_manager = UndoManager::with_scope_and_options(
-      self.context.doc(),
-      &self.data,
-      yrs::undo::Options::default(),
-    );
-    undo_manager.include_origin(self.origin().clone());
+    self.enable_undo_redo_with_options(self.origin().clone(), yrs::undo::Options::default());
+  }
+
+  /// Replaces the [UndoManager] with a new one that only tracks the transactions of the given
+  /// `origin`. The changes made within `options.capture_timeout_millis` are grouped into a
+  /// single undo/redo action.
+  pub fn enable_undo_redo_with_options(
+    &mut self,
+    origin: CollabOrigin,
+    options: yrs::undo::Options,
+  ) {
+    let mut undo_manager =
+      UndoManager::with_scope_and_options(self.context.doc(), &self.data, options);
+    undo_manager.include_origin(origin);
     self.context.undo_manager = Some(undo_manager);
   }
 